@@ -5,10 +5,35 @@ use std::fmt;
 #[derive(Debug)]
 pub enum BlobResolverError {
     Http(Box<reqwest::Error>),
+    /// Looking up the DID's PDS failed (e.g. the PLC directory was
+    /// unreachable or had no entry).
     DidResolution(String),
-    /// A `com.atproto.repo.getRecord` request failed or returned an
-    /// unexpected body.
+    /// The DID can never resolve: an unparseable DID or unsupported method.
+    InvalidDid(String),
+    /// A `com.atproto.repo.getRecord` request couldn't be made or returned
+    /// an unexpected body.
     RecordFetch(String),
+    /// The PDS answered `com.atproto.repo.getRecord` with this non-success
+    /// status.
+    RecordStatus(u16),
+    /// A blob source (PDS or CDN) answered with this non-success status.
+    BlobFetch(u16),
+}
+
+impl BlobResolverError {
+    /// Whether another attempt, or another source, might succeed: network
+    /// errors, 5xx, 429, and DID resolution (the PLC directory may be the
+    /// thing that's down). A 4xx means the blob really isn't there, and an
+    /// invalid DID won't resolve however often it's retried.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            BlobResolverError::Http(_) | BlobResolverError::DidResolution(_) => true,
+            BlobResolverError::BlobFetch(status) | BlobResolverError::RecordStatus(status) => {
+                *status >= 500 || *status == 429
+            }
+            BlobResolverError::RecordFetch(_) | BlobResolverError::InvalidDid(_) => false,
+        }
+    }
 }
 
 impl fmt::Display for BlobResolverError {
@@ -16,7 +41,14 @@ impl fmt::Display for BlobResolverError {
         match self {
             BlobResolverError::Http(err) => write!(f, "HTTP error: {}", err),
             BlobResolverError::DidResolution(msg) => write!(f, "DID resolution error: {}", msg),
+            BlobResolverError::InvalidDid(msg) => write!(f, "invalid DID: {}", msg),
             BlobResolverError::RecordFetch(msg) => write!(f, "record fetch error: {}", msg),
+            BlobResolverError::RecordStatus(status) => {
+                write!(f, "record fetch error: PDS returned status {}", status)
+            }
+            BlobResolverError::BlobFetch(status) => {
+                write!(f, "blob fetch error: source returned status {}", status)
+            }
         }
     }
 }
@@ -48,6 +80,17 @@ mod tests {
         assert_eq!(format!("{err}"), "DID resolution error: invalid DID format");
     }
 
+    #[test]
+    fn test_only_server_side_blob_failures_are_transient() {
+        assert!(BlobResolverError::BlobFetch(503).is_transient());
+        assert!(BlobResolverError::BlobFetch(429).is_transient());
+        assert!(!BlobResolverError::BlobFetch(404).is_transient());
+        assert!(!BlobResolverError::RecordFetch("gone".into()).is_transient());
+        assert!(!BlobResolverError::InvalidDid("did:key:z6Mk".into()).is_transient());
+        assert!(BlobResolverError::RecordStatus(502).is_transient());
+        assert!(!BlobResolverError::RecordStatus(400).is_transient());
+    }
+
     #[test]
     fn test_error_is_debug() {
         let err = BlobResolverError::DidResolution("test".to_string());
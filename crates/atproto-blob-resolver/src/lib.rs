@@ -8,4 +8,4 @@ pub mod resolver;
 
 pub use atproto_identity::Did;
 pub use error::{BlobResolverError, Result};
-pub use resolver::BlobResolver;
+pub use resolver::{BlobResolver, FetchedBlob};
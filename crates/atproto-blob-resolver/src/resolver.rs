@@ -5,44 +5,99 @@ use atproto_identity::{Did, DidExt, DidMethod};
 use jacquard_common::types::string::AtUri;
 use reqwest::Client;
 use std::str::FromStr;
+use std::time::Duration;
 use tracing::{debug, warn};
 
+/// Retries after a transient blob fetch failure, by default.
+const DEFAULT_FETCH_RETRIES: u32 = 2;
+/// Wait before the first retry; doubles for each one after.
+const DEFAULT_RETRY_BACKOFF: Duration = Duration::from_millis(250);
+
+/// A blob from [`BlobResolver::fetch_blob_for_did`].
+#[derive(Debug)]
+pub struct FetchedBlob {
+    pub data: Vec<u8>,
+    pub content_type: String,
+    /// Served by the CDN fallback rather than the author's PDS: a
+    /// re-encoded copy that may lag a deletion, so callers shouldn't keep
+    /// it as the blob.
+    pub from_cdn: bool,
+}
+
 /// Resolves AT Protocol DIDs to PDS endpoints and fetches blobs
 pub struct BlobResolver {
     client: Client,
+    plc_directory: String,
+    fetch_retries: u32,
+    retry_backoff: Duration,
+    /// Image CDN base (e.g. `https://cdn.bsky.app`) tried when the PDS
+    /// can't serve a blob. `None` disables the fallback.
+    cdn_fallback: Option<String>,
 }
 
 impl BlobResolver {
     /// Create a new blob resolver
     pub fn new() -> Self {
-        Self::with_client(Client::new())
+        Self::with_client(http_client::client())
     }
 
     /// Create a resolver using a caller-provided HTTP client — e.g. one
-    /// configured with a request timeout. [`BlobResolver::new`] uses a default
-    /// client with no timeout.
+    /// configured with a request timeout. [`BlobResolver::new`] uses the shared
+    /// default client, which bounds connects and reads but not whole requests.
     pub fn with_client(client: Client) -> Self {
-        Self { client }
+        Self {
+            client,
+            plc_directory: atproto_identity::plc_directory_url(),
+            fetch_retries: DEFAULT_FETCH_RETRIES,
+            retry_backoff: DEFAULT_RETRY_BACKOFF,
+            cdn_fallback: None,
+        }
+    }
+
+    /// Resolve `did:plc` documents against `url` instead of
+    /// [`atproto_identity::plc_directory_url`] — e.g. a mock directory in
+    /// tests.
+    pub fn with_plc_directory(mut self, url: &str) -> Self {
+        self.plc_directory = url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Retry transient blob fetch failures `retries` times, waiting
+    /// `backoff` before the first retry and doubling it after each.
+    pub fn with_retries(mut self, retries: u32, backoff: Duration) -> Self {
+        self.fetch_retries = retries;
+        self.retry_backoff = backoff;
+        self
+    }
+
+    /// Fall back to the image CDN at `url` (e.g. `https://cdn.bsky.app`)
+    /// when [`fetch_blob_for_did`](Self::fetch_blob_for_did) can't get a
+    /// blob from its PDS. The CDN only serves images, re-encoded as JPEG.
+    pub fn with_cdn_fallback(mut self, url: &str) -> Self {
+        self.cdn_fallback = Some(url.trim_end_matches('/').to_string());
+        self
     }
 
     /// Resolve a DID to its PDS URL.
     ///
     /// `did:plc` resolution (plc.directory lookup + `#atproto_pds` extraction) is
-    /// delegated to [`atproto_identity::resolve_pds_endpoint`] — the same
+    /// delegated to [`atproto_identity::resolve_pds_endpoint_via`] — the same
     /// implementation `IdentityResolver` uses — so the logic lives in one place.
     /// `did:web` keeps the host-derived shortcut below.
     pub async fn resolve_pds_url(&self, did: &Did) -> Result<String> {
         match did.method() {
-            Some(DidMethod::Plc(_)) => atproto_identity::resolve_pds_endpoint(&self.client, did)
-                .await
-                .ok_or_else(|| {
-                    BlobResolverError::DidResolution(format!(
-                        "could not resolve PDS endpoint for {}",
-                        did.as_str()
-                    ))
-                }),
+            Some(DidMethod::Plc(_)) => {
+                atproto_identity::resolve_pds_endpoint_via(&self.client, &self.plc_directory, did)
+                    .await
+                    .ok_or_else(|| {
+                        BlobResolverError::DidResolution(format!(
+                            "could not resolve PDS endpoint for {}",
+                            did.as_str()
+                        ))
+                    })
+            }
             Some(DidMethod::Web(host)) => self.resolve_web_did(did, host),
-            None => Err(BlobResolverError::DidResolution(format!(
+            None => Err(BlobResolverError::InvalidDid(format!(
                 "unsupported DID method: {did}"
             ))),
         }
@@ -57,7 +112,39 @@ impl BlobResolver {
         Ok(url)
     }
 
-    /// Fetch a blob from a PDS server
+    /// Resolve `did`'s PDS and fetch a blob from it, falling back to the
+    /// configured image CDN when the PDS (or its DID document) stays
+    /// unreachable. A definite miss such as a 404, or a DID that can't
+    /// resolve, is returned as-is: the CDN would only be serving a copy the
+    /// author has since removed.
+    pub async fn fetch_blob_for_did(&self, did: &Did, cid: &str) -> Result<FetchedBlob> {
+        let fetched = match self.resolve_pds_url(did).await {
+            Ok(pds_url) => self.fetch_blob(&pds_url, did.as_str(), cid).await,
+            Err(e) => Err(e),
+        };
+        let (data, content_type, from_cdn) = match (fetched, &self.cdn_fallback) {
+            (Ok((data, content_type)), _) => (data, content_type, false),
+            (Err(e), Some(cdn)) if e.is_transient() => {
+                warn!(did = %did, cid, error = %e, "PDS unavailable, fetching blob from CDN");
+                let url = format!(
+                    "{cdn}/img/feed_fullsize/plain/{}/{}@jpeg",
+                    did.as_str(),
+                    urlencoding::encode(cid)
+                );
+                let (data, content_type) = self.get_blob(&url).await?;
+                (data, content_type, true)
+            }
+            (Err(e), _) => return Err(e),
+        };
+        Ok(FetchedBlob {
+            data,
+            content_type,
+            from_cdn,
+        })
+    }
+
+    /// Fetch a blob from a PDS server, retrying transient failures with
+    /// exponential backoff.
     pub async fn fetch_blob(
         &self,
         pds_url: &str,
@@ -66,21 +153,35 @@ impl BlobResolver {
     ) -> Result<(Vec<u8>, String)> {
         let url = format!(
             "{}/xrpc/com.atproto.sync.getBlob?did={}&cid={}",
-            pds_url,
+            pds_url.trim_end_matches('/'),
             urlencoding::encode(did),
             urlencoding::encode(cid)
         );
 
         debug!(url = %url, "Fetching blob from PDS");
 
-        let response = self.client.get(&url).send().await?;
+        let mut backoff = self.retry_backoff;
+        let mut attempt = 0;
+        loop {
+            match self.get_blob(&url).await {
+                Err(e) if e.is_transient() && attempt < self.fetch_retries => {
+                    attempt += 1;
+                    debug!(url = %url, attempt, error = %e, "Retrying blob fetch");
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// One GET of a blob URL: the body and its content type.
+    async fn get_blob(&self, url: &str) -> Result<(Vec<u8>, String)> {
+        let response = self.client.get(url).send().await?;
 
         if !response.status().is_success() {
             warn!(status = %response.status(), url = %url, "Failed to fetch blob");
-            return Err(BlobResolverError::DidResolution(format!(
-                "PDS returned status {}",
-                response.status()
-            )));
+            return Err(BlobResolverError::BlobFetch(response.status().as_u16()));
         }
 
         let content_type = response
@@ -95,7 +196,7 @@ impl BlobResolver {
         debug!(
             size = data.len(),
             content_type = %content_type,
-            "Fetched blob from PDS"
+            "Fetched blob"
         );
 
         Ok((data, content_type))
@@ -128,10 +229,7 @@ impl BlobResolver {
 
         if !response.status().is_success() {
             warn!(status = %response.status(), url = %url, "Failed to fetch record");
-            return Err(BlobResolverError::RecordFetch(format!(
-                "PDS returned status {}",
-                response.status()
-            )));
+            return Err(BlobResolverError::RecordStatus(response.status().as_u16()));
         }
 
         let body: serde_json::Value = response.json().await?;
@@ -147,7 +245,7 @@ impl BlobResolver {
     /// Consolidates the parse-URI → resolve-DID → `getRecord` dance that every
     /// caller (the ingester's media resolver, backfill jobs) would otherwise
     /// repeat. URI/DID parse failures surface as
-    /// [`BlobResolverError::RecordFetch`] / [`BlobResolverError::DidResolution`].
+    /// [`BlobResolverError::RecordFetch`] / [`BlobResolverError::InvalidDid`].
     pub async fn fetch_record_by_aturi(&self, at_uri: &str) -> Result<serde_json::Value> {
         let parsed = AtUri::from_str(at_uri)
             .map_err(|_| BlobResolverError::RecordFetch(format!("unparseable AT-URI: {at_uri}")))?;
@@ -158,7 +256,7 @@ impl BlobResolver {
         };
         let authority = parsed.authority();
         let did = Did::new_owned(authority.as_str()).map_err(|e| {
-            BlobResolverError::DidResolution(format!("unparseable DID in {at_uri}: {e}"))
+            BlobResolverError::InvalidDid(format!("unparseable DID in {at_uri}: {e}"))
         })?;
         let pds_url = self.resolve_pds_url(&did).await?;
         self.fetch_record(
@@ -216,6 +314,183 @@ mod tests {
         let resolver = BlobResolver::new();
         let did = Did::new_owned("did:key:z6MkExample").expect("valid did syntax");
         let err = resolver.resolve_pds_url(&did).await.unwrap_err();
-        assert!(matches!(err, BlobResolverError::DidResolution(_)));
+        assert!(matches!(err, BlobResolverError::InvalidDid(_)));
+    }
+
+    #[tokio::test]
+    async fn fetch_record_by_aturi_resolves_the_pds_from_the_plc_directory() {
+        use wiremock::matchers::{method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        // One server plays both the PLC directory and the PDS.
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/did:plc:abc123"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "did:plc:abc123",
+                "service": [{
+                    "id": "#atproto_pds",
+                    "type": "AtprotoPersonalDataServer",
+                    "serviceEndpoint": server.uri(),
+                }],
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/xrpc/com.atproto.repo.getRecord"))
+            .and(query_param("repo", "did:plc:abc123"))
+            .and(query_param("collection", "bio.lexicons.temp.v0-1.occurrence"))
+            .and(query_param("rkey", "3k2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "uri": "at://did:plc:abc123/bio.lexicons.temp.v0-1.occurrence/3k2",
+                "cid": "bafyrecord",
+                "value": { "$type": "bio.lexicons.temp.v0-1.occurrence", "scientificName": "Quercus alba" },
+            })))
+            .mount(&server)
+            .await;
+
+        let resolver = BlobResolver::new().with_plc_directory(&server.uri());
+        let value = resolver
+            .fetch_record_by_aturi("at://did:plc:abc123/bio.lexicons.temp.v0-1.occurrence/3k2")
+            .await
+            .unwrap();
+        assert_eq!(value["scientificName"], "Quercus alba");
+    }
+
+    /// A server playing the PLC directory for `did:plc:abc123`, pointing its
+    /// PDS back at itself.
+    async fn plc_and_pds() -> wiremock::MockServer {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/did:plc:abc123"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "did:plc:abc123",
+                "service": [{
+                    "id": "#atproto_pds",
+                    "type": "AtprotoPersonalDataServer",
+                    "serviceEndpoint": server.uri(),
+                }],
+            })))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    fn test_resolver(server: &wiremock::MockServer) -> BlobResolver {
+        BlobResolver::new()
+            .with_plc_directory(&server.uri())
+            .with_retries(2, Duration::ZERO)
+            .with_cdn_fallback(&format!("{}/cdn", server.uri()))
+    }
+
+    #[tokio::test]
+    async fn failing_pds_is_retried_then_served_from_the_cdn() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, ResponseTemplate};
+
+        let server = plc_and_pds().await;
+        Mock::given(method("GET"))
+            .and(path("/xrpc/com.atproto.sync.getBlob"))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(3)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(
+                "/cdn/img/feed_fullsize/plain/did:plc:abc123/bafkreiblob@jpeg",
+            ))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "image/jpeg")
+                    .set_body_bytes(b"cdn-bytes".to_vec()),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let did = Did::new_owned("did:plc:abc123").unwrap();
+        let blob = test_resolver(&server)
+            .fetch_blob_for_did(&did, "bafkreiblob")
+            .await
+            .unwrap();
+        assert_eq!(blob.data, b"cdn-bytes");
+        assert_eq!(blob.content_type, "image/jpeg");
+        assert!(blob.from_cdn);
+    }
+
+    #[tokio::test]
+    async fn transient_pds_failure_recovers_on_retry() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, ResponseTemplate};
+
+        let server = plc_and_pds().await;
+        Mock::given(method("GET"))
+            .and(path("/xrpc/com.atproto.sync.getBlob"))
+            .respond_with(ResponseTemplate::new(502))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/xrpc/com.atproto.sync.getBlob"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"pds-bytes".to_vec()))
+            .mount(&server)
+            .await;
+
+        let did = Did::new_owned("did:plc:abc123").unwrap();
+        let blob = test_resolver(&server)
+            .fetch_blob_for_did(&did, "bafkreiblob")
+            .await
+            .unwrap();
+        assert_eq!(blob.data, b"pds-bytes");
+        assert!(!blob.from_cdn);
+    }
+
+    #[tokio::test]
+    async fn missing_blob_is_not_retried_or_fetched_from_the_cdn() {
+        use wiremock::matchers::{method, path, path_regex};
+        use wiremock::{Mock, ResponseTemplate};
+
+        let server = plc_and_pds().await;
+        Mock::given(method("GET"))
+            .and(path("/xrpc/com.atproto.sync.getBlob"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(path_regex("^/cdn/"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let did = Did::new_owned("did:plc:abc123").unwrap();
+        let err = test_resolver(&server)
+            .fetch_blob_for_did(&did, "bafkreiblob")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, BlobResolverError::BlobFetch(404)));
+    }
+
+    #[tokio::test]
+    async fn invalid_did_is_not_fetched_from_the_cdn() {
+        use wiremock::matchers::path_regex;
+        use wiremock::{Mock, ResponseTemplate};
+
+        let server = plc_and_pds().await;
+        Mock::given(path_regex("^/cdn/"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let did = Did::new_owned("did:key:z6MkExample").unwrap();
+        let err = test_resolver(&server)
+            .fetch_blob_for_did(&did, "bafkreiblob")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, BlobResolverError::InvalidDid(_)));
     }
 }
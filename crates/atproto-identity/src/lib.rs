@@ -2,14 +2,18 @@
 //!
 //! Resolves AT Protocol identities (handles to DIDs and vice versa)
 //! and fetches Bluesky profiles.
-//! All lookups are cached using moka async caches.
+//! All lookups are cached using moka async caches; stale profiles are
+//! served while a background refresh fetches them again.
 
 mod did;
 mod resolver;
 mod types;
 
 pub use did::{DidExt, DidMethod};
-pub use resolver::{resolve_pds_endpoint, IdentityResolver};
+pub use resolver::{
+    resolve_pds_endpoint, resolve_pds_endpoint_via, IdentityResolver, DEFAULT_PROFILE_HARD_TTL,
+    DEFAULT_PROFILE_SOFT_TTL,
+};
 pub use types::{Profile, ResolveResult};
 
 /// Validated AT Protocol DID, backed by jacquard's `Did` (default `SmolStr`
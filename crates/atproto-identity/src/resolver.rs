@@ -1,6 +1,6 @@
-use std::collections::HashMap;
-use std::sync::Arc;
-use std::time::Duration;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use moka::future::Cache;
 use reqwest::Client;
@@ -15,14 +15,43 @@ use crate::types::{
 
 const DEFAULT_SERVICE_URL: &str = "https://public.api.bsky.app";
 const CACHE_TTL_SECS: u64 = 300; // 5 minutes
+const CACHE_CAPACITY: u64 = 10_000;
 const BATCH_SIZE: usize = 25;
 
+/// Age past which a cached profile is still served but refetched in the
+/// background.
+pub const DEFAULT_PROFILE_SOFT_TTL: Duration = Duration::from_secs(60);
+
+/// Age past which a cached profile is dropped and must be refetched before
+/// it can be served.
+pub const DEFAULT_PROFILE_HARD_TTL: Duration = Duration::from_secs(CACHE_TTL_SECS);
+
 /// Resolves AT Protocol identities (handles ↔ DIDs) and fetches profiles
 pub struct IdentityResolver {
     client: Client,
     service_url: String,
     identity_cache: Cache<String, ResolveResult>,
-    profile_cache: Cache<String, Arc<Profile>>,
+    profiles: ProfileFetcher,
+    profile_soft_ttl: Duration,
+    /// Actors with a background refresh in flight, so repeated stale hits
+    /// don't pile up duplicate fetches.
+    refreshing: Arc<Mutex<HashSet<String>>>,
+}
+
+/// A cached profile and when it was fetched.
+#[derive(Clone)]
+struct CachedProfile {
+    profile: Arc<Profile>,
+    fetched_at: Instant,
+}
+
+/// Fetches profiles from the Bluesky API into the profile cache. Cheap to
+/// clone, so background refreshes can own one.
+#[derive(Clone)]
+struct ProfileFetcher {
+    client: Client,
+    service_url: String,
+    cache: Cache<String, CachedProfile>,
 }
 
 impl IdentityResolver {
@@ -49,29 +78,40 @@ impl IdentityResolver {
 
     /// Create a new resolver with a custom Bluesky API URL
     pub fn with_service_url(service_url: &str) -> Self {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(30))
-            .build()
-            .expect("Failed to create HTTP client");
+        let client = http_client::HttpClientConfig::default()
+            .with_timeout(Duration::from_secs(30))
+            .build();
 
         let identity_cache = Cache::builder()
-            .max_capacity(10_000)
+            .max_capacity(CACHE_CAPACITY)
             .time_to_live(Duration::from_secs(CACHE_TTL_SECS))
             .build();
 
-        let profile_cache = Cache::builder()
-            .max_capacity(10_000)
-            .time_to_live(Duration::from_secs(CACHE_TTL_SECS))
-            .build();
+        let profiles = ProfileFetcher {
+            client: client.clone(),
+            service_url: service_url.to_string(),
+            cache: profile_cache(DEFAULT_PROFILE_HARD_TTL),
+        };
 
         Self {
             client,
             service_url: service_url.to_string(),
             identity_cache,
-            profile_cache,
+            profiles,
+            profile_soft_ttl: DEFAULT_PROFILE_SOFT_TTL,
+            refreshing: Arc::default(),
         }
     }
 
+    /// Serve cached profiles younger than `soft` as-is; serve older ones
+    /// immediately but refetch them in the background; drop them entirely
+    /// once older than `hard`. Clears the profile cache.
+    pub fn with_profile_ttls(mut self, soft: Duration, hard: Duration) -> Self {
+        self.profile_soft_ttl = soft;
+        self.profiles.cache = profile_cache(hard);
+        self
+    }
+
     /// Resolve a handle to a DID
     pub async fn resolve_handle(&self, handle: &str) -> Option<ResolveResult> {
         // Check cache
@@ -185,7 +225,7 @@ impl IdentityResolver {
 
     /// Get the DID document for a DID
     async fn get_did_document(&self, did: &Did) -> Option<DidDocument> {
-        fetch_did_document(&self.client, did).await
+        fetch_did_document(&self.client, &crate::plc_directory_url(), did).await
     }
 
     /// Get the PDS endpoint for a DID
@@ -196,10 +236,128 @@ impl IdentityResolver {
     /// Get a user's profile
     pub async fn get_profile(&self, actor: &str) -> Option<Arc<Profile>> {
         // Check cache
-        if let Some(cached) = self.profile_cache.get(actor).await {
-            return Some(cached);
+        if let Some(cached) = self.profiles.cache.get(actor).await {
+            if cached.fetched_at.elapsed() >= self.profile_soft_ttl {
+                self.refresh_in_background(vec![actor.to_string()]);
+            }
+            return Some(cached.profile);
         }
 
+        self.profiles.fetch_one(actor).await
+    }
+
+    /// Batch resolve multiple DIDs/handles to profiles
+    pub async fn get_profiles(&self, actors: &[String]) -> HashMap<String, Arc<Profile>> {
+        let mut results = HashMap::new();
+        let mut to_fetch = Vec::new();
+        let mut stale = Vec::new();
+
+        // Check cache first
+        for actor in actors {
+            if let Some(cached) = self.profiles.cache.get(actor).await {
+                if cached.fetched_at.elapsed() >= self.profile_soft_ttl {
+                    stale.push(actor.clone());
+                }
+                results.insert(actor.clone(), cached.profile);
+            } else {
+                to_fetch.push(actor.clone());
+            }
+        }
+
+        if !stale.is_empty() {
+            self.refresh_in_background(stale);
+        }
+
+        results.extend(self.profiles.fetch_batch(&to_fetch).await);
+        results
+    }
+
+    /// Refetch `actors` on a spawned task, skipping any already being
+    /// refreshed. Callers keep serving the cached profiles meanwhile; a
+    /// failed refresh leaves them cached until the hard TTL.
+    fn refresh_in_background(&self, actors: Vec<String>) {
+        let Some(claim) = RefreshClaim::take(&self.refreshing, actors) else {
+            return;
+        };
+
+        let profiles = self.profiles.clone();
+        tokio::spawn(async move {
+            let fetched = profiles.fetch_batch(&claim.actors).await;
+            debug!(
+                requested = claim.actors.len(),
+                fetched = fetched.len(),
+                "refreshed stale profiles"
+            );
+        });
+    }
+}
+
+/// Actors one background refresh has marked in flight. Dropping the claim
+/// clears them, so a refresh that panics or is cancelled doesn't block
+/// later ones.
+struct RefreshClaim {
+    refreshing: Arc<Mutex<HashSet<String>>>,
+    actors: Vec<String>,
+}
+
+impl RefreshClaim {
+    /// Claim those of `actors` not already being refreshed, or `None` if
+    /// that leaves nothing to do.
+    fn take(refreshing: &Arc<Mutex<HashSet<String>>>, actors: Vec<String>) -> Option<Self> {
+        let actors: Vec<String> = {
+            let mut in_flight = refreshing.lock().unwrap();
+            actors
+                .into_iter()
+                .filter(|actor| in_flight.insert(actor.clone()))
+                .collect()
+        };
+        (!actors.is_empty()).then(|| Self {
+            refreshing: refreshing.clone(),
+            actors,
+        })
+    }
+}
+
+impl Drop for RefreshClaim {
+    fn drop(&mut self) {
+        // Runs during unwinding too, so a poisoned lock is still cleaned up
+        // rather than panicking again.
+        let mut in_flight = self
+            .refreshing
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        for actor in &self.actors {
+            in_flight.remove(actor);
+        }
+    }
+}
+
+impl Default for IdentityResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn profile_cache(ttl: Duration) -> Cache<String, CachedProfile> {
+    Cache::builder()
+        .max_capacity(CACHE_CAPACITY)
+        .time_to_live(ttl)
+        .build()
+}
+
+impl ProfileFetcher {
+    /// Cache `profile` under both its DID and handle.
+    async fn store(&self, profile: Arc<Profile>) {
+        let cached = CachedProfile {
+            profile: profile.clone(),
+            fetched_at: Instant::now(),
+        };
+        self.cache.insert(profile.did.clone(), cached.clone()).await;
+        self.cache.insert(profile.handle.clone(), cached).await;
+    }
+
+    /// Fetch one profile, bypassing the cache.
+    async fn fetch_one(&self, actor: &str) -> Option<Arc<Profile>> {
         let url = format!(
             "{}/xrpc/app.bsky.actor.getProfile?actor={}",
             self.service_url, actor
@@ -209,14 +367,8 @@ impl IdentityResolver {
             Ok(response) if response.status().is_success() => {
                 match response.json::<ProfileResponse>().await {
                     Ok(data) => {
-                        let did = data.did.clone();
-                        let handle = data.handle.clone();
                         let profile = Arc::new(Profile::from(data));
-
-                        // Cache by both DID and handle
-                        self.profile_cache.insert(did, profile.clone()).await;
-                        self.profile_cache.insert(handle, profile.clone()).await;
-
+                        self.store(profile.clone()).await;
                         Some(profile)
                     }
                     Err(e) => {
@@ -229,22 +381,12 @@ impl IdentityResolver {
         }
     }
 
-    /// Batch resolve multiple DIDs/handles to profiles
-    pub async fn get_profiles(&self, actors: &[String]) -> HashMap<String, Arc<Profile>> {
+    /// Fetch profiles for `actors` in batches, bypassing the cache. Keyed by
+    /// both DID and handle.
+    async fn fetch_batch(&self, actors: &[String]) -> HashMap<String, Arc<Profile>> {
         let mut results = HashMap::new();
-        let mut to_fetch = Vec::new();
 
-        // Check cache first
-        for actor in actors {
-            if let Some(cached) = self.profile_cache.get(actor).await {
-                results.insert(actor.clone(), cached);
-            } else {
-                to_fetch.push(actor.clone());
-            }
-        }
-
-        // Fetch uncached profiles in batches
-        for batch in to_fetch.chunks(BATCH_SIZE) {
+        for batch in actors.chunks(BATCH_SIZE) {
             let mut url = reqwest::Url::parse(&format!(
                 "{}/xrpc/app.bsky.actor.getProfiles",
                 self.service_url
@@ -261,15 +403,12 @@ impl IdentityResolver {
                 Ok(response) if response.status().is_success() => {
                     if let Ok(data) = response.json::<ProfilesResponse>().await {
                         for p in data.profiles {
-                            let did = p.did.clone();
-                            let handle = p.handle.clone();
                             let profile = Arc::new(Profile::from(p));
 
-                            results.insert(did.clone(), profile.clone());
-                            results.insert(handle.clone(), profile.clone());
+                            results.insert(profile.did.clone(), profile.clone());
+                            results.insert(profile.handle.clone(), profile.clone());
 
-                            self.profile_cache.insert(did, profile.clone()).await;
-                            self.profile_cache.insert(handle, profile).await;
+                            self.store(profile).await;
                         }
                     }
                 }
@@ -283,17 +422,16 @@ impl IdentityResolver {
     }
 }
 
-impl Default for IdentityResolver {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
-/// Fetch and deserialize a DID document. `did:plc` is resolved via
-/// plc.directory; `did:web` via the host's `/.well-known/did.json`.
-async fn fetch_did_document(client: &Client, did: &Did) -> Option<DidDocument> {
+/// Fetch and deserialize a DID document. `did:plc` is resolved via the PLC
+/// directory at `plc_directory`; `did:web` via the host's
+/// `/.well-known/did.json`.
+async fn fetch_did_document(
+    client: &Client,
+    plc_directory: &str,
+    did: &Did,
+) -> Option<DidDocument> {
     let url = match did.method() {
-        Some(DidMethod::Plc(_)) => format!("{}/{}", crate::plc_directory_url(), did.as_str()),
+        Some(DidMethod::Plc(_)) => format!("{plc_directory}/{}", did.as_str()),
         Some(DidMethod::Web(host)) => {
             let domain = host.replace("%3A", ":");
             format!("https://{domain}/.well-known/did.json")
@@ -318,9 +456,112 @@ async fn fetch_did_document(client: &Client, did: &Did) -> Option<DidDocument> {
 /// need PDS resolution (e.g. blob/record fetching) can reuse this logic without
 /// constructing the handle/profile caches.
 pub async fn resolve_pds_endpoint(client: &Client, did: &Did) -> Option<String> {
-    let doc = fetch_did_document(client, did).await?;
+    resolve_pds_endpoint_via(client, &crate::plc_directory_url(), did).await
+}
+
+/// [`resolve_pds_endpoint`] against an explicit PLC directory base URL (no
+/// trailing slash) instead of [`crate::plc_directory_url`].
+pub async fn resolve_pds_endpoint_via(
+    client: &Client,
+    plc_directory: &str,
+    did: &Did,
+) -> Option<String> {
+    let doc = fetch_did_document(client, plc_directory, did).await?;
     doc.service?
         .iter()
         .find(|s| s.id == "#atproto_pds")
         .map(|s| s.service_endpoint.clone())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const PROFILES_PATH: &str = "/xrpc/app.bsky.actor.getProfiles";
+
+    fn profiles_body(handle: &str) -> serde_json::Value {
+        json!({ "profiles": [{ "did": "did:plc:alice", "handle": handle }] })
+    }
+
+    fn alice() -> Vec<String> {
+        vec!["did:plc:alice".to_string()]
+    }
+
+    #[tokio::test]
+    async fn stale_hit_is_served_while_a_refresh_runs() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(PROFILES_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_json(profiles_body("old.test")))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        // The refresh sees the new handle, slowly: waiting on it would hold
+        // the stale hit for the whole delay.
+        Mock::given(method("GET"))
+            .and(path(PROFILES_PATH))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(profiles_body("new.test"))
+                    .set_delay(Duration::from_millis(500)),
+            )
+            .mount(&server)
+            .await;
+
+        let resolver = IdentityResolver::with_service_url(&server.uri())
+            .with_profile_ttls(Duration::ZERO, Duration::from_secs(60));
+        let first = resolver.get_profiles(&alice()).await;
+        assert_eq!(first["did:plc:alice"].handle, "old.test");
+
+        let started = Instant::now();
+        let stale = resolver.get_profiles(&alice()).await;
+        assert_eq!(stale["did:plc:alice"].handle, "old.test");
+        assert!(started.elapsed() < Duration::from_millis(500));
+
+        let refreshed = async {
+            while resolver.get_profiles(&alice()).await["did:plc:alice"].handle != "new.test" {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(5), refreshed)
+            .await
+            .expect("stale profile was never refreshed");
+    }
+
+    #[tokio::test]
+    async fn refresh_claim_is_released_when_the_refresh_panics() {
+        let refreshing = Arc::<Mutex<HashSet<String>>>::default();
+        let claim = RefreshClaim::take(&refreshing, alice()).unwrap();
+        // Already in flight, so a second stale hit doesn't claim it again.
+        assert!(RefreshClaim::take(&refreshing, alice()).is_none());
+
+        let refresh = tokio::spawn(async move {
+            let _claim = claim;
+            panic!("refresh failed");
+        });
+        assert!(refresh.await.unwrap_err().is_panic());
+        assert!(refreshing.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn fresh_hit_does_not_refetch() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(PROFILES_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_json(profiles_body("alice.test")))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let resolver = IdentityResolver::with_service_url(&server.uri());
+        for _ in 0..3 {
+            let profiles = resolver.get_profiles(&alice()).await;
+            assert_eq!(profiles["did:plc:alice"].handle, "alice.test");
+        }
+        // Give a wrongly spawned refresh the chance to hit the server.
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
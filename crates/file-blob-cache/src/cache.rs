@@ -1,55 +1,338 @@
 //! File-based blob caching with in-memory metadata
 
 use crate::types::{CacheEntry, CacheStats};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, MutexGuard};
 use tokio::fs;
-use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
 
+/// Hex characters of the cache key used as the shard directory name, giving
+/// 256 subdirectories so no single directory holds every blob.
+const SHARD_PREFIX_LEN: usize = 2;
+
+/// Content type assigned to blobs adopted from the old flat layout, whose
+/// original type was only ever held in memory.
+const ADOPTED_CONTENT_TYPE: &str = "application/octet-stream";
+
+/// Sidecar file in the cache directory holding the persisted index
+const INDEX_FILE: &str = "index.json";
+
+/// On-disk form of one index slot
+#[derive(Serialize, Deserialize)]
+struct PersistedSlot {
+    #[serde(flatten)]
+    entry: CacheEntry,
+    last_used: u64,
+}
+
+/// Callback invoked with the key and size of each blob the cache evicts
+pub type EvictionHook = Arc<dyn Fn(&str, u64) + Send + Sync>;
+
+/// Bookkeeping for one cached blob
+struct Slot {
+    entry: CacheEntry,
+    /// Index clock value at the most recent read or write; the lowest
+    /// unpinned slot is evicted first
+    last_used: u64,
+    /// Reads currently in progress; a slot with readers is never evicted
+    readers: usize,
+}
+
+/// Everything eviction decisions depend on, guarded by a single lock
+#[derive(Default)]
+struct Index {
+    slots: HashMap<String, Slot>,
+    /// Bytes held by tracked blobs plus bytes reserved by writes in flight.
+    /// Never exceeds the cache's `max_size`.
+    total_size: u64,
+    /// Monotonic counter ordering accesses for LRU
+    clock: u64,
+    /// Whether entries changed since the index was last persisted
+    dirty: bool,
+}
+
+impl Index {
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    /// Reserve `size` bytes, evicting least-recently-used unpinned blobs to
+    /// make room. Returns the evicted keys and sizes, or `None` without
+    /// evicting anything when the blob can't fit even after evicting every
+    /// unpinned entry.
+    ///
+    /// Evicted files are unlinked here, under the lock, so a concurrent
+    /// write of the same key can't land its file and then lose it to a
+    /// stale eviction.
+    fn reserve(&mut self, size: u64, max_size: u64) -> Option<Vec<(String, u64)>> {
+        let evictable: u64 = self
+            .slots
+            .values()
+            .filter(|slot| slot.readers == 0)
+            .map(|slot| slot.entry.size)
+            .sum();
+        if size > max_size || self.total_size - evictable + size > max_size {
+            return None;
+        }
+
+        let mut candidates: Vec<(u64, String)> = self
+            .slots
+            .iter()
+            .filter(|(_, slot)| slot.readers == 0)
+            .map(|(key, slot)| (slot.last_used, key.clone()))
+            .collect();
+        candidates.sort_unstable();
+
+        let mut evicted = Vec::new();
+        let mut candidates = candidates.into_iter();
+        while self.total_size + size > max_size {
+            let Some((_, key)) = candidates.next() else {
+                break;
+            };
+            if let Some(slot) = self.slots.remove(&key) {
+                self.total_size -= slot.entry.size;
+                self.dirty = true;
+                unlink(&slot.entry.path);
+                debug!(key = %key, "Evicted least recently used cache entry");
+                evicted.push((key, slot.entry.size));
+            }
+        }
+
+        self.total_size += size;
+        Some(evicted)
+    }
+}
+
+/// Delete a blob file, tolerating it already being gone
+fn unlink(path: &Path) {
+    if let Err(e) = std::fs::remove_file(path) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            warn!(path = ?path, error = %e, "Failed to remove cached file from disk");
+        }
+    }
+}
+
+/// Result of looking a key up in the index
+enum Lookup<'a> {
+    Hit(ReadLease<'a>, CacheEntry),
+    Expired,
+    Miss,
+}
+
+/// Pins a slot against eviction while its file is being read. Released on
+/// drop, so a cancelled read can't leave the slot pinned forever.
+struct ReadLease<'a> {
+    cache: &'a BlobCache,
+    key: String,
+}
+
+impl Drop for ReadLease<'_> {
+    fn drop(&mut self) {
+        if let Some(slot) = self.cache.index().slots.get_mut(&self.key) {
+            slot.readers = slot.readers.saturating_sub(1);
+        }
+    }
+}
+
 /// A blob cache with in-memory metadata and file-based storage
+///
+/// Blobs live at `{cache_dir}/{key[..2]}/{key}`. Files left at the top level
+/// by the old flat layout are moved into their shard the first time they're
+/// requested.
+///
+/// The index is saved to `{cache_dir}/index.json` by [`BlobCache::persist`]
+/// and reloaded by [`BlobCache::init`], so a restart keeps the cache warm.
+///
+/// Size accounting, LRU ordering and every change to which file sits at a
+/// blob's path happen under one lock, so concurrent writers can't push the
+/// cache past `max_size` and a blob being read is never evicted. Blob bytes
+/// are written to a temporary file outside the lock and renamed into place.
 pub struct BlobCache {
-    /// In-memory metadata for cached entries
-    entries: Arc<RwLock<HashMap<String, CacheEntry>>>,
+    /// In-memory metadata and size accounting for cached entries
+    index: Mutex<Index>,
     /// Directory where cached blobs are stored
     cache_dir: PathBuf,
     /// Maximum cache size in bytes
     max_size: u64,
     /// Cache TTL in seconds
     ttl_secs: u64,
-    /// Current total size of cached blobs
-    current_size: Arc<AtomicU64>,
     /// Cache hit counter
     hits: Arc<AtomicU64>,
     /// Cache miss counter
     misses: Arc<AtomicU64>,
+    /// Observer for evictions, if any
+    on_evict: Option<EvictionHook>,
 }
 
 impl BlobCache {
     /// Create a new blob cache
     pub fn new(cache_dir: PathBuf, max_size: u64, ttl_secs: u64) -> Self {
         Self {
-            entries: Arc::new(RwLock::new(HashMap::new())),
+            index: Mutex::new(Index::default()),
             cache_dir,
             max_size,
             ttl_secs,
-            current_size: Arc::new(AtomicU64::new(0)),
             hits: Arc::new(AtomicU64::new(0)),
             misses: Arc::new(AtomicU64::new(0)),
+            on_evict: None,
         }
     }
 
-    /// Initialize the cache by ensuring the cache directory exists
+    /// Call `hook` with the key and freed bytes of every blob evicted to
+    /// make room or dropped on expiry. It runs outside the cache's lock.
+    pub fn with_on_evict(mut self, hook: EvictionHook) -> Self {
+        self.on_evict = Some(hook);
+        self
+    }
+
+    fn report_evicted(&self, evicted: &[(String, u64)]) {
+        if let Some(hook) = &self.on_evict {
+            for (key, size) in evicted {
+                hook(key, *size);
+            }
+        }
+    }
+
+    /// Initialize the cache by ensuring the cache directory exists and
+    /// restoring the index persisted by a previous run
+    ///
+    /// The persisted index is reconciled with the shard directories: entries
+    /// whose file is missing or has the wrong size are dropped, and files
+    /// with no entry (including interrupted writes) are deleted. Top-level
+    /// files from the flat layout are left for lazy migration.
     pub async fn init(&self) -> std::io::Result<()> {
         fs::create_dir_all(&self.cache_dir).await?;
-        info!(cache_dir = ?self.cache_dir, "Cache initialized");
+
+        let mut persisted = self.read_persisted_index().await;
+        let mut restored = HashMap::new();
+        let mut pruned = 0usize;
+        let mut dirs = fs::read_dir(&self.cache_dir).await?;
+        while let Some(dir) = dirs.next_entry().await? {
+            let shard_name = dir.file_name();
+            let is_shard = shard_name.len() == SHARD_PREFIX_LEN
+                && shard_name
+                    .to_str()
+                    .is_some_and(|n| n.chars().all(|c| c.is_ascii_hexdigit()));
+            if !is_shard || !dir.file_type().await?.is_dir() {
+                continue;
+            }
+
+            let mut files = fs::read_dir(dir.path()).await?;
+            while let Some(file) = files.next_entry().await? {
+                let path = file.path();
+                let size = file.metadata().await?.len();
+                let slot = file
+                    .file_name()
+                    .to_str()
+                    .and_then(|key| Some((key.to_string(), persisted.remove(key)?)))
+                    .filter(|(_, slot)| slot.entry.size == size);
+                match slot {
+                    Some((key, PersistedSlot { entry, last_used })) => {
+                        let entry = CacheEntry { path, ..entry };
+                        restored.insert(
+                            key,
+                            Slot {
+                                entry,
+                                last_used,
+                                readers: 0,
+                            },
+                        );
+                    }
+                    None => {
+                        unlink(&path);
+                        pruned += 1;
+                    }
+                }
+            }
+        }
+
+        let evicted = {
+            let mut index = self.index();
+            index.clock = restored.values().map(|s| s.last_used).max().unwrap_or(0);
+            index.total_size = restored.values().map(|s| s.entry.size).sum();
+            index.slots = restored;
+            index.dirty = pruned > 0 || !persisted.is_empty();
+            // Shrink to fit in case `max_size` was lowered since the last run
+            let evicted = index.reserve(0, self.max_size).unwrap_or_default();
+            info!(
+                cache_dir = ?self.cache_dir,
+                entries = index.slots.len(),
+                total_size = index.total_size,
+                pruned,
+                "Cache initialized"
+            );
+            evicted
+        };
+        self.report_evicted(&evicted);
         Ok(())
     }
 
+    /// Load the persisted index, treating a missing or unreadable file as
+    /// empty (the reconcile in [`BlobCache::init`] then prunes the blobs).
+    async fn read_persisted_index(&self) -> HashMap<String, PersistedSlot> {
+        let path = self.cache_dir.join(INDEX_FILE);
+        let bytes = match fs::read(&path).await {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return HashMap::new(),
+            Err(e) => {
+                warn!(path = ?path, error = %e, "Failed to read cache index");
+                return HashMap::new();
+            }
+        };
+        serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+            warn!(path = ?path, error = %e, "Ignoring corrupt cache index");
+            HashMap::new()
+        })
+    }
+
+    /// Save the index so the next [`BlobCache::init`] can restore it. A no-op
+    /// when nothing was added or removed since the last save.
+    ///
+    /// Callers are expected to invoke this periodically; blobs written after
+    /// the last save are pruned on the next start.
+    pub async fn persist(&self) -> std::io::Result<()> {
+        let snapshot = {
+            let mut index = self.index();
+            if !index.dirty {
+                return Ok(());
+            }
+            index.dirty = false;
+            let slots: HashMap<&String, PersistedSlot> = index
+                .slots
+                .iter()
+                .map(|(key, slot)| {
+                    (
+                        key,
+                        PersistedSlot {
+                            entry: slot.entry.clone(),
+                            last_used: slot.last_used,
+                        },
+                    )
+                })
+                .collect();
+            serde_json::to_vec(&slots).map_err(std::io::Error::other)
+        };
+
+        let path = self.cache_dir.join(INDEX_FILE);
+        let tmp_path = path.with_extension("json.tmp");
+        let written = async {
+            fs::write(&tmp_path, snapshot?).await?;
+            fs::rename(&tmp_path, &path).await
+        }
+        .await;
+        if written.is_err() {
+            // Try again on the next call
+            self.index().dirty = true;
+        }
+        written
+    }
+
     /// Generate a cache key from DID and CID
     pub fn cache_key(did: &str, cid: &str) -> String {
         let mut hasher = Sha256::new();
@@ -57,28 +340,114 @@ impl BlobCache {
         hex::encode(hasher.finalize())
     }
 
-    /// Get a blob from the cache, returns (data, content_type) if found
-    pub async fn get(&self, did: &str, cid: &str) -> Option<(Vec<u8>, String)> {
-        let key = Self::cache_key(did, cid);
+    /// The index lock is never held across an await or while user code
+    /// runs, so a poisoned lock still holds consistent data.
+    fn index(&self) -> MutexGuard<'_, Index> {
+        self.index.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Sharded on-disk path for a cache key
+    fn blob_path(&self, key: &str) -> PathBuf {
+        self.cache_dir.join(&key[..SHARD_PREFIX_LEN]).join(key)
+    }
 
-        // Check in-memory metadata
-        let entry = {
-            let entries = self.entries.read().await;
-            entries.get(&key).cloned()
+    /// Find a live entry and pin it for reading
+    fn checkout(&self, key: &str) -> Lookup<'_> {
+        let mut index = self.index();
+        let tick = index.tick();
+        let Some(slot) = index.slots.get_mut(key) else {
+            return Lookup::Miss;
         };
 
-        if let Some(entry) = entry {
-            // Check if entry is expired
-            let age_secs = (Utc::now() - entry.created_at).num_seconds() as u64;
-            if age_secs > self.ttl_secs {
-                debug!(key = %key, age_secs, ttl_secs = self.ttl_secs, "Cache entry expired");
-                self.remove(&key).await;
-                self.misses.fetch_add(1, Ordering::Relaxed);
-                return None;
+        let age_secs = (Utc::now() - slot.entry.created_at).num_seconds() as u64;
+        if age_secs > self.ttl_secs {
+            debug!(key = %key, age_secs, ttl_secs = self.ttl_secs, "Cache entry expired");
+            return Lookup::Expired;
+        }
+
+        slot.last_used = tick;
+        slot.readers += 1;
+        let entry = slot.entry.clone();
+        Lookup::Hit(
+            ReadLease {
+                cache: self,
+                key: key.to_string(),
+            },
+            entry,
+        )
+    }
+
+    /// Move a blob left at the top level by the flat layout into its shard
+    /// and start tracking it. Returns whether the key is now tracked.
+    async fn adopt_flat_file(&self, key: &str) -> bool {
+        let flat_path = self.cache_dir.join(key);
+        let Ok(metadata) = fs::metadata(&flat_path).await else {
+            return false;
+        };
+        if !metadata.is_file() {
+            return false;
+        }
+        let path = self.blob_path(key);
+        if let Some(shard) = path.parent() {
+            if let Err(e) = fs::create_dir_all(shard).await {
+                warn!(key = %key, error = %e, "Failed to create cache shard directory");
+                return false;
             }
+        }
+
+        let mut index = self.index();
+        if index.slots.contains_key(key) {
+            return true;
+        }
+        let Some(evicted) = index.reserve(metadata.len(), self.max_size) else {
+            debug!(key = %key, "No room to adopt flat cache file");
+            return false;
+        };
+        if let Err(e) = std::fs::rename(&flat_path, &path) {
+            warn!(key = %key, error = %e, "Failed to migrate flat cache file");
+            index.total_size -= metadata.len();
+            drop(index);
+            self.report_evicted(&evicted);
+            return false;
+        }
+
+        let last_used = index.tick();
+        let entry = CacheEntry {
+            path,
+            content_type: ADOPTED_CONTENT_TYPE.to_string(),
+            size: metadata.len(),
+            created_at: metadata
+                .modified()
+                .map(DateTime::<Utc>::from)
+                .unwrap_or_else(|_| Utc::now()),
+        };
+        index.slots.insert(
+            key.to_string(),
+            Slot {
+                entry,
+                last_used,
+                readers: 0,
+            },
+        );
+        index.dirty = true;
+        drop(index);
+        debug!(key = %key, "Migrated flat cache file into shard");
+        self.report_evicted(&evicted);
+        true
+    }
 
-            // Try to read the file
-            match fs::read(&entry.path).await {
+    /// Get a blob from the cache, returns (data, content_type) if found
+    pub async fn get(&self, did: &str, cid: &str) -> Option<(Vec<u8>, String)> {
+        let key = Self::cache_key(did, cid);
+
+        // Check in-memory metadata, falling back to a pre-sharding file
+        let mut lookup = self.checkout(&key);
+        if matches!(lookup, Lookup::Miss) && self.adopt_flat_file(&key).await {
+            lookup = self.checkout(&key);
+        }
+
+        match lookup {
+            Lookup::Hit(lease, entry) => match fs::read(&entry.path).await {
                 Ok(data) => {
                     self.hits.fetch_add(1, Ordering::Relaxed);
                     debug!(key = %key, "Cache hit");
@@ -86,9 +455,16 @@ impl BlobCache {
                 }
                 Err(e) => {
                     warn!(key = %key, error = %e, "Failed to read cached file, removing entry");
-                    self.remove(&key).await;
+                    drop(lease);
+                    self.remove(&key);
+                }
+            },
+            Lookup::Expired => {
+                if let Some(size) = self.remove(&key) {
+                    self.report_evicted(&[(key, size)]);
                 }
             }
+            Lookup::Miss => {}
         }
 
         self.misses.fetch_add(1, Ordering::Relaxed);
@@ -96,6 +472,9 @@ impl BlobCache {
     }
 
     /// Store a blob in the cache
+    ///
+    /// A blob that can't fit, because it's larger than the whole cache or
+    /// everything evictable is being read, is skipped rather than stored.
     pub async fn put(
         &self,
         did: &str,
@@ -106,89 +485,97 @@ impl BlobCache {
         let key = Self::cache_key(did, cid);
         let size = data.len() as u64;
 
-        // Evict entries if needed to make room
-        self.evict_if_needed(size).await;
-
-        // Write to disk
-        let path = self.cache_dir.join(&key);
-        fs::write(&path, data).await?;
-
-        // Update metadata
-        let entry = CacheEntry {
-            path,
-            content_type: content_type.to_string(),
-            size,
-            created_at: Utc::now(),
+        // Evict entries if needed and reserve room
+        let (write_id, evicted) = {
+            let mut index = self.index();
+            let Some(evicted) = index.reserve(size, self.max_size) else {
+                debug!(key = %key, size, "No room in cache, not caching blob");
+                return Ok(());
+            };
+            (index.tick(), evicted)
         };
-
-        self.current_size.fetch_add(size, Ordering::Relaxed);
-        debug!(key = %key, size, "Cached blob");
-
-        {
-            let mut entries = self.entries.write().await;
-            entries.insert(key, entry);
+        self.report_evicted(&evicted);
+
+        // Write to a private temporary file
+        let path = self.blob_path(&key);
+        let tmp_path = path.with_extension(format!("{write_id}.tmp"));
+        let written = async {
+            if let Some(shard) = path.parent() {
+                fs::create_dir_all(shard).await?;
+            }
+            fs::write(&tmp_path, data).await
         }
-
-        Ok(())
-    }
-
-    /// Evict oldest entries until there's enough room for new_size bytes
-    async fn evict_if_needed(&self, new_size: u64) {
-        let current = self.current_size.load(Ordering::Relaxed);
-
-        if current + new_size <= self.max_size {
-            return;
+        .await;
+        if let Err(e) = written {
+            self.index().total_size -= size;
+            let _ = fs::remove_file(&tmp_path).await;
+            return Err(e);
         }
 
-        let target_size = self.max_size.saturating_sub(new_size);
-
-        loop {
-            let current = self.current_size.load(Ordering::Relaxed);
-            if current <= target_size {
-                break;
-            }
-
-            // Find and remove oldest entry
-            let oldest_key = {
-                let entries = self.entries.read().await;
-                entries
-                    .iter()
-                    .min_by_key(|(_, e)| e.created_at)
-                    .map(|(k, _)| k.clone())
-            };
-
-            if let Some(key) = oldest_key {
-                self.remove(&key).await;
-                debug!(key = %key, "Evicted oldest cache entry");
-            } else {
-                break;
+        // Move it into place and publish the metadata
+        let renamed = {
+            let mut index = self.index();
+            let renamed = std::fs::rename(&tmp_path, &path);
+            match renamed {
+                Ok(()) => {
+                    let last_used = index.tick();
+                    let entry = CacheEntry {
+                        path,
+                        content_type: content_type.to_string(),
+                        size,
+                        created_at: Utc::now(),
+                    };
+                    let mut slot = Slot {
+                        entry,
+                        last_used,
+                        readers: 0,
+                    };
+                    if let Some(replaced) = index.slots.remove(&key) {
+                        // Readers of the replaced file keep their open handle;
+                        // their leases now pin the new slot instead.
+                        index.total_size -= replaced.entry.size;
+                        slot.readers = replaced.readers;
+                    }
+                    index.slots.insert(key.clone(), slot);
+                    index.dirty = true;
+                    Ok(())
+                }
+                Err(e) => {
+                    index.total_size -= size;
+                    Err(e)
+                }
             }
-        }
-    }
-
-    /// Remove an entry from the cache
-    async fn remove(&self, key: &str) {
-        let entry = {
-            let mut entries = self.entries.write().await;
-            entries.remove(key)
         };
+        if let Err(e) = renamed {
+            let _ = fs::remove_file(&tmp_path).await;
+            return Err(e);
+        }
+        debug!(key = %key, size, "Cached blob");
 
-        if let Some(entry) = entry {
-            self.current_size.fetch_sub(entry.size, Ordering::Relaxed);
+        Ok(())
+    }
 
-            // Try to remove the file
-            if let Err(e) = fs::remove_file(&entry.path).await {
-                warn!(path = ?entry.path, error = %e, "Failed to remove cached file from disk");
+    /// Remove an entry from the cache unless it's being read, returning the
+    /// bytes freed
+    fn remove(&self, key: &str) -> Option<u64> {
+        let mut index = self.index();
+        if index.slots.get(key).is_some_and(|slot| slot.readers == 0) {
+            if let Some(slot) = index.slots.remove(key) {
+                index.total_size -= slot.entry.size;
+                index.dirty = true;
+                unlink(&slot.entry.path);
+                return Some(slot.entry.size);
             }
         }
+        None
     }
 
     /// Get current cache statistics
     pub async fn stats(&self) -> CacheStats {
-        let entries = self.entries.read().await;
+        let index = self.index();
         CacheStats {
-            entries: entries.len(),
-            total_size: self.current_size.load(Ordering::Relaxed),
+            entries: index.slots.len(),
+            total_size: index.total_size,
             hits: self.hits.load(Ordering::Relaxed),
             misses: self.misses.load(Ordering::Relaxed),
         }
@@ -335,4 +722,243 @@ mod tests {
         let stats = cache.stats().await;
         assert!(stats.total_size <= 20);
     }
+
+    #[tokio::test]
+    async fn test_blobs_are_stored_in_shards() {
+        let dir = tempdir().expect("should create temp directory");
+        let cache = BlobCache::new(dir.path().to_path_buf(), 1024 * 1024, 3600);
+        cache.init().await.expect("cache init should succeed");
+
+        cache
+            .put("did:plc:test", "bafytest", b"data", "text/plain")
+            .await
+            .expect("cache put should succeed");
+
+        let key = BlobCache::cache_key("did:plc:test", "bafytest");
+        let sharded = dir.path().join(&key[..2]).join(&key);
+        assert!(sharded.is_file());
+        assert!(!dir.path().join(&key).exists());
+    }
+
+    #[tokio::test]
+    async fn test_flat_file_is_migrated_on_read() {
+        let dir = tempdir().expect("should create temp directory");
+        let key = BlobCache::cache_key("did:plc:test", "bafytest");
+        std::fs::write(dir.path().join(&key), b"legacy").expect("should write flat file");
+
+        let cache = BlobCache::new(dir.path().to_path_buf(), 1024 * 1024, 3600);
+        cache.init().await.expect("cache init should succeed");
+
+        let (data, content_type) = cache
+            .get("did:plc:test", "bafytest")
+            .await
+            .expect("flat file should still be readable");
+        assert_eq!(data, b"legacy");
+        assert_eq!(content_type, ADOPTED_CONTENT_TYPE);
+
+        assert!(!dir.path().join(&key).exists());
+        assert!(dir.path().join(&key[..2]).join(&key).is_file());
+        assert_eq!(cache.stats().await.total_size, 6);
+    }
+
+    #[tokio::test]
+    async fn test_eviction_prefers_least_recently_used() {
+        let dir = tempdir().expect("should create temp directory");
+        let cache = BlobCache::new(dir.path().to_path_buf(), 20, 3600);
+        cache.init().await.expect("cache init should succeed");
+
+        cache
+            .put("did:plc:1", "cid1", b"0123456789", "text/plain")
+            .await
+            .expect("first cache put should succeed");
+        cache
+            .put("did:plc:2", "cid2", b"abcdefghij", "text/plain")
+            .await
+            .expect("second cache put should succeed");
+
+        // Touch the older entry so the newer one becomes least recently used
+        assert!(cache.get("did:plc:1", "cid1").await.is_some());
+        cache
+            .put("did:plc:3", "cid3", b"ABCDEFGHIJ", "text/plain")
+            .await
+            .expect("third cache put should succeed");
+
+        assert!(cache.get("did:plc:1", "cid1").await.is_some());
+        assert!(cache.get("did:plc:2", "cid2").await.is_none());
+        assert!(cache.get("did:plc:3", "cid3").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_oversized_blob_is_not_cached() {
+        let dir = tempdir().expect("should create temp directory");
+        let cache = BlobCache::new(dir.path().to_path_buf(), 4, 3600);
+        cache.init().await.expect("cache init should succeed");
+
+        cache
+            .put("did:plc:1", "cid1", b"0123456789", "text/plain")
+            .await
+            .expect("oversized put should be skipped, not fail");
+        assert!(cache.get("did:plc:1", "cid1").await.is_none());
+        assert_eq!(cache.stats().await.total_size, 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_puts_respect_max_size_and_spare_readers() {
+        const MAX_SIZE: u64 = 1000;
+        const BLOB: &[u8] = &[7; 100];
+
+        let dir = tempdir().expect("should create temp directory");
+        let cache = Arc::new(BlobCache::new(dir.path().to_path_buf(), MAX_SIZE, 3600));
+        cache.init().await.expect("cache init should succeed");
+
+        // The oldest entry, and so the first eviction candidate, held open
+        // for the whole run
+        cache
+            .put("did:plc:pinned", "cid", BLOB, "text/plain")
+            .await
+            .expect("cache put should succeed");
+        let pinned_key = BlobCache::cache_key("did:plc:pinned", "cid");
+        let Lookup::Hit(pinned_lease, pinned_entry) = cache.checkout(&pinned_key) else {
+            panic!("pinned entry should be cached");
+        };
+
+        let mut tasks = Vec::new();
+        for i in 0..64 {
+            let cache = cache.clone();
+            tasks.push(tokio::spawn(async move {
+                for j in 0..10 {
+                    let did = format!("did:plc:{i}");
+                    let cid = format!("cid{j}");
+                    cache
+                        .put(&did, &cid, BLOB, "text/plain")
+                        .await
+                        .expect("cache put should succeed");
+                    assert!(cache.stats().await.total_size <= MAX_SIZE);
+
+                    // A checked-out file must survive until released
+                    let key = BlobCache::cache_key(&did, &cid);
+                    if let Lookup::Hit(lease, entry) = cache.checkout(&key) {
+                        tokio::task::yield_now().await;
+                        let data = fs::read(&entry.path)
+                            .await
+                            .expect("leased file should not be evicted");
+                        assert_eq!(data, BLOB);
+                        drop(lease);
+                    }
+                }
+            }));
+        }
+        for task in tasks {
+            task.await.expect("task should not panic");
+        }
+
+        assert!(pinned_entry.path.is_file());
+        drop(pinned_lease);
+
+        let stats = cache.stats().await;
+        assert!(stats.total_size <= MAX_SIZE);
+        assert_eq!(stats.total_size, stats.entries as u64 * BLOB.len() as u64);
+
+        let mut on_disk = 0;
+        for shard in std::fs::read_dir(dir.path()).expect("should list cache dir") {
+            for file in std::fs::read_dir(shard.expect("should read shard").path())
+                .expect("should list shard")
+            {
+                on_disk += file.expect("should read file").metadata().unwrap().len();
+            }
+        }
+        assert_eq!(on_disk, stats.total_size);
+    }
+
+    #[tokio::test]
+    async fn test_index_survives_restart() {
+        let dir = tempdir().expect("should create temp directory");
+        {
+            let cache = BlobCache::new(dir.path().to_path_buf(), 1024 * 1024, 3600);
+            cache.init().await.expect("cache init should succeed");
+            cache
+                .put("did:plc:1", "cid1", b"0123456789", "image/jpeg")
+                .await
+                .expect("first cache put should succeed");
+            cache
+                .put("did:plc:2", "cid2", b"abcde", "image/png")
+                .await
+                .expect("second cache put should succeed");
+            cache.persist().await.expect("persist should succeed");
+        }
+
+        // An orphan with no index entry, as left by a write after the last save
+        let orphan = BlobCache::cache_key("did:plc:3", "cid3");
+        let orphan_path = dir.path().join(&orphan[..2]).join(&orphan);
+        std::fs::create_dir_all(orphan_path.parent().unwrap()).unwrap();
+        std::fs::write(&orphan_path, b"orphan").unwrap();
+
+        let cache = BlobCache::new(dir.path().to_path_buf(), 1024 * 1024, 3600);
+        cache.init().await.expect("cache init should succeed");
+
+        let stats = cache.stats().await;
+        assert_eq!(stats.entries, 2);
+        assert_eq!(stats.total_size, 15);
+        assert!(!orphan_path.exists());
+
+        let (data, content_type) = cache
+            .get("did:plc:1", "cid1")
+            .await
+            .expect("restored entry should be served");
+        assert_eq!(data, b"0123456789");
+        assert_eq!(content_type, "image/jpeg");
+    }
+
+    #[tokio::test]
+    async fn test_restart_drops_entries_missing_from_disk() {
+        let dir = tempdir().expect("should create temp directory");
+        {
+            let cache = BlobCache::new(dir.path().to_path_buf(), 1024 * 1024, 3600);
+            cache.init().await.expect("cache init should succeed");
+            cache
+                .put("did:plc:1", "cid1", b"0123456789", "image/jpeg")
+                .await
+                .expect("cache put should succeed");
+            cache.persist().await.expect("persist should succeed");
+        }
+        let key = BlobCache::cache_key("did:plc:1", "cid1");
+        std::fs::remove_file(dir.path().join(&key[..2]).join(&key)).unwrap();
+
+        let cache = BlobCache::new(dir.path().to_path_buf(), 1024 * 1024, 3600);
+        cache.init().await.expect("cache init should succeed");
+        assert_eq!(cache.stats().await.entries, 0);
+        assert_eq!(cache.stats().await.total_size, 0);
+    }
+
+    #[tokio::test]
+    async fn test_on_evict_reports_evicted_key() {
+        let evicted = Arc::new(Mutex::new(Vec::new()));
+        let hook_evicted = evicted.clone();
+        let dir = tempdir().expect("should create temp directory");
+        let cache = BlobCache::new(dir.path().to_path_buf(), 20, 3600).with_on_evict(Arc::new(
+            move |key: &str, size: u64| {
+                hook_evicted.lock().unwrap().push((key.to_string(), size));
+            },
+        ));
+        cache.init().await.expect("cache init should succeed");
+
+        cache
+            .put("did:plc:1", "cid1", b"0123456789", "text/plain")
+            .await
+            .expect("first cache put should succeed");
+        cache
+            .put("did:plc:2", "cid2", b"abcdefghij", "text/plain")
+            .await
+            .expect("second cache put should succeed");
+        assert!(evicted.lock().unwrap().is_empty());
+
+        cache
+            .put("did:plc:3", "cid3", b"ABCDEFGHIJ", "text/plain")
+            .await
+            .expect("third cache put should succeed");
+        assert_eq!(
+            *evicted.lock().unwrap(),
+            vec![(BlobCache::cache_key("did:plc:1", "cid1"), 10)]
+        );
+    }
 }
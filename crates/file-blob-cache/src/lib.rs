@@ -6,5 +6,5 @@
 mod cache;
 mod types;
 
-pub use cache::BlobCache;
+pub use cache::{BlobCache, EvictionHook};
 pub use types::{CacheEntry, CacheStats};
@@ -0,0 +1,140 @@
+//! Shared construction of outbound [`reqwest::Client`]s.
+//!
+//! Every service talks to PDSes, PLC, GBIF and friends; building those
+//! clients in one place keeps their timeouts, connection pooling and user
+//! agent consistent instead of drifting per crate.
+//!
+//! ```ignore
+//! // Defaults: bounded connect/read timeouts, no overall deadline.
+//! let client = http_client::client();
+//!
+//! // A caller that wants a hard cap on each request:
+//! let client = http_client::HttpClientConfig::default()
+//!     .with_timeout(Duration::from_secs(10))
+//!     .build();
+//! ```
+
+use std::time::Duration;
+
+/// User agent sent on every outbound request, so upstream operators can
+/// tell who is calling and how to reach us.
+pub const DEFAULT_USER_AGENT: &str = "Observ.ing/1.0 (+https://observ.ing)";
+
+/// Tuning for an outbound HTTP client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpClientConfig {
+    pub user_agent: String,
+    /// Limit on establishing the TCP/TLS connection.
+    pub connect_timeout: Duration,
+    /// Limit on each read from the socket, so a stalled upstream is dropped
+    /// even when no overall deadline is set.
+    pub read_timeout: Duration,
+    /// Deadline for the whole request, body included. `None` lets large
+    /// downloads run as long as data keeps arriving.
+    pub timeout: Option<Duration>,
+    /// How long an idle pooled connection is kept for reuse.
+    pub pool_idle_timeout: Duration,
+    /// Idle connections kept per host.
+    pub pool_max_idle_per_host: usize,
+}
+
+impl Default for HttpClientConfig {
+    fn default() -> Self {
+        Self {
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            connect_timeout: Duration::from_secs(10),
+            read_timeout: Duration::from_secs(30),
+            timeout: None,
+            pool_idle_timeout: Duration::from_secs(90),
+            pool_max_idle_per_host: 16,
+        }
+    }
+}
+
+impl HttpClientConfig {
+    /// Cap each request at `timeout` end to end.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// A builder carrying this configuration, for callers that need to add
+    /// settings of their own before building.
+    pub fn builder(&self) -> reqwest::ClientBuilder {
+        let builder = reqwest::Client::builder()
+            .user_agent(&self.user_agent)
+            .connect_timeout(self.connect_timeout)
+            .read_timeout(self.read_timeout)
+            .pool_idle_timeout(self.pool_idle_timeout)
+            .pool_max_idle_per_host(self.pool_max_idle_per_host);
+        match self.timeout {
+            Some(timeout) => builder.timeout(timeout),
+            None => builder,
+        }
+    }
+
+    /// Build the client. Panics only if the TLS backend can't initialise,
+    /// which no request could recover from anyway.
+    pub fn build(&self) -> reqwest::Client {
+        self.builder()
+            .build()
+            .expect("Failed to create HTTP client")
+    }
+}
+
+/// A client with the default [`HttpClientConfig`].
+pub fn client() -> reqwest::Client {
+    HttpClientConfig::default().build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn defaults_bound_connect_and_read_but_not_the_whole_request() {
+        let config = HttpClientConfig::default();
+        assert_eq!(config.user_agent, DEFAULT_USER_AGENT);
+        assert_eq!(config.connect_timeout, Duration::from_secs(10));
+        assert_eq!(config.read_timeout, Duration::from_secs(30));
+        assert_eq!(config.timeout, None);
+        assert_eq!(config.pool_idle_timeout, Duration::from_secs(90));
+        assert_eq!(config.pool_max_idle_per_host, 16);
+
+        let capped = HttpClientConfig::default().with_timeout(Duration::from_secs(5));
+        assert_eq!(capped.timeout, Some(Duration::from_secs(5)));
+    }
+
+    #[tokio::test]
+    async fn client_sends_the_observing_user_agent() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0; 4096];
+            let n = stream.read(&mut buf).await.unwrap();
+            stream
+                .write_all(b"HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n")
+                .await
+                .unwrap();
+            String::from_utf8_lossy(&buf[..n]).to_lowercase()
+        });
+
+        let response = client()
+            .get(format!("http://{addr}/"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 204);
+        let request = server.await.unwrap();
+        assert!(
+            request.contains(&format!(
+                "user-agent: {}",
+                DEFAULT_USER_AGENT.to_lowercase()
+            )),
+            "{request}"
+        );
+    }
+}
@@ -16,6 +16,8 @@ use crate::state::{AgentType, AppState, OAuthClientType};
 #[derive(Debug, Clone)]
 pub struct AuthUser {
     pub did: String,
+    /// Device session from the `session_id` cookie.
+    pub session_id: String,
 }
 
 /// Axum extractor that validates the session cookie and returns an [`AuthUser`].
@@ -46,10 +48,27 @@ pub fn session_did(cookies: &CookieJar) -> Option<String> {
     cookies.get("session_did").map(|c| c.value().to_string())
 }
 
+/// Extract the device session id from cookie.
+pub fn session_id(cookies: &CookieJar) -> Option<String> {
+    cookies.get("session_id").map(|c| c.value().to_string())
+}
+
 /// Validate session and return AuthUser (used for required auth in write endpoints)
 pub async fn require_auth(pool: &PgPool, cookies: &CookieJar) -> Result<AuthUser, ()> {
     let did = session_did(cookies).ok_or(())?;
 
+    // Every login gets a device session. Cookies without one predate device
+    // sessions and can't be revoked by logout-all, so they no longer
+    // authenticate; nor does a session that was logged out (here or via
+    // logout-all), even though the DID's OAuth tokens may remain.
+    let session_id = session_id(cookies).ok_or(())?;
+    let live = observing_db::oauth::touch_device_session(pool, &session_id, &did)
+        .await
+        .unwrap_or(false);
+    if !live {
+        return Err(());
+    }
+
     // Verify session exists in database
     let session = observing_db::oauth::get_session(pool, &did)
         .await
@@ -59,7 +78,7 @@ pub async fn require_auth(pool: &PgPool, cookies: &CookieJar) -> Result<AuthUser
         return Err(());
     }
 
-    Ok(AuthUser { did })
+    Ok(AuthUser { did, session_id })
 }
 
 /// Build a `StrongRef` from raw URI and CID strings, returning a user-facing
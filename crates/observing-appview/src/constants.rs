@@ -1,4 +1,6 @@
 // --- Occurrence query defaults ---
+// Page-size defaults and caps here are only fallbacks; the handlers read
+// them from `Config::page_limits`, which can override each via env.
 
 /// Default number of occurrences returned by the nearby endpoint.
 pub const DEFAULT_NEARBY_LIMIT: i64 = 100;
@@ -9,11 +11,32 @@ pub const MAX_NEARBY_LIMIT: i64 = 1000;
 /// Default search radius (in meters) for the nearby endpoint.
 pub const DEFAULT_NEARBY_RADIUS: f64 = 10_000.0;
 
+/// Default search radius (in meters) for an occurrence's similar
+/// observations.
+pub const DEFAULT_SIMILAR_RADIUS: f64 = 50_000.0;
+
+/// Largest radius (in meters) accepted for similar observations.
+pub const MAX_SIMILAR_RADIUS: f64 = 500_000.0;
+
+/// Largest radius (in meters) accepted for the needs-ID feed's region.
+pub const MAX_NEEDS_ID_RADIUS: f64 = 500_000.0;
+
 /// Default number of occurrences returned by the bounding-box endpoint.
 pub const DEFAULT_BBOX_LIMIT: i64 = 1000;
 
-/// Maximum number of points returned by the GeoJSON endpoint.
-pub const MAX_GEOJSON_LIMIT: i64 = 10_000;
+/// Maximum number of occurrences the bounding-box endpoint will return, and
+/// the fixed number of points returned by the GeoJSON endpoint.
+pub const MAX_BBOX_LIMIT: i64 = 10_000;
+
+/// Default heatmap cell size, in degrees.
+pub const DEFAULT_HEATMAP_CELL_SIZE: f64 = 1.0;
+
+/// Largest heatmap cell size, in degrees.
+pub const MAX_HEATMAP_CELL_SIZE: f64 = 30.0;
+
+/// Most grid cells a heatmap request may span; smaller cell sizes are
+/// widened to fit.
+pub const MAX_HEATMAP_CELLS: f64 = 20_000.0;
 
 /// Default coordinate uncertainty (in meters) assigned to new occurrences.
 pub const DEFAULT_COORDINATE_UNCERTAINTY: i32 = 50;
@@ -32,20 +55,151 @@ pub const DEFAULT_NOTIFICATION_LIMIT: i64 = 20;
 /// Maximum page size for the notifications list.
 pub const MAX_NOTIFICATION_LIMIT: i64 = 50;
 
+/// Default trailing window (in days) for the trending-taxa and leaderboard feeds.
+pub const DEFAULT_TRENDING_WINDOW_DAYS: i64 = 7;
+
+/// Maximum trailing window (in days) for the trending-taxa and leaderboard feeds.
+pub const MAX_TRENDING_WINDOW_DAYS: i64 = 365;
+
+/// Default trailing window (in days) for "recently active" in the nearby
+/// observers list.
+pub const DEFAULT_NEARBY_OBSERVERS_WINDOW_DAYS: i64 = 90;
+
+/// Maximum trailing window (in days) for the nearby observers list.
+pub const MAX_NEARBY_OBSERVERS_WINDOW_DAYS: i64 = 365;
+
+/// Largest radius (in meters) accepted for the nearby observers list.
+pub const MAX_NEARBY_OBSERVERS_RADIUS: f64 = 100_000.0;
+
+/// Default number of observers returned by the nearby observers endpoint.
+pub const DEFAULT_NEARBY_OBSERVERS_LIMIT: i64 = 20;
+
+/// Maximum number of observers the nearby observers endpoint will return.
+pub const MAX_NEARBY_OBSERVERS_LIMIT: i64 = 100;
+
+// --- Database ---
+
+/// Default server-side statement timeout (in milliseconds) for the appview's
+/// pools. `0` disables it.
+pub const DEFAULT_DB_STATEMENT_TIMEOUT_MS: u64 = 10_000;
+
+/// Statement timeout for the trending and leaderboard aggregations, which
+/// scan a whole window of occurrences.
+pub const AGGREGATE_STATEMENT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+
+// --- Request body limits ---
+
+/// Default cap (in bytes) on request bodies for ordinary JSON endpoints.
+pub const DEFAULT_JSON_BODY_LIMIT: usize = 64 * 1024;
+
+/// Default cap (in bytes) on request bodies for routes that accept inline
+/// base64-encoded images (occurrence create/update, species ID).
+pub const DEFAULT_UPLOAD_BODY_LIMIT: usize = 150 * 1024 * 1024;
+
+// --- Occurrence images ---
+
+/// Default cap on images attached to one occurrence; the lexicon's
+/// `associatedMedia` maxLength.
+pub const DEFAULT_MAX_OCCURRENCE_IMAGES: usize = 10;
+
+/// Default cap (in bytes) on the decoded images in one occurrence create or
+/// update.
+pub const DEFAULT_MAX_OCCURRENCE_IMAGE_BYTES: usize = 100 * 1024 * 1024;
+
+// --- Occurrence coordinates ---
+
+/// How close to 0,0 (in degrees on each axis) counts as null island.
+pub const NULL_ISLAND_EPSILON: f64 = 1e-4;
+
+/// Default uncertainty (in metres) below which whole-degree coordinates are
+/// rejected. A whole degree of latitude is ~111 km, so anything claiming to
+/// be much tighter than this almost certainly lost its decimals.
+pub const DEFAULT_INTEGER_COORDINATE_MIN_UNCERTAINTY: i32 = 1000;
+
+// --- Image URLs ---
+
+/// Bluesky's image CDN, for the `cdn` blob URL strategy.
+pub const BSKY_CDN_URL: &str = "https://cdn.bsky.app";
+
+/// How long (seconds) a signed link to a private blob stays valid. Long
+/// enough to outlast a page view, short enough that a leaked link dies soon.
+pub const PRIVATE_MEDIA_URL_TTL_SECS: i64 = 3600;
+
+// --- Read caching ---
+
+/// Default `max-age` (seconds) on anonymous read responses; matches the
+/// in-process feed cache's TTL, so a CDN holds a page no longer than the
+/// appview would.
+pub const DEFAULT_READ_CACHE_MAX_AGE_SECS: u32 = 5;
+
+/// Default `stale-while-revalidate` window (seconds) on anonymous read
+/// responses.
+pub const DEFAULT_READ_CACHE_STALE_SECS: u32 = 30;
+
 // --- Validation limits ---
 
 /// Maximum allowed length of a comment body (in characters).
 pub const MAX_COMMENT_LENGTH: usize = 3000;
 
+/// Maximum allowed length of an interaction's notes (in characters).
+pub const MAX_INTERACTION_COMMENT_LENGTH: usize = 3000;
+
+/// Maximum allowed length of an image's alt text (in characters). Matches the
+/// media lexicon's `alt` limit.
+pub const MAX_IMAGE_ALT_LENGTH: usize = 1000;
+
+/// Maximum allowed length of an image caption (in characters).
+pub const MAX_IMAGE_CAPTION_LENGTH: usize = 1000;
+
 /// Maximum allowed length of a scientific name (in characters).
 pub const MAX_SCIENTIFIC_NAME_LENGTH: usize = 256;
 
+/// Maximum allowed length of an identification qualifier such as `cf.` or
+/// `aff.` (in characters). Shared with the ingester, which drops longer ones.
+pub const MAX_IDENTIFICATION_QUALIFIER_LENGTH: usize =
+    observing_db::processing::MAX_IDENTIFICATION_QUALIFIER_LENGTH;
+
 /// Maximum allowed length of an interaction type string (in characters).
 pub const MAX_INTERACTION_TYPE_LENGTH: usize = 64;
 
 /// Minimum length for search queries (taxonomy search).
 pub const MIN_SEARCH_QUERY_LENGTH: usize = 2;
 
+/// Number of taxa returned by the autocomplete endpoint.
+pub const TAXON_SUGGEST_LIMIT: u32 = 10;
+
+/// Number of images returned for a taxon (local photos, then GBIF media).
+pub const TAXON_IMAGES_LIMIT: usize = 12;
+
+/// Number of interaction groups (type, role and partner taxon) returned for
+/// a taxon.
+pub const TAXON_INTERACTIONS_LIMIT: usize = 50;
+
+// --- Bulk import ---
+
+/// Most data rows accepted by one `POST /api/occurrences/import`.
+pub const MAX_IMPORT_ROWS: usize = 500;
+
+/// Request body cap for `POST /api/occurrences/import`: room for
+/// [`MAX_IMPORT_ROWS`] rows of a wide Darwin Core export.
+pub const MAX_IMPORT_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// Pause between PDS writes during an import, capping it at ~10 records/s.
+pub const IMPORT_ROW_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+// --- Idempotency ---
+
+/// How long a completed `Idempotency-Key` replays its original result.
+pub const IDEMPOTENCY_KEY_TTL: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+
+/// How long an unfinished `Idempotency-Key` blocks retries before it's
+/// treated as abandoned (e.g. the first request's process died).
+pub const IDEMPOTENCY_IN_PROGRESS_TIMEOUT: std::time::Duration =
+    std::time::Duration::from_secs(2 * 60);
+
+/// Longest accepted `Idempotency-Key` header value.
+pub const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 255;
+
 // --- Interaction defaults ---
 
 /// Default direction value for species interactions.
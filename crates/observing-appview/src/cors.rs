@@ -0,0 +1,180 @@
+//! CORS policy built from `CORS_ORIGINS`.
+//!
+//! Entries are exact origins (`https://observ.ing`) or subdomain wildcards
+//! (`https://*.observ.ing`, matching any depth of subdomain but not the bare
+//! domain). A request's `Origin` is checked against them on every request
+//! and echoed back, with credentials allowed, only when it matches. A lone
+//! `*` entry keeps the permissive no-credentials policy used in dev.
+
+use axum::http::{header, HeaderName, HeaderValue, Method};
+use tower_http::cors::{AllowCredentials, AllowOrigin, Any, CorsLayer};
+
+/// One `CORS_ORIGINS` entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum OriginPattern {
+    Exact(String),
+    /// `scheme://*.suffix`, stored as the scheme prefix (`https://`) and the
+    /// dotted suffix (`.observ.ing`).
+    Subdomain {
+        scheme: String,
+        suffix: String,
+    },
+}
+
+impl OriginPattern {
+    fn parse(entry: &str) -> Self {
+        let entry = entry.trim().trim_end_matches('/');
+        match entry.split_once("://*.") {
+            Some((scheme, domain)) => Self::Subdomain {
+                scheme: format!("{scheme}://"),
+                suffix: format!(".{domain}"),
+            },
+            None => Self::Exact(entry.to_string()),
+        }
+    }
+
+    fn matches(&self, origin: &str) -> bool {
+        match self {
+            Self::Exact(allowed) => origin == allowed,
+            Self::Subdomain { scheme, suffix } => origin
+                .strip_prefix(scheme.as_str())
+                .and_then(|host| host.strip_suffix(suffix.as_str()))
+                .is_some_and(|label| {
+                    !label.is_empty()
+                        && label
+                            .split('.')
+                            .all(|part| !part.is_empty() && is_host_label(part))
+                }),
+        }
+    }
+}
+
+/// Letters, digits and hyphens only, so a wildcard can't swallow a port,
+/// path, or userinfo smuggled into the Origin header.
+fn is_host_label(label: &str) -> bool {
+    label
+        .bytes()
+        .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+}
+
+/// Matches request origins against the configured patterns.
+#[derive(Debug, Clone)]
+struct OriginMatcher(Vec<OriginPattern>);
+
+impl OriginMatcher {
+    fn new(origins: &[String]) -> Self {
+        Self(origins.iter().map(|o| OriginPattern::parse(o)).collect())
+    }
+
+    fn allows(&self, origin: &HeaderValue) -> bool {
+        origin
+            .to_str()
+            .is_ok_and(|origin| self.0.iter().any(|p| p.matches(origin)))
+    }
+}
+
+/// Build the appview's CORS layer from the configured origins.
+pub fn layer(origins: &[String]) -> CorsLayer {
+    if origins.iter().any(|o| o == "*") {
+        return CorsLayer::new()
+            .allow_origin(Any)
+            .allow_methods(Any)
+            .allow_headers(Any);
+    }
+
+    let matcher = OriginMatcher::new(origins);
+    let credentials_matcher = matcher.clone();
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::predicate(move |origin, _| {
+            matcher.allows(origin)
+        }))
+        .allow_credentials(AllowCredentials::predicate(move |origin, _| {
+            credentials_matcher.allows(origin)
+        }))
+        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
+        .allow_headers([
+            header::CONTENT_TYPE,
+            header::COOKIE,
+            header::AUTHORIZATION,
+            HeaderName::from_static("idempotency-key"),
+        ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use axum::routing::get;
+    use axum::Router;
+    use tower::ServiceExt;
+
+    fn app(origins: &[&str]) -> Router {
+        let origins: Vec<String> = origins.iter().map(|o| o.to_string()).collect();
+        Router::new()
+            .route("/", get(|| async { StatusCode::OK }))
+            .layer(layer(&origins))
+    }
+
+    async fn cors_headers(app: Router, origin: &str) -> (Option<String>, Option<String>) {
+        let response = app
+            .oneshot(
+                Request::get("/")
+                    .header(header::ORIGIN, origin)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let get = |name| {
+            response
+                .headers()
+                .get(name)
+                .map(|v: &HeaderValue| v.to_str().unwrap().to_string())
+        };
+        (
+            get(header::ACCESS_CONTROL_ALLOW_ORIGIN),
+            get(header::ACCESS_CONTROL_ALLOW_CREDENTIALS),
+        )
+    }
+
+    #[tokio::test]
+    async fn subdomain_wildcard_echoes_origin_with_credentials() {
+        let app = app(&["https://observ.ing", "https://*.observ.ing"]);
+        let (origin, credentials) = cors_headers(app, "https://pr-42.preview.observ.ing").await;
+        assert_eq!(origin.as_deref(), Some("https://pr-42.preview.observ.ing"));
+        assert_eq!(credentials.as_deref(), Some("true"));
+    }
+
+    #[tokio::test]
+    async fn unlisted_origin_gets_no_cors_headers() {
+        let app = app(&["https://observ.ing", "https://*.observ.ing"]);
+        for origin in [
+            "https://evil.example",
+            "https://observ.ing.evil.example",
+            "https://evilobserv.ing",
+            "http://app.observ.ing",
+        ] {
+            let (allow_origin, credentials) = cors_headers(app.clone(), origin).await;
+            assert_eq!(allow_origin, None, "{origin}");
+            assert_eq!(credentials, None, "{origin}");
+        }
+    }
+
+    #[tokio::test]
+    async fn literal_star_allows_any_origin_without_credentials() {
+        let (origin, credentials) = cors_headers(app(&["*"]), "https://anything.example").await;
+        assert_eq!(origin.as_deref(), Some("*"));
+        assert_eq!(credentials, None);
+    }
+
+    #[test]
+    fn wildcard_does_not_match_bare_domain_or_smuggled_hosts() {
+        let pattern = OriginPattern::parse("https://*.observ.ing");
+        assert!(pattern.matches("https://app.observ.ing"));
+        assert!(!pattern.matches("https://observ.ing"));
+        assert!(!pattern.matches("https://.observ.ing"));
+        assert!(!pattern.matches("https://a:b@x.observ.ing"));
+        assert!(OriginPattern::parse("https://observ.ing/").matches("https://observ.ing"));
+    }
+}
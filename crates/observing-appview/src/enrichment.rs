@@ -1,14 +1,26 @@
 use std::collections::{HashMap, HashSet};
+use std::future::Future;
 use std::sync::Arc;
+use std::time::Instant;
 
-use atproto_identity::{IdentityResolver, Profile};
-use observing_db::quality::QualityIssue;
-use observing_db::types::{CommentRow, IdentificationRow, InteractionRow, OccurrenceRow};
+use atproto_identity::{Did, Profile};
+use observing_db::cursor::FeedCursor;
+use observing_db::quality::{QualityGrade, QualityIssue};
+use observing_db::types::{
+    ActivityRow, CommentRow, CommentThreadRow, IdentificationListRow, IdentificationRow,
+    InteractionRow, LeaderboardRow, LikerRow, NearbyObserverRow, OccurrenceRow,
+};
 use serde::Serialize;
 use sqlx::PgPool;
+use tracing::Instrument;
 use ts_rs::TS;
 
-use crate::taxonomy_client::TaxonomyClient;
+use crate::config::BlobUrlStrategy;
+use crate::constants;
+use crate::identity::IdentityProvider;
+use crate::media::signing::MediaSigner;
+use crate::server_timing;
+use crate::taxonomy_client::TaxonomyProvider;
 
 /// Enriched occurrence ready for API response
 #[derive(Debug, Clone, Serialize, TS)]
@@ -33,6 +45,12 @@ pub struct OccurrenceResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[ts(optional)]
     pub location: Option<LocationResponse>,
+    /// Meters from the query point; only set by the nearby feed, which
+    /// orders by it, and by similar-occurrence queries, where the point is
+    /// the reference occurrence.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub distance_meters: Option<f64>,
     /// Darwin Core dwc:organismQuantity — free text (an int/float, or
     /// categorical like "many"/"10-100").
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -54,21 +72,23 @@ pub struct OccurrenceResponse {
     /// Empty means the observation is "verifiable" — pass `?quality=verifiable`
     /// on feed requests to filter to just those rows.
     pub quality_issues: Vec<QualityIssue>,
+    pub quality_grade: QualityGrade,
 }
 
 impl OccurrenceResponse {
     /// Keyset-pagination cursor for the feeds: `created_at` paired with the
     /// unique `uri` tiebreaker, matching the feed `ORDER BY (created_at DESC,
     /// uri DESC)`. `created_at` alone is not unique, so a timestamp-only cursor
-    /// skips or duplicates rows that share a timestamp. The db side decodes
-    /// this in `observing_db::feeds::push_keyset_cursor`.
-    pub fn feed_cursor(&self) -> String {
-        format!("{}|{}", self.created_at, self.uri)
+    /// skips or duplicates rows that share a timestamp. Encoded as an opaque
+    /// [`FeedCursor`] token.
+    pub fn feed_cursor(&self) -> Option<String> {
+        let created_at = chrono::DateTime::parse_from_rfc3339(&self.created_at).ok()?;
+        Some(FeedCursor::new(created_at.to_utc(), self.uri.clone()).encode())
     }
 }
 
-/// A single image attached to an occurrence, with the SPDX license the
-/// uploader chose (when one is recorded on the underlying media record).
+/// A single image attached to an occurrence, with the SPDX license, alt text
+/// and caption the uploader gave it (when recorded on the media record).
 #[derive(Debug, Clone, Serialize, TS)]
 #[serde(rename_all = "camelCase")]
 #[ts(export, export_to = "bindings/")]
@@ -77,6 +97,12 @@ pub struct OccurrenceImage {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[ts(optional)]
     pub license: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub alt: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub caption: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, TS)]
@@ -146,6 +172,16 @@ pub struct EnrichedIdentification {
     #[serde(flatten)]
     pub row: IdentificationRow,
     pub identifier: ProfileSummary,
+    /// Superseded by a later identification from the same identifier. Only
+    /// set by the per-occurrence identification listing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub is_withdrawn: Option<bool>,
+    /// Names the occurrence's current community ID. Only set by the
+    /// per-occurrence identification listing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub agrees_with_community: Option<bool>,
 }
 
 /// Enriched comment with profile info
@@ -158,6 +194,127 @@ pub struct EnrichedComment {
     pub commenter: ProfileSummary,
 }
 
+/// A comment with its replies nested under it, oldest first
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, rename = "CommentThread", export_to = "bindings/")]
+pub struct CommentThreadNode {
+    #[serde(flatten)]
+    pub comment: EnrichedComment,
+    /// The comment was deleted and its body is blank; it's kept because it
+    /// still has replies.
+    pub deleted: bool,
+    pub replies: Vec<CommentThreadNode>,
+}
+
+/// Leaderboard entry with profile info
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnrichedLeaderboardEntry {
+    #[serde(flatten)]
+    pub row: LeaderboardRow,
+    pub observer: ProfileSummary,
+}
+
+/// Nearby observer with profile info
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnrichedNearbyObserver {
+    #[serde(flatten)]
+    pub row: NearbyObserverRow,
+    pub observer: ProfileSummary,
+}
+
+/// Someone who liked an occurrence, with profile info
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnrichedLiker {
+    #[serde(flatten)]
+    pub row: LikerRow,
+    pub liker: ProfileSummary,
+}
+
+impl EnrichedLiker {
+    /// Cursor for the next page of likers, keyed like the feeds on
+    /// `(created_at, uri)` of the like record.
+    pub fn cursor(&self) -> String {
+        FeedCursor::new(self.row.created_at.and_utc(), self.row.uri.clone()).encode()
+    }
+}
+
+/// One event in a user's activity stream, tagged by `type`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum ActivityItem {
+    Identification {
+        #[serde(flatten)]
+        event: ActivityEvent,
+        scientific_name: String,
+    },
+    Comment {
+        #[serde(flatten)]
+        event: ActivityEvent,
+        body: String,
+    },
+    /// A reply to one of the user's comments.
+    Reply {
+        #[serde(flatten)]
+        event: ActivityEvent,
+        body: String,
+        reply_to_uri: String,
+    },
+    Like {
+        #[serde(flatten)]
+        event: ActivityEvent,
+    },
+}
+
+/// Fields every [`ActivityItem`] carries.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityEvent {
+    /// The identification, comment or like record.
+    pub uri: String,
+    /// The occurrence acted on.
+    pub subject_uri: String,
+    pub created_at: String,
+    pub actor: ProfileSummary,
+}
+
+impl ActivityItem {
+    /// `None` for a row whose kind or kind-specific columns don't line up,
+    /// which the query never produces.
+    fn from_row(row: &ActivityRow, actor: ProfileSummary) -> Option<Self> {
+        let event = ActivityEvent {
+            uri: row.uri.clone(),
+            subject_uri: row.subject_uri.clone(),
+            created_at: row.created_at.to_rfc3339(),
+            actor,
+        };
+        Some(match row.kind.as_str() {
+            "identification" => Self::Identification {
+                event,
+                scientific_name: row.scientific_name.clone()?,
+            },
+            "comment" => Self::Comment {
+                event,
+                body: row.body.clone()?,
+            },
+            "reply" => Self::Reply {
+                event,
+                body: row.body.clone()?,
+                reply_to_uri: row.reply_to_uri.clone()?,
+            },
+            "like" => Self::Like { event },
+            _ => return None,
+        })
+    }
+}
+
 /// Enriched interaction with profile info
 #[derive(Debug, Clone, Serialize, TS)]
 #[serde(rename_all = "camelCase")]
@@ -186,21 +343,183 @@ fn profile_summary(did: &str, profiles: &HashMap<String, Arc<Profile>>) -> Profi
     }
 }
 
-fn extract_images(row: &OccurrenceRow) -> Vec<OccurrenceImage> {
+/// The occurrence's images the viewer may see, linked per `strategy`. `pds`
+/// is the author's PDS endpoint, only consulted for
+/// [`BlobUrlStrategy::PdsDirect`].
+fn extract_images(
+    row: &OccurrenceRow,
+    strategy: BlobUrlStrategy,
+    pds: Option<&str>,
+    access: &MediaAccess,
+) -> Vec<OccurrenceImage> {
     row.blob_entries()
         .iter()
-        .map(|blob| OccurrenceImage {
-            url: format!("/media/blob/{}/{}", row.did, blob.image.ref_.cid()),
-            license: blob.license.clone(),
+        .filter_map(|blob| {
+            Some(OccurrenceImage {
+                url: access.link(strategy, &row.did, blob.image.ref_.cid(), pds)?,
+                license: blob.license.clone(),
+                // Older records were written with an empty `alt`.
+                alt: blob.alt.clone().filter(|a| !a.is_empty()),
+                caption: blob.caption.clone(),
+            })
         })
         .collect()
 }
 
-/// Enrich a batch of occurrences with profiles, likes, community IDs, and taxonomy
+/// How image URLs are built: the configured strategy, plus the signer for
+/// private blobs when one is configured.
+#[derive(Clone, Copy)]
+pub struct BlobLinks<'a> {
+    pub strategy: BlobUrlStrategy,
+    pub signer: Option<&'a MediaSigner>,
+}
+
+/// Which blobs of a batch are private, and how to link them for `viewer`.
+/// Without a signer nothing can be marked private, so every blob follows the
+/// strategy.
+#[derive(Default)]
+pub struct MediaAccess<'a> {
+    signer: Option<&'a MediaSigner>,
+    viewer: Option<&'a str>,
+    /// Private `(did, cid)` pairs; `None` when the lookup failed.
+    private: Option<HashSet<(String, String)>>,
+}
+
+impl<'a> MediaAccess<'a> {
+    /// Look up which of `blobs` (`(did, cid)` pairs) are private.
+    pub async fn load(
+        pool: &PgPool,
+        signer: Option<&'a MediaSigner>,
+        viewer: Option<&'a str>,
+        blobs: &[(String, String)],
+    ) -> Self {
+        if signer.is_none() || blobs.is_empty() {
+            return Self::default();
+        }
+        let private = match timed(
+            "privacy",
+            blobs.len(),
+            observing_db::private_data::private_among(pool, blobs),
+        )
+        .await
+        {
+            Ok(private) => Some(private),
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to look up private media");
+                None
+            }
+        };
+        Self {
+            signer,
+            viewer,
+            private,
+        }
+    }
+
+    /// The URL to show for one blob, or `None` when the viewer may not see
+    /// it. Private blobs never leave the proxy: their owner gets a signed
+    /// link, everyone else nothing.
+    pub fn link(
+        &self,
+        strategy: BlobUrlStrategy,
+        did: &str,
+        cid: &str,
+        pds: Option<&str>,
+    ) -> Option<String> {
+        let Some(signer) = self.signer else {
+            return Some(blob_url(strategy, did, cid, pds));
+        };
+        match &self.private {
+            Some(private) if !private.contains(&(did.to_string(), cid.to_string())) => {
+                Some(blob_url(strategy, did, cid, pds))
+            }
+            _ if self.viewer == Some(did) => {
+                let expires_at =
+                    chrono::Utc::now().timestamp() + constants::PRIVATE_MEDIA_URL_TTL_SECS;
+                Some(format!(
+                    "{}?exp={expires_at}&sig={}",
+                    proxy_blob_url(did, cid),
+                    signer.sign(did, cid, expires_at)
+                ))
+            }
+            // Privacy unknown: the proxy checks again and fails closed.
+            None => Some(proxy_blob_url(did, cid)),
+            Some(_) => None,
+        }
+    }
+}
+
+/// Where one public blob is served from. Without a PDS endpoint, `PdsDirect`
+/// falls back to the proxy.
+pub fn blob_url(strategy: BlobUrlStrategy, did: &str, cid: &str, pds: Option<&str>) -> String {
+    match (strategy, pds) {
+        (BlobUrlStrategy::PdsDirect, Some(pds)) => format!(
+            "{}/xrpc/com.atproto.sync.getBlob?did={did}&cid={cid}",
+            pds.trim_end_matches('/')
+        ),
+        (BlobUrlStrategy::Cdn, _) => format!(
+            "{}/img/feed_fullsize/plain/{did}/{cid}@jpeg",
+            constants::BSKY_CDN_URL
+        ),
+        _ => proxy_blob_url(did, cid),
+    }
+}
+
+fn proxy_blob_url(did: &str, cid: &str) -> String {
+    format!("/media/blob/{did}/{cid}")
+}
+
+/// PDS endpoints for the authors in `dids`, when `strategy` links blobs
+/// there; empty otherwise. DID documents come from the resolver's cache.
+async fn pds_endpoints(
+    resolver: &dyn IdentityProvider,
+    dids: &[String],
+    strategy: BlobUrlStrategy,
+) -> HashMap<String, String> {
+    if strategy != BlobUrlStrategy::PdsDirect {
+        return HashMap::new();
+    }
+    let unique: HashSet<&String> = dids.iter().collect();
+    let lookups = unique.into_iter().map(|did| async move {
+        let parsed = Did::new_owned(did).ok()?;
+        let pds = resolver.resolve(&parsed).await?.pds_endpoint?;
+        Some((did.clone(), pds))
+    });
+    timed("pds", dids.len(), futures::future::join_all(lookups))
+        .await
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+/// Run one enrichment stage inside an `enrich_stage` span, then emit a debug
+/// event with its wall time. Stages that run concurrently each report their
+/// own duration, so a slow feed can be pinned on a stage; the `elapsed_ms`
+/// field is what a log-based latency distribution would be built from. The
+/// same duration goes into the response's `Server-Timing` as `enrich.{stage}`.
+async fn timed<T>(stage: &'static str, rows: usize, work: impl Future<Output = T>) -> T {
+    let started = Instant::now();
+    let out = work
+        .instrument(tracing::debug_span!("enrich_stage", stage))
+        .await;
+    let elapsed = started.elapsed();
+    tracing::debug!(
+        stage,
+        rows,
+        elapsed_ms = elapsed.as_secs_f64() * 1000.0,
+        "Enrichment stage finished"
+    );
+    server_timing::record(format!("enrich.{stage}"), elapsed);
+    out
+}
+
+/// Enrich a batch of occurrences with profiles, likes, community IDs, and
+/// taxonomy, linking images per `blob_links`.
 pub async fn enrich_occurrences(
     pool: &PgPool,
-    resolver: &IdentityResolver,
-    taxonomy: &TaxonomyClient,
+    resolver: &dyn IdentityProvider,
+    taxonomy: &dyn TaxonomyProvider,
+    blob_links: BlobLinks<'_>,
     rows: &[OccurrenceRow],
     viewer_did: Option<&str>,
 ) -> Vec<OccurrenceResponse> {
@@ -208,45 +527,66 @@ pub async fn enrich_occurrences(
         return Vec::new();
     }
 
+    let started = Instant::now();
     let uris: Vec<String> = rows.iter().map(|r| r.uri.clone()).collect();
+    let n = rows.len();
 
     // Stage 1: Batch-fetch all DB data concurrently
-    let (like_counts, viewer_likes, community_ids, identifications_by_uri) = tokio::join!(
-        async {
-            observing_db::likes::get_counts_for_occurrences(pool, &uris)
+    let ((like_counts, viewer_likes), consensus_by_uri, identifications_by_uri) = tokio::join!(
+        timed("likes", n, async {
+            tokio::join!(
+                async {
+                    observing_db::likes::get_counts_for_occurrences(pool, &uris)
+                        .await
+                        .unwrap_or_default()
+                },
+                async {
+                    if let Some(did) = viewer_did {
+                        observing_db::likes::get_user_like_statuses(pool, &uris, did)
+                            .await
+                            .unwrap_or_default()
+                    } else {
+                        HashSet::new()
+                    }
+                },
+            )
+        }),
+        timed("community_ids", n, async {
+            observing_db::identifications::get_consensus_for_occurrences(pool, &uris)
                 .await
                 .unwrap_or_default()
-        },
-        async {
-            if let Some(did) = viewer_did {
-                observing_db::likes::get_user_like_statuses(pool, &uris, did)
-                    .await
-                    .unwrap_or_default()
-            } else {
-                HashSet::new()
-            }
-        },
-        async {
-            observing_db::identifications::get_community_ids_for_occurrences(pool, &uris)
-                .await
-                .unwrap_or_default()
-        },
-        async {
+        }),
+        timed("identifications", n, async {
             observing_db::identifications::get_for_subjects_batch(pool, &uris)
                 .await
                 .unwrap_or_default()
-        },
+        }),
     );
 
-    // Stage 2: Batch profile resolution
+    // Stage 2: Batch profile resolution, plus PDS endpoints when images
+    // link there and which images are private
     let dids_vec: Vec<String> = rows.iter().map(|r| r.did.clone()).collect();
-    let profiles = resolver.get_profiles(&dids_vec).await;
+    let blobs: Vec<(String, String)> = rows
+        .iter()
+        .flat_map(|row| {
+            row.blob_entries()
+                .into_iter()
+                .map(|blob| (row.did.clone(), blob.image.ref_.cid().to_string()))
+        })
+        .collect();
+    let (profiles, pds_by_did, access) = tokio::join!(
+        timed("profiles", n, resolver.get_profiles(&dids_vec)),
+        pds_endpoints(resolver, &dids_vec, blob_links.strategy),
+        MediaAccess::load(pool, blob_links.signer, viewer_did, &blobs),
+    );
 
     // Stage 3: Resolve taxonomy for all occurrences (HTTP fallbacks run in parallel)
     let taxonomy_futures: Vec<_> = rows
         .iter()
         .map(|row| {
-            let community_id = community_ids.get(&row.uri).cloned();
+            let community_id = consensus_by_uri
+                .get(&row.uri)
+                .and_then(|c| c.scientific_name.clone());
             let identifications = identifications_by_uri
                 .get(&row.uri)
                 .cloned()
@@ -264,7 +604,7 @@ pub async fn enrich_occurrences(
         })
         .collect();
     let taxonomies: Vec<Option<EffectiveTaxonomy>> =
-        futures::future::join_all(taxonomy_futures).await;
+        timed("taxonomy", n, futures::future::join_all(taxonomy_futures)).await;
 
     // Stage 4: Build responses (pure data assembly, no I/O)
     let mut results = Vec::with_capacity(rows.len());
@@ -275,12 +615,19 @@ pub async fn enrich_occurrences(
             .map(|ids| ids.len() as i64)
             .unwrap_or(0);
 
-        let community_id = community_ids.get(&row.uri).cloned();
+        let consensus = consensus_by_uri.get(&row.uri);
+        let community_id = consensus.and_then(|c| c.scientific_name.clone());
         let effective_taxonomy = taxonomies[i].clone();
 
-        let images = extract_images(row);
+        let images = extract_images(
+            row,
+            blob_links.strategy,
+            pds_by_did.get(&row.did).map(String::as_str),
+            &access,
+        );
 
         let quality_issues = observing_db::quality::compute_issues(row, community_id.is_some());
+        let quality_grade = observing_db::quality::compute_grade(row, consensus);
 
         results.push(OccurrenceResponse {
             uri: row.uri.clone(),
@@ -302,6 +649,7 @@ pub async fn enrich_occurrences(
                 }),
                 _ => None,
             },
+            distance_meters: row.distance_meters,
             organism_quantity: row.organism_quantity.clone(),
             organism_quantity_type: row.organism_quantity_type.clone(),
             images,
@@ -309,16 +657,18 @@ pub async fn enrich_occurrences(
             like_count: Some(*like_counts.get(&row.uri).unwrap_or(&0)),
             viewer_has_liked: viewer_did.map(|_| viewer_likes.contains(&row.uri)),
             quality_issues,
+            quality_grade,
         });
     }
 
+    server_timing::record("enrich", started.elapsed());
     results
 }
 
 /// Resolve effective taxonomy using pre-fetched data.
 /// Only makes external HTTP calls (GBIF) when the DB doesn't have taxonomy info.
 async fn resolve_effective_taxonomy(
-    taxonomy: &TaxonomyClient,
+    taxonomy: &dyn TaxonomyProvider,
     community_id: Option<&str>,
     identifications: &[IdentificationRow],
     occurrence_kingdom: Option<&str>,
@@ -388,7 +738,7 @@ async fn resolve_effective_taxonomy(
 
 /// Generic helper: resolve profiles for a slice of rows and map each into an enriched type.
 async fn enrich_rows<R, E>(
-    resolver: &IdentityResolver,
+    resolver: &dyn IdentityProvider,
     rows: &[R],
     get_did: impl Fn(&R) -> &str,
     build: impl Fn(&R, ProfileSummary) -> E,
@@ -402,7 +752,7 @@ async fn enrich_rows<R, E>(
 
 /// Enrich identifications with profile info
 pub async fn enrich_identifications(
-    resolver: &IdentityResolver,
+    resolver: &dyn IdentityProvider,
     rows: &[IdentificationRow],
 ) -> Vec<EnrichedIdentification> {
     enrich_rows(
@@ -412,6 +762,27 @@ pub async fn enrich_identifications(
         |row, profile| EnrichedIdentification {
             identifier: profile,
             row: row.clone(),
+            is_withdrawn: None,
+            agrees_with_community: None,
+        },
+    )
+    .await
+}
+
+/// Enrich a flagged identification listing with profile info
+pub async fn enrich_identification_list(
+    resolver: &dyn IdentityProvider,
+    rows: &[IdentificationListRow],
+) -> Vec<EnrichedIdentification> {
+    enrich_rows(
+        resolver,
+        rows,
+        |r| &r.row.did,
+        |listed, profile| EnrichedIdentification {
+            identifier: profile,
+            row: listed.row.clone(),
+            is_withdrawn: Some(listed.is_withdrawn),
+            agrees_with_community: Some(listed.agrees_with_community),
         },
     )
     .await
@@ -419,7 +790,7 @@ pub async fn enrich_identifications(
 
 /// Enrich comments with profile info
 pub async fn enrich_comments(
-    resolver: &IdentityResolver,
+    resolver: &dyn IdentityProvider,
     rows: &[CommentRow],
 ) -> Vec<EnrichedComment> {
     enrich_rows(
@@ -434,9 +805,101 @@ pub async fn enrich_comments(
     .await
 }
 
+/// Enrich an occurrence's comments with profile info and nest replies
+/// under their parents (see [`assemble_comment_thread`])
+pub async fn enrich_comment_thread(
+    resolver: &dyn IdentityProvider,
+    rows: &[CommentThreadRow],
+) -> Vec<CommentThreadNode> {
+    let entries = enrich_rows(
+        resolver,
+        rows,
+        |r| &r.comment.did,
+        |row, profile| ThreadEntry {
+            comment: EnrichedComment {
+                commenter: profile,
+                row: row.comment.clone(),
+            },
+            parent_uri: row.parent_uri.clone(),
+            deleted: row.deleted,
+        },
+    )
+    .await;
+    assemble_comment_thread(entries)
+}
+
+/// One comment on its way into [`assemble_comment_thread`].
+struct ThreadEntry {
+    comment: EnrichedComment,
+    parent_uri: Option<String>,
+    deleted: bool,
+}
+
+/// Nest comments, given oldest first, into a reply tree. Comments without
+/// a parent in the list — top-level ones and orphaned replies — are roots.
+/// Order is kept at every level. Deleted comments stay only while they
+/// have replies under them.
+fn assemble_comment_thread(entries: Vec<ThreadEntry>) -> Vec<CommentThreadNode> {
+    let uris: HashSet<String> = entries.iter().map(|e| e.comment.row.uri.clone()).collect();
+    let mut replies: HashMap<String, Vec<ThreadEntry>> = HashMap::new();
+    let mut roots = Vec::new();
+    for entry in entries {
+        let parent = entry
+            .parent_uri
+            .clone()
+            .filter(|p| uris.contains(p) && *p != entry.comment.row.uri);
+        match parent {
+            Some(parent) => replies.entry(parent).or_default().push(entry),
+            None => roots.push(entry),
+        }
+    }
+
+    fn nest(
+        entry: ThreadEntry,
+        replies: &mut HashMap<String, Vec<ThreadEntry>>,
+    ) -> Option<CommentThreadNode> {
+        let children = replies.remove(&entry.comment.row.uri).unwrap_or_default();
+        let children: Vec<CommentThreadNode> = children
+            .into_iter()
+            .filter_map(|c| nest(c, replies))
+            .collect();
+        if entry.deleted && children.is_empty() {
+            return None;
+        }
+        Some(CommentThreadNode {
+            comment: entry.comment,
+            deleted: entry.deleted,
+            replies: children,
+        })
+    }
+
+    let mut thread: Vec<CommentThreadNode> = roots
+        .into_iter()
+        .filter_map(|e| nest(e, &mut replies))
+        .collect();
+
+    // Whatever is left replies in a cycle no root leads into (only possible
+    // with edited records). Show it flat rather than drop it.
+    let mut unreachable: Vec<ThreadEntry> = replies
+        .into_values()
+        .flatten()
+        .filter(|e| !e.deleted)
+        .collect();
+    unreachable.sort_by(|a, b| {
+        (a.comment.row.created_at, &a.comment.row.uri)
+            .cmp(&(b.comment.row.created_at, &b.comment.row.uri))
+    });
+    thread.extend(unreachable.into_iter().map(|e| CommentThreadNode {
+        comment: e.comment,
+        deleted: false,
+        replies: Vec::new(),
+    }));
+    thread
+}
+
 /// Enrich interactions with profile info
 pub async fn enrich_interactions(
-    resolver: &IdentityResolver,
+    resolver: &dyn IdentityProvider,
     rows: &[InteractionRow],
 ) -> Vec<EnrichedInteraction> {
     enrich_rows(
@@ -451,10 +914,73 @@ pub async fn enrich_interactions(
     .await
 }
 
+/// Enrich leaderboard rows with profile info
+pub async fn enrich_leaderboard(
+    resolver: &dyn IdentityProvider,
+    rows: &[LeaderboardRow],
+) -> Vec<EnrichedLeaderboardEntry> {
+    enrich_rows(
+        resolver,
+        rows,
+        |r| &r.did,
+        |row, profile| EnrichedLeaderboardEntry {
+            observer: profile,
+            row: row.clone(),
+        },
+    )
+    .await
+}
+
+/// Enrich likers with profile info
+pub async fn enrich_likers(
+    resolver: &dyn IdentityProvider,
+    rows: &[LikerRow],
+) -> Vec<EnrichedLiker> {
+    enrich_rows(
+        resolver,
+        rows,
+        |r| &r.did,
+        |row, profile| EnrichedLiker {
+            liker: profile,
+            row: row.clone(),
+        },
+    )
+    .await
+}
+
+/// Enrich activity rows with the acting user's profile
+pub async fn enrich_activity(
+    resolver: &dyn IdentityProvider,
+    rows: &[ActivityRow],
+) -> Vec<ActivityItem> {
+    enrich_rows(resolver, rows, |r| &r.did, ActivityItem::from_row)
+        .await
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+/// Enrich nearby observer rows with the observer's profile
+pub async fn enrich_nearby_observers(
+    resolver: &dyn IdentityProvider,
+    rows: &[NearbyObserverRow],
+) -> Vec<EnrichedNearbyObserver> {
+    enrich_rows(
+        resolver,
+        rows,
+        |r| &r.did,
+        |row, profile| EnrichedNearbyObserver {
+            observer: profile,
+            row: row.clone(),
+        },
+    )
+    .await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::Utc;
+    use chrono::{TimeZone, Utc};
     use observing_db::types::{BlobEntry, BlobImage, BlobRef};
 
     fn make_row(media: Option<serde_json::Value>) -> OccurrenceRow {
@@ -508,6 +1034,7 @@ mod tests {
             },
             alt: None,
             license: license.map(str::to_string),
+            caption: None,
         }
     }
 
@@ -515,22 +1042,102 @@ mod tests {
         serde_json::to_value(entries).unwrap()
     }
 
+    fn activity_row(kind: &str) -> ActivityRow {
+        ActivityRow {
+            kind: kind.into(),
+            uri: "at://did:plc:other/bio.lexicons.temp.v0-1.comment/1".into(),
+            did: "did:plc:other".into(),
+            subject_uri: "at://did:plc:me/bio.lexicons.temp.v0-1.occurrence/1".into(),
+            created_at: Utc.with_ymd_and_hms(2026, 6, 2, 21, 13, 49).unwrap(),
+            scientific_name: None,
+            body: None,
+            reply_to_uri: None,
+        }
+    }
+
+    #[test]
+    fn activity_items_are_tagged_by_type() {
+        let actor = || profile_summary("did:plc:other", &HashMap::new());
+        let identification = ActivityRow {
+            scientific_name: Some("Quercus alba".into()),
+            ..activity_row("identification")
+        };
+        let reply = ActivityRow {
+            body: Some("Agreed!".into()),
+            reply_to_uri: Some("at://did:plc:me/bio.lexicons.temp.v0-1.comment/0".into()),
+            ..activity_row("reply")
+        };
+        let items: Vec<serde_json::Value> = [identification, reply, activity_row("like")]
+            .iter()
+            .map(|row| serde_json::to_value(ActivityItem::from_row(row, actor()).unwrap()).unwrap())
+            .collect();
+
+        assert_eq!(items[0]["type"], "identification");
+        assert_eq!(items[0]["scientificName"], "Quercus alba");
+        assert_eq!(items[0]["actor"]["did"], "did:plc:other");
+        assert_eq!(items[1]["type"], "reply");
+        assert_eq!(items[1]["body"], "Agreed!");
+        assert_eq!(
+            items[1]["replyToUri"],
+            "at://did:plc:me/bio.lexicons.temp.v0-1.comment/0"
+        );
+        assert_eq!(items[2]["type"], "like");
+        assert_eq!(
+            items[2]["subjectUri"],
+            "at://did:plc:me/bio.lexicons.temp.v0-1.occurrence/1"
+        );
+        assert_eq!(items[2]["createdAt"], "2026-06-02T21:13:49+00:00");
+    }
+
+    #[test]
+    fn malformed_activity_rows_are_dropped() {
+        let actor = || profile_summary("did:plc:other", &HashMap::new());
+        // A comment without its body, and a kind this build doesn't know.
+        assert!(ActivityItem::from_row(&activity_row("comment"), actor()).is_none());
+        assert!(ActivityItem::from_row(&activity_row("mention"), actor()).is_none());
+    }
+
+    #[test]
+    fn liker_cursor_resumes_after_that_like() {
+        let created_at = chrono::NaiveDate::from_ymd_opt(2026, 6, 2)
+            .unwrap()
+            .and_hms_micro_opt(21, 13, 49, 123_456)
+            .unwrap();
+        let liker = EnrichedLiker {
+            row: LikerRow {
+                uri: "at://did:plc:abc/app.bsky.feed.like/3k2".into(),
+                did: "did:plc:abc".into(),
+                created_at,
+            },
+            liker: profile_summary("did:plc:abc", &HashMap::new()),
+        };
+        let cursor = FeedCursor::decode(&liker.cursor()).unwrap();
+        assert_eq!(cursor.created_at.naive_utc(), created_at);
+        assert_eq!(cursor.uri, "at://did:plc:abc/app.bsky.feed.like/3k2");
+    }
+
     #[test]
     fn test_extract_images_no_media() {
         let row = make_row(None);
-        assert!(extract_images(&row).is_empty());
+        assert!(
+            extract_images(&row, BlobUrlStrategy::Proxy, None, &MediaAccess::default()).is_empty()
+        );
     }
 
     #[test]
     fn test_extract_images_non_array() {
         let row = make_row(Some(serde_json::json!("not an array")));
-        assert!(extract_images(&row).is_empty());
+        assert!(
+            extract_images(&row, BlobUrlStrategy::Proxy, None, &MediaAccess::default()).is_empty()
+        );
     }
 
     #[test]
     fn test_extract_images_empty_array() {
         let row = make_row(Some(blobs_to_json(vec![])));
-        assert!(extract_images(&row).is_empty());
+        assert!(
+            extract_images(&row, BlobUrlStrategy::Proxy, None, &MediaAccess::default()).is_empty()
+        );
     }
 
     #[test]
@@ -540,7 +1147,7 @@ mod tests {
             "image/jpeg",
             "link",
         )])));
-        let images = extract_images(&row);
+        let images = extract_images(&row, BlobUrlStrategy::Proxy, None, &MediaAccess::default());
         assert_eq!(images.len(), 1);
         assert_eq!(images[0].url, "/media/blob/did:plc:test/bafkreiabc123");
         assert!(images[0].license.is_none());
@@ -553,7 +1160,7 @@ mod tests {
             "image/jpeg",
             "bare",
         )])));
-        let images = extract_images(&row);
+        let images = extract_images(&row, BlobUrlStrategy::Proxy, None, &MediaAccess::default());
         assert_eq!(images.len(), 1);
         assert_eq!(images[0].url, "/media/blob/did:plc:test/bafkreixyz789");
     }
@@ -564,7 +1171,7 @@ mod tests {
             blob_entry("cid1", "image/jpeg", "link"),
             blob_entry("cid2", "image/png", "link"),
         ])));
-        let images = extract_images(&row);
+        let images = extract_images(&row, BlobUrlStrategy::Proxy, None, &MediaAccess::default());
         assert_eq!(images.len(), 2);
         let urls: Vec<&str> = images.iter().map(|i| i.url.as_str()).collect();
         assert!(urls.contains(&"/media/blob/did:plc:test/cid1"));
@@ -579,18 +1186,159 @@ mod tests {
             "link",
             Some("CC-BY-4.0"),
         )])));
-        let images = extract_images(&row);
+        let images = extract_images(&row, BlobUrlStrategy::Proxy, None, &MediaAccess::default());
         assert_eq!(images.len(), 1);
         assert_eq!(images[0].license.as_deref(), Some("CC-BY-4.0"));
     }
 
+    #[test]
+    fn test_extract_images_round_trips_alt_and_caption() {
+        let mut entry = blob_entry("cidalt", "image/jpeg", "link");
+        entry.alt = Some("Seed head against the sky".into());
+        entry.caption = Some("Second visit".into());
+        let row = make_row(Some(blobs_to_json(vec![entry])));
+        let images = extract_images(&row, BlobUrlStrategy::Proxy, None, &MediaAccess::default());
+        assert_eq!(images[0].alt.as_deref(), Some("Seed head against the sky"));
+        assert_eq!(images[0].caption.as_deref(), Some("Second visit"));
+
+        let json = serde_json::to_value(&images[0]).unwrap();
+        assert_eq!(json["alt"], "Seed head against the sky");
+        assert_eq!(json["caption"], "Second visit");
+    }
+
+    #[test]
+    fn test_extract_images_without_alt() {
+        // Stored before alt text was recorded: empty or missing `alt`, no
+        // `caption` key at all.
+        let row = make_row(Some(serde_json::json!([
+            {"image": {"ref": {"$link": "cid1"}, "mimeType": "image/jpeg"}, "alt": ""},
+            {"image": {"ref": {"$link": "cid2"}, "mimeType": "image/jpeg"}},
+        ])));
+        let images = extract_images(&row, BlobUrlStrategy::Proxy, None, &MediaAccess::default());
+        assert_eq!(images.len(), 2);
+        for image in &images {
+            assert!(image.alt.is_none());
+            assert!(image.caption.is_none());
+            let json = serde_json::to_value(image).unwrap();
+            assert!(json.get("alt").is_none() && json.get("caption").is_none());
+        }
+    }
+
+    #[test]
+    fn test_extract_images_per_strategy() {
+        let row = make_row(Some(blobs_to_json(vec![blob_entry(
+            "bafkreiabc123",
+            "image/jpeg",
+            "link",
+        )])));
+        let url = |strategy, pds| {
+            extract_images(&row, strategy, pds, &MediaAccess::default())[0]
+                .url
+                .clone()
+        };
+
+        assert_eq!(
+            url(BlobUrlStrategy::Proxy, Some("https://pds.example")),
+            "/media/blob/did:plc:test/bafkreiabc123"
+        );
+        assert_eq!(
+            url(BlobUrlStrategy::PdsDirect, Some("https://pds.example/")),
+            "https://pds.example/xrpc/com.atproto.sync.getBlob?did=did:plc:test&cid=bafkreiabc123"
+        );
+        assert_eq!(
+            url(BlobUrlStrategy::Cdn, None),
+            "https://cdn.bsky.app/img/feed_fullsize/plain/did:plc:test/bafkreiabc123@jpeg"
+        );
+    }
+
+    #[test]
+    fn test_extract_images_links_private_blobs_through_the_signed_proxy() {
+        let row = make_row(Some(blobs_to_json(vec![
+            blob_entry("public", "image/jpeg", "link"),
+            blob_entry("private", "image/jpeg", "link"),
+        ])));
+        let signer = MediaSigner::new("secret");
+        let access = |viewer, private: Option<HashSet<(String, String)>>| MediaAccess {
+            signer: Some(&signer),
+            viewer,
+            private,
+        };
+        let private = HashSet::from([("did:plc:test".to_string(), "private".to_string())]);
+
+        // The owner sees both, the private one via a signed proxy link even
+        // though public images go to the CDN.
+        let owner = access(Some("did:plc:test"), Some(private.clone()));
+        let images = extract_images(&row, BlobUrlStrategy::Cdn, None, &owner);
+        assert_eq!(images.len(), 2);
+        assert!(images[0].url.starts_with(constants::BSKY_CDN_URL));
+        let (path, query) = images[1].url.split_once('?').unwrap();
+        assert_eq!(path, "/media/blob/did:plc:test/private");
+        let params: HashMap<&str, &str> = query
+            .split('&')
+            .filter_map(|kv| kv.split_once('='))
+            .collect();
+        let exp: i64 = params["exp"].parse().unwrap();
+        let now = chrono::Utc::now().timestamp();
+        assert_eq!(
+            signer.verify(
+                "did:plc:test",
+                "private",
+                Some(exp),
+                Some(params["sig"]),
+                now
+            ),
+            Ok(())
+        );
+
+        // Anyone else only sees the public one.
+        for viewer in [None, Some("did:plc:other")] {
+            let images = extract_images(
+                &row,
+                BlobUrlStrategy::Cdn,
+                None,
+                &access(viewer, Some(private.clone())),
+            );
+            assert_eq!(images.len(), 1);
+            assert!(images[0].url.ends_with("/public@jpeg"));
+        }
+
+        // A failed lookup links both through the proxy, which checks again.
+        let images = extract_images(&row, BlobUrlStrategy::Cdn, None, &access(None, None));
+        let urls: Vec<&str> = images.iter().map(|i| i.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            [
+                "/media/blob/did:plc:test/public",
+                "/media/blob/did:plc:test/private"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_images_pds_direct_without_endpoint_uses_proxy() {
+        let row = make_row(Some(blobs_to_json(vec![blob_entry(
+            "cid1",
+            "image/png",
+            "link",
+        )])));
+        let images = extract_images(
+            &row,
+            BlobUrlStrategy::PdsDirect,
+            None,
+            &MediaAccess::default(),
+        );
+        assert_eq!(images[0].url, "/media/blob/did:plc:test/cid1");
+    }
+
     #[test]
     fn test_extract_images_missing_image_field() {
         // Invalid blob entries that can't deserialize just get skipped by blob_entries()
         let row = make_row(Some(serde_json::json!([
             {"notImage": {"ref": {"$link": "cid1"}}}
         ])));
-        assert!(extract_images(&row).is_empty());
+        assert!(
+            extract_images(&row, BlobUrlStrategy::Proxy, None, &MediaAccess::default()).is_empty()
+        );
     }
 
     #[test]
@@ -598,7 +1346,9 @@ mod tests {
         let row = make_row(Some(serde_json::json!([
             {"image": {"mimeType": "image/jpeg"}}
         ])));
-        assert!(extract_images(&row).is_empty());
+        assert!(
+            extract_images(&row, BlobUrlStrategy::Proxy, None, &MediaAccess::default()).is_empty()
+        );
     }
 
     #[test]
@@ -617,6 +1367,20 @@ mod tests {
         assert_eq!(entry.image.ref_.cid(), "bafkreixyz789");
     }
 
+    #[test]
+    fn test_blob_entry_caption_only_serialized_when_set() {
+        let mut entry = blob_entry("cid", "image/jpeg", "link");
+        assert!(serde_json::to_value(&entry)
+            .unwrap()
+            .get("caption")
+            .is_none());
+
+        entry.caption = Some("Close-up".into());
+        let json = serde_json::to_value(&entry).unwrap();
+        let back: BlobEntry = serde_json::from_value(json).unwrap();
+        assert_eq!(back.caption.as_deref(), Some("Close-up"));
+    }
+
     #[test]
     fn test_profile_summary_found() {
         let mut profiles = HashMap::new();
@@ -645,4 +1409,319 @@ mod tests {
         assert!(summary.display_name.is_none());
         assert!(summary.avatar.is_none());
     }
+
+    #[tokio::test]
+    async fn effective_taxonomy_fast_fails_while_gbif_is_down() {
+        use crate::taxonomy::breaker::CircuitBreaker;
+        use crate::taxonomy::GbifClient;
+        use crate::taxonomy_client::TaxonomyClient;
+        use std::time::Duration;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/species/match"))
+            .respond_with(ResponseTemplate::new(503))
+            // Two failures open the breaker; later lookups never reach GBIF.
+            .expect(2)
+            .mount(&server)
+            .await;
+        let taxonomy = TaxonomyClient::with_parts(
+            GbifClient::with_base_url(&server.uri()),
+            CircuitBreaker::new(2, Duration::from_secs(60)),
+        );
+
+        for name in [
+            "Quercus alba",
+            "Quercus rubra",
+            "Acer rubrum",
+            "Pinus nigra",
+        ] {
+            let effective = resolve_effective_taxonomy(&taxonomy, Some(name), &[], None)
+                .await
+                .expect("a community ID always yields some taxonomy");
+            assert_eq!(effective.scientific_name, name);
+            assert_eq!(effective.vernacular_name, None);
+            assert_eq!(effective.kingdom, None);
+        }
+    }
+
+    /// Collects the `stage` of every "Enrichment stage finished" event.
+    struct StageEvents(Arc<std::sync::Mutex<Vec<String>>>);
+
+    impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for StageEvents {
+        fn on_event(
+            &self,
+            event: &tracing::Event<'_>,
+            _ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            #[derive(Default)]
+            struct Fields {
+                stage: Option<String>,
+                elapsed: bool,
+            }
+            impl tracing::field::Visit for Fields {
+                fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+                    if field.name() == "stage" {
+                        self.stage = Some(value.to_string());
+                    }
+                }
+                fn record_f64(&mut self, field: &tracing::field::Field, _value: f64) {
+                    self.elapsed |= field.name() == "elapsed_ms";
+                }
+                fn record_debug(
+                    &mut self,
+                    _field: &tracing::field::Field,
+                    _value: &dyn std::fmt::Debug,
+                ) {
+                }
+            }
+            let mut fields = Fields::default();
+            event.record(&mut fields);
+            if let (Some(stage), true) = (fields.stage, fields.elapsed) {
+                self.0.lock().unwrap().push(stage);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn each_enrichment_stage_emits_a_timing_event() {
+        use crate::taxonomy::breaker::CircuitBreaker;
+        use crate::taxonomy::GbifClient;
+        use crate::taxonomy_client::TaxonomyClient;
+        use atproto_identity::IdentityResolver;
+        use std::time::Duration;
+        use tracing_subscriber::layer::SubscriberExt;
+
+        // Nothing answers: DB reads fail fast and fall back to empty, the
+        // profile and GBIF lookups get 404s. Only the timings matter here.
+        let server = wiremock::MockServer::start().await;
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .acquire_timeout(Duration::from_millis(100))
+            .connect_lazy("postgres://127.0.0.1:1/observing")
+            .unwrap();
+        let resolver = IdentityResolver::with_service_url(&server.uri());
+        let taxonomy = TaxonomyClient::with_parts(
+            GbifClient::with_base_url(&server.uri()),
+            CircuitBreaker::new(2, Duration::from_secs(60)),
+        );
+
+        let stages = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let subscriber = tracing_subscriber::registry().with(StageEvents(stages.clone()));
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let enriched = enrich_occurrences(
+            &pool,
+            &resolver,
+            &taxonomy,
+            BlobLinks {
+                strategy: BlobUrlStrategy::Proxy,
+                signer: None,
+            },
+            &[make_row(None)],
+            Some("did:plc:viewer"),
+        )
+        .await;
+        assert_eq!(enriched.len(), 1);
+
+        let mut stages = stages.lock().unwrap().clone();
+        stages.sort();
+        assert_eq!(
+            stages,
+            [
+                "community_ids",
+                "identifications",
+                "likes",
+                "profiles",
+                "taxonomy"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn occurrences_merge_profiles_from_the_identity_provider() {
+        use crate::identity::MockIdentityProvider;
+        use crate::taxonomy_client::FakeTaxonomy;
+        use std::time::Duration;
+
+        // Identity and taxonomy come from in-memory fakes; DB reads fail
+        // fast against a closed local port and fall back to empty.
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .acquire_timeout(Duration::from_millis(100))
+            .connect_lazy("postgres://127.0.0.1:1/observing")
+            .unwrap();
+        let resolver = MockIdentityProvider::new([Profile {
+            did: "did:plc:alice".to_string(),
+            handle: "alice.example".to_string(),
+            display_name: Some("Alice".to_string()),
+            avatar: None,
+        }])
+        .with_pds("did:plc:alice", "https://pds.example");
+        let taxonomy = FakeTaxonomy::new(Vec::new());
+
+        let media = blobs_to_json(vec![blob_entry("bafkreiabc", "image/jpeg", "link")]);
+        let alice = OccurrenceRow {
+            uri: "at://did:plc:alice/bio.lexicons.temp.v0-1.occurrence/1".into(),
+            did: "did:plc:alice".into(),
+            ..make_row(Some(media.clone()))
+        };
+        let stranger = make_row(Some(media));
+
+        let enriched = enrich_occurrences(
+            &pool,
+            &resolver,
+            &taxonomy,
+            BlobLinks {
+                strategy: BlobUrlStrategy::PdsDirect,
+                signer: None,
+            },
+            &[alice, stranger],
+            None,
+        )
+        .await;
+
+        assert_eq!(enriched[0].observer.did, "did:plc:alice");
+        assert_eq!(
+            enriched[0].observer.handle.as_deref(),
+            Some("alice.example")
+        );
+        assert_eq!(enriched[0].observer.display_name.as_deref(), Some("Alice"));
+        assert_eq!(
+            enriched[0].images[0].url,
+            "https://pds.example/xrpc/com.atproto.sync.getBlob?did=did:plc:alice&cid=bafkreiabc"
+        );
+        // No profile or DID document: the bare DID, and images via the proxy.
+        assert_eq!(enriched[1].observer.did, "did:plc:test");
+        assert_eq!(enriched[1].observer.handle, None);
+        assert_eq!(
+            enriched[1].images[0].url,
+            "/media/blob/did:plc:test/bafkreiabc"
+        );
+    }
+
+    fn comment(uri: &str, minute: u32, parent: Option<&str>) -> ThreadEntry {
+        let did = "did:plc:commenter".to_string();
+        ThreadEntry {
+            comment: EnrichedComment {
+                row: CommentRow {
+                    uri: uri.into(),
+                    cid: "cid".into(),
+                    did: did.clone(),
+                    subject_uri: "at://did:plc:test/bio.lexicons.temp.v0-1.occurrence/1".into(),
+                    subject_cid: "cid".into(),
+                    body: format!("comment {uri}"),
+                    reply_to_uri: parent.map(Into::into),
+                    reply_to_cid: parent.map(|_| "cid".into()),
+                    created_at: Utc.with_ymd_and_hms(2026, 5, 1, 12, minute, 0).unwrap(),
+                },
+                commenter: ProfileSummary {
+                    did,
+                    handle: None,
+                    display_name: None,
+                    avatar: None,
+                },
+            },
+            parent_uri: parent.map(Into::into),
+            deleted: false,
+        }
+    }
+
+    fn deleted(mut entry: ThreadEntry) -> ThreadEntry {
+        entry.deleted = true;
+        entry.comment.row.body = String::new();
+        entry
+    }
+
+    fn shape(nodes: &[CommentThreadNode]) -> Vec<(String, Vec<String>)> {
+        nodes
+            .iter()
+            .map(|n| {
+                (
+                    n.comment.row.uri.clone(),
+                    n.replies
+                        .iter()
+                        .map(|r| r.comment.row.uri.clone())
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn nests_two_level_thread() {
+        let thread = assemble_comment_thread(vec![
+            comment("a", 0, None),
+            comment("b", 1, None),
+            comment("a1", 2, Some("a")),
+            comment("a1x", 3, Some("a1")),
+            comment("a2", 4, Some("a")),
+        ]);
+
+        assert_eq!(
+            shape(&thread),
+            vec![
+                ("a".to_string(), vec!["a1".to_string(), "a2".to_string()]),
+                ("b".to_string(), vec![]),
+            ]
+        );
+        assert_eq!(shape(&thread[0].replies)[0].1, vec!["a1x".to_string()]);
+    }
+
+    /// A reply whose parent was deleted comes back from the DB without a
+    /// parent URI and is shown at the top level.
+    #[test]
+    fn orphaned_reply_becomes_root() {
+        let mut orphan = comment("r", 1, Some("gone"));
+        orphan.parent_uri = None;
+        let thread = assemble_comment_thread(vec![comment("a", 0, None), orphan]);
+
+        assert_eq!(
+            shape(&thread),
+            vec![("a".to_string(), vec![]), ("r".to_string(), vec![])]
+        );
+    }
+
+    /// A soft-deleted comment keeps its replies nested under it; one with
+    /// nothing left under it disappears.
+    #[test]
+    fn replies_survive_soft_deleted_parent() {
+        let thread = assemble_comment_thread(vec![
+            deleted(comment("a", 0, None)),
+            comment("a1", 1, Some("a")),
+            deleted(comment("b", 2, None)),
+            deleted(comment("c", 3, None)),
+            deleted(comment("c1", 4, Some("c"))),
+        ]);
+
+        assert_eq!(
+            shape(&thread),
+            vec![("a".to_string(), vec!["a1".to_string()])]
+        );
+        assert!(thread[0].deleted);
+        assert_eq!(thread[0].comment.row.body, "");
+        assert!(!thread[0].replies[0].deleted);
+    }
+
+    #[test]
+    fn reply_cycle_is_kept_flat() {
+        let thread =
+            assemble_comment_thread(vec![comment("x", 0, Some("y")), comment("y", 1, Some("x"))]);
+
+        assert_eq!(
+            shape(&thread),
+            vec![("x".to_string(), vec![]), ("y".to_string(), vec![])]
+        );
+    }
+
+    #[test]
+    fn thread_serializes_comment_fields_beside_replies() {
+        let thread =
+            assemble_comment_thread(vec![comment("a", 0, None), comment("a1", 1, Some("a"))]);
+        let json = serde_json::to_value(&thread).unwrap();
+        assert_eq!(json[0]["uri"], "a");
+        assert_eq!(json[0]["commenter"]["did"], "did:plc:commenter");
+        assert_eq!(json[0]["replies"][0]["reply_to_uri"], "a");
+        assert_eq!(json[0]["replies"][0]["replies"], serde_json::json!([]));
+    }
 }
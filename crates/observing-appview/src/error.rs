@@ -9,9 +9,17 @@ pub enum AppError {
     NotFound(String),
     Unauthorized,
     Forbidden(String),
+    /// The request was based on a version of a resource that has since changed.
+    Conflict(String),
+    /// The request is well-formed but can't be applied as sent.
+    UnprocessableEntity(String),
     Internal(String),
     Database(sqlx::Error),
     ServiceUnavailable(String),
+    /// An upstream server (e.g. an author's PDS) failed or sent a bad reply.
+    BadGateway(String),
+    /// The caller already has a request of this kind in progress.
+    TooManyRequests(String),
 }
 
 impl IntoResponse for AppError {
@@ -21,6 +29,8 @@ impl IntoResponse for AppError {
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
             AppError::Unauthorized => (StatusCode::UNAUTHORIZED, "Authentication required".into()),
             AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
+            AppError::Conflict(msg) => (StatusCode::CONFLICT, msg),
+            AppError::UnprocessableEntity(msg) => (StatusCode::UNPROCESSABLE_ENTITY, msg),
             AppError::Internal(msg) => {
                 tracing::error!(error = %msg, "Internal server error");
                 (
@@ -36,20 +46,104 @@ impl IntoResponse for AppError {
                 )
             }
             AppError::ServiceUnavailable(msg) => (StatusCode::SERVICE_UNAVAILABLE, msg),
+            AppError::BadGateway(msg) => (StatusCode::BAD_GATEWAY, msg),
+            AppError::TooManyRequests(msg) => (StatusCode::TOO_MANY_REQUESTS, msg),
         };
 
         (status, axum::Json(json!({ "error": message }))).into_response()
     }
 }
 
+/// SQLSTATE `query_canceled`, raised when `statement_timeout` fires.
+const QUERY_CANCELED: &str = "57014";
+
 impl From<sqlx::Error> for AppError {
     fn from(e: sqlx::Error) -> Self {
+        let canceled = e
+            .as_database_error()
+            .and_then(|db| db.code())
+            .is_some_and(|code| code == QUERY_CANCELED);
+        if canceled {
+            tracing::warn!(error = %e, "Query hit the statement timeout");
+            return AppError::ServiceUnavailable(
+                "The query took too long; try narrowing the request".into(),
+            );
+        }
         AppError::Database(e)
     }
 }
 
+impl From<observing_db::cursor::CursorError> for AppError {
+    fn from(e: observing_db::cursor::CursorError) -> Self {
+        AppError::BadRequest(e.to_string())
+    }
+}
+
 impl From<crate::taxonomy_client::TaxonomyClientError> for AppError {
     fn from(e: crate::taxonomy_client::TaxonomyClientError) -> Self {
         AppError::Internal(e.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::error::{DatabaseError, ErrorKind};
+    use std::borrow::Cow;
+
+    #[derive(Debug)]
+    struct FakeDbError(&'static str);
+
+    impl std::fmt::Display for FakeDbError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "sqlstate {}", self.0)
+        }
+    }
+
+    impl std::error::Error for FakeDbError {}
+
+    impl DatabaseError for FakeDbError {
+        fn message(&self) -> &str {
+            "fake"
+        }
+        fn code(&self) -> Option<Cow<'_, str>> {
+            Some(Cow::Borrowed(self.0))
+        }
+        fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+        fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+        fn into_error_box(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+            self
+        }
+        fn kind(&self) -> ErrorKind {
+            ErrorKind::Other
+        }
+    }
+
+    #[test]
+    fn statement_timeout_is_service_unavailable() {
+        let err = AppError::from(sqlx::Error::Database(Box::new(FakeDbError(QUERY_CANCELED))));
+        assert_eq!(
+            err.into_response().status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+    }
+
+    #[test]
+    fn other_database_errors_stay_internal() {
+        let err = AppError::from(sqlx::Error::Database(Box::new(FakeDbError("23505"))));
+        assert_eq!(
+            err.into_response().status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+        assert_eq!(
+            AppError::from(sqlx::Error::RowNotFound)
+                .into_response()
+                .status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+}
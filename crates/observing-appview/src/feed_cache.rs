@@ -0,0 +1,226 @@
+//! Short-lived memoization of anonymous feed responses.
+//!
+//! A burst of identical feed requests (say, everyone landing on explore at
+//! once) would otherwise each run the feed query and the whole enrichment
+//! pipeline. Anonymous responses don't depend on who is asking, so the
+//! serialized body is cached for a few seconds under a key built from the
+//! normalized query; concurrent misses on the same key wait for one
+//! computation instead of racing. Signed-in requests carry viewer-specific
+//! state (likes, own identifications) and always bypass the cache. Each
+//! [`ResponseFormat`] is cached separately.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::body::Bytes;
+use axum::response::Response;
+use moka::future::Cache;
+use serde::Serialize;
+
+use crate::error::AppError;
+use crate::response_format::ResponseFormat;
+
+/// How long a cached feed page is served before it is recomputed.
+const FEED_CACHE_TTL: Duration = Duration::from_secs(5);
+
+/// Distinct feed queries kept at once.
+const FEED_CACHE_CAPACITY: u64 = 1_000;
+
+#[derive(Clone)]
+pub struct FeedCache {
+    responses: Cache<(String, ResponseFormat), Bytes>,
+}
+
+impl FeedCache {
+    pub fn new() -> Self {
+        Self::with_ttl(FEED_CACHE_TTL)
+    }
+
+    fn with_ttl(ttl: Duration) -> Self {
+        Self {
+            responses: Cache::builder()
+                .max_capacity(FEED_CACHE_CAPACITY)
+                .time_to_live(ttl)
+                .build(),
+        }
+    }
+
+    /// Serve `compute`'s response in `format`, from the cache when `key` is
+    /// set.
+    ///
+    /// Pass `None` for requests that must not share a response (anything
+    /// with a signed-in viewer). Errors are never cached.
+    pub async fn respond<T, F>(
+        &self,
+        key: Option<String>,
+        format: ResponseFormat,
+        compute: F,
+    ) -> Result<Response, AppError>
+    where
+        T: Serialize,
+        F: Future<Output = Result<T, AppError>>,
+    {
+        let body = match key {
+            Some(key) => self
+                .responses
+                .try_get_with((key, format), async { format.serialize(&compute.await?) })
+                .await
+                .map_err(unshare)?,
+            None => format.serialize(&compute.await?)?,
+        };
+        Ok(format.body_response(body))
+    }
+}
+
+/// Recover an owned error from one shared between coalesced callers.
+fn unshare(e: Arc<AppError>) -> AppError {
+    Arc::try_unwrap(e).unwrap_or_else(|e| match &*e {
+        AppError::BadRequest(msg) => AppError::BadRequest(msg.clone()),
+        AppError::NotFound(msg) => AppError::NotFound(msg.clone()),
+        AppError::Unauthorized => AppError::Unauthorized,
+        AppError::Forbidden(msg) => AppError::Forbidden(msg.clone()),
+        AppError::Conflict(msg) => AppError::Conflict(msg.clone()),
+        AppError::UnprocessableEntity(msg) => AppError::UnprocessableEntity(msg.clone()),
+        AppError::Internal(msg) => AppError::Internal(msg.clone()),
+        AppError::Database(err) => AppError::Internal(err.to_string()),
+        AppError::ServiceUnavailable(msg) => AppError::ServiceUnavailable(msg.clone()),
+        AppError::BadGateway(msg) => AppError::BadGateway(msg.clone()),
+        AppError::TooManyRequests(msg) => AppError::TooManyRequests(msg.clone()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Stands in for the feed query: counts how often it runs.
+    async fn query(hits: &AtomicUsize) -> Result<Vec<&'static str>, AppError> {
+        hits.fetch_add(1, Ordering::SeqCst);
+        Ok(vec!["at://did:plc:abc/bio.lexicons.temp.v0-1.occurrence/1"])
+    }
+
+    async fn body(response: Response) -> Bytes {
+        axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn identical_anonymous_requests_within_ttl_hit_the_db_once() {
+        let cache = FeedCache::new();
+        let hits = AtomicUsize::new(0);
+
+        let first = cache
+            .respond(
+                Some("explore:limit=20".into()),
+                ResponseFormat::Json,
+                query(&hits),
+            )
+            .await
+            .unwrap();
+        let second = cache
+            .respond(
+                Some("explore:limit=20".into()),
+                ResponseFormat::Json,
+                query(&hits),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+        assert_eq!(body(first).await, body(second).await);
+    }
+
+    #[tokio::test]
+    async fn different_queries_and_viewers_are_not_shared() {
+        let cache = FeedCache::new();
+        let hits = AtomicUsize::new(0);
+
+        cache
+            .respond(
+                Some("explore:limit=20".into()),
+                ResponseFormat::Json,
+                query(&hits),
+            )
+            .await
+            .unwrap();
+        cache
+            .respond(
+                Some("explore:limit=50".into()),
+                ResponseFormat::Json,
+                query(&hits),
+            )
+            .await
+            .unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+        // Signed-in requests bypass the cache every time.
+        cache
+            .respond(None, ResponseFormat::Json, query(&hits))
+            .await
+            .unwrap();
+        cache
+            .respond(None, ResponseFormat::Json, query(&hits))
+            .await
+            .unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn entries_expire_after_the_ttl() {
+        let cache = FeedCache::with_ttl(Duration::from_millis(20));
+        let hits = AtomicUsize::new(0);
+
+        cache
+            .respond(Some("k".into()), ResponseFormat::Json, query(&hits))
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        cache
+            .respond(Some("k".into()), ResponseFormat::Json, query(&hits))
+            .await
+            .unwrap();
+
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn formats_are_cached_separately() {
+        let cache = FeedCache::new();
+        let hits = AtomicUsize::new(0);
+
+        let json = cache
+            .respond(Some("k".into()), ResponseFormat::Json, query(&hits))
+            .await
+            .unwrap();
+        let msgpack = cache
+            .respond(Some("k".into()), ResponseFormat::MessagePack, query(&hits))
+            .await
+            .unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+        let decoded: Vec<String> = rmp_serde::from_slice(&body(msgpack).await).unwrap();
+        let expected: Vec<String> = serde_json::from_slice(&body(json).await).unwrap();
+        assert_eq!(decoded, expected);
+    }
+
+    #[tokio::test]
+    async fn errors_are_not_cached() {
+        let cache = FeedCache::new();
+        let failed = cache
+            .respond(Some("k".into()), ResponseFormat::Json, async {
+                Err::<(), _>(AppError::ServiceUnavailable("slow".into()))
+            })
+            .await;
+        assert!(matches!(failed, Err(AppError::ServiceUnavailable(_))));
+
+        let hits = AtomicUsize::new(0);
+        cache
+            .respond(Some("k".into()), ResponseFormat::Json, query(&hits))
+            .await
+            .unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,99 @@
+//! [`IdentityProvider`], the identity lookups enrichment and routes depend
+//! on, implemented by [`IdentityResolver`].
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use atproto_identity::{Did, IdentityResolver, Profile, ResolveResult};
+
+/// DID resolution and profile lookups. [`AppState`] holds one as a trait
+/// object so tests can swap [`IdentityResolver`] (and its Bluesky API and
+/// PLC calls) for canned profiles.
+///
+/// [`AppState`]: crate::state::AppState
+#[async_trait]
+pub trait IdentityProvider: Send + Sync {
+    /// Resolve a DID to its handle and PDS endpoint.
+    async fn resolve(&self, did: &Did) -> Option<ResolveResult>;
+
+    /// One profile, by DID or handle.
+    async fn get_profile(&self, actor: &str) -> Option<Arc<Profile>>;
+
+    /// Profiles for `actors` (DIDs or handles), keyed by both DID and
+    /// handle. Actors that couldn't be fetched are missing from the map.
+    async fn get_profiles(&self, actors: &[String]) -> HashMap<String, Arc<Profile>>;
+}
+
+#[async_trait]
+impl IdentityProvider for IdentityResolver {
+    async fn resolve(&self, did: &Did) -> Option<ResolveResult> {
+        self.resolve_did(did).await
+    }
+
+    async fn get_profile(&self, actor: &str) -> Option<Arc<Profile>> {
+        IdentityResolver::get_profile(self, actor).await
+    }
+
+    async fn get_profiles(&self, actors: &[String]) -> HashMap<String, Arc<Profile>> {
+        IdentityResolver::get_profiles(self, actors).await
+    }
+}
+
+/// [`IdentityProvider`] over a fixed set of profiles and PDS endpoints, for
+/// tests. Unknown actors resolve to nothing.
+#[cfg(test)]
+#[derive(Default)]
+pub(crate) struct MockIdentityProvider {
+    profiles: Vec<Arc<Profile>>,
+    pds_endpoints: HashMap<String, String>,
+}
+
+#[cfg(test)]
+impl MockIdentityProvider {
+    pub(crate) fn new(profiles: impl IntoIterator<Item = Profile>) -> Self {
+        Self {
+            profiles: profiles.into_iter().map(Arc::new).collect(),
+            pds_endpoints: HashMap::new(),
+        }
+    }
+
+    /// Resolve `did` to a DID document pointing at `pds`.
+    pub(crate) fn with_pds(mut self, did: &str, pds: &str) -> Self {
+        self.pds_endpoints.insert(did.to_string(), pds.to_string());
+        self
+    }
+
+    fn find(&self, actor: &str) -> Option<&Arc<Profile>> {
+        self.profiles
+            .iter()
+            .find(|p| p.did == actor || p.handle == actor)
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl IdentityProvider for MockIdentityProvider {
+    async fn resolve(&self, did: &Did) -> Option<ResolveResult> {
+        let pds_endpoint = self.pds_endpoints.get(did.as_str()).cloned();
+        let handle = self.find(did.as_str()).map(|p| p.handle.clone());
+        (pds_endpoint.is_some() || handle.is_some()).then(|| ResolveResult {
+            did: did.clone(),
+            handle,
+            pds_endpoint,
+        })
+    }
+
+    async fn get_profile(&self, actor: &str) -> Option<Arc<Profile>> {
+        self.find(actor).cloned()
+    }
+
+    async fn get_profiles(&self, actors: &[String]) -> HashMap<String, Arc<Profile>> {
+        let mut profiles = HashMap::new();
+        for profile in actors.iter().filter_map(|actor| self.find(actor)) {
+            profiles.insert(profile.did.clone(), profile.clone());
+            profiles.insert(profile.handle.clone(), profile.clone());
+        }
+        profiles
+    }
+}
@@ -0,0 +1,74 @@
+//! Fan-out of database change notifications to live-feed subscribers.
+//!
+//! A single background task holds a `LISTEN` connection on
+//! [`observing_db::live::OCCURRENCE_CHANNEL`] and rebroadcasts each decoded
+//! [`OccurrenceChange`] on a tokio broadcast channel. Every WebSocket client
+//! subscribes to that channel, so the database sees one listener no matter
+//! how many sockets are open.
+
+use std::time::Duration;
+
+use observing_db::live::{OccurrenceChange, OCCURRENCE_CHANNEL};
+use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+
+/// Buffered changes per subscriber before a slow client starts missing them.
+const CHANNEL_CAPACITY: usize = 256;
+
+/// Delay before re-establishing the `LISTEN` connection after it fails.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Clone)]
+pub struct LiveFeed {
+    tx: broadcast::Sender<OccurrenceChange>,
+}
+
+impl LiveFeed {
+    /// Start the listener task. It holds one pool connection for its lifetime.
+    pub fn spawn(pool: PgPool) -> Self {
+        let (tx, _) = broadcast::channel(CHANNEL_CAPACITY);
+        let feed = Self { tx };
+        let task_tx = feed.tx.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = listen(&pool, &task_tx).await {
+                    warn!(error = %e, "Live feed listener failed, reconnecting");
+                }
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            }
+        });
+        feed
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<OccurrenceChange> {
+        self.tx.subscribe()
+    }
+
+    /// Broadcast `change` as if the listener had received it.
+    #[cfg(test)]
+    pub(crate) fn publish(&self, change: OccurrenceChange) {
+        let _ = self.tx.send(change);
+    }
+}
+
+async fn listen(
+    pool: &PgPool,
+    tx: &broadcast::Sender<OccurrenceChange>,
+) -> Result<(), sqlx::Error> {
+    let mut listener = PgListener::connect_with(pool).await?;
+    listener.listen(OCCURRENCE_CHANNEL).await?;
+    info!(channel = OCCURRENCE_CHANNEL, "Live feed listening");
+
+    loop {
+        let notification = listener.recv().await?;
+        match serde_json::from_str::<OccurrenceChange>(notification.payload()) {
+            // No subscribers is not an error; the change is simply dropped.
+            Ok(change) => {
+                let _ = tx.send(change);
+            }
+            Err(e) => warn!(error = %e, "Ignoring malformed occurrence change payload"),
+        }
+    }
+}
@@ -1,14 +1,20 @@
 mod auth;
 mod config;
 mod constants;
+mod cors;
 mod enrichment;
 mod error;
+mod feed_cache;
+mod identity;
+mod live;
 mod media;
 mod middleware;
 mod oauth_store;
 mod resolver;
+mod response_format;
 mod responses;
 mod routes;
+mod server_timing;
 mod species_id_client;
 mod state;
 mod taxonomy;
@@ -17,18 +23,16 @@ mod validation;
 
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 use axum::extract::DefaultBodyLimit;
-use axum::http::{header, Method};
 use axum::middleware as axum_middleware;
 use axum::response::IntoResponse;
 use axum::routing::{get, post};
 use axum::Router;
 use observing_bootstrap::db::PoolConfig;
-use tower_http::compression::CompressionLayer;
-use tower_http::cors::{Any, CorsLayer};
 use tower_http::services::{ServeDir, ServeFile};
-use tracing::info;
+use tracing::{info, warn};
 
 use config::Config;
 use species_id_client::SpeciesIdClient;
@@ -47,13 +51,30 @@ async fn main() {
         .init();
 
     let config = Config::from_env();
+    if let Err(e) = config.validate() {
+        tracing::error!("{e}");
+        std::process::exit(1);
+    }
     info!(port = config.port, "Starting observing-appview");
 
     // Connect to database
-    let pool = PoolConfig::service()
+    let pool_config = PoolConfig::service().with_statement_timeout(config.db_statement_timeout());
+    let pool = pool_config
         .connect(&config.database_url)
         .await
         .expect("Failed to connect to database");
+    let read_pool = match config.database_read_url.as_deref() {
+        Some(url) => {
+            info!("Using a separate read replica pool");
+            pool_config
+                .connect(url)
+                .await
+                .expect("Failed to connect to read replica")
+        }
+        None => pool.clone(),
+    };
+
+    check_spatial_index(&pool).await;
 
     // Create OAuth client
     let oauth_client =
@@ -69,44 +90,130 @@ async fn main() {
         .as_deref()
         .map(|url| Arc::new(SpeciesIdClient::new(url)));
 
-    let media = media::MediaCache::from_env().await;
+    let media_cache = media::MediaCache::from_env().await;
+
+    let live = live::LiveFeed::spawn(pool.clone());
 
     let state = AppState {
-        pool,
-        resolver: Arc::new(atproto_identity::IdentityResolver::from_env()),
-        taxonomy: Arc::new(TaxonomyClient::new()),
+        pool: pool.clone(),
+        read_pool: read_pool.clone(),
+        resolver: Arc::new(
+            atproto_identity::IdentityResolver::from_env().with_profile_ttls(
+                Duration::from_secs(config.profile_cache_soft_ttl_secs),
+                Duration::from_secs(config.profile_cache_hard_ttl_secs),
+            ),
+        ),
+        taxonomy: Arc::new(TaxonomyClient::with_parts(
+            taxonomy::GbifClient::with_cache(
+                config.taxonomy_cache_capacity,
+                Duration::from_secs(config.taxonomy_cache_ttl_secs),
+            ),
+            taxonomy::breaker::CircuitBreaker::new(
+                config.taxonomy_breaker_failures,
+                Duration::from_secs(config.taxonomy_breaker_cooldown_secs),
+            ),
+        )),
         species_id,
         species_id_live,
         oauth_client: Arc::new(oauth_client),
-        media,
+        media: media_cache.clone(),
         public_url: config.public_url.clone(),
         hidden_dids: config.hidden_dids.clone(),
         admin_dids: config.admin_dids.clone(),
         ingester_url: config.ingester_url.clone(),
+        live,
+        feed_cache: feed_cache::FeedCache::new(),
+        page_limits: config.page_limits,
+        image_limits: config.image_limits,
+        coordinate_checks: config.coordinate_checks,
+        imports: routes::occurrences::ActiveImports::default(),
+        blob_urls: config.blob_urls,
+        recorded_conservation: state::recorded_conservation_cache(),
     };
 
-    // CORS
-    let cors = if config.cors_origins.iter().any(|o| o == "*") {
-        CorsLayer::new()
-            .allow_origin(Any)
-            .allow_methods(Any)
-            .allow_headers(Any)
-    } else {
-        let origins: Vec<_> = config
-            .cors_origins
-            .iter()
-            .filter_map(|o| o.parse().ok())
-            .collect();
-        CorsLayer::new()
-            .allow_origin(origins)
-            .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
-            .allow_headers([header::CONTENT_TYPE, header::COOKIE, header::AUTHORIZATION])
-            .allow_credentials(true)
+    let app = build_router(state, &config);
+
+    // Serve the frontend: use pre-built static files if available, otherwise proxy to Vite
+    let built_public = std::env::var("PUBLIC_PATH")
+        .map(PathBuf::from)
+        .ok()
+        .or_else(|| {
+            PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                .join("../../dist/public")
+                .canonicalize()
+                .ok()
+        });
+
+    let app = match built_public {
+        Some(path) => {
+            info!(path = %path.display(), "Serving pre-built frontend");
+            let fallback = ServeDir::new(&path).fallback(ServeFile::new(path.join("index.html")));
+            app.fallback_service(fallback)
+                .layer(axum_middleware::from_fn(middleware::static_cache_control))
+        }
+        None => {
+            // Where the Vite dev server is listening. Defaults to Vite's usual
+            // 5173, but a randomized-port dev stack (scripts/dev.sh) relocates
+            // it and sets VITE_DEV_SERVER_URL so this proxy still finds it.
+            let vite_url = std::env::var("VITE_DEV_SERVER_URL")
+                .unwrap_or_else(|_| "http://localhost:5173".to_string());
+            info!(
+                vite_url = %vite_url,
+                "No pre-built frontend found, proxying to Vite dev server"
+            );
+            let client = reqwest::Client::new();
+            app.fallback(move |req: axum::extract::Request| {
+                let client = client.clone();
+                let vite_url = vite_url.clone();
+                async move { vite_proxy(req, &vite_url, &client).await }
+            })
+        }
     };
 
+    // Surface the app's "front door" URL so humans (and tools) know where to
+    // open it. appview is always the front door on `PORT` — NOT the Vite dev
+    // server's :5173. In local dev the OAuth callback is registered at
+    // 127.0.0.1, so browsing at `localhost` breaks the session cookie origin.
+    match &config.public_url {
+        Some(url) => info!("Serving at {url}"),
+        None => info!(
+            "Open the app at http://127.0.0.1:{}  (use 127.0.0.1, not localhost, so OAuth callback cookies match)",
+            config.port
+        ),
+    }
+
+    observing_bootstrap::serve(app, config.port)
+        .await
+        .expect("Server failed");
+
+    // Requests have drained. Save the blob cache index so blobs cached since
+    // the last periodic save survive the restart, and release the database
+    // connections cleanly so Postgres doesn't see them reset when the
+    // instance stops.
+    if let Err(e) = media_cache.cache.persist().await {
+        warn!(error = %e, "Failed to persist media cache index");
+    }
+    pool.close().await;
+    read_pool.close().await;
+    info!("Shut down");
+}
+
+/// The API and media routes with their body limits, caching, compression
+/// and security layers. `main` adds the frontend fallback on top.
+fn build_router(state: AppState, config: &Config) -> Router {
+    let cors = cors::layer(&config.cors_origins);
+    // Only the image-carrying routes get the large cap; it overrides the
+    // router-wide JSON cap layered below.
+    let upload_limit = DefaultBodyLimit::max(config.upload_body_limit);
+    // Feed and occurrence reads get a Cache-Control policy; see
+    // `middleware::read_cache_control`.
+    let read_cache =
+        axum_middleware::from_fn_with_state(config.read_cache, middleware::read_cache_control);
+
     let app = Router::new()
         // Health
         .route("/health", get(routes::health::health))
+        .route("/health/taxonomy", get(routes::health::taxonomy))
         // OAuth
         .route(
             "/oauth/client-metadata.json",
@@ -115,35 +222,89 @@ async fn main() {
         .route("/oauth/login", get(routes::oauth::login))
         .route("/oauth/callback", get(routes::oauth::callback))
         .route("/oauth/logout", post(routes::oauth::logout))
+        .route("/oauth/logout-all", post(routes::oauth::logout_all))
+        .route("/oauth/sessions", get(routes::oauth::sessions))
         .route("/oauth/me", get(routes::oauth::me))
         // Occurrences - specific routes before wildcard
         .route(
             "/api/occurrences/nearby",
-            get(routes::occurrences::get_nearby),
+            get(routes::occurrences::get_nearby).layer(read_cache.clone()),
+        )
+        .route(
+            "/api/occurrences/feed",
+            get(routes::occurrences::get_feed).layer(read_cache.clone()),
+        )
+        .route(
+            "/api/occurrences/validate",
+            post(routes::occurrences::validate_occurrence),
+        )
+        .route(
+            "/api/occurrences/import",
+            post(routes::occurrences::import_occurrences)
+                .layer(DefaultBodyLimit::max(constants::MAX_IMPORT_BODY_BYTES)),
+        )
+        .route(
+            "/api/occurrences/bbox",
+            get(routes::occurrences::get_bbox).layer(read_cache.clone()),
+        )
+        .route(
+            "/api/occurrences/heatmap",
+            get(routes::occurrences::get_heatmap).layer(read_cache.clone()),
         )
-        .route("/api/occurrences/feed", get(routes::occurrences::get_feed))
-        .route("/api/occurrences/bbox", get(routes::occurrences::get_bbox))
         .route(
             "/api/occurrences/geojson",
-            get(routes::occurrences::get_geojson),
+            get(routes::occurrences::get_geojson).layer(read_cache.clone()),
         )
         .route(
             "/api/occurrences/{*uri}",
-            get(routes::occurrences::get_occurrence).delete(routes::occurrences::delete_occurrence),
+            get(routes::occurrences::get_occurrence)
+                .put(routes::occurrences::patch_occurrence)
+                .delete(routes::occurrences::delete_occurrence)
+                .layer(read_cache.clone()),
         )
         // Occurrences write (no wildcard)
         .route(
             "/api/occurrences",
             post(routes::occurrences::create_occurrence)
-                .put(routes::occurrences::update_occurrence),
+                .put(routes::occurrences::update_occurrence)
+                .layer(upload_limit),
         )
         // Feeds
-        .route("/api/feeds/explore", get(routes::feeds::get_explore))
-        .route("/api/feeds/home", get(routes::feeds::get_home))
+        .route(
+            "/api/feeds/explore",
+            get(routes::feeds::get_explore).layer(read_cache.clone()),
+        )
+        .route(
+            "/api/feeds/home",
+            get(routes::feeds::get_home).layer(read_cache.clone()),
+        )
+        .route(
+            "/api/feeds/needs-id",
+            get(routes::feeds::get_needs_id).layer(read_cache.clone()),
+        )
+        .route(
+            "/api/feeds/trending",
+            get(routes::feeds::get_trending).layer(read_cache.clone()),
+        )
+        .route(
+            "/api/feeds/leaderboard",
+            get(routes::feeds::get_leaderboard).layer(read_cache.clone()),
+        )
+        .route("/api/ws/feed", get(routes::live::feed_socket))
+        // Observers
+        .route("/api/observers/nearby", get(routes::observers::get_nearby))
         // Profiles
         .route(
             "/api/profiles/{did}/feed",
-            get(routes::profiles::get_profile_feed),
+            get(routes::profiles::get_profile_feed).layer(read_cache.clone()),
+        )
+        .route(
+            "/api/profiles/{did}/activity",
+            get(routes::profiles::get_activity).layer(read_cache.clone()),
+        )
+        .route(
+            "/api/profiles/{did}/pending",
+            get(routes::profiles::get_pending).layer(read_cache.clone()),
         )
         // Identifications
         .route(
@@ -157,6 +318,10 @@ async fn main() {
         )
         // Comments
         .route("/api/comments", post(routes::comments::create_comment))
+        .route(
+            "/api/comments/{*uri}",
+            get(routes::comments::get_for_occurrence),
+        )
         // Likes
         .route(
             "/api/likes",
@@ -181,6 +346,8 @@ async fn main() {
             "/api/notifications/read",
             post(routes::notifications::mark_read),
         )
+        // Media privacy
+        .route("/api/media/private", post(routes::media::mark_private))
         // User preferences
         .route(
             "/api/user/preferences",
@@ -188,9 +355,13 @@ async fn main() {
         )
         // Actors
         // Species identification
-        .route("/api/species-id", post(routes::species_id::identify))
+        .route(
+            "/api/species-id",
+            post(routes::species_id::identify).layer(upload_limit),
+        )
         // Taxonomy
         .route("/api/taxa/search", get(routes::taxonomy::search))
+        .route("/api/taxa/suggest", get(routes::taxonomy::suggest))
         .route("/api/taxa/validate", get(routes::taxonomy::validate))
         .route(
             "/api/taxa/{kingdom}/{name}",
@@ -209,77 +380,57 @@ async fn main() {
             "/api/taxa/{id}/occurrences",
             get(routes::taxonomy::get_taxon_occurrences_by_id),
         )
+        .route(
+            "/api/taxa/{id}/images",
+            get(routes::taxonomy::get_taxon_images),
+        )
+        .route(
+            "/api/taxa/{id}/interactions",
+            get(routes::taxonomy::get_taxon_interactions),
+        )
         // HTML admin browser (axum-admin), gated by AdminAuth. The legacy
         // `/admin` React page and `/admin/collections|tables` JSON API
         // were folded into this in #475's follow-up — `/admin` redirects
         // here so old bookmarks keep working.
         .route("/admin", get(routes::admin_browse::redirect_to_browse))
-        .nest_service("/admin/browse", routes::admin_browse::router(state.clone()))
-        // Media (blob/thumb cache, formerly observing-media-proxy)
+        .nest_service("/admin/browse", routes::admin_browse::router(state.clone()));
+
+    // Media (blob/thumb cache, formerly observing-media-proxy)
+    let media = Router::new()
         .route("/media/health", get(routes::media::health))
         .route("/media/blob/{did}/{cid}", get(routes::media::get_blob))
         .route("/media/thumb/{did}/{cid}", get(routes::media::get_thumb))
-        .layer(DefaultBodyLimit::max(150 * 1024 * 1024)) // 150MB for base64-encoded images
-        .layer(CompressionLayer::new())
+        .route("/media/meta/{did}/{cid}", get(routes::media::get_meta))
+        .route("/media/warm", post(routes::media::warm));
+
+    middleware::compress_except(app, media)
+        .layer(axum_middleware::from_fn(server_timing::layer))
+        .layer(DefaultBodyLimit::max(config.json_body_limit))
         .layer(cors)
         .layer(axum_middleware::map_response({
             let is_production = config.public_url.is_some();
             move |response| middleware::security_headers(response, is_production)
         }))
-        .with_state(state);
-
-    // Serve the frontend: use pre-built static files if available, otherwise proxy to Vite
-    let built_public = std::env::var("PUBLIC_PATH")
-        .map(PathBuf::from)
-        .ok()
-        .or_else(|| {
-            PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-                .join("../../dist/public")
-                .canonicalize()
-                .ok()
-        });
+        .with_state(state)
+}
 
-    let app = match built_public {
-        Some(path) => {
-            info!(path = %path.display(), "Serving pre-built frontend");
-            let fallback = ServeDir::new(&path).fallback(ServeFile::new(path.join("index.html")));
-            app.fallback_service(fallback)
-                .layer(axum_middleware::from_fn(middleware::static_cache_control))
+/// Warn when the GiST index behind nearby/bbox queries is missing. Purely
+/// advisory: the appview role can't create it, so the fix is running the
+/// migration job.
+async fn check_spatial_index(pool: &sqlx::PgPool) {
+    use observing_db::migrate::SpatialIndexStatus;
+    match observing_db::migrate::spatial_index_status(pool).await {
+        Ok(SpatialIndexStatus::Present { index }) => {
+            tracing::debug!(index = %index, "Spatial index on occurrences.location present");
         }
-        None => {
-            // Where the Vite dev server is listening. Defaults to Vite's usual
-            // 5173, but a randomized-port dev stack (scripts/dev.sh) relocates
-            // it and sets VITE_DEV_SERVER_URL so this proxy still finds it.
-            let vite_url = std::env::var("VITE_DEV_SERVER_URL")
-                .unwrap_or_else(|_| "http://localhost:5173".to_string());
-            info!(
-                vite_url = %vite_url,
-                "No pre-built frontend found, proxying to Vite dev server"
+        Ok(SpatialIndexStatus::Missing { plan_cost }) => {
+            tracing::warn!(
+                plan_cost,
+                "No GiST index on occurrences.location; radius queries will scan the whole table. Run observing-migrate to recreate it"
             );
-            let client = reqwest::Client::new();
-            app.fallback(move |req: axum::extract::Request| {
-                let client = client.clone();
-                let vite_url = vite_url.clone();
-                async move { vite_proxy(req, &vite_url, &client).await }
-            })
         }
-    };
-
-    // Surface the app's "front door" URL so humans (and tools) know where to
-    // open it. appview is always the front door on `PORT` — NOT the Vite dev
-    // server's :5173. In local dev the OAuth callback is registered at
-    // 127.0.0.1, so browsing at `localhost` breaks the session cookie origin.
-    match &config.public_url {
-        Some(url) => info!("Serving at {url}"),
-        None => info!(
-            "Open the app at http://127.0.0.1:{}  (use 127.0.0.1, not localhost, so OAuth callback cookies match)",
-            config.port
-        ),
+        Err(e) => tracing::warn!(error = %e, "Failed to check spatial index"),
     }
-
-    observing_bootstrap::serve(app, config.port)
-        .await
-        .expect("Server failed");
 }
 
 async fn vite_proxy(
@@ -334,3 +485,46 @@ async fn vite_proxy(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::taxonomy_client::FakeTaxonomy;
+
+    /// POST a JSON body of roughly `len` bytes through the real router.
+    async fn post_status(path: &str, len: usize) -> StatusCode {
+        let (state, _media_dir) = AppState::for_tests(Arc::new(FakeTaxonomy::new(vec![]))).await;
+        let body = serde_json::json!({
+            "blobs": [{ "did": "did:plc:abc", "cid": "A".repeat(len) }]
+        });
+        build_router(state, &Config::for_tests())
+            .oneshot(
+                Request::post(path)
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn json_routes_reject_bodies_over_the_json_limit() {
+        // `/media/warm` reads its body before any auth check, so only the
+        // router-wide cap decides.
+        assert_eq!(
+            post_status("/media/warm", constants::DEFAULT_JSON_BODY_LIMIT).await,
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+        // Under the cap it reaches the handler, which 404s with warming off.
+        assert_eq!(
+            post_status("/media/warm", 1024).await,
+            StatusCode::NOT_FOUND
+        );
+    }
+}
@@ -0,0 +1,153 @@
+//! Image metadata probing for `/media/meta/{did}/{cid}`.
+
+use std::io::Cursor;
+
+use image::{ImageReader, Limits};
+use serde::Serialize;
+
+/// Layout metadata for a cached image blob.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BlobMeta {
+    pub width: u32,
+    pub height: u32,
+    /// Lowercase format name sniffed from the bytes (e.g. `jpeg`, `png`).
+    pub format: String,
+    pub bytes: u64,
+    /// [BlurHash](https://blurha.sh) placeholder, when the image decodes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blurhash: Option<String>,
+}
+
+/// BlurHash components along x and y. 4×3 suits the landscape photos most
+/// observations are and keeps the hash at 28 characters.
+const BLURHASH_COMPONENTS: (u32, u32) = (4, 3);
+
+/// Longest side the image is shrunk to before hashing. A BlurHash only keeps
+/// a dozen low-frequency components, so a tiny thumbnail loses nothing and
+/// keeps the encode cheap.
+const BLURHASH_SAMPLE_SIZE: u32 = 32;
+
+/// Widest or tallest image the BlurHash decode accepts. Blobs come from
+/// arbitrary PDSes, so a tiny file claiming huge dimensions mustn't get to
+/// allocate a frame buffer to match.
+const MAX_DECODE_DIMENSION: u32 = 12_000;
+
+/// Cap on the decoder's total allocation, comfortably above a
+/// 12,000 × 9,000 RGB photo.
+const MAX_DECODE_ALLOC: u64 = 512 * 1024 * 1024;
+
+/// Read an image's dimensions from its header without decoding pixels.
+///
+/// The format is sniffed from the magic bytes rather than trusted from the
+/// PDS-reported content type, which is often a generic
+/// `application/octet-stream`.
+pub fn probe(data: &[u8]) -> Result<BlobMeta, image::ImageError> {
+    let reader = ImageReader::new(Cursor::new(data)).with_guessed_format()?;
+    let format = reader
+        .format()
+        .map(|f| format!("{f:?}").to_lowercase())
+        .unwrap_or_else(|| "unknown".to_string());
+    let (width, height) = reader.into_dimensions()?;
+    Ok(BlobMeta {
+        width,
+        height,
+        format,
+        bytes: data.len() as u64,
+        blurhash: None,
+    })
+}
+
+/// [`probe`] plus a BlurHash of the image.
+///
+/// Unlike the header probe this decodes every pixel, so callers run it off
+/// the async executor and cache the result.
+pub fn probe_with_blurhash(data: &[u8]) -> Result<BlobMeta, image::ImageError> {
+    let mut meta = probe(data)?;
+    meta.blurhash = blurhash(data);
+    Ok(meta)
+}
+
+fn decode_limits() -> Limits {
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_DECODE_DIMENSION);
+    limits.max_image_height = Some(MAX_DECODE_DIMENSION);
+    limits.max_alloc = Some(MAX_DECODE_ALLOC);
+    limits
+}
+
+fn blurhash(data: &[u8]) -> Option<String> {
+    let mut reader = ImageReader::new(Cursor::new(data))
+        .with_guessed_format()
+        .ok()?;
+    reader.limits(decode_limits());
+    let image = reader.decode().ok()?;
+    let sample = image
+        .thumbnail(BLURHASH_SAMPLE_SIZE, BLURHASH_SAMPLE_SIZE)
+        .to_rgba8();
+    let (x, y) = BLURHASH_COMPONENTS;
+    blurhash::encode(x, y, sample.width(), sample.height(), sample.as_raw()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageFormat, RgbImage};
+
+    #[test]
+    fn probe_reads_png_dimensions() {
+        let mut png = Vec::new();
+        RgbImage::new(3, 2)
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+
+        let meta = probe(&png).unwrap();
+        assert_eq!(meta.width, 3);
+        assert_eq!(meta.height, 2);
+        assert_eq!(meta.format, "png");
+        assert_eq!(meta.bytes, png.len() as u64);
+    }
+
+    #[test]
+    fn blurhash_is_stable_and_well_formed() {
+        let gradient = RgbImage::from_fn(64, 48, |x, y| {
+            image::Rgb([(x * 4) as u8, (y * 5) as u8, 128])
+        });
+        let mut png = Vec::new();
+        gradient
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+
+        let meta = probe_with_blurhash(&png).unwrap();
+        assert_eq!((meta.width, meta.height), (64, 48));
+        let hash = meta.blurhash.expect("a decodable image gets a hash");
+        // Size flag (1), max AC (1), DC (4) and 11 AC components (2 each).
+        assert_eq!(hash.len(), 28, "{hash}");
+        // The size flag encodes the 4×3 components: (4-1) + (3-1)*9 = 21 = 'L'.
+        assert!(hash.starts_with('L'), "{hash}");
+        const BASE83: &str =
+            "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz#$%*+,-.:;=?@[]^_{|}~";
+        assert!(hash.chars().all(|c| BASE83.contains(c)), "{hash}");
+
+        // Same bytes, same hash, so the cached value never flaps.
+        assert_eq!(probe_with_blurhash(&png).unwrap().blurhash, Some(hash));
+        // The header-only probe skips the decode.
+        assert_eq!(probe(&png).unwrap().blurhash, None);
+    }
+
+    #[test]
+    fn blurhash_skips_images_over_the_decode_limit() {
+        let mut png = Vec::new();
+        RgbImage::new(MAX_DECODE_DIMENSION + 1, 1)
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+
+        let meta = probe_with_blurhash(&png).unwrap();
+        assert_eq!(meta.width, MAX_DECODE_DIMENSION + 1);
+        assert_eq!(meta.blurhash, None);
+    }
+
+    #[test]
+    fn probe_rejects_non_image_bytes() {
+        assert!(probe(b"definitely not an image").is_err());
+    }
+}
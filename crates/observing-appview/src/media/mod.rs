@@ -4,32 +4,68 @@
 //! resolver live as a [`MediaCache`] held by [`crate::state::AppState`] and
 //! are used directly by the [`crate::routes::media`] handlers — no HTTP hop.
 
+pub mod meta;
+pub mod signing;
+
 use atproto_blob_resolver::BlobResolver;
 use chrono::{DateTime, Utc};
 use file_blob_cache::BlobCache;
+use moka::future::Cache;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+use meta::BlobMeta;
+use signing::MediaSigner;
 
 /// Default cache TTL: 24 hours, matching the previous media-proxy default.
 const DEFAULT_CACHE_TTL_SECS: u64 = 24 * 60 * 60;
 /// Default cache capacity: 1 GB, matching the previous media-proxy default.
 const DEFAULT_MAX_CACHE_SIZE: u64 = 1024 * 1024 * 1024;
+/// Probed image metadata entries kept in memory. Each is a few dozen bytes,
+/// and blobs are content-addressed so entries never go stale.
+const META_CACHE_CAPACITY: u64 = 100_000;
+/// How long a blob's public/private flag is trusted before re-checking the
+/// database. Bounds how long a newly-private blob stays reachable unsigned.
+const PRIVACY_CACHE_TTL: Duration = Duration::from_secs(60);
+/// Raw PDS records kept for `/api/occurrences/{uri}/record`. Unlike blobs,
+/// records can be edited, so entries only live long enough to absorb
+/// repeated requests.
+const RECORD_CACHE_CAPACITY: u64 = 1_000;
+const RECORD_CACHE_TTL: Duration = Duration::from_secs(30);
+/// Warm batches fetched at once; further `POST /media/warm` calls are
+/// refused until one finishes.
+const MAX_CONCURRENT_WARMS: usize = 4;
+/// How often the blob cache index is saved to disk, on top of the save at
+/// shutdown. Blobs cached since the last save are dropped if the process
+/// dies without shutting down.
+const INDEX_PERSIST_INTERVAL: Duration = Duration::from_secs(30);
 
-/// Cache + PDS-fetcher pair. Cheap to clone (everything inside is `Arc`-able
-/// or already cheap to share).
-pub struct MediaCache {
-    pub cache: BlobCache,
-    pub fetcher: BlobResolver,
-    pub started_at: DateTime<Utc>,
+/// Media proxy settings.
+pub struct MediaProxyConfig {
+    pub cache_dir: PathBuf,
+    pub max_cache_size: u64,
+    pub cache_ttl_secs: u64,
+    /// Shared HMAC secret for signed private-media URLs. Without it blobs
+    /// can't be marked private, and any already marked are a 404.
+    pub signing_secret: Option<String>,
+    /// Image CDN tried when a blob's PDS is unreachable. `None` disables the
+    /// fallback.
+    pub cdn_fallback: Option<String>,
+    /// Bearer token the ingester presents to `POST /media/warm`. Without it
+    /// the endpoint is disabled.
+    pub warm_secret: Option<String>,
 }
 
-impl MediaCache {
-    /// Build a new cache from environment variables.
-    ///
+impl MediaProxyConfig {
     /// Reads `CACHE_DIR` (default `./cache/media`), `MAX_CACHE_SIZE`
     /// (default 1 GB), and `CACHE_TTL_SECS` (default 24h) — same names the
-    /// previous media-proxy binary used.
-    pub async fn from_env() -> Arc<Self> {
+    /// previous media-proxy binary used — plus `MEDIA_SIGNING_SECRET`,
+    /// `MEDIA_WARM_SECRET`, and `MEDIA_CDN_FALLBACK_URL` (default the
+    /// Bluesky CDN; empty disables).
+    pub fn from_env() -> Self {
         let cache_dir = std::env::var("CACHE_DIR")
             .map(PathBuf::from)
             .unwrap_or_else(|_| PathBuf::from("./cache/media"));
@@ -41,23 +77,122 @@ impl MediaCache {
             .ok()
             .and_then(|s| s.parse::<u64>().ok())
             .unwrap_or(DEFAULT_CACHE_TTL_SECS);
+        let signing_secret = std::env::var("MEDIA_SIGNING_SECRET")
+            .ok()
+            .filter(|s| !s.is_empty());
+        let warm_secret = std::env::var("MEDIA_WARM_SECRET")
+            .ok()
+            .filter(|s| !s.is_empty());
+        let cdn_fallback = std::env::var("MEDIA_CDN_FALLBACK_URL")
+            .unwrap_or_else(|_| crate::constants::BSKY_CDN_URL.to_string());
+        Self {
+            cache_dir,
+            max_cache_size,
+            cache_ttl_secs,
+            signing_secret,
+            cdn_fallback: Some(cdn_fallback).filter(|s| !s.trim().is_empty()),
+            warm_secret,
+        }
+    }
+}
+
+/// Cache + PDS-fetcher pair. Cheap to clone (everything inside is `Arc`-able
+/// or already cheap to share).
+pub struct MediaCache {
+    pub cache: BlobCache,
+    pub fetcher: BlobResolver,
+    /// Probed dimensions keyed by [`BlobCache::cache_key`].
+    pub meta: Cache<String, BlobMeta>,
+    /// Whether each blob is private, keyed by [`BlobCache::cache_key`].
+    pub privacy: Cache<String, bool>,
+    /// Record bodies fetched via `com.atproto.repo.getRecord`, keyed by AT URI.
+    pub records: Cache<String, serde_json::Value>,
+    /// `None` when no signing secret is configured.
+    pub signer: Option<MediaSigner>,
+    /// `None` disables `POST /media/warm`.
+    pub warm_secret: Option<String>,
+    /// One permit per warm batch in flight.
+    pub warm_permits: Arc<Semaphore>,
+    /// Blobs evicted from the on-disk cache since startup.
+    pub evictions: Arc<AtomicU64>,
+    pub started_at: DateTime<Utc>,
+}
+
+impl MediaCache {
+    /// Build a new cache from environment variables.
+    pub async fn from_env() -> Arc<Self> {
+        Self::new(MediaProxyConfig::from_env()).await
+    }
+
+    pub async fn new(config: MediaProxyConfig) -> Arc<Self> {
+        let MediaProxyConfig {
+            cache_dir,
+            max_cache_size,
+            cache_ttl_secs,
+            signing_secret,
+            cdn_fallback,
+            warm_secret,
+        } = config;
 
         tracing::info!(
             cache_dir = %cache_dir.display(),
             max_cache_size_mb = max_cache_size / (1024 * 1024),
             cache_ttl_secs,
+            signed_urls = signing_secret.is_some(),
+            warm = warm_secret.is_some(),
+            cdn_fallback = cdn_fallback.as_deref().unwrap_or("off"),
             "Initializing in-process media cache"
         );
 
-        let cache = BlobCache::new(cache_dir, max_cache_size, cache_ttl_secs);
+        let evictions = Arc::new(AtomicU64::new(0));
+        let eviction_counter = evictions.clone();
+        let cache = BlobCache::new(cache_dir, max_cache_size, cache_ttl_secs).with_on_evict(
+            Arc::new(move |key: &str, bytes: u64| {
+                eviction_counter.fetch_add(1, Ordering::Relaxed);
+                tracing::debug!(key, bytes, "Evicted media blob");
+            }),
+        );
         if let Err(e) = cache.init().await {
             tracing::error!(error = %e, "Failed to initialize media cache directory");
         }
 
-        Arc::new(Self {
+        let media = Arc::new(Self {
             cache,
-            fetcher: BlobResolver::new(),
+            fetcher: match &cdn_fallback {
+                Some(url) => BlobResolver::new().with_cdn_fallback(url),
+                None => BlobResolver::new(),
+            },
+            meta: Cache::new(META_CACHE_CAPACITY),
+            privacy: Cache::builder()
+                .max_capacity(META_CACHE_CAPACITY)
+                .time_to_live(PRIVACY_CACHE_TTL)
+                .build(),
+            records: Cache::builder()
+                .max_capacity(RECORD_CACHE_CAPACITY)
+                .time_to_live(RECORD_CACHE_TTL)
+                .build(),
+            signer: signing_secret.map(MediaSigner::new),
+            warm_secret,
+            warm_permits: Arc::new(Semaphore::new(MAX_CONCURRENT_WARMS)),
+            evictions,
             started_at: Utc::now(),
-        })
+        });
+
+        let persisted = Arc::downgrade(&media);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(INDEX_PERSIST_INTERVAL);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                let Some(media) = persisted.upgrade() else {
+                    break;
+                };
+                if let Err(e) = media.cache.persist().await {
+                    tracing::warn!(error = %e, "Failed to persist media cache index");
+                }
+            }
+        });
+
+        media
     }
 }
@@ -1,7 +1,13 @@
-use axum::extract::Request;
-use axum::http::{header, HeaderValue};
+use axum::extract::{Request, State};
+use axum::http::{header, HeaderValue, Method};
 use axum::middleware::Next;
 use axum::response::Response;
+use axum::Router;
+use axum_extra::extract::CookieJar;
+use tower_http::compression::CompressionLayer;
+
+use crate::auth::session_did;
+use crate::config::ReadCachePolicy;
 
 /// Adds security headers to all responses.
 ///
@@ -24,6 +30,54 @@ pub async fn security_headers(mut response: Response, is_production: bool) -> Re
     response
 }
 
+/// `Cache-Control` for feed and occurrence reads, layered per route.
+///
+/// Anonymous reads are the same for everyone, so a successful one may be
+/// cached publicly for `policy`'s short lifetimes; `Vary: Cookie` keeps a
+/// shared cache from serving it to a signed-in viewer. Any request with a
+/// session cookie can carry viewer state (likes, own identifications,
+/// notifications) and gets `private, no-store`. Writes on the same route and
+/// responses that already set `Cache-Control` are left alone.
+pub async fn read_cache_control(
+    State(policy): State<ReadCachePolicy>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let is_read = matches!(*req.method(), Method::GET | Method::HEAD);
+    let signed_in = session_did(&CookieJar::from_headers(req.headers())).is_some();
+    let mut response = next.run(req).await;
+    if !is_read || response.headers().contains_key(header::CACHE_CONTROL) {
+        return response;
+    }
+
+    if signed_in {
+        response.headers_mut().insert(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static("private, no-store"),
+        );
+    } else if response.status().is_success() {
+        if let Ok(value) = HeaderValue::from_str(&policy.public_header()) {
+            let headers = response.headers_mut();
+            headers.insert(header::CACHE_CONTROL, value);
+            headers.append(header::VARY, HeaderValue::from_static("cookie"));
+        }
+    }
+    response
+}
+
+/// Merge `app` with gzip/br/deflate compression (negotiated from
+/// `Accept-Encoding`) and `uncompressed` without it.
+///
+/// Media blobs and thumbnails are already-compressed image data, so running
+/// them through the encoder again costs CPU for no size win; the media routes
+/// go in `uncompressed`.
+pub fn compress_except<S>(app: Router<S>, uncompressed: Router<S>) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    app.layer(CompressionLayer::new()).merge(uncompressed)
+}
+
 /// Per-path `Cache-Control` for the static frontend.
 ///
 /// Without this, browsers fall back to heuristic freshness on `index.html`
@@ -56,3 +110,137 @@ pub async fn static_cache_control(req: Request, next: Next) -> Response {
     }
     response
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use axum::http::{header, Request, StatusCode};
+    use axum::routing::get;
+    use axum::{Json, Router};
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    async fn get_with_gzip(app: Router, path: &str) -> axum::response::Response {
+        app.oneshot(
+            Request::get(path)
+                .header("accept-encoding", "gzip")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+    }
+
+    /// A feed-sized JSON route alongside a media route serving the same
+    /// amount of (pretend) image bytes.
+    fn compressed_app() -> Router {
+        async fn feed() -> Json<Value> {
+            let items: Vec<Value> = (0..500)
+                .map(|i| serde_json::json!({ "uri": format!("at://did:plc:abc/occurrence/{i}") }))
+                .collect();
+            Json(serde_json::json!({ "occurrences": items }))
+        }
+        async fn blob() -> ([(header::HeaderName, &'static str); 1], Vec<u8>) {
+            (
+                [(header::CONTENT_TYPE, "application/octet-stream")],
+                vec![0; 32 * 1024],
+            )
+        }
+        super::compress_except(
+            Router::new().route("/api/feeds/explore", get(feed)),
+            Router::new().route("/media/blob/{did}/{cid}", get(blob)),
+        )
+    }
+
+    #[tokio::test]
+    async fn large_json_response_is_gzipped_when_accepted() {
+        let response = get_with_gzip(compressed_app(), "/api/feeds/explore").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+    }
+
+    #[tokio::test]
+    async fn media_responses_are_not_recompressed() {
+        let response = get_with_gzip(compressed_app(), "/media/blob/did:plc:abc/bafy").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get(header::CONTENT_ENCODING).is_none());
+    }
+
+    /// A feed route and a route that 401s anonymous requests, both behind
+    /// the read cache policy.
+    fn cached_app() -> Router {
+        async fn feed() -> Json<Value> {
+            Json(serde_json::json!({ "occurrences": [] }))
+        }
+        async fn home(cookies: axum_extra::extract::CookieJar) -> StatusCode {
+            match crate::auth::session_did(&cookies) {
+                Some(_) => StatusCode::OK,
+                None => StatusCode::UNAUTHORIZED,
+            }
+        }
+        let policy = axum::middleware::from_fn_with_state(
+            crate::config::ReadCachePolicy::default(),
+            super::read_cache_control,
+        );
+        Router::new()
+            .route(
+                "/api/feeds/explore",
+                get(feed).post(feed).layer(policy.clone()),
+            )
+            .route("/api/feeds/home", get(home).layer(policy))
+    }
+
+    async fn cached_get(path: &str, cookie: Option<&str>) -> axum::response::Response {
+        let mut request = Request::get(path);
+        if let Some(cookie) = cookie {
+            request = request.header(header::COOKIE, cookie);
+        }
+        cached_app()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn anonymous_feed_is_publicly_cacheable() {
+        let response = cached_get("/api/feeds/explore", None).await;
+        assert_eq!(
+            response.headers()[header::CACHE_CONTROL],
+            "public, max-age=5, stale-while-revalidate=30"
+        );
+        assert_eq!(response.headers()[header::VARY], "cookie");
+    }
+
+    #[tokio::test]
+    async fn signed_in_feed_is_private() {
+        let response = cached_get("/api/feeds/explore", Some("session_did=did:plc:abc")).await;
+        assert_eq!(
+            response.headers()[header::CACHE_CONTROL],
+            "private, no-store"
+        );
+
+        let response = cached_get("/api/feeds/home", Some("session_did=did:plc:abc")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CACHE_CONTROL],
+            "private, no-store"
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_and_write_responses_are_not_marked_cacheable() {
+        let response = cached_get("/api/feeds/home", None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert!(response.headers().get(header::CACHE_CONTROL).is_none());
+
+        let response = cached_app()
+            .oneshot(
+                Request::post("/api/feeds/explore")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert!(response.headers().get(header::CACHE_CONTROL).is_none());
+    }
+}
@@ -7,6 +7,15 @@ use sqlx::PgPool;
 use std::fmt::{self, Debug, Display};
 use std::hash::Hash;
 
+use chrono::{DateTime, SecondsFormat, TimeDelta, Utc};
+
+/// Access tokens expiring within this window are refreshed on restore rather
+/// than handed out. The OAuth client only refreshes a token once it has
+/// actually expired, so a write that starts just before expiry (e.g. an
+/// occurrence with several images to upload) would otherwise have its later
+/// PDS calls rejected mid-action.
+pub const SESSION_REFRESH_MARGIN: TimeDelta = TimeDelta::minutes(5);
+
 #[derive(Debug)]
 pub enum PgStoreError {
     Database(sqlx::Error),
@@ -104,10 +113,14 @@ where
 
     async fn get(&self, key: &K) -> Result<Option<V>, Self::Error> {
         let value = observing_db::oauth::get_session(&self.pool, key.as_ref()).await?;
-        match value {
-            Some(json_str) => Ok(Some(serde_json::from_str(&json_str)?)),
-            None => Ok(None),
+        let Some(json_str) = value else {
+            return Ok(None);
+        };
+        let (session, due) = load_session(&json_str, Utc::now())?;
+        if due {
+            tracing::debug!(did = key.as_ref(), "OAuth session near expiry, refreshing");
         }
+        Ok(Some(session))
     }
 
     async fn set(&self, key: K, value: V) -> Result<(), Self::Error> {
@@ -129,3 +142,129 @@ where
         Ok(())
     }
 }
+
+/// Deserialize a stored session as it's handed to the OAuth client at `now`,
+/// marked expired when it's due a refresh (see [`expire_if_due`]). Returns
+/// the session and whether it was marked.
+fn load_session<V: DeserializeOwned>(
+    json_str: &str,
+    now: DateTime<Utc>,
+) -> Result<(V, bool), PgStoreError> {
+    let mut session: serde_json::Value = serde_json::from_str(json_str)?;
+    let due = expire_if_due(&mut session, now, SESSION_REFRESH_MARGIN);
+    Ok((serde_json::from_value(session)?, due))
+}
+
+/// If the stored session's access token expires within `margin` of `now`,
+/// rewrite its `token_set.expires_at` to `now` so the OAuth client treats it
+/// as expired and refreshes it before building the agent. Returns whether
+/// the session was marked. Sessions without an expiry are left alone.
+fn expire_if_due(session: &mut serde_json::Value, now: DateTime<Utc>, margin: TimeDelta) -> bool {
+    let Some(expires_at) = session.pointer_mut("/token_set/expires_at") else {
+        return false;
+    };
+    let Some(at) = expires_at
+        .as_str()
+        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+    else {
+        return false;
+    };
+    if at.with_timezone(&Utc) > now + margin {
+        return false;
+    }
+    *expires_at = serde_json::Value::String(now.to_rfc3339_opts(SecondsFormat::Millis, true));
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn session(expires_at: Option<&str>) -> serde_json::Value {
+        json!({
+            "dpop_key": { "kty": "EC", "crv": "P-256" },
+            "token_set": {
+                "iss": "https://pds.example",
+                "sub": "did:plc:abc",
+                "access_token": "access",
+                "refresh_token": "refresh",
+                "token_type": "DPoP",
+                "expires_at": expires_at,
+            }
+        })
+    }
+
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2026-01-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn session_near_expiry_is_marked_for_refresh() {
+        let mut s = session(Some("2026-01-01T12:02:00.000Z"));
+        assert!(expire_if_due(&mut s, now(), SESSION_REFRESH_MARGIN));
+        assert_eq!(s["token_set"]["expires_at"], "2026-01-01T12:00:00.000Z");
+        // The rest of the session is untouched.
+        assert_eq!(s["token_set"]["refresh_token"], "refresh");
+    }
+
+    /// The OAuth client's own check on restore: it refreshes a token whose
+    /// `expires_at` isn't in the future.
+    fn client_refreshes(session: &serde_json::Value, now: DateTime<Utc>) -> bool {
+        session["token_set"]["expires_at"]
+            .as_str()
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .is_some_and(|at| at.with_timezone(&Utc) <= now)
+    }
+
+    #[test]
+    fn restore_refreshes_once_the_margin_is_reached_and_not_before() {
+        let stored = session(Some("2026-01-01T13:00:00.000Z")).to_string();
+        let expires_at = DateTime::parse_from_rfc3339("2026-01-01T13:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let refresh_from = expires_at - SESSION_REFRESH_MARGIN;
+
+        // Restores before the margin hand the token out as is...
+        for before in [now(), refresh_from - TimeDelta::seconds(1)] {
+            let (restored, due) = load_session::<serde_json::Value>(&stored, before).unwrap();
+            assert!(!due, "marked at {before}");
+            assert!(
+                !client_refreshes(&restored, before),
+                "refreshed at {before}"
+            );
+        }
+        // ...and every restore from the margin on, even past expiry, gets a
+        // refreshed one.
+        for after in [
+            refresh_from,
+            refresh_from + TimeDelta::seconds(1),
+            expires_at + TimeDelta::minutes(1),
+        ] {
+            let (restored, due) = load_session::<serde_json::Value>(&stored, after).unwrap();
+            assert!(due, "not marked at {after}");
+            assert!(
+                client_refreshes(&restored, after),
+                "not refreshed at {after}"
+            );
+        }
+    }
+
+    #[test]
+    fn fresh_session_is_left_alone() {
+        let mut s = session(Some("2026-01-01T13:00:00.000Z"));
+        let before = s.clone();
+        assert!(!expire_if_due(&mut s, now(), SESSION_REFRESH_MARGIN));
+        assert_eq!(s, before);
+    }
+
+    #[test]
+    fn session_without_expiry_is_left_alone() {
+        let mut s = session(None);
+        assert!(!expire_if_due(&mut s, now(), SESSION_REFRESH_MARGIN));
+        let mut s = json!({ "unrelated": true });
+        assert!(!expire_if_due(&mut s, now(), SESSION_REFRESH_MARGIN));
+    }
+}
@@ -0,0 +1,172 @@
+//! Content negotiation for the feed and occurrence read endpoints.
+//!
+//! Feed pages are the largest responses the appview sends, so clients that
+//! ask for it with `Accept: application/msgpack` get the same response
+//! struct encoded as MessagePack instead of JSON. Field names are kept
+//! (`rmp_serde::to_vec_named`), so the decoded value has exactly the JSON
+//! shape. Everything else, including a missing or unrecognised `Accept`,
+//! gets JSON.
+
+use std::convert::Infallible;
+use std::time::Instant;
+
+use axum::body::Bytes;
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use axum::http::{header, HeaderMap, HeaderValue};
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+
+use crate::error::AppError;
+use crate::server_timing;
+
+const MSGPACK: &str = "application/msgpack";
+
+/// Wire format for a read response, chosen from the request's `Accept`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ResponseFormat {
+    #[default]
+    Json,
+    MessagePack,
+}
+
+impl ResponseFormat {
+    /// MessagePack when any `Accept` media range names it (either the
+    /// registered `application/msgpack` or the older `x-msgpack`) with a
+    /// non-zero quality; JSON otherwise.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let wants_msgpack = headers
+            .get_all(header::ACCEPT)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .any(|range| {
+                let mut parts = range.split(';').map(str::trim);
+                let media = parts.next().unwrap_or_default();
+                let refused = parts.any(|p| {
+                    p.strip_prefix("q=")
+                        .and_then(|q| q.parse::<f32>().ok())
+                        .is_some_and(|q| q == 0.0)
+                });
+                (media.eq_ignore_ascii_case(MSGPACK)
+                    || media.eq_ignore_ascii_case("application/x-msgpack"))
+                    && !refused
+            });
+        if wants_msgpack {
+            Self::MessagePack
+        } else {
+            Self::Json
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::MessagePack => MSGPACK,
+        }
+    }
+
+    /// Encode `value` in this format, timed as `serialize` in the
+    /// response's `Server-Timing`.
+    pub fn serialize(self, value: &impl Serialize) -> Result<Bytes, AppError> {
+        let started = Instant::now();
+        let encoded = match self {
+            Self::Json => serde_json::to_vec(value).map_err(|e| e.to_string()),
+            Self::MessagePack => rmp_serde::to_vec_named(value).map_err(|e| e.to_string()),
+        };
+        server_timing::record("serialize", started.elapsed());
+        encoded
+            .map(Bytes::from)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize response: {e}")))
+    }
+
+    /// A response carrying already-encoded `body`.
+    pub fn body_response(self, body: Bytes) -> Response {
+        (
+            [
+                (
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static(self.content_type()),
+                ),
+                // The body depends on `Accept`; keep shared caches from
+                // handing a MessagePack page to a JSON client.
+                (header::VARY, HeaderValue::from_static("accept")),
+            ],
+            body,
+        )
+            .into_response()
+    }
+
+    /// Serialize `value` and wrap it in a response.
+    pub fn respond(self, value: &impl Serialize) -> Result<Response, AppError> {
+        Ok(self.body_response(self.serialize(value)?))
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for ResponseFormat {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self::from_headers(&parts.headers))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accept(value: &str) -> ResponseFormat {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, value.parse().unwrap());
+        ResponseFormat::from_headers(&headers)
+    }
+
+    #[test]
+    fn json_is_the_default() {
+        assert_eq!(
+            ResponseFormat::from_headers(&HeaderMap::new()),
+            ResponseFormat::Json
+        );
+        assert_eq!(accept("application/json"), ResponseFormat::Json);
+        assert_eq!(accept("*/*"), ResponseFormat::Json);
+        assert_eq!(
+            accept("text/html, application/xml;q=0.9"),
+            ResponseFormat::Json
+        );
+    }
+
+    #[test]
+    fn msgpack_is_negotiated_from_accept() {
+        assert_eq!(accept("application/msgpack"), ResponseFormat::MessagePack);
+        assert_eq!(
+            accept("application/json;q=0.5, Application/MsgPack"),
+            ResponseFormat::MessagePack
+        );
+        assert_eq!(accept("application/x-msgpack"), ResponseFormat::MessagePack);
+        assert_eq!(accept("application/msgpack;q=0"), ResponseFormat::Json);
+    }
+
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Page {
+        occurrences: Vec<String>,
+        next_cursor: Option<String>,
+    }
+
+    #[tokio::test]
+    async fn msgpack_response_decodes_to_the_json_shape() {
+        let page = Page {
+            occurrences: vec!["at://did:plc:abc/bio.lexicons.temp.v0-1.occurrence/1".into()],
+            next_cursor: None,
+        };
+        let response = accept("application/msgpack").respond(&page).unwrap();
+        assert_eq!(response.headers()[header::CONTENT_TYPE], MSGPACK);
+        assert_eq!(response.headers()[header::VARY], "accept");
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let decoded: serde_json::Value = rmp_serde::from_slice(&body).unwrap();
+        assert_eq!(decoded, serde_json::to_value(&page).unwrap());
+    }
+}
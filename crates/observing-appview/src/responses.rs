@@ -1,8 +1,13 @@
+use observing_db::types::{
+    DensityCell, LeaderboardMetric, PendingOccurrenceRow, TaxonChangeRow, TaxonInteraction,
+    TrendingTaxonRow,
+};
 use serde::Serialize;
 use ts_rs::TS;
 
 use crate::enrichment::{
-    EnrichedComment, EnrichedIdentification, EnrichedInteraction, OccurrenceResponse,
+    ActivityItem, CommentThreadNode, EnrichedComment, EnrichedIdentification, EnrichedInteraction,
+    EnrichedLeaderboardEntry, EnrichedLiker, EnrichedNearbyObserver, OccurrenceResponse,
     ProfileSummary,
 };
 use crate::taxonomy_client::TaxonResult;
@@ -41,6 +46,8 @@ pub struct ExploreFilters {
     pub kingdom: Option<String>,
     pub start_date: Option<String>,
     pub end_date: Option<String>,
+    pub conservation_status: Option<String>,
+    pub threatened_only: bool,
 }
 
 #[derive(Serialize)]
@@ -64,6 +71,38 @@ pub struct HomeFeedResponse {
     pub cursor: Option<String>,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrendingTaxaResponse {
+    pub taxa: Vec<TrendingTaxonRow>,
+    pub window_days: i64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderboardResponse {
+    pub entries: Vec<EnrichedLeaderboardEntry>,
+    pub metric: LeaderboardMetric,
+    pub window_days: i64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityResponse {
+    /// Newest first.
+    pub items: Vec<ActivityItem>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NearbyObserversResponse {
+    pub observers: Vec<EnrichedNearbyObserver>,
+    pub radius: f64,
+    pub window_days: i64,
+}
+
 // --- Occurrence responses ---
 
 #[derive(Serialize)]
@@ -107,6 +146,16 @@ pub struct BboxResponse {
     pub meta: BboxMeta,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeatmapResponse {
+    /// Cell size actually used, in degrees; may be wider than requested.
+    pub cell_size: f64,
+    /// Sum of the cell counts.
+    pub total: i64,
+    pub cells: Vec<DensityCell>,
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GeoJsonFeature {
@@ -148,6 +197,63 @@ pub struct OccurrenceDetailResponse {
     pub comments: Vec<EnrichedComment>,
 }
 
+/// Everything the occurrence detail page renders, in one response.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OccurrenceFullResponse {
+    pub occurrence: OccurrenceResponse,
+    pub identifications: Vec<EnrichedIdentification>,
+    pub comments: Vec<EnrichedComment>,
+    pub likes: LikesSummary,
+    pub interactions: Vec<EnrichedInteraction>,
+    pub taxon_history: Vec<TaxonChangeRow>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaxonHistoryResponse {
+    pub occurrence_uri: String,
+    /// Community ID changes, newest first.
+    pub changes: Vec<TaxonChangeRow>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LikersResponse {
+    pub occurrence_uri: String,
+    /// All likes on the occurrence, not just this page, for "liked by Alice
+    /// and 12 others".
+    pub total: i32,
+    /// Newest like first.
+    pub likers: Vec<EnrichedLiker>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarOccurrencesResponse {
+    pub occurrence_uri: String,
+    /// Same species first, then the rest of the genus, nearest first.
+    pub occurrences: Vec<OccurrenceResponse>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingOccurrencesResponse {
+    /// Newest write first.
+    pub occurrences: Vec<PendingOccurrenceRow>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LikesSummary {
+    pub count: i32,
+    /// Absent for anonymous viewers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub viewer_has_liked: Option<bool>,
+}
+
 // --- Notification responses ---
 
 #[derive(Serialize)]
@@ -163,6 +269,17 @@ pub struct UnreadCountResponse {
 pub struct IdentificationListResponse {
     pub identifications: Vec<EnrichedIdentification>,
     pub community_id: Option<String>,
+    /// Present when a `limit` was given and more identifications may follow.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+}
+
+// --- Comment responses ---
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommentThreadResponse {
+    pub comments: Vec<CommentThreadNode>,
 }
 
 // --- Interaction responses ---
@@ -181,6 +298,38 @@ pub struct TaxonSearchResponse {
     pub results: Vec<TaxonResult>,
 }
 
+/// One image of a taxon: a photo from a local occurrence (served through the
+/// media proxy) or a GBIF media item.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaxonImage {
+    pub url: String,
+    /// `"local"` or `"gbif"`.
+    pub source: &'static str,
+    /// The occurrence the photo belongs to; local images only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub occurrence_uri: Option<String>,
+    /// Likes on that occurrence; local images only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub like_count: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creator: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaxonImagesResponse {
+    pub images: Vec<TaxonImage>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaxonInteractionsResponse {
+    pub interactions: Vec<TaxonInteraction>,
+}
+
 // --- Profile responses ---
 
 #[derive(Serialize)]
@@ -65,7 +65,7 @@ impl IngesterApi {
     /// `http://localhost:9000`). They share one `reqwest::Client`.
     pub fn tables(base_url: &str) -> Vec<IngesterApi> {
         let base = base_url.trim_end_matches('/').to_string();
-        let client = reqwest::Client::new();
+        let client = http_client::client();
         [View::RecentEvents, View::Stats, View::TapState, View::Repos]
             .into_iter()
             .map(|view| IngesterApi {
@@ -1,7 +1,8 @@
-use axum::extract::State;
+use axum::extract::{Path, State};
 use axum::Json;
 use jacquard_common::types::collection::Collection;
 use jacquard_common::types::string::Datetime;
+use observing_db::processing::sanitize_text;
 use observing_lexicons::ing_observ::temp::comment::{Comment, CommentRecord};
 use serde::Deserialize;
 use tracing::info;
@@ -9,8 +10,9 @@ use ts_rs::TS;
 
 use crate::auth::{self, AuthUser};
 use crate::constants;
+use crate::enrichment;
 use crate::error::AppError;
-use crate::responses::RecordCreatedResponse;
+use crate::responses::{CommentThreadResponse, RecordCreatedResponse};
 use crate::state::AppState;
 use crate::validation::validate_string_length;
 
@@ -27,12 +29,25 @@ pub struct CreateCommentRequest {
     reply_to_cid: Option<String>,
 }
 
+/// GET /api/comments/{*uri} — an occurrence's comments as a reply tree,
+/// oldest first at each level. Replies whose parent was deleted are listed
+/// at the top level.
+pub async fn get_for_occurrence(
+    State(state): State<AppState>,
+    Path(occurrence_uri): Path<String>,
+) -> Result<Json<CommentThreadResponse>, AppError> {
+    let rows = observing_db::comments::get_thread(&state.read_pool, &occurrence_uri).await?;
+    let comments = enrichment::enrich_comment_thread(&*state.resolver, &rows).await;
+    Ok(Json(CommentThreadResponse { comments }))
+}
+
 pub async fn create_comment(
     State(state): State<AppState>,
     user: AuthUser,
     Json(body): Json<CreateCommentRequest>,
 ) -> Result<Json<RecordCreatedResponse>, AppError> {
-    validate_string_length(&body.body, 1, constants::MAX_COMMENT_LENGTH, "Comment body")?;
+    let text = sanitize_text(&body.body, usize::MAX);
+    validate_string_length(&text, 1, constants::MAX_COMMENT_LENGTH, "Comment body")?;
 
     let subject = auth::build_strong_ref(&body.occurrence_uri, &body.occurrence_cid)?;
 
@@ -42,7 +57,7 @@ pub async fn create_comment(
     };
 
     let record = Comment::new()
-        .body(&body.body)
+        .body(&text)
         .created_at(Datetime::now())
         .subject(subject)
         .maybe_reply_to(reply_to)
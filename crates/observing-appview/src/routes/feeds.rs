@@ -1,15 +1,26 @@
 use axum::extract::{Query, State};
+use axum::response::Response;
 use axum::Json;
-use observing_db::quality::QualitySelection;
-use observing_db::types::{ExploreFeedOptions, HomeFeedOptions};
+use observing_db::cursor::FeedCursor;
+use observing_db::quality::{QualityGrade, QualitySelection};
+use observing_db::types::{
+    BoundingBox, ExploreFeedOptions, HomeFeedOptions, Kingdom, LeaderboardMetric, NearbyArea,
+    NeedsIdFeedOptions,
+};
 use serde::Deserialize;
 
 use crate::auth::session_did;
 use crate::constants;
 use crate::enrichment;
 use crate::error::AppError;
-use crate::responses::{ExploreFeedResponse, ExploreFilters, ExploreMeta, HomeFeedResponse};
+use crate::response_format::ResponseFormat;
+use crate::responses::{
+    ExploreFeedResponse, ExploreFilters, ExploreMeta, HomeFeedResponse, LeaderboardResponse,
+    TrendingTaxaResponse,
+};
+use crate::server_timing;
 use crate::state::AppState;
+use crate::taxonomy::gbif::IucnCategory;
 
 #[derive(Deserialize)]
 pub struct ExploreParams {
@@ -22,59 +33,133 @@ pub struct ExploreParams {
     #[serde(rename = "endDate")]
     end_date: Option<String>,
     quality: Option<QualitySelection>,
+    #[serde(rename = "qualityGrade")]
+    quality_grade: Option<QualityGrade>,
+    /// Minimum IUCN category, e.g. `EN` for Endangered or worse.
+    #[serde(rename = "conservationStatus")]
+    conservation_status: Option<String>,
+    /// Only Vulnerable, Endangered, or Critically Endangered taxa.
+    #[serde(rename = "threatenedOnly", default)]
+    threatened_only: bool,
+}
+
+/// IUCN codes matching the explore feed's conservation filters, or an empty
+/// list (no filter) when neither is set. Both together intersect.
+fn conservation_categories(
+    min_status: Option<&str>,
+    threatened_only: bool,
+) -> Result<Vec<String>, AppError> {
+    let min = min_status
+        .map(|code| {
+            code.to_ascii_uppercase()
+                .parse::<IucnCategory>()
+                .map_err(|()| AppError::BadRequest(format!("Unknown IUCN category: {code}")))
+        })
+        .transpose()?;
+    if min.is_none() && !threatened_only {
+        return Ok(Vec::new());
+    }
+    Ok(IucnCategory::ALL
+        .iter()
+        .filter(|c| min.is_none_or(|min| c.is_at_least(min)))
+        .filter(|c| !threatened_only || c.is_threatened())
+        .map(|c| c.as_code().to_string())
+        .collect())
+}
+
+/// The backbone spelling of a `kingdom` filter. An unknown kingdom is a 400
+/// rather than a feed that silently matches nothing.
+fn kingdom_filter(kingdom: Option<&str>) -> Result<Option<String>, AppError> {
+    kingdom
+        .map(|name| {
+            Kingdom::lookup(name)
+                .map(|k| k.name.to_string())
+                .ok_or_else(|| AppError::BadRequest(format!("Unknown kingdom: {name}")))
+        })
+        .transpose()
 }
 
 pub async fn get_explore(
     State(state): State<AppState>,
     cookies: axum_extra::extract::CookieJar,
+    format: ResponseFormat,
     Query(params): Query<ExploreParams>,
-) -> Result<Json<ExploreFeedResponse>, AppError> {
-    let limit = params
-        .limit
-        .unwrap_or(constants::DEFAULT_FEED_LIMIT)
-        .min(constants::MAX_FEED_LIMIT);
+) -> Result<Response, AppError> {
+    let limit = state.page_limits.feed.resolve(params.limit);
+    let kingdom = kingdom_filter(params.kingdom.as_deref())?;
 
     let options = ExploreFeedOptions {
         limit: Some(limit),
-        cursor: params.cursor,
+        cursor: params
+            .cursor
+            .as_deref()
+            .map(FeedCursor::decode)
+            .transpose()?,
         taxon: params.taxon.clone(),
-        kingdom: params.kingdom.clone(),
+        kingdom: kingdom.clone(),
         start_date: params.start_date.clone(),
         end_date: params.end_date.clone(),
         quality: params.quality.unwrap_or_default(),
+        quality_grade: params.quality_grade,
+        conservation_categories: conservation_categories(
+            params.conservation_status.as_deref(),
+            params.threatened_only,
+        )?,
+    };
+    let filters = ExploreFilters {
+        taxon: params.taxon,
+        kingdom,
+        start_date: params.start_date,
+        end_date: params.end_date,
+        conservation_status: params.conservation_status,
+        threatened_only: params.threatened_only,
     };
-
-    let rows =
-        observing_db::feeds::get_explore_feed(&state.pool, &options, &state.hidden_dids).await?;
 
     let viewer = session_did(&cookies);
-    let occurrences = enrichment::enrich_occurrences(
-        &state.pool,
-        &state.resolver,
-        &state.taxonomy,
-        &rows,
-        viewer.as_deref(),
-    )
-    .await;
+    // The options carry every normalized filter; the raw conservation
+    // params are echoed back in `meta`, so they're part of the key too.
+    let cache_key = viewer.is_none().then(|| {
+        format!(
+            "explore:{options:?}:{:?}:{}",
+            filters.conservation_status, filters.threatened_only
+        )
+    });
+    state
+        .feed_cache
+        .respond(cache_key, format, async {
+            let rows = server_timing::timed(
+                "db",
+                observing_db::feeds::get_explore_feed(
+                    &state.read_pool,
+                    &options,
+                    &state.hidden_dids,
+                ),
+            )
+            .await?;
 
-    let next_cursor = if occurrences.len() as i64 == limit {
-        occurrences.last().map(|o| o.feed_cursor())
-    } else {
-        None
-    };
+            let occurrences = enrichment::enrich_occurrences(
+                &state.read_pool,
+                &*state.resolver,
+                &*state.taxonomy,
+                state.blob_links(),
+                &rows,
+                viewer.as_deref(),
+            )
+            .await;
 
-    Ok(Json(ExploreFeedResponse {
-        occurrences,
-        cursor: next_cursor,
-        meta: ExploreMeta {
-            filters: ExploreFilters {
-                taxon: params.taxon,
-                kingdom: params.kingdom,
-                start_date: params.start_date,
-                end_date: params.end_date,
-            },
-        },
-    }))
+            let next_cursor = if occurrences.len() as i64 == limit {
+                occurrences.last().and_then(|o| o.feed_cursor())
+            } else {
+                None
+            };
+
+            Ok(ExploreFeedResponse {
+                occurrences,
+                cursor: next_cursor,
+                meta: ExploreMeta { filters },
+            })
+        })
+        .await
 }
 
 #[derive(Deserialize)]
@@ -87,40 +172,410 @@ pub struct HomeParams {
 pub async fn get_home(
     State(state): State<AppState>,
     cookies: axum_extra::extract::CookieJar,
+    format: ResponseFormat,
     Query(params): Query<HomeParams>,
-) -> Result<Json<HomeFeedResponse>, AppError> {
+) -> Result<Response, AppError> {
     let viewer = session_did(&cookies).ok_or(AppError::Unauthorized)?;
-    let limit = params
-        .limit
-        .unwrap_or(constants::DEFAULT_FEED_LIMIT)
-        .min(constants::MAX_FEED_LIMIT);
+    let limit = state.page_limits.feed.resolve(params.limit);
 
     let options = HomeFeedOptions {
         limit: Some(limit),
-        cursor: params.cursor,
+        cursor: params
+            .cursor
+            .as_deref()
+            .map(FeedCursor::decode)
+            .transpose()?,
         quality: params.quality.unwrap_or_default(),
     };
 
-    let rows =
-        observing_db::feeds::get_home_feed(&state.pool, &options, &state.hidden_dids).await?;
+    let rows = server_timing::timed(
+        "db",
+        observing_db::feeds::get_home_feed(&state.read_pool, &options, &state.hidden_dids),
+    )
+    .await?;
 
     let occurrences = enrichment::enrich_occurrences(
-        &state.pool,
-        &state.resolver,
-        &state.taxonomy,
+        &state.read_pool,
+        &*state.resolver,
+        &*state.taxonomy,
+        state.blob_links(),
         &rows,
         Some(&viewer),
     )
     .await;
 
     let next_cursor = if occurrences.len() as i64 == limit {
-        occurrences.last().map(|o| o.feed_cursor())
+        occurrences.last().and_then(|o| o.feed_cursor())
     } else {
         None
     };
 
-    Ok(Json(HomeFeedResponse {
+    format.respond(&HomeFeedResponse {
         occurrences,
         cursor: next_cursor,
+    })
+}
+
+#[derive(Deserialize)]
+pub struct NeedsIdParams {
+    limit: Option<i64>,
+    cursor: Option<String>,
+    kingdom: Option<String>,
+    /// `minLng,minLat,maxLng,maxLat`; the four-parameter form below also
+    /// works, but not both.
+    bbox: Option<String>,
+    #[serde(rename = "minLat")]
+    min_lat: Option<f64>,
+    #[serde(rename = "minLng")]
+    min_lng: Option<f64>,
+    #[serde(rename = "maxLat")]
+    max_lat: Option<f64>,
+    #[serde(rename = "maxLng")]
+    max_lng: Option<f64>,
+    lat: Option<f64>,
+    lng: Option<f64>,
+    /// Meters around `lat`/`lng`.
+    radius: Option<f64>,
+}
+
+/// The "help identify" queue: occurrences nobody but their observer has
+/// identified yet, optionally narrowed to a kingdom and a region (a
+/// `bbox`, a `lat`/`lng`/`radius` circle, or both).
+pub async fn get_needs_id(
+    State(state): State<AppState>,
+    cookies: axum_extra::extract::CookieJar,
+    format: ResponseFormat,
+    Query(params): Query<NeedsIdParams>,
+) -> Result<Response, AppError> {
+    let limit = state.page_limits.feed.resolve(params.limit);
+
+    let options = NeedsIdFeedOptions {
+        limit: Some(limit),
+        cursor: params
+            .cursor
+            .as_deref()
+            .map(FeedCursor::decode)
+            .transpose()?,
+        kingdom: kingdom_filter(params.kingdom.as_deref())?,
+        bbox: bbox_param(
+            params.bbox.as_deref(),
+            optional_bbox(
+                params.min_lat,
+                params.min_lng,
+                params.max_lat,
+                params.max_lng,
+            )?,
+        )?,
+        near: nearby_area(params.lat, params.lng, params.radius)?,
+    };
+
+    let viewer = session_did(&cookies);
+    let cache_key = viewer.is_none().then(|| format!("needs-id:{options:?}"));
+    state
+        .feed_cache
+        .respond(cache_key, format, async {
+            let rows = server_timing::timed(
+                "db",
+                observing_db::feeds::get_needs_id_feed(
+                    &state.read_pool,
+                    &options,
+                    &state.hidden_dids,
+                ),
+            )
+            .await?;
+
+            let occurrences = enrichment::enrich_occurrences(
+                &state.read_pool,
+                &*state.resolver,
+                &*state.taxonomy,
+                state.blob_links(),
+                &rows,
+                viewer.as_deref(),
+            )
+            .await;
+
+            let next_cursor = if occurrences.len() as i64 == limit {
+                occurrences.last().and_then(|o| o.feed_cursor())
+            } else {
+                None
+            };
+
+            Ok(HomeFeedResponse {
+                occurrences,
+                cursor: next_cursor,
+            })
+        })
+        .await
+}
+
+#[derive(Deserialize)]
+pub struct TrendingParams {
+    days: Option<i64>,
+    limit: Option<i64>,
+    #[serde(rename = "minLat")]
+    min_lat: Option<f64>,
+    #[serde(rename = "minLng")]
+    min_lng: Option<f64>,
+    #[serde(rename = "maxLat")]
+    max_lat: Option<f64>,
+    #[serde(rename = "maxLng")]
+    max_lng: Option<f64>,
+}
+
+/// Read an optional bounding box from query params: all four corners or none.
+pub(crate) fn optional_bbox(
+    min_lat: Option<f64>,
+    min_lng: Option<f64>,
+    max_lat: Option<f64>,
+    max_lng: Option<f64>,
+) -> Result<Option<BoundingBox>, AppError> {
+    match (min_lat, min_lng, max_lat, max_lng) {
+        (Some(min_lat), Some(min_lng), Some(max_lat), Some(max_lng)) => Ok(Some(BoundingBox {
+            min_lat,
+            min_lng,
+            max_lat,
+            max_lng,
+        })),
+        (None, None, None, None) => Ok(None),
+        _ => Err(AppError::BadRequest(
+            "minLat, minLng, maxLat and maxLng must be given together".into(),
+        )),
+    }
+}
+
+/// A bounding box from a `bbox=minLng,minLat,maxLng,maxLat` param, or
+/// `corners` when it's absent. Giving both is ambiguous and rejected.
+fn bbox_param(
+    bbox: Option<&str>,
+    corners: Option<BoundingBox>,
+) -> Result<Option<BoundingBox>, AppError> {
+    let Some(bbox) = bbox else {
+        return Ok(corners);
+    };
+    if corners.is_some() {
+        return Err(AppError::BadRequest(
+            "Give either bbox or minLat/minLng/maxLat/maxLng, not both".into(),
+        ));
+    }
+    let invalid = || AppError::BadRequest(format!("Invalid bbox: {bbox}"));
+    let values = bbox
+        .split(',')
+        .map(|v| v.trim().parse::<f64>().ok().filter(|v| v.is_finite()))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(invalid)?;
+    match values[..] {
+        [min_lng, min_lat, max_lng, max_lat] => Ok(Some(BoundingBox {
+            min_lat,
+            min_lng,
+            max_lat,
+            max_lng,
+        })),
+        _ => Err(invalid()),
+    }
+}
+
+/// A search circle from `lat`, `lng` and an optional `radius` in meters
+/// (default [`constants::DEFAULT_NEARBY_RADIUS`], capped at
+/// [`constants::MAX_NEEDS_ID_RADIUS`]).
+fn nearby_area(
+    lat: Option<f64>,
+    lng: Option<f64>,
+    radius: Option<f64>,
+) -> Result<Option<NearbyArea>, AppError> {
+    match (lat, lng) {
+        (Some(lat), Some(lng)) => Ok(Some(NearbyArea {
+            lat,
+            lng,
+            radius_meters: radius
+                .filter(|r| r.is_finite())
+                .unwrap_or(constants::DEFAULT_NEARBY_RADIUS)
+                .clamp(0.0, constants::MAX_NEEDS_ID_RADIUS),
+        })),
+        (None, None) if radius.is_none() => Ok(None),
+        _ => Err(AppError::BadRequest(
+            "lat and lng must be given together, and radius needs both".into(),
+        )),
+    }
+}
+
+pub async fn get_trending(
+    State(state): State<AppState>,
+    Query(params): Query<TrendingParams>,
+) -> Result<Json<TrendingTaxaResponse>, AppError> {
+    let window_days = params
+        .days
+        .unwrap_or(constants::DEFAULT_TRENDING_WINDOW_DAYS)
+        .clamp(1, constants::MAX_TRENDING_WINDOW_DAYS);
+    let limit = state.page_limits.feed.resolve(params.limit);
+    let bbox = optional_bbox(
+        params.min_lat,
+        params.min_lng,
+        params.max_lat,
+        params.max_lng,
+    )?;
+
+    // Aggregates over the whole window; allowed to outlast the default
+    // statement timeout.
+    let mut tx = observing_bootstrap::db::begin_with_statement_timeout(
+        &state.read_pool,
+        constants::AGGREGATE_STATEMENT_TIMEOUT,
+    )
+    .await?;
+    let taxa = observing_db::feeds::trending_taxa(
+        &mut *tx,
+        chrono::Duration::days(window_days),
+        bbox.as_ref(),
+        limit,
+        &state.hidden_dids,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(Json(TrendingTaxaResponse { taxa, window_days }))
+}
+
+#[derive(Deserialize)]
+pub struct LeaderboardParams {
+    metric: Option<LeaderboardMetric>,
+    days: Option<i64>,
+    limit: Option<i64>,
+    #[serde(rename = "minLat")]
+    min_lat: Option<f64>,
+    #[serde(rename = "minLng")]
+    min_lng: Option<f64>,
+    #[serde(rename = "maxLat")]
+    max_lat: Option<f64>,
+    #[serde(rename = "maxLng")]
+    max_lng: Option<f64>,
+}
+
+pub async fn get_leaderboard(
+    State(state): State<AppState>,
+    Query(params): Query<LeaderboardParams>,
+) -> Result<Json<LeaderboardResponse>, AppError> {
+    let metric = params.metric.unwrap_or_default();
+    let window_days = params
+        .days
+        .unwrap_or(constants::DEFAULT_TRENDING_WINDOW_DAYS)
+        .clamp(1, constants::MAX_TRENDING_WINDOW_DAYS);
+    let limit = state.page_limits.feed.resolve(params.limit);
+    let bbox = optional_bbox(
+        params.min_lat,
+        params.min_lng,
+        params.max_lat,
+        params.max_lng,
+    )?;
+
+    // Aggregates over the whole window; allowed to outlast the default
+    // statement timeout.
+    let mut tx = observing_bootstrap::db::begin_with_statement_timeout(
+        &state.read_pool,
+        constants::AGGREGATE_STATEMENT_TIMEOUT,
+    )
+    .await?;
+    let rows = observing_db::feeds::leaderboard(
+        &mut *tx,
+        metric,
+        chrono::Duration::days(window_days),
+        bbox.as_ref(),
+        limit,
+        &state.hidden_dids,
+    )
+    .await?;
+    tx.commit().await?;
+
+    let entries = enrichment::enrich_leaderboard(&*state.resolver, &rows).await;
+
+    Ok(Json(LeaderboardResponse {
+        entries,
+        metric,
+        window_days,
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kingdom_filter_canonicalises_known_kingdoms() {
+        assert_eq!(kingdom_filter(None).unwrap(), None);
+        assert_eq!(
+            kingdom_filter(Some("plantae")).unwrap().as_deref(),
+            Some("Plantae")
+        );
+    }
+
+    #[test]
+    fn kingdom_filter_rejects_a_typo() {
+        let err = kingdom_filter(Some("Plantea")).unwrap_err();
+        assert!(matches!(err, AppError::BadRequest(ref msg) if msg.contains("Plantea")));
+    }
+
+    #[test]
+    fn bbox_param_reads_lng_lat_order() {
+        assert_eq!(
+            bbox_param(Some("-123,37, -122,38"), None).unwrap(),
+            Some(BoundingBox {
+                min_lat: 37.0,
+                min_lng: -123.0,
+                max_lat: 38.0,
+                max_lng: -122.0,
+            })
+        );
+        assert_eq!(bbox_param(None, None).unwrap(), None);
+        for bad in ["1,2,3", "1,2,3,north", "1,2,3,4,5", "NaN,2,3,4"] {
+            assert!(bbox_param(Some(bad), None).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn bbox_param_conflicts_with_corner_params() {
+        let corners = optional_bbox(Some(37.0), Some(-123.0), Some(38.0), Some(-122.0)).unwrap();
+        assert_eq!(bbox_param(None, corners).unwrap(), corners);
+        assert!(bbox_param(Some("-123,37,-122,38"), corners).is_err());
+    }
+
+    #[test]
+    fn nearby_area_defaults_and_caps_the_radius() {
+        let area = |radius| {
+            nearby_area(Some(37.77), Some(-122.42), radius)
+                .unwrap()
+                .unwrap()
+                .radius_meters
+        };
+        assert_eq!(area(None), constants::DEFAULT_NEARBY_RADIUS);
+        assert_eq!(area(Some(2_500.0)), 2_500.0);
+        assert_eq!(area(Some(1e9)), constants::MAX_NEEDS_ID_RADIUS);
+        assert_eq!(nearby_area(None, None, None).unwrap(), None);
+        assert!(nearby_area(Some(37.77), None, None).is_err());
+        assert!(nearby_area(None, None, Some(1_000.0)).is_err());
+    }
+
+    #[test]
+    fn no_conservation_filter_by_default() {
+        assert!(conservation_categories(None, false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn threatened_only_selects_vu_en_cr() {
+        assert_eq!(
+            conservation_categories(None, true).unwrap(),
+            ["CR", "EN", "VU"]
+        );
+    }
+
+    #[test]
+    fn minimum_status_includes_everything_worse() {
+        assert_eq!(
+            conservation_categories(Some("en"), false).unwrap(),
+            ["EX", "EW", "CR", "EN"]
+        );
+        // Intersected with threatened-only, extinct categories drop out.
+        assert_eq!(
+            conservation_categories(Some("EN"), true).unwrap(),
+            ["CR", "EN"]
+        );
+        assert!(conservation_categories(Some("XX"), false).is_err());
+    }
+}
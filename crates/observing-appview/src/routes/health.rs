@@ -1,6 +1,11 @@
+use axum::extract::State;
+use axum::http::StatusCode;
 use axum::Json;
 use serde::Serialize;
 
+use crate::state::AppState;
+use crate::taxonomy::CacheStats;
+
 #[derive(Serialize)]
 pub struct HealthResponse {
     pub status: &'static str,
@@ -9,3 +14,38 @@ pub struct HealthResponse {
 pub async fn health() -> Json<HealthResponse> {
     Json(HealthResponse { status: "ok" })
 }
+
+#[derive(Serialize)]
+pub struct UpstreamHealthResponse {
+    pub status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The taxonomy lookup cache, to tell a cold cache from a slow upstream.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache: Option<CacheStats>,
+}
+
+/// Whether GBIF is reachable, plus the lookup cache's stats. 503 when it
+/// isn't, so a deep health check can alert on it without failing the
+/// liveness probe at `/health`.
+pub async fn taxonomy(State(state): State<AppState>) -> (StatusCode, Json<UpstreamHealthResponse>) {
+    let cache = state.taxonomy.cache_stats();
+    match state.taxonomy.ping().await {
+        Ok(()) => (
+            StatusCode::OK,
+            Json(UpstreamHealthResponse {
+                status: "ok",
+                error: None,
+                cache,
+            }),
+        ),
+        Err(e) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(UpstreamHealthResponse {
+                status: "unavailable",
+                error: Some(e.to_string()),
+                cache,
+            }),
+        ),
+    }
+}
@@ -1,6 +1,10 @@
-use axum::extract::{Path, State};
+use axum::extract::{Path, Query, State};
 use axum::Json;
 use jacquard_common::types::collection::Collection;
+use observing_db::cursor::IdentificationCursor;
+use observing_db::types::{
+    IdentificationConfidence, IdentificationListOptions, IdentificationSort,
+};
 use observing_lexicons::bio_lexicons::temp::v0_1::identification::{
     Identification, IdentificationRecord, IdentificationTaxonRank,
 };
@@ -19,21 +23,54 @@ use crate::validation::validate_string_length;
 use jacquard_common::types::string::AtUri;
 use std::str::FromStr;
 
+#[derive(Deserialize)]
+pub struct IdentificationListParams {
+    limit: Option<i64>,
+    cursor: Option<String>,
+    #[serde(default)]
+    sort: IdentificationSort,
+}
+
+/// An occurrence's identifications, each flagged as withdrawn and/or agreeing
+/// with the community ID. Without a `limit` every identification comes back
+/// in one page, newest first.
 pub async fn get_for_occurrence(
     State(state): State<AppState>,
     Path(occurrence_uri): Path<String>,
+    Query(params): Query<IdentificationListParams>,
 ) -> Result<Json<IdentificationListResponse>, AppError> {
-    let rows =
-        observing_db::identifications::get_for_occurrence(&state.pool, &occurrence_uri).await?;
+    let options = IdentificationListOptions {
+        limit: params.limit.map(|l| state.page_limits.feed.clamp(l)),
+        cursor: params
+            .cursor
+            .as_deref()
+            .map(IdentificationCursor::decode)
+            .transpose()?,
+        sort: params.sort,
+    };
+    let rows = observing_db::identifications::list_for_occurrence(
+        &state.read_pool,
+        &occurrence_uri,
+        &options,
+    )
+    .await?;
 
-    let identifications = enrichment::enrich_identifications(&state.resolver, &rows).await;
+    // A short page is the last one.
+    let cursor = options
+        .limit
+        .filter(|&limit| rows.len() as i64 == limit)
+        .and(rows.last())
+        .map(|row| row.cursor(options.sort).encode());
+
+    let identifications = enrichment::enrich_identification_list(&*state.resolver, &rows).await;
 
     let community_id =
-        observing_db::identifications::get_community_id(&state.pool, &occurrence_uri).await?;
+        observing_db::identifications::get_community_id(&state.read_pool, &occurrence_uri).await?;
 
     Ok(Json(IdentificationListResponse {
         identifications,
         community_id,
+        cursor,
     }))
 }
 
@@ -53,6 +90,14 @@ pub struct CreateIdentificationRequest {
     /// when validation doesn't return a kingdom of its own.
     #[ts(optional)]
     kingdom: Option<String>,
+    /// Darwin Core `identificationQualifier` expressing uncertainty about the
+    /// name, e.g. `cf.` or `aff.`.
+    #[ts(optional)]
+    identification_qualifier: Option<String>,
+    /// How sure the identifier is. Tokens outside the scale are rejected
+    /// when the body is deserialized.
+    #[ts(optional)]
+    confidence: Option<IdentificationConfidence>,
 }
 
 pub async fn create_identification(
@@ -69,13 +114,48 @@ pub async fn create_identification(
 
     // Validate taxonomy via GBIF
     let fields = TaxonFields::from_validation(
-        &state.taxonomy,
+        &*state.taxonomy,
         &body.scientific_name,
         body.taxon_rank.clone(),
         body.kingdom.as_deref(),
     )
     .await;
 
+    let record_value = identification_record_value(&body, &fields, chrono::Utc::now())?;
+
+    let (agent, did_parsed) = auth::require_agent(&state.oauth_client, &user.did).await?;
+    let resp = auth::create_at_record(&agent, did_parsed, IdentificationRecord::NSID, record_value)
+        .await?;
+
+    info!(uri = %resp.uri, "Created identification");
+
+    Ok(Json(RecordCreatedResponse {
+        success: true,
+        uri: resp.uri.to_string(),
+        cid: resp.cid.as_ref().to_string(),
+    }))
+}
+
+/// Build the identification record JSON written to the user's PDS.
+fn identification_record_value(
+    body: &CreateIdentificationRequest,
+    fields: &TaxonFields,
+    created_at: chrono::DateTime<chrono::Utc>,
+) -> Result<serde_json::Value, AppError> {
+    let qualifier = body
+        .identification_qualifier
+        .as_deref()
+        .map(str::trim)
+        .filter(|q| !q.is_empty());
+    if let Some(qualifier) = qualifier {
+        validate_string_length(
+            qualifier,
+            1,
+            constants::MAX_IDENTIFICATION_QUALIFIER_LENGTH,
+            "Identification qualifier",
+        )?;
+    }
+
     let occurrence = auth::build_strong_ref(&body.occurrence_uri, &body.occurrence_cid)?;
 
     let record = Identification::new()
@@ -96,21 +176,23 @@ pub async fn create_identification(
     if let Some(obj) = record_value.as_object_mut() {
         obj.insert(
             "createdAt".to_string(),
-            serde_json::json!(chrono::Utc::now().to_rfc3339()),
+            serde_json::json!(created_at.to_rfc3339()),
         );
+        if let Some(qualifier) = qualifier {
+            obj.insert(
+                "identificationQualifier".to_string(),
+                serde_json::json!(qualifier),
+            );
+        }
+        if let Some(confidence) = body.confidence {
+            obj.insert(
+                "confidence".to_string(),
+                serde_json::json!(confidence.as_str()),
+            );
+        }
     }
 
-    let (agent, did_parsed) = auth::require_agent(&state.oauth_client, &user.did).await?;
-    let resp = auth::create_at_record(&agent, did_parsed, IdentificationRecord::NSID, record_value)
-        .await?;
-
-    info!(uri = %resp.uri, "Created identification");
-
-    Ok(Json(RecordCreatedResponse {
-        success: true,
-        uri: resp.uri.to_string(),
-        cid: resp.cid.as_ref().to_string(),
-    }))
+    Ok(record_value)
 }
 
 pub async fn delete_identification(
@@ -158,3 +240,102 @@ pub async fn delete_identification(
     // and refresh community IDs for the occurrence.
     Ok(Json(SuccessResponse { success: true }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OCCURRENCE_URI: &str = "at://did:plc:author/bio.lexicons.temp.v0-1.occurrence/abc";
+    const OCCURRENCE_CID: &str = "bafyreigh2akiscaildcqabsyg3dfr6chu3fgpregiymsck7e7aqa4s52zy";
+
+    fn request(qualifier: Option<&str>) -> CreateIdentificationRequest {
+        CreateIdentificationRequest {
+            occurrence_uri: OCCURRENCE_URI.into(),
+            occurrence_cid: OCCURRENCE_CID.into(),
+            scientific_name: "Quercus alba".into(),
+            taxon_rank: None,
+            kingdom: None,
+            identification_qualifier: qualifier.map(Into::into),
+            confidence: None,
+        }
+    }
+
+    /// The record the create path writes must be read back by the same
+    /// extraction the ingester runs on the firehose.
+    #[test]
+    fn qualifier_round_trips_through_ingest_extraction() {
+        let now = chrono::Utc::now();
+        let value =
+            identification_record_value(&request(Some("cf.")), &TaxonFields::default(), now)
+                .unwrap();
+        assert_eq!(value["identificationQualifier"], "cf.");
+
+        let params = observing_db::processing::identification_from_json(
+            &value,
+            "at://did:plc:identifier/bio.lexicons.temp.v0-1.identification/xyz".into(),
+            "bafyident".into(),
+            "did:plc:identifier".into(),
+            now,
+        )
+        .unwrap();
+        assert_eq!(params.identification_qualifier.as_deref(), Some("cf."));
+        assert_eq!(params.scientific_name, "Quercus alba");
+    }
+
+    #[test]
+    fn blank_qualifier_is_omitted() {
+        let value = identification_record_value(
+            &request(Some(" ")),
+            &TaxonFields::default(),
+            chrono::Utc::now(),
+        )
+        .unwrap();
+        assert!(value.get("identificationQualifier").is_none());
+    }
+
+    #[test]
+    fn overlong_qualifier_is_rejected() {
+        let long = "x".repeat(constants::MAX_IDENTIFICATION_QUALIFIER_LENGTH + 1);
+        let err = identification_record_value(
+            &request(Some(&long)),
+            &TaxonFields::default(),
+            chrono::Utc::now(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, AppError::BadRequest(_)));
+    }
+
+    #[test]
+    fn confidence_round_trips_through_ingest_extraction() {
+        let mut body = request(None);
+        body.confidence = Some(IdentificationConfidence::Tentative);
+        let now = chrono::Utc::now();
+        let value = identification_record_value(&body, &TaxonFields::default(), now).unwrap();
+        assert_eq!(value["confidence"], "tentative");
+
+        let params = observing_db::processing::identification_from_json(
+            &value,
+            "at://did:plc:identifier/bio.lexicons.temp.v0-1.identification/xyz".into(),
+            "bafyident".into(),
+            "did:plc:identifier".into(),
+            now,
+        )
+        .unwrap();
+        assert_eq!(params.confidence, Some(IdentificationConfidence::Tentative));
+    }
+
+    #[test]
+    fn confidence_outside_scale_is_rejected() {
+        let body = |confidence: serde_json::Value| {
+            serde_json::from_value::<CreateIdentificationRequest>(serde_json::json!({
+                "occurrenceUri": OCCURRENCE_URI,
+                "occurrenceCid": OCCURRENCE_CID,
+                "scientificName": "Quercus alba",
+                "confidence": confidence,
+            }))
+        };
+        assert!(body(serde_json::json!("certain")).is_ok());
+        assert!(body(serde_json::json!("definitely")).is_err());
+        assert!(body(serde_json::json!(100)).is_err());
+    }
+}
@@ -2,6 +2,7 @@ use axum::extract::{Path, State};
 use axum::Json;
 use jacquard_common::types::collection::Collection;
 use jacquard_common::types::string::Datetime;
+use observing_db::processing::sanitize_optional_text;
 use observing_db::types::InteractionDirection;
 use observing_lexicons::ing_observ::temp::interaction::{
     Interaction, InteractionInteractionType, InteractionRecord, InteractionSubject, Taxon,
@@ -22,9 +23,9 @@ pub async fn get_for_occurrence(
     State(state): State<AppState>,
     Path(uri): Path<String>,
 ) -> Result<Json<InteractionListResponse>, AppError> {
-    let rows = observing_db::interactions::get_for_occurrence(&state.pool, &uri).await?;
+    let rows = observing_db::interactions::get_for_occurrence(&state.read_pool, &uri).await?;
 
-    let interactions = enrichment::enrich_interactions(&state.resolver, &rows).await;
+    let interactions = enrichment::enrich_interactions(&*state.resolver, &rows).await;
 
     Ok(Json(InteractionListResponse { interactions }))
 }
@@ -96,6 +97,16 @@ pub async fn create_interaction(
         .as_deref()
         .unwrap_or(constants::DEFAULT_INTERACTION_DIRECTION);
 
+    let comment = sanitize_optional_text(body.comment.as_deref(), usize::MAX);
+    if let Some(comment) = &comment {
+        validate_string_length(
+            comment,
+            1,
+            constants::MAX_INTERACTION_COMMENT_LENGTH,
+            "Interaction comment",
+        )?;
+    }
+
     let subject_a = build_interaction_subject(&body.subject_a)?;
     let subject_b = build_interaction_subject(&body.subject_b)?;
 
@@ -107,7 +118,7 @@ pub async fn create_interaction(
         ))
         .direction(direction)
         .created_at(Datetime::now())
-        .maybe_comment(body.comment.as_deref().map(Into::into))
+        .maybe_comment(comment.as_deref().map(Into::into))
         .build();
 
     let record_value = auth::serialize_at_record(&record)?;
@@ -0,0 +1,190 @@
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Query, State};
+use axum::response::Response;
+use observing_db::live::OccurrenceChange;
+use observing_db::types::BoundingBox;
+use serde::Deserialize;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::debug;
+
+use crate::error::AppError;
+use crate::routes::feeds::optional_bbox;
+use crate::state::AppState;
+
+#[derive(Deserialize)]
+pub struct LiveFeedParams {
+    #[serde(rename = "minLat")]
+    min_lat: Option<f64>,
+    #[serde(rename = "minLng")]
+    min_lng: Option<f64>,
+    #[serde(rename = "maxLat")]
+    max_lat: Option<f64>,
+    #[serde(rename = "maxLng")]
+    max_lng: Option<f64>,
+}
+
+/// Upgrade to a WebSocket that streams occurrence changes as JSON text
+/// frames, optionally restricted to a bounding box.
+pub async fn feed_socket(
+    State(state): State<AppState>,
+    Query(params): Query<LiveFeedParams>,
+    ws: WebSocketUpgrade,
+) -> Result<Response, AppError> {
+    let bbox = optional_bbox(
+        params.min_lat,
+        params.min_lng,
+        params.max_lat,
+        params.max_lng,
+    )?;
+    let rx = state.live.subscribe();
+    let hidden_dids = state.hidden_dids.clone();
+    Ok(ws.on_upgrade(move |socket| stream_changes(socket, rx, bbox, hidden_dids)))
+}
+
+async fn stream_changes(
+    mut socket: WebSocket,
+    mut rx: tokio::sync::broadcast::Receiver<OccurrenceChange>,
+    bbox: Option<BoundingBox>,
+    hidden_dids: Vec<String>,
+) {
+    loop {
+        tokio::select! {
+            change = rx.recv() => match change {
+                Ok(change) => {
+                    if !should_forward(&change, bbox.as_ref(), &hidden_dids) {
+                        continue;
+                    }
+                    let Ok(text) = serde_json::to_string(&change) else {
+                        continue;
+                    };
+                    if socket.send(Message::Text(text.into())).await.is_err() {
+                        break;
+                    }
+                }
+                // A slow client missed some changes; keep streaming from
+                // the newest rather than disconnecting it.
+                Err(RecvError::Lagged(skipped)) => {
+                    debug!(skipped, "Live feed subscriber lagged");
+                }
+                Err(RecvError::Closed) => break,
+            },
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                // Clients don't send anything meaningful; pings are
+                // answered by axum.
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+/// Whether a subscriber scoped to `bbox` should receive `change`.
+///
+/// Deletes carry no coordinates and are always forwarded so clients can drop
+/// the occurrence if they're showing it. Upserts without a location can't be
+/// placed in any box, so they only reach unscoped subscribers.
+fn should_forward(
+    change: &OccurrenceChange,
+    bbox: Option<&BoundingBox>,
+    hidden_dids: &[String],
+) -> bool {
+    if hidden_dids.iter().any(|d| d == &change.did) {
+        return false;
+    }
+    let Some(bbox) = bbox else {
+        return true;
+    };
+    match (change.latitude, change.longitude) {
+        (Some(lat), Some(lng)) => bbox.contains(lat, lng),
+        _ => change.action == observing_db::live::ChangeAction::Delete,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use observing_db::live::ChangeAction;
+
+    fn change(action: ChangeAction, coords: Option<(f64, f64)>) -> OccurrenceChange {
+        OccurrenceChange {
+            uri: "at://did:plc:abc/bio.lexicons.temp.v0-1.occurrence/1".into(),
+            did: "did:plc:abc".into(),
+            action,
+            latitude: coords.map(|c| c.0),
+            longitude: coords.map(|c| c.1),
+        }
+    }
+
+    const BAY_AREA: BoundingBox = BoundingBox {
+        min_lat: 37.0,
+        min_lng: -123.0,
+        max_lat: 38.0,
+        max_lng: -122.0,
+    };
+
+    #[test]
+    fn unscoped_subscriber_gets_everything_visible() {
+        assert!(should_forward(
+            &change(ChangeAction::Upsert, None),
+            None,
+            &[]
+        ));
+        assert!(!should_forward(
+            &change(ChangeAction::Upsert, Some((37.5, -122.5))),
+            None,
+            &["did:plc:abc".to_string()]
+        ));
+    }
+
+    #[test]
+    fn bbox_subscriber_gets_only_changes_inside() {
+        let inside = change(ChangeAction::Upsert, Some((37.5, -122.5)));
+        let outside = change(ChangeAction::Upsert, Some((51.5, -0.1)));
+        let unlocated = change(ChangeAction::Upsert, None);
+        assert!(should_forward(&inside, Some(&BAY_AREA), &[]));
+        assert!(!should_forward(&outside, Some(&BAY_AREA), &[]));
+        assert!(!should_forward(&unlocated, Some(&BAY_AREA), &[]));
+    }
+
+    #[test]
+    fn deletes_reach_bbox_subscribers() {
+        let delete = change(ChangeAction::Delete, None);
+        assert!(should_forward(&delete, Some(&BAY_AREA), &[]));
+    }
+
+    #[tokio::test]
+    async fn served_socket_streams_broadcast_changes() {
+        use crate::taxonomy_client::FakeTaxonomy;
+        use futures::StreamExt;
+        use std::sync::Arc;
+
+        let (state, _media_dir) = AppState::for_tests(Arc::new(FakeTaxonomy::new(vec![]))).await;
+        let live = state.live.clone();
+        let app = axum::Router::new()
+            .route("/api/ws/feed", axum::routing::get(feed_socket))
+            .with_state(state);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        // The handler subscribes before answering the upgrade, so a change
+        // published once the handshake completes reaches this socket.
+        let (mut socket, _) = tokio_tungstenite::connect_async(format!(
+            "ws://{addr}/api/ws/feed?minLat=37&minLng=-123&maxLat=38&maxLng=-122"
+        ))
+        .await
+        .unwrap();
+        let outside = change(ChangeAction::Upsert, Some((51.5, -0.1)));
+        let inside = change(ChangeAction::Upsert, Some((37.5, -122.5)));
+        live.publish(outside);
+        live.publish(inside.clone());
+
+        let frame = tokio::time::timeout(std::time::Duration::from_secs(5), socket.next())
+            .await
+            .expect("a frame within the timeout")
+            .expect("socket still open")
+            .unwrap();
+        let received: OccurrenceChange = serde_json::from_str(frame.to_text().unwrap()).unwrap();
+        assert_eq!(received, inside);
+    }
+}
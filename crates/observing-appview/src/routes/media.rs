@@ -3,22 +3,39 @@
 //! URL surface preserved from the previous external `observing-media-proxy`:
 //!   * `GET /media/blob/{did}/{cid}`  — full image
 //!   * `GET /media/thumb/{did}/{cid}` — thumbnail (currently same bytes)
+//!   * `GET /media/meta/{did}/{cid}`  — image dimensions/format/size (JSON)
 //!   * `GET /media/health`            — cache stats / uptime
+//!   * `POST /media/warm`             — prefetch blobs into the cache
+//!     (ingester only; needs `MEDIA_WARM_SECRET` as a bearer token)
+//!
+//! plus `POST /api/media/private`, where a signed-in user marks their own
+//! blobs private.
 //!
 //! The appview mounts these under `/media` so client URLs like
 //! `/media/blob/{did}/{cid}` continue to resolve unchanged.
+//!
+//! Blobs marked private additionally require a signed `?exp=&sig=` pair
+//! (see [`crate::media::signing`]), and are a 404 when no signing secret is
+//! configured.
 
-use atproto_blob_resolver::Did;
+use atproto_blob_resolver::{BlobResolverError, Did};
 use axum::{
     body::Body,
-    extract::{Path, State},
-    http::{header, StatusCode},
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
     response::{IntoResponse, Json, Response},
 };
 use chrono::Utc;
-use serde::Serialize;
-use tracing::{error, warn};
+use jacquard_common::types::string::Cid;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::str::FromStr;
+use std::sync::atomic::Ordering;
+use tracing::{debug, error, warn};
 
+use crate::auth::AuthUser;
+use crate::error::AppError;
+use crate::media::meta::{self, BlobMeta};
 use crate::media::MediaCache;
 use crate::state::AppState;
 
@@ -27,11 +44,145 @@ struct ErrorResponse {
     error: String,
 }
 
+/// Signature parameters on a media URL. Ignored for public blobs.
+#[derive(Deserialize)]
+pub struct SignedMediaParams {
+    exp: Option<i64>,
+    sig: Option<String>,
+}
+
 #[derive(Serialize)]
 pub struct HealthResponse {
     pub status: &'static str,
     pub uptime_secs: u64,
     pub cache: file_blob_cache::CacheStats,
+    pub evictions: u64,
+}
+
+/// Most blobs one `POST /media/warm` may queue.
+const MAX_WARM_BLOBS: usize = 20;
+
+#[derive(Deserialize)]
+pub struct WarmRequest {
+    blobs: Vec<WarmBlob>,
+}
+
+#[derive(Deserialize)]
+pub struct WarmBlob {
+    did: String,
+    cid: String,
+}
+
+#[derive(Serialize)]
+pub struct WarmResponse {
+    pub queued: usize,
+}
+
+/// `POST /media/warm` — fetch blobs into the cache before anyone views them.
+///
+/// The ingester calls this for each new occurrence's images so the first
+/// feed render doesn't wait on the author's PDS. Best-effort: the fetches
+/// run after the 202 goes out and failures are only logged. Entries with an
+/// unparseable DID are skipped.
+///
+/// Not for browsers: the caller must present `MEDIA_WARM_SECRET` as a
+/// bearer token (404 when none is configured), and a 503 is returned while
+/// the maximum number of batches is already being fetched.
+pub async fn warm(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<WarmRequest>,
+) -> Response {
+    let Some(secret) = state.media.warm_secret.as_deref() else {
+        return error_response(StatusCode::NOT_FOUND, "Not found");
+    };
+    if !bearer_matches(&headers, secret) {
+        return error_response(StatusCode::UNAUTHORIZED, "Invalid warm token");
+    }
+    if req.blobs.len() > MAX_WARM_BLOBS {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            &format!("At most {MAX_WARM_BLOBS} blobs per request"),
+        );
+    }
+    let blobs: Vec<(Did, String)> = req
+        .blobs
+        .into_iter()
+        .filter_map(|b| Some((Did::new_owned(&b.did).ok()?, b.cid)))
+        .collect();
+    let queued = blobs.len();
+
+    let Ok(permit) = state.media.warm_permits.clone().try_acquire_owned() else {
+        return error_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Too many warm requests in flight",
+        );
+    };
+    let media = state.media.clone();
+    tokio::spawn(async move {
+        let _permit = permit;
+        for (did, cid) in blobs {
+            match fetch_and_cache(&media, &did, &cid).await {
+                Ok((_, _, origin)) => debug!(did = %did, cid, ?origin, "Warmed blob"),
+                Err(e) => debug!(did = %did, cid, error = %e, "Failed to warm blob"),
+            }
+        }
+    });
+
+    (StatusCode::ACCEPTED, Json(WarmResponse { queued })).into_response()
+}
+
+/// Whether `headers` carry `Authorization: Bearer <secret>`. Digests are
+/// compared so the check doesn't leak how much of the token matched.
+fn bearer_matches(headers: &HeaderMap, secret: &str) -> bool {
+    let Some(token) = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+    else {
+        return false;
+    };
+    Sha256::digest(token.as_bytes()) == Sha256::digest(secret.as_bytes())
+}
+
+#[derive(Deserialize)]
+pub struct MarkPrivateRequest {
+    cids: Vec<String>,
+}
+
+/// `POST /api/media/private` — mark the signed-in user's blobs private.
+///
+/// From then on the proxy only serves them with a signed link, which
+/// enrichment mints for the owner alone; everyone else no longer sees them
+/// in feeds or galleries, and `pds`/`cdn` URL strategies link them through
+/// the proxy instead. Meant for images uploaded but not yet shared: a copy
+/// an HTTP cache already holds under its public URL can't be recalled.
+/// 404 when no `MEDIA_SIGNING_SECRET` is configured, since nothing would
+/// enforce the mark.
+pub async fn mark_private(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(req): Json<MarkPrivateRequest>,
+) -> Result<StatusCode, AppError> {
+    if state.media.signer.is_none() {
+        return Err(AppError::NotFound("Private media is not enabled".into()));
+    }
+    let max = state.image_limits.max_count;
+    if req.cids.is_empty() || req.cids.len() > max {
+        return Err(AppError::BadRequest(format!(
+            "Give between 1 and {max} blob CIDs"
+        )));
+    }
+    if let Some(bad) = req.cids.iter().find(|cid| Cid::from_str(cid).is_err()) {
+        return Err(AppError::BadRequest(format!("Invalid CID: {bad}")));
+    }
+
+    for cid in &req.cids {
+        observing_db::private_data::mark_media_private(&state.pool, &user.did, cid).await?;
+        let key = file_blob_cache::BlobCache::cache_key(&user.did, cid);
+        state.media.privacy.invalidate(&key).await;
+    }
+    Ok(StatusCode::NO_CONTENT)
 }
 
 /// `GET /media/health` — service liveness + cache stats.
@@ -42,6 +193,7 @@ pub async fn health(State(state): State<AppState>) -> Json<HealthResponse> {
         status: "ok",
         uptime_secs,
         cache: cache_stats,
+        evictions: state.media.evictions.load(Ordering::Relaxed),
     })
 }
 
@@ -49,8 +201,9 @@ pub async fn health(State(state): State<AppState>) -> Json<HealthResponse> {
 pub async fn get_blob(
     State(state): State<AppState>,
     Path((did, cid)): Path<(String, String)>,
+    Query(params): Query<SignedMediaParams>,
 ) -> Response {
-    serve_blob(&state.media, &did, &cid).await
+    serve_blob(&state, &did, &cid, &params).await
 }
 
 /// `GET /media/thumb/{did}/{cid}` — thumbnail.
@@ -59,76 +212,450 @@ pub async fn get_blob(
 pub async fn get_thumb(
     State(state): State<AppState>,
     Path((did, cid)): Path<(String, String)>,
+    Query(params): Query<SignedMediaParams>,
 ) -> Response {
-    serve_blob(&state.media, &did, &cid).await
+    serve_blob(&state, &did, &cid, &params).await
 }
 
-async fn serve_blob(media: &MediaCache, did_str: &str, cid: &str) -> Response {
-    let did = match Did::new_owned(did_str) {
+/// `GET /media/meta/{did}/{cid}` — image dimensions for reflow-free layout,
+/// plus a BlurHash placeholder.
+///
+/// Decodes the cached (or freshly fetched) blob once on a blocking thread and
+/// memoizes the result; CIDs are content hashes, so it never goes stale.
+pub async fn get_meta(
+    State(state): State<AppState>,
+    Path((did, cid)): Path<(String, String)>,
+    Query(params): Query<SignedMediaParams>,
+) -> Response {
+    let did = match parse_did(&did) {
         Ok(d) => d,
+        Err(resp) => return resp,
+    };
+    let private = match authorize(&state, &did, &cid, &params).await {
+        Ok(private) => private,
+        Err(resp) => return resp,
+    };
+
+    let key = file_blob_cache::BlobCache::cache_key(did.as_str(), &cid);
+    if let Some(meta) = state.media.meta.get(&key).await {
+        return meta_response(meta, cache_control(private, BlobOrigin::Cache));
+    }
+
+    let (data, origin) = match fetch_and_cache(&state.media, &did, &cid).await {
+        Ok((data, _, origin)) => (data, origin),
         Err(e) => {
-            warn!(did = %did_str, error = %e, "Rejecting blob request with invalid DID");
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(ErrorResponse {
-                    error: format!("Invalid DID: {e}"),
-                }),
-            )
-                .into_response();
+            warn!(did = %did, cid = %cid, error = %e, "Failed to fetch blob for metadata");
+            return fetch_error_response(&e);
+        }
+    };
+
+    let probed = match tokio::task::spawn_blocking(move || meta::probe_with_blurhash(&data)).await {
+        Ok(probed) => probed,
+        Err(e) => {
+            error!(did = %did, cid = %cid, error = %e, "Media probe task failed");
+            return error_response(StatusCode::INTERNAL_SERVER_ERROR, "Failed to read blob");
+        }
+    };
+    match probed {
+        Ok(meta) => {
+            // The CDN's copy is re-encoded, so its format and size aren't
+            // the blob's; describe it without remembering it.
+            if origin != BlobOrigin::Cdn {
+                state.media.meta.insert(key, meta.clone()).await;
+            }
+            meta_response(meta, cache_control(private, origin))
+        }
+        Err(e) => {
+            warn!(did = %did, cid = %cid, error = %e, "Blob is not a readable image");
+            error_response(StatusCode::UNSUPPORTED_MEDIA_TYPE, "Blob is not an image")
         }
+    }
+}
+
+fn meta_response(meta: BlobMeta, cache_control: &'static str) -> Response {
+    ([(header::CACHE_CONTROL, cache_control)], Json(meta)).into_response()
+}
+
+/// Public blobs are content-addressed and cached forever; private ones must
+/// not outlive their signature in any shared or browser cache. A CDN
+/// fallback copy is only a stand-in until the PDS is back, so it's cached
+/// briefly.
+fn cache_control(private: bool, origin: BlobOrigin) -> &'static str {
+    if private {
+        "private, no-store"
+    } else if origin == BlobOrigin::Cdn {
+        "public, max-age=300"
+    } else {
+        "public, max-age=31536000, immutable"
+    }
+}
+
+/// Check the request may read this blob, returning whether it's private.
+///
+/// Private blobs need a valid, unexpired signature (403 otherwise), and a
+/// failed privacy lookup is treated as private (fail closed). Without a
+/// signing secret nothing can mint those links, so private blobs are a 404.
+async fn authorize(
+    state: &AppState,
+    did: &Did,
+    cid: &str,
+    params: &SignedMediaParams,
+) -> Result<bool, Response> {
+    let key = file_blob_cache::BlobCache::cache_key(did.as_str(), cid);
+    let private = match state.media.privacy.get(&key).await {
+        Some(private) => private,
+        None => match observing_db::private_data::is_media_private(&state.pool, did.as_str(), cid)
+            .await
+        {
+            Ok(private) => {
+                state.media.privacy.insert(key, private).await;
+                private
+            }
+            Err(e) => {
+                error!(did = %did, cid = %cid, error = %e, "Failed to check media privacy");
+                true
+            }
+        },
     };
+    if !private {
+        return Ok(false);
+    }
+    let Some(signer) = &state.media.signer else {
+        return Err(error_response(StatusCode::NOT_FOUND, "Blob not found"));
+    };
+
+    signer
+        .verify(
+            did.as_str(),
+            cid,
+            params.exp,
+            params.sig.as_deref(),
+            Utc::now().timestamp(),
+        )
+        .map(|()| true)
+        .map_err(|e| error_response(StatusCode::FORBIDDEN, &e.to_string()))
+}
 
-    match fetch_and_cache(media, &did, cid).await {
-        Ok((data, content_type, from_cache)) => Response::builder()
+fn error_response(status: StatusCode, message: &str) -> Response {
+    (
+        status,
+        Json(ErrorResponse {
+            error: message.to_string(),
+        }),
+    )
+        .into_response()
+}
+
+/// A 400 for a DID that can never resolve, otherwise a 404.
+fn fetch_error_response(e: &BlobResolverError) -> Response {
+    match e {
+        BlobResolverError::InvalidDid(msg) => {
+            error_response(StatusCode::BAD_REQUEST, &format!("Invalid DID: {msg}"))
+        }
+        _ => error_response(StatusCode::NOT_FOUND, "Blob not found"),
+    }
+}
+
+/// Parse the path DID, rendering a 400 on failure.
+fn parse_did(did_str: &str) -> Result<Did, Response> {
+    Did::new_owned(did_str).map_err(|e| {
+        warn!(did = %did_str, error = %e, "Rejecting blob request with invalid DID");
+        error_response(StatusCode::BAD_REQUEST, &format!("Invalid DID: {e}"))
+    })
+}
+
+async fn serve_blob(
+    state: &AppState,
+    did_str: &str,
+    cid: &str,
+    params: &SignedMediaParams,
+) -> Response {
+    let did = match parse_did(did_str) {
+        Ok(d) => d,
+        Err(resp) => return resp,
+    };
+    let private = match authorize(state, &did, cid, params).await {
+        Ok(private) => private,
+        Err(resp) => return resp,
+    };
+
+    match fetch_and_cache(&state.media, &did, cid).await {
+        Ok((data, content_type, origin)) => Response::builder()
             .status(StatusCode::OK)
             .header(header::CONTENT_TYPE, content_type)
-            .header(header::CACHE_CONTROL, "public, max-age=31536000, immutable")
-            .header("X-Cache", if from_cache { "HIT" } else { "MISS" })
+            .header(header::CACHE_CONTROL, cache_control(private, origin))
+            .header(
+                "X-Cache",
+                if origin == BlobOrigin::Cache {
+                    "HIT"
+                } else {
+                    "MISS"
+                },
+            )
             .body(Body::from(data))
             .unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response()),
         Err(e) => {
             warn!(did = %did, cid = %cid, error = %e, "Failed to fetch blob");
-            (
-                StatusCode::NOT_FOUND,
-                Json(ErrorResponse {
-                    error: "Blob not found".to_string(),
-                }),
-            )
-                .into_response()
+            fetch_error_response(&e)
         }
     }
 }
 
-/// Fetch a blob, using cache if available; populate the cache on miss.
+/// Where [`fetch_and_cache`] found a blob.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BlobOrigin {
+    Cache,
+    /// The author's PDS; the blob is now cached too.
+    Pds,
+    /// The CDN fallback. Never cached: the copy is re-encoded and would
+    /// outlive the author deleting the blob.
+    Cdn,
+}
+
+/// Fetch a blob, using cache if available; populate the cache on a miss
+/// served by the PDS.
 async fn fetch_and_cache(
     media: &MediaCache,
     did: &Did,
     cid: &str,
-) -> Result<(Vec<u8>, String, bool), Box<dyn std::error::Error + Send + Sync>> {
+) -> Result<(Vec<u8>, String, BlobOrigin), BlobResolverError> {
     let did_str = did.as_str();
 
     if let Some((data, content_type)) = media.cache.get(did_str, cid).await {
-        return Ok((data, content_type, true));
+        return Ok((data, content_type, BlobOrigin::Cache));
     }
 
-    let pds_url = media.fetcher.resolve_pds_url(did).await.map_err(|e| {
-        error!(did = %did, error = %e, "Failed to resolve PDS URL");
-        e
-    })?;
-
-    let (data, content_type) = media
+    let blob = media
         .fetcher
-        .fetch_blob(&pds_url, did_str, cid)
+        .fetch_blob_for_did(did, cid)
         .await
         .map_err(|e| {
-            error!(did = %did, cid = %cid, error = %e, "Failed to fetch blob from PDS");
+            error!(did = %did, cid = %cid, error = %e, "Failed to fetch blob");
             e
         })?;
+    if blob.from_cdn {
+        return Ok((blob.data, blob.content_type, BlobOrigin::Cdn));
+    }
 
-    if let Err(e) = media.cache.put(did_str, cid, &data, &content_type).await {
+    if let Err(e) = media
+        .cache
+        .put(did_str, cid, &blob.data, &blob.content_type)
+        .await
+    {
         warn!(did = %did, cid = %cid, error = %e, "Failed to cache blob");
         // Continue even if caching fails
     }
 
-    Ok((data, content_type, false))
+    Ok((blob.data, blob.content_type, BlobOrigin::Pds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::media::MediaProxyConfig;
+    use crate::taxonomy_client::FakeTaxonomy;
+    use std::sync::Arc;
+
+    const DID: &str = "did:plc:abc";
+    const CID: &str = "bafkreiabc";
+
+    /// A media cache in its own temporary directory, which must outlive it.
+    async fn media(
+        signing_secret: Option<&str>,
+        warm_secret: Option<&str>,
+    ) -> (Arc<MediaCache>, tempfile::TempDir) {
+        let dir = tempfile::tempdir().expect("should create temp directory");
+        let media = MediaCache::new(MediaProxyConfig {
+            cache_dir: dir.path().to_path_buf(),
+            max_cache_size: 1024 * 1024,
+            cache_ttl_secs: 60,
+            signing_secret: signing_secret.map(str::to_string),
+            cdn_fallback: None,
+            warm_secret: warm_secret.map(str::to_string),
+        })
+        .await;
+        (media, dir)
+    }
+
+    fn unsigned() -> SignedMediaParams {
+        SignedMediaParams {
+            exp: None,
+            sig: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn without_a_signer_private_blobs_are_not_found() {
+        let (state, _media_dir) = AppState::for_tests(Arc::new(FakeTaxonomy::new(vec![]))).await;
+        let did = Did::new_owned(DID).unwrap();
+        let key = file_blob_cache::BlobCache::cache_key(DID, CID);
+        state.media.privacy.insert(key, true).await;
+
+        let resp = authorize(&state, &did, CID, &unsigned())
+            .await
+            .expect_err("private blob without a signer");
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn without_a_signer_public_blobs_are_served() {
+        let (state, _media_dir) = AppState::for_tests(Arc::new(FakeTaxonomy::new(vec![]))).await;
+        let did = Did::new_owned(DID).unwrap();
+        let key = file_blob_cache::BlobCache::cache_key(DID, CID);
+        state.media.privacy.insert(key, false).await;
+
+        let private = authorize(&state, &did, CID, &unsigned()).await;
+        assert!(matches!(private, Ok(false)));
+    }
+
+    #[tokio::test]
+    async fn without_a_signer_a_failed_lookup_is_not_found() {
+        // The test pool points at a closed port, so the privacy lookup
+        // fails and is treated as private.
+        let (state, _media_dir) = AppState::for_tests(Arc::new(FakeTaxonomy::new(vec![]))).await;
+        let did = Did::new_owned(DID).unwrap();
+
+        let resp = authorize(&state, &did, CID, &unsigned())
+            .await
+            .expect_err("possibly-private blob without a signer");
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn with_a_signer_a_failed_lookup_needs_a_signature() {
+        let (mut state, _media_dir) =
+            AppState::for_tests(Arc::new(FakeTaxonomy::new(vec![]))).await;
+        let (cache, _cache_dir) = media(Some("secret"), None).await;
+        state.media = cache;
+        let did = Did::new_owned(DID).unwrap();
+
+        let resp = authorize(&state, &did, CID, &unsigned())
+            .await
+            .expect_err("unsigned request for a possibly-private blob");
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    fn owner() -> AuthUser {
+        AuthUser {
+            did: DID.to_string(),
+            session_id: "device".to_string(),
+        }
+    }
+
+    fn mark(cids: &[&str]) -> Json<MarkPrivateRequest> {
+        Json(MarkPrivateRequest {
+            cids: cids.iter().map(|c| c.to_string()).collect(),
+        })
+    }
+
+    #[tokio::test]
+    async fn marking_private_needs_a_signer() {
+        let (state, _media_dir) = AppState::for_tests(Arc::new(FakeTaxonomy::new(vec![]))).await;
+        let cid = "bafkreigh2akiscaildcqabsyg3dfr6chu3fgpregiymsck7e7aqa4s52zy";
+
+        let err = mark_private(State(state), owner(), mark(&[cid]))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::NotFound(_)), "{err:?}");
+    }
+
+    #[tokio::test]
+    async fn marking_private_checks_cids_before_writing() {
+        let (mut state, _media_dir) =
+            AppState::for_tests(Arc::new(FakeTaxonomy::new(vec![]))).await;
+        let (cache, _cache_dir) = media(Some("secret"), None).await;
+        state.media = cache;
+
+        // The test pool points at a closed port, so reaching the insert
+        // would be a database error rather than a bad request.
+        for cids in [&[][..], &["not-a-cid"][..]] {
+            let err = mark_private(State(state.clone()), owner(), mark(cids))
+                .await
+                .unwrap_err();
+            assert!(matches!(err, AppError::BadRequest(_)), "{err:?}");
+        }
+    }
+
+    #[test]
+    fn cdn_fallback_copies_are_cached_briefly() {
+        assert_eq!(
+            cache_control(false, BlobOrigin::Pds),
+            "public, max-age=31536000, immutable"
+        );
+        assert_eq!(
+            cache_control(false, BlobOrigin::Cache),
+            "public, max-age=31536000, immutable"
+        );
+        assert_eq!(cache_control(false, BlobOrigin::Cdn), "public, max-age=300");
+        assert_eq!(cache_control(true, BlobOrigin::Cdn), "private, no-store");
+    }
+
+    #[test]
+    fn an_unresolvable_did_is_a_bad_request() {
+        let invalid = BlobResolverError::InvalidDid("unsupported DID method".into());
+        assert_eq!(
+            fetch_error_response(&invalid).status(),
+            StatusCode::BAD_REQUEST
+        );
+        let missing = BlobResolverError::BlobFetch(404);
+        assert_eq!(
+            fetch_error_response(&missing).status(),
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    fn bearer(token: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            format!("Bearer {token}").parse().unwrap(),
+        );
+        headers
+    }
+
+    fn no_blobs() -> Json<WarmRequest> {
+        Json(WarmRequest { blobs: vec![] })
+    }
+
+    #[tokio::test]
+    async fn warm_is_disabled_without_a_secret() {
+        let (state, _media_dir) = AppState::for_tests(Arc::new(FakeTaxonomy::new(vec![]))).await;
+
+        let resp = warm(State(state), bearer("anything"), no_blobs()).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn warm_requires_the_bearer_secret() {
+        let (mut state, _media_dir) =
+            AppState::for_tests(Arc::new(FakeTaxonomy::new(vec![]))).await;
+        let (cache, _cache_dir) = media(None, Some("warm-secret")).await;
+        state.media = cache;
+
+        let resp = warm(State(state.clone()), HeaderMap::new(), no_blobs()).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        let resp = warm(State(state.clone()), bearer("wrong"), no_blobs()).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        let resp = warm(State(state), bearer("warm-secret"), no_blobs()).await;
+        assert_eq!(resp.status(), StatusCode::ACCEPTED);
+    }
+
+    #[tokio::test]
+    async fn warm_is_refused_while_every_permit_is_held() {
+        let (mut state, _media_dir) =
+            AppState::for_tests(Arc::new(FakeTaxonomy::new(vec![]))).await;
+        let (cache, _cache_dir) = media(None, Some("warm-secret")).await;
+        state.media = cache;
+        let permits = state.media.warm_permits.available_permits() as u32;
+        let _held = state
+            .media
+            .warm_permits
+            .clone()
+            .acquire_many_owned(permits)
+            .await
+            .unwrap();
+
+        let resp = warm(State(state), bearer("warm-secret"), no_blobs()).await;
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
 }
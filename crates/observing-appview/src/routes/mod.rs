@@ -6,9 +6,11 @@ pub mod health;
 pub mod identifications;
 pub mod interactions;
 pub mod likes;
+pub mod live;
 pub mod media;
 pub mod notifications;
 pub mod oauth;
+pub mod observers;
 pub mod occurrences;
 pub mod preferences;
 pub mod profiles;
@@ -7,7 +7,6 @@ use axum::Json;
 use serde::{Deserialize, Serialize};
 
 use crate::auth::AuthUser;
-use crate::constants;
 use crate::error::AppError;
 use crate::responses::{SuccessResponse, UnreadCountResponse};
 use crate::state::AppState;
@@ -66,10 +65,7 @@ pub async fn list(
     user: AuthUser,
     Query(params): Query<ListParams>,
 ) -> Result<Json<NotificationListResponse>, AppError> {
-    let limit = params
-        .limit
-        .unwrap_or(constants::DEFAULT_NOTIFICATION_LIMIT)
-        .min(constants::MAX_NOTIFICATION_LIMIT);
+    let limit = state.page_limits.notifications.resolve(params.limit);
     let cursor = params.cursor.and_then(|c| c.parse::<i64>().ok());
 
     let rows = observing_db::notifications::list(&state.pool, &user.did, limit, cursor).await?;
@@ -1,16 +1,52 @@
 use axum::extract::{Query, State};
-use axum::http::StatusCode;
-use axum::response::{IntoResponse, Redirect, Response};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{AppendHeaders, IntoResponse, Redirect, Response};
 use axum::Json;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
+use crate::auth::{self, AuthUser};
 use crate::error::AppError;
 use crate::state::AppState;
 
 const SESSION_MAX_AGE_SECS: i64 = 14 * 24 * 60 * 60;
 
+/// `Set-Cookie` value for one of the session cookies. `max_age` of 0 clears
+/// it.
+///
+/// SameSite=None lets the cookie be sent on cross-site requests, which is
+/// required when the Capacitor mobile app (origin https://localhost) calls
+/// the appview at observ.ing. Browsers require Secure for SameSite=None, so
+/// we only set both in production (HTTPS). In local dev we omit both — the
+/// browser then treats the cookie as default Lax, which is fine for
+/// same-origin use.
+fn session_cookie(name: &str, value: &str, max_age: i64, secure: bool) -> String {
+    let attrs = if secure {
+        "; Secure; SameSite=None"
+    } else {
+        ""
+    };
+    format!("{name}={value}; HttpOnly; Path=/; Max-Age={max_age}{attrs}")
+}
+
+/// `Set-Cookie` headers that clear both session cookies. `secure` must match
+/// how they were set: a clearing cookie without `SameSite=None` can be
+/// ignored on the cross-site responses the mobile app receives.
+fn clear_session_cookies(secure: bool) -> AppendHeaders<[(header::HeaderName, String); 2]> {
+    AppendHeaders([
+        (
+            header::SET_COOKIE,
+            session_cookie("session_did", "", 0, secure),
+        ),
+        (
+            header::SET_COOKIE,
+            session_cookie("session_id", "", 0, secure),
+        ),
+    ])
+}
+
 #[derive(Deserialize)]
 pub struct LoginParams {
     handle: Option<String>,
@@ -68,6 +104,7 @@ pub struct CallbackParams {
 pub async fn callback(
     State(state): State<AppState>,
     Query(params): Query<CallbackParams>,
+    headers: HeaderMap,
 ) -> Response {
     info!("OAuth callback received");
 
@@ -85,24 +122,50 @@ pub async fn callback(
                     let did_str = did.to_string();
                     info!(did = %did_str, "OAuth callback successful");
 
-                    // SameSite=None lets the cookie be sent on cross-site
-                    // requests, which is required when the Capacitor mobile
-                    // app (origin https://localhost) calls the appview at
-                    // observ.ing. Browsers require Secure for SameSite=None,
-                    // so we only set both in production (HTTPS). In local
-                    // dev we omit both — the browser then treats the cookie
-                    // as default Lax, which is fine for same-origin use.
-                    let attrs = if state.public_url.is_some() {
-                        "; Secure; SameSite=None"
-                    } else {
-                        ""
+                    let secure = state.public_url.is_some();
+                    let user_agent = headers
+                        .get(header::USER_AGENT)
+                        .and_then(|v| v.to_str().ok());
+                    // Device sessions are what logout and logout-all revoke,
+                    // so a login that can't record one is refused.
+                    let session_id = match observing_db::oauth::create_device_session(
+                        &state.pool,
+                        &did_str,
+                        user_agent,
+                    )
+                    .await
+                    {
+                        Ok(id) => id,
+                        Err(e) => {
+                            error!(error = %e, "Failed to record device session");
+                            return (
+                                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                                "Authentication failed",
+                            )
+                                .into_response();
+                        }
                     };
-                    let cookie = format!(
-                        "session_did={}; HttpOnly; Path=/; Max-Age={}{}",
-                        did_str, SESSION_MAX_AGE_SECS, attrs,
-                    );
                     (
-                        [(axum::http::header::SET_COOKIE, cookie)],
+                        AppendHeaders([
+                            (
+                                header::SET_COOKIE,
+                                session_cookie(
+                                    "session_did",
+                                    &did_str,
+                                    SESSION_MAX_AGE_SECS,
+                                    secure,
+                                ),
+                            ),
+                            (
+                                header::SET_COOKIE,
+                                session_cookie(
+                                    "session_id",
+                                    &session_id,
+                                    SESSION_MAX_AGE_SECS,
+                                    secure,
+                                ),
+                            ),
+                        ]),
                         Redirect::to("/?just-authed=1"),
                     )
                         .into_response()
@@ -129,22 +192,96 @@ pub async fn callback(
 }
 
 /// POST /oauth/logout
-/// Clears session cookie and returns { success: true }.
-pub async fn logout(cookies: axum_extra::extract::CookieJar) -> Response {
-    let did = cookies.get("session_did").map(|c| c.value().to_string());
+/// Ends this device's session, clears the session cookies and returns
+/// { success: true }.
+pub async fn logout(
+    State(state): State<AppState>,
+    cookies: axum_extra::extract::CookieJar,
+) -> Response {
+    let did = auth::session_did(&cookies);
     if let Some(ref did) = did {
         info!(did = %did, "Logout");
     }
+    if let Some(id) = auth::session_id(&cookies) {
+        if let Err(e) = observing_db::oauth::delete_device_session(&state.pool, &id).await {
+            warn!(error = %e, "Failed to delete device session on logout");
+        }
+    }
 
-    // Clear the cookie
-    let cookie = "session_did=; HttpOnly; Path=/; Max-Age=0";
+    let secure = state.public_url.is_some();
     (
-        [(axum::http::header::SET_COOKIE, cookie)],
+        clear_session_cookies(secure),
         Json(json!({ "success": true })),
     )
         .into_response()
 }
 
+/// One of the viewer's logged-in devices.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceSessionInfo {
+    /// Whether this is the device making the request.
+    current: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    user_agent: Option<String>,
+    created_at: DateTime<Utc>,
+    last_used_at: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+pub struct SessionsResponse {
+    sessions: Vec<DeviceSessionInfo>,
+}
+
+impl SessionsResponse {
+    /// Session ids are bearer credentials, so they're reduced to a
+    /// `current` flag rather than returned.
+    fn new(sessions: Vec<observing_db::oauth::DeviceSession>, current: &str) -> Self {
+        Self {
+            sessions: sessions
+                .into_iter()
+                .map(|s| DeviceSessionInfo {
+                    current: s.id == current,
+                    user_agent: s.user_agent,
+                    created_at: s.created_at,
+                    last_used_at: s.last_used_at,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// GET /oauth/sessions
+/// Lists the viewer's logged-in devices, most recently used first.
+pub async fn sessions(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<SessionsResponse>, AppError> {
+    let sessions = observing_db::oauth::list_device_sessions(&state.pool, &user.did).await?;
+    Ok(Json(SessionsResponse::new(sessions, &user.session_id)))
+}
+
+/// POST /oauth/logout-all
+/// Ends every device session for the viewer and drops the stored OAuth
+/// tokens, so all devices must sign in again. Returns { success: true, revoked }.
+pub async fn logout_all(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Response, AppError> {
+    let mut tx = state.pool.begin().await?;
+    let revoked = observing_db::oauth::delete_device_sessions(&mut *tx, &user.did).await?;
+    observing_db::oauth::delete_session(&mut *tx, &user.did).await?;
+    tx.commit().await?;
+    info!(did = %user.did, revoked, "Logged out everywhere");
+
+    let secure = state.public_url.is_some();
+    Ok((
+        clear_session_cookies(secure),
+        Json(json!({ "success": true, "revoked": revoked })),
+    )
+        .into_response())
+}
+
 /// GET /oauth/client-metadata.json
 /// Serves OAuth client metadata for AT Protocol authorization servers.
 pub async fn client_metadata(State(state): State<AppState>) -> Response {
@@ -189,11 +326,21 @@ pub async fn me(
     State(state): State<AppState>,
     cookies: axum_extra::extract::CookieJar,
 ) -> Result<Json<MeResponse>, AppError> {
-    let did = match cookies.get("session_did") {
-        Some(c) => c.value().to_string(),
+    let did = match auth::session_did(&cookies) {
+        Some(did) => did,
         None => return Ok(Json(MeResponse { user: None })),
     };
 
+    // A device logged out elsewhere (logout-all) stays logged out even once
+    // the user signs in again on another device. Cookies from before device
+    // sessions existed count as logged out.
+    let Some(id) = auth::session_id(&cookies) else {
+        return Ok(Json(MeResponse { user: None }));
+    };
+    if !observing_db::oauth::touch_device_session(&state.pool, &id, &did).await? {
+        return Ok(Json(MeResponse { user: None }));
+    }
+
     let did_parsed = match atrium_api::types::string::Did::new(did.clone()) {
         Ok(d) => d,
         Err(_) => return Ok(Json(MeResponse { user: None })),
@@ -239,3 +386,81 @@ pub async fn me(
         }),
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use observing_db::oauth::DeviceSession;
+
+    fn device(id: &str, user_agent: &str) -> DeviceSession {
+        DeviceSession {
+            id: id.to_string(),
+            did: "did:plc:abc".to_string(),
+            user_agent: Some(user_agent.to_string()),
+            created_at: Utc::now(),
+            last_used_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn session_list_flags_only_the_current_device() {
+        let sessions = vec![
+            device("phone", "Mobile Safari"),
+            device("laptop", "Firefox"),
+        ];
+        let json = serde_json::to_value(SessionsResponse::new(sessions, "laptop")).unwrap();
+        let sessions = json["sessions"].as_array().unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0]["current"], false);
+        assert_eq!(sessions[0]["userAgent"], "Mobile Safari");
+        assert_eq!(sessions[1]["current"], true);
+        // Ids are credentials and never leave the server.
+        assert!(sessions.iter().all(|s| s.get("id").is_none()));
+    }
+
+    #[test]
+    fn session_cookie_is_cross_site_only_when_secure() {
+        assert_eq!(
+            session_cookie("session_id", "abc", 60, true),
+            "session_id=abc; HttpOnly; Path=/; Max-Age=60; Secure; SameSite=None"
+        );
+        assert_eq!(
+            session_cookie("session_id", "abc", 60, false),
+            "session_id=abc; HttpOnly; Path=/; Max-Age=60"
+        );
+    }
+
+    fn cleared_cookies(secure: bool) -> Vec<String> {
+        let response = (clear_session_cookies(secure), "ok").into_response();
+        response
+            .headers()
+            .get_all(header::SET_COOKIE)
+            .iter()
+            .map(|v| v.to_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn logout_clears_both_session_cookies() {
+        assert_eq!(
+            cleared_cookies(false),
+            [
+                "session_did=; HttpOnly; Path=/; Max-Age=0",
+                "session_id=; HttpOnly; Path=/; Max-Age=0",
+            ]
+        );
+    }
+
+    #[test]
+    fn secure_logout_clears_the_cross_site_cookies() {
+        // Same attributes as the cookies set at login in production, so the
+        // clearing cookies also reach the Capacitor app.
+        assert_eq!(
+            cleared_cookies(true),
+            [
+                "session_did=; HttpOnly; Path=/; Max-Age=0; Secure; SameSite=None",
+                "session_id=; HttpOnly; Path=/; Max-Age=0; Secure; SameSite=None",
+            ]
+        );
+    }
+}
@@ -0,0 +1,66 @@
+use axum::extract::{Query, State};
+use axum::Json;
+use observing_db::types::{NearbyArea, NearbyObserversOptions};
+use serde::Deserialize;
+
+use crate::auth::session_did;
+use crate::constants;
+use crate::enrichment;
+use crate::error::AppError;
+use crate::responses::NearbyObserversResponse;
+use crate::state::AppState;
+
+#[derive(Deserialize)]
+pub struct NearbyObserversParams {
+    lat: Option<f64>,
+    lng: Option<f64>,
+    radius: Option<f64>,
+    days: Option<i64>,
+    limit: Option<i64>,
+}
+
+/// Other people recording near a point recently, for finding local
+/// observers to follow. The signed-in viewer is left out of their own list.
+pub async fn get_nearby(
+    State(state): State<AppState>,
+    cookies: axum_extra::extract::CookieJar,
+    Query(params): Query<NearbyObserversParams>,
+) -> Result<Json<NearbyObserversResponse>, AppError> {
+    let lat = params
+        .lat
+        .ok_or_else(|| AppError::BadRequest("lat is required".into()))?;
+    let lng = params
+        .lng
+        .ok_or_else(|| AppError::BadRequest("lng is required".into()))?;
+    let radius = params
+        .radius
+        .unwrap_or(constants::DEFAULT_NEARBY_RADIUS)
+        .clamp(0.0, constants::MAX_NEARBY_OBSERVERS_RADIUS);
+    let window_days = params
+        .days
+        .unwrap_or(constants::DEFAULT_NEARBY_OBSERVERS_WINDOW_DAYS)
+        .clamp(1, constants::MAX_NEARBY_OBSERVERS_WINDOW_DAYS);
+    let limit = state.page_limits.nearby_observers.resolve(params.limit);
+
+    let options = NearbyObserversOptions {
+        area: NearbyArea {
+            lat,
+            lng,
+            radius_meters: radius,
+        },
+        window: chrono::Duration::days(window_days),
+        exclude_did: session_did(&cookies),
+        limit,
+    };
+    let rows =
+        observing_db::observers::nearby_observers(&state.read_pool, &options, &state.hidden_dids)
+            .await?;
+
+    let observers = enrichment::enrich_nearby_observers(&*state.resolver, &rows).await;
+
+    Ok(Json(NearbyObserversResponse {
+        observers,
+        radius,
+        window_days,
+    }))
+}
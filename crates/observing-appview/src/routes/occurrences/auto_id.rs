@@ -1,18 +1,50 @@
 use jacquard_common::deps::smol_str::SmolStr;
 use jacquard_common::types::collection::Collection;
 use jacquard_common::types::string::UriValue;
+use observing_db::community_ids::TaxonomicHierarchy;
 use observing_lexicons::bio_lexicons::temp::v0_1::identification::{
     Identification, IdentificationRecord, IdentificationTaxonRank,
 };
+use serde::Serialize;
 use serde_json::Value;
 
 use crate::auth;
 use crate::error::AppError;
 use crate::state::AppState;
 
-/// Build an identification record value for a given scientific name.
+/// Taxonomy an auto-created identification will carry, after GBIF validation.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedTaxon {
+    pub scientific_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub taxon_rank: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kingdom: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub taxon_id: Option<String>,
+}
+
+/// Build an identification record value for an already-resolved taxon (see
+/// [`resolve_taxon`]). Returns the record JSON value ready to be posted via
+/// the agent.
+pub fn build_identification_record(
+    taxon: &ResolvedTaxon,
+    occurrence_uri: &str,
+    occurrence_cid: &str,
+) -> Result<Value, AppError> {
+    assemble_identification_record(
+        &taxon.scientific_name,
+        taxon.taxon_rank.as_deref(),
+        taxon.kingdom.as_deref(),
+        taxon.taxon_id.as_deref(),
+        occurrence_uri,
+        occurrence_cid,
+    )
+}
+
+/// Resolve the taxonomy fields for an identification of `scientific_name`.
 ///
-/// Validates taxonomy and constructs the AT Protocol identification record.
 /// `user_taxon_rank` is used only when taxonomy validation cannot resolve a
 /// rank — taxonomy is authoritative for known taxa. `user_kingdom` is
 /// forwarded to GBIF as a disambiguator and used as a fallback when
@@ -20,16 +52,13 @@ use crate::state::AppState;
 /// hint). `user_taxon_id` is the stable taxon URI from the user's GBIF
 /// autocomplete pick; it takes priority over the URI validation resolves,
 /// since the user's selection is the authoritative match.
-/// Returns the record JSON value ready to be posted via the agent.
-pub async fn build_identification_record(
+pub async fn resolve_taxon(
     state: &AppState,
     scientific_name: &str,
     user_taxon_rank: Option<&str>,
     user_kingdom: Option<&str>,
     user_taxon_id: Option<&str>,
-    occurrence_uri: &str,
-    occurrence_cid: &str,
-) -> Result<Value, AppError> {
+) -> ResolvedTaxon {
     let mut taxon_rank = None;
     let mut kingdom = None;
     let mut taxon_id = user_taxon_id.map(str::to_owned);
@@ -51,14 +80,28 @@ pub async fn build_identification_record(
         kingdom = user_kingdom.map(str::to_owned);
     }
 
-    assemble_identification_record(
-        scientific_name,
-        taxon_rank.as_deref(),
-        kingdom.as_deref(),
-        taxon_id.as_deref(),
-        occurrence_uri,
-        occurrence_cid,
-    )
+    ResolvedTaxon {
+        scientific_name: scientific_name.to_string(),
+        taxon_rank,
+        kingdom,
+        taxon_id,
+    }
+}
+
+/// Whether a taxon at `taxon_rank` is specific enough for an auto-created
+/// identification under the request's `min_rank` floor.
+///
+/// No floor always passes. With a floor, a missing or unrecognized rank
+/// fails: the identification is only created when the rank is known to be
+/// at least as specific as the floor.
+pub fn meets_rank_floor(taxon_rank: Option<&str>, min_rank: Option<&str>) -> bool {
+    let Some(min_rank) = min_rank else {
+        return true;
+    };
+    taxon_rank.is_some_and(|rank| {
+        TaxonomicHierarchy::is_known_rank(rank)
+            && TaxonomicHierarchy::rank_level(rank) <= TaxonomicHierarchy::rank_level(min_rank)
+    })
 }
 
 /// Assemble the AT Protocol identification record JSON from already-resolved
@@ -182,4 +225,29 @@ mod tests {
             value.get("taxonID")
         );
     }
+
+    #[test]
+    fn no_rank_floor_always_passes() {
+        assert!(meets_rank_floor(Some("genus"), None));
+        assert!(meets_rank_floor(None, None));
+    }
+
+    /// A species-level floor accepts species and finer, but not a genus —
+    /// a user unsure of the species gets no identification committed.
+    #[test]
+    fn rank_floor_rejects_coarser_taxa() {
+        assert!(meets_rank_floor(Some("species"), Some("species")));
+        assert!(meets_rank_floor(Some("subspecies"), Some("species")));
+        assert!(meets_rank_floor(Some("SPECIES"), Some("species")));
+        assert!(!meets_rank_floor(Some("genus"), Some("species")));
+        assert!(meets_rank_floor(Some("genus"), Some("family")));
+    }
+
+    /// With a floor set, a rank that can't be placed in the hierarchy isn't
+    /// assumed to clear it.
+    #[test]
+    fn rank_floor_rejects_unknown_rank() {
+        assert!(!meets_rank_floor(None, Some("species")));
+        assert!(!meets_rank_floor(Some("form"), Some("species")));
+    }
 }
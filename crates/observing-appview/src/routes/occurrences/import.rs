@@ -0,0 +1,564 @@
+//! `POST /api/occurrences/import` — bulk occurrence creation from a Darwin
+//! Core CSV.
+//!
+//! Every row goes through the same checks and PDS write as
+//! [`super::write::create_occurrence`] (without media). Rows are validated
+//! up front; the PDS writes then run one at a time, paced by
+//! [`constants::IMPORT_ROW_INTERVAL`], and each row's outcome is streamed
+//! back as a line of NDJSON followed by a final summary line. Each account
+//! runs one import at a time (see [`ActiveImports`]).
+
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, PoisonError};
+use tracing::{info, warn};
+
+use crate::auth::{self, AuthUser};
+use crate::config::CoordinateChecks;
+use crate::constants;
+use crate::error::AppError;
+use crate::state::{AgentType, AppState};
+
+use super::write::{
+    check_create_request, finish_occurrence, parse_form_field, publish_occurrence,
+    CreateOccurrenceRequest,
+};
+
+/// Darwin Core column → `CreateOccurrenceRequest` field, for the text fields.
+/// Columns not listed here (and not numeric, below) are ignored, so a full
+/// DwC export can be uploaded as-is.
+const TEXT_COLUMNS: &[(&str, &str)] = &[
+    ("eventDate", "eventDate"),
+    ("scientificName", "scientificName"),
+    ("taxonRank", "taxonRank"),
+    ("kingdom", "kingdom"),
+    ("taxonID", "taxonId"),
+    ("organismQuantity", "organismQuantity"),
+    ("organismQuantityType", "organismQuantityType"),
+    ("license", "license"),
+];
+
+/// Outcome of one imported row, streamed as it completes.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ImportRowResult {
+    /// 1-based index of the data row (the header isn't counted).
+    row: usize,
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    /// Set when the record was created but a follow-up step (the automatic
+    /// identification) failed. The row still counts as created.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    warning: Option<String>,
+}
+
+/// A row whose record reached the PDS.
+struct ImportedRow {
+    uri: String,
+    cid: String,
+    warning: Option<String>,
+}
+
+/// Final line of the import stream.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ImportSummary {
+    done: bool,
+    created: usize,
+    failed: usize,
+}
+
+/// DIDs with an import in progress, so an account can't run several at once
+/// and multiply the load on its PDS.
+#[derive(Clone, Default)]
+pub struct ActiveImports(Arc<Mutex<HashSet<String>>>);
+
+impl ActiveImports {
+    /// Claim the import slot for `did`, or `None` if it already has one.
+    fn start(&self, did: &str) -> Option<ImportSlot> {
+        let mut active = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        active.insert(did.to_string()).then(|| ImportSlot {
+            imports: self.clone(),
+            did: did.to_string(),
+        })
+    }
+}
+
+/// A DID's claim on [`ActiveImports`], released on drop: when the import
+/// stream finishes, or is dropped because the client went away.
+struct ImportSlot {
+    imports: ActiveImports,
+    did: String,
+}
+
+impl Drop for ImportSlot {
+    fn drop(&mut self) {
+        self.imports
+            .0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&self.did);
+    }
+}
+
+/// POST /api/occurrences/import — create one occurrence per CSV row.
+///
+/// The body is a CSV whose header row names Darwin Core terms
+/// (`decimalLatitude` and `decimalLongitude` are required). A malformed
+/// file, or one over [`constants::MAX_IMPORT_ROWS`] rows, is rejected
+/// outright, and a request made while the same account's previous import is
+/// still running gets a 429. Otherwise the response is
+/// `application/x-ndjson` with one [`ImportRowResult`] per row and an
+/// [`ImportSummary`] at the end. A bad row is reported and skipped without
+/// stopping the import.
+pub async fn import_occurrences(
+    State(state): State<AppState>,
+    user: AuthUser,
+    body: String,
+) -> Result<Response, AppError> {
+    let slot = state.imports.start(&user.did).ok_or_else(|| {
+        AppError::TooManyRequests("An import is already running for this account".into())
+    })?;
+    let rows = prepare_import(&body, state.coordinate_checks)?;
+    let (agent, did) = auth::require_agent(&state.oauth_client, &user.did).await?;
+
+    info!(did = %user.did, rows = rows.len(), "Starting occurrence import");
+
+    let run = ImportRun {
+        _slot: slot,
+        state,
+        agent,
+        did,
+        user_did: user.did,
+        rows: rows.into_iter().enumerate(),
+        created: 0,
+        failed: 0,
+        published_any: false,
+        finished: false,
+    };
+    let lines = futures::stream::unfold(run, ImportRun::next_line);
+
+    Ok((
+        [(header::CONTENT_TYPE, "application/x-ndjson")],
+        Body::from_stream(lines),
+    )
+        .into_response())
+}
+
+/// Parse and validate an import body, up to the point of writing to the
+/// PDS. Each entry is the row's request, or the reason it can't be imported.
+fn prepare_import(
+    csv: &str,
+    checks: CoordinateChecks,
+) -> Result<Vec<Result<CreateOccurrenceRequest, String>>, AppError> {
+    let mut records = parse_csv(csv).map_err(AppError::BadRequest)?.into_iter();
+    let header = records
+        .next()
+        .ok_or_else(|| AppError::BadRequest("CSV is empty".into()))?;
+    for required in ["decimalLatitude", "decimalLongitude"] {
+        if !header.iter().any(|column| column.trim() == required) {
+            return Err(AppError::BadRequest(format!(
+                "CSV is missing the {required} column"
+            )));
+        }
+    }
+
+    let rows: Vec<Vec<String>> = records
+        .filter(|record| record.iter().any(|field| !field.trim().is_empty()))
+        .collect();
+    if rows.is_empty() {
+        return Err(AppError::BadRequest("CSV has no data rows".into()));
+    }
+    if rows.len() > constants::MAX_IMPORT_ROWS {
+        return Err(AppError::BadRequest(format!(
+            "CSV has {} rows; imports are limited to {}",
+            rows.len(),
+            constants::MAX_IMPORT_ROWS
+        )));
+    }
+
+    Ok(rows
+        .iter()
+        .map(|row| row_request(&header, row, checks).map_err(row_error))
+        .collect())
+}
+
+/// Build and check the create request for one CSV row.
+fn row_request(
+    header: &[String],
+    row: &[String],
+    checks: CoordinateChecks,
+) -> Result<CreateOccurrenceRequest, AppError> {
+    let mut fields = Map::new();
+    for (column, value) in header.iter().zip(row) {
+        let (column, value) = (column.trim(), value.trim());
+        if value.is_empty() {
+            continue;
+        }
+        let field = match column {
+            "decimalLatitude" => ("latitude", number(value, column)?),
+            "decimalLongitude" => ("longitude", number(value, column)?),
+            "coordinateUncertaintyInMeters" => (
+                "coordinateUncertaintyInMeters",
+                parse_form_field::<i32>(Some(value.to_string()), column)?.into(),
+            ),
+            _ => match TEXT_COLUMNS.iter().find(|(dwc, _)| *dwc == column) {
+                Some((_, name)) => (*name, value.into()),
+                None => continue,
+            },
+        };
+        fields.insert(field.0.to_string(), field.1);
+    }
+    for (name, column) in [
+        ("latitude", "decimalLatitude"),
+        ("longitude", "decimalLongitude"),
+    ] {
+        if !fields.contains_key(name) {
+            return Err(AppError::BadRequest(format!("Missing {column}")));
+        }
+    }
+
+    let request: CreateOccurrenceRequest = serde_json::from_value(Value::Object(fields))
+        .map_err(|e| AppError::BadRequest(format!("Invalid row: {e}")))?;
+    check_create_request(&request, checks)?;
+    Ok(request)
+}
+
+fn number(value: &str, column: &str) -> Result<Value, AppError> {
+    let n = parse_form_field::<f64>(Some(value.to_string()), column)?;
+    Ok(n.into())
+}
+
+/// The message reported for a failed row. Internal details stay in the
+/// logs, as they would for a single create.
+fn row_error(e: AppError) -> String {
+    match e {
+        AppError::BadRequest(msg)
+        | AppError::NotFound(msg)
+        | AppError::Forbidden(msg)
+        | AppError::Conflict(msg)
+        | AppError::UnprocessableEntity(msg)
+        | AppError::ServiceUnavailable(msg)
+        | AppError::BadGateway(msg)
+        | AppError::TooManyRequests(msg) => msg,
+        AppError::Unauthorized => "Authentication required".into(),
+        AppError::Internal(msg) => {
+            warn!(error = %msg, "Occurrence import row failed");
+            "Failed to create occurrence".into()
+        }
+        AppError::Database(e) => {
+            warn!(error = %e, "Occurrence import row failed");
+            "Failed to create occurrence".into()
+        }
+    }
+}
+
+/// State threaded through the import stream.
+struct ImportRun {
+    /// Held until the stream is dropped.
+    _slot: ImportSlot,
+    state: AppState,
+    agent: AgentType,
+    did: atrium_api::types::string::Did,
+    user_did: String,
+    rows: std::iter::Enumerate<std::vec::IntoIter<Result<CreateOccurrenceRequest, String>>>,
+    created: usize,
+    failed: usize,
+    published_any: bool,
+    finished: bool,
+}
+
+impl ImportRun {
+    /// Process the next row and yield its NDJSON line, then the summary.
+    async fn next_line(mut self) -> Option<(Result<String, std::convert::Infallible>, Self)> {
+        if let Some((index, prepared)) = self.rows.next() {
+            let outcome = match prepared {
+                Ok(request) => self.import_row(&request).await.map_err(row_error),
+                Err(e) => Err(e),
+            };
+            let result = match outcome {
+                Ok(ImportedRow { uri, cid, warning }) => {
+                    self.created += 1;
+                    ImportRowResult {
+                        row: index + 1,
+                        success: true,
+                        uri: Some(uri),
+                        cid: Some(cid),
+                        error: None,
+                        warning,
+                    }
+                }
+                Err(error) => {
+                    self.failed += 1;
+                    ImportRowResult {
+                        row: index + 1,
+                        success: false,
+                        uri: None,
+                        cid: None,
+                        error: Some(error),
+                        warning: None,
+                    }
+                }
+            };
+            return Some((Ok(ndjson_line(&result)), self));
+        }
+
+        if self.finished {
+            return None;
+        }
+        self.finished = true;
+        info!(
+            did = %self.user_did,
+            created = self.created,
+            failed = self.failed,
+            "Finished occurrence import"
+        );
+        let summary = ImportSummary {
+            done: true,
+            created: self.created,
+            failed: self.failed,
+        };
+        Some((Ok(ndjson_line(&summary)), self))
+    }
+
+    /// Publish one row. Once the record is on the PDS the row has been
+    /// created, so a failed follow-up is reported as a warning rather than
+    /// an error that would invite the user to import it again.
+    async fn import_row(
+        &mut self,
+        request: &CreateOccurrenceRequest,
+    ) -> Result<ImportedRow, AppError> {
+        // Space out PDS writes so a large import doesn't hammer the user's
+        // PDS (or trip its own rate limits).
+        if self.published_any {
+            tokio::time::sleep(constants::IMPORT_ROW_INTERVAL).await;
+        }
+        self.published_any = true;
+
+        let (uri, cid) =
+            publish_occurrence(&self.agent, self.did.clone(), request, Vec::new()).await?;
+        let warning = finish_occurrence(
+            &self.state,
+            &self.agent,
+            &self.user_did,
+            request,
+            &uri,
+            &cid,
+        )
+        .await
+        .err()
+        .map(|e| {
+            format!(
+                "Occurrence created, but its identification failed: {}",
+                row_error(e)
+            )
+        });
+        Ok(ImportedRow { uri, cid, warning })
+    }
+}
+
+fn ndjson_line<T: Serialize>(value: &T) -> String {
+    let mut line = serde_json::to_string(value).unwrap_or_default();
+    line.push('\n');
+    line
+}
+
+/// Split CSV text into records of fields (RFC 4180: comma-separated,
+/// double-quoted fields may contain commas, newlines and `""` escapes;
+/// CRLF or LF line endings). A quote opens a quoted field only as the
+/// field's first character; anywhere else, as in `5" pot`, it's literal. A
+/// leading UTF-8 BOM, as spreadsheet exports often write, is skipped.
+fn parse_csv(text: &str) -> Result<Vec<Vec<String>>, String> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut at_field_start = true;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        let field_start = std::mem::replace(&mut at_field_start, false);
+        match c {
+            '"' if field_start => in_quotes = true,
+            ',' => {
+                record.push(std::mem::take(&mut field));
+                at_field_start = true;
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+                at_field_start = true;
+            }
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(format!(
+            "Unterminated quoted field in CSV record {}",
+            records.len() + 1
+        ));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_quoted_fields() {
+        let records =
+            parse_csv("a,b,c\r\n\"x, y\",\"say \"\"hi\"\"\",\"two\nlines\"\n1,,3").unwrap();
+        assert_eq!(
+            records,
+            vec![
+                vec!["a", "b", "c"],
+                vec!["x, y", "say \"hi\"", "two\nlines"],
+                vec!["1", "", "3"],
+            ]
+        );
+    }
+
+    #[test]
+    fn one_import_per_did_at_a_time() {
+        let imports = ActiveImports::default();
+        let slot = imports.start("did:plc:abc").expect("first import starts");
+        assert!(imports.start("did:plc:abc").is_none());
+        assert!(imports.start("did:plc:xyz").is_some());
+
+        drop(slot);
+        assert!(imports.start("did:plc:abc").is_some());
+    }
+
+    #[test]
+    fn quote_inside_an_unquoted_field_is_literal() {
+        let records = parse_csv("size,note\n5\" pot,\"\"\"\"\n12\",x\"y\"\n").unwrap();
+        assert_eq!(
+            records,
+            vec![
+                vec!["size", "note"],
+                vec!["5\" pot", "\""],
+                vec!["12\"", "x\"y\""],
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_unterminated_quote() {
+        assert!(parse_csv("a,b\n\"open,1\n").is_err());
+    }
+
+    #[test]
+    fn partial_success_reports_each_row() {
+        let csv = "\u{feff}occurrenceID,decimalLatitude,decimalLongitude,eventDate,scientificName,taxonID\n\
+                   obs-1,37.77,-122.42,2026-05-01,Quercus agrifolia,https://www.gbif.org/species/2878045\n\
+                   obs-2,137.0,-122.42,2026-05-01,Quercus agrifolia,\n\
+                   obs-3,37.8,-122.4,,,\n";
+
+        let rows = prepare_import(csv, CoordinateChecks::default())
+            .ok()
+            .unwrap();
+        assert_eq!(rows.len(), 3);
+
+        let first = rows[0].as_ref().unwrap();
+        assert_eq!(first.latitude, 37.77);
+        assert_eq!(first.scientific_name.as_deref(), Some("Quercus agrifolia"));
+        assert_eq!(
+            first.taxon_id.as_deref(),
+            Some("https://www.gbif.org/species/2878045")
+        );
+        assert_eq!(rows[1].as_ref().err().unwrap(), "Invalid coordinates");
+        assert!(rows[2].is_ok());
+    }
+
+    #[test]
+    fn reports_unparseable_numbers_per_row() {
+        let rows = prepare_import(
+            "decimalLatitude,decimalLongitude\nnorth,0\n1.5,2.5\n",
+            CoordinateChecks::default(),
+        )
+        .ok()
+        .unwrap();
+        assert_eq!(rows[0].as_ref().err().unwrap(), "Invalid decimalLatitude");
+        assert!(rows[1].is_ok());
+    }
+
+    #[test]
+    fn rejects_missing_coordinate_columns() {
+        assert!(matches!(
+            prepare_import("latitude,longitude\n1,2\n", CoordinateChecks::default()),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_files_over_the_row_cap() {
+        let mut csv = String::from("decimalLatitude,decimalLongitude\n");
+        for _ in 0..=constants::MAX_IMPORT_ROWS {
+            csv.push_str("1,2\n");
+        }
+        assert!(matches!(
+            prepare_import(&csv, CoordinateChecks::default()),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn result_lines_are_ndjson() {
+        let line = ndjson_line(&ImportRowResult {
+            row: 2,
+            success: false,
+            uri: None,
+            cid: None,
+            error: Some("Invalid coordinates".into()),
+            warning: None,
+        });
+        assert_eq!(
+            line,
+            "{\"row\":2,\"success\":false,\"error\":\"Invalid coordinates\"}\n"
+        );
+
+        let line = ndjson_line(&ImportRowResult {
+            row: 3,
+            success: true,
+            uri: Some("at://did:plc:abc/bio.lexicons.temp.v0-1.occurrence/1".into()),
+            cid: Some("bafyrecord".into()),
+            error: None,
+            warning: Some("Occurrence created, but its identification failed".into()),
+        });
+        let value: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["success"], true);
+        assert_eq!(value["cid"], "bafyrecord");
+        assert_eq!(
+            value["warning"],
+            "Occurrence created, but its identification failed"
+        );
+    }
+}
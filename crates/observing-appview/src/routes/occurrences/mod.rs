@@ -1,6 +1,10 @@
 mod auto_id;
+mod import;
 mod read;
 mod write;
 
-pub use read::{get_bbox, get_feed, get_geojson, get_nearby, get_occurrence};
-pub use write::{create_occurrence, delete_occurrence, update_occurrence};
+pub use import::{import_occurrences, ActiveImports};
+pub use read::{get_bbox, get_feed, get_geojson, get_heatmap, get_nearby, get_occurrence};
+pub use write::{
+    create_occurrence, delete_occurrence, patch_occurrence, update_occurrence, validate_occurrence,
+};
@@ -1,15 +1,30 @@
+use atproto_blob_resolver::{BlobResolver, BlobResolverError};
 use axum::extract::{Path, Query, State};
+use axum::response::{IntoResponse, Response};
 use axum::Json;
+use jacquard_common::types::collection::Collection;
+use jacquard_common::types::string::AtUri;
+use moka::future::Cache;
+use observing_db::cursor::FeedCursor;
+use observing_db::types::BoundingBox;
+use observing_lexicons::bio_lexicons::temp::v0_1::occurrence::OccurrenceRecord;
 use serde::Deserialize;
+use std::str::FromStr;
+use tracing::warn;
 
 use crate::auth::session_did;
 use crate::constants;
-use crate::enrichment;
+use crate::enrichment::{self, OccurrenceResponse};
 use crate::error::AppError;
+use crate::response_format::ResponseFormat;
 use crate::responses::{
     BboxBounds, BboxMeta, BboxResponse, GeoJsonFeature, GeoJsonPoint, GeoJsonProperties,
-    GeoJsonResponse, NearbyMeta, NearbyResponse, OccurrenceDetailResponse, OccurrenceListResponse,
+    GeoJsonResponse, HeatmapResponse, LikersResponse, LikesSummary, NearbyMeta, NearbyResponse,
+    OccurrenceDetailResponse, OccurrenceFullResponse, OccurrenceListResponse,
+    SimilarOccurrencesResponse, TaxonHistoryResponse,
 };
+use crate::routes::feeds::optional_bbox;
+use crate::server_timing;
 use crate::state::AppState;
 
 #[derive(Deserialize)]
@@ -24,8 +39,9 @@ pub struct NearbyParams {
 pub async fn get_nearby(
     State(state): State<AppState>,
     cookies: axum_extra::extract::CookieJar,
+    format: ResponseFormat,
     Query(params): Query<NearbyParams>,
-) -> Result<Json<NearbyResponse>, AppError> {
+) -> Result<Response, AppError> {
     let lat = params
         .lat
         .ok_or_else(|| AppError::BadRequest("lat is required".into()))?;
@@ -33,34 +49,35 @@ pub async fn get_nearby(
         .lng
         .ok_or_else(|| AppError::BadRequest("lng is required".into()))?;
     let radius = params.radius.unwrap_or(constants::DEFAULT_NEARBY_RADIUS);
-    let limit = params
-        .limit
-        .unwrap_or(constants::DEFAULT_NEARBY_LIMIT)
-        .min(constants::MAX_NEARBY_LIMIT);
+    let limit = state.page_limits.nearby.resolve(params.limit);
     let offset = params.offset.unwrap_or(0);
 
-    let rows = observing_db::occurrences::get_nearby(
-        &state.pool,
-        lat,
-        lng,
-        radius,
-        limit,
-        offset,
-        &state.hidden_dids,
+    let rows = server_timing::timed(
+        "db",
+        observing_db::occurrences::get_nearby(
+            &state.read_pool,
+            lat,
+            lng,
+            radius,
+            limit,
+            offset,
+            &state.hidden_dids,
+        ),
     )
     .await?;
 
     let viewer = session_did(&cookies);
     let occurrences = enrichment::enrich_occurrences(
-        &state.pool,
-        &state.resolver,
-        &state.taxonomy,
+        &state.read_pool,
+        &*state.resolver,
+        &*state.taxonomy,
+        state.blob_links(),
         &rows,
         viewer.as_deref(),
     )
     .await;
 
-    Ok(Json(NearbyResponse {
+    format.respond(&NearbyResponse {
         meta: NearbyMeta {
             lat,
             lng,
@@ -70,7 +87,7 @@ pub async fn get_nearby(
             count: occurrences.len(),
         },
         occurrences,
-    }))
+    })
 }
 
 #[derive(Deserialize)]
@@ -82,37 +99,44 @@ pub struct FeedParams {
 pub async fn get_feed(
     State(state): State<AppState>,
     cookies: axum_extra::extract::CookieJar,
+    format: ResponseFormat,
     Query(params): Query<FeedParams>,
-) -> Result<Json<OccurrenceListResponse>, AppError> {
-    let limit = params
-        .limit
-        .unwrap_or(constants::DEFAULT_FEED_LIMIT)
-        .min(constants::MAX_FEED_LIMIT);
-
-    let rows = observing_db::occurrences::get_feed(
-        &state.pool,
-        limit,
-        params.cursor.as_deref(),
-        &state.hidden_dids,
+) -> Result<Response, AppError> {
+    let limit = state.page_limits.feed.resolve(params.limit);
+
+    let cursor = params
+        .cursor
+        .as_deref()
+        .map(FeedCursor::decode)
+        .transpose()?;
+    let rows = server_timing::timed(
+        "db",
+        observing_db::occurrences::get_feed(
+            &state.read_pool,
+            limit,
+            cursor.as_ref(),
+            &state.hidden_dids,
+        ),
     )
     .await?;
 
     let viewer = session_did(&cookies);
     let occurrences = enrichment::enrich_occurrences(
-        &state.pool,
-        &state.resolver,
-        &state.taxonomy,
+        &state.read_pool,
+        &*state.resolver,
+        &*state.taxonomy,
+        state.blob_links(),
         &rows,
         viewer.as_deref(),
     )
     .await;
 
-    let next_cursor = occurrences.last().map(|o| o.created_at.clone());
+    let next_cursor = occurrences.last().and_then(|o| o.feed_cursor());
 
-    Ok(Json(OccurrenceListResponse {
+    format.respond(&OccurrenceListResponse {
         occurrences,
         cursor: next_cursor,
-    }))
+    })
 }
 
 #[derive(Deserialize)]
@@ -131,8 +155,9 @@ pub struct BboxParams {
 pub async fn get_bbox(
     State(state): State<AppState>,
     cookies: axum_extra::extract::CookieJar,
+    format: ResponseFormat,
     Query(params): Query<BboxParams>,
-) -> Result<Json<BboxResponse>, AppError> {
+) -> Result<Response, AppError> {
     let min_lat = params
         .min_lat
         .ok_or_else(|| AppError::BadRequest("minLat is required".into()))?;
@@ -145,30 +170,34 @@ pub async fn get_bbox(
     let max_lng = params
         .max_lng
         .ok_or_else(|| AppError::BadRequest("maxLng is required".into()))?;
-    let limit = params.limit.unwrap_or(constants::DEFAULT_BBOX_LIMIT);
+    let limit = state.page_limits.bbox.resolve(params.limit);
 
-    let rows = observing_db::occurrences::get_by_bounding_box(
-        &state.pool,
-        min_lat,
-        min_lng,
-        max_lat,
-        max_lng,
-        limit,
-        &state.hidden_dids,
+    let rows = server_timing::timed(
+        "db",
+        observing_db::occurrences::get_by_bounding_box(
+            &state.read_pool,
+            min_lat,
+            min_lng,
+            max_lat,
+            max_lng,
+            limit,
+            &state.hidden_dids,
+        ),
     )
     .await?;
 
     let viewer = session_did(&cookies);
     let occurrences = enrichment::enrich_occurrences(
-        &state.pool,
-        &state.resolver,
-        &state.taxonomy,
+        &state.read_pool,
+        &*state.resolver,
+        &*state.taxonomy,
+        state.blob_links(),
         &rows,
         viewer.as_deref(),
     )
     .await;
 
-    Ok(Json(BboxResponse {
+    format.respond(&BboxResponse {
         meta: BboxMeta {
             bounds: BboxBounds {
                 min_lat,
@@ -179,7 +208,7 @@ pub async fn get_bbox(
             count: occurrences.len(),
         },
         occurrences,
-    }))
+    })
 }
 
 pub async fn get_geojson(
@@ -200,12 +229,12 @@ pub async fn get_geojson(
         .ok_or_else(|| AppError::BadRequest("maxLng is required".into()))?;
 
     let rows = observing_db::occurrences::get_by_bounding_box(
-        &state.pool,
+        &state.read_pool,
         min_lat,
         min_lng,
         max_lat,
         max_lng,
-        constants::MAX_GEOJSON_LIMIT,
+        state.page_limits.bbox.max,
         &state.hidden_dids,
     )
     .await?;
@@ -238,41 +267,609 @@ pub async fn get_geojson(
     }))
 }
 
+#[derive(Deserialize)]
+pub struct HeatmapParams {
+    #[serde(rename = "minLat")]
+    min_lat: Option<f64>,
+    #[serde(rename = "minLng")]
+    min_lng: Option<f64>,
+    #[serde(rename = "maxLat")]
+    max_lat: Option<f64>,
+    #[serde(rename = "maxLng")]
+    max_lng: Option<f64>,
+    /// Degrees; see [`heatmap_cell_size`].
+    #[serde(rename = "cellSize")]
+    cell_size: Option<f64>,
+}
+
+/// Occurrence density per grid cell for the map overview. The bbox is
+/// optional; without one the grid covers the whole world.
+pub async fn get_heatmap(
+    State(state): State<AppState>,
+    format: ResponseFormat,
+    Query(params): Query<HeatmapParams>,
+) -> Result<Response, AppError> {
+    let bbox = optional_bbox(
+        params.min_lat,
+        params.min_lng,
+        params.max_lat,
+        params.max_lng,
+    )?;
+    let cell_size = heatmap_cell_size(params.cell_size, bbox.as_ref());
+
+    // A world-wide grid touches every occurrence; allowed to outlast the
+    // default statement timeout like the other aggregates.
+    let mut tx = observing_bootstrap::db::begin_with_statement_timeout(
+        &state.read_pool,
+        constants::AGGREGATE_STATEMENT_TIMEOUT,
+    )
+    .await?;
+    let cells = server_timing::timed(
+        "db",
+        observing_db::occurrences::density_grid(
+            &mut *tx,
+            bbox.as_ref(),
+            cell_size,
+            &state.hidden_dids,
+        ),
+    )
+    .await?;
+    tx.commit().await?;
+
+    format.respond(&HeatmapResponse {
+        cell_size,
+        total: cells.iter().map(|c| c.count).sum(),
+        cells,
+    })
+}
+
+/// The requested cell size (or the default), widened so the area holds at
+/// most [`constants::MAX_HEATMAP_CELLS`] cells and capped at
+/// [`constants::MAX_HEATMAP_CELL_SIZE`].
+fn heatmap_cell_size(requested: Option<f64>, bbox: Option<&BoundingBox>) -> f64 {
+    let (width, height) = bbox.map_or((360.0, 180.0), |b| {
+        ((b.max_lng - b.min_lng).abs(), (b.max_lat - b.min_lat).abs())
+    });
+    let smallest = (width * height / constants::MAX_HEATMAP_CELLS).sqrt();
+    requested
+        .filter(|size| size.is_finite() && *size > 0.0)
+        .unwrap_or(constants::DEFAULT_HEATMAP_CELL_SIZE)
+        .max(smallest)
+        .min(constants::MAX_HEATMAP_CELL_SIZE)
+}
+
+#[derive(Deserialize)]
+pub struct SimilarParams {
+    radius: Option<f64>,
+    limit: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct LikersParams {
+    limit: Option<i64>,
+    cursor: Option<String>,
+}
+
+/// `GET /api/occurrences/{uri}`, or `{uri}/full` for [`get_occurrence_full`],
+/// `{uri}/taxon-history`, `{uri}/similar` for [`get_similar`], `{uri}/likes`
+/// for [`get_likers`] or `{uri}/record` for [`get_raw_record`].
+///
+/// The suffixed forms share this route because the URI is a catch-all
+/// segment, so nothing can be routed after it.
 pub async fn get_occurrence(
     State(state): State<AppState>,
     cookies: axum_extra::extract::CookieJar,
+    format: ResponseFormat,
     Path(uri): Path<String>,
-) -> Result<Json<OccurrenceDetailResponse>, AppError> {
-    let row = observing_db::occurrences::get(&state.pool, &uri)
+    Query(similar): Query<SimilarParams>,
+    Query(likers): Query<LikersParams>,
+) -> Result<Response, AppError> {
+    let viewer = session_did(&cookies);
+    if let Some(uri) = strip_view_suffix(&uri, "/full") {
+        return format.respond(&get_occurrence_full(&state, uri, viewer.as_deref()).await?);
+    }
+    if let Some(uri) = strip_view_suffix(&uri, "/taxon-history") {
+        return format.respond(&get_taxon_history(&state, uri).await?);
+    }
+    if let Some(uri) = strip_view_suffix(&uri, "/similar") {
+        return format.respond(&get_similar(&state, uri, &similar, viewer.as_deref()).await?);
+    }
+    if let Some(uri) = strip_view_suffix(&uri, "/likes") {
+        return format.respond(&get_likers(&state, uri, &likers).await?);
+    }
+    if let Some(uri) = strip_view_suffix(&uri, "/record") {
+        // The author's record verbatim, for clients that verify it; always
+        // JSON, like the PDS serves it.
+        let record = get_indexed_raw_record(&state, uri).await?;
+        return Ok(Json(record).into_response());
+    }
+    format.respond(&get_occurrence_detail(&state, &uri, viewer.as_deref()).await?)
+}
+
+/// The occurrence URI from a `{uri}{suffix}` path such as `{uri}/full`. A
+/// URI whose record key is literally `full` is still the plain occurrence:
+/// only a suffix after a complete `at://{did}/{collection}/{rkey}` counts.
+fn strip_view_suffix<'a>(path: &'a str, suffix: &str) -> Option<&'a str> {
+    let uri = path.strip_suffix(suffix)?;
+    let segments = uri.strip_prefix("at://")?.split('/').count();
+    (segments == 3).then_some(uri)
+}
+
+/// Other observations of the occurrence's community-ID taxon near it, falling
+/// back to the rest of its genus. Empty when there's no consensus yet.
+async fn get_similar(
+    state: &AppState,
+    uri: &str,
+    params: &SimilarParams,
+    viewer: Option<&str>,
+) -> Result<SimilarOccurrencesResponse, AppError> {
+    let radius = params
+        .radius
+        .unwrap_or(constants::DEFAULT_SIMILAR_RADIUS)
+        .clamp(0.0, constants::MAX_SIMILAR_RADIUS);
+    let limit = state.page_limits.feed.resolve(params.limit);
+
+    let rows = server_timing::timed(
+        "db",
+        observing_db::feeds::get_similar_occurrences(
+            &state.read_pool,
+            uri,
+            radius,
+            limit,
+            &state.hidden_dids,
+        ),
+    )
+    .await?;
+
+    let occurrences = enrichment::enrich_occurrences(
+        &state.read_pool,
+        &*state.resolver,
+        &*state.taxonomy,
+        state.blob_links(),
+        &rows,
+        viewer,
+    )
+    .await;
+
+    Ok(SimilarOccurrencesResponse {
+        occurrence_uri: uri.to_string(),
+        occurrences,
+    })
+}
+
+/// Who liked the occurrence, newest first, with their profiles. An
+/// occurrence nobody has liked (or that doesn't exist) gets an empty page.
+async fn get_likers(
+    state: &AppState,
+    uri: &str,
+    params: &LikersParams,
+) -> Result<LikersResponse, AppError> {
+    let limit = state.page_limits.feed.resolve(params.limit);
+    let options = observing_db::types::LikersOptions {
+        limit: Some(limit),
+        cursor: params
+            .cursor
+            .as_deref()
+            .map(FeedCursor::decode)
+            .transpose()?,
+    };
+    let uris = [uri.to_string()];
+    let (rows, counts) = server_timing::timed("db", async {
+        tokio::try_join!(
+            observing_db::likes::get_likers(&state.read_pool, uri, &options),
+            observing_db::likes::get_counts_for_occurrences(&state.read_pool, &uris),
+        )
+    })
+    .await?;
+
+    let likers = enrichment::enrich_likers(&*state.resolver, &rows).await;
+    // A short page is the last one.
+    let cursor = if likers.len() as i64 == limit {
+        likers.last().map(|l| l.cursor())
+    } else {
+        None
+    };
+    Ok(LikersResponse {
+        occurrence_uri: uri.to_string(),
+        total: counts.get(uri).copied().unwrap_or(0),
+        likers,
+        cursor,
+    })
+}
+
+/// How the occurrence's community ID has shifted, newest first.
+async fn get_taxon_history(state: &AppState, uri: &str) -> Result<TaxonHistoryResponse, AppError> {
+    let changes = observing_db::identifications::taxon_history(&state.read_pool, uri).await?;
+    Ok(TaxonHistoryResponse {
+        occurrence_uri: uri.to_string(),
+        changes,
+    })
+}
+
+/// [`get_raw_record`] for an occurrence in the index. Any other URI is a 404
+/// without contacting a PDS, so the endpoint can't be used to make the
+/// server fetch from arbitrary hosts (a `did:web` can name any of them).
+async fn get_indexed_raw_record(
+    state: &AppState,
+    uri: &str,
+) -> Result<serde_json::Value, AppError> {
+    let is_occurrence = AtUri::from_str(uri)
+        .ok()
+        .and_then(|at_uri| {
+            at_uri
+                .collection()
+                .map(|c| c.as_str() == OccurrenceRecord::NSID)
+        })
+        .unwrap_or(false);
+    if !is_occurrence
+        || observing_db::occurrences::get(&state.read_pool, uri)
+            .await?
+            .is_none()
+    {
+        return Err(AppError::NotFound("Occurrence not found".into()));
+    }
+    get_raw_record(&state.media.fetcher, &state.media.records, uri).await
+}
+
+/// The record exactly as the author's PDS serves it from
+/// `com.atproto.repo.getRecord`, for debugging and interop. Cached briefly,
+/// keyed by URI. A record the PDS says isn't there is a 404; any other
+/// failure upstream is a 502.
+async fn get_raw_record(
+    fetcher: &BlobResolver,
+    cache: &Cache<String, serde_json::Value>,
+    uri: &str,
+) -> Result<serde_json::Value, AppError> {
+    cache
+        .try_get_with(uri.to_string(), fetcher.fetch_record_by_aturi(uri))
+        .await
+        .map_err(|e| match *e {
+            BlobResolverError::InvalidDid(_) => {
+                AppError::NotFound(format!("Record not available: {e}"))
+            }
+            BlobResolverError::RecordStatus(_) if !e.is_transient() => {
+                AppError::NotFound(format!("Record not available: {e}"))
+            }
+            BlobResolverError::Http(_)
+            | BlobResolverError::DidResolution(_)
+            | BlobResolverError::RecordFetch(_)
+            | BlobResolverError::RecordStatus(_)
+            | BlobResolverError::BlobFetch(_) => {
+                warn!(uri, error = %e, "Failed to fetch record from the author's PDS");
+                AppError::BadGateway("Could not fetch the record from the author's PDS".into())
+            }
+        })
+}
+
+async fn load_occurrence(
+    state: &AppState,
+    uri: &str,
+    viewer: Option<&str>,
+) -> Result<OccurrenceResponse, AppError> {
+    let row = observing_db::occurrences::get(&state.read_pool, uri)
         .await?
         .ok_or_else(|| AppError::NotFound("Occurrence not found".into()))?;
 
-    let viewer = session_did(&cookies);
     let enriched = enrichment::enrich_occurrences(
-        &state.pool,
-        &state.resolver,
-        &state.taxonomy,
+        &state.read_pool,
+        &*state.resolver,
+        &*state.taxonomy,
+        state.blob_links(),
         &[row],
-        viewer.as_deref(),
+        viewer,
     )
     .await;
 
-    let occurrence = enriched
+    enriched
         .into_iter()
         .next()
-        .ok_or_else(|| AppError::Internal("Failed to enrich occurrence".into()))?;
+        .ok_or_else(|| AppError::Internal("Failed to enrich occurrence".into()))
+}
+
+async fn get_occurrence_detail(
+    state: &AppState,
+    uri: &str,
+    viewer: Option<&str>,
+) -> Result<OccurrenceDetailResponse, AppError> {
+    let occurrence = load_occurrence(state, uri, viewer).await?;
 
     let identification_rows =
-        observing_db::identifications::get_for_occurrence(&state.pool, &uri).await?;
+        observing_db::identifications::get_for_occurrence(&state.read_pool, uri).await?;
     let identifications =
-        enrichment::enrich_identifications(&state.resolver, &identification_rows).await;
+        enrichment::enrich_identifications(&*state.resolver, &identification_rows).await;
 
-    let comment_rows = observing_db::comments::get_for_occurrence(&state.pool, &uri).await?;
-    let comments = enrichment::enrich_comments(&state.resolver, &comment_rows).await;
+    let comment_rows = observing_db::comments::get_for_occurrence(&state.read_pool, uri).await?;
+    let comments = enrichment::enrich_comments(&*state.resolver, &comment_rows).await;
 
-    Ok(Json(OccurrenceDetailResponse {
+    Ok(OccurrenceDetailResponse {
         occurrence,
         identifications,
         comments,
-    }))
+    })
+}
+
+/// The detail page's occurrence, identifications, comments, likes and
+/// interactions in one round trip. The row lookups run concurrently, as do
+/// the profile enrichments.
+async fn get_occurrence_full(
+    state: &AppState,
+    uri: &str,
+    viewer: Option<&str>,
+) -> Result<OccurrenceFullResponse, AppError> {
+    let (occurrence, identification_rows, comment_rows, interaction_rows, taxon_history) = tokio::try_join!(
+        load_occurrence(state, uri, viewer),
+        async {
+            observing_db::identifications::get_for_occurrence(&state.read_pool, uri)
+                .await
+                .map_err(AppError::from)
+        },
+        async {
+            observing_db::comments::get_for_occurrence(&state.read_pool, uri)
+                .await
+                .map_err(AppError::from)
+        },
+        async {
+            observing_db::interactions::get_for_occurrence(&state.read_pool, uri)
+                .await
+                .map_err(AppError::from)
+        },
+        async {
+            observing_db::identifications::taxon_history(&state.read_pool, uri)
+                .await
+                .map_err(AppError::from)
+        },
+    )?;
+
+    let (identifications, comments, interactions) = tokio::join!(
+        enrichment::enrich_identifications(&*state.resolver, &identification_rows),
+        enrichment::enrich_comments(&*state.resolver, &comment_rows),
+        enrichment::enrich_interactions(&*state.resolver, &interaction_rows),
+    );
+
+    let likes = LikesSummary {
+        count: occurrence.like_count.unwrap_or(0),
+        viewer_has_liked: occurrence.viewer_has_liked,
+    };
+
+    Ok(OccurrenceFullResponse {
+        occurrence,
+        identifications,
+        comments,
+        likes,
+        interactions,
+        taxon_history,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_suffix_needs_a_complete_occurrence_uri() {
+        assert_eq!(
+            strip_view_suffix(
+                "at://did:plc:abc/bio.lexicons.temp.v0-1.occurrence/3k2/full",
+                "/full"
+            ),
+            Some("at://did:plc:abc/bio.lexicons.temp.v0-1.occurrence/3k2")
+        );
+        // An occurrence whose record key happens to be `full`.
+        assert_eq!(
+            strip_view_suffix(
+                "at://did:plc:abc/bio.lexicons.temp.v0-1.occurrence/full",
+                "/full"
+            ),
+            None
+        );
+        assert_eq!(
+            strip_view_suffix(
+                "at://did:plc:abc/bio.lexicons.temp.v0-1.occurrence/3k2",
+                "/full"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn taxon_history_suffix_is_recognised() {
+        assert_eq!(
+            strip_view_suffix(
+                "at://did:plc:abc/bio.lexicons.temp.v0-1.occurrence/3k2/taxon-history",
+                "/taxon-history"
+            ),
+            Some("at://did:plc:abc/bio.lexicons.temp.v0-1.occurrence/3k2")
+        );
+        assert_eq!(
+            strip_view_suffix(
+                "at://did:plc:abc/bio.lexicons.temp.v0-1.occurrence/3k2/taxon-history",
+                "/full"
+            ),
+            None
+        );
+    }
+
+    /// `suffix` is split off a complete occurrence URI, but an occurrence
+    /// whose record key is the suffix's name stays the plain occurrence.
+    fn assert_view_suffix(suffix: &str) {
+        let occurrence = "at://did:plc:abc/bio.lexicons.temp.v0-1.occurrence/3k2";
+        assert_eq!(
+            strip_view_suffix(&format!("{occurrence}{suffix}"), suffix),
+            Some(occurrence)
+        );
+        let named_like_suffix =
+            format!("at://did:plc:abc/bio.lexicons.temp.v0-1.occurrence{suffix}");
+        assert_eq!(strip_view_suffix(&named_like_suffix, suffix), None);
+    }
+
+    #[test]
+    fn similar_suffix_is_recognised() {
+        assert_view_suffix("/similar");
+    }
+
+    #[test]
+    fn heatmap_cell_size_is_bounded_by_the_area() {
+        let bay_area = BoundingBox {
+            min_lat: 37.0,
+            min_lng: -123.0,
+            max_lat: 38.0,
+            max_lng: -122.0,
+        };
+        assert_eq!(heatmap_cell_size(Some(0.05), Some(&bay_area)), 0.05);
+        assert_eq!(
+            heatmap_cell_size(None, Some(&bay_area)),
+            constants::DEFAULT_HEATMAP_CELL_SIZE
+        );
+        // The whole world at 0.01 degrees would be hundreds of millions of
+        // cells; it's widened to fit the cap.
+        let world = heatmap_cell_size(Some(0.01), None);
+        assert!((360.0 / world) * (180.0 / world) <= constants::MAX_HEATMAP_CELLS + 1.0);
+        assert_eq!(
+            heatmap_cell_size(Some(1000.0), None),
+            constants::MAX_HEATMAP_CELL_SIZE
+        );
+        assert_eq!(
+            heatmap_cell_size(Some(f64::NAN), Some(&bay_area)),
+            constants::DEFAULT_HEATMAP_CELL_SIZE
+        );
+    }
+
+    #[test]
+    fn likes_suffix_is_recognised() {
+        assert_view_suffix("/likes");
+    }
+
+    #[tokio::test]
+    async fn raw_record_comes_from_the_authors_pds_and_is_cached() {
+        use wiremock::matchers::{method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        const URI: &str = "at://did:plc:abc/bio.lexicons.temp.v0-1.occurrence/3k2";
+        let record = serde_json::json!({
+            "$type": "bio.lexicons.temp.v0-1.occurrence",
+            "scientificName": "Quercus alba",
+            "eventDate": "2024-06-15T08:30:00Z",
+        });
+
+        // The mock serves both the PLC directory and the PDS it points to.
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/did:plc:abc"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "did:plc:abc",
+                "service": [{
+                    "id": "#atproto_pds",
+                    "type": "AtprotoPersonalDataServer",
+                    "serviceEndpoint": server.uri(),
+                }],
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/xrpc/com.atproto.repo.getRecord"))
+            .and(query_param("rkey", "3k2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "uri": URI,
+                "cid": "bafyrecord",
+                "value": record,
+            })))
+            // The second lookup is served from the cache.
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let fetcher = BlobResolver::new().with_plc_directory(&server.uri());
+        let cache = Cache::new(10);
+        assert_eq!(get_raw_record(&fetcher, &cache, URI).await.unwrap(), record);
+        assert_eq!(get_raw_record(&fetcher, &cache, URI).await.unwrap(), record);
+    }
+
+    #[tokio::test]
+    async fn raw_record_missing_from_the_pds_is_not_found() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/did:plc:abc"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "did:plc:abc",
+                "service": [{
+                    "id": "#atproto_pds",
+                    "type": "AtprotoPersonalDataServer",
+                    "serviceEndpoint": server.uri(),
+                }],
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/xrpc/com.atproto.repo.getRecord"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+                "error": "RecordNotFound",
+            })))
+            .mount(&server)
+            .await;
+
+        let fetcher = BlobResolver::new().with_plc_directory(&server.uri());
+        let err = get_raw_record(
+            &fetcher,
+            &Cache::new(10),
+            "at://did:plc:abc/bio.lexicons.temp.v0-1.occurrence/gone",
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::NotFound(_)));
+    }
+
+    #[tokio::test]
+    async fn raw_record_outside_the_occurrence_collection_is_not_fetched() {
+        use crate::taxonomy_client::FakeTaxonomy;
+        use std::sync::Arc;
+
+        // The test pool points at a closed port, so reaching the index
+        // lookup (let alone the PDS) would fail with a database error.
+        let (state, _media_dir) = AppState::for_tests(Arc::new(FakeTaxonomy::new(vec![]))).await;
+        for uri in [
+            "at://did:web:internal.example/app.bsky.feed.post/1",
+            "not a uri",
+        ] {
+            let err = get_indexed_raw_record(&state, uri).await.unwrap_err();
+            assert!(matches!(err, AppError::NotFound(_)), "{uri}: {err:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn raw_record_pds_failure_is_a_bad_gateway() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/did:plc:abc"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "did:plc:abc",
+                "service": [{
+                    "id": "#atproto_pds",
+                    "type": "AtprotoPersonalDataServer",
+                    "serviceEndpoint": server.uri(),
+                }],
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/xrpc/com.atproto.repo.getRecord"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+
+        let fetcher = BlobResolver::new().with_plc_directory(&server.uri());
+        let err = get_raw_record(
+            &fetcher,
+            &Cache::new(10),
+            "at://did:plc:abc/bio.lexicons.temp.v0-1.occurrence/3k2",
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::BadGateway(_)));
+    }
 }
@@ -1,79 +1,105 @@
+use async_trait::async_trait;
 use atrium_api::types::{BlobRef as AtriumBlobRef, TypedBlobRef};
-use axum::extract::{Path, State};
+use axum::extract::{FromRequest, Multipart, Path, Request, State};
+use axum::http::{header, HeaderMap};
+use axum::response::{IntoResponse, Response};
 use axum::Json;
 use jacquard_common::deps::smol_str::SmolStr;
 use jacquard_common::types::collection::Collection;
 use jacquard_common::types::string::Datetime;
+use observing_db::community_ids::TaxonomicHierarchy;
+use observing_db::idempotency::{self, Reservation};
+use observing_db::processing::round_coordinate;
 use observing_db::types::{BlobEntry, BlobImage, BlobRef as DbBlobRef};
 use observing_lexicons::bio_lexicons::temp::v0_1::media::MediaRecord;
 use observing_lexicons::bio_lexicons::temp::v0_1::occurrence::{
     Occurrence, OccurrenceOrganismQuantityType, OccurrenceRecord,
 };
 use observing_lexicons::com_atproto::repo::strong_ref::StrongRef;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::future::Future;
 use tracing::{info, warn};
 use ts_rs::TS;
 
 use crate::auth::{self, AuthUser};
+use crate::config::{CoordinateChecks, ImageLimits};
 use crate::constants;
 use crate::error::AppError;
 use crate::responses::{RecordCreatedResponse, SuccessResponse};
 use crate::state::{AgentType, AppState};
-use crate::validation::validate_license;
+use crate::validation::{validate_license, validate_string_length};
 use jacquard_common::types::string::AtUri;
 use std::str::FromStr;
 
-use super::auto_id;
+use super::auto_id::{self, ResolvedTaxon};
 
-#[derive(Deserialize, TS)]
+#[derive(Deserialize, Serialize, TS)]
 #[serde(rename_all = "camelCase")]
 #[ts(export, export_to = "bindings/")]
 pub struct CreateOccurrenceRequest {
-    latitude: f64,
-    longitude: f64,
+    pub(super) latitude: f64,
+    pub(super) longitude: f64,
     #[ts(optional)]
-    coordinate_uncertainty_in_meters: Option<i32>,
+    pub(super) coordinate_uncertainty_in_meters: Option<i32>,
     /// Darwin Core dwc:organismQuantity — free text (a count, a range like
     /// "10-100", or a categorical value like "many"). Written verbatim.
     #[ts(optional)]
-    organism_quantity: Option<String>,
+    pub(super) organism_quantity: Option<String>,
     /// Darwin Core dwc:organismQuantityType — the quantification system the
     /// quantity uses ("individuals", "percent-cover", or an open-vocab value).
     #[ts(optional)]
-    organism_quantity_type: Option<String>,
+    pub(super) organism_quantity_type: Option<String>,
     #[ts(optional)]
-    event_date: Option<String>,
+    pub(super) event_date: Option<String>,
     #[ts(optional)]
-    images: Option<Vec<ImageUpload>>,
+    pub(super) images: Option<Vec<ImageUpload>>,
     /// SPDX license identifier applied to each uploaded media record (e.g.
     /// `CC-BY-4.0`). Validated against `validation::ALLOWED_LICENSES`. When
     /// omitted, the PDS media record stores no license.
     #[ts(optional)]
-    license: Option<String>,
+    pub(super) license: Option<String>,
     #[ts(optional)]
-    scientific_name: Option<String>,
+    pub(super) scientific_name: Option<String>,
     #[ts(optional)]
-    taxon_rank: Option<String>,
+    pub(super) taxon_rank: Option<String>,
     /// Optional kingdom hint from a GBIF autocomplete pick. Disambiguates
     /// genus-level names for the auto-identification's GBIF validate call
     /// and acts as a fallback when validation doesn't return a kingdom.
     #[ts(optional)]
-    kingdom: Option<String>,
+    pub(super) kingdom: Option<String>,
     /// Stable taxon URI from a GBIF autocomplete pick (e.g. a GBIF species
     /// URI). Written to the auto-created identification's `taxonID` field.
     #[ts(optional)]
-    taxon_id: Option<String>,
+    pub(super) taxon_id: Option<String>,
+    /// Whether to auto-create an identification from `scientific_name`.
+    /// Defaults to `true`; `false` posts the occurrence without committing
+    /// to an ID.
+    #[ts(optional)]
+    pub(super) auto_identify: Option<bool>,
+    /// Coarsest rank (e.g. `species`) the resolved taxon may have for the
+    /// identification to be auto-created. When omitted, any rank is.
+    #[ts(optional)]
+    pub(super) min_auto_id_rank: Option<String>,
 }
 
-#[derive(Deserialize, TS)]
+#[derive(Deserialize, Serialize, TS)]
 #[serde(rename_all = "camelCase")]
 #[ts(export, export_to = "bindings/")]
 pub struct ImageUpload {
     data: String, // base64
-    /// Deserialized from frontend but unused — PDS infers MIME type from bytes.
+    /// Deserialized from frontend but unused — the bytes are sniffed by
+    /// [`check_image_bytes`] and the PDS infers the MIME type itself.
     #[allow(dead_code)]
     mime_type: String,
+    /// Alt text describing the image, written to the media record's `alt`.
+    #[ts(optional)]
+    alt: Option<String>,
+    /// Free-text caption shown alongside the image.
+    #[ts(optional)]
+    caption: Option<String>,
 }
 
 #[derive(Deserialize, TS)]
@@ -81,6 +107,11 @@ pub struct ImageUpload {
 #[ts(export, export_to = "bindings/")]
 pub struct UpdateOccurrenceRequest {
     uri: String,
+    /// CID of the record version the edit was made against. When given, the
+    /// update only applies if the record is still at that version and fails
+    /// with 409 Conflict otherwise; when omitted the last write wins.
+    #[ts(optional)]
+    expected_cid: Option<String>,
     latitude: f64,
     longitude: f64,
     #[ts(optional)]
@@ -119,35 +150,592 @@ pub struct UpdateOccurrenceRequest {
     taxon_id: Option<String>,
 }
 
-pub async fn create_occurrence(
-    State(state): State<AppState>,
-    user: AuthUser,
-    Json(body): Json<CreateOccurrenceRequest>,
-) -> Result<Json<RecordCreatedResponse>, AppError> {
-    // Validate coordinates
-    if !(-90.0..=90.0).contains(&body.latitude) || !(-180.0..=180.0).contains(&body.longitude) {
+/// Body of `PUT /api/occurrences/{uri}`: a partial edit of an occurrence's
+/// metadata. Only the fields present are changed; everything else on the
+/// record (location, media, `createdAt`) is written back as it was.
+#[derive(Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "bindings/")]
+pub struct PatchOccurrenceRequest {
+    /// See `UpdateOccurrenceRequest::expected_cid`. Without it the patch is
+    /// still applied atomically against the version it was read from.
+    #[ts(optional)]
+    expected_cid: Option<String>,
+    #[ts(optional)]
+    event_date: Option<String>,
+    /// See `CreateOccurrenceRequest::organism_quantity`. An empty string
+    /// removes the value.
+    #[ts(optional)]
+    organism_quantity: Option<String>,
+    /// See `CreateOccurrenceRequest::organism_quantity_type`. An empty
+    /// string removes the value.
+    #[ts(optional)]
+    organism_quantity_type: Option<String>,
+}
+
+/// Body of `POST /api/occurrences`, in either of its two encodings:
+///
+/// - `application/json`: a [`CreateOccurrenceRequest`] with images inlined
+///   as base64 under `images`.
+/// - `multipart/form-data`: one text part per request field (same camelCase
+///   names) and one `images` file part per image, carrying the raw bytes.
+///   Skips the base64 round-trip and its ~33% size overhead. Images sent
+///   this way carry no alt text or caption.
+pub struct CreateOccurrenceBody {
+    request: CreateOccurrenceRequest,
+    /// Decoded images, in upload order.
+    images: Vec<NewImage>,
+}
+
+/// An uploaded image ready to be stored as a blob plus media record.
+#[derive(Debug, PartialEq)]
+struct NewImage {
+    bytes: Vec<u8>,
+    alt: Option<String>,
+    caption: Option<String>,
+}
+
+impl NewImage {
+    fn bytes(bytes: Vec<u8>) -> Self {
+        Self {
+            bytes,
+            alt: None,
+            caption: None,
+        }
+    }
+}
+
+impl<S: Send + Sync> FromRequest<S> for CreateOccurrenceBody {
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let is_multipart = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|ct| ct.starts_with("multipart/form-data"));
+
+        if is_multipart {
+            let multipart = Multipart::from_request(req, state)
+                .await
+                .map_err(IntoResponse::into_response)?;
+            Self::from_multipart(multipart)
+                .await
+                .map_err(IntoResponse::into_response)
+        } else {
+            let Json(mut request) = Json::<CreateOccurrenceRequest>::from_request(req, state)
+                .await
+                .map_err(IntoResponse::into_response)?;
+            let images = decode_images(&request.images.take().unwrap_or_default())
+                .map_err(IntoResponse::into_response)?;
+            Ok(Self { request, images })
+        }
+    }
+}
+
+impl CreateOccurrenceBody {
+    async fn from_multipart(mut multipart: Multipart) -> Result<Self, AppError> {
+        let bad_part = |e: axum::extract::multipart::MultipartError| {
+            AppError::BadRequest(format!("Invalid multipart body: {}", e.body_text()))
+        };
+
+        let mut fields = std::collections::HashMap::new();
+        let mut images = Vec::new();
+        while let Some(field) = multipart.next_field().await.map_err(bad_part)? {
+            let name = field.name().unwrap_or_default().to_string();
+            if name == "images" {
+                let bytes = field.bytes().await.map_err(bad_part)?.to_vec();
+                check_image_bytes(&bytes, images.len())?;
+                images.push(NewImage::bytes(bytes));
+            } else {
+                fields.insert(name, field.text().await.map_err(bad_part)?);
+            }
+        }
+
+        // Browsers send empty strings for blank form inputs; treat those as
+        // absent like an omitted JSON field.
+        let mut text = |name: &str| fields.remove(name).filter(|v| !v.is_empty());
+        let request = CreateOccurrenceRequest {
+            latitude: parse_form_field(text("latitude"), "latitude")?
+                .ok_or_else(|| AppError::BadRequest("Missing latitude".into()))?,
+            longitude: parse_form_field(text("longitude"), "longitude")?
+                .ok_or_else(|| AppError::BadRequest("Missing longitude".into()))?,
+            coordinate_uncertainty_in_meters: parse_form_field(
+                text("coordinateUncertaintyInMeters"),
+                "coordinateUncertaintyInMeters",
+            )?,
+            organism_quantity: text("organismQuantity"),
+            organism_quantity_type: text("organismQuantityType"),
+            event_date: text("eventDate"),
+            images: None,
+            license: text("license"),
+            scientific_name: text("scientificName"),
+            taxon_rank: text("taxonRank"),
+            kingdom: text("kingdom"),
+            taxon_id: text("taxonId"),
+            auto_identify: parse_form_field(text("autoIdentify"), "autoIdentify")?,
+            min_auto_id_rank: text("minAutoIdRank"),
+        };
+        Ok(Self { request, images })
+    }
+}
+
+/// Parse an optional numeric form field, naming it in the error.
+pub(super) fn parse_form_field<T: FromStr>(
+    value: Option<String>,
+    name: &str,
+) -> Result<Option<T>, AppError> {
+    value
+        .map(|v| {
+            v.trim()
+                .parse()
+                .map_err(|_| AppError::BadRequest(format!("Invalid {name}")))
+        })
+        .transpose()
+}
+
+/// Decode base64 JSON image uploads to raw bytes, keeping each image's alt
+/// text and caption. Blank text is treated as absent.
+fn decode_images(images: &[ImageUpload]) -> Result<Vec<NewImage>, AppError> {
+    use base64::Engine;
+
+    images
+        .iter()
+        .enumerate()
+        .map(|(index, img)| {
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(&img.data)
+                .map_err(|e| AppError::BadRequest(format!("Invalid base64 image data: {e}")))?;
+            check_image_bytes(&bytes, index)?;
+            let text = |value: &Option<String>, max: usize, what: &str| {
+                let value = value.as_deref().map(str::trim).filter(|v| !v.is_empty());
+                if let Some(v) = value {
+                    validate_string_length(v, 1, max, &format!("Image {} {what}", index + 1))?;
+                }
+                Ok::<_, AppError>(value.map(str::to_string))
+            };
+            Ok(NewImage {
+                bytes,
+                alt: text(&img.alt, constants::MAX_IMAGE_ALT_LENGTH, "alt text")?,
+                caption: text(&img.caption, constants::MAX_IMAGE_CAPTION_LENGTH, "caption")?,
+            })
+        })
+        .collect()
+}
+
+/// Reject an upload whose leading bytes aren't a JPEG, PNG, WebP or GIF
+/// signature. The client's `mimeType` is never trusted, and the PDS stores
+/// whatever it's given, so without this any file could become an "image"
+/// blob. Runs before anything is uploaded so a bad file fails the whole
+/// request rather than leaving earlier blobs behind.
+fn check_image_bytes(bytes: &[u8], index: usize) -> Result<(), AppError> {
+    use image::ImageFormat;
+
+    match image::guess_format(bytes) {
+        Ok(ImageFormat::Jpeg | ImageFormat::Png | ImageFormat::WebP | ImageFormat::Gif) => Ok(()),
+        _ => Err(AppError::BadRequest(format!(
+            "Image {} is not a JPEG, PNG, WebP or GIF file",
+            index + 1
+        ))),
+    }
+}
+
+/// Fail a create or update whose images exceed `limits` before any blob is
+/// uploaded, rather than having the PDS reject one partway through and leave
+/// the earlier blobs and media records behind. `retained` counts media the
+/// record already has and keeps; only new images count towards the size.
+fn check_image_limits(
+    images: &[NewImage],
+    retained: usize,
+    limits: ImageLimits,
+) -> Result<(), AppError> {
+    let count = retained + images.len();
+    if count > limits.max_count {
+        return Err(AppError::BadRequest(format!(
+            "Too many images: {count} (max {})",
+            limits.max_count
+        )));
+    }
+    let total: usize = images.iter().map(|img| img.bytes.len()).sum();
+    if total > limits.max_total_bytes {
+        return Err(AppError::BadRequest(format!(
+            "Images total {total} bytes (max {})",
+            limits.max_total_bytes
+        )));
+    }
+    Ok(())
+}
+
+/// Reject coordinates that are out of range, or that `checks` flags as
+/// implausible: null island, or whole degrees claiming a tight uncertainty.
+fn check_coordinates(
+    latitude: f64,
+    longitude: f64,
+    uncertainty: Option<i32>,
+    checks: CoordinateChecks,
+) -> Result<(), AppError> {
+    if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
         return Err(AppError::BadRequest("Invalid coordinates".into()));
     }
 
+    if checks.reject_null_island
+        && latitude.abs() < constants::NULL_ISLAND_EPSILON
+        && longitude.abs() < constants::NULL_ISLAND_EPSILON
+    {
+        return Err(AppError::BadRequest(
+            "Coordinates 0,0 look like a missing location".into(),
+        ));
+    }
+
+    let uncertainty = uncertainty.unwrap_or(constants::DEFAULT_COORDINATE_UNCERTAINTY);
+    if uncertainty < checks.integer_min_uncertainty_m
+        && latitude.fract() == 0.0
+        && longitude.fract() == 0.0
+    {
+        return Err(AppError::BadRequest(format!(
+            "Whole-degree coordinates need a coordinateUncertaintyInMeters of at least {}",
+            checks.integer_min_uncertainty_m
+        )));
+    }
+    Ok(())
+}
+
+/// Checks on a create request that need no network calls; shared by
+/// [`create_occurrence`] and its dry run, [`validate_occurrence`].
+pub(super) fn check_create_request(
+    body: &CreateOccurrenceRequest,
+    checks: CoordinateChecks,
+) -> Result<(), AppError> {
+    check_coordinates(
+        body.latitude,
+        body.longitude,
+        body.coordinate_uncertainty_in_meters,
+        checks,
+    )?;
+
     if let Some(ref license) = body.license {
         validate_license(license)?;
     }
 
-    // Restore OAuth session for AT Protocol operations
-    let (agent, did_parsed) = auth::require_agent(&state.oauth_client, &user.did).await?;
+    if let Some(ref rank) = body.min_auto_id_rank {
+        if !TaxonomicHierarchy::is_known_rank(rank) {
+            return Err(AppError::BadRequest(format!(
+                "Unknown minAutoIdRank: {rank}"
+            )));
+        }
+    }
+    Ok(())
+}
 
-    // Upload blobs and create media records on the PDS. The DB row will be
-    // populated by the ingester when the firehose commit arrives; the
-    // ingester resolves associatedMedia strong refs back into blob entries
-    // for the `associated_media` column.
-    let (_blob_entries, media_refs) = upload_media_records(
-        &agent,
-        &user.did,
-        body.images.as_deref().unwrap_or(&[]),
-        body.license.as_deref(),
+/// The scientific name to auto-identify the new occurrence as, or `None`
+/// when the request gives no name or turns auto-identification off.
+fn auto_id_name(body: &CreateOccurrenceRequest) -> Option<&str> {
+    if !body.auto_identify.unwrap_or(true) {
+        return None;
+    }
+    body.scientific_name.as_deref().filter(|n| !n.is_empty())
+}
+
+/// Resolve the taxonomy for the request's auto-identification, or `None`
+/// when none should be created (see [`auto_id_name`] and
+/// [`auto_id::meets_rank_floor`]).
+async fn resolve_auto_id(
+    state: &AppState,
+    body: &CreateOccurrenceRequest,
+) -> Option<ResolvedTaxon> {
+    let name = auto_id_name(body)?;
+    let taxon = auto_id::resolve_taxon(
+        state,
+        name,
+        body.taxon_rank.as_deref(),
+        body.kingdom.as_deref(),
+        body.taxon_id.as_deref(),
     )
-    .await?;
+    .await;
+    if !auto_id::meets_rank_floor(
+        taxon.taxon_rank.as_deref(),
+        body.min_auto_id_rank.as_deref(),
+    ) {
+        info!(
+            rank = ?taxon.taxon_rank,
+            min_rank = ?body.min_auto_id_rank,
+            "Skipping auto-identification below requested rank floor"
+        );
+        return None;
+    }
+    Some(taxon)
+}
+
+/// The occurrence record [`create_occurrence`] would write for `body`, minus
+/// any media.
+fn preview_occurrence_record(
+    body: &CreateOccurrenceRequest,
+    checks: CoordinateChecks,
+) -> Result<serde_json::Value, AppError> {
+    check_create_request(body, checks)?;
+    build_occurrence_record_json(
+        body.latitude,
+        body.longitude,
+        body.coordinate_uncertainty_in_meters,
+        body.organism_quantity.as_deref(),
+        body.organism_quantity_type.as_deref(),
+        body.event_date.as_deref(),
+        Vec::new(),
+    )
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OccurrencePreviewResponse {
+    /// The occurrence record as it would be written to the PDS (without
+    /// media, since nothing is uploaded).
+    record: serde_json::Value,
+    /// Taxonomy the auto-created identification would carry; absent when no
+    /// scientific name was given, auto-identification is off, or the taxon
+    /// is coarser than `minAutoIdRank`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    identification: Option<ResolvedTaxon>,
+}
 
+/// POST /api/occurrences/validate — dry run of [`create_occurrence`].
+///
+/// Runs the same validation and taxonomy resolution and returns what would
+/// be written, without touching the PDS or the database. Takes the JSON
+/// create body; images are ignored and should be left out.
+pub async fn validate_occurrence(
+    State(state): State<AppState>,
+    _user: AuthUser,
+    Json(body): Json<CreateOccurrenceRequest>,
+) -> Result<Json<OccurrencePreviewResponse>, AppError> {
+    let record = preview_occurrence_record(&body, state.coordinate_checks)?;
+
+    let identification = resolve_auto_id(&state, &body).await;
+
+    Ok(Json(OccurrencePreviewResponse {
+        record,
+        identification,
+    }))
+}
+
+/// The request's `Idempotency-Key` header, if any. Keys must be 1–255
+/// visible ASCII characters.
+fn idempotency_key(headers: &HeaderMap) -> Result<Option<String>, AppError> {
+    let Some(value) = headers.get("idempotency-key") else {
+        return Ok(None);
+    };
+    let key = value
+        .to_str()
+        .ok()
+        .map(str::trim)
+        .filter(|k| {
+            !k.is_empty()
+                && k.len() <= constants::MAX_IDEMPOTENCY_KEY_LENGTH
+                && k.bytes().all(|b| b.is_ascii_graphic())
+        })
+        .ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "Idempotency-Key must be 1-{} visible ASCII characters",
+                constants::MAX_IDEMPOTENCY_KEY_LENGTH
+            ))
+        })?;
+    Ok(Some(key.to_string()))
+}
+
+/// The response for a key an earlier request already used, or `None` when
+/// this request holds the key and should go ahead.
+fn replayed_response(
+    reservation: Reservation,
+) -> Option<Result<Json<RecordCreatedResponse>, AppError>> {
+    match reservation {
+        Reservation::Reserved => None,
+        Reservation::Completed { uri, cid } => Some(Ok(Json(RecordCreatedResponse {
+            success: true,
+            uri,
+            cid,
+        }))),
+        Reservation::InProgress => Some(Err(AppError::Conflict(
+            "A request with this Idempotency-Key is still in progress".into(),
+        ))),
+        Reservation::Mismatch => Some(Err(AppError::UnprocessableEntity(
+            "This Idempotency-Key was already used for a different request".into(),
+        ))),
+    }
+}
+
+/// Digest an `Idempotency-Key` is bound to: the create request's fields and
+/// its images, so a key reused for a different observation is caught.
+fn request_fingerprint(body: &CreateOccurrenceRequest, images: &[NewImage]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(serde_json::to_vec(body).unwrap_or_default());
+    for image in images {
+        // Length-prefixed so bytes can't shift between images.
+        hasher.update((image.bytes.len() as u64).to_be_bytes());
+        hasher.update(&image.bytes);
+        hasher.update(serde_json::to_vec(&(&image.alt, &image.caption)).unwrap_or_default());
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Where idempotency keys are kept: Postgres in production, memory in tests.
+#[async_trait]
+pub(super) trait IdempotencyStore: Send + Sync {
+    async fn reserve(
+        &self,
+        did: &str,
+        key: &str,
+        request_hash: &str,
+    ) -> Result<Reservation, AppError>;
+    async fn complete(&self, did: &str, key: &str, uri: &str, cid: &str) -> Result<(), AppError>;
+    async fn release(&self, did: &str, key: &str) -> Result<(), AppError>;
+}
+
+#[async_trait]
+impl IdempotencyStore for PgPool {
+    async fn reserve(
+        &self,
+        did: &str,
+        key: &str,
+        request_hash: &str,
+    ) -> Result<Reservation, AppError> {
+        Ok(idempotency::reserve(
+            self,
+            did,
+            key,
+            request_hash,
+            constants::IDEMPOTENCY_KEY_TTL,
+            constants::IDEMPOTENCY_IN_PROGRESS_TIMEOUT,
+        )
+        .await?)
+    }
+
+    async fn complete(&self, did: &str, key: &str, uri: &str, cid: &str) -> Result<(), AppError> {
+        Ok(idempotency::complete(self, did, key, uri, cid).await?)
+    }
+
+    async fn release(&self, did: &str, key: &str) -> Result<(), AppError> {
+        Ok(idempotency::release(self, did, key).await?)
+    }
+}
+
+/// Attempts at recording a created record against its key before giving up.
+const COMPLETE_ATTEMPTS: u32 = 3;
+
+/// Outcome of [`create_once`].
+pub(super) enum KeyedCreate<T> {
+    /// This request created the record at `uri`/`cid`.
+    Created { extra: T, uri: String, cid: String },
+    /// An earlier request with the same key already did.
+    Replayed(Json<RecordCreatedResponse>),
+}
+
+/// Run `create` unless an earlier request with `key` already did. Without a
+/// key it always runs. Once the record exists the key is bound to it, with
+/// retries: a key left unbound would let a client retry create a duplicate,
+/// so if it can't be recorded the request fails rather than reporting
+/// success.
+pub(super) async fn create_once<T>(
+    store: &dyn IdempotencyStore,
+    did: &str,
+    key: Option<(&str, &str)>,
+    create: impl Future<Output = Result<(T, String, String), AppError>>,
+) -> Result<KeyedCreate<T>, AppError> {
+    let Some((key, request_hash)) = key else {
+        let (extra, uri, cid) = create.await?;
+        return Ok(KeyedCreate::Created { extra, uri, cid });
+    };
+    if let Some(response) = replayed_response(store.reserve(did, key, request_hash).await?) {
+        return response.map(KeyedCreate::Replayed);
+    }
+
+    let (extra, uri, cid) = match create.await {
+        Ok(created) => created,
+        Err(e) => {
+            // The occurrence record wasn't written (media records uploaded
+            // before the failure may be left behind), so the key is free
+            // for a retry.
+            if let Err(e) = store.release(did, key).await {
+                warn!(error = ?e, "Failed to release idempotency key");
+            }
+            return Err(e);
+        }
+    };
+
+    let mut attempt = 1;
+    while let Err(e) = store.complete(did, key, &uri, &cid).await {
+        if attempt == COMPLETE_ATTEMPTS {
+            return Err(AppError::Internal(format!(
+                "Created {uri} but could not record its Idempotency-Key: {e:?}"
+            )));
+        }
+        warn!(error = ?e, attempt, "Failed to record idempotency key result; retrying");
+        tokio::time::sleep(std::time::Duration::from_millis(50 * u64::from(attempt))).await;
+        attempt += 1;
+    }
+    Ok(KeyedCreate::Created { extra, uri, cid })
+}
+
+/// POST /api/occurrences — create an occurrence record (JSON or multipart;
+/// see [`CreateOccurrenceBody`]). With an `Idempotency-Key` header, a retry
+/// of a create that already reached the PDS returns the first record's URI
+/// and CID instead of creating a duplicate; reusing the key for a different
+/// request is a 422.
+pub async fn create_occurrence(
+    State(state): State<AppState>,
+    user: AuthUser,
+    headers: HeaderMap,
+    CreateOccurrenceBody {
+        request: body,
+        images,
+    }: CreateOccurrenceBody,
+) -> Result<Json<RecordCreatedResponse>, AppError> {
+    let idempotency_key = idempotency_key(&headers)?;
+    check_create_request(&body, state.coordinate_checks)?;
+    check_image_limits(&images, 0, state.image_limits)?;
+
+    let fingerprint = idempotency_key
+        .as_ref()
+        .map(|_| request_fingerprint(&body, &images));
+    let key = idempotency_key.as_deref().zip(fingerprint.as_deref());
+
+    let create = async {
+        // Restore OAuth session for AT Protocol operations
+        let (agent, did_parsed) = auth::require_agent(&state.oauth_client, &user.did).await?;
+
+        // Upload blobs and create media records on the PDS. The DB row will
+        // be populated by the ingester when the firehose commit arrives; the
+        // ingester resolves associatedMedia strong refs back into blob
+        // entries for the `associated_media` column.
+        let (_blob_entries, media_refs) =
+            upload_media_records(&agent, &user.did, images, body.license.as_deref()).await?;
+
+        let (uri, cid) = publish_occurrence(&agent, did_parsed, &body, media_refs).await?;
+        Ok::<_, AppError>((agent, uri, cid))
+    };
+
+    // The key is bound as soon as the record exists: a retry after a later
+    // failure (e.g. the auto-identification) must not create a second one.
+    let (agent, uri, cid) = match create_once(&state.pool, &user.did, key, create).await? {
+        KeyedCreate::Created { extra, uri, cid } => (extra, uri, cid),
+        KeyedCreate::Replayed(response) => return Ok(response),
+    };
+
+    finish_occurrence(&state, &agent, &user.did, &body, &uri, &cid).await?;
+
+    Ok(Json(RecordCreatedResponse {
+        success: true,
+        uri,
+        cid,
+    }))
+}
+
+/// Write the occurrence record for `body` to the user's PDS, returning its
+/// URI and CID.
+pub(super) async fn publish_occurrence(
+    agent: &AgentType,
+    did: atrium_api::types::string::Did,
+    body: &CreateOccurrenceRequest,
+    media_refs: Vec<StrongRef>,
+) -> Result<(String, String), AppError> {
     let record_value = build_occurrence_record_json(
         body.latitude,
         body.longitude,
@@ -162,46 +750,51 @@ pub async fn create_occurrence(
     // observing-ingester to parse the same record into DB rows — we no longer
     // do that here, so there is a single writer for the occurrences and
     // associated media state.
-    let resp =
-        auth::create_at_record(&agent, did_parsed, OccurrenceRecord::NSID, record_value).await?;
+    let resp = auth::create_at_record(agent, did, OccurrenceRecord::NSID, record_value).await?;
 
     let uri = resp.uri.to_string();
     let cid = resp.cid.as_ref().to_string();
 
     info!(uri = %uri, "Created occurrence (PDS); awaiting ingester for DB row");
+    Ok((uri, cid))
+}
+
+/// The appview-side follow-up to a published occurrence: private location
+/// data and the auto-created identification.
+pub(super) async fn finish_occurrence(
+    state: &AppState,
+    agent: &AgentType,
+    user_did: &str,
+    body: &CreateOccurrenceRequest,
+    uri: &str,
+    cid: &str,
+) -> Result<(), AppError> {
+    track_write(state, uri, user_did, cid).await;
 
     // Private location data is intentionally never written to the PDS, so the
     // ingester has no path to populate it. This is still the appview's job.
     if let Err(e) =
-        observing_db::private_data::save(&state.pool, &uri, body.latitude, body.longitude, "open")
+        observing_db::private_data::save(&state.pool, uri, body.latitude, body.longitude, "open")
             .await
     {
         warn!(error = %e, "Failed to save private location data");
     }
 
-    // Auto-create first identification if a scientific name was provided
-    if let Some(ref scientific_name) = body.scientific_name {
-        if !scientific_name.is_empty() {
-            create_auto_identification(
-                &state,
-                &agent,
-                &user.did,
-                scientific_name,
-                body.taxon_rank.as_deref(),
-                body.kingdom.as_deref(),
-                body.taxon_id.as_deref(),
-                &uri,
-                &cid,
-            )
-            .await?;
-        }
+    // Auto-create first identification if a scientific name was provided,
+    // unless the request opted out or the taxon is below its rank floor
+    if let Some(taxon) = resolve_auto_id(state, body).await {
+        create_auto_identification(agent, user_did, &taxon, uri, cid).await?;
     }
+    Ok(())
+}
 
-    Ok(Json(RecordCreatedResponse {
-        success: true,
-        uri,
-        cid,
-    }))
+/// Note a record just written to the PDS so it's listed by
+/// `/api/profiles/{did}/pending` until the ingester indexes it. Best effort:
+/// the write itself already succeeded.
+async fn track_write(state: &AppState, uri: &str, did: &str, cid: &str) {
+    if let Err(e) = observing_db::occurrence_writes::record(&state.pool, uri, did, cid).await {
+        warn!(error = %e, "Failed to record occurrence write");
+    }
 }
 
 /// DELETE /api/occurrences/{*uri} — delete an occurrence record via PDS deleteRecord.
@@ -246,6 +839,10 @@ pub async fn delete_occurrence(
             }
         })?;
 
+    if let Err(e) = observing_db::occurrence_writes::forget(&state.pool, &uri).await {
+        warn!(error = %e, "Failed to forget occurrence write");
+    }
+
     // The firehose delete commit will trigger the ingester to remove the row
     // (and cascade to identifications/comments/likes/interactions via FK).
     Ok(Json(SuccessResponse { success: true }))
@@ -257,60 +854,44 @@ pub async fn update_occurrence(
     user: AuthUser,
     Json(body): Json<UpdateOccurrenceRequest>,
 ) -> Result<Json<RecordCreatedResponse>, AppError> {
-    // Validate coordinates
-    if !(-90.0..=90.0).contains(&body.latitude) || !(-180.0..=180.0).contains(&body.longitude) {
-        return Err(AppError::BadRequest("Invalid coordinates".into()));
-    }
+    check_coordinates(
+        body.latitude,
+        body.longitude,
+        body.coordinate_uncertainty_in_meters,
+        state.coordinate_checks,
+    )?;
 
     if let Some(ref license) = body.license {
         validate_license(license)?;
     }
+    let images = decode_images(body.images.as_deref().unwrap_or(&[]))?;
+    let retained = body.retained_blob_cids.as_ref().map_or(0, Vec::len);
+    check_image_limits(&images, retained, state.image_limits)?;
 
     // Parse AT URI and enforce ownership / collection match
-    let at_uri =
-        AtUri::from_str(&body.uri).map_err(|_| AppError::BadRequest("Invalid AT URI".into()))?;
-    if at_uri.authority().as_str() != user.did {
-        return Err(AppError::Forbidden(
-            "You can only edit your own records".into(),
-        ));
-    }
-    if at_uri
-        .collection()
-        .is_none_or(|c| c.as_str() != OccurrenceRecord::NSID)
-    {
-        return Err(AppError::BadRequest(
-            "URI does not reference an occurrence record".into(),
-        ));
-    }
-
-    let (collection_nsid, rkey_parsed) = auth::parse_collection_and_rkey(&at_uri)?;
+    let (collection_nsid, rkey_parsed) = own_occurrence_record(&body.uri, &user.did)?;
 
     let (agent, did_parsed) = auth::require_agent(&state.oauth_client, &user.did).await?;
 
     // Fetch existing PDS record so we can preserve retained associatedMedia strong refs
-    let existing = agent
-        .api
-        .com
-        .atproto
-        .repo
-        .get_record(
-            atrium_api::com::atproto::repo::get_record::ParametersData {
-                cid: None,
-                collection: collection_nsid.clone(),
-                repo: atrium_api::types::string::AtIdentifier::Did(did_parsed.clone()),
-                rkey: rkey_parsed.clone(),
-            }
-            .into(),
-        )
-        .await
-        .map_err(|e| {
-            if matches!(e, atrium_api::xrpc::Error::Authentication(_)) {
-                tracing::warn!(error = %e, "AT Protocol authentication failed (session expired)");
-                AppError::Unauthorized
-            } else {
-                AppError::Internal(format!("Failed to fetch record: {e}"))
-            }
-        })?;
+    let existing = get_record(
+        &agent,
+        did_parsed.clone(),
+        collection_nsid.clone(),
+        rkey_parsed.clone(),
+    )
+    .await?;
+
+    // Fail before uploading any new images if the record already moved on;
+    // `swap_record` below catches an edit that lands in between.
+    let current_cid = existing.cid.as_ref().map(|c| c.as_ref().to_string());
+    check_expected_cid(body.expected_cid.as_deref(), current_cid.as_deref())?;
+    let swap_record = body
+        .expected_cid
+        .as_deref()
+        .map(str::parse::<atrium_api::types::string::Cid>)
+        .transpose()
+        .map_err(|_| AppError::BadRequest("Invalid expectedCid".into()))?;
 
     let existing_value: serde_json::Value = serde_json::to_value(&existing.value)
         .map_err(|e| AppError::Internal(format!("Failed to serialize existing record: {e}")))?;
@@ -354,13 +935,8 @@ pub async fn update_occurrence(
     }
 
     // Upload new images and append their strong refs to what was retained
-    let (_new_blob_entries, new_media_refs) = upload_media_records(
-        &agent,
-        &user.did,
-        body.images.as_deref().unwrap_or(&[]),
-        body.license.as_deref(),
-    )
-    .await?;
+    let (_new_blob_entries, new_media_refs) =
+        upload_media_records(&agent, &user.did, images, body.license.as_deref()).await?;
     media_refs.extend(new_media_refs);
 
     let record_value = build_occurrence_record_json(
@@ -376,38 +952,21 @@ pub async fn update_occurrence(
     // putRecord on the PDS. The firehose commit that follows triggers the
     // ingester to refresh the occurrence row — the appview no longer writes
     // directly to that table, mirroring the create flow.
-    let resp = agent
-        .api
-        .com
-        .atproto
-        .repo
-        .put_record(
-            atrium_api::com::atproto::repo::put_record::InputData {
-                collection: collection_nsid,
-                record: serde_json::from_value(record_value)
-                    .map_err(|e| AppError::Internal(format!("Failed to convert record: {e}")))?,
-                repo: atrium_api::types::string::AtIdentifier::Did(did_parsed),
-                rkey: rkey_parsed,
-                swap_commit: None,
-                swap_record: None,
-                validate: None,
-            }
-            .into(),
-        )
-        .await
-        .map_err(|e| {
-            if matches!(e, atrium_api::xrpc::Error::Authentication(_)) {
-                tracing::warn!(error = %e, "AT Protocol authentication failed (session expired)");
-                AppError::Unauthorized
-            } else {
-                AppError::Internal(format!("Failed to put record: {e}"))
-            }
-        })?;
+    let resp = put_record(
+        &agent,
+        did_parsed,
+        collection_nsid,
+        rkey_parsed,
+        record_value,
+        swap_record,
+    )
+    .await?;
 
     let uri = resp.uri.clone();
     let cid = resp.cid.as_ref().to_string();
 
     info!(uri = %uri, "Updated occurrence (PDS); awaiting ingester for DB refresh");
+    track_write(&state, &uri, &user.did, &cid).await;
 
     // Private location data is intentionally never written to the PDS, so the
     // ingester has no path to populate it. This is still the appview's job.
@@ -430,18 +989,15 @@ pub async fn update_occurrence(
                 .iter()
                 .any(|id| id.did == user.did && id.scientific_name == trimmed);
             if !already_identified {
-                create_auto_identification(
+                let taxon = auto_id::resolve_taxon(
                     &state,
-                    &agent,
-                    &user.did,
                     trimmed,
                     body.taxon_rank.as_deref(),
                     body.kingdom.as_deref(),
                     body.taxon_id.as_deref(),
-                    &uri,
-                    &cid,
                 )
-                .await?;
+                .await;
+                create_auto_identification(&agent, &user.did, &taxon, &uri, &cid).await?;
             }
         }
     }
@@ -453,27 +1009,237 @@ pub async fn update_occurrence(
     }))
 }
 
+/// PUT /api/occurrences/{*uri} — change only the given metadata fields of an
+/// occurrence, in place.
+///
+/// Reads the current record, applies [`PatchOccurrenceRequest`] to it and
+/// writes it back with `swapRecord` set to the version that was read, so a
+/// concurrent edit fails with 409 rather than being overwritten. The URI is
+/// unchanged, so identifications, comments and likes stay attached; the
+/// ingester refreshes the DB row from the resulting firehose commit.
+pub async fn patch_occurrence(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(uri): Path<String>,
+    Json(body): Json<PatchOccurrenceRequest>,
+) -> Result<Json<RecordCreatedResponse>, AppError> {
+    let (collection, rkey) = own_occurrence_record(&uri, &user.did)?;
+    let (agent, did) = auth::require_agent(&state.oauth_client, &user.did).await?;
+
+    let existing = get_record(&agent, did.clone(), collection.clone(), rkey.clone()).await?;
+    let current_cid = existing.cid.as_ref().map(|c| c.as_ref().to_string());
+    check_expected_cid(body.expected_cid.as_deref(), current_cid.as_deref())?;
+
+    let existing_value = serde_json::to_value(&existing.value)
+        .map_err(|e| AppError::Internal(format!("Failed to serialize existing record: {e}")))?;
+    let record_value = apply_occurrence_patch(existing_value, &body)?;
+
+    let resp = put_record(
+        &agent,
+        did,
+        collection,
+        rkey,
+        record_value,
+        existing.cid.clone(),
+    )
+    .await?;
+
+    let uri = resp.uri.clone();
+    let cid = resp.cid.as_ref().to_string();
+    info!(uri = %uri, "Patched occurrence (PDS); awaiting ingester for DB refresh");
+    track_write(&state, &uri, &user.did, &cid).await;
+
+    Ok(Json(RecordCreatedResponse {
+        success: true,
+        uri,
+        cid,
+    }))
+}
+
+/// Apply a [`PatchOccurrenceRequest`] to an occurrence record's JSON,
+/// leaving every field the patch doesn't name untouched.
+fn apply_occurrence_patch(
+    mut record: serde_json::Value,
+    patch: &PatchOccurrenceRequest,
+) -> Result<serde_json::Value, AppError> {
+    if patch.event_date.is_none()
+        && patch.organism_quantity.is_none()
+        && patch.organism_quantity_type.is_none()
+    {
+        return Err(AppError::BadRequest("No fields to update".into()));
+    }
+    let obj = record
+        .as_object_mut()
+        .ok_or_else(|| AppError::Internal("Occurrence record is not an object".into()))?;
+
+    if let Some(ref event_date) = patch.event_date {
+        let event_date = event_date.trim();
+        if observing_db::processing::expand_event_date(event_date).is_none() {
+            return Err(AppError::BadRequest("Invalid eventDate format".into()));
+        }
+        obj.insert("eventDate".into(), json!(event_date));
+    }
+    for (key, value) in [
+        ("organismQuantity", &patch.organism_quantity),
+        ("organismQuantityType", &patch.organism_quantity_type),
+    ] {
+        match value.as_deref().map(str::trim) {
+            None => {}
+            Some("") => {
+                obj.remove(key);
+            }
+            Some(value) => {
+                obj.insert(key.into(), json!(value));
+            }
+        }
+    }
+    Ok(record)
+}
+
+/// Parse `uri`, check it names one of `user_did`'s occurrence records, and
+/// split out the collection and rkey the repo APIs take.
+fn own_occurrence_record(
+    uri: &str,
+    user_did: &str,
+) -> Result<
+    (
+        atrium_api::types::string::Nsid,
+        atrium_api::types::string::RecordKey,
+    ),
+    AppError,
+> {
+    let at_uri = AtUri::from_str(uri).map_err(|_| AppError::BadRequest("Invalid AT URI".into()))?;
+    if at_uri.authority().as_str() != user_did {
+        return Err(AppError::Forbidden(
+            "You can only edit your own records".into(),
+        ));
+    }
+    if at_uri
+        .collection()
+        .is_none_or(|c| c.as_str() != OccurrenceRecord::NSID)
+    {
+        return Err(AppError::BadRequest(
+            "URI does not reference an occurrence record".into(),
+        ));
+    }
+    auth::parse_collection_and_rkey(&at_uri)
+}
+
+/// Fetch the current version of a record from the user's PDS.
+async fn get_record(
+    agent: &AgentType,
+    did: atrium_api::types::string::Did,
+    collection: atrium_api::types::string::Nsid,
+    rkey: atrium_api::types::string::RecordKey,
+) -> Result<atrium_api::com::atproto::repo::get_record::Output, AppError> {
+    agent
+        .api
+        .com
+        .atproto
+        .repo
+        .get_record(
+            atrium_api::com::atproto::repo::get_record::ParametersData {
+                cid: None,
+                collection,
+                repo: atrium_api::types::string::AtIdentifier::Did(did),
+                rkey,
+            }
+            .into(),
+        )
+        .await
+        .map_err(|e| {
+            if matches!(e, atrium_api::xrpc::Error::Authentication(_)) {
+                tracing::warn!(error = %e, "AT Protocol authentication failed (session expired)");
+                AppError::Unauthorized
+            } else {
+                AppError::Internal(format!("Failed to fetch record: {e}"))
+            }
+        })
+}
+
+/// putRecord on the user's PDS. A `swap_record` that no longer matches maps
+/// to the same 409 as [`check_expected_cid`].
+async fn put_record(
+    agent: &AgentType,
+    did: atrium_api::types::string::Did,
+    collection: atrium_api::types::string::Nsid,
+    rkey: atrium_api::types::string::RecordKey,
+    record_value: serde_json::Value,
+    swap_record: Option<atrium_api::types::string::Cid>,
+) -> Result<atrium_api::com::atproto::repo::put_record::Output, AppError> {
+    agent
+        .api
+        .com
+        .atproto
+        .repo
+        .put_record(
+            atrium_api::com::atproto::repo::put_record::InputData {
+                collection,
+                record: serde_json::from_value(record_value)
+                    .map_err(|e| AppError::Internal(format!("Failed to convert record: {e}")))?,
+                repo: atrium_api::types::string::AtIdentifier::Did(did),
+                rkey,
+                swap_commit: None,
+                swap_record,
+                validate: None,
+            }
+            .into(),
+        )
+        .await
+        .map_err(|e| match e {
+            atrium_api::xrpc::Error::Authentication(_) => {
+                tracing::warn!(error = %e, "AT Protocol authentication failed (session expired)");
+                AppError::Unauthorized
+            }
+            atrium_api::xrpc::Error::XrpcResponse(atrium_api::xrpc::error::XrpcError {
+                error:
+                    Some(atrium_api::xrpc::error::XrpcErrorKind::Custom(
+                        atrium_api::com::atproto::repo::put_record::Error::InvalidSwap(_),
+                    )),
+                ..
+            }) => stale_edit(),
+            e => AppError::Internal(format!("Failed to put record: {e}")),
+        })
+}
+
+/// Reject an edit made against `expected` when the record is now at
+/// `current`. No expectation means the caller accepts last-write-wins.
+fn check_expected_cid(expected: Option<&str>, current: Option<&str>) -> Result<(), AppError> {
+    match expected {
+        Some(expected) if current != Some(expected) => Err(stale_edit()),
+        _ => Ok(()),
+    }
+}
+
+fn stale_edit() -> AppError {
+    AppError::Conflict(
+        "The occurrence was changed since it was loaded; reload it and try again".into(),
+    )
+}
+
 /// Upload each image as a blob, create a `bio.lexicons.temp.v0-1.media` record per
 /// blob, and return parallel `(blob_entries, media_refs)` vecs. The DB stores
 /// blob entries for efficient image serving; the PDS occurrence record stores
-/// strong refs to the media records under `associatedMedia`. Media-record
+/// strong refs to the media records under `associatedMedia`. Each image's alt
+/// text goes on its media record and blob entry; the caption rides along as an
+/// extension field the lexicon doesn't define. Media-record
 /// creation failures are logged and skipped (blob already uploaded is retained
 /// in DB).
 async fn upload_media_records(
     agent: &AgentType,
     user_did: &str,
-    images: &[ImageUpload],
+    images: Vec<NewImage>,
     license: Option<&str>,
 ) -> Result<(Vec<BlobEntry>, Vec<StrongRef>), AppError> {
-    use base64::Engine;
-
     let mut blob_entries = Vec::with_capacity(images.len());
     let mut media_refs = Vec::with_capacity(images.len());
 
-    for img in images {
-        let bytes = base64::engine::general_purpose::STANDARD
-            .decode(&img.data)
-            .map_err(|e| AppError::BadRequest(format!("Invalid base64 image data: {e}")))?;
+    for NewImage {
+        bytes,
+        alt,
+        caption,
+    } in images
+    {
         let blob_resp = agent
             .api
             .com
@@ -495,8 +1261,9 @@ async fn upload_media_records(
                 ref_: DbBlobRef::Link { link: cid_str },
                 mime_type,
             },
-            alt: None,
+            alt: alt.clone(),
             license: license.map(str::to_string),
+            caption: caption.clone(),
         });
 
         // The media record still uses the raw atrium BlobRef value, which
@@ -513,6 +1280,12 @@ async fn upload_media_records(
         if let Some(license) = license {
             media_record_value["license"] = serde_json::Value::String(license.to_string());
         }
+        if let Some(alt) = alt {
+            media_record_value["alt"] = serde_json::Value::String(alt);
+        }
+        if let Some(caption) = caption {
+            media_record_value["caption"] = serde_json::Value::String(caption);
+        }
         let did_for_media = atrium_api::types::string::Did::new(user_did.to_string())
             .map_err(|e| AppError::Internal(format!("Invalid DID: {e}")))?;
         match auth::create_at_record(agent, did_for_media, MediaRecord::NSID, media_record_value)
@@ -570,13 +1343,17 @@ fn build_occurrence_record_json(
         Some(media_refs)
     };
 
+    // The published record carries only as many digits as the uncertainty
+    // supports; the exact point stays in the private location table.
+    let uncertainty =
+        coordinate_uncertainty_in_meters.unwrap_or(constants::DEFAULT_COORDINATE_UNCERTAINTY);
+    let latitude = round_coordinate(latitude, uncertainty);
+    let longitude = round_coordinate(longitude, uncertainty);
+
     let record = Occurrence::new()
         .decimal_latitude(SmolStr::from(latitude.to_string()))
         .decimal_longitude(SmolStr::from(longitude.to_string()))
-        .coordinate_uncertainty_in_meters(
-            coordinate_uncertainty_in_meters.unwrap_or(constants::DEFAULT_COORDINATE_UNCERTAINTY)
-                as i64,
-        )
+        .coordinate_uncertainty_in_meters(uncertainty as i64)
         .event_date(SmolStr::from(event_date_str))
         .maybe_organism_quantity(
             organism_quantity
@@ -613,28 +1390,14 @@ fn build_occurrence_record_json(
 /// Jetstream delivers commits in repo order, so the preceding occurrence
 /// upsert (needed to satisfy the FK on `identifications.subject_uri`) is
 /// guaranteed to run first.
-#[allow(clippy::too_many_arguments)]
 async fn create_auto_identification(
-    state: &AppState,
     agent: &AgentType,
     user_did: &str,
-    scientific_name: &str,
-    user_taxon_rank: Option<&str>,
-    user_kingdom: Option<&str>,
-    user_taxon_id: Option<&str>,
+    taxon: &ResolvedTaxon,
     occurrence_uri: &str,
     occurrence_cid: &str,
 ) -> Result<(), AppError> {
-    let id_value = auto_id::build_identification_record(
-        state,
-        scientific_name,
-        user_taxon_rank,
-        user_kingdom,
-        user_taxon_id,
-        occurrence_uri,
-        occurrence_cid,
-    )
-    .await?;
+    let id_value = auto_id::build_identification_record(taxon, occurrence_uri, occurrence_cid)?;
     let id_did = atrium_api::types::string::Did::new(user_did.to_string())
         .map_err(|e| AppError::Internal(format!("Invalid DID: {e}")))?;
     match auth::create_at_record(agent, id_did, auto_id::identification_nsid(), id_value).await {
@@ -647,3 +1410,792 @@ async fn create_auto_identification(
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Mutex;
+
+    const BOUNDARY: &str = "occurrence-test-boundary";
+
+    fn multipart_request(parts: &[(&str, Option<&str>, &[u8])]) -> Request {
+        let mut body = Vec::new();
+        for (name, filename, data) in parts {
+            body.extend_from_slice(format!("--{BOUNDARY}\r\n").as_bytes());
+            match filename {
+                Some(filename) => body.extend_from_slice(
+                    format!(
+                        "Content-Disposition: form-data; name=\"{name}\"; filename=\"{filename}\"\r\nContent-Type: image/jpeg\r\n\r\n"
+                    )
+                    .as_bytes(),
+                ),
+                None => body.extend_from_slice(
+                    format!("Content-Disposition: form-data; name=\"{name}\"\r\n\r\n").as_bytes(),
+                ),
+            }
+            body.extend_from_slice(data);
+            body.extend_from_slice(b"\r\n");
+        }
+        body.extend_from_slice(format!("--{BOUNDARY}--\r\n").as_bytes());
+
+        Request::post("/api/occurrences")
+            .header(
+                header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={BOUNDARY}"),
+            )
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    async fn extract(req: Request) -> Result<CreateOccurrenceBody, Response> {
+        CreateOccurrenceBody::from_request(req, &()).await
+    }
+
+    #[tokio::test]
+    async fn multipart_body_carries_fields_and_raw_image_bytes() {
+        let image: &[u8] = &[0xff, 0xd8, 0xff, 0xe0, 0x00, 0x10, b'J', b'F', b'I', b'F'];
+        let req = multipart_request(&[
+            ("latitude", None, b"37.77"),
+            ("longitude", None, b"-122.42"),
+            ("coordinateUncertaintyInMeters", None, b"25"),
+            ("scientificName", None, b"Quercus agrifolia"),
+            ("license", None, b"CC-BY-4.0"),
+            ("eventDate", None, b""),
+            ("images", Some("oak.jpg"), image),
+        ]);
+
+        let body = extract(req).await.ok().unwrap();
+        assert_eq!(body.request.latitude, 37.77);
+        assert_eq!(body.request.longitude, -122.42);
+        assert_eq!(body.request.coordinate_uncertainty_in_meters, Some(25));
+        assert_eq!(
+            body.request.scientific_name.as_deref(),
+            Some("Quercus agrifolia")
+        );
+        assert_eq!(body.request.license.as_deref(), Some("CC-BY-4.0"));
+        assert_eq!(body.request.event_date, None);
+        assert_eq!(body.images, vec![NewImage::bytes(image.to_vec())]);
+    }
+
+    #[tokio::test]
+    async fn multipart_body_requires_coordinates() {
+        let req = multipart_request(&[("latitude", None, b"37.77")]);
+        let rejection = extract(req).await.err().unwrap();
+        assert_eq!(rejection.status(), axum::http::StatusCode::BAD_REQUEST);
+
+        let req = multipart_request(&[("latitude", None, b"north"), ("longitude", None, b"0")]);
+        let rejection = extract(req).await.err().unwrap();
+        assert_eq!(rejection.status(), axum::http::StatusCode::BAD_REQUEST);
+    }
+
+    fn create_request(body: serde_json::Value) -> CreateOccurrenceRequest {
+        serde_json::from_value(body).unwrap()
+    }
+
+    #[test]
+    fn preview_returns_the_record_create_would_write() {
+        let record = preview_occurrence_record(
+            &create_request(json!({
+                "latitude": 37.5,
+                "longitude": -122.25,
+                "coordinateUncertaintyInMeters": 1000,
+                "eventDate": "2026-05-01",
+                "organismQuantity": "3",
+                "scientificName": "Quercus agrifolia",
+            })),
+            CoordinateChecks::default(),
+        )
+        .unwrap();
+        assert_eq!(record["$type"], OccurrenceRecord::NSID);
+        assert_eq!(record["decimalLatitude"], "37.5");
+        assert_eq!(record["decimalLongitude"], "-122.25");
+        assert_eq!(record["coordinateUncertaintyInMeters"], 1000);
+        assert_eq!(record["eventDate"], "2026-05-01");
+        assert_eq!(record["organismQuantity"], "3");
+        assert!(record.get("media").is_none());
+    }
+
+    #[test]
+    fn published_coordinates_are_rounded_to_uncertainty() {
+        let record = preview_occurrence_record(
+            &create_request(json!({
+                "latitude": 37.774929483712,
+                "longitude": -122.419415523901,
+                "coordinateUncertaintyInMeters": 5000,
+            })),
+            CoordinateChecks::default(),
+        )
+        .unwrap();
+        assert_eq!(record["decimalLatitude"], "37.77");
+        assert_eq!(record["decimalLongitude"], "-122.42");
+
+        // Without a declared uncertainty the default (50 m) applies.
+        let record = preview_occurrence_record(
+            &create_request(json!({
+                "latitude": 37.774929483712,
+                "longitude": -122.419415523901,
+            })),
+            CoordinateChecks::default(),
+        )
+        .unwrap();
+        assert_eq!(record["decimalLatitude"], "37.7749");
+        assert_eq!(record["coordinateUncertaintyInMeters"], 50);
+    }
+
+    #[test]
+    fn stale_expected_cid_is_a_conflict() {
+        assert!(check_expected_cid(None, Some("bafyreicurrent")).is_ok());
+        assert!(check_expected_cid(Some("bafyreicurrent"), Some("bafyreicurrent")).is_ok());
+
+        let err = check_expected_cid(Some("bafyreistale"), Some("bafyreicurrent")).unwrap_err();
+        assert_eq!(
+            err.into_response().status(),
+            axum::http::StatusCode::CONFLICT
+        );
+        // A PDS that doesn't report the current CID can't confirm the swap.
+        assert!(check_expected_cid(Some("bafyreistale"), None).is_err());
+    }
+
+    /// Coordinate checks turned off, for tests about other fields.
+    const ANY_COORDINATES: CoordinateChecks = CoordinateChecks {
+        reject_null_island: false,
+        integer_min_uncertainty_m: 0,
+    };
+
+    #[test]
+    fn null_island_is_rejected() {
+        let checks = CoordinateChecks::default();
+        for (lat, lng) in [(0.0, 0.0), (0.00001, -0.00002)] {
+            let err = check_coordinates(lat, lng, Some(10), checks).unwrap_err();
+            assert!(
+                matches!(&err, AppError::BadRequest(m) if m.contains("0,0")),
+                "{err:?}"
+            );
+        }
+        // On the equator or the prime meridian is fine.
+        assert!(check_coordinates(0.0, 32.58, Some(10), checks).is_ok());
+        assert!(check_coordinates(51.4779, 0.0, Some(10), checks).is_ok());
+        assert!(check_coordinates(0.0, 0.0, Some(10), ANY_COORDINATES).is_ok());
+    }
+
+    #[test]
+    fn integer_coordinates_need_a_large_uncertainty() {
+        let checks = CoordinateChecks::default();
+        assert!(matches!(
+            check_coordinates(37.0, -122.0, Some(10), checks),
+            Err(AppError::BadRequest(_))
+        ));
+        // A missing uncertainty falls back to the tight default.
+        assert!(check_coordinates(37.0, -122.0, None, checks).is_err());
+        assert!(check_coordinates(37.0, -122.0, Some(50_000), checks).is_ok());
+        // Only both axes being whole degrees is suspicious.
+        assert!(check_coordinates(37.0, -122.5, Some(10), checks).is_ok());
+        assert!(check_coordinates(37.0, -122.0, Some(10), ANY_COORDINATES).is_ok());
+    }
+
+    #[test]
+    fn preview_rejects_what_create_rejects() {
+        for body in [
+            json!({ "latitude": 91.0, "longitude": 0.0 }),
+            json!({ "latitude": 0.0, "longitude": 0.0, "eventDate": "last tuesday" }),
+            json!({ "latitude": 0.0, "longitude": 0.0, "license": "WTFPL" }),
+        ] {
+            assert!(
+                matches!(
+                    preview_occurrence_record(&create_request(body.clone()), ANY_COORDINATES),
+                    Err(AppError::BadRequest(_))
+                ),
+                "{body}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn json_body_decodes_base64_images() {
+        let req = Request::post("/api/occurrences")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(
+                json!({
+                    "latitude": 1.5,
+                    "longitude": 2.5,
+                    "images": [{ "data": "/9j/4AAQSkZJRg==", "mimeType": "image/jpeg" }],
+                })
+                .to_string(),
+            ))
+            .unwrap();
+
+        let body = extract(req).await.ok().unwrap();
+        assert_eq!(body.request.latitude, 1.5);
+        assert!(body.request.images.is_none());
+        assert_eq!(body.images, vec![NewImage::bytes(JPEG_HEADER.to_vec())]);
+    }
+
+    #[tokio::test]
+    async fn json_body_keeps_image_alt_and_caption() {
+        let req = Request::post("/api/occurrences")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(
+                json!({
+                    "latitude": 1.5,
+                    "longitude": 2.5,
+                    "images": [
+                        {
+                            "data": "/9j/4AAQSkZJRg==",
+                            "mimeType": "image/jpeg",
+                            "alt": " Upper side of a leaf ",
+                            "caption": "Found on the north slope",
+                        },
+                        { "data": "/9j/4AAQSkZJRg==", "mimeType": "image/jpeg", "alt": "  " },
+                    ],
+                })
+                .to_string(),
+            ))
+            .unwrap();
+        let body = extract(req).await.ok().unwrap();
+        assert_eq!(body.images[0].alt.as_deref(), Some("Upper side of a leaf"));
+        assert_eq!(
+            body.images[0].caption.as_deref(),
+            Some("Found on the north slope")
+        );
+        // Blank alt text is the same as none.
+        assert_eq!(body.images[1], NewImage::bytes(JPEG_HEADER.to_vec()));
+    }
+
+    #[test]
+    fn overlong_alt_text_is_rejected() {
+        let upload = ImageUpload {
+            data: "/9j/4AAQSkZJRg==".into(),
+            mime_type: "image/jpeg".into(),
+            alt: Some("a".repeat(constants::MAX_IMAGE_ALT_LENGTH + 1)),
+            caption: None,
+        };
+        let err = decode_images(&[upload]).unwrap_err();
+        assert!(
+            matches!(&err, AppError::BadRequest(m) if m.starts_with("Image 1 alt text")),
+            "{err:?}"
+        );
+    }
+
+    const JPEG_HEADER: &[u8] = &[0xff, 0xd8, 0xff, 0xe0, 0x00, 0x10, b'J', b'F', b'I', b'F'];
+
+    #[test]
+    fn image_sniffing_accepts_a_jpeg_header() {
+        assert!(check_image_bytes(JPEG_HEADER, 0).is_ok());
+        assert!(check_image_bytes(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR", 0).is_ok());
+    }
+
+    #[test]
+    fn image_sniffing_rejects_non_images() {
+        for bytes in [&b"just some text"[..], &b"%PDF-1.7"[..], &b""[..]] {
+            let err = check_image_bytes(bytes, 1).unwrap_err();
+            assert!(
+                matches!(&err, AppError::BadRequest(m) if m.starts_with("Image 2 ")),
+                "{err:?}"
+            );
+        }
+    }
+
+    const LIMITS: ImageLimits = ImageLimits {
+        max_count: 3,
+        max_total_bytes: 1000,
+    };
+
+    fn images_of(sizes: &[usize]) -> Vec<NewImage> {
+        sizes.iter().map(|&n| NewImage::bytes(vec![0; n])).collect()
+    }
+
+    #[test]
+    fn image_limits_cap_the_count() {
+        let images = images_of(&[10; 3]);
+        assert!(check_image_limits(&images, 0, LIMITS).is_ok());
+
+        let images = images_of(&[10; 4]);
+        let err = check_image_limits(&images, 0, LIMITS).unwrap_err();
+        assert!(matches!(&err, AppError::BadRequest(m) if m.starts_with("Too many images")));
+    }
+
+    #[test]
+    fn image_limits_count_retained_media() {
+        let images = images_of(&[10; 2]);
+        assert!(check_image_limits(&images, 1, LIMITS).is_ok());
+        assert!(check_image_limits(&images, 2, LIMITS).is_err());
+    }
+
+    #[test]
+    fn image_limits_cap_the_total_size() {
+        let images = images_of(&[500, 500]);
+        assert!(check_image_limits(&images, 0, LIMITS).is_ok());
+
+        // Each image is small; together they're over.
+        let images = images_of(&[500, 501]);
+        let err = check_image_limits(&images, 0, LIMITS).unwrap_err();
+        assert!(
+            matches!(&err, AppError::BadRequest(m) if m.starts_with("Images total 1001 bytes"))
+        );
+    }
+
+    #[tokio::test]
+    async fn json_body_rejects_a_text_blob_labelled_as_jpeg() {
+        let req = Request::post("/api/occurrences")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(
+                json!({
+                    "latitude": 1.5,
+                    "longitude": 2.5,
+                    "images": [{ "data": "aGVsbG8=", "mimeType": "image/jpeg" }],
+                })
+                .to_string(),
+            ))
+            .unwrap();
+
+        let rejection = extract(req).await.err().unwrap();
+        assert_eq!(rejection.status(), axum::http::StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn multipart_body_rejects_a_text_blob() {
+        let req = multipart_request(&[
+            ("latitude", None, b"37.77"),
+            ("longitude", None, b"-122.42"),
+            ("images", Some("notes.jpg"), b"not really a photo"),
+        ]);
+        let rejection = extract(req).await.err().unwrap();
+        assert_eq!(rejection.status(), axum::http::StatusCode::BAD_REQUEST);
+    }
+
+    fn key_headers(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("idempotency-key", value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn idempotency_key_is_optional() {
+        assert_eq!(idempotency_key(&HeaderMap::new()).ok().unwrap(), None);
+    }
+
+    #[test]
+    fn idempotency_key_is_trimmed() {
+        assert_eq!(
+            idempotency_key(&key_headers(" 7c1f-4e2a ")).ok().unwrap(),
+            Some("7c1f-4e2a".to_string())
+        );
+    }
+
+    #[test]
+    fn idempotency_key_rejects_malformed_values() {
+        let too_long = "k".repeat(constants::MAX_IDEMPOTENCY_KEY_LENGTH + 1);
+        for value in ["", "   ", "two words", too_long.as_str()] {
+            assert!(
+                matches!(
+                    idempotency_key(&key_headers(value)),
+                    Err(AppError::BadRequest(_))
+                ),
+                "{value:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn fresh_key_proceeds_and_busy_key_conflicts() {
+        assert!(replayed_response(Reservation::Reserved).is_none());
+        assert!(matches!(
+            replayed_response(Reservation::InProgress),
+            Some(Err(AppError::Conflict(_)))
+        ));
+    }
+
+    /// Idempotency keys in memory, keyed by `(did, key)` to the request
+    /// digest and, once completed, the record.
+    #[derive(Default)]
+    struct MemoryKeys {
+        keys: Mutex<HashMap<(String, String), (String, Option<(String, String)>)>>,
+        /// How many upcoming `complete` calls fail.
+        failing_completes: AtomicU32,
+    }
+
+    #[async_trait]
+    impl IdempotencyStore for MemoryKeys {
+        async fn reserve(
+            &self,
+            did: &str,
+            key: &str,
+            request_hash: &str,
+        ) -> Result<Reservation, AppError> {
+            let mut keys = self.keys.lock().unwrap();
+            Ok(match keys.get(&(did.into(), key.into())) {
+                None => {
+                    keys.insert((did.into(), key.into()), (request_hash.into(), None));
+                    Reservation::Reserved
+                }
+                Some((hash, _)) if hash != request_hash => Reservation::Mismatch,
+                Some((_, Some((uri, cid)))) => Reservation::Completed {
+                    uri: uri.clone(),
+                    cid: cid.clone(),
+                },
+                Some((_, None)) => Reservation::InProgress,
+            })
+        }
+
+        async fn complete(
+            &self,
+            did: &str,
+            key: &str,
+            uri: &str,
+            cid: &str,
+        ) -> Result<(), AppError> {
+            let failing = self.failing_completes.load(Ordering::SeqCst);
+            if failing > 0 {
+                self.failing_completes.store(failing - 1, Ordering::SeqCst);
+                return Err(AppError::Internal("store unavailable".into()));
+            }
+            if let Some(entry) = self.keys.lock().unwrap().get_mut(&(did.into(), key.into())) {
+                entry.1 = Some((uri.into(), cid.into()));
+            }
+            Ok(())
+        }
+
+        async fn release(&self, did: &str, key: &str) -> Result<(), AppError> {
+            let mut keys = self.keys.lock().unwrap();
+            if keys
+                .get(&(did.into(), key.into()))
+                .is_some_and(|(_, record)| record.is_none())
+            {
+                keys.remove(&(did.into(), key.into()));
+            }
+            Ok(())
+        }
+    }
+
+    /// Create through `create_once` with a fake PDS write that mints a new
+    /// record per call (counted in `creates`), or fails when `fail` is set.
+    /// Returns the URI the client would see.
+    async fn keyed_create(
+        store: &MemoryKeys,
+        key: Option<&str>,
+        request_hash: &str,
+        creates: &AtomicU32,
+        fail: bool,
+    ) -> Result<String, AppError> {
+        let create = async {
+            if fail {
+                return Err(AppError::BadGateway("PDS unavailable".into()));
+            }
+            let n = creates.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok((
+                (),
+                format!("at://did:plc:abc/bio.lexicons.temp.v0-1.occurrence/{n}"),
+                format!("bafyrei{n}"),
+            ))
+        };
+        let key = key.map(|k| (k, request_hash));
+        Ok(
+            match create_once(store, "did:plc:abc", key, create).await? {
+                KeyedCreate::Created { uri, .. } => uri,
+                KeyedCreate::Replayed(Json(response)) => response.uri,
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn one_key_creates_one_record() {
+        let store = MemoryKeys::default();
+        let creates = AtomicU32::new(0);
+
+        let first = keyed_create(&store, Some("k1"), "body", &creates, false).await;
+        let retry = keyed_create(&store, Some("k1"), "body", &creates, false).await;
+        assert_eq!(first.unwrap(), retry.unwrap());
+        assert_eq!(creates.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn distinct_keys_create_two_records() {
+        let store = MemoryKeys::default();
+        let creates = AtomicU32::new(0);
+
+        let first = keyed_create(&store, Some("k1"), "body", &creates, false).await;
+        let second = keyed_create(&store, Some("k2"), "body", &creates, false).await;
+        assert_ne!(first.unwrap(), second.unwrap());
+        assert_eq!(creates.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn reusing_a_key_for_another_request_is_unprocessable() {
+        let store = MemoryKeys::default();
+        let creates = AtomicU32::new(0);
+
+        keyed_create(&store, Some("k1"), "body", &creates, false)
+            .await
+            .unwrap();
+        let reused = keyed_create(&store, Some("k1"), "other body", &creates, false).await;
+        assert!(
+            matches!(reused, Err(AppError::UnprocessableEntity(_))),
+            "{reused:?}"
+        );
+        assert_eq!(creates.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn a_failed_create_frees_its_key() {
+        let store = MemoryKeys::default();
+        let creates = AtomicU32::new(0);
+
+        assert!(keyed_create(&store, Some("k1"), "body", &creates, true)
+            .await
+            .is_err());
+        keyed_create(&store, Some("k1"), "body", &creates, false)
+            .await
+            .unwrap();
+        assert_eq!(creates.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn key_completion_is_retried_then_fails_the_request() {
+        let store = MemoryKeys::default();
+        let creates = AtomicU32::new(0);
+
+        store.failing_completes.store(1, Ordering::SeqCst);
+        let first = keyed_create(&store, Some("k1"), "body", &creates, false).await;
+        let retry = keyed_create(&store, Some("k1"), "body", &creates, false).await;
+        assert_eq!(first.unwrap(), retry.unwrap());
+
+        store
+            .failing_completes
+            .store(COMPLETE_ATTEMPTS, Ordering::SeqCst);
+        let unrecorded = keyed_create(&store, Some("k2"), "body", &creates, false).await;
+        assert!(
+            matches!(unrecorded, Err(AppError::Internal(_))),
+            "{unrecorded:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn without_a_key_every_request_creates() {
+        let store = MemoryKeys::default();
+        let creates = AtomicU32::new(0);
+
+        for _ in 0..2 {
+            keyed_create(&store, None, "body", &creates, false)
+                .await
+                .unwrap();
+        }
+        assert_eq!(creates.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn fingerprint_covers_fields_and_images() {
+        let body = |lat: f64| {
+            create_request(json!({
+                "latitude": lat,
+                "longitude": -122.42,
+            }))
+        };
+        let photo = [NewImage::bytes(JPEG_HEADER.to_vec())];
+
+        let base = request_fingerprint(&body(37.77), &photo);
+        assert_eq!(base, request_fingerprint(&body(37.77), &photo));
+        assert_ne!(base, request_fingerprint(&body(37.78), &photo));
+        assert_ne!(base, request_fingerprint(&body(37.77), &[]));
+    }
+
+    #[test]
+    fn auto_identify_defaults_on() {
+        let body = create_request(json!({
+            "latitude": 0.0,
+            "longitude": 0.0,
+            "scientificName": "Quercus alba",
+        }));
+        assert_eq!(auto_id_name(&body), Some("Quercus alba"));
+    }
+
+    #[test]
+    fn auto_identify_false_skips_identification() {
+        let body = create_request(json!({
+            "latitude": 0.0,
+            "longitude": 0.0,
+            "scientificName": "Quercus alba",
+            "autoIdentify": false,
+        }));
+        assert_eq!(auto_id_name(&body), None);
+    }
+
+    #[test]
+    fn min_auto_id_rank_must_be_a_known_rank() {
+        let body = |rank: &str| {
+            create_request(json!({
+                "latitude": 0.0,
+                "longitude": 0.0,
+                "minAutoIdRank": rank,
+            }))
+        };
+        assert!(check_create_request(&body("species"), ANY_COORDINATES).is_ok());
+        assert!(check_create_request(&body("Genus"), ANY_COORDINATES).is_ok());
+        assert!(matches!(
+            check_create_request(&body("speces"), ANY_COORDINATES),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn validate_resolves_auto_id_through_the_taxonomy_provider() {
+        use crate::taxonomy_client::FakeTaxonomy;
+        use std::sync::Arc;
+
+        let taxonomy = FakeTaxonomy::new(vec![
+            FakeTaxonomy::taxon("Quercus agrifolia", "species", "Plantae"),
+            FakeTaxonomy::taxon("Quercus", "genus", "Plantae"),
+        ]);
+        let (state, _media_dir) = AppState::for_tests(Arc::new(taxonomy)).await;
+        let validate = |body: serde_json::Value| {
+            let state = state.clone();
+            async move {
+                let user = AuthUser {
+                    did: "did:plc:observer".to_string(),
+                    session_id: "device".to_string(),
+                };
+                let Json(preview) =
+                    validate_occurrence(State(state), user, Json(create_request(body)))
+                        .await
+                        .unwrap();
+                preview.identification
+            }
+        };
+
+        assert_eq!(
+            validate(json!({
+                "latitude": 34.05,
+                "longitude": -118.25,
+                "scientificName": "Quercus agrifolia",
+            }))
+            .await,
+            Some(ResolvedTaxon {
+                scientific_name: "Quercus agrifolia".to_string(),
+                taxon_rank: Some("species".to_string()),
+                kingdom: Some("Plantae".to_string()),
+                taxon_id: Some("https://example.org/taxa/Quercus agrifolia".to_string()),
+            })
+        );
+
+        // The resolved rank, not the caller's, is checked against the floor.
+        assert_eq!(
+            validate(json!({
+                "latitude": 34.05,
+                "longitude": -118.25,
+                "scientificName": "Quercus",
+                "taxonRank": "species",
+                "minAutoIdRank": "species",
+            }))
+            .await,
+            None
+        );
+
+        // A name the provider doesn't know keeps the caller's rank and kingdom.
+        let unknown = validate(json!({
+            "latitude": 34.05,
+            "longitude": -118.25,
+            "scientificName": "Quercus nova",
+            "taxonRank": "species",
+            "kingdom": "Plantae",
+        }))
+        .await
+        .unwrap();
+        assert_eq!(unknown.taxon_rank.as_deref(), Some("species"));
+        assert_eq!(unknown.kingdom.as_deref(), Some("Plantae"));
+        assert_eq!(unknown.taxon_id, None);
+    }
+
+    #[tokio::test]
+    async fn multipart_body_reads_auto_id_flags() {
+        let req = multipart_request(&[
+            ("latitude", None, b"1.5"),
+            ("longitude", None, b"2.5"),
+            ("autoIdentify", None, b"false"),
+            ("minAutoIdRank", None, b"species"),
+        ]);
+
+        let body = extract(req).await.ok().unwrap();
+        assert_eq!(body.request.auto_identify, Some(false));
+        assert_eq!(body.request.min_auto_id_rank.as_deref(), Some("species"));
+    }
+
+    fn patch(body: serde_json::Value) -> PatchOccurrenceRequest {
+        serde_json::from_value(body).unwrap()
+    }
+
+    fn stored_occurrence() -> serde_json::Value {
+        json!({
+            "$type": "bio.lexicons.temp.v0-1.occurrence",
+            "decimalLatitude": "37.77",
+            "decimalLongitude": "-122.42",
+            "coordinateUncertaintyInMeters": 1000,
+            "eventDate": "2026-05-01",
+            "organismQuantity": "3",
+            "organismQuantityType": "individuals",
+            "associatedMedia": [{ "uri": "at://did:plc:abc/bio.lexicons.temp.v0-1.media/1", "cid": "bafyreimedia" }],
+            "createdAt": "2026-05-01T12:00:00Z",
+        })
+    }
+
+    /// A patch rewrites only the named field: location, media and the
+    /// original `createdAt` go back to the PDS as they were.
+    #[test]
+    fn patch_changes_only_given_fields() {
+        let before = stored_occurrence();
+        let after =
+            apply_occurrence_patch(before.clone(), &patch(json!({ "eventDate": "2026-04-30" })))
+                .ok()
+                .unwrap();
+
+        assert_eq!(after["eventDate"], "2026-04-30");
+        let mut unchanged = after.clone();
+        unchanged["eventDate"] = before["eventDate"].clone();
+        assert_eq!(unchanged, before);
+    }
+
+    #[test]
+    fn patch_empty_string_removes_quantity() {
+        let after = apply_occurrence_patch(
+            stored_occurrence(),
+            &patch(json!({ "organismQuantity": "", "organismQuantityType": "" })),
+        )
+        .ok()
+        .unwrap();
+
+        assert!(after.get("organismQuantity").is_none());
+        assert!(after.get("organismQuantityType").is_none());
+        assert_eq!(after["eventDate"], "2026-05-01");
+    }
+
+    #[test]
+    fn patch_rejects_bad_or_empty_edits() {
+        for body in [json!({}), json!({ "eventDate": "last tuesday" })] {
+            assert!(
+                matches!(
+                    apply_occurrence_patch(stored_occurrence(), &patch(body.clone())),
+                    Err(AppError::BadRequest(_))
+                ),
+                "{body}"
+            );
+        }
+    }
+
+    #[test]
+    fn patch_is_restricted_to_own_occurrences() {
+        let uri = "at://did:plc:abc/bio.lexicons.temp.v0-1.occurrence/3kabc";
+        assert!(own_occurrence_record(uri, "did:plc:abc").is_ok());
+        assert!(matches!(
+            own_occurrence_record(uri, "did:plc:other"),
+            Err(AppError::Forbidden(_))
+        ));
+        assert!(matches!(
+            own_occurrence_record(
+                "at://did:plc:abc/bio.lexicons.temp.v0-1.identification/3kabc",
+                "did:plc:abc"
+            ),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+}
@@ -1,14 +1,16 @@
 use atproto_identity::Did;
 use axum::extract::{Path, Query, State};
 use axum::Json;
-use observing_db::types::{ProfileFeedOptions, ProfileFeedType};
+use observing_db::cursor::FeedCursor;
+use observing_db::types::{ActivityOptions, ActivityRow, ProfileFeedOptions, ProfileFeedType};
 use serde::Deserialize;
 
-use crate::auth::session_did;
-use crate::constants;
+use crate::auth::{session_did, AuthUser};
 use crate::enrichment::{self, ProfileSummary};
 use crate::error::AppError;
-use crate::responses::{ProfileCounts, ProfileFeedResponse};
+use crate::responses::{
+    ActivityResponse, PendingOccurrencesResponse, ProfileCounts, ProfileFeedResponse,
+};
 use crate::state::AppState;
 
 #[derive(Deserialize)]
@@ -31,10 +33,7 @@ pub async fn get_profile_feed(
     let did =
         Did::new_owned(&did).map_err(|e| AppError::BadRequest(format!("Invalid DID: {e}")))?;
 
-    let limit = params
-        .limit
-        .unwrap_or(constants::DEFAULT_FEED_LIMIT)
-        .min(constants::MAX_FEED_LIMIT);
+    let limit = state.page_limits.feed.resolve(params.limit);
 
     let feed_type = match params.feed_type.as_deref() {
         Some("observations") => ProfileFeedType::Observations,
@@ -44,34 +43,42 @@ pub async fn get_profile_feed(
 
     let options = ProfileFeedOptions {
         limit: Some(limit),
-        cursor: params.cursor,
+        cursor: params
+            .cursor
+            .as_deref()
+            .map(FeedCursor::decode)
+            .transpose()?,
         feed_type: Some(feed_type),
     };
 
-    let result = observing_db::feeds::get_profile_feed(&state.pool, did.as_str(), &options).await?;
+    let result =
+        observing_db::feeds::get_profile_feed(&state.read_pool, did.as_str(), &options).await?;
 
     let viewer = session_did(&cookies);
     let (occurrences, identifications, profile) = tokio::join!(
         enrichment::enrich_occurrences(
-            &state.pool,
-            &state.resolver,
-            &state.taxonomy,
+            &state.read_pool,
+            &*state.resolver,
+            &*state.taxonomy,
+            state.blob_links(),
             &result.occurrences,
             viewer.as_deref(),
         ),
-        enrichment::enrich_identifications(&state.resolver, &result.identifications),
+        enrichment::enrich_identifications(&*state.resolver, &result.identifications),
         state.resolver.get_profile(did.as_str()),
     );
 
-    let next_cursor = occurrences
+    let next_cursor = result
+        .occurrences
         .last()
-        .map(|o| o.created_at.clone())
+        .map(|o| FeedCursor::new(o.created_at, o.uri.clone()))
         .or_else(|| {
             result
                 .identifications
                 .last()
-                .map(|i| i.date_identified.to_string())
-        });
+                .map(|i| FeedCursor::new(i.date_identified, i.uri.clone()))
+        })
+        .map(|c| c.encode());
 
     Ok(Json(ProfileFeedResponse {
         profile: ProfileSummary {
@@ -90,3 +97,185 @@ pub async fn get_profile_feed(
         cursor: next_cursor,
     }))
 }
+
+#[derive(Deserialize)]
+pub struct PendingParams {
+    limit: Option<i64>,
+}
+
+/// The viewer's occurrence writes that the ingester hasn't confirmed, e.g.
+/// because the PDS never emitted the firehose commit. Only the record's
+/// author may list them.
+pub async fn get_pending(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(did): Path<String>,
+    Query(params): Query<PendingParams>,
+) -> Result<Json<PendingOccurrencesResponse>, AppError> {
+    if did != user.did {
+        return Err(AppError::Forbidden(
+            "You can only list your own pending records".into(),
+        ));
+    }
+    let limit = state.page_limits.feed.resolve(params.limit);
+    let occurrences =
+        observing_db::occurrence_writes::list_pending(&state.pool, &user.did, limit).await?;
+    Ok(Json(PendingOccurrencesResponse { occurrences }))
+}
+
+#[derive(Deserialize)]
+pub struct ActivityParams {
+    limit: Option<i64>,
+    cursor: Option<String>,
+}
+
+/// What other people have done with the user's records: identifications,
+/// comments and likes on their occurrences and replies to their comments,
+/// merged into one stream, newest first.
+pub async fn get_activity(
+    State(state): State<AppState>,
+    Path(did): Path<String>,
+    Query(params): Query<ActivityParams>,
+) -> Result<Json<ActivityResponse>, AppError> {
+    let did =
+        Did::new_owned(&did).map_err(|e| AppError::BadRequest(format!("Invalid DID: {e}")))?;
+
+    let limit = state.page_limits.feed.resolve(params.limit);
+    let options = ActivityOptions {
+        limit: Some(limit),
+        cursor: params
+            .cursor
+            .as_deref()
+            .map(FeedCursor::decode)
+            .transpose()?,
+    };
+    let rows =
+        observing_db::feeds::activity(&state.read_pool, did.as_str(), &options, &state.hidden_dids)
+            .await?;
+
+    let cursor = next_activity_cursor(&rows, limit);
+    let items = enrichment::enrich_activity(&*state.resolver, &rows).await;
+
+    Ok(Json(ActivityResponse { items, cursor }))
+}
+
+/// Cursor for the page after `rows`, or `None` when `rows` is short of
+/// `limit` and so already the last page.
+fn next_activity_cursor(rows: &[ActivityRow], limit: i64) -> Option<String> {
+    if rows.len() as i64 != limit {
+        return None;
+    }
+    rows.last()
+        .map(|r| FeedCursor::new(r.created_at, r.uri.clone()).encode())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn activity_row(kind: &str, second: u32, rkey: &str) -> ActivityRow {
+        ActivityRow {
+            kind: kind.into(),
+            uri: format!("at://did:plc:other/bio.lexicons.temp.v0-1.{kind}/{rkey}"),
+            did: "did:plc:other".into(),
+            subject_uri: "at://did:plc:me/bio.lexicons.temp.v0-1.occurrence/1".into(),
+            created_at: Utc.with_ymd_and_hms(2026, 6, 2, 21, 13, second).unwrap(),
+            scientific_name: None,
+            body: None,
+            reply_to_uri: None,
+        }
+    }
+
+    #[test]
+    fn activity_cursor_only_on_a_full_page() {
+        let rows = [
+            activity_row("like", 49, "b"),
+            activity_row("comment", 48, "a"),
+        ];
+        let cursor = FeedCursor::decode(&next_activity_cursor(&rows, 2).unwrap()).unwrap();
+        assert_eq!(
+            cursor,
+            FeedCursor::new(rows[1].created_at, rows[1].uri.clone())
+        );
+        // A short page is the last one; a cursor would only buy an empty
+        // request.
+        assert_eq!(next_activity_cursor(&rows, 3), None);
+        assert_eq!(next_activity_cursor(&[], 3), None);
+    }
+
+    /// One page of `stream` as `feeds::activity` returns it: rows strictly
+    /// after `cursor` in `(created_at DESC, uri DESC)` order, up to `limit`.
+    fn page(stream: &[ActivityRow], cursor: Option<&str>, limit: i64) -> Vec<ActivityRow> {
+        let after = cursor.map(|c| FeedCursor::decode(c).unwrap());
+        let mut rows: Vec<ActivityRow> = stream
+            .iter()
+            .filter(|r| {
+                after
+                    .as_ref()
+                    .is_none_or(|c| (r.created_at, r.uri.as_str()) < (c.created_at, c.uri.as_str()))
+            })
+            .cloned()
+            .collect();
+        rows.sort_by(|a, b| (b.created_at, &b.uri).cmp(&(a.created_at, &a.uri)));
+        rows.truncate(limit as usize);
+        rows
+    }
+
+    #[tokio::test]
+    async fn activity_pages_through_mixed_event_types_in_order() {
+        let identification = ActivityRow {
+            scientific_name: Some("Quercus alba".into()),
+            ..activity_row("identification", 50, "a")
+        };
+        // A like and a comment at the same instant: the URI breaks the tie,
+        // so neither is skipped or repeated across the page boundary.
+        let like = activity_row("like", 49, "a");
+        let comment = ActivityRow {
+            body: Some("Nice find".into()),
+            ..activity_row("comment", 49, "a")
+        };
+        let reply = ActivityRow {
+            body: Some("Agreed!".into()),
+            reply_to_uri: Some("at://did:plc:me/bio.lexicons.temp.v0-1.comment/0".into()),
+            ..activity_row("reply", 48, "a")
+        };
+        let older_identification = ActivityRow {
+            scientific_name: Some("Quercus rubra".into()),
+            ..activity_row("identification", 47, "b")
+        };
+        // Shuffled, as the union hands them to the sort.
+        let stream = [
+            comment.clone(),
+            older_identification.clone(),
+            like.clone(),
+            reply.clone(),
+            identification.clone(),
+        ];
+        let resolver = crate::identity::MockIdentityProvider::default();
+
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        loop {
+            let rows = page(&stream, cursor.as_deref(), 2);
+            let items = enrichment::enrich_activity(&resolver, &rows).await;
+            seen.extend(
+                items
+                    .iter()
+                    .map(|item| serde_json::to_value(item).unwrap())
+                    .map(|v| (v["type"].as_str().unwrap().to_string(), v["uri"].clone())),
+            );
+            cursor = next_activity_cursor(&rows, 2);
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        let expected: Vec<(String, serde_json::Value)> =
+            [identification, like, comment, reply, older_identification]
+                .iter()
+                .map(|r| (r.kind.clone(), r.uri.clone().into()))
+                .collect();
+        assert_eq!(seen, expected);
+    }
+}
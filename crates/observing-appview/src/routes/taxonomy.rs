@@ -1,16 +1,30 @@
+use std::collections::HashSet;
+
 use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
 use axum::Json;
-use observing_db::types::TaxonOccurrenceOptions;
+use observing_db::cursor::FeedCursor;
+use observing_db::types::{
+    Kingdom, LocalTaxonMatchRow, TaxonImageRow, TaxonOccurrenceOptions, KINGDOMS,
+};
 use serde::Deserialize;
+use serde_json::json;
 
 use crate::auth::session_did;
+use crate::config::BlobUrlStrategy;
 use crate::constants;
-use crate::enrichment;
+use crate::enrichment::{self, MediaAccess};
 use crate::error::AppError;
-use crate::responses::{OccurrenceListResponse, TaxonSearchResponse};
+use crate::responses::{
+    OccurrenceListResponse, TaxonImage, TaxonImagesResponse, TaxonInteractionsResponse,
+    TaxonSearchResponse,
+};
 use crate::state::AppState;
+use crate::taxonomy::gbif::build_taxon_path;
 use crate::taxonomy_client::{
-    TaxonDetail, TaxonDetailWithCount, TaxonomyClientError, ValidateResponse,
+    TaxonDetail, TaxonDetailWithCount, TaxonMedia, TaxonResult, TaxonomyClientError,
+    TaxonomyProvider, ValidateResponse,
 };
 
 #[derive(Deserialize)]
@@ -42,6 +56,100 @@ pub async fn search(
     Ok(Json(TaxonSearchResponse { results }))
 }
 
+/// Autocomplete over taxa: names already observed here (most-observed first)
+/// followed by GBIF search results. Local names still come back when GBIF is
+/// unreachable.
+pub async fn suggest(
+    State(state): State<AppState>,
+    Query(params): Query<SearchParams>,
+) -> Result<Json<TaxonSearchResponse>, AppError> {
+    let query = params
+        .q
+        .ok_or_else(|| AppError::BadRequest("q is required".into()))?;
+    let query = query.trim();
+
+    if query.len() < constants::MIN_SEARCH_QUERY_LENGTH {
+        return Err(AppError::BadRequest(format!(
+            "Search query must be at least {} characters",
+            constants::MIN_SEARCH_QUERY_LENGTH
+        )));
+    }
+
+    let limit = constants::TAXON_SUGGEST_LIMIT;
+    let (local, remote) = tokio::join!(
+        observing_db::feeds::suggest_local_taxa(
+            &state.read_pool,
+            query,
+            limit as i64,
+            &state.hidden_dids
+        ),
+        state.taxonomy.search(query, Some(limit)),
+    );
+
+    Ok(Json(TaxonSearchResponse {
+        results: merge_suggestions(local?, remote.unwrap_or_default(), limit as usize),
+    }))
+}
+
+/// Rank local matches ahead of GBIF-only ones, deduping by name.
+///
+/// A local name that GBIF also returned takes GBIF's richer entry (key,
+/// common name, ancestry) but keeps its local position.
+fn merge_suggestions(
+    local: Vec<LocalTaxonMatchRow>,
+    mut remote: Vec<TaxonResult>,
+    limit: usize,
+) -> Vec<TaxonResult> {
+    let mut seen = HashSet::new();
+    let mut results = Vec::with_capacity(limit);
+
+    for row in local {
+        if !seen.insert(row.scientific_name.to_lowercase()) {
+            continue;
+        }
+        let gbif = remote.iter().position(|r| {
+            r.scientific_name.eq_ignore_ascii_case(&row.scientific_name)
+                && (row.kingdom.is_none() || r.kingdom == row.kingdom)
+        });
+        results.push(match gbif {
+            Some(i) => remote.remove(i),
+            None => local_taxon_result(row),
+        });
+    }
+    for result in remote {
+        if seen.insert(result.scientific_name.to_lowercase()) {
+            results.push(result);
+        }
+    }
+
+    results.truncate(limit);
+    results
+}
+
+fn local_taxon_result(row: LocalTaxonMatchRow) -> TaxonResult {
+    let rank = row
+        .taxon_rank
+        .map(|r| r.to_lowercase())
+        .unwrap_or_else(|| "unknown".to_string());
+    TaxonResult {
+        id: build_taxon_path(&row.scientific_name, &rank, row.kingdom.as_deref()),
+        taxon_id: None,
+        scientific_name: row.scientific_name,
+        common_name: None,
+        photo_url: None,
+        rank,
+        kingdom: row.kingdom,
+        phylum: None,
+        class: None,
+        order: None,
+        family: None,
+        genus: None,
+        species: None,
+        source: "local".to_string(),
+        conservation_status: None,
+    }
+}
+
 #[derive(Deserialize)]
 pub struct ValidateParams {
     name: Option<String>,
@@ -77,31 +185,148 @@ pub struct TaxonOccurrenceParams {
     cursor: Option<String>,
 }
 
+/// Why a `/api/taxa/{kingdom}/{name}` path didn't resolve.
+#[derive(Debug)]
+pub enum TaxonPathError {
+    UnknownKingdom(String),
+    /// GBIF has no exact match for the name within the kingdom. Suggestions
+    /// are close matches, possibly from other kingdoms.
+    NameNotFound {
+        name: String,
+        kingdom: &'static str,
+        suggestions: Vec<TaxonResult>,
+    },
+    App(AppError),
+}
+
+impl From<AppError> for TaxonPathError {
+    fn from(e: AppError) -> Self {
+        TaxonPathError::App(e)
+    }
+}
+
+impl From<TaxonomyClientError> for TaxonPathError {
+    fn from(e: TaxonomyClientError) -> Self {
+        TaxonPathError::App(e.into())
+    }
+}
+
+impl IntoResponse for TaxonPathError {
+    fn into_response(self) -> Response {
+        match self {
+            TaxonPathError::UnknownKingdom(kingdom) => (
+                StatusCode::NOT_FOUND,
+                Json(json!({
+                    "error": format!("Unknown kingdom \"{kingdom}\""),
+                    "kingdoms": KINGDOMS.iter().map(|k| k.name).collect::<Vec<_>>(),
+                })),
+            )
+                .into_response(),
+            TaxonPathError::NameNotFound {
+                name,
+                kingdom,
+                suggestions,
+            } => (
+                StatusCode::NOT_FOUND,
+                Json(json!({
+                    "error": format!("No taxon named \"{name}\" in {kingdom}"),
+                    "suggestions": suggestions,
+                })),
+            )
+                .into_response(),
+            TaxonPathError::App(e) => e.into_response(),
+        }
+    }
+}
+
+/// Check that `name` is a real taxon in `kingdom`, returning the canonical
+/// kingdom and GBIF's spelling of the name.
+///
+/// GBIF's match endpoint treats the kingdom as a hint and happily returns a
+/// fuzzy or cross-kingdom match, so only an exact match inside the requested
+/// kingdom passes. Anything else is a [`TaxonPathError::NameNotFound`]
+/// carrying the match and full-text search hits as suggestions. A failed
+/// lookup is a 503 rather than a 404, so an upstream outage isn't reported
+/// as the taxon not existing.
+async fn resolve_taxon_path(
+    taxonomy: &dyn TaxonomyProvider,
+    kingdom: &str,
+    name: &str,
+) -> Result<(&'static str, String), TaxonPathError> {
+    let kingdom = Kingdom::lookup(kingdom)
+        .map(|k| k.name)
+        .ok_or_else(|| TaxonPathError::UnknownKingdom(kingdom.to_string()))?;
+
+    let validated = taxonomy
+        .validate(name, Some(kingdom))
+        .await
+        .ok_or_else(|| {
+            AppError::ServiceUnavailable("Taxonomy lookup failed; try again shortly".into())
+        })?;
+    if let ValidateResponse {
+        valid: true,
+        taxon: Some(taxon),
+        ..
+    } = &validated
+    {
+        if taxon.kingdom.as_deref() == Some(kingdom) {
+            return Ok((kingdom, taxon.scientific_name.clone()));
+        }
+    }
+
+    let matched = validated
+        .taxon
+        .into_iter()
+        .chain(validated.suggestions.into_iter().flatten());
+    let searched = taxonomy
+        .search(name, Some(constants::TAXON_SUGGEST_LIMIT))
+        .await
+        .unwrap_or_default();
+    let mut seen = HashSet::new();
+    let suggestions = matched
+        .chain(searched)
+        // A failed match falls back to the kingdom itself; that's no help.
+        .filter(|t| t.rank != "kingdom" && seen.insert(t.id.clone()))
+        .take(constants::TAXON_SUGGEST_LIMIT as usize)
+        .collect();
+
+    Err(TaxonPathError::NameNotFound {
+        name: name.to_string(),
+        kingdom,
+        suggestions,
+    })
+}
+
+/// Taxon detail for a `{kingdom}/{name}` path. Returns 404 with the valid
+/// kingdoms, or with suggested taxa, when the path doesn't name a taxon.
 pub async fn get_taxon_by_kingdom_name(
     State(state): State<AppState>,
     Path((kingdom, name)): Path<(String, String)>,
-) -> Result<Json<TaxonDetailWithCount>, AppError> {
+) -> Result<Json<TaxonDetailWithCount>, TaxonPathError> {
     // Frontend uses dashes in URLs (e.g., "Morus-alba"), convert to spaces
     let name = name.replace('-', " ");
+    let (kingdom, name) = resolve_taxon_path(&*state.taxonomy, &kingdom, &name).await?;
 
     let detail = state
         .taxonomy
-        .get_by_name(&name, Some(&kingdom))
+        .get_by_name(&name, Some(kingdom))
         .await?
         .ok_or_else(|| AppError::NotFound("Taxon not found".into()))?;
+    record_conservation_status(&state, &detail);
 
-    let count = observing_db::feeds::count_occurrences_by_taxon(
-        &state.pool,
+    let local_stats = observing_db::feeds::taxon_local_stats(
+        &state.read_pool,
         &name,
         &detail.rank,
-        Some(&kingdom),
+        Some(kingdom),
     )
     .await
-    .unwrap_or(0);
+    .unwrap_or_default();
 
     Ok(Json(TaxonDetailWithCount {
         detail,
-        observation_count: count,
+        observation_count: local_stats.occurrence_count,
+        local_stats,
     }))
 }
 
@@ -125,10 +350,7 @@ pub async fn get_taxon_occurrences_by_kingdom_name(
     Path((kingdom, name)): Path<(String, String)>,
     Query(params): Query<TaxonOccurrenceParams>,
 ) -> Result<Json<OccurrenceListResponse>, AppError> {
-    let limit = params
-        .limit
-        .unwrap_or(constants::DEFAULT_FEED_LIMIT)
-        .min(constants::MAX_FEED_LIMIT);
+    let limit = state.page_limits.feed.resolve(params.limit);
     let name = name.replace('-', " ");
 
     // Look up taxon to get rank
@@ -144,12 +366,16 @@ pub async fn get_taxon_occurrences_by_kingdom_name(
 
     let options = TaxonOccurrenceOptions {
         limit: Some(limit),
-        cursor: params.cursor,
+        cursor: params
+            .cursor
+            .as_deref()
+            .map(FeedCursor::decode)
+            .transpose()?,
         kingdom: Some(kingdom),
     };
 
     let rows = observing_db::feeds::get_occurrences_by_taxon(
-        &state.pool,
+        &state.read_pool,
         &name,
         &rank,
         &options,
@@ -159,15 +385,16 @@ pub async fn get_taxon_occurrences_by_kingdom_name(
 
     let viewer = session_did(&cookies);
     let occurrences = enrichment::enrich_occurrences(
-        &state.pool,
-        &state.resolver,
-        &state.taxonomy,
+        &state.read_pool,
+        &*state.resolver,
+        &*state.taxonomy,
+        state.blob_links(),
         &rows,
         viewer.as_deref(),
     )
     .await;
 
-    let next_cursor = occurrences.last().map(|o| o.feed_cursor());
+    let next_cursor = occurrences.last().and_then(|o| o.feed_cursor());
 
     Ok(Json(OccurrenceListResponse {
         occurrences,
@@ -175,6 +402,45 @@ pub async fn get_taxon_occurrences_by_kingdom_name(
     }))
 }
 
+/// Refresh a looked-up taxon's conservation status for the explore feed's
+/// filter. `resolve_taxa` records it for every identified taxon; this keeps
+/// a viewed taxon current between passes. Only a status this process hasn't
+/// already written goes to the database. Runs in the background, so a
+/// failed write just leaves the last recorded status.
+fn record_conservation_status(state: &AppState, detail: &TaxonDetail) {
+    let Some(status) = detail.conservation_status.clone() else {
+        return;
+    };
+    let Ok(taxon_key) = detail
+        .id
+        .strip_prefix("gbif:")
+        .unwrap_or(&detail.id)
+        .parse::<i64>()
+    else {
+        return;
+    };
+    let pool = state.pool.clone();
+    let recorded = state.recorded_conservation.clone();
+    tokio::spawn(async move {
+        if recorded.get(&taxon_key).await.as_ref() == Some(&status) {
+            return;
+        }
+        match observing_db::taxa::upsert_conservation_status(
+            &pool,
+            taxon_key,
+            &status.category,
+            &status.source,
+        )
+        .await
+        {
+            Ok(_) => recorded.insert(taxon_key, status).await,
+            Err(e) => {
+                tracing::warn!(taxon_key, error = %e, "Failed to record conservation status")
+            }
+        }
+    });
+}
+
 /// Resolve `/api/taxa/{id}`-style input to a TaxonDetail. Accepts either a
 /// GBIF id (`gbif:NNN` / bare numeric) or a scientific name (e.g. a kingdom
 /// name like `Animalia`).
@@ -198,19 +464,21 @@ pub async fn get_taxon_by_id(
     let detail = resolve_taxon_by_id_or_name(&state, &id)
         .await?
         .ok_or_else(|| AppError::NotFound("Taxon not found".into()))?;
+    record_conservation_status(&state, &detail);
 
-    let count = observing_db::feeds::count_occurrences_by_taxon(
-        &state.pool,
+    let local_stats = observing_db::feeds::taxon_local_stats(
+        &state.read_pool,
         &detail.scientific_name,
         &detail.rank,
         detail.kingdom.as_deref(),
     )
     .await
-    .unwrap_or(0);
+    .unwrap_or_default();
 
     Ok(Json(TaxonDetailWithCount {
         detail,
-        observation_count: count,
+        observation_count: local_stats.occurrence_count,
+        local_stats,
     }))
 }
 
@@ -220,10 +488,7 @@ pub async fn get_taxon_occurrences_by_id(
     Path(id): Path<String>,
     Query(params): Query<TaxonOccurrenceParams>,
 ) -> Result<Json<OccurrenceListResponse>, AppError> {
-    let limit = params
-        .limit
-        .unwrap_or(constants::DEFAULT_FEED_LIMIT)
-        .min(constants::MAX_FEED_LIMIT);
+    let limit = state.page_limits.feed.resolve(params.limit);
 
     // Look up taxon to get name + rank
     let detail = resolve_taxon_by_id_or_name(&state, &id)
@@ -237,12 +502,16 @@ pub async fn get_taxon_occurrences_by_id(
 
     let options = TaxonOccurrenceOptions {
         limit: Some(limit),
-        cursor: params.cursor,
+        cursor: params
+            .cursor
+            .as_deref()
+            .map(FeedCursor::decode)
+            .transpose()?,
         kingdom,
     };
 
     let rows = observing_db::feeds::get_occurrences_by_taxon(
-        &state.pool,
+        &state.read_pool,
         &name,
         &rank,
         &options,
@@ -252,18 +521,465 @@ pub async fn get_taxon_occurrences_by_id(
 
     let viewer = session_did(&cookies);
     let occurrences = enrichment::enrich_occurrences(
-        &state.pool,
-        &state.resolver,
-        &state.taxonomy,
+        &state.read_pool,
+        &*state.resolver,
+        &*state.taxonomy,
+        state.blob_links(),
         &rows,
         viewer.as_deref(),
     )
     .await;
 
-    let next_cursor = occurrences.last().map(|o| o.feed_cursor());
+    let next_cursor = occurrences.last().and_then(|o| o.feed_cursor());
 
     Ok(Json(OccurrenceListResponse {
         occurrences,
         cursor: next_cursor,
     }))
 }
+
+/// Images of a taxon: photos from its most-liked local occurrences first,
+/// then GBIF media to fill the rest.
+pub async fn get_taxon_images(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<TaxonImagesResponse>, AppError> {
+    let detail = resolve_taxon_by_id_or_name(&state, &id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Taxon not found".into()))?;
+
+    let limit = constants::TAXON_IMAGES_LIMIT;
+    let local = observing_db::feeds::top_taxon_images(
+        &state.read_pool,
+        &detail.scientific_name,
+        &detail.rank,
+        detail.kingdom.as_deref(),
+        limit as i64,
+        &state.hidden_dids,
+    )
+    .await?;
+
+    // No viewer: the gallery is the same for everyone, so private photos
+    // stay out of it.
+    let blobs: Vec<(String, String)> = local
+        .iter()
+        .flat_map(|row| {
+            row.blob_entries()
+                .into_iter()
+                .map(|blob| (row.did.clone(), blob.image.ref_.cid().to_string()))
+        })
+        .collect();
+    let links = state.blob_links();
+    let access = MediaAccess::load(&state.read_pool, links.signer, None, &blobs).await;
+
+    Ok(Json(TaxonImagesResponse {
+        images: rank_taxon_images(
+            local,
+            detail.media.as_deref().unwrap_or_default(),
+            limit,
+            links.strategy,
+            &access,
+        ),
+    }))
+}
+
+/// Interactions recorded with the taxon on either side, grouped by type and
+/// partner taxon, from the taxon's perspective. Below kingdom rank, only
+/// subjects recorded in the taxon's kingdom (or with none) count.
+pub async fn get_taxon_interactions(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<TaxonInteractionsResponse>, AppError> {
+    let detail = resolve_taxon_by_id_or_name(&state, &id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Taxon not found".into()))?;
+
+    let kingdom = detail
+        .kingdom
+        .as_deref()
+        .filter(|_| !detail.rank.eq_ignore_ascii_case("kingdom"));
+    let interactions = observing_db::interactions::for_taxon(
+        &state.read_pool,
+        &detail.scientific_name,
+        kingdom,
+        constants::TAXON_INTERACTIONS_LIMIT,
+        &state.hidden_dids,
+    )
+    .await?;
+
+    Ok(Json(TaxonInteractionsResponse { interactions }))
+}
+
+/// Local occurrence photos in the order the query ranked them, linked per
+/// `strategy` and `access`, followed by GBIF still images, deduped by URL.
+fn rank_taxon_images(
+    local: Vec<TaxonImageRow>,
+    gbif: &[TaxonMedia],
+    limit: usize,
+    strategy: BlobUrlStrategy,
+    access: &MediaAccess,
+) -> Vec<TaxonImage> {
+    let mut seen = HashSet::new();
+    let mut images = Vec::with_capacity(limit);
+
+    for row in local {
+        for blob in row.blob_entries() {
+            let Some(url) = access.link(strategy, &row.did, blob.image.ref_.cid(), None) else {
+                continue;
+            };
+            images.push(TaxonImage {
+                url,
+                source: "local",
+                occurrence_uri: Some(row.uri.clone()),
+                like_count: Some(row.like_count),
+                license: blob.license,
+                creator: None,
+            });
+        }
+    }
+    for media in gbif.iter().filter(|m| m.r#type == "StillImage") {
+        images.push(TaxonImage {
+            url: media.url.clone(),
+            source: "gbif",
+            occurrence_uri: None,
+            like_count: None,
+            license: media.license.clone(),
+            creator: media.creator.clone(),
+        });
+    }
+
+    images.retain(|image| seen.insert(image.url.clone()));
+    images.truncate(limit);
+    images
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(name: &str, count: i64) -> LocalTaxonMatchRow {
+        LocalTaxonMatchRow {
+            scientific_name: name.to_string(),
+            kingdom: Some("Plantae".to_string()),
+            taxon_rank: Some("species".to_string()),
+            observation_count: count,
+        }
+    }
+
+    fn gbif(name: &str) -> TaxonResult {
+        TaxonResult {
+            taxon_id: Some(format!("https://www.gbif.org/species/{}", name.len())),
+            common_name: Some(format!("{name} (common)")),
+            source: "gbif".to_string(),
+            ..local_taxon_result(local(name, 0))
+        }
+    }
+
+    fn names(results: &[TaxonResult]) -> Vec<&str> {
+        results.iter().map(|r| r.scientific_name.as_str()).collect()
+    }
+
+    #[test]
+    fn locally_popular_taxon_outranks_gbif_only_match() {
+        let results = merge_suggestions(
+            vec![local("Quercus lobata", 42)],
+            vec![gbif("Quercus robur"), gbif("Quercus alba")],
+            10,
+        );
+        assert_eq!(
+            names(&results),
+            ["Quercus lobata", "Quercus robur", "Quercus alba"]
+        );
+        assert_eq!(results[0].source, "local");
+        assert_eq!(results[0].id, "Plantae/Quercus lobata");
+    }
+
+    #[test]
+    fn local_match_takes_gbif_details_without_duplicating() {
+        let results = merge_suggestions(
+            vec![local("Quercus lobata", 42), local("quercus robur", 3)],
+            vec![
+                gbif("Quercus alba"),
+                gbif("Quercus robur"),
+                gbif("Quercus lobata"),
+            ],
+            10,
+        );
+        assert_eq!(
+            names(&results),
+            ["Quercus lobata", "Quercus robur", "Quercus alba"]
+        );
+        assert_eq!(results[0].source, "gbif");
+        assert!(results[0].common_name.is_some());
+    }
+
+    #[test]
+    fn merged_suggestions_respect_limit() {
+        let results = merge_suggestions(
+            vec![local("Quercus lobata", 42), local("Quercus agrifolia", 7)],
+            vec![gbif("Quercus robur")],
+            2,
+        );
+        assert_eq!(names(&results), ["Quercus lobata", "Quercus agrifolia"]);
+    }
+
+    fn image_row(uri: &str, cids: &[&str], like_count: i64) -> TaxonImageRow {
+        let blobs: Vec<_> = cids
+            .iter()
+            .map(|cid| {
+                serde_json::json!({
+                    "image": {
+                        "$type": "blob",
+                        "ref": { "$link": cid },
+                        "mimeType": "image/jpeg",
+                        "size": 1000
+                    },
+                    "license": "CC-BY-4.0"
+                })
+            })
+            .collect();
+        TaxonImageRow {
+            uri: uri.to_string(),
+            did: "did:plc:test".to_string(),
+            associated_media: Some(serde_json::Value::Array(blobs)),
+            like_count,
+        }
+    }
+
+    fn gbif_media(url: &str, kind: &str) -> TaxonMedia {
+        TaxonMedia {
+            r#type: kind.to_string(),
+            url: url.to_string(),
+            title: None,
+            description: None,
+            source: None,
+            creator: Some("GBIF contributor".to_string()),
+            license: None,
+        }
+    }
+
+    #[test]
+    fn local_images_rank_above_gbif_media() {
+        let images = rank_taxon_images(
+            vec![
+                image_row("at://did:plc:test/occ/1", &["bafkreia", "bafkreib"], 5),
+                image_row("at://did:plc:test/occ/2", &["bafkreic"], 1),
+            ],
+            &[
+                gbif_media("https://example.org/1.jpg", "StillImage"),
+                gbif_media("https://example.org/call.mp3", "Sound"),
+            ],
+            10,
+            BlobUrlStrategy::Proxy,
+            &MediaAccess::default(),
+        );
+        let urls: Vec<&str> = images.iter().map(|i| i.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            [
+                "/media/blob/did:plc:test/bafkreia",
+                "/media/blob/did:plc:test/bafkreib",
+                "/media/blob/did:plc:test/bafkreic",
+                "https://example.org/1.jpg",
+            ]
+        );
+        assert_eq!(images[0].source, "local");
+        assert_eq!(images[0].like_count, Some(5));
+        assert_eq!(images[0].license.as_deref(), Some("CC-BY-4.0"));
+        assert_eq!(images[3].source, "gbif");
+        assert_eq!(images[3].creator.as_deref(), Some("GBIF contributor"));
+    }
+
+    #[test]
+    fn taxon_images_respect_limit() {
+        let images = rank_taxon_images(
+            vec![image_row("at://did:plc:test/occ/1", &["bafkreia"], 0)],
+            &[
+                gbif_media("https://example.org/1.jpg", "StillImage"),
+                gbif_media("https://example.org/1.jpg", "StillImage"),
+                gbif_media("https://example.org/2.jpg", "StillImage"),
+            ],
+            2,
+            BlobUrlStrategy::Proxy,
+            &MediaAccess::default(),
+        );
+        let sources: Vec<&str> = images.iter().map(|i| i.source).collect();
+        assert_eq!(sources, ["local", "gbif"]);
+    }
+
+    #[test]
+    fn taxon_images_follow_the_blob_url_strategy() {
+        let images = rank_taxon_images(
+            vec![image_row("at://did:plc:test/occ/1", &["bafkreia"], 0)],
+            &[],
+            10,
+            BlobUrlStrategy::Cdn,
+            &MediaAccess::default(),
+        );
+        assert_eq!(
+            images[0].url,
+            "https://cdn.bsky.app/img/feed_fullsize/plain/did:plc:test/bafkreia@jpeg"
+        );
+    }
+
+    // ---------- {kingdom}/{name} path resolution ----------
+
+    use crate::taxonomy::breaker::CircuitBreaker;
+    use crate::taxonomy::GbifClient;
+    use crate::taxonomy_client::TaxonomyClient;
+    use axum::body::to_bytes;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// A v2 match response for a Plantae species.
+    fn match_body(name: &str, match_type: &str) -> serde_json::Value {
+        json!({
+            "synonym": false,
+            "usage": {
+                "key": "2878688",
+                "name": name,
+                "canonicalName": name,
+                "rank": "SPECIES"
+            },
+            "classification": [
+                { "key": "6", "name": "Plantae", "rank": "KINGDOM" },
+                { "key": "2877951", "name": "Quercus", "rank": "GENUS" }
+            ],
+            "diagnostics": {
+                "matchType": match_type,
+                "issues": [],
+                "processingFlags": [],
+                "alternatives": [],
+                "timings": {}
+            }
+        })
+    }
+
+    /// Taxonomy client against a mock GBIF whose match endpoint answers
+    /// `scientificName` with `body`. Search is left unmocked (404), which
+    /// the client treats as no results.
+    async fn taxonomy_with_match(
+        scientific_name: &str,
+        body: serde_json::Value,
+    ) -> (MockServer, TaxonomyClient) {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/species/match"))
+            .and(query_param("scientificName", scientific_name))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .mount(&server)
+            .await;
+        let client = TaxonomyClient::with_parts(
+            GbifClient::with_base_url(&server.uri()),
+            CircuitBreaker::default(),
+        );
+        (server, client)
+    }
+
+    async fn response_json(err: TaxonPathError) -> (StatusCode, serde_json::Value) {
+        let response = err.into_response();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn valid_kingdom_name_pair_resolves() {
+        let (_server, taxonomy) =
+            taxonomy_with_match("Quercus alba", match_body("Quercus alba", "EXACT")).await;
+
+        let (kingdom, name) = resolve_taxon_path(&taxonomy, "plantae", "Quercus alba")
+            .await
+            .expect("an exact match in the kingdom resolves");
+        assert_eq!(kingdom, "Plantae");
+        assert_eq!(name, "Quercus alba");
+    }
+
+    #[tokio::test]
+    async fn unknown_kingdom_is_not_found_without_a_lookup() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(0)
+            .mount(&server)
+            .await;
+        let taxonomy = TaxonomyClient::with_parts(
+            GbifClient::with_base_url(&server.uri()),
+            CircuitBreaker::default(),
+        );
+
+        let err = resolve_taxon_path(&taxonomy, "Plante", "Quercus alba")
+            .await
+            .unwrap_err();
+        let (status, body) = response_json(err).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"], "Unknown kingdom \"Plante\"");
+        assert!(body["kingdoms"]
+            .as_array()
+            .unwrap()
+            .contains(&json!("Plantae")));
+    }
+
+    #[tokio::test]
+    async fn name_in_another_kingdom_suggests_the_real_one() {
+        let (_server, taxonomy) =
+            taxonomy_with_match("Quercus alba", match_body("Quercus alba", "EXACT")).await;
+
+        let err = resolve_taxon_path(&taxonomy, "Animalia", "Quercus alba")
+            .await
+            .unwrap_err();
+        let (status, body) = response_json(err).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"], "No taxon named \"Quercus alba\" in Animalia");
+        assert_eq!(body["suggestions"][0]["id"], "Plantae/Quercus alba");
+    }
+
+    #[tokio::test]
+    async fn misspelled_name_returns_suggestions() {
+        let (_server, taxonomy) =
+            taxonomy_with_match("Quercus albba", match_body("Quercus alba", "FUZZY")).await;
+
+        let err = resolve_taxon_path(&taxonomy, "Plantae", "Quercus albba")
+            .await
+            .unwrap_err();
+        let (status, body) = response_json(err).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"], "No taxon named \"Quercus albba\" in Plantae");
+        let suggestions = body["suggestions"].as_array().unwrap();
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0]["scientificName"], "Quercus alba");
+    }
+
+    #[tokio::test]
+    async fn upstream_failure_is_unavailable_not_not_found() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/species/match"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+        let taxonomy = TaxonomyClient::with_parts(
+            GbifClient::with_base_url(&server.uri()),
+            CircuitBreaker::default(),
+        );
+
+        let err = resolve_taxon_path(&taxonomy, "Plantae", "Quercus alba")
+            .await
+            .unwrap_err();
+        let (status, _) = response_json(err).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn interactions_of_an_unknown_taxon_are_not_found() {
+        use crate::taxonomy_client::FakeTaxonomy;
+        use std::sync::Arc;
+
+        let (state, _media_dir) = AppState::for_tests(Arc::new(FakeTaxonomy::new(vec![]))).await;
+        let err = get_taxon_interactions(State(state), Path("Nonexistus fictus".into()))
+            .await
+            .err()
+            .expect("no taxon to list interactions for");
+        assert!(matches!(err, AppError::NotFound(_)), "{err:?}");
+    }
+}
@@ -0,0 +1,160 @@
+//! `Server-Timing` breakdowns for read responses.
+//!
+//! The [`layer`] middleware gives each request a task-local list of timings;
+//! handlers, the enrichment stages and response serialization add to it with
+//! [`record`] / [`timed`], and the list goes out as a `Server-Timing` header
+//! so browser devtools show where a slow feed spent its time:
+//!
+//! ```text
+//! Server-Timing: db;dur=12.4, enrich.likes;dur=3.1, enrich;dur=41.0, serialize;dur=0.6
+//! ```
+//!
+//! Recording outside a request (or on a spawned task) is a no-op, and a
+//! response that recorded nothing gets no header.
+
+use std::cell::RefCell;
+use std::fmt::Write;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use axum::extract::Request;
+use axum::http::HeaderValue;
+use axum::middleware::Next;
+use axum::response::Response;
+
+tokio::task_local! {
+    static TIMINGS: RefCell<Vec<(String, Duration)>>;
+}
+
+/// Add `elapsed` to the `metric` timing of the current request. Repeated
+/// metrics (a handler that runs two queries) are summed.
+pub fn record(metric: impl Into<String>, elapsed: Duration) {
+    let metric = metric.into();
+    let _ = TIMINGS.try_with(|timings| {
+        let mut timings = timings.borrow_mut();
+        match timings.iter_mut().find(|(name, _)| *name == metric) {
+            Some((_, total)) => *total += elapsed,
+            None => timings.push((metric, elapsed)),
+        }
+    });
+}
+
+/// Await `work` and record its wall time under `metric`.
+pub async fn timed<T>(metric: &'static str, work: impl Future<Output = T>) -> T {
+    let started = Instant::now();
+    let out = work.await;
+    record(metric, started.elapsed());
+    out
+}
+
+/// Middleware: collect the request's timings and send them back as
+/// `Server-Timing`.
+pub async fn layer(req: Request, next: Next) -> Response {
+    TIMINGS
+        .scope(RefCell::default(), async {
+            let mut response = next.run(req).await;
+            let header = TIMINGS.with(|timings| header_value(&timings.borrow()));
+            if let Some(value) = header.and_then(|h| HeaderValue::from_str(&h).ok()) {
+                response.headers_mut().insert("server-timing", value);
+            }
+            response
+        })
+        .await
+}
+
+/// `name;dur=ms` entries in the order they were first recorded.
+fn header_value(timings: &[(String, Duration)]) -> Option<String> {
+    if timings.is_empty() {
+        return None;
+    }
+    let mut value = String::new();
+    for (i, (name, elapsed)) in timings.iter().enumerate() {
+        if i > 0 {
+            value.push_str(", ");
+        }
+        let _ = write!(value, "{name};dur={:.1}", elapsed.as_secs_f64() * 1000.0);
+    }
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use axum::response::Response;
+    use axum::routing::get;
+    use axum::Router;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::error::AppError;
+    use crate::response_format::ResponseFormat;
+
+    /// A feed route shaped like the real ones: a query, enrichment, then
+    /// serialization through [`ResponseFormat`].
+    fn app() -> Router {
+        async fn feed(format: ResponseFormat) -> Result<Response, AppError> {
+            let rows = timed("db", async { vec!["at://did:plc:abc/occurrence/1"] }).await;
+            record("enrich.likes", Duration::from_micros(1_500));
+            record("enrich", Duration::from_millis(4));
+            format.respond(&serde_json::json!({ "occurrences": rows }))
+        }
+        async fn health() -> &'static str {
+            "ok"
+        }
+        Router::new()
+            .route("/api/feeds/explore", get(feed))
+            .route("/health", get(health))
+            .layer(axum::middleware::from_fn(layer))
+    }
+
+    async fn get_path(path: &str) -> Response {
+        app()
+            .oneshot(Request::get(path).body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn feed_response_carries_a_well_formed_server_timing() {
+        let response = get_path("/api/feeds/explore").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let header = response.headers()["server-timing"].to_str().unwrap();
+        let metrics: Vec<(&str, f64)> = header
+            .split(", ")
+            .map(|entry| {
+                let (name, dur) = entry.split_once(";dur=").expect("name;dur=ms");
+                assert!(!name.is_empty() && !name.contains([' ', ',', ';']));
+                (name, dur.parse().expect("numeric duration"))
+            })
+            .collect();
+
+        let names: Vec<&str> = metrics.iter().map(|(name, _)| *name).collect();
+        assert_eq!(names, ["db", "enrich.likes", "enrich", "serialize"]);
+        assert_eq!(metrics[1].1, 1.5);
+        assert_eq!(metrics[2].1, 4.0);
+    }
+
+    #[tokio::test]
+    async fn untimed_routes_get_no_header() {
+        let response = get_path("/health").await;
+        assert!(response.headers().get("server-timing").is_none());
+    }
+
+    #[test]
+    fn repeated_metrics_are_summed() {
+        let timings = RefCell::default();
+        TIMINGS.sync_scope(timings, || {
+            record("db", Duration::from_millis(2));
+            record("db", Duration::from_millis(3));
+            let header = TIMINGS.with(|t| header_value(&t.borrow()));
+            assert_eq!(header.as_deref(), Some("db;dur=5.0"));
+        });
+    }
+
+    #[test]
+    fn recording_outside_a_request_is_a_noop() {
+        record("db", Duration::from_millis(1));
+    }
+}
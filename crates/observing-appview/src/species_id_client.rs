@@ -11,10 +11,9 @@ pub struct SpeciesIdClient {
 
 impl SpeciesIdClient {
     pub fn new(base_url: &str) -> Self {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(30))
-            .build()
-            .expect("Failed to create HTTP client");
+        let client = http_client::HttpClientConfig::default()
+            .with_timeout(Duration::from_secs(30))
+            .build();
 
         Self {
             client,
@@ -1,12 +1,17 @@
-use atproto_identity::IdentityResolver;
 use sqlx::postgres::PgPool;
 use std::sync::Arc;
 
+use crate::config::{BlobUrlStrategy, CoordinateChecks, ImageLimits, PageLimits};
+use crate::enrichment::BlobLinks;
+use crate::feed_cache::FeedCache;
+use crate::identity::IdentityProvider;
+use crate::live::LiveFeed;
 use crate::media::MediaCache;
 use crate::oauth_store::{PgSessionStore, PgStateStore};
 use crate::resolver::HickoryDnsTxtResolver;
+use crate::routes::occurrences::ActiveImports;
 use crate::species_id_client::SpeciesIdClient;
-use crate::taxonomy_client::TaxonomyClient;
+use crate::taxonomy_client::{ConservationStatus, TaxonomyProvider};
 
 use atrium_api::types::string::{Did, Handle};
 use atrium_common::resolver::Resolver;
@@ -91,9 +96,14 @@ pub type AgentType = atrium_api::agent::Agent<OAuthSessionType>;
 /// Shared application state passed to all route handlers
 #[derive(Clone)]
 pub struct AppState {
+    /// Primary pool: all writes, sessions, and reads that must see them.
     pub pool: PgPool,
-    pub resolver: Arc<IdentityResolver>,
-    pub taxonomy: Arc<TaxonomyClient>,
+    /// Pool for feed, detail and taxonomy reads. A replica when
+    /// `DATABASE_READ_URL` is set, otherwise the same pool as `pool`, so
+    /// results may lag a write by the replica's delay.
+    pub read_pool: PgPool,
+    pub resolver: Arc<dyn IdentityProvider>,
+    pub taxonomy: Arc<dyn TaxonomyProvider>,
     pub species_id: Option<Arc<SpeciesIdClient>>,
     /// Faster ViT-L service for the live camera loop. `None` falls back to
     /// `species_id` so single-service deployments (e.g. local dev) still work.
@@ -110,6 +120,95 @@ pub struct AppState {
     /// Base URL of the tap-ingester service, if configured. Enables the
     /// HTTP-backed `ingester/*` tables in the admin browser.
     pub ingester_url: Option<String>,
+    /// Broadcast of occurrence changes for `/api/ws/feed` subscribers.
+    pub live: LiveFeed,
+    /// Seconds-long cache of anonymous explore and needs-ID responses.
+    pub feed_cache: FeedCache,
+    /// Default and maximum `limit` per list endpoint (see [`PageLimits`]).
+    pub page_limits: PageLimits,
+    /// Caps on the images one occurrence create or update may attach.
+    pub image_limits: ImageLimits,
+    /// Which implausible coordinates occurrence writes reject.
+    pub coordinate_checks: CoordinateChecks,
+    /// Accounts with a bulk import running.
+    pub imports: ActiveImports,
+    /// Where occurrence image URLs point (see [`BlobUrlStrategy`]).
+    pub blob_urls: BlobUrlStrategy,
+    /// Conservation statuses this process last wrote, by taxon key, so
+    /// repeat detail views don't rewrite an unchanged status.
+    pub recorded_conservation: moka::future::Cache<i64, ConservationStatus>,
+}
+
+impl AppState {
+    /// How enriched responses link occurrence images.
+    pub fn blob_links(&self) -> BlobLinks<'_> {
+        BlobLinks {
+            strategy: self.blob_urls,
+            signer: self.media.signer.as_ref(),
+        }
+    }
+}
+
+#[cfg(test)]
+impl AppState {
+    /// State for handler tests, resolving taxa through `taxonomy`. Identity
+    /// lookups find no one, the pools point at a closed port and every other
+    /// client is at its defaults, so only handlers that stay off the
+    /// database and network will succeed.
+    ///
+    /// The media cache lives in the returned directory, private to the
+    /// test; keep it bound until the test ends.
+    pub(crate) async fn for_tests(
+        taxonomy: Arc<dyn TaxonomyProvider>,
+    ) -> (Self, tempfile::TempDir) {
+        let media_dir = tempfile::tempdir().expect("should create temp directory");
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .acquire_timeout(std::time::Duration::from_millis(100))
+            .connect_lazy("postgres://127.0.0.1:1/observing")
+            .expect("lazy pool never connects up front");
+        let media = MediaCache::new(crate::media::MediaProxyConfig {
+            cache_dir: media_dir.path().to_path_buf(),
+            max_cache_size: 1024 * 1024,
+            cache_ttl_secs: 60,
+            signing_secret: None,
+            cdn_fallback: None,
+            warm_secret: None,
+        })
+        .await;
+        let state = Self {
+            pool: pool.clone(),
+            read_pool: pool.clone(),
+            resolver: Arc::new(crate::identity::MockIdentityProvider::default()),
+            taxonomy,
+            species_id: None,
+            species_id_live: None,
+            oauth_client: Arc::new(create_oauth_client(pool.clone(), None, 3000)),
+            media,
+            public_url: None,
+            hidden_dids: Vec::new(),
+            admin_dids: Vec::new(),
+            ingester_url: None,
+            live: LiveFeed::spawn(pool),
+            feed_cache: FeedCache::new(),
+            page_limits: PageLimits::default(),
+            image_limits: ImageLimits::default(),
+            coordinate_checks: CoordinateChecks::default(),
+            imports: ActiveImports::default(),
+            blob_urls: BlobUrlStrategy::default(),
+            recorded_conservation: recorded_conservation_cache(),
+        };
+        (state, media_dir)
+    }
+}
+
+/// Taxa whose last recorded conservation status is remembered. An hour's
+/// TTL lets a status another process changed be restored without waiting
+/// on a restart.
+pub fn recorded_conservation_cache() -> moka::future::Cache<i64, ConservationStatus> {
+    moka::future::Cache::builder()
+        .max_capacity(10_000)
+        .time_to_live(std::time::Duration::from_secs(60 * 60))
+        .build()
 }
 
 /// Create an OAuthClient.
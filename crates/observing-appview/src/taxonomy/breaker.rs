@@ -0,0 +1,118 @@
+//! Circuit breaker for upstream taxonomy lookups.
+//!
+//! Feed enrichment resolves taxonomy per occurrence, so an unreachable GBIF
+//! would otherwise make every feed request wait out one timeout per row.
+//! After `failure_threshold` consecutive failures the breaker opens and
+//! lookups fail immediately for `cooldown`; callers fall back to whatever
+//! they have locally. Once the cooldown elapses calls go through again, and
+//! a single further failure re-opens it.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tracing::{info, warn};
+
+/// Consecutive failures before the breaker opens.
+pub const DEFAULT_FAILURE_THRESHOLD: u32 = 5;
+
+/// How long an open breaker refuses calls.
+pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(30);
+
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    cooldown: Duration,
+    state: Mutex<BreakerState>,
+}
+
+#[derive(Default)]
+struct BreakerState {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+            state: Mutex::new(BreakerState::default()),
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, BreakerState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Whether the breaker is refusing calls right now.
+    pub fn is_open(&self) -> bool {
+        self.state()
+            .open_until
+            .is_some_and(|until| Instant::now() < until)
+    }
+
+    pub fn record_success(&self) {
+        let mut state = self.state();
+        if state.consecutive_failures >= self.failure_threshold {
+            info!("Taxonomy upstream recovered, closing circuit breaker");
+        }
+        *state = BreakerState::default();
+    }
+
+    pub fn record_failure(&self) {
+        let mut state = self.state();
+        state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+        if state.consecutive_failures >= self.failure_threshold {
+            warn!(
+                failures = state.consecutive_failures,
+                cooldown_secs = self.cooldown.as_secs(),
+                "Taxonomy upstream failing, opening circuit breaker"
+            );
+            state.open_until = Some(Instant::now() + self.cooldown);
+        }
+    }
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(DEFAULT_FAILURE_THRESHOLD, DEFAULT_COOLDOWN)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opens_after_threshold_consecutive_failures() {
+        let breaker = CircuitBreaker::new(3, Duration::from_secs(60));
+        breaker.record_failure();
+        breaker.record_failure();
+        assert!(!breaker.is_open());
+        breaker.record_failure();
+        assert!(breaker.is_open());
+    }
+
+    #[test]
+    fn success_resets_the_failure_count() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+        breaker.record_failure();
+        breaker.record_success();
+        breaker.record_failure();
+        assert!(!breaker.is_open());
+    }
+
+    #[test]
+    fn reopens_on_first_failure_after_cooldown() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+        breaker.record_failure();
+        breaker.record_failure();
+        assert!(breaker.is_open());
+
+        // Let the cooldown elapse: calls are allowed again, but the count
+        // isn't reset until a success, so one more failure trips it.
+        breaker.state().open_until = Some(Instant::now());
+        assert!(!breaker.is_open());
+        breaker.record_failure();
+        assert!(breaker.is_open());
+    }
+}
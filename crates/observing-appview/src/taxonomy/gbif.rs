@@ -8,7 +8,7 @@
 use crate::taxonomy::wikidata::WikidataClient;
 use crate::taxonomy_client::{
     ConservationStatus, TaxonAncestor, TaxonDescription, TaxonDetail, TaxonMedia, TaxonReference,
-    TaxonResult, ValidateResponse,
+    TaxonResult, TaxonSitelink, ValidateResponse,
 };
 use gbif::checklistbank::{
     types::{
@@ -19,14 +19,30 @@ use gbif::checklistbank::{
 };
 use gbif::Uuid;
 use moka::future::Cache;
+use moka::notification::RemovalCause;
+use moka::policy::EvictionPolicy;
+use std::cell::Cell;
+use std::future::Future;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 use tracing::warn;
+use wikidata_client::EntitySummary;
 
 /// Base URL for the GBIF web services. The OpenAPI spec paths already include
 /// the `/v1/` and `/v2/` prefixes, so we point the client at the host root.
 const GBIF_BASE_URL: &str = "https://api.gbif.org";
 
+/// Entries kept in the GBIF cache before the least recently used are evicted.
+pub const DEFAULT_CACHE_CAPACITY: u64 = 10_000;
+
+/// How long a cached GBIF response is served before it's refetched.
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// Deadline for [`GbifClient::ping`]; a health probe shouldn't hang on a
+/// slow upstream.
+const PING_TIMEOUT: Duration = Duration::from_secs(3);
+
 /// GBIF Backbone Taxonomy dataset key. Used as a `datasetKey` filter to
 /// restrict species/search results to authoritative backbone entries.
 static BACKBONE_DATASET_KEY: std::sync::LazyLock<Uuid> = std::sync::LazyLock::new(|| {
@@ -69,6 +85,9 @@ macro_rules! iucn_categories {
         }
 
         impl IucnCategory {
+            /// Every category, in declaration order.
+            pub const ALL: &'static [Self] = &[$( Self::$variant, )+];
+
             /// This category's IUCN Red List code (e.g. `"EX"`).
             pub fn as_code(self) -> &'static str {
                 match self {
@@ -116,6 +135,48 @@ iucn_categories! {
     Ne => "NE",
 }
 
+impl IucnCategory {
+    /// Position on the Red List extinction-risk scale, from Least Concern (0)
+    /// up to Extinct (6). `None` for Data Deficient and Not Evaluated, which
+    /// say nothing about risk.
+    pub fn severity(self) -> Option<u8> {
+        match self {
+            Self::Lc => Some(0),
+            Self::Nt => Some(1),
+            Self::Vu => Some(2),
+            Self::En => Some(3),
+            Self::Cr => Some(4),
+            Self::Ew => Some(5),
+            Self::Ex => Some(6),
+            Self::Dd | Self::Ne => None,
+        }
+    }
+
+    /// Vulnerable, Endangered, or Critically Endangered — the Red List's
+    /// "threatened" categories.
+    pub fn is_threatened(self) -> bool {
+        matches!(self, Self::Vu | Self::En | Self::Cr)
+    }
+
+    /// Whether this category is at or above `min` on the risk scale, e.g.
+    /// `category.is_at_least(IucnCategory::Vu)`. Always false for DD/NE.
+    pub fn is_at_least(self, min: Self) -> bool {
+        self >= min
+    }
+}
+
+/// Orders by extinction risk (LC < NT < VU < EN < CR < EW < EX). DD and NE
+/// are only comparable to themselves, so range checks exclude them.
+impl PartialOrd for IucnCategory {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        match (self.severity(), other.severity()) {
+            (Some(a), Some(b)) => Some(a.cmp(&b)),
+            _ if self == other => Some(std::cmp::Ordering::Equal),
+            _ => None,
+        }
+    }
+}
+
 /// Walk a v2 match's additional_status entries, find one tagged with the
 /// IUCN dataset, and parse its status_code into [`IucnCategory`].
 fn extract_iucn_status(m: &NameUsageMatch) -> Option<IucnCategory> {
@@ -146,7 +207,7 @@ fn gbif_taxon_uri(key: u64) -> String {
 
 /// Build a path-based taxon identifier: "{kingdom}/{name}", or just
 /// "{name}" for kingdom-rank taxa.
-fn build_taxon_path(scientific_name: &str, rank: &str, kingdom: Option<&str>) -> String {
+pub(crate) fn build_taxon_path(scientific_name: &str, rank: &str, kingdom: Option<&str>) -> String {
     if rank.eq_ignore_ascii_case("kingdom") {
         return scientific_name.to_string();
     }
@@ -156,55 +217,130 @@ fn build_taxon_path(scientific_name: &str, rank: &str, kingdom: Option<&str>) ->
     }
 }
 
+tokio::task_local! {
+    /// Set when a lookup inside [`GbifClient::tracking_upstream`] misses the
+    /// cache and goes to GBIF.
+    static REACHED_UPSTREAM: Cell<bool>;
+}
+
 /// Taxonomy client that wraps the GBIF API with caching and app-specific
 /// type conversion.
 pub struct GbifClient {
     api: GbifChecklistbankClient,
+    base_url: String,
+    /// Short-deadline client for [`ping`](Self::ping).
+    probe: reqwest::Client,
     wikidata: WikidataClient,
     cache: Cache<String, CachedValue>,
     hits: AtomicU64,
     misses: AtomicU64,
+    /// Entries dropped to stay within capacity (not TTL expiries). Shared
+    /// with the cache's eviction listener.
+    evictions: Arc<AtomicU64>,
 }
 
 #[derive(Clone)]
 enum CachedValue {
     SearchResults(Vec<TaxonResult>),
-    TaxonDetail(Box<TaxonDetail>),
+    /// `None` records a GBIF 404 so unknown keys aren't refetched.
+    TaxonDetail(Option<Box<TaxonDetail>>),
     Children(Vec<TaxonResult>),
     Match(Option<Box<NameUsageMatch>>),
 }
 
 impl GbifClient {
-    const CACHE_TTL_MINS: u64 = 30;
-
     pub fn new() -> Self {
-        Self::with_base_url(GBIF_BASE_URL)
+        Self::with_cache(DEFAULT_CACHE_CAPACITY, DEFAULT_CACHE_TTL)
+    }
+
+    /// Construct a client against GBIF with a cache holding up to
+    /// `capacity` entries, each served for at most `ttl`.
+    pub fn with_cache(capacity: u64, ttl: Duration) -> Self {
+        Self::build(GBIF_BASE_URL, capacity, ttl)
     }
 
     /// Construct a client pointed at an arbitrary base URL. Production uses
     /// the GBIF host; tests point it at a wiremock server.
     pub fn with_base_url(base_url: &str) -> Self {
+        Self::build(base_url, DEFAULT_CACHE_CAPACITY, DEFAULT_CACHE_TTL)
+    }
+
+    fn build(base_url: &str, capacity: u64, ttl: Duration) -> Self {
+        let evictions = Arc::new(AtomicU64::new(0));
+        let counter = evictions.clone();
+        // LRU rather than moka's default TinyLFU: a crawl touching more than
+        // `capacity` taxa once each should push out its own one-off entries,
+        // not the taxa every page keeps asking for.
+        let cache = Cache::builder()
+            .max_capacity(capacity)
+            .time_to_live(ttl)
+            .eviction_policy(EvictionPolicy::lru())
+            .eviction_listener(move |_key, _value, cause| {
+                if cause == RemovalCause::Size {
+                    counter.fetch_add(1, Ordering::Relaxed);
+                }
+            })
+            .build();
         Self {
             api: GbifChecklistbankClient::new(base_url),
-            wikidata: WikidataClient::new(),
-            cache: Cache::builder()
-                .max_capacity(10_000)
-                .time_to_live(Duration::from_secs(Self::CACHE_TTL_MINS * 60))
+            base_url: base_url.trim_end_matches('/').to_string(),
+            probe: http_client::HttpClientConfig::default()
+                .with_timeout(PING_TIMEOUT)
                 .build(),
+            wikidata: WikidataClient::new(),
+            cache,
             hits: AtomicU64::new(0),
             misses: AtomicU64::new(0),
+            evictions,
+        }
+    }
+
+    /// Check that GBIF answers, without touching the cache.
+    ///
+    /// Fetches the Animalia usage (`/v1/species/1`), a small record that
+    /// always exists, and ignores the body.
+    pub async fn ping(&self) -> Result<(), String> {
+        let url = format!("{}/v1/species/1", self.base_url);
+        let response = self
+            .probe
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| format!("GBIF unreachable: {e}"))?;
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(format!("GBIF returned {}", response.status()))
         }
     }
 
-    #[allow(dead_code)] // exposed for future health/diagnostics endpoint
     pub fn cache_stats(&self) -> CacheStats {
         CacheStats {
             entries: self.cache.entry_count(),
             hits: self.hits.load(Ordering::Relaxed),
             misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
         }
     }
 
+    /// Count a cache miss; the caller goes to GBIF next.
+    fn record_miss(&self) {
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let _ = REACHED_UPSTREAM.try_with(|reached| reached.set(true));
+    }
+
+    /// Run `lookup`, also reporting whether any part of it missed the cache
+    /// and called GBIF. A lookup served entirely from the cache says nothing
+    /// about upstream health.
+    pub async fn tracking_upstream<T>(lookup: impl Future<Output = T>) -> (T, bool) {
+        REACHED_UPSTREAM
+            .scope(Cell::new(false), async {
+                let value = lookup.await;
+                (value, REACHED_UPSTREAM.with(Cell::get))
+            })
+            .await
+    }
+
     // ---------- low-level wrappers around the generated client ----------
 
     /// v2 `/species/match` with just the scientific name + optional kingdom
@@ -230,7 +366,7 @@ impl GbifClient {
             self.hits.fetch_add(1, Ordering::Relaxed);
             return Ok(cached.map(|b| *b));
         }
-        self.misses.fetch_add(1, Ordering::Relaxed);
+        self.record_miss();
 
         // Order matches the generated signature (26 positional args).
         let result = self
@@ -356,7 +492,7 @@ impl GbifClient {
             self.hits.fetch_add(1, Ordering::Relaxed);
             return results;
         }
-        self.misses.fetch_add(1, Ordering::Relaxed);
+        self.record_miss();
 
         let results = self.search_gbif(query, limit).await;
         self.cache
@@ -394,26 +530,25 @@ impl GbifClient {
     /// genus-level names like "Pinus") — without a hint a non-EXACT result
     /// can leave us with `taxon: None` and no kingdom for the caller to
     /// store.
-    pub async fn validate(&self, name: &str, kingdom_hint: Option<&str>) -> ValidateResponse {
-        let gbif_match = match self.match_name_raw(name, kingdom_hint).await {
-            Ok(Some(m)) => m,
-            _ => {
-                return ValidateResponse {
-                    valid: false,
-                    matched_name: None,
-                    taxon: None,
-                    suggestions: Some(vec![]),
-                };
-            }
+    ///
+    /// A name GBIF doesn't know is an invalid response; only a failed
+    /// lookup is an `Err`.
+    pub async fn validate(
+        &self,
+        name: &str,
+        kingdom_hint: Option<&str>,
+    ) -> Result<ValidateResponse, GbifError> {
+        let no_match = ValidateResponse {
+            valid: false,
+            matched_name: None,
+            taxon: None,
+            suggestions: Some(vec![]),
+        };
+        let Some(gbif_match) = self.match_name_raw(name, kingdom_hint).await? else {
+            return Ok(no_match);
         };
-
         let Some(usage) = &gbif_match.usage else {
-            return ValidateResponse {
-                valid: false,
-                matched_name: None,
-                taxon: None,
-                suggestions: Some(vec![]),
-            };
+            return Ok(no_match);
         };
 
         let is_exact = gbif_match
@@ -428,7 +563,7 @@ impl GbifClient {
             &gbif_match.classification,
         );
 
-        if is_exact {
+        Ok(if is_exact {
             ValidateResponse {
                 valid: true,
                 matched_name: usage.canonical_name.clone().or_else(|| usage.name.clone()),
@@ -442,19 +577,23 @@ impl GbifClient {
                 taxon: None,
                 suggestions: Some(vec![taxon]),
             }
-        }
+        })
     }
 
     /// Get detailed taxon information by GBIF ID.
+    ///
+    /// `Ok(None)` means the taxon doesn't exist: a malformed id, or a 404
+    /// from GBIF, which is cached like a hit. Any other upstream failure is
+    /// an `Err` and is not cached, so the next call retries.
     pub async fn get_by_id(&self, taxon_id: &str) -> Result<Option<TaxonDetail>, GbifError> {
         let numeric_id = taxon_id.strip_prefix("gbif:").unwrap_or(taxon_id);
         let cache_key = format!("detail:{}", numeric_id);
 
         if let Some(CachedValue::TaxonDetail(detail)) = self.cache.get(&cache_key).await {
             self.hits.fetch_add(1, Ordering::Relaxed);
-            return Ok(Some(*detail));
+            return Ok(detail.map(|d| *d));
         }
-        self.misses.fetch_add(1, Ordering::Relaxed);
+        self.record_miss();
 
         let key: i32 = match numeric_id.parse() {
             Ok(k) => k,
@@ -463,22 +602,17 @@ impl GbifClient {
 
         let data = match self.get_name_usage(key).await? {
             Some(d) => d,
-            None => return Ok(None),
+            None => {
+                self.cache
+                    .insert(cache_key, CachedValue::TaxonDetail(None))
+                    .await;
+                return Ok(None);
+            }
         };
 
-        // Fetch children, descriptions, references, media, Wikidata URL, and
-        // Wikidata's primary image (P18) in parallel.
-        let key_u64 = key as u64;
-        let key_slice = [key_u64];
-        let (
-            children,
-            descriptions,
-            references,
-            media,
-            vernacular_names,
-            wikidata_url,
-            wikidata_images,
-        ) = tokio::join!(
+        // Fetch children, descriptions, references, media, and the Wikidata
+        // summary (URL, description, primary image, sitelinks) in parallel.
+        let (children, descriptions, references, media, vernacular_names, wikidata) = tokio::join!(
             self.get_children(taxon_id, 20),
             async {
                 match self
@@ -524,8 +658,7 @@ impl GbifClient {
                     Err(e) => Err(GbifError::from(e)),
                 }
             },
-            self.wikidata.get_entity_url(key_u64),
-            self.wikidata.get_images_for_keys(&key_slice, 600),
+            self.wikidata.get_taxon_summary(key as u64),
         );
 
         let children = children.unwrap_or_default();
@@ -584,23 +717,16 @@ impl GbifClient {
             .map(|r| r.to_lowercase())
             .unwrap_or_else(|| "unknown".to_string());
 
-        // Prefer Wikidata's primary image (P18); fall back to the first GBIF
-        // media item when Wikidata has no image for this taxon.
-        let photo_url = wikidata_images
-            .get(&key_u64)
-            .cloned()
-            .or_else(|| media.first().map(|m| m.url.clone()));
-
         // Prefer the best entry from the full vernacular list; fall back to the
         // v1 scalar when the list is empty or the request failed.
         let common_name =
             pick_vernacular(&vernacular_names.unwrap_or_default()).or(data.vernacular_name);
 
-        let taxon_detail = TaxonDetail {
+        let mut taxon_detail = TaxonDetail {
             id: build_taxon_path(resolved_name, &resolved_rank, data.kingdom.as_deref()),
             scientific_name: resolved_name.to_string(),
             common_name,
-            photo_url,
+            photo_url: media.first().map(|m| m.url.clone()),
             rank: resolved_rank,
             kingdom: data.kingdom,
             phylum: data.phylum,
@@ -629,13 +755,15 @@ impl GbifClient {
             },
             media: if media.is_empty() { None } else { Some(media) },
             gbif_url: Some(format!("https://www.gbif.org/species/{}", data.key)),
-            wikidata_url,
+            wikidata_url: None,
+            sitelinks: None,
         };
+        apply_wikidata(&mut taxon_detail, wikidata);
 
         self.cache
             .insert(
                 cache_key,
-                CachedValue::TaxonDetail(Box::new(taxon_detail.clone())),
+                CachedValue::TaxonDetail(Some(Box::new(taxon_detail.clone()))),
             )
             .await;
         Ok(Some(taxon_detail))
@@ -706,7 +834,7 @@ impl GbifClient {
             self.hits.fetch_add(1, Ordering::Relaxed);
             return Ok(results);
         }
-        self.misses.fetch_add(1, Ordering::Relaxed);
+        self.record_miss();
 
         let key: i32 = match numeric_id.parse() {
             Ok(k) => k,
@@ -964,6 +1092,46 @@ impl Default for GbifClient {
     }
 }
 
+/// Shortest GBIF description treated as usable prose. Shorter entries are
+/// typically stubs like "Tree." or a bare habitat keyword.
+const MIN_GBIF_DESCRIPTION_CHARS: usize = 80;
+
+/// Merge a Wikidata summary into a GBIF-built detail.
+///
+/// Wikidata's primary image (P18) takes precedence over GBIF media, and its
+/// entity id, URL and Wikipedia sitelinks are always carried over. Its short
+/// description only fills `description` when GBIF has nothing usable.
+fn apply_wikidata(detail: &mut TaxonDetail, summary: Option<EntitySummary>) {
+    let Some(summary) = summary else {
+        return;
+    };
+    detail.wikidata_id = summary.qid().map(str::to_string);
+    if summary.image.is_some() {
+        detail.photo_url = summary.image;
+    }
+    let has_gbif_description = detail
+        .descriptions
+        .iter()
+        .flatten()
+        .any(|d| d.description.trim().chars().count() >= MIN_GBIF_DESCRIPTION_CHARS);
+    if detail.description.is_none() && !has_gbif_description {
+        detail.description = summary.description;
+    }
+    if !summary.sitelinks.is_empty() {
+        detail.sitelinks = Some(
+            summary
+                .sitelinks
+                .into_iter()
+                .map(|s| TaxonSitelink {
+                    language: s.language,
+                    url: s.url,
+                })
+                .collect(),
+        );
+    }
+    detail.wikidata_url = Some(summary.url);
+}
+
 /// Pick the best vernacular name for a search result.
 fn pick_vernacular_name(item: &NameUsageSearchResult) -> Option<String> {
     pick_vernacular(&item.vernacular_names)
@@ -1022,12 +1190,13 @@ fn pick_vernacular(names: &[gbif::checklistbank::types::VernacularName]) -> Opti
 }
 
 /// Cache hit/miss/entry-count snapshot.
-#[allow(dead_code)] // exposed for future health/diagnostics endpoint
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct CacheStats {
     pub entries: u64,
     pub hits: u64,
     pub misses: u64,
+    /// Entries evicted for capacity since startup.
+    pub evictions: u64,
 }
 
 #[cfg(test)]
@@ -1219,6 +1388,50 @@ mod tests {
         assert!("ex".parse::<IucnCategory>().is_err());
     }
 
+    #[test]
+    fn test_iucn_category_ordering() {
+        use IucnCategory::*;
+        let scale = [Lc, Nt, Vu, En, Cr, Ew, Ex];
+        for pair in scale.windows(2) {
+            assert!(pair[0] < pair[1], "{} < {}", pair[0], pair[1]);
+        }
+        assert!(En.is_at_least(Vu));
+        assert!(Vu.is_at_least(Vu));
+        assert!(!Nt.is_at_least(Vu));
+
+        // DD/NE are unranked: equal to themselves, incomparable otherwise
+        for unranked in [Dd, Ne] {
+            assert_eq!(
+                unranked.partial_cmp(&unranked),
+                Some(std::cmp::Ordering::Equal)
+            );
+            for ranked in scale {
+                assert_eq!(unranked.partial_cmp(&ranked), None);
+                assert!(!unranked.is_at_least(ranked));
+            }
+        }
+        assert_eq!(Dd.partial_cmp(&Ne), None);
+    }
+
+    #[test]
+    fn test_iucn_category_is_threatened() {
+        use IucnCategory::*;
+        assert_eq!(IucnCategory::ALL.len(), 9);
+        for (category, threatened) in [
+            (Ex, false),
+            (Ew, false),
+            (Cr, true),
+            (En, true),
+            (Vu, true),
+            (Nt, false),
+            (Lc, false),
+            (Dd, false),
+            (Ne, false),
+        ] {
+            assert_eq!(category.is_threatened(), threatened, "{category}");
+        }
+    }
+
     #[test]
     fn test_gbif_taxon_uri_format() {
         // The shape the ingester parses back out (`observing-db::processing`).
@@ -1426,7 +1639,7 @@ mod tests {
             .await;
 
         let client = GbifClient::with_base_url(&server.uri());
-        let resp = client.validate("Passer domesticus", None).await;
+        let resp = client.validate("Passer domesticus", None).await.unwrap();
 
         assert!(resp.valid, "an EXACT match should validate");
         let taxon = resp.taxon.expect("an exact match returns a taxon");
@@ -1436,4 +1649,245 @@ mod tests {
             "the resolved taxon carries the GBIF species URI used as dwc:taxonID"
         );
     }
+
+    // ---------- Wikidata enrichment ----------
+
+    /// A GBIF-only detail as `get_by_id` builds it before enrichment.
+    fn gbif_detail(descriptions: Option<Vec<TaxonDescription>>) -> TaxonDetail {
+        TaxonDetail {
+            id: "Quercus alba".into(),
+            scientific_name: "Quercus alba".into(),
+            common_name: Some("White Oak".into()),
+            photo_url: Some("https://api.gbif.org/media/1.jpg".into()),
+            rank: "species".into(),
+            kingdom: Some("Plantae".into()),
+            phylum: None,
+            class: None,
+            order: None,
+            family: Some("Fagaceae".into()),
+            genus: Some("Quercus".into()),
+            species: Some("Quercus alba".into()),
+            source: "gbif".into(),
+            conservation_status: None,
+            description: None,
+            wikidata_id: None,
+            ancestors: vec![],
+            children: vec![],
+            num_descendants: None,
+            extinct: None,
+            descriptions,
+            references: None,
+            media: None,
+            gbif_url: Some("https://www.gbif.org/species/2878688".into()),
+            wikidata_url: None,
+            sitelinks: None,
+        }
+    }
+
+    /// SPARQL JSON results for Q218155, one row per Wikipedia sitelink.
+    fn sparql_summary_body() -> serde_json::Value {
+        let row = |lang: &str| {
+            json!({
+                "item": { "type": "uri", "value": "http://www.wikidata.org/entity/Q218155" },
+                "description": { "type": "literal", "xml:lang": "en", "value": "species of oak" },
+                "image": {
+                    "type": "uri",
+                    "value": "http://commons.wikimedia.org/wiki/Special:FilePath/Quercus%20alba.jpg"
+                },
+                "article": { "type": "uri", "value": format!("https://{lang}.wikipedia.org/wiki/Quercus_alba") },
+                "lang": { "type": "literal", "value": lang }
+            })
+        };
+        json!({
+            "head": { "vars": ["item", "description", "image", "article", "lang"] },
+            "results": { "bindings": [row("fr"), row("en"), row("en")] }
+        })
+    }
+
+    async fn mocked_summary() -> Option<EntitySummary> {
+        let server = MockServer::start().await;
+        Mock::given(path("/sparql"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(sparql_summary_body()))
+            .mount(&server)
+            .await;
+        let wikidata = WikidataClient::with_endpoint(&format!("{}/sparql", server.uri()));
+        wikidata.get_taxon_summary(2878688).await
+    }
+
+    #[tokio::test]
+    async fn wikidata_summary_fills_missing_gbif_description() {
+        let mut detail = gbif_detail(Some(vec![TaxonDescription {
+            description: "Tree.".into(),
+            r#type: None,
+            source: None,
+        }]));
+        apply_wikidata(&mut detail, mocked_summary().await);
+
+        assert_eq!(detail.description.as_deref(), Some("species of oak"));
+        assert_eq!(detail.wikidata_id.as_deref(), Some("Q218155"));
+        assert_eq!(
+            detail.wikidata_url.as_deref(),
+            Some("https://www.wikidata.org/wiki/Q218155")
+        );
+        assert_eq!(
+            detail.photo_url.as_deref(),
+            Some("http://commons.wikimedia.org/wiki/Special:FilePath/Quercus%20alba.jpg?width=600"),
+            "P18 beats GBIF media"
+        );
+
+        let sitelinks = detail.sitelinks.expect("sitelinks");
+        let langs: Vec<&str> = sitelinks.iter().map(|s| s.language.as_str()).collect();
+        assert_eq!(langs, ["en", "fr"], "deduplicated and sorted by language");
+        // GBIF fields are left alone.
+        assert_eq!(detail.common_name.as_deref(), Some("White Oak"));
+        assert_eq!(detail.source, "gbif");
+    }
+
+    #[tokio::test]
+    async fn wikidata_description_does_not_override_good_gbif_description() {
+        let prose = "Quercus alba is a long-lived deciduous tree of eastern North America, \
+                     growing in a wide range of soils and habitats.";
+        let mut detail = gbif_detail(Some(vec![TaxonDescription {
+            description: prose.into(),
+            r#type: Some("general".into()),
+            source: None,
+        }]));
+        apply_wikidata(&mut detail, mocked_summary().await);
+
+        assert!(detail.description.is_none());
+        assert_eq!(detail.wikidata_id.as_deref(), Some("Q218155"));
+        assert!(detail.sitelinks.is_some());
+    }
+
+    #[test]
+    fn missing_wikidata_summary_leaves_gbif_detail_untouched() {
+        let mut detail = gbif_detail(None);
+        apply_wikidata(&mut detail, None);
+        assert_eq!(
+            detail.photo_url.as_deref(),
+            Some("https://api.gbif.org/media/1.jpg")
+        );
+        assert!(detail.description.is_none());
+        assert!(detail.wikidata_url.is_none());
+        assert!(detail.sitelinks.is_none());
+    }
+
+    // ---------- get_by_id upstream errors ----------
+
+    #[tokio::test]
+    async fn get_by_id_caches_a_404_as_not_found() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/species/999999999"))
+            .respond_with(ResponseTemplate::new(404))
+            // The miss is definitive: the second lookup is served from cache.
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = GbifClient::with_base_url(&server.uri());
+        for _ in 0..2 {
+            let detail = client
+                .get_by_id("gbif:999999999")
+                .await
+                .expect("a 404 is not an error");
+            assert!(detail.is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn tracking_upstream_ignores_cache_hits() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/species/999999999"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = GbifClient::with_base_url(&server.uri());
+        let (_, reached) = GbifClient::tracking_upstream(client.get_by_id("gbif:999999999")).await;
+        assert!(reached, "the first lookup misses the cache");
+        let (_, reached) = GbifClient::tracking_upstream(client.get_by_id("gbif:999999999")).await;
+        assert!(!reached, "the repeat is a cache hit");
+    }
+
+    #[tokio::test]
+    async fn get_by_id_surfaces_a_5xx_as_an_error_and_retries() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/species/5231190"))
+            .respond_with(ResponseTemplate::new(500))
+            // Nothing is cached for a failure, so each call goes upstream.
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let client = GbifClient::with_base_url(&server.uri());
+        for _ in 0..2 {
+            assert!(
+                client.get_by_id("gbif:5231190").await.is_err(),
+                "a 500 must not look like a missing taxon"
+            );
+        }
+    }
+
+    // ---------- ping ----------
+
+    #[tokio::test]
+    async fn ping_succeeds_when_gbif_answers() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/species/1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "key": 1 })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = GbifClient::with_base_url(&server.uri());
+        assert_eq!(client.ping().await, Ok(()));
+        assert_eq!(client.cache_stats().entries, 0, "ping must not cache");
+    }
+
+    #[tokio::test]
+    async fn ping_reports_an_unhealthy_upstream() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/species/1"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+
+        let client = GbifClient::with_base_url(&server.uri());
+        let err = client.ping().await.unwrap_err();
+        assert!(err.contains("503"), "got: {err}");
+    }
+
+    // ---------- cache eviction ----------
+
+    #[tokio::test]
+    async fn cache_keeps_recently_used_entries_past_capacity() {
+        let client = GbifClient::build(GBIF_BASE_URL, 4, DEFAULT_CACHE_TTL);
+        let hot = "detail:hot".to_string();
+        client
+            .cache
+            .insert(hot.clone(), CachedValue::Children(vec![]))
+            .await;
+
+        // A crawl of one-off keys, with the hot key read between each.
+        for i in 0..20 {
+            client
+                .cache
+                .insert(format!("detail:{i}"), CachedValue::Children(vec![]))
+                .await;
+            assert!(client.cache.get(&hot).await.is_some(), "hot entry evicted");
+            client.cache.run_pending_tasks().await;
+        }
+
+        let stats = client.cache_stats();
+        assert!(stats.entries <= 4, "over capacity: {}", stats.entries);
+        assert!(stats.evictions >= 16, "evictions: {}", stats.evictions);
+        assert!(client.cache.get(&hot).await.is_some());
+        assert!(client.cache.get("detail:0").await.is_none());
+    }
 }
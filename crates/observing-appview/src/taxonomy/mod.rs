@@ -6,6 +6,7 @@
 //! directly; response shapes are still defined in [`crate::taxonomy_client`]
 //! so TypeScript bindings remain stable.
 
+pub mod breaker;
 pub mod gbif;
 pub mod wikidata;
 
@@ -1,9 +1,12 @@
-//! Wikidata integration for taxon images and entity lookups via GBIF taxon IDs.
+//! Wikidata integration for taxon images, descriptions and entity lookups via
+//! GBIF taxon IDs.
 //!
 //! Thin wrapper around `wikidata_client` that works with GBIF numeric keys.
 
 use std::collections::HashMap;
-use wikidata_client::WikidataClient as Client;
+use wikidata_client::{EntitySummary, WikidataClient as Client};
+
+const USER_AGENT: &str = "Observing/1.0 (https://observ.ing; taxonomy service)";
 
 /// Client for fetching taxon data from Wikidata using GBIF taxon IDs (property P846).
 pub struct WikidataClient {
@@ -13,7 +16,15 @@ pub struct WikidataClient {
 impl WikidataClient {
     pub fn new() -> Self {
         Self {
-            client: Client::with_user_agent("Observing/1.0 (https://observ.ing; taxonomy service)"),
+            client: Client::with_user_agent(USER_AGENT),
+        }
+    }
+
+    /// Construct a client against an arbitrary SPARQL endpoint. Production
+    /// uses Wikidata's; tests point it at a wiremock server.
+    pub fn with_endpoint(endpoint: &str) -> Self {
+        Self {
+            client: Client::with_endpoint(endpoint, USER_AGENT),
         }
     }
 
@@ -38,15 +49,12 @@ impl WikidataClient {
             .collect()
     }
 
-    /// Get the Wikidata entity URL for a GBIF taxon ID.
-    /// Returns e.g. `"https://www.wikidata.org/wiki/Q12345"`.
-    pub async fn get_entity_url(&self, gbif_key: u64) -> Option<String> {
-        let key_str = gbif_key.to_string();
-        let results = self
-            .client
-            .get_entities_by_property("P846", &[key_str.as_str()])
-            .await;
-        results.into_values().next()
+    /// Get the Wikidata entity for a GBIF taxon ID: its URL, English
+    /// description, primary image (600px thumbnail) and Wikipedia sitelinks.
+    pub async fn get_taxon_summary(&self, gbif_key: u64) -> Option<EntitySummary> {
+        self.client
+            .get_entity_summary_by_property("P846", &gbif_key.to_string(), "en", 600)
+            .await
     }
 }
 
@@ -1,15 +1,20 @@
-//! Public response types for the taxonomy API and a thin in-process
-//! [`TaxonomyClient`] facade over [`crate::taxonomy::GbifClient`].
+//! Public response types for the taxonomy API, the [`TaxonomyProvider`]
+//! trait routes resolve taxa through, and [`TaxonomyClient`], its in-process
+//! implementation over [`crate::taxonomy::GbifClient`].
 //!
 //! Response shapes (`TaxonResult`, `TaxonDetail`, `ValidateResponse`, …) are
 //! retained here as the canonical TS-bound types so generated bindings stay
 //! stable across the service collapse.
 
+use async_trait::async_trait;
+use observing_db::types::TaxonLocalStats;
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::future::Future;
 use ts_rs::TS;
 
-use crate::taxonomy::GbifClient;
+use crate::taxonomy::breaker::CircuitBreaker;
+use crate::taxonomy::{GbifClient, GbifError};
 
 /// Error from the taxonomy resolver. Used to keep the `?`/`From` plumbing in
 /// route handlers identical to the previous HTTP-client-era code.
@@ -28,10 +33,60 @@ impl From<crate::taxonomy::GbifError> for TaxonomyClientError {
     }
 }
 
+/// The taxonomy lookups routes and enrichment depend on. [`AppState`] holds
+/// one as a trait object so tests can swap [`TaxonomyClient`] (and its GBIF
+/// calls) for a canned implementation.
+///
+/// [`AppState`]: crate::state::AppState
+#[async_trait]
+pub trait TaxonomyProvider: Send + Sync {
+    /// Probe the upstream for the health check.
+    async fn ping(&self) -> Result<(), TaxonomyClientError>;
+
+    /// Snapshot of the lookup cache for the health check, or `None` when
+    /// the provider doesn't cache.
+    fn cache_stats(&self) -> Option<crate::taxonomy::CacheStats> {
+        None
+    }
+
+    /// Search taxa by name. Returns `None` only on internal failure; an empty
+    /// or erroring query yields an empty list, so most callers can use
+    /// `.unwrap_or_default()`.
+    async fn search(&self, query: &str, limit: Option<u32>) -> Option<Vec<TaxonResult>>;
+
+    /// Validate a taxon name with an optional kingdom hint for
+    /// disambiguation. Returns `None` if the lookup itself failed.
+    async fn validate(&self, name: &str, kingdom_hint: Option<&str>) -> Option<ValidateResponse>;
+
+    /// Get taxon detail by ID (`gbif:NNN` or bare numeric).
+    async fn get_by_id(&self, id: &str) -> Result<Option<TaxonDetail>, TaxonomyClientError>;
+
+    /// Get taxon detail by scientific name with optional kingdom hint.
+    async fn get_by_name(
+        &self,
+        name: &str,
+        kingdom: Option<&str>,
+    ) -> Result<Option<TaxonDetail>, TaxonomyClientError>;
+
+    /// Get children of a taxon by scientific name with optional kingdom hint.
+    /// Returns `Ok(None)` only on lookup failure; an empty parent yields
+    /// `Ok(Some(vec![]))`.
+    async fn get_children(
+        &self,
+        name: &str,
+        kingdom: Option<&str>,
+    ) -> Result<Option<Vec<TaxonResult>>, TaxonomyClientError>;
+}
+
 /// In-process taxonomy facade. Wraps [`GbifClient`] so routes can stay
 /// agnostic to whether resolution happens locally or over HTTP.
+///
+/// Fallible lookups go through a [`CircuitBreaker`]: while GBIF is failing
+/// they return an error immediately instead of waiting on the upstream, so
+/// enrichment degrades to the locally known taxonomy.
 pub struct TaxonomyClient {
     inner: GbifClient,
+    breaker: CircuitBreaker,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -70,7 +125,7 @@ pub struct TaxonResult {
     pub conservation_status: Option<ConservationStatus>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
 #[ts(export, export_to = "bindings/")]
 pub struct ConservationStatus {
     pub category: String,
@@ -126,6 +181,15 @@ pub struct TaxonMedia {
     pub license: Option<String>,
 }
 
+/// A Wikipedia article about the taxon, from its Wikidata sitelinks.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "bindings/")]
+pub struct TaxonSitelink {
+    pub language: String,
+    pub url: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(rename_all = "camelCase")]
 #[ts(export, export_to = "bindings/")]
@@ -176,6 +240,8 @@ pub struct TaxonDetail {
     pub gbif_url: Option<String>,
     #[ts(optional)]
     pub wikidata_url: Option<String>,
+    #[ts(optional)]
+    pub sitelinks: Option<Vec<TaxonSitelink>>,
 }
 
 #[derive(Debug, Clone, Serialize, TS)]
@@ -185,6 +251,7 @@ pub struct TaxonDetailWithCount {
     #[serde(flatten)]
     pub detail: TaxonDetail,
     pub observation_count: i64,
+    pub local_stats: TaxonLocalStats,
 }
 
 #[derive(Debug, Serialize, Deserialize, TS)]
@@ -229,7 +296,7 @@ impl TaxonFields {
     /// validate often returns no taxon at all). It also acts as a fallback
     /// when the validation succeeds but doesn't carry a kingdom of its own.
     pub async fn from_validation(
-        taxonomy: &TaxonomyClient,
+        taxonomy: &dyn TaxonomyProvider,
         scientific_name: &str,
         rank_override: Option<String>,
         kingdom_hint: Option<&str>,
@@ -266,60 +333,82 @@ impl TaxonFields {
 impl TaxonomyClient {
     /// Construct a new client backed by an in-memory GBIF + Wikidata stack.
     pub fn new() -> Self {
-        Self {
-            inner: GbifClient::new(),
+        Self::with_parts(GbifClient::new(), CircuitBreaker::default())
+    }
+
+    pub fn with_parts(inner: GbifClient, breaker: CircuitBreaker) -> Self {
+        Self { inner, breaker }
+    }
+
+    /// Run an upstream lookup through the circuit breaker. Only lookups that
+    /// actually reached GBIF count towards it: cache hits would otherwise
+    /// keep resetting the failure count during an outage.
+    async fn guarded<T>(
+        &self,
+        call: impl Future<Output = Result<T, GbifError>>,
+    ) -> Result<T, TaxonomyClientError> {
+        if self.breaker.is_open() {
+            return Err(TaxonomyClientError(
+                "taxonomy upstream unavailable (circuit open)".to_string(),
+            ));
+        }
+        let (result, reached_upstream) = GbifClient::tracking_upstream(call).await;
+        if reached_upstream {
+            match &result {
+                Ok(_) => self.breaker.record_success(),
+                Err(_) => self.breaker.record_failure(),
+            }
         }
+        result.map_err(Into::into)
     }
+}
 
-    /// Snapshot of the inner GBIF cache (entries / hits / misses).
-    #[allow(dead_code)] // exposed for future health/diagnostics endpoint
-    pub fn cache_stats(&self) -> crate::taxonomy::CacheStats {
-        self.inner.cache_stats()
+#[async_trait]
+impl TaxonomyProvider for TaxonomyClient {
+    /// Probe GBIF for the health check. Bypasses the circuit breaker so a
+    /// probe neither trips nor resets it.
+    async fn ping(&self) -> Result<(), TaxonomyClientError> {
+        self.inner.ping().await.map_err(TaxonomyClientError)
     }
 
-    /// Search taxa by name. Returns `None` only on internal failure; the
-    /// inner client logs and returns an empty list for empty/erroring queries
-    /// so most callers can use `.unwrap_or_default()`.
-    pub async fn search(&self, query: &str, limit: Option<u32>) -> Option<Vec<TaxonResult>> {
+    /// The inner GBIF cache's entries, hits, misses and evictions.
+    fn cache_stats(&self) -> Option<crate::taxonomy::CacheStats> {
+        Some(self.inner.cache_stats())
+    }
+
+    /// The inner client logs and returns an empty list for empty/erroring
+    /// queries, so this never returns `None`.
+    async fn search(&self, query: &str, limit: Option<u32>) -> Option<Vec<TaxonResult>> {
         Some(self.inner.search(query, limit.unwrap_or(10)).await)
     }
 
-    /// Validate a taxon name with an optional kingdom hint for GBIF
-    /// disambiguation. Returns `None` if the lookup itself failed —
-    /// preserved for parity with the prior HTTP client.
-    pub async fn validate(
-        &self,
-        name: &str,
-        kingdom_hint: Option<&str>,
-    ) -> Option<ValidateResponse> {
-        Some(self.inner.validate(name, kingdom_hint).await)
+    async fn validate(&self, name: &str, kingdom_hint: Option<&str>) -> Option<ValidateResponse> {
+        self.guarded(self.inner.validate(name, kingdom_hint))
+            .await
+            .inspect_err(|e| tracing::warn!(name, error = %e, "Taxon validation failed"))
+            .ok()
     }
 
-    /// Get taxon detail by GBIF ID (`gbif:NNN` or bare numeric).
-    pub async fn get_by_id(&self, id: &str) -> Result<Option<TaxonDetail>, TaxonomyClientError> {
-        Ok(self.inner.get_by_id(id).await?)
+    async fn get_by_id(&self, id: &str) -> Result<Option<TaxonDetail>, TaxonomyClientError> {
+        self.guarded(self.inner.get_by_id(id)).await
     }
 
-    /// Get taxon detail by scientific name with optional kingdom hint.
-    pub async fn get_by_name(
+    async fn get_by_name(
         &self,
         name: &str,
         kingdom: Option<&str>,
     ) -> Result<Option<TaxonDetail>, TaxonomyClientError> {
-        Ok(self.inner.get_by_name(name, kingdom).await?)
+        self.guarded(self.inner.get_by_name(name, kingdom)).await
     }
 
-    /// Get children of a taxon by scientific name with optional kingdom hint.
-    /// Returns `Ok(None)` only on lookup failure; an empty parent yields
-    /// `Ok(Some(vec![]))`.
-    pub async fn get_children(
+    async fn get_children(
         &self,
         name: &str,
         kingdom: Option<&str>,
     ) -> Result<Option<Vec<TaxonResult>>, TaxonomyClientError> {
-        Ok(Some(
-            self.inner.get_children_by_name(name, kingdom, 20).await?,
-        ))
+        self.guarded(self.inner.get_children_by_name(name, kingdom, 20))
+            .await
+            .map(Some)
     }
 }
 
@@ -328,3 +417,95 @@ impl Default for TaxonomyClient {
         Self::new()
     }
 }
+
+/// Canned [`TaxonomyProvider`] for handler tests: knows only the taxa it is
+/// given and never touches the network.
+#[cfg(test)]
+pub(crate) struct FakeTaxonomy {
+    taxa: Vec<TaxonResult>,
+}
+
+#[cfg(test)]
+impl FakeTaxonomy {
+    pub(crate) fn new(taxa: Vec<TaxonResult>) -> Self {
+        Self { taxa }
+    }
+
+    /// A minimal taxon as validate/search would return it.
+    pub(crate) fn taxon(scientific_name: &str, rank: &str, kingdom: &str) -> TaxonResult {
+        TaxonResult {
+            id: scientific_name.to_string(),
+            taxon_id: Some(format!("https://example.org/taxa/{scientific_name}")),
+            scientific_name: scientific_name.to_string(),
+            common_name: None,
+            photo_url: None,
+            rank: rank.to_string(),
+            kingdom: Some(kingdom.to_string()),
+            phylum: None,
+            class: None,
+            order: None,
+            family: None,
+            genus: None,
+            species: None,
+            source: "fake".to_string(),
+            conservation_status: None,
+        }
+    }
+
+    fn find(&self, name: &str, kingdom: Option<&str>) -> Option<&TaxonResult> {
+        self.taxa.iter().find(|t| {
+            t.scientific_name.eq_ignore_ascii_case(name)
+                && kingdom.is_none_or(|k| t.kingdom.as_deref() == Some(k))
+        })
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl TaxonomyProvider for FakeTaxonomy {
+    async fn ping(&self) -> Result<(), TaxonomyClientError> {
+        Ok(())
+    }
+
+    async fn search(&self, query: &str, limit: Option<u32>) -> Option<Vec<TaxonResult>> {
+        let query = query.to_lowercase();
+        Some(
+            self.taxa
+                .iter()
+                .filter(|t| t.scientific_name.to_lowercase().contains(&query))
+                .take(limit.unwrap_or(10) as usize)
+                .cloned()
+                .collect(),
+        )
+    }
+
+    async fn validate(&self, name: &str, kingdom_hint: Option<&str>) -> Option<ValidateResponse> {
+        let taxon = self.find(name, kingdom_hint).cloned();
+        Some(ValidateResponse {
+            valid: taxon.is_some(),
+            matched_name: taxon.as_ref().map(|t| t.scientific_name.clone()),
+            taxon,
+            suggestions: None,
+        })
+    }
+
+    async fn get_by_id(&self, _id: &str) -> Result<Option<TaxonDetail>, TaxonomyClientError> {
+        Ok(None)
+    }
+
+    async fn get_by_name(
+        &self,
+        _name: &str,
+        _kingdom: Option<&str>,
+    ) -> Result<Option<TaxonDetail>, TaxonomyClientError> {
+        Ok(None)
+    }
+
+    async fn get_children(
+        &self,
+        _name: &str,
+        _kingdom: Option<&str>,
+    ) -> Result<Option<Vec<TaxonResult>>, TaxonomyClientError> {
+        Ok(Some(Vec::new()))
+    }
+}
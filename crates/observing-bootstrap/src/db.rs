@@ -13,9 +13,11 @@
 //! layers `pg_url_env` resolution on top for the common Cloud Run / Cloud SQL
 //! case.
 
+use std::str::FromStr;
 use std::time::Duration;
 
-use sqlx::postgres::{PgPool, PgPoolOptions};
+use sqlx::postgres::{PgConnectOptions, PgPool, PgPoolOptions};
+use sqlx::{Postgres, Transaction};
 
 /// Connection-pool sizing for one workload.
 ///
@@ -23,7 +25,8 @@ use sqlx::postgres::{PgPool, PgPoolOptions};
 /// [`migration`](Self::migration), [`worker`](Self::worker), [`job`](Self::job)),
 /// then override individual fields with the builder setters if a specific
 /// deployment needs to. All presets use the same defaults for the pieces they
-/// don't care about (no idle recycling, no max lifetime).
+/// don't care about (no idle recycling, no max lifetime, no statement
+/// timeout).
 #[derive(Debug, Clone)]
 pub struct PoolConfig {
     /// Upper bound on open connections.
@@ -36,6 +39,10 @@ pub struct PoolConfig {
     /// Retire and reopen a connection after it has lived this long, regardless
     /// of activity. `None` never retires on age.
     pub max_lifetime: Option<Duration>,
+    /// Server-side `statement_timeout` set on every connection, so a stuck
+    /// query errors instead of holding its connection. `None` leaves the
+    /// server default (usually unlimited).
+    pub statement_timeout: Option<Duration>,
 }
 
 impl PoolConfig {
@@ -48,6 +55,7 @@ impl PoolConfig {
             acquire_timeout: Duration::from_secs(5),
             idle_timeout: Some(Duration::from_secs(300)),
             max_lifetime: Some(Duration::from_secs(1800)),
+            statement_timeout: None,
         }
     }
 
@@ -59,6 +67,7 @@ impl PoolConfig {
             acquire_timeout: Duration::from_secs(5),
             idle_timeout: Some(Duration::from_secs(300)),
             max_lifetime: None,
+            statement_timeout: None,
         }
     }
 
@@ -71,6 +80,7 @@ impl PoolConfig {
             acquire_timeout: Duration::from_secs(30),
             idle_timeout: None,
             max_lifetime: None,
+            statement_timeout: None,
         }
     }
 
@@ -82,6 +92,7 @@ impl PoolConfig {
             acquire_timeout: Duration::from_secs(10),
             idle_timeout: None,
             max_lifetime: None,
+            statement_timeout: None,
         }
     }
 
@@ -93,6 +104,7 @@ impl PoolConfig {
             acquire_timeout: Duration::from_secs(30),
             idle_timeout: None,
             max_lifetime: None,
+            statement_timeout: None,
         }
     }
 
@@ -120,6 +132,21 @@ impl PoolConfig {
         self
     }
 
+    /// Override the statement timeout (`None` keeps the server default).
+    pub fn with_statement_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.statement_timeout = timeout;
+        self
+    }
+
+    /// Parse `url`, adding the statement timeout if one is configured.
+    fn connect_options(&self, url: &str) -> Result<PgConnectOptions, sqlx::Error> {
+        let options = PgConnectOptions::from_str(url)?;
+        Ok(match self.statement_timeout {
+            Some(timeout) => options.options([("statement_timeout", timeout.as_millis())]),
+            None => options,
+        })
+    }
+
     /// Connect a pool against an already-resolved connection string.
     ///
     /// Use this when the caller owns URL resolution — e.g. a migration runner
@@ -135,7 +162,7 @@ impl PoolConfig {
         if let Some(lifetime) = self.max_lifetime {
             opts = opts.max_lifetime(Some(lifetime));
         }
-        opts.connect(url).await
+        opts.connect_with(self.connect_options(url)?).await
     }
 
     /// Resolve the connection string from the environment, then connect.
@@ -157,3 +184,43 @@ impl PoolConfig {
             .map_err(|e| format!("failed to connect: {e}"))
     }
 }
+
+/// Begin a transaction whose statements may run for up to `timeout`,
+/// overriding the pool's [`PoolConfig::statement_timeout`]. For the few
+/// aggregation queries that legitimately take longer than the default.
+pub async fn begin_with_statement_timeout(
+    pool: &PgPool,
+    timeout: Duration,
+) -> Result<Transaction<'static, Postgres>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    // SET doesn't take bind parameters; the value is an integer we format.
+    sqlx::query(sqlx::AssertSqlSafe(format!(
+        "SET LOCAL statement_timeout = {}",
+        timeout.as_millis()
+    )))
+    .execute(&mut *tx)
+    .await?;
+    Ok(tx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statement_timeout_is_passed_as_a_startup_option() {
+        let options = PoolConfig::service()
+            .with_statement_timeout(Some(Duration::from_secs(5)))
+            .connect_options("postgres://localhost/observing")
+            .unwrap();
+        assert_eq!(options.get_options(), Some("-c statement_timeout=5000"));
+    }
+
+    #[test]
+    fn presets_leave_the_statement_timeout_unset() {
+        let options = PoolConfig::service()
+            .connect_options("postgres://localhost/observing")
+            .unwrap();
+        assert_eq!(options.get_options(), None);
+    }
+}
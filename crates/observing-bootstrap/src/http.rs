@@ -1,19 +1,186 @@
 //! HTTP service scaffolding.
 
+use std::future::Future;
 use std::net::SocketAddr;
+use std::time::Duration;
 
-/// Bind an axum app to `0.0.0.0:<port>` and serve it until the process exits.
+use tokio::net::TcpListener;
+
+/// How long in-flight requests get to finish after a shutdown signal before
+/// the server stops waiting for them. Cloud Run sends SIGKILL 10s after
+/// SIGTERM, so this leaves the caller a moment for its own cleanup (e.g.
+/// closing a database pool).
+pub const DRAIN_TIMEOUT: Duration = Duration::from_secs(8);
+
+/// Bind an axum app to `0.0.0.0:<port>` and serve it until SIGTERM/SIGINT.
 ///
 /// Centralizes the `SocketAddr::from(([0, 0, 0, 0], port))` →
 /// `TcpListener::bind` → `axum::serve` sequence that every HTTP service
 /// repeated. Binding to `0.0.0.0` (all interfaces) is required for the Cloud
 /// Run TCP startup probe to reach the container.
 ///
+/// On [`shutdown_signal`] the listener stops accepting and in-flight requests
+/// are drained for up to [`DRAIN_TIMEOUT`]; this returns once they finish (or
+/// the timeout lapses), so callers can clean up before exiting.
+///
 /// Returns the bind/serve [`std::io::Error`] to the caller; the message logged
 /// on startup includes the resolved address.
 pub async fn serve(router: axum::Router, port: u16) -> std::io::Result<()> {
     let addr = SocketAddr::from(([0, 0, 0, 0], port));
     tracing::info!("Starting HTTP server on {addr}");
-    let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, router).await
+    let listener = TcpListener::bind(addr).await?;
+    serve_until(listener, router, shutdown_signal(), DRAIN_TIMEOUT).await
+}
+
+/// Serve `router` on `listener` until `signal` resolves, then drain in-flight
+/// requests for at most `drain_timeout`.
+pub async fn serve_until(
+    listener: TcpListener,
+    router: axum::Router,
+    signal: impl Future<Output = ()> + Send + 'static,
+    drain_timeout: Duration,
+) -> std::io::Result<()> {
+    let (draining_tx, draining_rx) = tokio::sync::oneshot::channel();
+    let server = axum::serve(listener, router).with_graceful_shutdown(async move {
+        signal.await;
+        tracing::info!("Shutdown signal received, draining in-flight requests");
+        let _ = draining_tx.send(());
+    });
+
+    tokio::select! {
+        result = server => result,
+        () = async {
+            match draining_rx.await {
+                Ok(()) => tokio::time::sleep(drain_timeout).await,
+                // The server exited without a shutdown signal; let its own
+                // branch report the outcome.
+                Err(_) => std::future::pending().await,
+            }
+        } => {
+            tracing::warn!(?drain_timeout, "Drain timed out, abandoning in-flight requests");
+            Ok(())
+        }
+    }
+}
+
+/// Resolve on the first SIGTERM (what Cloud Run sends before stopping an
+/// instance) or Ctrl-C.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!(error = %e, "Failed to listen for Ctrl-C");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = ctrl_c => {}
+        () = terminate => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use axum::routing::get;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+    use tokio::sync::{oneshot, Notify};
+
+    /// A router whose only route signals `started` and then takes `delay`.
+    fn slow_router(started: Arc<Notify>, delay: Duration) -> axum::Router {
+        axum::Router::new().route(
+            "/slow",
+            get(move || async move {
+                started.notify_one();
+                tokio::time::sleep(delay).await;
+                "done"
+            }),
+        )
+    }
+
+    async fn get_slow(addr: SocketAddr) -> std::io::Result<String> {
+        let mut stream = TcpStream::connect(addr).await?;
+        stream
+            .write_all(b"GET /slow HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await?;
+        Ok(response)
+    }
+
+    async fn start(
+        delay: Duration,
+        drain_timeout: Duration,
+    ) -> (
+        SocketAddr,
+        Arc<Notify>,
+        oneshot::Sender<()>,
+        tokio::task::JoinHandle<std::io::Result<()>>,
+    ) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let started = Arc::new(Notify::new());
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let server = tokio::spawn(serve_until(
+            listener,
+            slow_router(started.clone(), delay),
+            async {
+                let _ = shutdown_rx.await;
+            },
+            drain_timeout,
+        ));
+        (addr, started, shutdown_tx, server)
+    }
+
+    #[tokio::test]
+    async fn in_flight_request_completes_after_shutdown_signal() {
+        let (addr, started, shutdown, server) =
+            start(Duration::from_millis(200), Duration::from_secs(5)).await;
+
+        let request = tokio::spawn(get_slow(addr));
+        started.notified().await;
+        shutdown.send(()).unwrap();
+
+        let response = request.await.unwrap().unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+        assert!(response.ends_with("done"), "{response}");
+        server.await.unwrap().unwrap();
+
+        // Nothing is accepted once drained.
+        assert!(get_slow(addr).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn drain_gives_up_after_timeout() {
+        let (addr, started, shutdown, server) =
+            start(Duration::from_secs(60), Duration::from_millis(50)).await;
+
+        let _request = tokio::spawn(get_slow(addr));
+        started.notified().await;
+        shutdown.send(()).unwrap();
+
+        tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .expect("server should stop once the drain timeout lapses")
+            .unwrap()
+            .unwrap();
+    }
 }
@@ -1,7 +1,8 @@
 //! Shared bootstrap helpers for observ.ing binaries.
 //!
 //! Independent, feature-gated pieces:
-//! - [`serve`] (feature `http`) — bind + serve an axum app, for HTTP services.
+//! - [`serve`] (feature `http`) — bind + serve an axum app, for HTTP services,
+//!   draining in-flight requests on SIGTERM/SIGINT.
 //! - [`db`] (feature `db`) — Postgres pool construction sized per workload.
 //! - [`job`] (feature `job`) — scaffolding for one-shot batch jobs (data
 //!   backfills, replays).
@@ -13,7 +14,7 @@
 #[cfg(feature = "http")]
 mod http;
 #[cfg(feature = "http")]
-pub use http::serve;
+pub use http::{serve, serve_until, shutdown_signal, DRAIN_TIMEOUT};
 
 #[cfg(feature = "db")]
 pub mod db;
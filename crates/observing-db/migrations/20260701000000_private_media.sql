@@ -0,0 +1,11 @@
+-- Blobs that may only be served through signed, expiring media URLs.
+--
+-- The media proxy serves any blob by (did, cid) unsigned, which is fine for
+-- public observation photos but makes private media guessable by CID. A row
+-- here makes the proxy demand a valid `exp`/`sig` pair for that blob.
+CREATE TABLE appview.private_media (
+    did TEXT NOT NULL,
+    cid TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (did, cid)
+);
@@ -0,0 +1,44 @@
+-- IUCN Red List category per GBIF taxon, for conservation-status filters.
+--
+-- GBIF reports the category alongside a name match but the `taxa` cache
+-- doesn't keep it. The taxonomy resolver (`resolve_taxa` and anything else
+-- going through `taxonomy_resolver::Resolver`) records the category with
+-- each upstream match, or `NE` when there is none, and the appview writes a
+-- row whenever a taxon lookup comes back with a status. The explore feed
+-- joins occurrences' consensus taxon (`community_ids.accepted_taxon_key`)
+-- against it instead of calling GBIF per row. Lives in the appview schema
+-- because the appview owns it; the resolver runs as `ingester_runtime`,
+-- which gets write access below.
+CREATE TABLE appview.taxon_conservation (
+    -- GBIF usageKey, matching `taxa.taxon_key`.
+    taxon_key BIGINT PRIMARY KEY,
+    -- Red List code: EX, EW, CR, EN, VU, NT, LC, DD or NE.
+    category TEXT NOT NULL,
+    source TEXT NOT NULL DEFAULT 'IUCN',
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Last time the status was looked up upstream, changed or not. Keys
+    -- GBIF doesn't know are recorded as NE too, so `resolve_taxa`'s
+    -- backfill skips every key until its check is due again.
+    checked_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX taxon_conservation_category_idx
+    ON appview.taxon_conservation (category);
+
+COMMENT ON TABLE appview.taxon_conservation IS
+    'IUCN Red List category per GBIF taxon. Written by the taxonomy resolver '
+    'on every upstream match (NE when GBIF reports none) and by the appview '
+    'on taxon detail lookups.';
+
+-- No-op on local/CI where the runtime role doesn't exist.
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_roles WHERE rolname = 'ingester_runtime') THEN
+        RAISE NOTICE 'ingester_runtime role not found; skipping grants (expected on local/CI)';
+        RETURN;
+    END IF;
+
+    EXECUTE 'GRANT SELECT, INSERT, UPDATE ON TABLE appview.taxon_conservation
+             TO ingester_runtime';
+END
+$$;
@@ -0,0 +1,21 @@
+-- Per-device login sessions.
+--
+-- `oauth_sessions` holds one set of OAuth tokens per DID, shared by every
+-- browser the user signed in from, so it can't say which devices are logged
+-- in. Each successful OAuth callback now also inserts a row here and hands
+-- its id to the browser as the `session_id` cookie; requests carrying that
+-- cookie are only authenticated while the row exists. Lives in the appview
+-- schema because the appview is the writer.
+CREATE TABLE appview.oauth_device_sessions (
+    -- Opaque bearer value stored in the `session_id` cookie.
+    id TEXT PRIMARY KEY DEFAULT gen_random_uuid()::text,
+    did TEXT NOT NULL,
+    -- `User-Agent` of the browser that completed the login.
+    user_agent TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Bumped at most every few minutes, not per request.
+    last_used_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX oauth_device_sessions_did_idx
+    ON appview.oauth_device_sessions (did);
@@ -0,0 +1,8 @@
+-- IANA timezone at each occurrence's coordinates (e.g. "Europe/London").
+--
+-- Observers often submit an `eventDate` without an offset; the zone lets
+-- local-time analyses (time of day, season) interpret it at the observation
+-- site. Resolved from the point by the record processor on every upsert,
+-- so it's NULL exactly when the location is. Existing rows fill in as
+-- they're re-ingested or backfilled.
+ALTER TABLE occurrences ADD COLUMN IF NOT EXISTS timezone TEXT;
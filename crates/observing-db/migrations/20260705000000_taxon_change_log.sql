@@ -0,0 +1,28 @@
+-- History of each occurrence's community ID.
+--
+-- `community_ids` only holds the current consensus; a refresh that flips
+-- an occurrence to a different taxon overwrites the old one. After every
+-- refresh the ingester appends a row here for each occurrence whose
+-- consensus differs from the last one logged, so the detail page can show
+-- how an identification evolved and who tipped it.
+--
+-- `old_name` is NULL for an occurrence's first consensus. The seed below
+-- records the current state as that baseline, so the first refresh after
+-- this migration only logs real changes.
+CREATE TABLE ingester.taxon_change_log (
+    id                BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    occurrence_uri    TEXT        NOT NULL,
+    old_name          TEXT,
+    new_name          TEXT        NOT NULL,
+    -- Newest identification on the occurrence when the change was seen.
+    triggered_by_uri  TEXT,
+    changed_at        TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX taxon_change_log_occurrence_idx
+    ON ingester.taxon_change_log (occurrence_uri, changed_at DESC, id DESC);
+
+INSERT INTO ingester.taxon_change_log (occurrence_uri, new_name)
+SELECT occurrence_uri, scientific_name
+FROM ingester.community_ids
+WHERE scientific_name IS NOT NULL;
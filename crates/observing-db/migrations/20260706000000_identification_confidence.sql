@@ -0,0 +1,10 @@
+-- How sure an identifier is of their identification: "tentative",
+-- "likely" or "certain", read from the record's app-specific `confidence`
+-- field. Nullable — records that omit it count as "likely" when the
+-- consensus is confidence-weighted (see `community_ids::ConsensusWeighting`).
+--
+-- Unlike the free-text column dropped in 20260227000000, this one is
+-- constrained to the defined scale.
+ALTER TABLE identifications
+    ADD COLUMN IF NOT EXISTS confidence TEXT
+    CHECK (confidence IN ('tentative', 'likely', 'certain'));
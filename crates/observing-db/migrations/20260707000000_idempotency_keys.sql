@@ -0,0 +1,18 @@
+-- Client-supplied `Idempotency-Key`s for record-creating POSTs, so a mobile
+-- retry of a create that already reached the PDS returns the first result
+-- instead of writing a duplicate record. Keys are scoped per DID and bound to
+-- a digest of the first request's body (`request_hash`); a retry whose
+-- digest differs is rejected with a 422 instead of getting the first record
+-- back as if it were the new one. `uri`/`cid` stay NULL while the first
+-- request is still in flight. Rows are short-lived: `idempotency::reserve`
+-- deletes a DID's expired keys before taking a new one. Lives in the appview
+-- schema because the appview is the writer.
+CREATE TABLE appview.idempotency_keys (
+    did TEXT NOT NULL,
+    key TEXT NOT NULL,
+    request_hash TEXT NOT NULL,
+    uri TEXT,
+    cid TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (did, key)
+);
@@ -0,0 +1,99 @@
+-- Soft-delete comments and identifications.
+--
+-- A firehose delete used to remove the row outright, which orphaned the
+-- replies under a deleted comment and left `taxon_change_log` crediting
+-- identifications that no longer resolve. Deletes now stamp `deleted_at`
+-- instead; reads skip tombstoned rows, and a comment thread keeps a
+-- deleted comment (without its body) while it still has replies.
+--
+-- An upsert of the same URI clears `deleted_at` again.
+ALTER TABLE comments ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+ALTER TABLE identifications ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+
+-- Whether community IDs weight votes by identification confidence. A
+-- deployment choice: the ingester writes it from `CONSENSUS_WEIGHTING` at
+-- startup (`identifications::set_consensus_weighting`).
+--
+--   * `unweighted` (default): one vote per identifier.
+--   * `confidence`: votes weighted as `IdentificationConfidence::weight`
+--     gives them (tentative 0.5, likely or none 1.0, certain 1.5).
+CREATE TABLE IF NOT EXISTS ingester.consensus_settings (
+    -- Pins the table to a single row.
+    id        BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    weighting TEXT NOT NULL DEFAULT 'unweighted'
+        CHECK (weighting IN ('unweighted', 'confidence'))
+);
+
+INSERT INTO ingester.consensus_settings DEFAULT VALUES
+ON CONFLICT (id) DO NOTHING;
+
+-- Rebuild `community_ids` so tombstoned identifications don't vote, and
+-- each identifier's latest identification votes with its weight under the
+-- configured weighting. Otherwise the same shape as
+-- 20260506000000_community_ids_via_taxa.sql: a user whose newest ID was
+-- deleted falls back to their previous one, as a hard delete did.
+--
+-- The taxon with the most weight wins; ties fall back to the identifier
+-- count, then the name. `id_count` is still the number of identifiers
+-- backing the winner, which is what the quality-grade agreement rules
+-- count. Under `unweighted` every vote weighs 1.0, so `vote_weight` equals
+-- `id_count`.
+--
+-- Research grade needs 2/3 of the vote weight behind the consensus
+-- (`quality::RESEARCH_REQUIREMENTS`). `total_weight` sums every current
+-- identifier's vote under the configured weighting, so
+-- `vote_weight / total_weight` is the share both the Rust rule and the
+-- feeds' grade filter read.
+DROP MATERIALIZED VIEW IF EXISTS ingester.community_ids;
+
+CREATE MATERIALIZED VIEW ingester.community_ids AS
+WITH latest_ids AS (
+    SELECT DISTINCT ON (did, subject_uri)
+        subject_uri, scientific_name, kingdom, accepted_taxon_key, confidence
+    FROM ingester.identifications
+    WHERE deleted_at IS NULL
+    ORDER BY did, subject_uri, date_identified DESC
+),
+votes AS (
+    SELECT
+        l.subject_uri,
+        l.scientific_name,
+        l.kingdom,
+        l.accepted_taxon_key,
+        COUNT(*) AS id_count,
+        SUM(CASE
+                WHEN s.weighting = 'unweighted' THEN 1.0
+                WHEN l.confidence = 'tentative' THEN 0.5
+                WHEN l.confidence = 'certain' THEN 1.5
+                ELSE 1.0
+            END) AS vote_weight
+    FROM latest_ids l
+    CROSS JOIN ingester.consensus_settings s
+    GROUP BY l.subject_uri, l.scientific_name, l.kingdom, l.accepted_taxon_key
+)
+SELECT DISTINCT ON (o.uri)
+    o.uri AS occurrence_uri,
+    v.scientific_name,
+    v.kingdom,
+    v.accepted_taxon_key,
+    v.id_count,
+    v.vote_weight,
+    SUM(v.vote_weight) OVER (PARTITION BY o.uri) AS total_weight
+FROM ingester.occurrences o
+JOIN votes v ON v.subject_uri = o.uri
+ORDER BY o.uri, v.vote_weight DESC, v.id_count DESC, v.scientific_name;
+
+CREATE UNIQUE INDEX community_ids_occurrence_uri_idx
+    ON ingester.community_ids (occurrence_uri);
+
+CREATE INDEX community_ids_accepted_taxon_key_idx
+    ON ingester.community_ids (accepted_taxon_key)
+    WHERE accepted_taxon_key IS NOT NULL;
+
+DO $$ BEGIN
+    IF EXISTS (SELECT 1 FROM pg_roles WHERE rolname = 'ingester_runtime') THEN
+        EXECUTE 'GRANT SELECT, UPDATE ON TABLE ingester.consensus_settings
+                 TO ingester_runtime';
+        EXECUTE 'ALTER MATERIALIZED VIEW ingester.community_ids OWNER TO ingester_runtime';
+    END IF;
+END $$;
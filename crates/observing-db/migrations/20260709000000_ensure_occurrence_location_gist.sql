@@ -0,0 +1,5 @@
+-- Radius and bounding-box queries on occurrences depend on a GiST index over
+-- `location`. The initial migration creates it, but a table rebuilt or
+-- restored outside migrations can come back without it; recreate it here so
+-- running migrations always repairs that. A no-op on healthy databases.
+CREATE INDEX IF NOT EXISTS occurrences_location_idx ON occurrences USING GIST (location);
@@ -0,0 +1,37 @@
+-- Occurrence records the appview has written to a user's PDS, so a write
+-- whose firehose commit never reaches the ingester (a PDS or relay problem)
+-- can be found instead of silently missing. The ingester stamps
+-- `confirmed_at` when it indexes or deletes the record; pending writes are
+-- the unconfirmed rows, and confirmed rows are pruned once they're a day
+-- old. One row per record, holding its latest appview write; a new appview
+-- write of the same record clears the stamp again, and deleting the record
+-- through the appview removes it. Lives in the appview schema because the
+-- appview is the writer.
+CREATE TABLE appview.occurrence_writes (
+    uri TEXT PRIMARY KEY,
+    did TEXT NOT NULL,
+    cid TEXT NOT NULL,
+    written_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    confirmed_at TIMESTAMPTZ
+);
+
+CREATE INDEX occurrence_writes_pending_idx
+    ON appview.occurrence_writes (did, written_at DESC)
+    WHERE confirmed_at IS NULL;
+
+CREATE INDEX occurrence_writes_confirmed_at_idx
+    ON appview.occurrence_writes (confirmed_at)
+    WHERE confirmed_at IS NOT NULL;
+
+-- No-op on local/CI where the runtime role doesn't exist.
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_roles WHERE rolname = 'ingester_runtime') THEN
+        RAISE NOTICE 'ingester_runtime role not found; skipping grants (expected on local/CI)';
+        RETURN;
+    END IF;
+
+    EXECUTE 'GRANT SELECT, UPDATE, DELETE ON TABLE appview.occurrence_writes
+             TO ingester_runtime';
+END
+$$;
@@ -0,0 +1,6 @@
+-- Taxon pages list the interactions a taxon takes part in, matching it on
+-- either subject's taxon name. Interactions were only indexed by occurrence.
+CREATE INDEX IF NOT EXISTS interactions_subject_a_taxon_name_idx
+    ON ingester.interactions (subject_a_taxon_name);
+CREATE INDEX IF NOT EXISTS interactions_subject_b_taxon_name_idx
+    ON ingester.interactions (subject_b_taxon_name);
@@ -1,10 +1,9 @@
-use crate::types::{CommentRow, UpsertCommentParams};
+use crate::live::{self, RecordChange};
+use crate::types::{CommentRow, CommentThreadRow, UpsertCommentParams};
+use sqlx::PgPool;
 
-/// Upsert a comment record
-pub async fn upsert(
-    executor: impl sqlx::PgExecutor<'_>,
-    p: &UpsertCommentParams,
-) -> Result<(), sqlx::Error> {
+/// Upsert a comment record, then announce it on [`live::COMMENT_CHANNEL`].
+pub async fn upsert(pool: &PgPool, p: &UpsertCommentParams) -> Result<(), sqlx::Error> {
     sqlx::query!(
         r#"
         INSERT INTO comments (
@@ -20,7 +19,8 @@ pub async fn upsert(
             reply_to_uri = EXCLUDED.reply_to_uri,
             reply_to_cid = EXCLUDED.reply_to_cid,
             created_at = EXCLUDED.created_at,
-            indexed_at = NOW()
+            indexed_at = NOW(),
+            deleted_at = NULL
         "#,
         p.uri,
         p.cid,
@@ -32,16 +32,28 @@ pub async fn upsert(
         p.reply_to_cid as _,
         p.created_at,
     )
-    .execute(executor)
+    .execute(pool)
     .await?;
+
+    let change = RecordChange::upsert(&p.uri, &p.did, &p.subject_uri);
+    live::announce(pool, live::COMMENT_CHANNEL, &change).await;
     Ok(())
 }
 
-/// Delete a comment
-pub async fn delete(executor: impl sqlx::PgExecutor<'_>, uri: &str) -> Result<(), sqlx::Error> {
-    sqlx::query!("DELETE FROM comments WHERE uri = $1", uri)
-        .execute(executor)
-        .await?;
+/// Soft-delete a comment, then announce it on [`live::COMMENT_CHANNEL`].
+/// The row stays so replies keep their place in the thread (see
+/// [`get_thread`]), but its body is cleared in the same update.
+pub async fn delete(pool: &PgPool, uri: &str) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE comments SET deleted_at = NOW(), body = '' WHERE uri = $1 AND deleted_at IS NULL",
+        uri
+    )
+    .execute(pool)
+    .await?;
+
+    if let Some(change) = RecordChange::delete(uri) {
+        live::announce(pool, live::COMMENT_CHANNEL, &change).await;
+    }
     Ok(())
 }
 
@@ -58,7 +70,7 @@ pub async fn get_for_occurrence(
             reply_to_uri, reply_to_cid,
             created_at as "created_at: chrono::DateTime<chrono::Utc>"
         FROM comments
-        WHERE subject_uri = $1
+        WHERE subject_uri = $1 AND deleted_at IS NULL
         ORDER BY created_at ASC
         "#,
         occurrence_uri,
@@ -66,3 +78,50 @@ pub async fn get_for_occurrence(
     .fetch_all(executor)
     .await
 }
+
+/// Get all comments for an occurrence with their reply parents resolved,
+/// oldest first. A reply whose parent is gone (or belongs to another
+/// occurrence) comes back with no `parent_uri`, so callers can show it at
+/// the top level instead of losing it.
+///
+/// Soft-deleted comments are included, flagged `deleted` and with their
+/// body blanked, so a thread keeps its shape; callers drop the ones left
+/// with no replies.
+pub async fn get_thread(
+    executor: impl sqlx::PgExecutor<'_>,
+    occurrence_uri: &str,
+) -> Result<Vec<CommentThreadRow>, sqlx::Error> {
+    sqlx::query_as::<_, CommentThreadRow>(THREAD_QUERY)
+        .bind(occurrence_uri)
+        .fetch_all(executor)
+        .await
+}
+
+const THREAD_QUERY: &str = r#"
+    SELECT
+        c.uri, c.cid, c.did, c.subject_uri, c.subject_cid,
+        CASE WHEN c.deleted_at IS NULL THEN c.body ELSE '' END AS body,
+        c.reply_to_uri, c.reply_to_cid, c.created_at,
+        parent.uri AS parent_uri,
+        c.deleted_at IS NOT NULL AS deleted
+    FROM comments c
+    LEFT JOIN comments parent
+        ON parent.uri = c.reply_to_uri
+        AND parent.subject_uri = c.subject_uri
+    WHERE c.subject_uri = $1
+    ORDER BY c.created_at ASC, c.uri ASC
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A tombstoned parent still joins, so its replies stay nested under
+    /// it, but its text never leaves the database.
+    #[test]
+    fn thread_keeps_deleted_parents_without_their_body() {
+        assert!(THREAD_QUERY.contains("CASE WHEN c.deleted_at IS NULL THEN c.body ELSE '' END"));
+        assert!(THREAD_QUERY.contains("c.deleted_at IS NOT NULL AS deleted"));
+        assert!(!THREAD_QUERY.contains("parent.deleted_at"));
+    }
+}
@@ -1,4 +1,5 @@
-use crate::types::IdentificationRow;
+use crate::quality;
+use crate::types::{ConsensusVotes, IdentificationConfidence, IdentificationRow};
 
 /// Result of community ID calculation
 #[derive(Debug, Clone)]
@@ -7,20 +8,67 @@ pub struct CommunityIdResult {
     pub kingdom: Option<String>,
     pub taxon_rank: Option<String>,
     pub identification_count: usize,
+    /// Share of the vote weight behind the winning taxon.
     pub confidence: f64,
+    /// The votes meet [`quality::RESEARCH_REQUIREMENTS`].
     pub is_research_grade: bool,
 }
 
-const RESEARCH_GRADE_THRESHOLD: f64 = 2.0 / 3.0;
-const MIN_IDS_FOR_RESEARCH_GRADE: usize = 2;
+/// How much each identification's vote counts toward the consensus.
+///
+/// The ingester stores the deployment's choice for the `community_ids`
+/// matview; see [`crate::identifications::set_consensus_weighting`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConsensusWeighting {
+    /// One vote per identifier.
+    #[default]
+    Unweighted,
+    /// Votes weighted by [`IdentificationConfidence::weight`], so a
+    /// "certain" identification outweighs a "tentative" one.
+    Confidence,
+}
+
+impl ConsensusWeighting {
+    /// The token stored in `consensus_settings.weighting`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Unweighted => "unweighted",
+            Self::Confidence => "confidence",
+        }
+    }
+}
+
+impl std::str::FromStr for ConsensusWeighting {
+    type Err = String;
+
+    fn from_str(token: &str) -> Result<Self, Self::Err> {
+        match token {
+            "unweighted" => Ok(Self::Unweighted),
+            "confidence" => Ok(Self::Confidence),
+            other => Err(format!("unknown consensus weighting: {other}")),
+        }
+    }
+}
 
 /// Calculate the community ID from a set of identifications.
 ///
 /// Implements iNaturalist-style consensus:
 /// - Deduplicates by user (keeps most recent identification per user)
 /// - Groups by taxon name + kingdom (avoids cross-kingdom homonyms)
-/// - 2/3 majority required for research grade
+/// - Research grade by [`quality::RESEARCH_REQUIREMENTS`], the rule the
+///   feeds' grade filter applies
 pub fn calculate(identifications: &[IdentificationRow]) -> Option<CommunityIdResult> {
+    calculate_with(identifications, ConsensusWeighting::Unweighted)
+}
+
+/// [`calculate`] with a choice of vote weighting, as the `community_ids`
+/// matview applies it. Under [`ConsensusWeighting::Confidence`] the winner
+/// and the 2/3 majority are both taken over summed weights rather than
+/// identifier counts.
+pub fn calculate_with(
+    identifications: &[IdentificationRow],
+    weighting: ConsensusWeighting,
+) -> Option<CommunityIdResult> {
     if identifications.is_empty() {
         return None;
     }
@@ -29,22 +77,29 @@ pub fn calculate(identifications: &[IdentificationRow]) -> Option<CommunityIdRes
     let deduplicated = deduplicate_by_user(identifications);
 
     // Group by taxon
-    let taxon_counts = group_by_taxon(&deduplicated);
+    let taxon_counts = group_by_taxon(&deduplicated, weighting);
 
     // Find winner
     let winner = find_winner(&taxon_counts)?;
 
-    let confidence = winner.count as f64 / deduplicated.len() as f64;
-    let is_research_grade =
-        deduplicated.len() >= MIN_IDS_FOR_RESEARCH_GRADE && confidence >= RESEARCH_GRADE_THRESHOLD;
+    let total_weight: f64 = taxon_counts.iter().map(|t| t.weight).sum();
+    let votes = ConsensusVotes {
+        agreeing: winner.identifiers,
+        agreeing_weight: winner.weight,
+        total_weight,
+        at_species_rank: winner
+            .taxon_rank
+            .as_deref()
+            .is_some_and(TaxonomicHierarchy::is_species_or_below),
+    };
 
     Some(CommunityIdResult {
         scientific_name: winner.scientific_name.clone(),
         kingdom: winner.kingdom.clone(),
         taxon_rank: winner.taxon_rank.clone(),
         identification_count: deduplicated.len(),
-        confidence,
-        is_research_grade,
+        confidence: winner.weight / total_weight,
+        is_research_grade: quality::is_research_consensus(&votes),
     })
 }
 
@@ -52,7 +107,17 @@ struct TaxonCount {
     scientific_name: String,
     kingdom: Option<String>,
     taxon_rank: Option<String>,
-    count: usize,
+    identifiers: i64,
+    weight: f64,
+}
+
+fn vote_weight(id: &IdentificationRow, weighting: ConsensusWeighting) -> f64 {
+    match weighting {
+        ConsensusWeighting::Unweighted => 1.0,
+        ConsensusWeighting::Confidence => {
+            IdentificationConfidence::weight(id.confidence.as_deref().and_then(|c| c.parse().ok()))
+        }
+    }
 }
 
 /// Keep only each user's most recent identification
@@ -70,8 +135,11 @@ fn deduplicate_by_user(identifications: &[IdentificationRow]) -> Vec<&Identifica
     latest_by_user.into_values().collect()
 }
 
-/// Group identifications by scientific name + kingdom
-fn group_by_taxon(identifications: &[&IdentificationRow]) -> Vec<TaxonCount> {
+/// Group identifications by scientific name + kingdom, summing vote weights
+fn group_by_taxon(
+    identifications: &[&IdentificationRow],
+    weighting: ConsensusWeighting,
+) -> Vec<TaxonCount> {
     let mut counts: std::collections::HashMap<String, TaxonCount> =
         std::collections::HashMap::new();
 
@@ -83,37 +151,24 @@ fn group_by_taxon(identifications: &[&IdentificationRow]) -> Vec<TaxonCount> {
             scientific_name: id.scientific_name.clone(),
             kingdom: id.kingdom.clone(),
             taxon_rank: id.taxon_rank.clone(),
-            count: 0,
+            identifiers: 0,
+            weight: 0.0,
         });
-        entry.count += 1;
+        entry.identifiers += 1;
+        entry.weight += vote_weight(id, weighting);
     }
 
     counts.into_values().collect()
 }
 
-/// Find the winning taxon (most votes; if ties, first sorted wins)
+/// Find the winning taxon (most vote weight, then most identifiers, as the
+/// matview orders them; remaining ties pick any)
 fn find_winner(taxon_counts: &[TaxonCount]) -> Option<&TaxonCount> {
-    if taxon_counts.is_empty() {
-        return None;
-    }
-    taxon_counts.iter().max_by_key(|t| t.count)
-}
-
-/// Quality grade for an occurrence
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum QualityGrade {
-    Research,
-    NeedsId,
-    Casual,
-}
-
-/// Determine quality grade from a community ID result
-pub fn quality_grade(result: &Option<CommunityIdResult>) -> QualityGrade {
-    match result {
-        None => QualityGrade::Casual,
-        Some(r) if r.is_research_grade => QualityGrade::Research,
-        Some(_) => QualityGrade::NeedsId,
-    }
+    taxon_counts.iter().max_by(|a, b| {
+        a.weight
+            .total_cmp(&b.weight)
+            .then(a.identifiers.cmp(&b.identifiers))
+    })
 }
 
 /// Taxonomic rank ordering utilities
@@ -140,6 +195,16 @@ impl TaxonomicHierarchy {
             .unwrap_or(0)
     }
 
+    /// Whether `rank` is one of the ranks this hierarchy orders
+    pub fn is_known_rank(rank: &str) -> bool {
+        Self::RANK_ORDER.contains(&rank.to_lowercase().as_str())
+    }
+
+    /// Whether `rank` is species or a rank below it
+    pub fn is_species_or_below(rank: &str) -> bool {
+        Self::is_known_rank(rank) && Self::rank_level(rank) <= Self::rank_level("species")
+    }
+
     /// Check if rank1 is more specific than rank2
     pub fn is_more_specific(rank1: &str, rank2: &str) -> bool {
         Self::rank_level(rank1) < Self::rank_level(rank2)
@@ -161,6 +226,7 @@ mod tests {
             scientific_name: name.to_string(),
             taxon_rank: Some("species".to_string()),
             identification_qualifier: None,
+            confidence: None,
             taxon_id: None,
             identification_verification_status: None,
             type_status: None,
@@ -279,29 +345,131 @@ mod tests {
         assert!(!result.is_research_grade);
     }
 
+    fn with_confidence(mut id: IdentificationRow, confidence: &str) -> IdentificationRow {
+        id.confidence = Some(confidence.to_string());
+        id
+    }
+
     #[test]
-    fn test_quality_grade() {
-        assert_eq!(quality_grade(&None), QualityGrade::Casual);
-
-        let rg = Some(CommunityIdResult {
-            scientific_name: "Test".to_string(),
-            kingdom: None,
-            taxon_rank: None,
-            identification_count: 3,
-            confidence: 1.0,
-            is_research_grade: true,
-        });
-        assert_eq!(quality_grade(&rg), QualityGrade::Research);
-
-        let needs_id = Some(CommunityIdResult {
-            scientific_name: "Test".to_string(),
-            kingdom: None,
-            taxon_rank: None,
-            identification_count: 1,
-            confidence: 1.0,
-            is_research_grade: false,
-        });
-        assert_eq!(quality_grade(&needs_id), QualityGrade::NeedsId);
+    fn test_confidence_weighting_changes_winner() {
+        let ids = vec![
+            with_confidence(
+                make_id(
+                    "user1",
+                    "Quercus alba",
+                    Some("Plantae"),
+                    "2024-01-01 12:00:00",
+                ),
+                "certain",
+            ),
+            with_confidence(
+                make_id(
+                    "user2",
+                    "Quercus rubra",
+                    Some("Plantae"),
+                    "2024-01-02 12:00:00",
+                ),
+                "tentative",
+            ),
+            with_confidence(
+                make_id(
+                    "user3",
+                    "Quercus rubra",
+                    Some("Plantae"),
+                    "2024-01-03 12:00:00",
+                ),
+                "tentative",
+            ),
+        ];
+
+        // Two heads beat one...
+        let unweighted = calculate(&ids).unwrap();
+        assert_eq!(unweighted.scientific_name, "Quercus rubra");
+        assert!(unweighted.is_research_grade);
+
+        // ...unless both are tentative and the one is certain: 1.5 vs 0.5 + 0.5.
+        let weighted = calculate_with(&ids, ConsensusWeighting::Confidence).unwrap();
+        assert_eq!(weighted.scientific_name, "Quercus alba");
+        assert_eq!(weighted.identification_count, 3);
+        assert_eq!(weighted.confidence, 0.6);
+        assert!(!weighted.is_research_grade);
+    }
+
+    #[test]
+    fn test_confidence_weighting_without_confidences_matches_unweighted() {
+        let ids = vec![
+            make_id(
+                "user1",
+                "Quercus alba",
+                Some("Plantae"),
+                "2024-01-01 12:00:00",
+            ),
+            make_id(
+                "user2",
+                "Quercus rubra",
+                Some("Plantae"),
+                "2024-01-02 12:00:00",
+            ),
+        ];
+        let weighted = calculate_with(&ids, ConsensusWeighting::Confidence).unwrap();
+        assert_eq!(weighted.confidence, calculate(&ids).unwrap().confidence);
+    }
+
+    #[test]
+    fn test_missing_confidence_is_one_full_vote() {
+        let ids = vec![
+            make_id(
+                "user1",
+                "Quercus alba",
+                Some("Plantae"),
+                "2024-01-01 12:00:00",
+            ),
+            with_confidence(
+                make_id(
+                    "user2",
+                    "Quercus alba",
+                    Some("Plantae"),
+                    "2024-01-02 12:00:00",
+                ),
+                "likely",
+            ),
+            make_id(
+                "user3",
+                "Quercus rubra",
+                Some("Plantae"),
+                "2024-01-03 12:00:00",
+            ),
+        ];
+        let result = calculate_with(&ids, ConsensusWeighting::Confidence).unwrap();
+        assert_eq!(result.scientific_name, "Quercus alba");
+        assert_eq!(result.confidence, 2.0 / 3.0);
+        assert!(result.is_research_grade);
+    }
+
+    #[test]
+    fn test_consensus_weighting_tokens_round_trip() {
+        for weighting in [
+            ConsensusWeighting::Unweighted,
+            ConsensusWeighting::Confidence,
+        ] {
+            assert_eq!(weighting.as_str().parse(), Ok(weighting));
+        }
+        assert!("weighted".parse::<ConsensusWeighting>().is_err());
+    }
+
+    #[test]
+    fn test_research_grade_needs_a_species_consensus() {
+        let ids: Vec<_> = ["user1", "user2"]
+            .into_iter()
+            .map(|did| {
+                let mut id = make_id(did, "Quercus", Some("Plantae"), "2024-01-01 12:00:00");
+                id.taxon_rank = Some("genus".to_string());
+                id
+            })
+            .collect();
+        let result = calculate(&ids).unwrap();
+        assert_eq!(result.confidence, 1.0);
+        assert!(!result.is_research_grade);
     }
 
     #[test]
@@ -0,0 +1,191 @@
+//! Opaque keyset-pagination cursors for the occurrence feeds and an
+//! occurrence's identification list.
+//!
+//! Feeds order by `(created_at DESC, uri DESC)` and resume after the last
+//! row of the previous page. The cursor carries both halves of that sort key
+//! as URL-safe base64 over a small JSON object, so clients treat it as an
+//! opaque token and the layout can grow (a new sort key, say) without
+//! breaking the wire format. Anything that doesn't decode is rejected with a
+//! [`CursorError`] instead of being passed to SQL and silently paging from
+//! the wrong place.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Position just past the last row of a feed page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeedCursor {
+    #[serde(rename = "t")]
+    pub created_at: DateTime<Utc>,
+    /// Tiebreaker for rows sharing `created_at`.
+    #[serde(rename = "u")]
+    pub uri: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CursorError {
+    /// Not URL-safe base64.
+    Encoding,
+    /// Decoded, but not a cursor this server issued.
+    Payload(String),
+}
+
+impl std::fmt::Display for CursorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Encoding => write!(f, "malformed cursor: not base64url"),
+            Self::Payload(e) => write!(f, "malformed cursor: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for CursorError {}
+
+fn encode<T: Serialize>(cursor: &T) -> String {
+    let json = serde_json::to_vec(cursor).expect("cursor fields always serialize");
+    URL_SAFE_NO_PAD.encode(json)
+}
+
+fn decode<T: for<'de> Deserialize<'de>>(token: &str) -> Result<T, CursorError> {
+    let json = URL_SAFE_NO_PAD
+        .decode(token)
+        .map_err(|_| CursorError::Encoding)?;
+    serde_json::from_slice(&json).map_err(|e| CursorError::Payload(e.to_string()))
+}
+
+impl FeedCursor {
+    pub fn new(created_at: DateTime<Utc>, uri: impl Into<String>) -> Self {
+        Self {
+            created_at,
+            uri: uri.into(),
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        encode(self)
+    }
+
+    pub fn decode(token: &str) -> Result<Self, CursorError> {
+        decode(token)
+    }
+}
+
+/// Position just past the last row of an occurrence's identification list,
+/// which orders by `(date_identified DESC, uri DESC)`, behind
+/// `agrees_with_community DESC` when sorted by agreement.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdentificationCursor {
+    /// Set only for the agreement sort.
+    #[serde(rename = "a", default, skip_serializing_if = "Option::is_none")]
+    pub agrees_with_community: Option<bool>,
+    #[serde(rename = "t")]
+    pub date_identified: DateTime<Utc>,
+    /// Tiebreaker for rows sharing `date_identified`.
+    #[serde(rename = "u")]
+    pub uri: String,
+}
+
+impl IdentificationCursor {
+    pub fn encode(&self) -> String {
+        encode(self)
+    }
+
+    pub fn decode(token: &str) -> Result<Self, CursorError> {
+        decode(token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn cursor() -> FeedCursor {
+        FeedCursor::new(
+            Utc.with_ymd_and_hms(2026, 6, 2, 21, 13, 49).unwrap()
+                + chrono::Duration::microseconds(123_456),
+            "at://did:plc:x/bio.lexicons.temp.v0-1.occurrence/3k2",
+        )
+    }
+
+    #[test]
+    fn round_trips_through_an_opaque_token() {
+        let token = cursor().encode();
+        // Safe to drop into a query string unescaped.
+        assert!(
+            token
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+            "{token}"
+        );
+        // Sub-second precision survives, so rows a microsecond apart still
+        // paginate correctly.
+        assert_eq!(FeedCursor::decode(&token), Ok(cursor()));
+    }
+
+    #[test]
+    fn malformed_tokens_are_rejected() {
+        // The old ad-hoc `<created_at>|<uri>` form.
+        assert_eq!(
+            FeedCursor::decode("2026-06-02T21:13:49Z|at://did:plc:x/coll/rkey"),
+            Err(CursorError::Encoding)
+        );
+        // Valid base64 around the wrong payload.
+        let not_json = URL_SAFE_NO_PAD.encode("hello");
+        assert!(matches!(
+            FeedCursor::decode(&not_json),
+            Err(CursorError::Payload(_))
+        ));
+        let missing_uri = URL_SAFE_NO_PAD.encode(r#"{"t":"2026-06-02T21:13:49Z"}"#);
+        assert!(matches!(
+            FeedCursor::decode(&missing_uri),
+            Err(CursorError::Payload(_))
+        ));
+        let bad_time = URL_SAFE_NO_PAD.encode(r#"{"t":"yesterday","u":"at://x"}"#);
+        assert!(matches!(
+            FeedCursor::decode(&bad_time),
+            Err(CursorError::Payload(_))
+        ));
+        assert!(FeedCursor::decode("").is_err());
+    }
+
+    #[test]
+    fn identification_cursor_round_trips_with_and_without_agreement() {
+        let recent = IdentificationCursor {
+            agrees_with_community: None,
+            date_identified: cursor().created_at,
+            uri: "at://did:plc:y/bio.lexicons.temp.v0-1.identification/3k2".into(),
+        };
+        let agreement = IdentificationCursor {
+            agrees_with_community: Some(true),
+            ..recent.clone()
+        };
+        assert_eq!(IdentificationCursor::decode(&recent.encode()), Ok(recent));
+        assert_eq!(
+            IdentificationCursor::decode(&agreement.encode()),
+            Ok(agreement)
+        );
+    }
+
+    #[test]
+    fn malformed_identification_cursors_are_rejected() {
+        // The old ad-hoc `<date>|<uri>` form, which reached SQL unchecked.
+        assert_eq!(
+            IdentificationCursor::decode("2026-06-02T21:13:49+00:00|at://did:plc:y/coll/rkey"),
+            Err(CursorError::Encoding)
+        );
+        let bad_time = URL_SAFE_NO_PAD.encode(r#"{"t":"not-a-date","u":"at://x"}"#);
+        assert!(matches!(
+            IdentificationCursor::decode(&bad_time),
+            Err(CursorError::Payload(_))
+        ));
+        let bad_agreement =
+            URL_SAFE_NO_PAD.encode(r#"{"a":"1","t":"2026-06-02T21:13:49Z","u":"at://x"}"#);
+        assert!(matches!(
+            IdentificationCursor::decode(&bad_agreement),
+            Err(CursorError::Payload(_))
+        ));
+    }
+}
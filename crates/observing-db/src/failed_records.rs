@@ -5,16 +5,22 @@
 //! tap-ingester's main loop: a row exists ⇔ at least one ack-and-drop
 //! happened for that URI. A subsequent successful upsert for the same
 //! URI does NOT auto-clear the ledger row — entries can become stale
-//! once redelivery succeeds, and an explicit cleanup pass (or admin
-//! tool) prunes them. That keeps the hot write path one query instead
-//! of two.
+//! once redelivery succeeds, and a replay prunes them via [`superseded`].
+//! That keeps the hot write path one query instead of two. Deletes do
+//! clear the row, since a hard-deleted record leaves nothing behind for
+//! [`superseded`] to compare against.
 //!
-//! `record_json` is stored as JSONB so a future replay job can iterate
-//! the ledger and call the appropriate `processing::*_from_json` +
-//! `upsert` directly without going back to the firehose.
+//! `record_json` is stored as JSONB so a replay can call the appropriate
+//! `processing::*_from_json` + `upsert` directly without going back to
+//! the firehose. Two things replay it: tap-ingester's periodic task
+//! retries transient failures with backoff ([`list_retryable`], then
+//! [`clear`] or another [`record`]) until a row runs out of attempts, and
+//! the task runner's one-shot `replay-failed-records` job sweeps whatever
+//! is left once a fix for the underlying failure has shipped.
 
 use chrono::{DateTime, Utc};
 use serde_json::Value;
+use sqlx::FromRow;
 
 /// Inputs for [`record`]. Borrowed so callers don't have to clone the
 /// firehose payload just to log a failure.
@@ -115,3 +121,128 @@ pub async fn count_total(executor: impl sqlx::PgExecutor<'_>) -> Result<i64, sql
         .await?;
     Ok(row.count)
 }
+
+/// A ledger row with everything needed to replay it.
+#[derive(Debug, Clone, FromRow)]
+pub struct RetryableRecord {
+    pub uri: String,
+    pub collection: String,
+    pub did: String,
+    pub cid: Option<String>,
+    pub action: String,
+    pub record_json: Option<Value>,
+    pub attempts: i32,
+    pub last_attempt_at: DateTime<Utc>,
+}
+
+/// Exponential backoff between replay attempts: `base` after the first
+/// failure, doubling with each further attempt, capped at `max`.
+#[derive(Debug, Clone, Copy)]
+pub struct Backoff {
+    pub base: chrono::Duration,
+    pub max: chrono::Duration,
+}
+
+// The backoff is evaluated here rather than by the caller so `LIMIT` only
+// counts rows that are actually due; filtering afterwards let a batch of
+// not-yet-due rows starve the due ones behind them.
+const RETRYABLE_QUERY: &str = r#"
+    SELECT uri, collection, did, cid, action, record_json, attempts, last_attempt_at
+    FROM ingester.failed_records
+    WHERE attempts < $1
+    AND last_attempt_at
+        + LEAST($2 * power(2, LEAST(GREATEST(attempts - 1, 0), 30)), $3) * interval '1 second'
+        <= NOW()
+    ORDER BY last_attempt_at ASC
+    LIMIT $4
+"#;
+
+/// Failures with fewer than `max_attempts` attempts whose `backoff` has
+/// elapsed, least recently tried first.
+pub async fn list_retryable(
+    executor: impl sqlx::PgExecutor<'_>,
+    max_attempts: i32,
+    backoff: Backoff,
+    limit: i64,
+) -> Result<Vec<RetryableRecord>, sqlx::Error> {
+    sqlx::query_as(RETRYABLE_QUERY)
+        .bind(max_attempts)
+        .bind(backoff.base.as_seconds_f64())
+        .bind(backoff.max.as_seconds_f64())
+        .bind(limit)
+        .fetch_all(executor)
+        .await
+}
+
+// A ledger row is stale once anything has been indexed for its URI after
+// the failure was last recorded: replaying it would overwrite the newer
+// commit, or undo a newer delete of a soft-deleted record.
+const SUPERSEDED_QUERY: &str = r#"
+    SELECT EXISTS (
+        SELECT 1 FROM occurrences WHERE uri = $1 AND indexed_at > $2
+        UNION ALL
+        SELECT 1 FROM identifications
+        WHERE uri = $1 AND (indexed_at > $2 OR deleted_at > $2)
+        UNION ALL
+        SELECT 1 FROM comments
+        WHERE uri = $1 AND (indexed_at > $2 OR deleted_at > $2)
+        UNION ALL
+        SELECT 1 FROM interactions WHERE uri = $1 AND indexed_at > $2
+        UNION ALL
+        SELECT 1 FROM likes WHERE uri = $1 AND indexed_at > $2::timestamp
+    )
+"#;
+
+/// Whether a newer commit or delete for `row`'s URI has been indexed since
+/// the failure, in which case the row should be cleared, not replayed.
+pub async fn superseded(
+    executor: impl sqlx::PgExecutor<'_>,
+    row: &RetryableRecord,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar(SUPERSEDED_QUERY)
+        .bind(&row.uri)
+        .bind(row.last_attempt_at)
+        .fetch_one(executor)
+        .await
+}
+
+/// Remove a URI from the ledger once it has been persisted.
+pub async fn clear(executor: impl sqlx::PgExecutor<'_>, uri: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM ingester.failed_records WHERE uri = $1")
+        .bind(uri)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retryable_query_filters_on_backoff_before_limiting() {
+        let backoff = RETRYABLE_QUERY.find("power(2,").unwrap();
+        let limit = RETRYABLE_QUERY.find("LIMIT $4").unwrap();
+        assert!(backoff < limit);
+        assert!(RETRYABLE_QUERY.contains("WHERE attempts < $1"));
+        assert!(RETRYABLE_QUERY.contains("<= NOW()"));
+    }
+
+    #[test]
+    fn superseded_query_checks_every_replayed_collection() {
+        for table in [
+            "occurrences",
+            "identifications",
+            "comments",
+            "interactions",
+            "likes",
+        ] {
+            assert!(
+                SUPERSEDED_QUERY.contains(&format!("FROM {table}")),
+                "{table} is not checked"
+            );
+        }
+        assert_eq!(SUPERSEDED_QUERY.matches("uri = $1").count(), 5);
+        assert_eq!(SUPERSEDED_QUERY.matches("deleted_at > $2").count(), 2);
+    }
+}
@@ -1,8 +1,14 @@
+use crate::cursor::FeedCursor;
 use crate::occurrence_columns;
-use crate::quality::{QualityCriterion, IMPRECISE_UNCERTAINTY_THRESHOLD_M};
+use crate::quality::{
+    GradeRequirement, QualityCriterion, QualityGrade, IMPRECISE_UNCERTAINTY_THRESHOLD_M,
+    RESEARCH_REQUIREMENTS, VERIFIABLE_REQUIREMENTS,
+};
 use crate::types::{
-    ExploreFeedOptions, HomeFeedOptions, IdentificationRow, OccurrenceRow, ProfileCounts,
-    ProfileFeedOptions, ProfileFeedResult, ProfileFeedType, TaxonOccurrenceOptions,
+    ActivityOptions, ActivityRow, BoundingBox, ExploreFeedOptions, HomeFeedOptions,
+    IdentificationRow, LeaderboardMetric, LeaderboardRow, LocalTaxonMatchRow, NearbyArea,
+    NeedsIdFeedOptions, OccurrenceRow, ProfileCounts, ProfileFeedOptions, ProfileFeedResult,
+    ProfileFeedType, TaxonImageRow, TaxonLocalStats, TaxonOccurrenceOptions, TrendingTaxonRow,
 };
 use sqlx::{PgPool, Postgres, QueryBuilder};
 
@@ -10,27 +16,16 @@ use sqlx::{PgPool, Postgres, QueryBuilder};
 ///
 /// Feeds order by `(created_at DESC, uri DESC)`. `created_at` is not unique, so
 /// a timestamp-only cursor (`created_at < $ts`) skips every other row sharing
-/// the boundary timestamp and ties sort arbitrarily between queries. The cursor
-/// encodes both halves as `"<created_at>|<uri>"` (see
-/// `OccurrenceResponse::feed_cursor` in the appview) and we compare the row
-/// tuple against it. Any caller that uses this MUST order by
-/// `created_at DESC, uri DESC` so the predicate and sort agree. Legacy
-/// single-value cursors (no `|`) fall back to the timestamp-only predicate.
-fn push_keyset_cursor(qb: &mut QueryBuilder<Postgres>, cursor: &str) {
-    match cursor.split_once('|') {
-        Some((created_at, uri)) => {
-            qb.push(" AND (created_at, uri) < (");
-            qb.push_bind(created_at.to_string());
-            qb.push("::timestamptz, ");
-            qb.push_bind(uri.to_string());
-            qb.push(")");
-        }
-        None => {
-            qb.push(" AND created_at < ");
-            qb.push_bind(cursor.to_string());
-            qb.push("::timestamptz");
-        }
-    }
+/// the boundary timestamp and ties sort arbitrarily between queries. The
+/// [`FeedCursor`] carries both halves and we compare the row tuple against
+/// it. Any caller that uses this MUST order by `created_at DESC, uri DESC` so
+/// the predicate and sort agree.
+pub(crate) fn push_keyset_cursor(qb: &mut QueryBuilder<Postgres>, cursor: &FeedCursor) {
+    qb.push(" AND (created_at, uri) < (");
+    qb.push_bind(cursor.created_at);
+    qb.push("::timestamptz, ");
+    qb.push_bind(cursor.uri.clone());
+    qb.push(")");
 }
 
 /// Get the explore feed with optional filters
@@ -75,7 +70,15 @@ pub async fn get_explore_feed(
         push_quality_filter(&mut qb, &options.quality.criteria);
     }
 
-    if let Some(cursor) = options.cursor.as_deref() {
+    if let Some(grade) = options.quality_grade {
+        push_grade_filter(&mut qb, grade);
+    }
+
+    if !options.conservation_categories.is_empty() {
+        push_conservation_filter(&mut qb, &options.conservation_categories);
+    }
+
+    if let Some(cursor) = options.cursor.as_ref() {
         push_keyset_cursor(&mut qb, cursor);
     }
 
@@ -88,6 +91,67 @@ pub async fn get_explore_feed(
         .await
 }
 
+/// Occurrences nobody but their observer has identified, newest first.
+///
+/// The observer's own identification (created alongside the occurrence when
+/// they named it) doesn't count: the queue is for observations still
+/// waiting on a second opinion.
+pub async fn get_needs_id_feed(
+    executor: impl sqlx::PgExecutor<'_>,
+    options: &NeedsIdFeedOptions,
+    hidden_dids: &[String],
+) -> Result<Vec<OccurrenceRow>, sqlx::Error> {
+    let mut qb = needs_id_feed_query(options, hidden_dids);
+    qb.build_query_as::<OccurrenceRow>()
+        .fetch_all(executor)
+        .await
+}
+
+fn needs_id_feed_query(
+    options: &NeedsIdFeedOptions,
+    hidden_dids: &[String],
+) -> QueryBuilder<Postgres> {
+    let mut qb = QueryBuilder::<Postgres>::new(concat!(
+        "SELECT ",
+        occurrence_columns!(),
+        " FROM occurrences WHERE NOT EXISTS (SELECT 1 FROM identifications i",
+        " WHERE i.subject_uri = occurrences.uri AND i.did != occurrences.did",
+        " AND i.deleted_at IS NULL)"
+    ));
+
+    if !hidden_dids.is_empty() {
+        qb.push(" AND did != ALL(");
+        qb.push_bind(hidden_dids.to_vec());
+        qb.push(")");
+    }
+
+    // An occurrence with no identification at all has no consensus, so also
+    // accept the kingdom the observer recorded.
+    if let Some(kingdom) = options.kingdom.as_deref() {
+        qb.push(" AND (kingdom = ");
+        qb.push_bind(kingdom);
+        qb.push(" OR ");
+        push_consensus_rank_filter(&mut qb, "kingdom", kingdom);
+        qb.push(")");
+    }
+
+    if let Some(bbox) = options.bbox.as_ref() {
+        push_bbox_filter(&mut qb, bbox);
+    }
+
+    if let Some(area) = options.near.as_ref() {
+        push_nearby_filter(&mut qb, area);
+    }
+
+    if let Some(cursor) = options.cursor.as_ref() {
+        push_keyset_cursor(&mut qb, cursor);
+    }
+
+    qb.push(" ORDER BY created_at DESC, uri DESC LIMIT ");
+    qb.push_bind(options.limit.unwrap_or(20));
+    qb
+}
+
 /// Push one WHERE clause per requested criterion. Each clause matches the
 /// corresponding branch of [`crate::quality::compute_issues`] — i.e. the row
 /// is kept only when that quality issue would be absent. Keep the two in sync:
@@ -124,6 +188,71 @@ fn push_quality_filter(qb: &mut QueryBuilder<Postgres>, criteria: &[QualityCrite
     }
 }
 
+/// Keep only rows of `grade`, applying the same [`VERIFIABLE_REQUIREMENTS`]
+/// and [`RESEARCH_REQUIREMENTS`] as [`crate::quality::compute_grade`].
+fn push_grade_filter(qb: &mut QueryBuilder<Postgres>, grade: QualityGrade) {
+    // Without the verifiable requirements the row is casual whatever its
+    // identifications say.
+    qb.push(" AND (");
+    for (i, requirement) in VERIFIABLE_REQUIREMENTS.into_iter().enumerate() {
+        if i > 0 {
+            qb.push(" AND ");
+        }
+        push_grade_requirement(qb, requirement);
+    }
+    qb.push(") = ");
+    let research = match grade {
+        QualityGrade::Casual => {
+            qb.push("FALSE");
+            return;
+        }
+        QualityGrade::NeedsId => " AND NOT EXISTS",
+        QualityGrade::Research => " AND EXISTS",
+    };
+    qb.push("TRUE");
+    qb.push(research);
+    qb.push(
+        " (SELECT 1 FROM community_ids ci \
+         JOIN taxa t ON t.taxon_key = ci.accepted_taxon_key \
+         WHERE ci.occurrence_uri = occurrences.uri",
+    );
+    for requirement in RESEARCH_REQUIREMENTS {
+        qb.push(" AND ");
+        push_grade_requirement(qb, requirement);
+    }
+    qb.push(")");
+}
+
+/// Render one grade requirement against `occurrences`, with the consensus
+/// requirements inside [`push_grade_filter`]'s `community_ids ci` / `taxa t`
+/// subquery. The votes are read the same way as
+/// [`crate::identifications::get_consensus_for_occurrences`].
+fn push_grade_requirement(qb: &mut QueryBuilder<Postgres>, requirement: GradeRequirement) {
+    match requirement {
+        GradeRequirement::HasDate => {
+            qb.push("event_date_raw IS NOT NULL");
+        }
+        GradeRequirement::HasLocation => {
+            qb.push("location IS NOT NULL");
+        }
+        GradeRequirement::HasMedia => {
+            qb.push("COALESCE(jsonb_array_length(associated_media), 0) > 0");
+        }
+        GradeRequirement::SpeciesRank => {
+            qb.push("t.species_key IS NOT NULL");
+        }
+        GradeRequirement::MinAgreeing(min) => {
+            qb.push("ci.id_count >= ");
+            qb.push_bind(min);
+        }
+        GradeRequirement::AgreeingShare(share) => {
+            qb.push("ci.vote_weight >= ");
+            qb.push_bind(share);
+            qb.push(" * ci.total_weight");
+        }
+    }
+}
+
 /// Get the profile feed for a user
 pub async fn get_profile_feed(
     pool: &PgPool,
@@ -137,9 +266,10 @@ pub async fn get_profile_feed(
         r#"
         SELECT
             (SELECT COUNT(*) FROM occurrences WHERE did = $1),
-            (SELECT COUNT(*) FROM identifications WHERE did = $1),
-            (SELECT COUNT(DISTINCT (scientific_name, kingdom)) FROM occurrences
-             WHERE did = $1 AND scientific_name IS NOT NULL)
+            (SELECT COUNT(*) FROM identifications WHERE did = $1 AND deleted_at IS NULL),
+            (SELECT COUNT(DISTINCT ci.accepted_taxon_key) FROM occurrences o
+             JOIN community_ids ci ON ci.occurrence_uri = o.uri
+             WHERE o.did = $1)
         "#,
     )
     .bind(did)
@@ -159,106 +289,20 @@ pub async fn get_profile_feed(
         feed_type,
         ProfileFeedType::Observations | ProfileFeedType::All
     ) {
-        occurrences = if let Some(ref cursor) = options.cursor {
-            sqlx::query_as!(
-                OccurrenceRow,
-                r#"
-                SELECT
-                    uri, cid, did, scientific_name,
-                    event_date_raw as event_date,
-                    ST_Y(location::geometry) as latitude,
-                    ST_X(location::geometry) as longitude,
-                    coordinate_uncertainty_meters,
-                    associated_media, recorded_by,
-                    taxon_id, taxon_rank, kingdom, phylum, class, "order" as order_, family, genus,
-                    organism_quantity, organism_quantity_type,
-                    created_at,
-                    NULL::float8 as distance_meters,
-                    NULL::text as source
-                FROM occurrences
-                WHERE did = $1 AND created_at < ($3::text)::timestamptz
-                ORDER BY created_at DESC, uri DESC
-                LIMIT $2
-                "#,
-                did,
-                limit,
-                cursor.as_str(),
-            )
+        occurrences = profile_occurrences_query(did, limit, options.cursor.as_ref())
+            .build_query_as::<OccurrenceRow>()
             .fetch_all(pool)
-            .await?
-        } else {
-            sqlx::query_as!(
-                OccurrenceRow,
-                r#"
-                SELECT
-                    uri, cid, did, scientific_name,
-                    event_date_raw as event_date,
-                    ST_Y(location::geometry) as latitude,
-                    ST_X(location::geometry) as longitude,
-                    coordinate_uncertainty_meters,
-                    associated_media, recorded_by,
-                    taxon_id, taxon_rank, kingdom, phylum, class, "order" as order_, family, genus,
-                    organism_quantity, organism_quantity_type,
-                    created_at,
-                    NULL::float8 as distance_meters,
-                    NULL::text as source
-                FROM occurrences
-                WHERE did = $1
-                ORDER BY created_at DESC, uri DESC
-                LIMIT $2
-                "#,
-                did,
-                limit,
-            )
-            .fetch_all(pool)
-            .await?
-        };
+            .await?;
     }
 
     if matches!(
         feed_type,
         ProfileFeedType::Identifications | ProfileFeedType::All
     ) {
-        identifications = if let Some(ref cursor) = options.cursor {
-            sqlx::query_as!(
-                IdentificationRow,
-                r#"
-                SELECT
-                    uri, cid, did, subject_uri, subject_cid, scientific_name,
-                    taxon_rank, identification_qualifier, taxon_id,
-                    identification_verification_status, type_status, date_identified,
-                    kingdom, phylum, class, "order" as order_, family, genus
-                FROM identifications
-                WHERE did = $1 AND date_identified < ($3::text)::timestamptz
-                ORDER BY date_identified DESC
-                LIMIT $2
-                "#,
-                did,
-                limit,
-                cursor.as_str(),
-            )
+        identifications = profile_identifications_query(did, limit, options.cursor.as_ref())
+            .build_query_as::<IdentificationRow>()
             .fetch_all(pool)
-            .await?
-        } else {
-            sqlx::query_as!(
-                IdentificationRow,
-                r#"
-                SELECT
-                    uri, cid, did, subject_uri, subject_cid, scientific_name,
-                    taxon_rank, identification_qualifier, taxon_id,
-                    identification_verification_status, type_status, date_identified,
-                    kingdom, phylum, class, "order" as order_, family, genus
-                FROM identifications
-                WHERE did = $1
-                ORDER BY date_identified DESC
-                LIMIT $2
-                "#,
-                did,
-                limit,
-            )
-            .fetch_all(pool)
-            .await?
-        };
+            .await?;
     }
 
     Ok(ProfileFeedResult {
@@ -268,6 +312,54 @@ pub async fn get_profile_feed(
     })
 }
 
+/// A user's occurrences, newest first, keyset-paged on `(created_at, uri)`.
+fn profile_occurrences_query(
+    did: &str,
+    limit: i64,
+    cursor: Option<&FeedCursor>,
+) -> QueryBuilder<Postgres> {
+    let mut qb = QueryBuilder::<Postgres>::new(concat!(
+        "SELECT ",
+        occurrence_columns!(),
+        " FROM occurrences WHERE did = "
+    ));
+    qb.push_bind(did.to_string());
+    if let Some(cursor) = cursor {
+        push_keyset_cursor(&mut qb, cursor);
+    }
+    qb.push(" ORDER BY created_at DESC, uri DESC LIMIT ");
+    qb.push_bind(limit);
+    qb
+}
+
+/// A user's live identifications, newest first. Pages on
+/// `(date_identified, uri)` the way [`push_keyset_cursor`] pages occurrences,
+/// so identifications made in the same instant aren't skipped.
+fn profile_identifications_query(
+    did: &str,
+    limit: i64,
+    cursor: Option<&FeedCursor>,
+) -> QueryBuilder<Postgres> {
+    let mut qb = QueryBuilder::<Postgres>::new(
+        "SELECT uri, cid, did, subject_uri, subject_cid, scientific_name, \
+         taxon_rank, identification_qualifier, confidence, taxon_id, \
+         identification_verification_status, type_status, date_identified, \
+         kingdom, phylum, class, \"order\", family, genus \
+         FROM identifications WHERE deleted_at IS NULL AND did = ",
+    );
+    qb.push_bind(did.to_string());
+    if let Some(cursor) = cursor {
+        qb.push(" AND (date_identified, uri) < (");
+        qb.push_bind(cursor.created_at);
+        qb.push("::timestamptz, ");
+        qb.push_bind(cursor.uri.clone());
+        qb.push(")");
+    }
+    qb.push(" ORDER BY date_identified DESC, uri DESC LIMIT ");
+    qb.push_bind(limit);
+    qb
+}
+
 /// Get the home feed (all occurrences, reverse chronological)
 pub async fn get_home_feed(
     executor: impl sqlx::PgExecutor<'_>,
@@ -290,7 +382,7 @@ pub async fn get_home_feed(
         push_quality_filter(&mut qb, &options.quality.criteria);
     }
 
-    if let Some(cursor) = options.cursor.as_deref() {
+    if let Some(cursor) = options.cursor.as_ref() {
         push_keyset_cursor(&mut qb, cursor);
     }
 
@@ -335,7 +427,7 @@ pub async fn get_occurrences_by_taxon(
         }
     }
 
-    if let Some(cursor) = options.cursor.as_deref() {
+    if let Some(cursor) = options.cursor.as_ref() {
         push_keyset_cursor(&mut qb, cursor);
     }
 
@@ -347,6 +439,67 @@ pub async fn get_occurrences_by_taxon(
         .await
 }
 
+/// Occurrences within `radius_meters` of `uri` whose consensus taxon is in
+/// the same genus, with its own species ranked first. Within each tier the
+/// nearest come first, so a same-species match always outranks a closer
+/// congener. Excludes `uri` itself; returns nothing when the occurrence has
+/// no consensus at genus level or below.
+pub async fn get_similar_occurrences(
+    executor: impl sqlx::PgExecutor<'_>,
+    uri: &str,
+    radius_meters: f64,
+    limit: i64,
+    hidden_dids: &[String],
+) -> Result<Vec<OccurrenceRow>, sqlx::Error> {
+    let mut qb = similar_occurrences_query(uri, radius_meters, limit, hidden_dids);
+    qb.build_query_as::<OccurrenceRow>()
+        .fetch_all(executor)
+        .await
+}
+
+fn similar_occurrences_query(
+    uri: &str,
+    radius_meters: f64,
+    limit: i64,
+    hidden_dids: &[String],
+) -> QueryBuilder<Postgres> {
+    let mut qb = QueryBuilder::<Postgres>::new(
+        "WITH target AS (\
+         SELECT o.uri, o.location, t.species, t.genus FROM occurrences o \
+         JOIN community_ids ci ON ci.occurrence_uri = o.uri \
+         JOIN taxa t ON t.taxon_key = ci.accepted_taxon_key \
+         WHERE o.uri = ",
+    );
+    qb.push_bind(uri);
+    qb.push(
+        " AND t.genus IS NOT NULL), \
+         matches AS (\
+         SELECT ci.occurrence_uri, COALESCE(t.species = target.species, FALSE) AS same_species \
+         FROM target, community_ids ci JOIN taxa t ON t.taxon_key = ci.accepted_taxon_key \
+         WHERE t.genus = target.genus) \
+         SELECT o.uri, o.cid, o.did, o.scientific_name, o.event_date_raw AS event_date, \
+         ST_Y(o.location::geometry) AS latitude, ST_X(o.location::geometry) AS longitude, \
+         o.coordinate_uncertainty_meters, o.associated_media, o.recorded_by, \
+         o.taxon_id, o.taxon_rank, o.kingdom, o.phylum, o.class, o.\"order\", o.family, o.genus, \
+         o.organism_quantity, o.organism_quantity_type, o.created_at, \
+         ST_Distance(o.location, target.location) AS distance_meters, NULL::text AS source \
+         FROM target \
+         JOIN occurrences o ON o.uri != target.uri AND ST_DWithin(o.location, target.location, ",
+    );
+    qb.push_bind(radius_meters);
+    qb.push(") JOIN matches m ON m.occurrence_uri = o.uri");
+
+    if !hidden_dids.is_empty() {
+        qb.push(" WHERE o.did != ALL(");
+        qb.push_bind(hidden_dids.to_vec());
+        qb.push(")");
+    }
+
+    qb.push(" ORDER BY m.same_species DESC, distance_meters, o.uri LIMIT ");
+    qb.push_bind(limit);
+    qb
+}
+
 /// Count occurrences matching a taxon
 pub async fn count_occurrences_by_taxon(
     executor: impl sqlx::PgExecutor<'_>,
@@ -354,21 +507,344 @@ pub async fn count_occurrences_by_taxon(
     taxon_rank: &str,
     kingdom: Option<&str>,
 ) -> Result<i64, sqlx::Error> {
-    let rank_lower = taxon_rank.to_lowercase();
-
     let mut qb = QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM occurrences WHERE ");
+    push_taxon_filter(&mut qb, taxon_name, taxon_rank, kingdom);
 
-    push_consensus_rank_filter(&mut qb, &rank_lower, taxon_name);
+    let (count,): (i64,) = qb.build_query_as().fetch_one(executor).await?;
+    Ok(count)
+}
+
+/// Occurrence count, observer count and first/last event dates for a taxon,
+/// matched the same way as [`count_occurrences_by_taxon`].
+pub async fn taxon_local_stats(
+    executor: impl sqlx::PgExecutor<'_>,
+    taxon_name: &str,
+    taxon_rank: &str,
+    kingdom: Option<&str>,
+) -> Result<TaxonLocalStats, sqlx::Error> {
+    let mut qb = taxon_local_stats_query(taxon_name, taxon_rank, kingdom);
+    qb.build_query_as::<TaxonLocalStats>()
+        .fetch_one(executor)
+        .await
+}
+
+fn taxon_local_stats_query(
+    taxon_name: &str,
+    taxon_rank: &str,
+    kingdom: Option<&str>,
+) -> QueryBuilder<Postgres> {
+    // Dates come from the observation's event date, not when the record was
+    // posted: "first seen 2019" is about the organism. Undated rows still
+    // count towards the totals.
+    let mut qb = QueryBuilder::<Postgres>::new(
+        "SELECT COUNT(*) AS occurrence_count, \
+         MIN(event_date_start) AS first_observed, \
+         MAX(event_date_start) AS last_observed, \
+         COUNT(DISTINCT did) AS observer_count \
+         FROM occurrences WHERE ",
+    );
+    push_taxon_filter(&mut qb, taxon_name, taxon_rank, kingdom);
+    qb
+}
+
+/// Occurrences of a taxon that carry photos, most-liked first, matched the
+/// same way as [`count_occurrences_by_taxon`]. Ties fall back to the feed
+/// order (newest first).
+pub async fn top_taxon_images(
+    executor: impl sqlx::PgExecutor<'_>,
+    taxon_name: &str,
+    taxon_rank: &str,
+    kingdom: Option<&str>,
+    limit: i64,
+    hidden_dids: &[String],
+) -> Result<Vec<TaxonImageRow>, sqlx::Error> {
+    let mut qb = top_taxon_images_query(taxon_name, taxon_rank, kingdom, limit, hidden_dids);
+    qb.build_query_as::<TaxonImageRow>()
+        .fetch_all(executor)
+        .await
+}
+
+fn top_taxon_images_query(
+    taxon_name: &str,
+    taxon_rank: &str,
+    kingdom: Option<&str>,
+    limit: i64,
+    hidden_dids: &[String],
+) -> QueryBuilder<Postgres> {
+    let mut qb = QueryBuilder::<Postgres>::new(
+        "SELECT uri, did, associated_media, \
+         (SELECT COUNT(*) FROM likes WHERE likes.subject_uri = occurrences.uri) AS like_count \
+         FROM occurrences WHERE ",
+    );
+    push_taxon_filter(&mut qb, taxon_name, taxon_rank, kingdom);
+    qb.push(" AND COALESCE(jsonb_array_length(associated_media), 0) > 0");
+
+    if !hidden_dids.is_empty() {
+        qb.push(" AND did != ALL(");
+        qb.push_bind(hidden_dids.to_vec());
+        qb.push(")");
+    }
+
+    qb.push(" ORDER BY like_count DESC, created_at DESC, uri DESC LIMIT ");
+    qb.push_bind(limit);
+    qb
+}
+
+/// Match occurrences whose consensus taxon is `taxon_name` at `taxon_rank`,
+/// narrowed to `kingdom` for ranks below it (names can repeat across
+/// kingdoms).
+fn push_taxon_filter(
+    qb: &mut QueryBuilder<Postgres>,
+    taxon_name: &str,
+    taxon_rank: &str,
+    kingdom: Option<&str>,
+) {
+    let rank_lower = taxon_rank.to_lowercase();
+    push_consensus_rank_filter(qb, &rank_lower, taxon_name);
 
     if let Some(kingdom) = kingdom {
         if rank_lower != "kingdom" {
             qb.push(" AND ");
-            push_consensus_rank_filter(&mut qb, "kingdom", kingdom);
+            push_consensus_rank_filter(qb, "kingdom", kingdom);
         }
     }
+}
 
-    let (count,): (i64,) = qb.build_query_as().fetch_one(executor).await?;
-    Ok(count)
+/// Taxa with the most new observations in the trailing `window`, optionally
+/// restricted to a bounding box.
+///
+/// Groups on each occurrence's community ID taxon (via `community_ids` →
+/// `taxa`), so occurrences nobody has identified don't count. Orders by
+/// observation count, breaking ties on distinct observers then name so the
+/// ranking is stable between requests. The owning DID is the complete observer
+/// set for a record now that co-observers are gone (see the
+/// `drop_occurrence_observers` migration), so distinct observers are counted
+/// over `did`.
+pub async fn trending_taxa(
+    executor: impl sqlx::PgExecutor<'_>,
+    window: chrono::Duration,
+    bbox: Option<&BoundingBox>,
+    limit: i64,
+    hidden_dids: &[String],
+) -> Result<Vec<TrendingTaxonRow>, sqlx::Error> {
+    let mut qb = trending_taxa_query(window, bbox, limit, hidden_dids);
+    qb.build_query_as::<TrendingTaxonRow>()
+        .fetch_all(executor)
+        .await
+}
+
+fn trending_taxa_query(
+    window: chrono::Duration,
+    bbox: Option<&BoundingBox>,
+    limit: i64,
+    hidden_dids: &[String],
+) -> QueryBuilder<Postgres> {
+    let mut qb = QueryBuilder::<Postgres>::new(
+        "SELECT t.scientific_name, t.kingdom, \
+         COUNT(*) AS observation_count, \
+         COUNT(DISTINCT o.did) AS distinct_observer_count \
+         FROM occurrences o \
+         JOIN community_ids ci ON ci.occurrence_uri = o.uri \
+         JOIN taxa t ON t.taxon_key = ci.accepted_taxon_key \
+         WHERE TRUE",
+    );
+
+    push_within_window(&mut qb, "o.created_at", window);
+
+    if let Some(bbox) = bbox {
+        push_bbox_filter(&mut qb, bbox);
+    }
+
+    if !hidden_dids.is_empty() {
+        qb.push(" AND o.did != ALL(");
+        qb.push_bind(hidden_dids.to_vec());
+        qb.push(")");
+    }
+
+    qb.push(
+        " GROUP BY t.scientific_name, t.kingdom \
+         ORDER BY observation_count DESC, distinct_observer_count DESC, t.scientific_name \
+         LIMIT ",
+    );
+    qb.push_bind(limit);
+    qb
+}
+
+/// Consensus taxon names starting with `prefix` (case-insensitive),
+/// most-observed first, for taxon autocomplete.
+///
+/// Reads each occurrence's community ID taxon (via `community_ids` → `taxa`)
+/// like [`trending_taxa`] but over all time, so names the community already
+/// uses surface without a GBIF round trip.
+pub async fn suggest_local_taxa(
+    executor: impl sqlx::PgExecutor<'_>,
+    prefix: &str,
+    limit: i64,
+    hidden_dids: &[String],
+) -> Result<Vec<LocalTaxonMatchRow>, sqlx::Error> {
+    let mut qb = suggest_local_taxa_query(prefix, limit, hidden_dids);
+    qb.build_query_as::<LocalTaxonMatchRow>()
+        .fetch_all(executor)
+        .await
+}
+
+fn suggest_local_taxa_query(
+    prefix: &str,
+    limit: i64,
+    hidden_dids: &[String],
+) -> QueryBuilder<Postgres> {
+    let mut qb = QueryBuilder::<Postgres>::new(
+        "SELECT t.scientific_name, t.kingdom, t.rank AS taxon_rank, \
+         COUNT(*) AS observation_count \
+         FROM occurrences o \
+         JOIN community_ids ci ON ci.occurrence_uri = o.uri \
+         JOIN taxa t ON t.taxon_key = ci.accepted_taxon_key \
+         WHERE t.scientific_name ILIKE ",
+    );
+    qb.push_bind(like_prefix_pattern(prefix));
+
+    if !hidden_dids.is_empty() {
+        qb.push(" AND o.did != ALL(");
+        qb.push_bind(hidden_dids.to_vec());
+        qb.push(")");
+    }
+
+    qb.push(
+        " GROUP BY t.scientific_name, t.kingdom, t.rank \
+         ORDER BY observation_count DESC, t.scientific_name \
+         LIMIT ",
+    );
+    qb.push_bind(limit);
+    qb
+}
+
+/// `LIKE` pattern matching strings that start with `prefix` literally, with
+/// the wildcard characters in user input escaped.
+fn like_prefix_pattern(prefix: &str) -> String {
+    let mut pattern = String::with_capacity(prefix.len() + 1);
+    for c in prefix.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
+
+/// Top contributors in the trailing `window`, optionally restricted to a
+/// bounding box, ranked by `metric`.
+///
+/// The per-metric counts mirror the profile counts in [`get_profile_feed`]:
+/// observations and species are credited to the occurrence's owning DID (the
+/// only observer since co-observers were dropped), identifications to the
+/// identifier. For identifications the window applies to `date_identified`
+/// and the region to the subject occurrence's location.
+pub async fn leaderboard(
+    executor: impl sqlx::PgExecutor<'_>,
+    metric: LeaderboardMetric,
+    window: chrono::Duration,
+    bbox: Option<&BoundingBox>,
+    limit: i64,
+    hidden_dids: &[String],
+) -> Result<Vec<LeaderboardRow>, sqlx::Error> {
+    let mut qb = leaderboard_query(metric, window, bbox, limit, hidden_dids);
+    qb.build_query_as::<LeaderboardRow>()
+        .fetch_all(executor)
+        .await
+}
+
+fn leaderboard_query(
+    metric: LeaderboardMetric,
+    window: chrono::Duration,
+    bbox: Option<&BoundingBox>,
+    limit: i64,
+    hidden_dids: &[String],
+) -> QueryBuilder<Postgres> {
+    let mut qb = match metric {
+        LeaderboardMetric::Observations => {
+            let mut qb = QueryBuilder::<Postgres>::new(
+                "SELECT did, COUNT(*) AS count FROM occurrences WHERE TRUE",
+            );
+            push_within_window(&mut qb, "created_at", window);
+            if let Some(bbox) = bbox {
+                push_bbox_filter(&mut qb, bbox);
+            }
+            qb
+        }
+        LeaderboardMetric::Species => {
+            let mut qb = QueryBuilder::<Postgres>::new(
+                "SELECT did, COUNT(DISTINCT ci.accepted_taxon_key) AS count \
+                 FROM occurrences o \
+                 JOIN community_ids ci ON ci.occurrence_uri = o.uri \
+                 WHERE TRUE",
+            );
+            push_within_window(&mut qb, "created_at", window);
+            if let Some(bbox) = bbox {
+                push_bbox_filter(&mut qb, bbox);
+            }
+            qb
+        }
+        LeaderboardMetric::Identifications => {
+            let mut qb = QueryBuilder::<Postgres>::new(
+                "SELECT did, COUNT(*) AS count FROM identifications WHERE deleted_at IS NULL",
+            );
+            push_within_window(&mut qb, "date_identified", window);
+            if let Some(bbox) = bbox {
+                qb.push(" AND subject_uri IN (SELECT uri FROM occurrences WHERE TRUE");
+                push_bbox_filter(&mut qb, bbox);
+                qb.push(")");
+            }
+            qb
+        }
+    };
+
+    if !hidden_dids.is_empty() {
+        qb.push(" AND did != ALL(");
+        qb.push_bind(hidden_dids.to_vec());
+        qb.push(")");
+    }
+
+    qb.push(" GROUP BY did ORDER BY count DESC, did LIMIT ");
+    qb.push_bind(limit);
+    qb
+}
+
+/// Keep only rows whose timestamp `column` falls within the trailing `window`.
+/// For occurrences that is `created_at` (when the record was made, not its
+/// eventDate).
+fn push_within_window(qb: &mut QueryBuilder<Postgres>, column: &str, window: chrono::Duration) {
+    qb.push(" AND ");
+    qb.push(column);
+    qb.push(" >= NOW() - make_interval(secs => ");
+    qb.push_bind(window.num_seconds() as f64);
+    qb.push(")");
+}
+
+/// Keep only rows whose location falls inside `bbox`. Rows without a
+/// location never match.
+pub(crate) fn push_bbox_filter(qb: &mut QueryBuilder<Postgres>, bbox: &BoundingBox) {
+    qb.push(" AND location && ST_MakeEnvelope(");
+    qb.push_bind(bbox.min_lng);
+    qb.push(", ");
+    qb.push_bind(bbox.min_lat);
+    qb.push(", ");
+    qb.push_bind(bbox.max_lng);
+    qb.push(", ");
+    qb.push_bind(bbox.max_lat);
+    qb.push(", 4326)::geography");
+}
+
+/// Keep only rows whose location lies within `area`. Rows without a
+/// location never match.
+fn push_nearby_filter(qb: &mut QueryBuilder<Postgres>, area: &NearbyArea) {
+    qb.push(" AND ST_DWithin(location, ST_SetSRID(ST_MakePoint(");
+    qb.push_bind(area.lng);
+    qb.push(", ");
+    qb.push_bind(area.lat);
+    qb.push("), 4326)::geography, ");
+    qb.push_bind(area.radius_meters);
+    qb.push(")");
 }
 
 /// Restrict the outer occurrences query to rows whose consensus
@@ -397,6 +873,15 @@ fn push_consensus_rank_filter(qb: &mut QueryBuilder<Postgres>, rank_lower: &str,
     qb.push(")");
 }
 
+/// Keep only occurrences whose consensus taxon has one of `categories` as
+/// its recorded conservation status. Occurrences without a consensus, or
+/// whose taxon has no recorded status, never match.
+fn push_conservation_filter(qb: &mut QueryBuilder<Postgres>, categories: &[String]) {
+    qb.push(" AND uri IN (SELECT ci.occurrence_uri FROM community_ids ci JOIN taxon_conservation tc ON tc.taxon_key = ci.accepted_taxon_key WHERE tc.category = ANY(");
+    qb.push_bind(categories.to_vec());
+    qb.push("))");
+}
+
 /// Keep only occurrences whose eventDate interval overlaps the requested
 /// window. Both bounds arrive as `YYYY-MM-DD` dates; the window is the
 /// half-open range `[start 00:00, (end + 1 day) 00:00)`, so the end date is
@@ -435,14 +920,339 @@ fn push_date_overlap_filter(
     qb.push(", '[)')");
 }
 
+/// Activity on a user's records by other people, newest first: identifications,
+/// comments and likes on their occurrences, and replies to their comments
+/// anywhere. A reply on one of their own occurrences appears once, as a
+/// `reply`. Paged by [`FeedCursor`] like the feeds.
+pub async fn activity(
+    executor: impl sqlx::PgExecutor<'_>,
+    did: &str,
+    options: &ActivityOptions,
+    hidden_dids: &[String],
+) -> Result<Vec<ActivityRow>, sqlx::Error> {
+    activity_query(did, options, hidden_dids)
+        .build_query_as::<ActivityRow>()
+        .fetch_all(executor)
+        .await
+}
+
+fn activity_query(
+    did: &str,
+    options: &ActivityOptions,
+    hidden_dids: &[String],
+) -> QueryBuilder<Postgres> {
+    // The user's DID is bound once, in `me`, and every branch reads it from
+    // there rather than repeating a placeholder.
+    let mut qb = QueryBuilder::<Postgres>::new("WITH me AS (SELECT ");
+    qb.push_bind(did.to_string());
+    qb.push(
+        r#"::text AS did)
+        SELECT kind, uri, did, subject_uri, created_at, scientific_name, body, reply_to_uri
+        FROM (
+            SELECT 'identification' AS kind, i.uri, i.did, i.subject_uri,
+                i.date_identified AS created_at, i.scientific_name,
+                NULL::text AS body, NULL::text AS reply_to_uri
+            FROM identifications i
+            JOIN occurrences o ON o.uri = i.subject_uri
+            JOIN me ON o.did = me.did
+            WHERE i.did <> o.did AND i.deleted_at IS NULL
+            UNION ALL
+            SELECT CASE WHEN p.did = me.did THEN 'reply' ELSE 'comment' END, c.uri, c.did,
+                c.subject_uri, c.created_at, NULL, c.body,
+                CASE WHEN p.did = me.did THEN p.uri END
+            FROM comments c
+            CROSS JOIN me
+            LEFT JOIN occurrences o ON o.uri = c.subject_uri
+            LEFT JOIN comments p ON p.uri = c.reply_to_uri
+            WHERE (o.did = me.did OR p.did = me.did) AND c.did <> me.did
+                AND c.deleted_at IS NULL
+            UNION ALL
+            SELECT 'like', l.uri, l.did, l.subject_uri, l.created_at AT TIME ZONE 'UTC',
+                NULL, NULL, NULL
+            FROM likes l
+            JOIN occurrences o ON o.uri = l.subject_uri
+            JOIN me ON o.did = me.did
+            WHERE l.did <> o.did
+        ) activity
+        WHERE TRUE"#,
+    );
+
+    if !hidden_dids.is_empty() {
+        qb.push(" AND did != ALL(");
+        qb.push_bind(hidden_dids.to_vec());
+        qb.push(")");
+    }
+
+    if let Some(cursor) = options.cursor.as_ref() {
+        push_keyset_cursor(&mut qb, cursor);
+    }
+
+    qb.push(" ORDER BY created_at DESC, uri DESC LIMIT ");
+    qb.push_bind(options.limit.unwrap_or(20));
+    qb
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn test_cursor() -> FeedCursor {
+        FeedCursor::new(
+            "2026-06-02T21:13:49Z".parse().unwrap(),
+            "at://did:plc:x/coll/rkey",
+        )
+    }
+
+    #[test]
+    fn similar_occurrences_rank_same_species_before_distance() {
+        let qb = similar_occurrences_query(
+            "at://did:plc:x/bio.lexicons.temp.occurrence/1",
+            50_000.0,
+            20,
+            &["did:plc:hidden".to_string()],
+        );
+        let sql = qb.sql();
+        let sql = sql.as_str();
+        // The reference taxon and point come from the occurrence itself...
+        assert!(
+            sql.contains("WHERE o.uri = $1 AND t.genus IS NOT NULL"),
+            "got: {sql}"
+        );
+        // ...congeners are candidates, flagged when they share the species...
+        assert!(sql.contains("WHERE t.genus = target.genus"), "got: {sql}");
+        assert!(
+            sql.contains("COALESCE(t.species = target.species, FALSE) AS same_species"),
+            "got: {sql}"
+        );
+        // ...within the radius, never the occurrence itself.
+        assert!(
+            sql.contains("o.uri != target.uri AND ST_DWithin(o.location, target.location, $2)"),
+            "got: {sql}"
+        );
+        assert!(sql.contains("WHERE o.did != ALL($3)"), "got: {sql}");
+        // A same-species match far away still outranks a nearby congener.
+        assert!(
+            sql.ends_with("ORDER BY m.same_species DESC, distance_meters, o.uri LIMIT $4"),
+            "got: {sql}"
+        );
+    }
+
+    #[test]
+    fn similar_occurrences_without_hidden_dids_skips_the_filter() {
+        let qb = similar_occurrences_query("at://did:plc:x/coll/1", 1000.0, 5, &[]);
+        let sql = qb.sql();
+        assert!(!sql.contains("ALL("), "got: {}", sql.as_str());
+        assert!(sql.as_str().ends_with("LIMIT $3"), "got: {}", sql.as_str());
+    }
+
+    #[test]
+    fn suggest_local_taxa_ranks_by_observation_count() {
+        let qb = suggest_local_taxa_query("Quer", 10, &["did:plc:hidden".to_string()]);
+        let sql = qb.sql();
+        let sql = sql.as_str();
+        assert!(sql.contains("t.scientific_name ILIKE $1"), "got: {sql}");
+        assert!(sql.contains("o.did != ALL($2)"), "got: {sql}");
+        assert!(
+            sql.contains("ORDER BY observation_count DESC, t.scientific_name"),
+            "got: {sql}"
+        );
+    }
+
+    #[test]
+    fn suggest_local_taxa_reads_the_consensus_taxon() {
+        let qb = suggest_local_taxa_query("Quer", 10, &[]);
+        let sql = qb.sql();
+        let sql = sql.as_str();
+        // The ingester leaves `occurrences.scientific_name` NULL; the name
+        // lives on the community ID's taxon.
+        assert!(
+            sql.contains(
+                "JOIN community_ids ci ON ci.occurrence_uri = o.uri \
+                 JOIN taxa t ON t.taxon_key = ci.accepted_taxon_key"
+            ),
+            "got: {sql}"
+        );
+        assert!(
+            sql.contains("SELECT t.scientific_name, t.kingdom, t.rank AS taxon_rank"),
+            "got: {sql}"
+        );
+        assert!(
+            sql.contains("GROUP BY t.scientific_name, t.kingdom, t.rank"),
+            "got: {sql}"
+        );
+        assert!(!sql.contains("did != ALL"), "got: {sql}");
+    }
+
+    #[test]
+    fn top_taxon_images_ranks_by_likes_and_skips_imageless_rows() {
+        let qb = top_taxon_images_query(
+            "Quercus alba",
+            "species",
+            Some("Plantae"),
+            12,
+            &["did:plc:hidden".to_string()],
+        );
+        let sql = qb.sql();
+        let sql = sql.as_str();
+        assert!(
+            sql.starts_with(
+                "SELECT uri, did, associated_media, (SELECT COUNT(*) FROM likes \
+                 WHERE likes.subject_uri = occurrences.uri) AS like_count FROM occurrences WHERE "
+            ),
+            "got: {sql}"
+        );
+        assert!(sql.contains("t.species = $1"), "got: {sql}");
+        assert!(sql.contains("t.kingdom = $2"), "got: {sql}");
+        assert!(
+            sql.contains("COALESCE(jsonb_array_length(associated_media), 0) > 0"),
+            "got: {sql}"
+        );
+        assert!(sql.contains("did != ALL($3)"), "got: {sql}");
+        assert!(
+            sql.contains("ORDER BY like_count DESC, created_at DESC, uri DESC LIMIT $4"),
+            "got: {sql}"
+        );
+    }
+
+    #[test]
+    fn needs_id_feed_excludes_occurrences_identified_by_others() {
+        let options = NeedsIdFeedOptions::default();
+        let qb = needs_id_feed_query(&options, &[]);
+        let sql = qb.sql();
+        let sql = sql.as_str();
+        // Only identifications from someone other than the observer count.
+        assert!(
+            sql.contains("NOT EXISTS (SELECT 1 FROM identifications i WHERE i.subject_uri = occurrences.uri AND i.did != occurrences.did AND i.deleted_at IS NULL)"),
+            "got: {sql}"
+        );
+        assert!(
+            sql.ends_with("ORDER BY created_at DESC, uri DESC LIMIT $1"),
+            "got: {sql}"
+        );
+    }
+
+    #[test]
+    fn needs_id_feed_filters_by_kingdom_and_region() {
+        let options = NeedsIdFeedOptions {
+            kingdom: Some("Fungi".into()),
+            bbox: Some(BoundingBox {
+                min_lat: 37.0,
+                min_lng: -123.0,
+                max_lat: 38.0,
+                max_lng: -122.0,
+            }),
+            cursor: Some(test_cursor()),
+            ..Default::default()
+        };
+        let qb = needs_id_feed_query(&options, &["did:plc:hidden".to_string()]);
+        let sql = qb.sql();
+        let sql = sql.as_str();
+        assert!(sql.contains("did != ALL($1)"), "got: {sql}");
+        assert!(sql.contains("(kingdom = $2 OR uri IN ("), "got: {sql}");
+        assert!(sql.contains("t.kingdom = $3)"), "got: {sql}");
+        assert!(sql.contains("location && ST_MakeEnvelope("), "got: {sql}");
+        assert!(sql.contains("(created_at, uri) < ("), "got: {sql}");
+    }
+
+    #[test]
+    fn needs_id_feed_combines_radius_with_kingdom_and_region() {
+        let options = NeedsIdFeedOptions {
+            kingdom: Some("Plantae".into()),
+            bbox: Some(BoundingBox {
+                min_lat: 37.0,
+                min_lng: -123.0,
+                max_lat: 38.0,
+                max_lng: -122.0,
+            }),
+            near: Some(NearbyArea {
+                lat: 37.77,
+                lng: -122.42,
+                radius_meters: 5_000.0,
+            }),
+            cursor: Some(test_cursor()),
+            ..Default::default()
+        };
+        let qb = needs_id_feed_query(&options, &[]);
+        let sql = qb.sql();
+        let sql = sql.as_str();
+        // Every filter narrows the same needs-ID predicate.
+        assert!(
+            sql.contains("FROM occurrences WHERE NOT EXISTS (SELECT 1 FROM identifications i"),
+            "got: {sql}"
+        );
+        assert!(sql.contains("(kingdom = $1 OR uri IN ("), "got: {sql}");
+        assert!(
+            sql.contains("AND location && ST_MakeEnvelope($3, $4, $5, $6, 4326)::geography"),
+            "got: {sql}"
+        );
+        assert!(
+            sql.contains(
+                "AND ST_DWithin(location, ST_SetSRID(ST_MakePoint($7, $8), 4326)::geography, $9)"
+            ),
+            "got: {sql}"
+        );
+        assert!(sql.contains("(created_at, uri) < ("), "got: {sql}");
+        assert!(
+            sql.ends_with("ORDER BY created_at DESC, uri DESC LIMIT $12"),
+            "got: {sql}"
+        );
+    }
+
+    #[test]
+    fn needs_id_feed_radius_alone_is_the_only_spatial_filter() {
+        let options = NeedsIdFeedOptions {
+            near: Some(NearbyArea {
+                lat: 0.5,
+                lng: 0.5,
+                radius_meters: 1_000.0,
+            }),
+            ..Default::default()
+        };
+        let qb = needs_id_feed_query(&options, &[]);
+        let sql = qb.sql();
+        let sql = sql.as_str();
+        assert!(!sql.contains("ST_MakeEnvelope"), "got: {sql}");
+        assert!(sql.contains("ST_DWithin(location,"), "got: {sql}");
+    }
+
+    #[test]
+    fn taxon_local_stats_aggregates_the_consensus_taxon() {
+        let qb = taxon_local_stats_query("Quercus", "Genus", Some("Plantae"));
+        let sql = qb.sql();
+        let sql = sql.as_str();
+        assert!(sql.contains("COUNT(*) AS occurrence_count"), "got: {sql}");
+        assert!(
+            sql.contains("MIN(event_date_start) AS first_observed"),
+            "got: {sql}"
+        );
+        assert!(
+            sql.contains("MAX(event_date_start) AS last_observed"),
+            "got: {sql}"
+        );
+        assert!(
+            sql.contains("COUNT(DISTINCT did) AS observer_count"),
+            "got: {sql}"
+        );
+        // Same consensus match as the taxon feed: genus column, then kingdom.
+        assert!(sql.contains("WHERE t.genus = $1)"), "got: {sql}");
+        assert!(sql.contains("AND uri IN ("), "got: {sql}");
+        assert!(sql.contains("WHERE t.kingdom = $2)"), "got: {sql}");
+
+        // A kingdom is its own scope; no second filter.
+        let qb = taxon_local_stats_query("Plantae", "kingdom", Some("Plantae"));
+        assert!(!qb.sql().as_str().contains("$2"));
+    }
+
+    #[test]
+    fn like_prefix_pattern_escapes_wildcards() {
+        assert_eq!(like_prefix_pattern("Quercus"), "Quercus%");
+        assert_eq!(like_prefix_pattern("50%_off\\"), "50\\%\\_off\\\\%");
+    }
+
     #[test]
     fn keyset_cursor_compound_uses_row_value_comparison() {
         let mut qb = QueryBuilder::<Postgres>::new("SELECT 1 FROM occurrences WHERE TRUE");
-        push_keyset_cursor(&mut qb, "2026-06-02T21:13:49Z|at://did:plc:x/coll/rkey");
+        push_keyset_cursor(&mut qb, &test_cursor());
         let sql = qb.sql();
         let sql = sql.as_str();
         // Tuple comparison against (created_at, uri) so a shared timestamp can't
@@ -451,6 +1261,33 @@ mod tests {
         assert!(sql.contains("::timestamptz"));
     }
 
+    #[test]
+    fn profile_feeds_page_on_a_compound_keyset() {
+        let qb = profile_occurrences_query("did:plc:x", 20, Some(&test_cursor()));
+        let sql = qb.sql();
+        let sql = sql.as_str();
+        assert!(
+            sql.contains("did = $1 AND (created_at, uri) < ($2::timestamptz, $3)"),
+            "got: {sql}"
+        );
+        assert!(
+            sql.ends_with("ORDER BY created_at DESC, uri DESC LIMIT $4"),
+            "got: {sql}"
+        );
+
+        let qb = profile_identifications_query("did:plc:x", 20, Some(&test_cursor()));
+        let sql = qb.sql();
+        let sql = sql.as_str();
+        assert!(
+            sql.contains("did = $1 AND (date_identified, uri) < ($2::timestamptz, $3)"),
+            "got: {sql}"
+        );
+        assert!(
+            sql.ends_with("ORDER BY date_identified DESC, uri DESC LIMIT $4"),
+            "got: {sql}"
+        );
+    }
+
     #[test]
     fn date_overlap_filter_uses_range_overlap_with_inclusive_end_day() {
         let mut qb = QueryBuilder::<Postgres>::new("SELECT 1 FROM occurrences WHERE TRUE");
@@ -486,13 +1323,270 @@ mod tests {
     }
 
     #[test]
-    fn keyset_cursor_legacy_value_falls_back_to_timestamp() {
+    fn conservation_filter_joins_consensus_taxon_status() {
         let mut qb = QueryBuilder::<Postgres>::new("SELECT 1 FROM occurrences WHERE TRUE");
-        push_keyset_cursor(&mut qb, "2026-06-02T21:13:49Z");
+        push_conservation_filter(&mut qb, &["VU".to_string(), "EN".to_string()]);
+        let sql = qb.sql();
+        let sql = sql.as_str();
+        assert!(
+            sql.contains("JOIN taxon_conservation tc ON tc.taxon_key = ci.accepted_taxon_key"),
+            "got: {sql}"
+        );
+        assert!(sql.contains("tc.category = ANY($1)"), "got: {sql}");
+    }
+
+    #[test]
+    fn trending_taxa_groups_recent_rows_by_taxon() {
+        let qb = trending_taxa_query(chrono::Duration::days(7), None, 20, &[]);
+        let sql = qb.sql();
+        let sql = sql.as_str();
+        assert!(
+            sql.contains("o.created_at >= NOW() - make_interval(secs => "),
+            "got: {sql}"
+        );
+        assert!(sql.contains("COUNT(DISTINCT o.did) AS distinct_observer_count"));
+        // Ranked on the community ID taxon, not the submitted columns the
+        // ingester leaves NULL.
+        assert!(
+            sql.contains("JOIN taxa t ON t.taxon_key = ci.accepted_taxon_key"),
+            "got: {sql}"
+        );
+        assert!(
+            sql.contains("GROUP BY t.scientific_name, t.kingdom"),
+            "got: {sql}"
+        );
+        assert!(
+            sql.contains("ORDER BY observation_count DESC"),
+            "got: {sql}"
+        );
+        // No region and no hidden DIDs → neither clause is emitted.
+        assert!(!sql.contains("ST_MakeEnvelope"), "got: {sql}");
+        assert!(!sql.contains("did != ALL"), "got: {sql}");
+    }
+
+    #[test]
+    fn trending_taxa_scopes_to_bbox_and_hidden_dids() {
+        let bbox = BoundingBox {
+            min_lat: 37.0,
+            min_lng: -123.0,
+            max_lat: 38.0,
+            max_lng: -122.0,
+        };
+        let hidden = vec!["did:plc:hidden".to_string()];
+        let qb = trending_taxa_query(chrono::Duration::days(30), Some(&bbox), 10, &hidden);
+        let sql = qb.sql();
+        let sql = sql.as_str();
+        // The region predicate precedes GROUP BY so it filters rows, not groups.
+        let envelope = sql.find("location && ST_MakeEnvelope(").expect(sql);
+        let group_by = sql.find("GROUP BY").expect(sql);
+        assert!(envelope < group_by, "got: {sql}");
+        assert!(sql.contains("4326)::geography"), "got: {sql}");
+        assert!(sql.contains("AND o.did != ALL("), "got: {sql}");
+    }
+
+    #[test]
+    fn leaderboard_counts_each_metric_per_did() {
+        let window = chrono::Duration::days(7);
+
+        let qb = leaderboard_query(LeaderboardMetric::Observations, window, None, 10, &[]);
+        let sql = qb.sql();
+        let sql = sql.as_str();
+        assert!(
+            sql.contains("COUNT(*) AS count FROM occurrences"),
+            "got: {sql}"
+        );
+        assert!(sql.contains("created_at >= NOW()"), "got: {sql}");
+
+        let qb = leaderboard_query(LeaderboardMetric::Species, window, None, 10, &[]);
         let sql = qb.sql();
         let sql = sql.as_str();
-        // A pre-upgrade cursor (no `|`) still paginates, just without the tiebreaker.
-        assert!(sql.contains("AND created_at < "), "got: {sql}");
-        assert!(!sql.contains("(created_at, uri)"), "got: {sql}");
+        // Same distinct-taxon definition as the profile species count: the
+        // community ID taxon, since the submitted name columns stay NULL.
+        assert!(
+            sql.contains("COUNT(DISTINCT ci.accepted_taxon_key) AS count"),
+            "got: {sql}"
+        );
+        assert!(
+            sql.contains("JOIN community_ids ci ON ci.occurrence_uri = o.uri"),
+            "got: {sql}"
+        );
+
+        let qb = leaderboard_query(LeaderboardMetric::Identifications, window, None, 10, &[]);
+        let sql = qb.sql();
+        let sql = sql.as_str();
+        assert!(
+            sql.contains("FROM identifications WHERE deleted_at IS NULL"),
+            "got: {sql}"
+        );
+        assert!(sql.contains("date_identified >= NOW()"), "got: {sql}");
+
+        for metric in [
+            LeaderboardMetric::Observations,
+            LeaderboardMetric::Species,
+            LeaderboardMetric::Identifications,
+        ] {
+            let qb = leaderboard_query(metric, window, None, 10, &[]);
+            let sql = qb.sql();
+            let sql = sql.as_str();
+            assert!(
+                sql.contains("GROUP BY did ORDER BY count DESC"),
+                "got: {sql}"
+            );
+        }
+    }
+
+    #[test]
+    fn leaderboard_identifications_scope_region_via_subject() {
+        let bbox = BoundingBox {
+            min_lat: 37.0,
+            min_lng: -123.0,
+            max_lat: 38.0,
+            max_lng: -122.0,
+        };
+        let qb = leaderboard_query(
+            LeaderboardMetric::Identifications,
+            chrono::Duration::days(7),
+            Some(&bbox),
+            10,
+            &[],
+        );
+        let sql = qb.sql();
+        let sql = sql.as_str();
+        // Identifications have no location of their own; the region comes
+        // from the occurrence they identify.
+        assert!(
+            sql.contains("subject_uri IN (SELECT uri FROM occurrences WHERE TRUE AND location && ST_MakeEnvelope("),
+            "got: {sql}"
+        );
+    }
+
+    #[test]
+    fn activity_unions_every_event_type_on_the_users_records() {
+        let sql = activity_query("did:plc:me", &ActivityOptions::default(), &[])
+            .sql()
+            .as_str()
+            .to_string();
+        // Identifications, comments/replies and likes share one column list
+        // so they can be merged into a single stream.
+        assert_eq!(sql.matches("UNION ALL").count(), 2, "got: {sql}");
+        assert!(sql.contains("'identification' AS kind"), "got: {sql}");
+        assert!(
+            sql.contains("CASE WHEN p.did = me.did THEN 'reply' ELSE 'comment' END"),
+            "got: {sql}"
+        );
+        assert!(sql.contains("SELECT 'like', l.uri"), "got: {sql}");
+        // The user's own identifications, comments and likes are not news.
+        assert!(sql.contains("i.did <> o.did"), "got: {sql}");
+        assert!(sql.contains("c.did <> me.did"), "got: {sql}");
+        assert!(sql.contains("l.did <> o.did"), "got: {sql}");
+        // Retracted identifications and deleted comments drop out.
+        assert!(sql.contains("i.deleted_at IS NULL"), "got: {sql}");
+        assert!(sql.contains("c.deleted_at IS NULL"), "got: {sql}");
+        // `likes.created_at` has no zone; it's compared as UTC like the rest.
+        assert!(
+            sql.contains("l.created_at AT TIME ZONE 'UTC'"),
+            "got: {sql}"
+        );
+        assert!(
+            sql.ends_with("WHERE TRUE ORDER BY created_at DESC, uri DESC LIMIT $2"),
+            "got: {sql}"
+        );
+    }
+
+    #[test]
+    fn activity_binds_the_did_once_for_every_branch() {
+        let sql = activity_query("did:plc:me", &ActivityOptions::default(), &[])
+            .sql()
+            .as_str()
+            .to_string();
+        assert!(
+            sql.starts_with("WITH me AS (SELECT $1::text AS did)"),
+            "got: {sql}"
+        );
+        // No branch refers to the DID by placeholder, so reordering binds
+        // can't hand it another value.
+        assert_eq!(sql.matches("$1").count(), 1, "got: {sql}");
+        assert_eq!(sql.matches("JOIN me").count(), 3, "got: {sql}");
+    }
+
+    #[test]
+    fn activity_skips_hidden_actors() {
+        let sql = activity_query(
+            "did:plc:me",
+            &ActivityOptions::default(),
+            &["did:plc:hidden".to_string()],
+        )
+        .sql()
+        .as_str()
+        .to_string();
+        // Applied to the merged stream, so it covers every event type.
+        assert!(
+            sql.ends_with(
+                "WHERE TRUE AND did != ALL($2) ORDER BY created_at DESC, uri DESC LIMIT $3"
+            ),
+            "got: {sql}"
+        );
+    }
+
+    #[test]
+    fn activity_pages_over_the_merged_stream() {
+        let sql = activity_query(
+            "did:plc:me",
+            &ActivityOptions {
+                limit: Some(5),
+                cursor: Some(test_cursor()),
+            },
+            &[],
+        )
+        .sql()
+        .as_str()
+        .to_string();
+        // The cursor applies after the union, so a page boundary falling
+        // between a like and a comment at the same instant is still exact.
+        assert!(
+            sql.ends_with(
+                "WHERE TRUE AND (created_at, uri) < ($2::timestamptz, $3) \
+                 ORDER BY created_at DESC, uri DESC LIMIT $4"
+            ),
+            "got: {sql}"
+        );
+    }
+
+    fn grade_sql(grade: QualityGrade) -> String {
+        let mut qb = QueryBuilder::<Postgres>::new("SELECT uri FROM occurrences WHERE TRUE");
+        push_grade_filter(&mut qb, grade);
+        qb.sql().as_str().to_string()
+    }
+
+    #[test]
+    fn casual_grade_is_anything_unverifiable() {
+        let sql = grade_sql(QualityGrade::Casual);
+        assert!(
+            sql.ends_with("AND COALESCE(jsonb_array_length(associated_media), 0) > 0) = FALSE"),
+            "got: {sql}"
+        );
+        assert!(!sql.contains("community_ids"), "got: {sql}");
+    }
+
+    #[test]
+    fn research_and_needs_id_split_verifiable_rows_on_consensus() {
+        let research = grade_sql(QualityGrade::Research);
+        let needs_id = grade_sql(QualityGrade::NeedsId);
+        for sql in [&research, &needs_id] {
+            assert!(sql.contains(") = TRUE"), "got: {sql}");
+            assert!(sql.contains("t.species_key IS NOT NULL"), "got: {sql}");
+            assert!(
+                sql.contains("AND ci.id_count >= $1 AND ci.vote_weight >= $2 * ci.total_weight)"),
+                "got: {sql}"
+            );
+        }
+        assert!(
+            research.contains("TRUE AND EXISTS (SELECT 1"),
+            "got: {research}"
+        );
+        assert!(
+            needs_id.contains("TRUE AND NOT EXISTS (SELECT 1"),
+            "got: {needs_id}"
+        );
     }
 }
@@ -0,0 +1,107 @@
+//! Idempotency keys for record-creating requests.
+//!
+//! A request carrying a key first [`reserve`]s it. The winner creates the
+//! record and [`complete`]s the key with the result; a retry with the same
+//! key then gets that result back instead of creating a second record. If
+//! the create fails before the record is written, the key is [`release`]d so
+//! the client can retry it. Each key is bound to a digest of the request
+//! that reserved it, so reusing it for a different request is caught.
+
+use sqlx::PgPool;
+use std::time::Duration;
+
+/// Outcome of [`reserve`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reservation {
+    /// The key is new; the caller should perform the create.
+    Reserved,
+    /// An earlier request with this key already created `uri`.
+    Completed { uri: String, cid: String },
+    /// An earlier request with this key hasn't finished yet.
+    InProgress,
+    /// The key was first used for a request with a different digest.
+    Mismatch,
+}
+
+/// Claim `key` for `did`, binding it to `request_hash`.
+///
+/// Completed keys older than `ttl`, and reservations left unfinished for
+/// longer than `in_progress_timeout` (e.g. by a crashed request), are
+/// cleared first, so both can be reused.
+pub async fn reserve(
+    pool: &PgPool,
+    did: &str,
+    key: &str,
+    request_hash: &str,
+    ttl: Duration,
+    in_progress_timeout: Duration,
+) -> Result<Reservation, sqlx::Error> {
+    sqlx::query(
+        "DELETE FROM idempotency_keys WHERE did = $1 \
+         AND (created_at < NOW() - make_interval(secs => $2) \
+         OR (uri IS NULL AND created_at < NOW() - make_interval(secs => $3)))",
+    )
+    .bind(did)
+    .bind(ttl.as_secs_f64())
+    .bind(in_progress_timeout.as_secs_f64())
+    .execute(pool)
+    .await?;
+
+    let inserted = sqlx::query(
+        "INSERT INTO idempotency_keys (did, key, request_hash) VALUES ($1, $2, $3) \
+         ON CONFLICT (did, key) DO NOTHING",
+    )
+    .bind(did)
+    .bind(key)
+    .bind(request_hash)
+    .execute(pool)
+    .await?;
+    if inserted.rows_affected() == 1 {
+        return Ok(Reservation::Reserved);
+    }
+
+    let existing: Option<(Option<String>, Option<String>, String)> = sqlx::query_as(
+        "SELECT uri, cid, request_hash FROM idempotency_keys WHERE did = $1 AND key = $2",
+    )
+    .bind(did)
+    .bind(key)
+    .fetch_optional(pool)
+    .await?;
+    Ok(match existing {
+        Some((_, _, hash)) if hash != request_hash => Reservation::Mismatch,
+        Some((Some(uri), Some(cid), _)) => Reservation::Completed { uri, cid },
+        _ => Reservation::InProgress,
+    })
+}
+
+/// Record the result of the create made under `key`.
+pub async fn complete(
+    executor: impl sqlx::PgExecutor<'_>,
+    did: &str,
+    key: &str,
+    uri: &str,
+    cid: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE idempotency_keys SET uri = $3, cid = $4 WHERE did = $1 AND key = $2")
+        .bind(did)
+        .bind(key)
+        .bind(uri)
+        .bind(cid)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+/// Drop an unfinished reservation after a failed create.
+pub async fn release(
+    executor: impl sqlx::PgExecutor<'_>,
+    did: &str,
+    key: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM idempotency_keys WHERE did = $1 AND key = $2 AND uri IS NULL")
+        .bind(did)
+        .bind(key)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
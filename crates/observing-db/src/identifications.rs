@@ -1,11 +1,17 @@
-use crate::types::{IdentificationRow, UpsertIdentificationParams};
-use sqlx::PgPool;
+use crate::community_ids::ConsensusWeighting;
+use crate::cursor::IdentificationCursor;
+use crate::live::{self, RecordChange};
+use crate::types::{
+    ConsensusRow, IdentificationConfidence, IdentificationListOptions, IdentificationListRow,
+    IdentificationRow, IdentificationSort, TaxonChangeRow, UpsertIdentificationParams,
+};
+use sqlx::{PgPool, Postgres, QueryBuilder};
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::Notify;
-use tracing::{error, trace};
+use tracing::{debug, error, trace};
 
 /// Upsert an identification record.
 ///
@@ -18,21 +24,27 @@ use tracing::{error, trace};
 /// Uses the dynamic query API rather than the `query!` macro so the new
 /// `accepted_taxon_key` column doesn't require regenerating the offline
 /// sqlx-prepare cache.
+///
+/// Announces the write on [`live::IDENTIFICATION_CHANNEL`].
 pub async fn upsert(pool: &PgPool, p: &UpsertIdentificationParams) -> Result<(), sqlx::Error> {
     sqlx::query(
         r#"
         INSERT INTO identifications (
             uri, cid, did, subject_uri, subject_cid, scientific_name,
-            taxon_rank, taxon_id, date_identified, kingdom, accepted_taxon_key
-        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            taxon_rank, taxon_id, date_identified, kingdom, accepted_taxon_key,
+            identification_qualifier, confidence
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
         ON CONFLICT (uri) DO UPDATE SET
             cid = $2,
             scientific_name = $6,
+            identification_qualifier = $12,
+            confidence = $13,
             taxon_rank = COALESCE($7, identifications.taxon_rank),
             taxon_id = COALESCE($8, identifications.taxon_id),
             kingdom = COALESCE($10, identifications.kingdom),
             accepted_taxon_key = COALESCE($11, identifications.accepted_taxon_key),
-            indexed_at = NOW()
+            indexed_at = NOW(),
+            deleted_at = NULL
         "#,
     )
     .bind(&p.uri)
@@ -46,21 +58,36 @@ pub async fn upsert(pool: &PgPool, p: &UpsertIdentificationParams) -> Result<(),
     .bind(p.date_identified)
     .bind(&p.kingdom)
     .bind(p.accepted_taxon_key)
+    .bind(&p.identification_qualifier)
+    .bind(p.confidence.map(IdentificationConfidence::as_str))
     .execute(pool)
     .await?;
 
+    let change = RecordChange::upsert(&p.uri, &p.did, &p.subject_uri);
+    live::announce(pool, live::IDENTIFICATION_CHANNEL, &change).await;
     Ok(())
 }
 
-/// Delete an identification.
+/// Soft-delete an identification: the row stays (so `taxon_change_log`
+/// credits still resolve) but drops out of reads and the consensus. Its
+/// free-text qualifier is cleared in the same update.
 ///
 /// Like [`upsert`], does NOT refresh the `community_ids` matview; callers
 /// drive that via a debounced [`CommunityIdsRefresher`] or a batch-end
-/// [`refresh_community_ids`].
+/// [`refresh_community_ids`]. Announces the delete on
+/// [`live::IDENTIFICATION_CHANNEL`].
 pub async fn delete(pool: &PgPool, uri: &str) -> Result<(), sqlx::Error> {
-    sqlx::query!("DELETE FROM identifications WHERE uri = $1", uri)
-        .execute(pool)
-        .await?;
+    sqlx::query!(
+        "UPDATE identifications SET deleted_at = NOW(), identification_qualifier = NULL \
+         WHERE uri = $1 AND deleted_at IS NULL",
+        uri
+    )
+    .execute(pool)
+    .await?;
+
+    if let Some(change) = RecordChange::delete(uri) {
+        live::announce(pool, live::IDENTIFICATION_CHANNEL, &change).await;
+    }
     Ok(())
 }
 
@@ -74,11 +101,11 @@ pub async fn get_for_occurrence(
         r#"
         SELECT
             uri, cid, did, subject_uri, subject_cid, scientific_name,
-            taxon_rank, identification_qualifier, taxon_id,
+            taxon_rank, identification_qualifier, confidence, taxon_id,
             identification_verification_status, type_status, date_identified,
             kingdom, phylum, class, "order" as order_, family, genus
         FROM identifications
-        WHERE subject_uri = $1
+        WHERE subject_uri = $1 AND deleted_at IS NULL
         ORDER BY date_identified DESC
         "#,
         occurrence_uri,
@@ -87,6 +114,98 @@ pub async fn get_for_occurrence(
     .await
 }
 
+/// Identifications for an occurrence, paged and flagged.
+///
+/// Each row says whether it was withdrawn (its identifier has since posted a
+/// newer identification, so only their latest counts toward the community
+/// ID) and whether it agrees with the current community ID. A cursor that
+/// doesn't belong to `options.sort` yields an empty page.
+pub async fn list_for_occurrence(
+    executor: impl sqlx::PgExecutor<'_>,
+    occurrence_uri: &str,
+    options: &IdentificationListOptions,
+) -> Result<Vec<IdentificationListRow>, sqlx::Error> {
+    let mut qb = list_for_occurrence_query(occurrence_uri, options);
+    qb.build_query_as::<IdentificationListRow>()
+        .fetch_all(executor)
+        .await
+}
+
+fn list_for_occurrence_query(
+    occurrence_uri: &str,
+    options: &IdentificationListOptions,
+) -> QueryBuilder<Postgres> {
+    let mut qb = QueryBuilder::<Postgres>::new(
+        r#"WITH listed AS (
+            SELECT
+                i.uri, i.cid, i.did, i.subject_uri, i.subject_cid, i.scientific_name,
+                i.taxon_rank, i.identification_qualifier, i.confidence, i.taxon_id,
+                i.identification_verification_status, i.type_status, i.date_identified,
+                i.kingdom, i.phylum, i.class, i."order", i.family, i.genus,
+                i.date_identified < MAX(i.date_identified) OVER (PARTITION BY i.did)
+                    AS is_withdrawn,
+                COALESCE(
+                    c.scientific_name = i.scientific_name
+                        AND c.kingdom IS NOT DISTINCT FROM i.kingdom,
+                    FALSE
+                ) AS agrees_with_community
+            FROM identifications i
+            LEFT JOIN community_ids c ON c.occurrence_uri = i.subject_uri
+            WHERE i.deleted_at IS NULL AND i.subject_uri = "#,
+    );
+    qb.push_bind(occurrence_uri.to_string());
+    qb.push(") SELECT * FROM listed WHERE TRUE");
+
+    if let Some(cursor) = options.cursor.as_ref() {
+        push_list_cursor(&mut qb, options.sort, cursor);
+    }
+
+    qb.push(match options.sort {
+        IdentificationSort::Recent => " ORDER BY date_identified DESC, uri DESC",
+        IdentificationSort::Agreement => {
+            " ORDER BY agrees_with_community DESC, date_identified DESC, uri DESC"
+        }
+    });
+
+    if let Some(limit) = options.limit {
+        qb.push(" LIMIT ");
+        qb.push_bind(limit);
+    }
+    qb
+}
+
+/// Keyset predicate for [`IdentificationListRow::cursor`]. The row tuple is
+/// compared in the same order as the sort so ties on `date_identified` can't
+/// skip or repeat rows.
+fn push_list_cursor(
+    qb: &mut QueryBuilder<Postgres>,
+    sort: IdentificationSort,
+    cursor: &IdentificationCursor,
+) {
+    let agrees = match (sort, cursor.agrees_with_community) {
+        (IdentificationSort::Recent, None) => None,
+        (IdentificationSort::Agreement, Some(agrees)) => Some(agrees),
+        _ => {
+            qb.push(" AND FALSE");
+            return;
+        }
+    };
+
+    qb.push(" AND (");
+    if agrees.is_some() {
+        qb.push("agrees_with_community, ");
+    }
+    qb.push("date_identified, uri) < (");
+    if let Some(agrees) = agrees {
+        qb.push_bind(agrees);
+        qb.push(", ");
+    }
+    qb.push_bind(cursor.date_identified);
+    qb.push(", ");
+    qb.push_bind(cursor.uri.clone());
+    qb.push(")");
+}
+
 /// Get identifications for multiple occurrences (batch)
 pub async fn get_for_subjects_batch(
     executor: impl sqlx::PgExecutor<'_>,
@@ -101,11 +220,11 @@ pub async fn get_for_subjects_batch(
         r#"
         SELECT
             uri, cid, did, subject_uri, subject_cid, scientific_name,
-            taxon_rank, identification_qualifier, taxon_id,
+            taxon_rank, identification_qualifier, confidence, taxon_id,
             identification_verification_status, type_status, date_identified,
             kingdom, phylum, class, "order" as order_, family, genus
         FROM identifications
-        WHERE subject_uri = ANY($1)
+        WHERE subject_uri = ANY($1) AND deleted_at IS NULL
         ORDER BY subject_uri, date_identified DESC
         "#,
         uris,
@@ -169,19 +288,122 @@ pub async fn get_community_ids_for_occurrences(
         .collect())
 }
 
-/// Refresh the community IDs materialized view.
+/// Community IDs for multiple occurrences (batch), with the vote counts and
+/// rank [`crate::quality::compute_grade`] needs. Occurrences without a
+/// consensus are absent from the map.
+pub async fn get_consensus_for_occurrences(
+    executor: impl sqlx::PgExecutor<'_>,
+    uris: &[String],
+) -> Result<HashMap<String, ConsensusRow>, sqlx::Error> {
+    if uris.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let rows = sqlx::query_as::<_, ConsensusRow>(GET_CONSENSUS_FOR_OCCURRENCES)
+        .bind(uris)
+        .fetch_all(executor)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| (r.occurrence_uri.clone(), r))
+        .collect())
+}
+
+/// `id_count` is already one vote per identifier (the view keeps each
+/// identifier's latest identification), and the weights are summed under
+/// the configured weighting. A taxon at species rank or below is the only
+/// kind with a `species_key`.
+const GET_CONSENSUS_FOR_OCCURRENCES: &str = r#"
+    SELECT
+        ci.occurrence_uri,
+        ci.scientific_name,
+        ci.id_count AS agreeing,
+        ci.vote_weight::float8 AS agreeing_weight,
+        ci.total_weight::float8 AS total_weight,
+        COALESCE(t.species_key IS NOT NULL, FALSE) AS at_species_rank
+    FROM community_ids ci
+    LEFT JOIN taxa t ON t.taxon_key = ci.accepted_taxon_key
+    WHERE ci.occurrence_uri = ANY($1)
+"#;
+
+/// Refresh the community IDs materialized view, then append any consensus
+/// changes to `taxon_change_log`.
 ///
 /// `REFRESH MATERIALIZED VIEW CONCURRENTLY` cannot run inside a transaction,
-/// so the executor must be a real connection/pool, not a transaction handle.
-/// On the firehose hot path prefer [`CommunityIdsRefresher`], which coalesces
-/// these calls; use this directly only for one-shot/batch refreshes.
-pub async fn refresh_community_ids(executor: impl sqlx::PgExecutor<'_>) -> Result<(), sqlx::Error> {
+/// so this takes the pool rather than a transaction handle. On the firehose
+/// hot path prefer [`CommunityIdsRefresher`], which coalesces these calls;
+/// use this directly only for one-shot/batch refreshes.
+pub async fn refresh_community_ids(pool: &PgPool) -> Result<(), sqlx::Error> {
     sqlx::query!("REFRESH MATERIALIZED VIEW CONCURRENTLY community_ids")
-        .execute(executor)
+        .execute(pool)
         .await?;
+    let logged = sqlx::query(LOG_CONSENSUS_CHANGES).execute(pool).await?;
+    if logged.rows_affected() > 0 {
+        debug!(
+            changes = logged.rows_affected(),
+            "logged community ID changes"
+        );
+    }
     Ok(())
 }
 
+/// Store the vote weighting the `community_ids` matview applies from its
+/// next refresh on. Returns whether the setting changed, i.e. whether the
+/// view needs a refresh to follow it.
+pub async fn set_consensus_weighting(
+    executor: impl sqlx::PgExecutor<'_>,
+    weighting: ConsensusWeighting,
+) -> Result<bool, sqlx::Error> {
+    let updated = sqlx::query(
+        "UPDATE consensus_settings SET weighting = $1 WHERE weighting IS DISTINCT FROM $1",
+    )
+    .bind(weighting.as_str())
+    .execute(executor)
+    .await?;
+    Ok(updated.rows_affected() > 0)
+}
+
+/// Log each occurrence whose consensus name differs from the last one in
+/// `taxon_change_log` (or that has none logged yet), crediting the newest
+/// live identification on it. Comparing against the log rather than a
+/// pre-refresh snapshot keeps it correct when several processes refresh.
+const LOG_CONSENSUS_CHANGES: &str = r#"
+    INSERT INTO taxon_change_log (occurrence_uri, old_name, new_name, triggered_by_uri)
+    SELECT ci.occurrence_uri, last.new_name, ci.scientific_name,
+           (SELECT i.uri FROM identifications i
+            WHERE i.subject_uri = ci.occurrence_uri AND i.deleted_at IS NULL
+            ORDER BY i.date_identified DESC, i.uri DESC
+            LIMIT 1)
+    FROM community_ids ci
+    LEFT JOIN LATERAL (
+        SELECT l.new_name FROM taxon_change_log l
+        WHERE l.occurrence_uri = ci.occurrence_uri
+        ORDER BY l.changed_at DESC, l.id DESC
+        LIMIT 1
+    ) last ON TRUE
+    WHERE last.new_name IS DISTINCT FROM ci.scientific_name
+"#;
+
+/// Consensus changes for an occurrence, newest first. The first-consensus
+/// baseline (no `old_name`) is left out: it isn't a change.
+pub async fn taxon_history(
+    executor: impl sqlx::PgExecutor<'_>,
+    occurrence_uri: &str,
+) -> Result<Vec<TaxonChangeRow>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT old_name, new_name, triggered_by_uri, changed_at
+        FROM taxon_change_log
+        WHERE occurrence_uri = $1 AND old_name IS NOT NULL
+        ORDER BY changed_at DESC, id DESC
+        "#,
+    )
+    .bind(occurrence_uri)
+    .fetch_all(executor)
+    .await
+}
+
 /// Coalesces `REFRESH MATERIALIZED VIEW CONCURRENTLY community_ids`.
 ///
 /// The `community_ids` matview aggregates the *entire* identifications⋈occurrences
@@ -251,3 +473,140 @@ impl CommunityIdsRefresher {
         self.notify.notify_one();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OCCURRENCE: &str = "at://did:plc:x/bio.lexicons.temp.v0-1.occurrence/1";
+
+    fn list_cursor(agrees_with_community: Option<bool>) -> IdentificationCursor {
+        IdentificationCursor {
+            agrees_with_community,
+            date_identified: "2026-06-02T21:13:49Z".parse().unwrap(),
+            uri: "at://did:plc:y/coll/rkey".into(),
+        }
+    }
+
+    fn sql(options: &IdentificationListOptions) -> String {
+        list_for_occurrence_query(OCCURRENCE, options)
+            .sql()
+            .as_str()
+            .to_string()
+    }
+
+    #[test]
+    fn consensus_log_only_inserts_when_the_name_changed() {
+        let sql = LOG_CONSENSUS_CHANGES;
+        // One row per occurrence at most: the comparison is against the
+        // single latest log entry, not every past one.
+        assert!(sql.contains("ORDER BY l.changed_at DESC, l.id DESC"));
+        assert!(sql.contains("LIMIT 1\n    ) last ON TRUE"));
+        assert!(sql.contains("WHERE last.new_name IS DISTINCT FROM ci.scientific_name"));
+    }
+
+    #[test]
+    fn consensus_log_never_credits_a_deleted_identification() {
+        assert!(LOG_CONSENSUS_CHANGES
+            .contains("WHERE i.subject_uri = ci.occurrence_uri AND i.deleted_at IS NULL"));
+    }
+
+    #[test]
+    fn default_listing_is_unbounded_newest_first() {
+        let sql = sql(&IdentificationListOptions::default());
+        assert!(
+            sql.contains("WHERE i.deleted_at IS NULL AND i.subject_uri = $1)"),
+            "got: {sql}"
+        );
+        assert!(
+            sql.ends_with("ORDER BY date_identified DESC, uri DESC"),
+            "got: {sql}"
+        );
+        assert!(!sql.contains("LIMIT"), "got: {sql}");
+    }
+
+    /// Tombstones are left out before the window function runs, so a
+    /// deleted newer identification doesn't mark the older one withdrawn.
+    #[test]
+    fn listing_skips_soft_deleted_before_flagging_withdrawn() {
+        let sql = sql(&IdentificationListOptions::default());
+        let filter = sql.find("i.deleted_at IS NULL").expect("tombstone filter");
+        let listed_end = sql.find(") SELECT * FROM listed").unwrap();
+        assert!(filter < listed_end, "got: {sql}");
+    }
+
+    #[test]
+    fn recent_page_resumes_after_cursor() {
+        let sql = sql(&IdentificationListOptions {
+            limit: Some(10),
+            cursor: Some(list_cursor(None)),
+            sort: IdentificationSort::Recent,
+        });
+        assert!(
+            sql.contains("AND (date_identified, uri) < ($2, $3)"),
+            "got: {sql}"
+        );
+        assert!(sql.ends_with("LIMIT $4"), "got: {sql}");
+    }
+
+    #[test]
+    fn agreement_page_orders_and_resumes_on_agreement_first() {
+        let sql = sql(&IdentificationListOptions {
+            limit: Some(10),
+            cursor: Some(list_cursor(Some(true))),
+            sort: IdentificationSort::Agreement,
+        });
+        assert!(
+            sql.contains("AND (agrees_with_community, date_identified, uri) < ($2, $3, $4)"),
+            "got: {sql}"
+        );
+        assert!(
+            sql.contains("ORDER BY agrees_with_community DESC, date_identified DESC, uri DESC"),
+            "got: {sql}"
+        );
+    }
+
+    #[test]
+    fn cursor_from_another_sort_yields_empty_page() {
+        let sql = sql(&IdentificationListOptions {
+            limit: None,
+            cursor: Some(list_cursor(None)),
+            sort: IdentificationSort::Agreement,
+        });
+        assert!(sql.contains("WHERE TRUE AND FALSE"), "got: {sql}");
+    }
+
+    #[test]
+    fn row_cursor_matches_sort() {
+        let row = IdentificationListRow {
+            row: IdentificationRow {
+                uri: "at://did:plc:y/coll/rkey".into(),
+                cid: "bafy".into(),
+                did: "did:plc:y".into(),
+                subject_uri: OCCURRENCE.into(),
+                subject_cid: "bafyocc".into(),
+                scientific_name: "Quercus alba".into(),
+                taxon_rank: None,
+                identification_qualifier: None,
+                confidence: None,
+                taxon_id: None,
+                identification_verification_status: None,
+                type_status: None,
+                date_identified: "2026-06-02T21:13:49Z".parse().unwrap(),
+                kingdom: None,
+                phylum: None,
+                class: None,
+                order_: None,
+                family: None,
+                genus: None,
+            },
+            is_withdrawn: false,
+            agrees_with_community: true,
+        };
+        assert_eq!(row.cursor(IdentificationSort::Recent), list_cursor(None));
+        assert_eq!(
+            row.cursor(IdentificationSort::Agreement),
+            list_cursor(Some(true))
+        );
+    }
+}
@@ -1,4 +1,8 @@
-use crate::types::{InteractionRow, UpsertInteractionParams};
+use std::collections::HashMap;
+
+use sqlx::{FromRow, Postgres, QueryBuilder};
+
+use crate::types::{InteractionRole, InteractionRow, TaxonInteraction, UpsertInteractionParams};
 
 /// Upsert an interaction record
 pub async fn upsert(
@@ -136,3 +140,241 @@ pub async fn get(
     .fetch_optional(executor)
     .await
 }
+
+/// Interactions recorded with `taxon_name` on either side, grouped by
+/// interaction type and partner taxon and normalized to the taxon's role, so
+/// an `AtoB` record with the taxon as subject B reads the same as a `BtoA`
+/// one with it as subject A. Names match exactly; `kingdom` narrows to
+/// subjects recorded in it (or with no kingdom). Most frequent first.
+pub async fn for_taxon(
+    executor: impl sqlx::PgExecutor<'_>,
+    taxon_name: &str,
+    kingdom: Option<&str>,
+    limit: usize,
+    hidden_dids: &[String],
+) -> Result<Vec<TaxonInteraction>, sqlx::Error> {
+    let sides = taxon_interactions_query(taxon_name, kingdom, hidden_dids)
+        .build_query_as::<TaxonInteractionSide>()
+        .fetch_all(executor)
+        .await?;
+    Ok(normalize_taxon_interactions(sides, limit))
+}
+
+/// Grouped interactions with the taxon on one subject, before normalizing
+/// `direction` to the taxon's role.
+#[derive(Debug, FromRow)]
+struct TaxonInteractionSide {
+    interaction_type: String,
+    direction: String,
+    taxon_is_subject_a: bool,
+    partner_taxon_name: String,
+    partner_kingdom: Option<String>,
+    interaction_count: i64,
+}
+
+fn taxon_interactions_query(
+    taxon_name: &str,
+    kingdom: Option<&str>,
+    hidden_dids: &[String],
+) -> QueryBuilder<Postgres> {
+    let mut qb = QueryBuilder::<Postgres>::new(
+        "SELECT interaction_type, direction, taxon_is_subject_a, \
+         partner_taxon_name, partner_kingdom, COUNT(*) AS interaction_count FROM (",
+    );
+    push_taxon_side(
+        &mut qb,
+        "SELECT interaction_type, direction, TRUE AS taxon_is_subject_a, \
+         subject_b_taxon_name AS partner_taxon_name, subject_b_kingdom AS partner_kingdom \
+         FROM interactions WHERE subject_b_taxon_name IS NOT NULL AND subject_a_taxon_name = ",
+        " AND (subject_a_kingdom IS NULL OR subject_a_kingdom = ",
+        taxon_name,
+        kingdom,
+        hidden_dids,
+    );
+    qb.push(" UNION ALL ");
+    push_taxon_side(
+        &mut qb,
+        "SELECT interaction_type, direction, FALSE AS taxon_is_subject_a, \
+         subject_a_taxon_name AS partner_taxon_name, subject_a_kingdom AS partner_kingdom \
+         FROM interactions WHERE subject_a_taxon_name IS NOT NULL AND subject_b_taxon_name = ",
+        " AND (subject_b_kingdom IS NULL OR subject_b_kingdom = ",
+        taxon_name,
+        kingdom,
+        hidden_dids,
+    );
+    qb.push(
+        ") sides GROUP BY interaction_type, direction, taxon_is_subject_a, \
+         partner_taxon_name, partner_kingdom",
+    );
+    qb
+}
+
+/// One arm of the union in [`taxon_interactions_query`]: `select` ends in
+/// the taxon name comparison, `kingdom_filter` in the kingdom comparison.
+fn push_taxon_side(
+    qb: &mut QueryBuilder<Postgres>,
+    select: &'static str,
+    kingdom_filter: &'static str,
+    taxon_name: &str,
+    kingdom: Option<&str>,
+    hidden_dids: &[String],
+) {
+    qb.push(select);
+    qb.push_bind(taxon_name);
+    if let Some(kingdom) = kingdom {
+        qb.push(kingdom_filter);
+        qb.push_bind(kingdom);
+        qb.push(")");
+    }
+    if !hidden_dids.is_empty() {
+        qb.push(" AND did != ALL(");
+        qb.push_bind(hidden_dids.to_vec());
+        qb.push(")");
+    }
+}
+
+/// Merge per-side groups that mean the same thing from the taxon's side,
+/// most frequent first, keeping at most `limit`.
+fn normalize_taxon_interactions(
+    sides: Vec<TaxonInteractionSide>,
+    limit: usize,
+) -> Vec<TaxonInteraction> {
+    let mut counts: HashMap<(String, InteractionRole, String, Option<String>), i64> =
+        HashMap::new();
+    for side in sides {
+        let role = InteractionRole::from_direction(&side.direction, side.taxon_is_subject_a);
+        *counts
+            .entry((
+                side.interaction_type,
+                role,
+                side.partner_taxon_name,
+                side.partner_kingdom,
+            ))
+            .or_default() += side.interaction_count;
+    }
+
+    let mut interactions: Vec<TaxonInteraction> = counts
+        .into_iter()
+        .map(
+            |((interaction_type, role, partner_taxon_name, partner_kingdom), interaction_count)| {
+                TaxonInteraction {
+                    interaction_type,
+                    role,
+                    partner_taxon_name,
+                    partner_kingdom,
+                    interaction_count,
+                }
+            },
+        )
+        .collect();
+    interactions.sort_by(|a, b| {
+        b.interaction_count
+            .cmp(&a.interaction_count)
+            .then_with(|| a.interaction_type.cmp(&b.interaction_type))
+            .then_with(|| a.partner_taxon_name.cmp(&b.partner_taxon_name))
+            .then_with(|| a.partner_kingdom.cmp(&b.partner_kingdom))
+            .then_with(|| a.role.cmp(&b.role))
+    });
+    interactions.truncate(limit);
+    interactions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn side(
+        interaction_type: &str,
+        direction: &str,
+        taxon_is_subject_a: bool,
+        partner: &str,
+        count: i64,
+    ) -> TaxonInteractionSide {
+        TaxonInteractionSide {
+            interaction_type: interaction_type.into(),
+            direction: direction.into(),
+            taxon_is_subject_a,
+            partner_taxon_name: partner.into(),
+            partner_kingdom: Some("Plantae".into()),
+            interaction_count: count,
+        }
+    }
+
+    #[test]
+    fn a_to_b_and_b_to_a_normalize_to_the_taxons_side() {
+        // Apis mellifera pollinates Trifolium repens, recorded both ways
+        // round: bee as A with AtoB, and bee as B with BtoA.
+        let merged = normalize_taxon_interactions(
+            vec![
+                side("pollinates", "AtoB", true, "Trifolium repens", 3),
+                side("pollinates", "BtoA", false, "Trifolium repens", 2),
+            ],
+            10,
+        );
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].role, InteractionRole::Actor);
+        assert_eq!(merged[0].interaction_count, 5);
+
+        // Seen from the clover, the same records make it the target.
+        let merged = normalize_taxon_interactions(
+            vec![
+                side("pollinates", "AtoB", false, "Apis mellifera", 3),
+                side("pollinates", "BtoA", true, "Apis mellifera", 2),
+            ],
+            10,
+        );
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].role, InteractionRole::Target);
+        assert_eq!(merged[0].interaction_count, 5);
+    }
+
+    #[test]
+    fn bidirectional_records_are_mutual_from_either_side() {
+        for taxon_is_subject_a in [true, false] {
+            assert_eq!(
+                InteractionRole::from_direction("bidirectional", taxon_is_subject_a),
+                InteractionRole::Mutual
+            );
+        }
+    }
+
+    #[test]
+    fn roles_and_types_stay_separate_and_sort_by_count() {
+        let merged = normalize_taxon_interactions(
+            vec![
+                side("eats", "AtoB", true, "Quercus robur", 1),
+                side("eats", "BtoA", true, "Quercus robur", 4),
+                side("parasitizes", "AtoB", true, "Quercus robur", 2),
+            ],
+            2,
+        );
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].role, InteractionRole::Target);
+        assert_eq!(merged[0].interaction_count, 4);
+        assert_eq!(merged[1].interaction_type, "parasitizes");
+    }
+
+    #[test]
+    fn query_matches_the_taxon_on_either_subject() {
+        let hidden = vec!["did:plc:hidden".to_string()];
+        let qb = taxon_interactions_query("Apis mellifera", Some("Animalia"), &hidden);
+        let sql = qb.sql();
+        let sql = sql.as_str();
+        assert!(sql.contains("subject_a_taxon_name = $1"));
+        assert!(sql.contains("(subject_a_kingdom IS NULL OR subject_a_kingdom = $2)"));
+        assert!(sql.contains(" UNION ALL "));
+        assert!(sql.contains("subject_b_taxon_name = $4"));
+        assert!(sql.contains("(subject_b_kingdom IS NULL OR subject_b_kingdom = $5)"));
+        assert_eq!(sql.matches("did != ALL(").count(), 2);
+        assert!(sql.ends_with("partner_taxon_name, partner_kingdom"));
+    }
+
+    #[test]
+    fn query_without_kingdom_skips_the_kingdom_filter() {
+        let qb = taxon_interactions_query("Apis mellifera", None, &[]);
+        let sql = qb.sql();
+        let sql = sql.as_str();
+        assert!(!sql.contains("kingdom = $"));
+        assert!(!sql.contains("did != ALL("));
+    }
+}
@@ -1,13 +1,18 @@
 pub mod comments;
 pub mod community_ids;
+pub mod cursor;
 pub mod failed_records;
 pub mod feeds;
+pub mod idempotency;
 pub mod identifications;
 pub mod interactions;
 pub mod likes;
+pub mod live;
 pub mod migrate;
 pub mod notifications;
 pub mod oauth;
+pub mod observers;
+pub mod occurrence_writes;
 pub mod occurrences;
 pub mod private_data;
 #[cfg(feature = "processing")]
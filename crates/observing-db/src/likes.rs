@@ -1,11 +1,11 @@
-use crate::types::CreateLikeParams;
+use crate::live::{self, RecordChange};
+use crate::types::{CreateLikeParams, LikerRow, LikersOptions};
+use sqlx::{PgPool, Postgres, QueryBuilder};
 use std::collections::{HashMap, HashSet};
 
-/// Create a like (no-op if already exists for subject+user)
-pub async fn create(
-    executor: impl sqlx::PgExecutor<'_>,
-    p: &CreateLikeParams,
-) -> Result<(), sqlx::Error> {
+/// Create a like (no-op if already exists for subject+user), then announce it
+/// on [`live::LIKE_CHANNEL`].
+pub async fn create(pool: &PgPool, p: &CreateLikeParams) -> Result<(), sqlx::Error> {
     sqlx::query!(
         r#"
         INSERT INTO likes (uri, cid, did, subject_uri, subject_cid, created_at)
@@ -19,16 +19,23 @@ pub async fn create(
         p.subject_cid,
         p.created_at,
     )
-    .execute(executor)
+    .execute(pool)
     .await?;
+
+    let change = RecordChange::upsert(&p.uri, &p.did, &p.subject_uri);
+    live::announce(pool, live::LIKE_CHANNEL, &change).await;
     Ok(())
 }
 
-/// Delete a like by URI
-pub async fn delete(executor: impl sqlx::PgExecutor<'_>, uri: &str) -> Result<(), sqlx::Error> {
+/// Delete a like by URI, then announce it on [`live::LIKE_CHANNEL`].
+pub async fn delete(pool: &PgPool, uri: &str) -> Result<(), sqlx::Error> {
     sqlx::query!("DELETE FROM likes WHERE uri = $1", uri)
-        .execute(executor)
+        .execute(pool)
         .await?;
+
+    if let Some(change) = RecordChange::delete(uri) {
+        live::announce(pool, live::LIKE_CHANNEL, &change).await;
+    }
     Ok(())
 }
 
@@ -93,3 +100,80 @@ pub async fn get_user_like_statuses(
 
     Ok(rows.into_iter().map(|r| r.subject_uri).collect())
 }
+
+/// Who liked `subject_uri`, newest like first. Page with
+/// [`LikersOptions::cursor`] set from the previous page's last row.
+pub async fn get_likers(
+    executor: impl sqlx::PgExecutor<'_>,
+    subject_uri: &str,
+    options: &LikersOptions,
+) -> Result<Vec<LikerRow>, sqlx::Error> {
+    likers_query(subject_uri, options)
+        .build_query_as::<LikerRow>()
+        .fetch_all(executor)
+        .await
+}
+
+fn likers_query(subject_uri: &str, options: &LikersOptions) -> QueryBuilder<Postgres> {
+    let mut qb = QueryBuilder::<Postgres>::new(
+        "SELECT uri, did, created_at FROM likes WHERE subject_uri = ",
+    );
+    qb.push_bind(subject_uri.to_string());
+
+    // `likes.created_at` has no time zone, so compare against the cursor's
+    // UTC wall time rather than casting to timestamptz.
+    if let Some(cursor) = &options.cursor {
+        qb.push(" AND (created_at, uri) < (");
+        qb.push_bind(cursor.created_at.naive_utc());
+        qb.push("::timestamp, ");
+        qb.push_bind(cursor.uri.clone());
+        qb.push(")");
+    }
+
+    qb.push(" ORDER BY created_at DESC, uri DESC LIMIT ");
+    qb.push_bind(options.limit.unwrap_or(50));
+    qb
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::FeedCursor;
+    use chrono::{TimeZone, Utc};
+
+    const OCCURRENCE: &str = "at://did:plc:x/bio.lexicons.temp.v0-1.occurrence/1";
+
+    fn sql(options: &LikersOptions) -> String {
+        likers_query(OCCURRENCE, options).sql().as_str().to_string()
+    }
+
+    #[test]
+    fn first_page_is_newest_first_and_bounded() {
+        let sql = sql(&LikersOptions::default());
+        assert_eq!(
+            sql,
+            "SELECT uri, did, created_at FROM likes WHERE subject_uri = $1 \
+             ORDER BY created_at DESC, uri DESC LIMIT $2"
+        );
+    }
+
+    #[test]
+    fn later_pages_continue_after_the_cursor() {
+        let sql = sql(&LikersOptions {
+            limit: Some(10),
+            cursor: Some(FeedCursor::new(
+                Utc.with_ymd_and_hms(2026, 6, 2, 21, 13, 49).unwrap(),
+                "at://did:plc:y/app.bsky.feed.like/3".into(),
+            )),
+        });
+        // The keyset tuple matches the ORDER BY so likes sharing a timestamp
+        // are neither skipped nor repeated across pages.
+        assert!(
+            sql.contains(
+                "WHERE subject_uri = $1 AND (created_at, uri) < ($2::timestamp, $3) \
+                 ORDER BY created_at DESC, uri DESC LIMIT $4"
+            ),
+            "got: {sql}"
+        );
+    }
+}
@@ -0,0 +1,177 @@
+//! Change notifications for real-time consumers.
+//!
+//! The upsert/delete functions announce each write with `pg_notify` on a
+//! per-record-kind channel; any service can `LISTEN` on those channels (e.g.
+//! via `sqlx::postgres::PgListener`) and decode the payload instead of
+//! polling. Emission is compiled in by the default `notify` feature; batch
+//! backfills build without it so a bulk load doesn't flood listeners.
+//!
+//! The announcing write functions take `&PgPool` rather than an executor:
+//! each write autocommits, then its notification goes out as a separate
+//! autocommit statement, so a listener that re-reads the row always sees it.
+//! They can't join a caller's transaction. A write that needs one should go
+//! through the transaction and call [`notify`] once it has committed.
+
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+/// `LISTEN`/`NOTIFY` channel carrying [`OccurrenceChange`] payloads.
+pub const OCCURRENCE_CHANNEL: &str = "occurrence_changed";
+
+/// `LISTEN`/`NOTIFY` channel carrying [`RecordChange`] payloads for
+/// identifications.
+pub const IDENTIFICATION_CHANNEL: &str = "identification_changed";
+
+/// `LISTEN`/`NOTIFY` channel carrying [`RecordChange`] payloads for comments.
+pub const COMMENT_CHANNEL: &str = "comment_changed";
+
+/// `LISTEN`/`NOTIFY` channel carrying [`RecordChange`] payloads for likes.
+pub const LIKE_CHANNEL: &str = "like_changed";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeAction {
+    Upsert,
+    Delete,
+}
+
+/// JSON payload of an [`OCCURRENCE_CHANNEL`] notification.
+///
+/// Carries just enough for listeners to route the change (who, where, what
+/// happened); consumers that need the full row re-read it by `uri`. Deletes
+/// have no coordinates since the row is already gone.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OccurrenceChange {
+    pub uri: String,
+    pub did: String,
+    pub action: ChangeAction,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+}
+
+/// JSON payload of an identification, comment, or like notification.
+///
+/// `subject_uri` is the occurrence the record is attached to, so caches keyed
+/// by occurrence can invalidate without a lookup. Deletes don't know it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordChange {
+    pub uri: String,
+    pub did: String,
+    pub action: ChangeAction,
+    pub subject_uri: Option<String>,
+}
+
+impl RecordChange {
+    pub(crate) fn upsert(uri: &str, did: &str, subject_uri: &str) -> Self {
+        Self {
+            uri: uri.to_string(),
+            did: did.to_string(),
+            action: ChangeAction::Upsert,
+            subject_uri: Some(subject_uri.to_string()),
+        }
+    }
+
+    /// `None` when `uri` isn't an AT URI we can take the repo DID from.
+    pub(crate) fn delete(uri: &str) -> Option<Self> {
+        Some(Self {
+            uri: uri.to_string(),
+            did: did_from_at_uri(uri)?.to_string(),
+            action: ChangeAction::Delete,
+            subject_uri: None,
+        })
+    }
+}
+
+/// Publish `payload` as JSON on `channel`.
+///
+/// Runs `pg_notify` as its own autocommit statement on `pool`, so it's
+/// delivered straight away and isn't tied to any transaction the caller has
+/// open. Call it only after the change it announces has committed, or a
+/// listener may re-read before the change is visible.
+pub async fn notify(
+    pool: &PgPool,
+    channel: &str,
+    payload: &impl Serialize,
+) -> Result<(), sqlx::Error> {
+    let payload = serde_json::to_string(payload)
+        .map_err(|e| sqlx::Error::Protocol(format!("encode change payload: {e}")))?;
+    sqlx::query("SELECT pg_notify($1, $2)")
+        .bind(channel)
+        .bind(payload)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Best-effort notification from a write path. The write already succeeded,
+/// so a failed NOTIFY is logged rather than surfaced (which would make callers
+/// retry a committed write). A no-op without the `notify` feature.
+#[cfg_attr(not(feature = "notify"), allow(unused_variables))]
+pub(crate) async fn announce(pool: &PgPool, channel: &str, payload: &(impl Serialize + Sync)) {
+    #[cfg(feature = "notify")]
+    if let Err(e) = notify(pool, channel, payload).await {
+        tracing::warn!(error = %e, channel, "Failed to send change notification");
+    }
+}
+
+/// Extract the repo DID from an `at://{did}/{collection}/{rkey}` URI.
+pub(crate) fn did_from_at_uri(uri: &str) -> Option<&str> {
+    uri.strip_prefix("at://")?
+        .split('/')
+        .next()
+        .filter(|did| !did.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn change_payload_round_trips_as_camel_case_json() {
+        let change = OccurrenceChange {
+            uri: "at://did:plc:abc/bio.lexicons.temp.v0-1.occurrence/1".into(),
+            did: "did:plc:abc".into(),
+            action: ChangeAction::Upsert,
+            latitude: Some(37.5),
+            longitude: Some(-122.25),
+        };
+        let json = serde_json::to_value(&change).unwrap();
+        assert_eq!(json["action"], "upsert");
+        assert_eq!(json["latitude"], 37.5);
+        let back: OccurrenceChange = serde_json::from_value(json).unwrap();
+        assert_eq!(back, change);
+    }
+
+    #[test]
+    fn record_change_carries_subject_on_upsert_only() {
+        let upsert = RecordChange::upsert(
+            "at://did:plc:abc/bio.lexicons.temp.v0-1.identification/1",
+            "did:plc:abc",
+            "at://did:plc:xyz/bio.lexicons.temp.v0-1.occurrence/1",
+        );
+        let json = serde_json::to_value(&upsert).unwrap();
+        assert_eq!(
+            json["subjectUri"],
+            "at://did:plc:xyz/bio.lexicons.temp.v0-1.occurrence/1"
+        );
+
+        let delete =
+            RecordChange::delete("at://did:plc:abc/bio.lexicons.temp.v0-1.identification/1")
+                .unwrap();
+        assert_eq!(delete.did, "did:plc:abc");
+        assert_eq!(delete.action, ChangeAction::Delete);
+        assert_eq!(delete.subject_uri, None);
+    }
+
+    #[test]
+    fn did_from_at_uri_takes_authority() {
+        assert_eq!(
+            did_from_at_uri("at://did:plc:abc/bio.lexicons.temp.v0-1.occurrence/1"),
+            Some("did:plc:abc")
+        );
+        assert_eq!(did_from_at_uri("https://example.com/x"), None);
+        assert_eq!(did_from_at_uri("at:///coll/rkey"), None);
+    }
+}
@@ -1,13 +1,247 @@
+use sqlx::migrate::Migrator;
 use sqlx::PgPool;
 use tracing::info;
 
+static MIGRATOR: Migrator = sqlx::migrate!();
+
 /// Run all database migrations (versioned, tracked in `_sqlx_migrations` table)
 pub async fn migrate(pool: &PgPool) -> Result<(), sqlx::Error> {
     info!("Running database migrations...");
-    sqlx::migrate!()
+    MIGRATOR
         .run(pool)
         .await
         .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
     info!("Database migrations completed");
     Ok(())
 }
+
+/// Where one migration stands relative to the database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    /// Applied, but the file has changed since (checksum differs).
+    Modified,
+    /// Recorded as attempted but not successful.
+    Failed,
+    /// Recorded in the database but absent from this build.
+    Unknown,
+}
+
+impl MigrationState {
+    /// Whether the database disagrees with this build in a way running
+    /// migrations won't fix.
+    pub fn is_drift(self) -> bool {
+        matches!(self, Self::Modified | Self::Failed | Self::Unknown)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    pub state: MigrationState,
+}
+
+/// A row of `_sqlx_migrations`.
+struct AppliedMigration {
+    version: i64,
+    checksum: Vec<u8>,
+    success: bool,
+}
+
+/// Compare the migrations embedded in this build with those recorded in
+/// the database, without creating the tracking table if it's missing.
+pub async fn status(pool: &PgPool) -> Result<Vec<MigrationStatus>, sqlx::Error> {
+    let tracked: bool = sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+        .fetch_one(pool)
+        .await?;
+    let applied = if tracked {
+        sqlx::query_as::<_, (i64, Vec<u8>, bool)>(
+            "SELECT version, checksum, success FROM _sqlx_migrations ORDER BY version",
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|(version, checksum, success)| AppliedMigration {
+            version,
+            checksum,
+            success,
+        })
+        .collect()
+    } else {
+        Vec::new()
+    };
+    Ok(reconcile(&MIGRATOR, &applied))
+}
+
+/// Whether `occurrences.location` carries the GiST index that radius and
+/// bounding-box queries rely on.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpatialIndexStatus {
+    Present {
+        index: String,
+    },
+    /// No GiST index on `location`. `plan_cost` is the planner's total cost
+    /// estimate for a representative radius query, which without the index
+    /// degrades to a sequential scan of every occurrence.
+    Missing {
+        plan_cost: Option<f64>,
+    },
+}
+
+/// Representative radius query, planned (not run) when the index is missing.
+const RADIUS_QUERY_PLAN: &str = r#"
+    EXPLAIN (FORMAT JSON)
+    SELECT uri FROM occurrences
+    WHERE ST_DWithin(location, ST_SetSRID(ST_MakePoint(0, 0), 4326)::geography, 10000)
+"#;
+
+/// Check for a GiST index on `occurrences(location)`. The initial migration
+/// creates one, but a manual rebuild or restore can leave it out, and
+/// nothing else notices until nearby queries time out.
+pub async fn spatial_index_status(pool: &PgPool) -> Result<SpatialIndexStatus, sqlx::Error> {
+    let indexes = sqlx::query_as::<_, (String, String)>(
+        r#"
+        SELECT c.relname::text, pg_get_indexdef(i.indexrelid)
+        FROM pg_index i
+        JOIN pg_class c ON c.oid = i.indexrelid
+        WHERE i.indrelid = 'occurrences'::regclass AND i.indisvalid
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+    if let Some((index, _)) = indexes.into_iter().find(|(_, def)| is_location_gist(def)) {
+        return Ok(SpatialIndexStatus::Present { index });
+    }
+    let plan: serde_json::Value = sqlx::query_scalar(RADIUS_QUERY_PLAN)
+        .fetch_one(pool)
+        .await?;
+    Ok(SpatialIndexStatus::Missing {
+        plan_cost: plan_total_cost(&plan),
+    })
+}
+
+/// Whether an index definition (as printed by `pg_get_indexdef`) is a GiST
+/// index whose leading column is `location`.
+fn is_location_gist(indexdef: &str) -> bool {
+    let def = indexdef.to_ascii_lowercase();
+    def.split_once(" using gist (")
+        .is_some_and(|(_, cols)| cols.starts_with("location,") || cols.starts_with("location)"))
+}
+
+/// Top-level `Total Cost` from `EXPLAIN (FORMAT JSON)` output.
+fn plan_total_cost(plan: &serde_json::Value) -> Option<f64> {
+    plan.get(0)?.get("Plan")?.get("Total Cost")?.as_f64()
+}
+
+fn reconcile(migrator: &Migrator, applied: &[AppliedMigration]) -> Vec<MigrationStatus> {
+    let mut statuses: Vec<MigrationStatus> = migrator
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+        .map(|m| {
+            let state = match applied.iter().find(|a| a.version == m.version) {
+                None => MigrationState::Pending,
+                Some(a) if !a.success => MigrationState::Failed,
+                Some(a) if *a.checksum != *m.checksum => MigrationState::Modified,
+                Some(_) => MigrationState::Applied,
+            };
+            MigrationStatus {
+                version: m.version,
+                description: m.description.to_string(),
+                state,
+            }
+        })
+        .collect();
+    for a in applied {
+        if !statuses.iter().any(|s| s.version == a.version) {
+            statuses.push(MigrationStatus {
+                version: a.version,
+                description: String::new(),
+                state: MigrationState::Unknown,
+            });
+        }
+    }
+    statuses.sort_by_key(|s| s.version);
+    statuses
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all_applied() -> Vec<AppliedMigration> {
+        MIGRATOR
+            .iter()
+            .filter(|m| !m.migration_type.is_down_migration())
+            .map(|m| AppliedMigration {
+                version: m.version,
+                checksum: m.checksum.to_vec(),
+                success: true,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn location_gist_index_is_recognised() {
+        assert!(is_location_gist(
+            "CREATE INDEX occurrences_location_idx ON public.occurrences USING gist (location)"
+        ));
+        assert!(is_location_gist(
+            "CREATE INDEX occ_loc_date ON public.occurrences USING gist (location, event_date)"
+        ));
+    }
+
+    #[test]
+    fn other_indexes_do_not_count_as_spatial() {
+        for def in [
+            "CREATE INDEX occurrences_did_idx ON public.occurrences USING btree (did)",
+            "CREATE INDEX occurrences_location_btree ON public.occurrences USING btree (location)",
+            "CREATE INDEX occurrences_loc_idx ON public.occurrences USING gist (location_geom)",
+            "CREATE INDEX occ_date_loc ON public.occurrences USING gist (event_date, location)",
+        ] {
+            assert!(!is_location_gist(def), "{def}");
+        }
+    }
+
+    #[test]
+    fn plan_total_cost_reads_the_root_node() {
+        let plan = serde_json::json!([{
+            "Plan": {"Node Type": "Seq Scan", "Startup Cost": 0.0, "Total Cost": 48213.5}
+        }]);
+        assert_eq!(plan_total_cost(&plan), Some(48213.5));
+        assert_eq!(plan_total_cost(&serde_json::json!([])), None);
+    }
+
+    #[test]
+    fn fresh_database_reports_everything_pending() {
+        let statuses = reconcile(&MIGRATOR, &[]);
+        assert!(!statuses.is_empty());
+        assert!(statuses.iter().all(|s| s.state == MigrationState::Pending));
+    }
+
+    #[test]
+    fn migrated_database_reports_everything_applied() {
+        let statuses = reconcile(&MIGRATOR, &all_applied());
+        assert!(statuses.iter().all(|s| s.state == MigrationState::Applied));
+    }
+
+    #[test]
+    fn drift_is_reported() {
+        let mut applied = all_applied();
+        applied[0].checksum = vec![0];
+        applied[1].success = false;
+        applied.push(AppliedMigration {
+            version: 1,
+            checksum: vec![],
+            success: true,
+        });
+
+        let statuses = reconcile(&MIGRATOR, &applied);
+        assert_eq!(statuses[0].version, 1);
+        assert_eq!(statuses[0].state, MigrationState::Unknown);
+        assert_eq!(statuses[1].state, MigrationState::Modified);
+        assert_eq!(statuses[2].state, MigrationState::Failed);
+        assert!(statuses.iter().skip(3).all(|s| !s.state.is_drift()));
+    }
+}
@@ -1,3 +1,6 @@
+use chrono::{DateTime, Utc};
+use sqlx::FromRow;
+
 // OAuth state methods (for PKCE flow, short-lived)
 
 /// Get OAuth state value (only if not expired)
@@ -99,3 +102,92 @@ pub async fn delete_session(
         .await?;
     Ok(())
 }
+
+// Per-device sessions (one row per browser login, for listing and revocation)
+
+/// A browser that completed the OAuth login for a DID.
+#[derive(Debug, Clone, FromRow)]
+pub struct DeviceSession {
+    pub id: String,
+    pub did: String,
+    pub user_agent: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: DateTime<Utc>,
+}
+
+/// Record a new device login for `did` and return its session id.
+pub async fn create_device_session(
+    executor: impl sqlx::PgExecutor<'_>,
+    did: &str,
+    user_agent: Option<&str>,
+) -> Result<String, sqlx::Error> {
+    sqlx::query_scalar(
+        "INSERT INTO oauth_device_sessions (did, user_agent) VALUES ($1, $2) RETURNING id",
+    )
+    .bind(did)
+    .bind(user_agent)
+    .fetch_one(executor)
+    .await
+}
+
+/// Whether device session `id` is still live for `did`, bumping its
+/// `last_used_at` when it was last bumped more than five minutes ago (so an
+/// active browser doesn't write on every request).
+pub async fn touch_device_session(
+    executor: impl sqlx::PgExecutor<'_>,
+    id: &str,
+    did: &str,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"WITH touched AS (
+            UPDATE oauth_device_sessions SET last_used_at = NOW()
+            WHERE id = $1 AND did = $2 AND last_used_at < NOW() - INTERVAL '5 minutes'
+            RETURNING 1
+        )
+        SELECT EXISTS (SELECT 1 FROM oauth_device_sessions WHERE id = $1 AND did = $2)"#,
+    )
+    .bind(id)
+    .bind(did)
+    .fetch_one(executor)
+    .await
+}
+
+/// Device sessions for `did`, most recently used first.
+pub async fn list_device_sessions(
+    executor: impl sqlx::PgExecutor<'_>,
+    did: &str,
+) -> Result<Vec<DeviceSession>, sqlx::Error> {
+    sqlx::query_as::<_, DeviceSession>(
+        r#"SELECT id, did, user_agent, created_at, last_used_at
+        FROM oauth_device_sessions
+        WHERE did = $1
+        ORDER BY last_used_at DESC"#,
+    )
+    .bind(did)
+    .fetch_all(executor)
+    .await
+}
+
+/// End one device session.
+pub async fn delete_device_session(
+    executor: impl sqlx::PgExecutor<'_>,
+    id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM oauth_device_sessions WHERE id = $1")
+        .bind(id)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+/// End every device session for `did`, returning how many there were.
+pub async fn delete_device_sessions(
+    executor: impl sqlx::PgExecutor<'_>,
+    did: &str,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM oauth_device_sessions WHERE did = $1")
+        .bind(did)
+        .execute(executor)
+        .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,107 @@
+//! Observer-level aggregates: who has been recording where.
+
+use sqlx::{Postgres, QueryBuilder};
+
+use crate::types::{NearbyObserverRow, NearbyObserversOptions};
+
+/// Observers with at least one occurrence within `options.area` created in
+/// the trailing `options.window`, most active first.
+///
+/// Uses the same `ST_DWithin` geography test as
+/// [`crate::occurrences::get_nearby`]. `exclude_did` drops the requesting
+/// viewer so nobody is suggested to themselves.
+pub async fn nearby_observers(
+    executor: impl sqlx::PgExecutor<'_>,
+    options: &NearbyObserversOptions,
+    hidden_dids: &[String],
+) -> Result<Vec<NearbyObserverRow>, sqlx::Error> {
+    let mut qb = nearby_observers_query(options, hidden_dids);
+    qb.build_query_as::<NearbyObserverRow>()
+        .fetch_all(executor)
+        .await
+}
+
+fn nearby_observers_query(
+    options: &NearbyObserversOptions,
+    hidden_dids: &[String],
+) -> QueryBuilder<Postgres> {
+    let mut qb = QueryBuilder::<Postgres>::new(
+        "SELECT did, COUNT(*) AS occurrence_count, MAX(created_at) AS last_observed \
+         FROM occurrences WHERE ST_DWithin(location, ST_SetSRID(ST_MakePoint(",
+    );
+    qb.push_bind(options.area.lng);
+    qb.push(", ");
+    qb.push_bind(options.area.lat);
+    qb.push("), 4326)::geography, ");
+    qb.push_bind(options.area.radius_meters);
+    qb.push(") AND created_at >= NOW() - make_interval(secs => ");
+    qb.push_bind(options.window.num_seconds() as f64);
+    qb.push(")");
+
+    if let Some(did) = options.exclude_did.as_deref() {
+        qb.push(" AND did != ");
+        qb.push_bind(did.to_string());
+    }
+
+    if !hidden_dids.is_empty() {
+        qb.push(" AND did != ALL(");
+        qb.push_bind(hidden_dids.to_vec());
+        qb.push(")");
+    }
+
+    qb.push(" GROUP BY did ORDER BY occurrence_count DESC, last_observed DESC LIMIT ");
+    qb.push_bind(options.limit);
+    qb
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::NearbyArea;
+
+    fn options(exclude_did: Option<&str>) -> NearbyObserversOptions {
+        NearbyObserversOptions {
+            area: NearbyArea {
+                lat: 37.77,
+                lng: -122.42,
+                radius_meters: 5_000.0,
+            },
+            window: chrono::Duration::days(30),
+            exclude_did: exclude_did.map(str::to_string),
+            limit: 20,
+        }
+    }
+
+    #[test]
+    fn nearby_observers_groups_recent_occurrences_within_radius() {
+        let qb = nearby_observers_query(
+            &options(Some("did:plc:viewer")),
+            &["did:plc:hidden".to_string()],
+        );
+        let sql = qb.sql();
+        let sql = sql.as_str();
+        // Longitude first, as PostGIS points are (x, y).
+        assert!(
+            sql.contains(
+                "ST_DWithin(location, ST_SetSRID(ST_MakePoint($1, $2), 4326)::geography, $3)"
+            ),
+            "got: {sql}"
+        );
+        assert!(
+            sql.contains("created_at >= NOW() - make_interval(secs => $4)"),
+            "got: {sql}"
+        );
+        assert!(sql.contains("AND did != $5"), "got: {sql}");
+        assert!(sql.contains("AND did != ALL($6)"), "got: {sql}");
+        assert!(sql.contains("GROUP BY did"), "got: {sql}");
+        assert!(sql.ends_with("LIMIT $7"), "got: {sql}");
+    }
+
+    #[test]
+    fn anonymous_nearby_observers_excludes_nobody() {
+        let qb = nearby_observers_query(&options(None), &[]);
+        let sql = qb.sql();
+        let sql = sql.as_str();
+        assert!(!sql.contains("did !="), "got: {sql}");
+    }
+}
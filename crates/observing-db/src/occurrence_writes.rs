@@ -0,0 +1,111 @@
+//! Occurrence writes the appview made to a user's PDS.
+//!
+//! The appview never writes `occurrences` itself; the ingester fills it from
+//! the firehose. Recording each write here lets [`list_pending`] find the
+//! ones whose commit hasn't been indexed. The ingester [`confirm`]s a write
+//! when it indexes or deletes the record, whatever the CID (a later edit
+//! from another client moves it on), and [`prune_confirmed`] drops
+//! confirmed rows once they're no longer interesting.
+
+use crate::types::PendingOccurrenceRow;
+
+/// Record that `did` just wrote `uri` at `cid`, replacing any earlier write
+/// of the same record. The new write is unconfirmed until the ingester
+/// sees it.
+pub async fn record(
+    executor: impl sqlx::PgExecutor<'_>,
+    uri: &str,
+    did: &str,
+    cid: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO occurrence_writes (uri, did, cid) VALUES ($1, $2, $3) \
+         ON CONFLICT (uri) DO UPDATE SET did = EXCLUDED.did, cid = EXCLUDED.cid, \
+         written_at = NOW(), confirmed_at = NULL",
+    )
+    .bind(uri)
+    .bind(did)
+    .bind(cid)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Drop the write for a record that has been deleted.
+pub async fn forget(executor: impl sqlx::PgExecutor<'_>, uri: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM occurrence_writes WHERE uri = $1")
+        .bind(uri)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+/// Mark the write of `uri` confirmed: the ingester has indexed (or
+/// deleted) the record. A no-op for records the appview didn't write.
+pub async fn confirm(executor: impl sqlx::PgExecutor<'_>, uri: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE occurrence_writes SET confirmed_at = NOW() \
+         WHERE uri = $1 AND confirmed_at IS NULL",
+    )
+    .bind(uri)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Delete writes confirmed more than `older_than` ago, returning how many.
+pub async fn prune_confirmed(
+    executor: impl sqlx::PgExecutor<'_>,
+    older_than: chrono::Duration,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "DELETE FROM occurrence_writes \
+         WHERE confirmed_at < NOW() - $1 * interval '1 second'",
+    )
+    .bind(older_than.as_seconds_f64())
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+const PENDING_QUERY: &str = r#"
+    SELECT w.uri, w.cid, w.written_at
+    FROM occurrence_writes w
+    WHERE w.did = $1
+    AND w.confirmed_at IS NULL
+    ORDER BY w.written_at DESC
+    LIMIT $2
+"#;
+
+/// `did`'s writes the ingester hasn't confirmed yet, newest first. A write
+/// from moments ago is normally still in flight; one that stays here means
+/// the commit was lost.
+pub async fn list_pending(
+    executor: impl sqlx::PgExecutor<'_>,
+    did: &str,
+    limit: i64,
+) -> Result<Vec<PendingOccurrenceRow>, sqlx::Error> {
+    sqlx::query_as::<_, PendingOccurrenceRow>(PENDING_QUERY)
+        .bind(did)
+        .bind(limit)
+        .fetch_all(executor)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_unconfirmed_writes_are_pending() {
+        // Reads the ingester's stamp instead of probing `occurrences`.
+        assert!(PENDING_QUERY.contains("AND w.confirmed_at IS NULL"));
+        assert!(!PENDING_QUERY.contains("occurrences o"));
+    }
+
+    #[test]
+    fn pending_writes_are_scoped_to_the_author() {
+        assert!(PENDING_QUERY.contains("WHERE w.did = $1"));
+        assert!(PENDING_QUERY.contains("ORDER BY w.written_at DESC"));
+    }
+}
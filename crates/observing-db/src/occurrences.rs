@@ -1,4 +1,10 @@
-use crate::types::{OccurrenceRow, UpsertOccurrenceParams};
+use crate::cursor::FeedCursor;
+use crate::feeds::{push_bbox_filter, push_keyset_cursor};
+use crate::live::{self, ChangeAction, OccurrenceChange};
+use crate::types::{
+    BoundingBox, DensityCell, OccurrenceRow, TaxonOccurrenceOptions, UpsertOccurrenceParams,
+};
+use sqlx::{PgPool, Postgres, QueryBuilder};
 
 /// Standard SELECT columns for OccurrenceRow in QueryBuilder (runtime) queries.
 /// Does not include the SELECT keyword or FROM clause.
@@ -24,11 +30,9 @@ macro_rules! occurrence_columns {
     };
 }
 
-/// Upsert an occurrence record
-pub async fn upsert(
-    executor: impl sqlx::PgExecutor<'_>,
-    p: &UpsertOccurrenceParams,
-) -> Result<(), sqlx::Error> {
+/// Upsert an occurrence record, then announce it on
+/// [`live::OCCURRENCE_CHANNEL`].
+pub async fn upsert(pool: &PgPool, p: &UpsertOccurrenceParams) -> Result<(), sqlx::Error> {
     sqlx::query!(
         r#"
         INSERT INTO occurrences (
@@ -37,14 +41,14 @@ pub async fn upsert(
             associated_media, recorded_by,
             taxon_id, taxon_rank, kingdom,
             organism_quantity, organism_quantity_type,
-            created_at, event_date_raw, event_date_end
+            created_at, event_date_raw, event_date_end, timezone
         ) VALUES (
             $1, $2, $3, $4, $5,
             ST_SetSRID(ST_MakePoint($6, $7), 4326)::geography,
             $8, $9, $10,
             $11, $12, $13,
             $14, $15,
-            $16, $17, $18
+            $16, $17, $18, $19
         )
         ON CONFLICT (uri) DO UPDATE SET
             cid = $2,
@@ -61,6 +65,7 @@ pub async fn upsert(
             kingdom = COALESCE($13, occurrences.kingdom),
             organism_quantity = COALESCE($14, occurrences.organism_quantity),
             organism_quantity_type = COALESCE($15, occurrences.organism_quantity_type),
+            timezone = $19,
             indexed_at = NOW()
         "#,
         p.uri,
@@ -84,17 +89,40 @@ pub async fn upsert(
         p.created_at,
         p.event_date_raw as _,
         p.event_date_end as _,
+        // Derived from the coordinates, so it follows them: an edit that
+        // moves or drops the point replaces or clears the zone.
+        p.timezone as _,
     )
-    .execute(executor)
+    .execute(pool)
     .await?;
+
+    let change = OccurrenceChange {
+        uri: p.uri.clone(),
+        did: p.did.clone(),
+        action: ChangeAction::Upsert,
+        latitude: p.latitude,
+        longitude: p.longitude,
+    };
+    live::announce(pool, live::OCCURRENCE_CHANNEL, &change).await;
     Ok(())
 }
 
-/// Delete an occurrence
-pub async fn delete(executor: impl sqlx::PgExecutor<'_>, uri: &str) -> Result<(), sqlx::Error> {
+/// Delete an occurrence, then announce it on [`live::OCCURRENCE_CHANNEL`].
+pub async fn delete(pool: &PgPool, uri: &str) -> Result<(), sqlx::Error> {
     sqlx::query!("DELETE FROM occurrences WHERE uri = $1", uri)
-        .execute(executor)
+        .execute(pool)
         .await?;
+
+    if let Some(did) = live::did_from_at_uri(uri) {
+        let change = OccurrenceChange {
+            uri: uri.to_string(),
+            did: did.to_string(),
+            action: ChangeAction::Delete,
+            latitude: None,
+            longitude: None,
+        };
+        live::announce(pool, live::OCCURRENCE_CHANNEL, &change).await;
+    }
     Ok(())
 }
 
@@ -214,66 +242,260 @@ pub async fn get_by_bounding_box(
     .await
 }
 
-/// Get occurrences feed (chronological, cursor-based)
+/// Occurrence counts per `cell_size`-degree grid square, for heatmaps. Each
+/// point snaps to its nearest grid node, so cells are centred on multiples of
+/// `cell_size`. `None` counts the whole world. Only non-empty cells are
+/// returned; their counts add up to the occurrences in `bbox`.
+pub async fn density_grid(
+    executor: impl sqlx::PgExecutor<'_>,
+    bbox: Option<&BoundingBox>,
+    cell_size: f64,
+    hidden_dids: &[String],
+) -> Result<Vec<DensityCell>, sqlx::Error> {
+    density_grid_query(bbox, cell_size, hidden_dids)
+        .build_query_as::<DensityCell>()
+        .fetch_all(executor)
+        .await
+}
+
+fn density_grid_query(
+    bbox: Option<&BoundingBox>,
+    cell_size: f64,
+    hidden_dids: &[String],
+) -> QueryBuilder<Postgres> {
+    let mut qb = QueryBuilder::<Postgres>::new(
+        "SELECT ST_Y(cell) AS lat, ST_X(cell) AS lng, COUNT(*) AS count \
+         FROM (SELECT ST_SnapToGrid(location::geometry, ",
+    );
+    qb.push_bind(cell_size);
+    qb.push(") AS cell FROM occurrences WHERE location IS NOT NULL");
+
+    if let Some(bbox) = bbox {
+        push_bbox_filter(&mut qb, bbox);
+    }
+
+    if !hidden_dids.is_empty() {
+        qb.push(" AND did != ALL(");
+        qb.push_bind(hidden_dids.to_vec());
+        qb.push(")");
+    }
+
+    qb.push(") snapped GROUP BY 1, 2");
+    qb
+}
+
+/// Get occurrences feed (chronological, cursor-based), keyset-paged on
+/// `(created_at, uri)`.
 pub async fn get_feed(
     executor: impl sqlx::PgExecutor<'_>,
     limit: i64,
-    cursor: Option<&str>,
+    cursor: Option<&FeedCursor>,
     hidden_dids: &[String],
 ) -> Result<Vec<OccurrenceRow>, sqlx::Error> {
-    if let Some(cursor) = cursor {
-        sqlx::query_as!(
-            OccurrenceRow,
-            r#"
-            SELECT
-                uri, cid, did, scientific_name,
-                event_date_raw as event_date,
-                ST_Y(location::geometry) as latitude,
-                ST_X(location::geometry) as longitude,
-                coordinate_uncertainty_meters,
-                associated_media, recorded_by,
-                taxon_id, taxon_rank, kingdom, phylum, class, "order" as order_, family, genus,
-                organism_quantity, organism_quantity_type,
-                created_at,
-                NULL::float8 as distance_meters,
-                NULL::text as source
-            FROM occurrences
-            WHERE created_at < ($2::text)::timestamptz
-            AND did != ALL($3)
-            ORDER BY created_at DESC
-            LIMIT $1
-            "#,
-            limit,
-            cursor,
-            hidden_dids,
-        )
+    feed_query(limit, cursor, hidden_dids)
+        .build_query_as::<OccurrenceRow>()
         .fetch_all(executor)
         .await
-    } else {
-        sqlx::query_as!(
-            OccurrenceRow,
-            r#"
-            SELECT
-                uri, cid, did, scientific_name,
-                event_date_raw as event_date,
-                ST_Y(location::geometry) as latitude,
-                ST_X(location::geometry) as longitude,
-                coordinate_uncertainty_meters,
-                associated_media, recorded_by,
-                taxon_id, taxon_rank, kingdom, phylum, class, "order" as order_, family, genus,
-                organism_quantity, organism_quantity_type,
-                created_at,
-                NULL::float8 as distance_meters,
-                NULL::text as source
-            FROM occurrences
-            WHERE did != ALL($2)
-            ORDER BY created_at DESC
-            LIMIT $1
-            "#,
-            limit,
-            hidden_dids,
-        )
+}
+
+fn feed_query(
+    limit: i64,
+    cursor: Option<&FeedCursor>,
+    hidden_dids: &[String],
+) -> QueryBuilder<Postgres> {
+    let mut qb = QueryBuilder::<Postgres>::new(concat!(
+        "SELECT ",
+        occurrence_columns!(),
+        " FROM occurrences WHERE did != ALL("
+    ));
+    qb.push_bind(hidden_dids.to_vec());
+    qb.push(")");
+    if let Some(cursor) = cursor {
+        push_keyset_cursor(&mut qb, cursor);
+    }
+    qb.push(" ORDER BY created_at DESC, uri DESC LIMIT ");
+    qb.push_bind(limit);
+    qb
+}
+
+/// GBIF usage key from a taxon id in any of the forms we accept: the
+/// `https://www.gbif.org/species/{key}` URI stored on identifications, the
+/// `gbif:{key}` path id, or a bare key.
+pub fn gbif_taxon_key(taxon_id: &str) -> Option<i64> {
+    let key = taxon_id
+        .strip_prefix("https://www.gbif.org/species/")
+        .or_else(|| taxon_id.strip_prefix("gbif:"))
+        .unwrap_or(taxon_id);
+    key.parse().ok().filter(|k| *k > 0)
+}
+
+/// Occurrences with at least one identification of GBIF taxon `taxon_id`
+/// (see [`gbif_taxon_key`] for accepted forms), newest first.
+///
+/// An identification matches on its stored `taxon_id`, or on its resolved
+/// `accepted_taxon_key` being the taxon or any descendant of it: the cached
+/// `taxa` row carries its full ancestry keys, so a genus id also finds
+/// occurrences identified to each species in the genus. Unlike
+/// [`crate::feeds::get_occurrences_by_taxon`] this looks at every
+/// identification, not just the consensus. `options.kingdom` is ignored since
+/// a GBIF key is already unambiguous. Returns nothing for an id that isn't a
+/// GBIF key.
+pub async fn get_by_gbif_taxon_id(
+    executor: impl sqlx::PgExecutor<'_>,
+    taxon_id: &str,
+    options: &TaxonOccurrenceOptions,
+    hidden_dids: &[String],
+) -> Result<Vec<OccurrenceRow>, sqlx::Error> {
+    let Some(taxon_key) = gbif_taxon_key(taxon_id) else {
+        return Ok(Vec::new());
+    };
+    let mut qb = gbif_taxon_query(taxon_key, options, hidden_dids);
+    qb.build_query_as::<OccurrenceRow>()
         .fetch_all(executor)
         .await
+}
+
+fn gbif_taxon_query(
+    taxon_key: i64,
+    options: &TaxonOccurrenceOptions,
+    hidden_dids: &[String],
+) -> QueryBuilder<Postgres> {
+    let mut qb = QueryBuilder::<Postgres>::new(concat!(
+        "SELECT ",
+        occurrence_columns!(),
+        " FROM occurrences WHERE uri IN (\
+         SELECT i.subject_uri FROM identifications i \
+         LEFT JOIN taxa t ON t.taxon_key = i.accepted_taxon_key \
+         WHERE i.deleted_at IS NULL AND (i.taxon_id = "
+    ));
+    qb.push_bind(format!("https://www.gbif.org/species/{taxon_key}"));
+    qb.push(" OR ");
+    qb.push_bind(taxon_key);
+    qb.push(
+        " IN (i.accepted_taxon_key, t.kingdom_key, t.phylum_key, t.class_key, \
+         t.order_key, t.family_key, t.genus_key, t.species_key)))",
+    );
+
+    if !hidden_dids.is_empty() {
+        qb.push(" AND did != ALL(");
+        qb.push_bind(hidden_dids.to_vec());
+        qb.push(")");
+    }
+
+    if let Some(cursor) = options.cursor.as_ref() {
+        push_keyset_cursor(&mut qb, cursor);
+    }
+
+    qb.push(" ORDER BY created_at DESC, uri DESC LIMIT ");
+    qb.push_bind(options.limit.unwrap_or(20));
+    qb
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gbif_taxon_key_accepts_each_id_form() {
+        assert_eq!(
+            gbif_taxon_key("https://www.gbif.org/species/2879737"),
+            Some(2879737)
+        );
+        assert_eq!(gbif_taxon_key("gbif:2879737"), Some(2879737));
+        assert_eq!(gbif_taxon_key("2879737"), Some(2879737));
+        assert_eq!(gbif_taxon_key("Quercus alba"), None);
+        assert_eq!(gbif_taxon_key("gbif:0"), None);
+    }
+
+    #[test]
+    fn gbif_taxon_query_links_identifications_and_descendants() {
+        let options = TaxonOccurrenceOptions {
+            limit: Some(5),
+            cursor: Some(crate::cursor::FeedCursor::new(
+                "2026-06-02T21:13:49Z".parse().unwrap(),
+                "at://did:plc:x/coll/rkey",
+            )),
+            kingdom: None,
+        };
+        let qb = gbif_taxon_query(2879737, &options, &["did:plc:hidden".to_string()]);
+        let sql = qb.sql();
+        let sql = sql.as_str();
+        // The identification's own taxon_id links it to its occurrence...
+        assert!(
+            sql.contains("SELECT i.subject_uri FROM identifications i"),
+            "got: {sql}"
+        );
+        assert!(
+            sql.contains("WHERE i.deleted_at IS NULL AND (i.taxon_id = $1"),
+            "got: {sql}"
+        );
+        // ...as does any resolved taxon under the requested one.
+        assert!(
+            sql.contains("$2 IN (i.accepted_taxon_key, t.kingdom_key"),
+            "got: {sql}"
+        );
+        assert!(sql.contains("t.species_key))"), "got: {sql}");
+        assert!(sql.contains("did != ALL($3)"), "got: {sql}");
+        assert!(sql.contains("(created_at, uri) < ("), "got: {sql}");
+        assert!(
+            sql.ends_with("ORDER BY created_at DESC, uri DESC LIMIT $6"),
+            "got: {sql}"
+        );
+    }
+
+    #[test]
+    fn feed_pages_on_created_at_and_uri() {
+        let cursor = crate::cursor::FeedCursor::new(
+            "2026-06-02T21:13:49Z".parse().unwrap(),
+            "at://did:plc:x/coll/rkey",
+        );
+        let qb = feed_query(20, Some(&cursor), &[]);
+        let sql = qb.sql();
+        let sql = sql.as_str();
+        assert!(
+            sql.contains("did != ALL($1) AND (created_at, uri) < ($2::timestamptz, $3)"),
+            "got: {sql}"
+        );
+        assert!(
+            sql.ends_with("ORDER BY created_at DESC, uri DESC LIMIT $4"),
+            "got: {sql}"
+        );
+    }
+
+    #[test]
+    fn density_grid_counts_every_occurrence_in_the_box_once() {
+        let bbox = BoundingBox {
+            min_lat: 37.0,
+            min_lng: -123.0,
+            max_lat: 38.0,
+            max_lng: -122.0,
+        };
+        let qb = density_grid_query(Some(&bbox), 0.1, &["did:plc:hidden".to_string()]);
+        let sql = qb.sql();
+        let sql = sql.as_str();
+        // Each occurrence is snapped once and counted in exactly one cell, so
+        // the cell counts sum to the bbox total.
+        assert!(
+            sql.starts_with(
+                "SELECT ST_Y(cell) AS lat, ST_X(cell) AS lng, COUNT(*) AS count \
+                 FROM (SELECT ST_SnapToGrid(location::geometry, $1) AS cell FROM occurrences"
+            ),
+            "got: {sql}"
+        );
+        assert!(
+            sql.contains("AND location && ST_MakeEnvelope($2, $3, $4, $5, 4326)::geography"),
+            "got: {sql}"
+        );
+        assert!(sql.contains("AND did != ALL($6)"), "got: {sql}");
+        assert!(sql.ends_with(") snapped GROUP BY 1, 2"), "got: {sql}");
+    }
+
+    #[test]
+    fn density_grid_without_a_box_covers_the_world() {
+        let qb = density_grid_query(None, 10.0, &[]);
+        let sql = qb.sql();
+        let sql = sql.as_str();
+        assert!(!sql.contains("ST_MakeEnvelope"), "got: {sql}");
+        assert!(!sql.contains("did != ALL"), "got: {sql}");
     }
 }
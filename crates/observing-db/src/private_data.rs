@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use crate::types::OccurrencePrivateDataRow;
 
 /// Save private location data for an occurrence
@@ -57,3 +59,57 @@ pub async fn delete(executor: impl sqlx::PgExecutor<'_>, uri: &str) -> Result<()
         .await?;
     Ok(())
 }
+
+/// Mark a blob private so the media proxy only serves it via signed URLs
+pub async fn mark_media_private(
+    executor: impl sqlx::PgExecutor<'_>,
+    did: &str,
+    cid: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO private_media (did, cid) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+        .bind(did)
+        .bind(cid)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+/// Whether a blob has been marked private
+pub async fn is_media_private(
+    executor: impl sqlx::PgExecutor<'_>,
+    did: &str,
+    cid: &str,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM private_media WHERE did = $1 AND cid = $2)")
+        .bind(did)
+        .bind(cid)
+        .fetch_one(executor)
+        .await
+}
+
+/// Which of `blobs` (`(did, cid)` pairs) are marked private.
+pub async fn private_among(
+    executor: impl sqlx::PgExecutor<'_>,
+    blobs: &[(String, String)],
+) -> Result<HashSet<(String, String)>, sqlx::Error> {
+    if blobs.is_empty() {
+        return Ok(HashSet::new());
+    }
+    let (dids, cids): (Vec<&str>, Vec<&str>) = blobs
+        .iter()
+        .map(|(did, cid)| (did.as_str(), cid.as_str()))
+        .unzip();
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        r#"
+        SELECT p.did, p.cid
+        FROM private_media p
+        JOIN UNNEST($1::text[], $2::text[]) AS b(did, cid)
+            ON p.did = b.did AND p.cid = b.cid
+        "#,
+    )
+    .bind(&dids)
+    .bind(&cids)
+    .fetch_all(executor)
+    .await?;
+    Ok(rows.into_iter().collect())
+}
@@ -5,8 +5,8 @@
 //! the ingester (asynchronous firehose path).
 
 use crate::types::{
-    BlobEntry, CreateLikeParams, UpsertCommentParams, UpsertIdentificationParams,
-    UpsertInteractionParams, UpsertOccurrenceParams,
+    BlobEntry, CreateLikeParams, IdentificationConfidence, UpsertCommentParams,
+    UpsertIdentificationParams, UpsertInteractionParams, UpsertOccurrenceParams,
 };
 use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
 use observing_lexicons::bio_lexicons::temp::v0_1::occurrence::Occurrence;
@@ -16,6 +16,7 @@ use observing_lexicons::ing_observ::temp::{
 };
 use serde::Deserialize;
 use serde_json::Value;
+use std::sync::LazyLock;
 
 /// Errors that can occur during record processing
 #[derive(Debug)]
@@ -37,6 +38,90 @@ impl std::fmt::Display for ProcessingError {
 
 impl std::error::Error for ProcessingError {}
 
+/// Decimal places of latitude/longitude worth keeping for a point whose
+/// declared `coordinateUncertaintyInMeters` is `uncertainty_meters`.
+///
+/// A decimal degree of latitude is ~111 km, so each place is a tenfold finer
+/// grid (2 places ≈ 1.1 km, 4 ≈ 11 m). The kept precision is the coarsest
+/// grid no larger than ~1.1× the uncertainty, which keeps the rounding shift
+/// within the stated uncertainty while dropping digits that imply more
+/// accuracy than the observer claimed.
+pub fn coordinate_decimals(uncertainty_meters: i32) -> u32 {
+    match uncertainty_meters {
+        100_000.. => 0,
+        10_000.. => 1,
+        1_000.. => 2,
+        100.. => 3,
+        10.. => 4,
+        1.. => 5,
+        _ => 6,
+    }
+}
+
+/// Round a coordinate to [`coordinate_decimals`] places for
+/// `uncertainty_meters`.
+pub fn round_coordinate(value: f64, uncertainty_meters: i32) -> f64 {
+    let scale = 10f64.powi(coordinate_decimals(uncertainty_meters) as i32);
+    (value * scale).round() / scale
+}
+
+/// Timezone polygon index, built on first use (it decompresses the bundled
+/// boundary data, so it's shared rather than rebuilt per record).
+static TIMEZONE_FINDER: LazyLock<tzf_rs::DefaultFinder> = LazyLock::new(tzf_rs::DefaultFinder::new);
+
+/// IANA timezone (e.g. `America/Los_Angeles`) containing a point.
+///
+/// Lets an `eventDate` recorded without an offset be read as local time at
+/// the observation site. Open ocean resolves to the nautical `Etc/GMT±N`
+/// zones; `None` only when the lookup finds nothing at all.
+pub fn timezone_at(latitude: f64, longitude: f64) -> Option<String> {
+    let name = TIMEZONE_FINDER.get_tz_name(longitude, latitude);
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Byte limit on a comment `body`, matching the lexicon's `maxLength`.
+pub const MAX_COMMENT_BODY_LENGTH: usize = 3000;
+
+/// Byte limit on an interaction's `comment` notes, matching the lexicon's
+/// `maxLength`.
+pub const MAX_INTERACTION_COMMENT_LENGTH: usize = 3000;
+
+/// Byte limit on an identification qualifier such as `cf.` or `aff.`. The
+/// appview rejects longer ones on create; the ingester drops them.
+pub const MAX_IDENTIFICATION_QUALIFIER_LENGTH: usize = 16;
+
+/// Clean user-entered free text before it's stored or published.
+///
+/// Normalizes `\r\n` and lone `\r` to `\n`, drops control characters other
+/// than newline and tab, trims surrounding whitespace, and caps the result
+/// at `max_bytes` (the unit lexicon `maxLength` counts), cutting on a char
+/// boundary. HTML is left alone: escaping is the renderer's job, and the
+/// text must round-trip to the PDS record unchanged.
+pub fn sanitize_text(value: &str, max_bytes: usize) -> String {
+    let normalized = value.replace("\r\n", "\n").replace('\r', "\n");
+    let cleaned: String = normalized
+        .chars()
+        .filter(|c| !c.is_control() || matches!(c, '\n' | '\t'))
+        .collect();
+    let trimmed = cleaned.trim();
+    if trimmed.len() <= max_bytes {
+        return trimmed.to_string();
+    }
+    let mut end = max_bytes;
+    while !trimmed.is_char_boundary(end) {
+        end -= 1;
+    }
+    trimmed[..end].trim_end().to_string()
+}
+
+/// [`sanitize_text`] for optional fields; text that cleans down to nothing
+/// becomes `None`.
+pub fn sanitize_optional_text(value: Option<&str>, max_bytes: usize) -> Option<String> {
+    value
+        .map(|v| sanitize_text(v, max_bytes))
+        .filter(|v| !v.is_empty())
+}
+
 /// Parse an ISO 8601 date string into a DateTime<Utc>
 pub fn parse_datetime(s: &str) -> Option<DateTime<Utc>> {
     DateTime::parse_from_rfc3339(s)
@@ -228,11 +313,24 @@ pub fn occurrence_from_json(
         })
         .and_then(|s| s.parse::<f64>().ok());
 
+    let coordinate_uncertainty_meters = record
+        .coordinate_uncertainty_in_meters
+        .or_else(|| {
+            location
+                .and_then(|l| l.get("coordinateUncertaintyInMeters"))
+                .and_then(|v| v.as_i64())
+        })
+        .map(|v| v as i32);
+
     // Both coordinates must be present together to mean anything; mixing
     // one with NULL would silently misplace the point at the equator or
-    // prime meridian.
+    // prime meridian. Digits finer than the declared uncertainty are dropped
+    // so the stored point doesn't claim more accuracy than the observer did.
     let coords = match (lat, lng) {
-        (Some(lat), Some(lng)) => Some((lat, lng)),
+        (Some(lat), Some(lng)) => Some(match coordinate_uncertainty_meters {
+            Some(u) => (round_coordinate(lat, u), round_coordinate(lng, u)),
+            None => (lat, lng),
+        }),
         _ => None,
     };
 
@@ -295,14 +393,8 @@ pub fn occurrence_from_json(
             event_date_raw,
             longitude: coords.map(|(_, lng)| lng),
             latitude: coords.map(|(lat, _)| lat),
-            coordinate_uncertainty_meters: record
-                .coordinate_uncertainty_in_meters
-                .or_else(|| {
-                    location
-                        .and_then(|l| l.get("coordinateUncertaintyInMeters"))
-                        .and_then(|v| v.as_i64())
-                })
-                .map(|v| v as i32),
+            timezone: coords.and_then(|(lat, lng)| timezone_at(lat, lng)),
+            coordinate_uncertainty_meters,
             organism_quantity: record.organism_quantity.map(|q| q.to_string()),
             organism_quantity_type: record
                 .organism_quantity_type
@@ -380,6 +472,22 @@ pub fn identification_from_json(
         .and_then(|v| v.as_str())
         .map(|s| s.to_string());
 
+    // App-specific extra field (not in the upstream lexicon), like `createdAt`.
+    // One too long to be a qualifier is dropped rather than stored.
+    let identification_qualifier = record_json
+        .get("identificationQualifier")
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty() && s.len() <= MAX_IDENTIFICATION_QUALIFIER_LENGTH)
+        .map(|s| s.to_string());
+
+    // Also app-specific. Tokens outside the scale are dropped rather than
+    // failing the whole record.
+    let confidence = record_json
+        .get("confidence")
+        .and_then(|v| v.as_str())
+        .and_then(|s| s.parse::<IdentificationConfidence>().ok());
+
     let date_identified = record_json
         .get("createdAt")
         .and_then(|v| v.as_str())
@@ -396,6 +504,8 @@ pub fn identification_from_json(
         scientific_name,
         taxon_rank,
         taxon_id,
+        identification_qualifier,
+        confidence,
         date_identified,
         kingdom,
         // Resolved by the taxonomy resolver before upsert; the JSON record
@@ -426,7 +536,7 @@ pub fn comment_from_json(
         did,
         subject_uri: record.subject.uri.to_string(),
         subject_cid: record.subject.cid.to_string(),
-        body: record.body.to_string(),
+        body: sanitize_text(&record.body, MAX_COMMENT_BODY_LENGTH),
         reply_to_uri: record.reply_to.as_ref().map(|r| r.uri.to_string()),
         reply_to_cid: record.reply_to.as_ref().map(|r| r.cid.to_string()),
         created_at,
@@ -489,7 +599,7 @@ pub fn interaction_from_json(
         subject_b_kingdom: subject_b.kingdom,
         interaction_type: record.interaction_type.as_ref().to_string(),
         direction: record.direction.to_string(),
-        comment: record.comment.map(|s| s.to_string()),
+        comment: sanitize_optional_text(record.comment.as_deref(), MAX_INTERACTION_COMMENT_LENGTH),
         created_at,
     })
 }
@@ -531,6 +641,157 @@ mod tests {
         DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
     }
 
+    #[test]
+    fn coordinate_decimals_follow_uncertainty() {
+        for (uncertainty, decimals) in [
+            (250_000, 0),
+            (25_000, 1),
+            (1_000, 2),
+            (5_000, 2),
+            (999, 3),
+            (100, 3),
+            (50, 4),
+            (10, 4),
+            (3, 5),
+            (0, 6),
+        ] {
+            assert_eq!(
+                coordinate_decimals(uncertainty),
+                decimals,
+                "uncertainty {uncertainty}m"
+            );
+        }
+    }
+
+    #[test]
+    fn round_coordinate_drops_false_precision() {
+        assert_eq!(round_coordinate(37.774929483712, 1_000), 37.77);
+        assert_eq!(round_coordinate(-122.419415523901, 1_000), -122.42);
+        assert_eq!(round_coordinate(37.774929483712, 50), 37.7749);
+        assert_eq!(round_coordinate(37.774929483712, 0), 37.774929);
+    }
+
+    #[test]
+    fn occurrence_from_json_rounds_to_declared_uncertainty() {
+        let record = serde_json::json!({
+            "$type": "bio.lexicons.temp.v0-1.occurrence",
+            "decimalLatitude": "37.774929483712",
+            "decimalLongitude": "-122.419415523901",
+            "coordinateUncertaintyInMeters": 2000,
+            "eventDate": "2024-06-15"
+        });
+        let parsed = occurrence_from_json(
+            &record,
+            "at://did:plc:author/bio.lexicons.temp.v0-1.occurrence/xyz".into(),
+            "bafyreioccurrence".into(),
+            "did:plc:author".into(),
+            Utc::now(),
+        )
+        .unwrap();
+        assert_eq!(parsed.params.latitude, Some(37.77));
+        assert_eq!(parsed.params.longitude, Some(-122.42));
+        assert_eq!(parsed.params.coordinate_uncertainty_meters, Some(2000));
+    }
+
+    #[test]
+    fn timezone_at_resolves_known_places() {
+        for ((lat, lng), zone) in [
+            ((37.7749, -122.4194), "America/Los_Angeles"),
+            ((40.7128, -74.0060), "America/New_York"),
+            ((51.5074, -0.1278), "Europe/London"),
+            ((-33.8688, 151.2093), "Australia/Sydney"),
+            ((35.6762, 139.6503), "Asia/Tokyo"),
+        ] {
+            assert_eq!(timezone_at(lat, lng).as_deref(), Some(zone), "{lat},{lng}");
+        }
+    }
+
+    #[test]
+    fn occurrence_from_json_attaches_timezone() {
+        let parse = |record: serde_json::Value| {
+            occurrence_from_json(
+                &record,
+                "at://did:plc:author/bio.lexicons.temp.v0-1.occurrence/xyz".into(),
+                "bafyreioccurrence".into(),
+                "did:plc:author".into(),
+                Utc::now(),
+            )
+            .unwrap()
+            .params
+        };
+
+        let located = parse(serde_json::json!({
+            "$type": "bio.lexicons.temp.v0-1.occurrence",
+            "decimalLatitude": "-33.8688",
+            "decimalLongitude": "151.2093",
+            "eventDate": "2024-06-15T08:30:00"
+        }));
+        assert_eq!(located.timezone.as_deref(), Some("Australia/Sydney"));
+
+        let unlocated = parse(serde_json::json!({
+            "$type": "bio.lexicons.temp.v0-1.occurrence",
+            "eventDate": "2024-06-15"
+        }));
+        assert_eq!(unlocated.timezone, None);
+    }
+
+    #[test]
+    fn sanitize_text_strips_control_characters_and_normalizes_newlines() {
+        assert_eq!(
+            sanitize_text(
+                "  Nice\u{0}\u{7} find\r\nsecond\rthird\tline\u{1b}[31m \n",
+                100
+            ),
+            "Nice find\nsecond\nthird\tline[31m"
+        );
+        assert_eq!(sanitize_text("\u{0}\u{8}  ", 100), "");
+    }
+
+    #[test]
+    fn sanitize_text_caps_length_on_a_char_boundary() {
+        assert_eq!(sanitize_text("abcdef", 4), "abcd");
+        // "é" is two bytes; a cut through it backs off to the boundary.
+        assert_eq!(sanitize_text("caféine", 4), "caf");
+        assert_eq!(sanitize_text("word  next", 6), "word");
+        let long = "x".repeat(MAX_COMMENT_BODY_LENGTH + 10);
+        assert_eq!(
+            sanitize_text(&long, MAX_COMMENT_BODY_LENGTH).len(),
+            MAX_COMMENT_BODY_LENGTH
+        );
+    }
+
+    #[test]
+    fn sanitize_optional_text_drops_empty_results() {
+        assert_eq!(sanitize_optional_text(None, 10), None);
+        assert_eq!(sanitize_optional_text(Some(" \r\n\u{0} "), 10), None);
+        assert_eq!(
+            sanitize_optional_text(Some(" seen twice "), 10).as_deref(),
+            Some("seen twice")
+        );
+    }
+
+    #[test]
+    fn comment_from_json_sanitizes_body() {
+        let record = serde_json::json!({
+            "$type": "ing.observ.temp.comment",
+            "subject": {
+                "uri": "at://did:plc:author/bio.lexicons.temp.v0-1.occurrence/xyz",
+                "cid": "bafyreioccurrence"
+            },
+            "body": "  Looks right\r\nto me\u{0}  ",
+            "createdAt": "2024-06-15T08:30:45.123Z"
+        });
+        let params = comment_from_json(
+            &record,
+            "at://did:plc:commenter/ing.observ.temp.comment/xyz".to_string(),
+            "bafyreicomment".to_string(),
+            "did:plc:commenter".to_string(),
+            Utc::now(),
+        )
+        .unwrap();
+        assert_eq!(params.body, "Looks right\nto me");
+    }
+
     #[test]
     fn expand_event_date_covers_lexicon_examples() {
         // Single date.
@@ -767,6 +1028,91 @@ mod tests {
         assert_eq!(params.date_identified, fallback);
     }
 
+    /// `identificationQualifier` rides along as an extra field outside the
+    /// upstream lexicon; a firehose record carrying "cf." lands in the params
+    /// verbatim, and a blank or over-long qualifier is treated as absent.
+    #[test]
+    fn test_identification_from_json_extracts_qualifier() {
+        let mut record = serde_json::json!({
+            "$type": "bio.lexicons.temp.v0-1.identification",
+            "scientificName": "Quercus alba",
+            "identificationQualifier": "cf.",
+            "occurrence": {
+                "uri": "at://did:plc:author/bio.lexicons.temp.v0-1.occurrence/abc",
+                "cid": "bafyreioccurrence"
+            }
+        });
+
+        assert_valid_lexicon::<Identification>(&record);
+
+        let params = identification_from_json(
+            &record,
+            "uri".into(),
+            "cid".into(),
+            "did:plc:x".into(),
+            Utc::now(),
+        )
+        .expect("record should parse");
+        assert_eq!(params.identification_qualifier.as_deref(), Some("cf."));
+
+        record["identificationQualifier"] = serde_json::json!("  ");
+        let params = identification_from_json(
+            &record,
+            "uri".into(),
+            "cid".into(),
+            "did:plc:x".into(),
+            Utc::now(),
+        )
+        .expect("record should parse");
+        assert!(params.identification_qualifier.is_none());
+
+        // Past the appview's create limit, it's dropped too.
+        record["identificationQualifier"] =
+            serde_json::json!("x".repeat(MAX_IDENTIFICATION_QUALIFIER_LENGTH + 1));
+        let params = identification_from_json(
+            &record,
+            "uri".into(),
+            "cid".into(),
+            "did:plc:x".into(),
+            Utc::now(),
+        )
+        .expect("record should parse");
+        assert!(params.identification_qualifier.is_none());
+    }
+
+    /// `confidence` is read against the defined scale; an unknown token is
+    /// dropped instead of rejecting the identification.
+    #[test]
+    fn test_identification_from_json_extracts_confidence() {
+        let mut record = serde_json::json!({
+            "$type": "bio.lexicons.temp.v0-1.identification",
+            "scientificName": "Quercus alba",
+            "confidence": "certain",
+            "occurrence": {
+                "uri": "at://did:plc:author/bio.lexicons.temp.v0-1.occurrence/abc",
+                "cid": "bafyreioccurrence"
+            }
+        });
+
+        let parse = |record: &Value| {
+            identification_from_json(
+                record,
+                "uri".into(),
+                "cid".into(),
+                "did:plc:x".into(),
+                Utc::now(),
+            )
+            .expect("record should parse")
+        };
+        assert_eq!(
+            parse(&record).confidence,
+            Some(IdentificationConfidence::Certain)
+        );
+
+        record["confidence"] = serde_json::json!("absolutely");
+        assert!(parse(&record).confidence.is_none());
+    }
+
     /// Regression guard: `taxonID` is the canonical Darwin Core casing
     /// (uppercase ID) per the bio.lexicons.temp.v0-1.identification schema on
     /// lexicons.bio. A prior bug read the camelCased `taxonId`, silently
@@ -3,8 +3,10 @@
 //! An occurrence is "verifiable" when [`compute_issues`] returns an empty list.
 //! Callers can filter feeds on this in SQL (see [`feeds`](crate::feeds)) and
 //! surface individual codes in API responses for UI badges.
+//!
+//! [`compute_grade`] sums these up as an iNaturalist-style [`QualityGrade`].
 
-use crate::types::OccurrenceRow;
+use crate::types::{ConsensusRow, ConsensusVotes, OccurrenceRow};
 use serde::de::value::{Error as ValueError, StrDeserializer};
 use serde::de::{self, Deserializer, IntoDeserializer};
 use serde::{Deserialize, Serialize};
@@ -169,6 +171,110 @@ pub fn compute_issues(row: &OccurrenceRow, has_consensus_id: bool) -> Vec<Qualit
     issues
 }
 
+/// iNaturalist-style quality grade.
+///
+/// Derived at read time from the occurrence and its row in `community_ids`,
+/// which the ingester refreshes on every identification change, so the grade
+/// follows new and withdrawn identifications without a stored column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "bindings/", rename_all = "snake_case")]
+pub enum QualityGrade {
+    /// Missing a date, location or media; not usable as a record.
+    Casual,
+    /// Verifiable, but the community hasn't agreed on a species yet.
+    NeedsId,
+    /// Verifiable, with at least two identifiers and a 2/3 majority on a
+    /// species-or-lower taxon.
+    Research,
+}
+
+/// One condition of a [`QualityGrade`]. The grade rules are written once, as
+/// [`VERIFIABLE_REQUIREMENTS`] and [`RESEARCH_REQUIREMENTS`]; [`compute_grade`]
+/// and [`crate::community_ids::calculate`] evaluate them in Rust and the
+/// feeds' grade filter renders each one to SQL.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GradeRequirement {
+    HasDate,
+    HasLocation,
+    HasMedia,
+    /// The consensus taxon is a species or below.
+    SpeciesRank,
+    /// At least this many identifiers agree with the consensus.
+    MinAgreeing(i64),
+    /// At least this share of the vote weight backs the consensus.
+    AgreeingShare(f64),
+}
+
+/// Share of the vote weight a research-grade consensus needs.
+const RESEARCH_GRADE_THRESHOLD: f64 = 2.0 / 3.0;
+/// Identifiers a research-grade consensus needs.
+const MIN_IDS_FOR_RESEARCH_GRADE: i64 = 2;
+
+/// What an occurrence needs to be anything but [`QualityGrade::Casual`].
+pub const VERIFIABLE_REQUIREMENTS: [GradeRequirement; 3] = [
+    GradeRequirement::HasDate,
+    GradeRequirement::HasLocation,
+    GradeRequirement::HasMedia,
+];
+
+/// What a verifiable occurrence's community ID needs for
+/// [`QualityGrade::Research`].
+pub const RESEARCH_REQUIREMENTS: [GradeRequirement; 3] = [
+    GradeRequirement::SpeciesRank,
+    GradeRequirement::MinAgreeing(MIN_IDS_FOR_RESEARCH_GRADE),
+    GradeRequirement::AgreeingShare(RESEARCH_GRADE_THRESHOLD),
+];
+
+impl GradeRequirement {
+    /// Whether `row`, with its community ID votes if it has any, meets this
+    /// requirement. Consensus requirements fail without a community ID.
+    pub fn is_met(self, row: &OccurrenceRow, votes: Option<&ConsensusVotes>) -> bool {
+        match self {
+            Self::HasDate => row.event_date.is_some(),
+            Self::HasLocation => row.latitude.is_some() && row.longitude.is_some(),
+            Self::HasMedia => !row.blob_entries().is_empty(),
+            Self::SpeciesRank | Self::MinAgreeing(_) | Self::AgreeingShare(_) => {
+                votes.is_some_and(|v| self.is_met_by_votes(v))
+            }
+        }
+    }
+
+    /// Whether community ID `votes` meet this requirement. Requirements on
+    /// the occurrence itself never are.
+    fn is_met_by_votes(self, votes: &ConsensusVotes) -> bool {
+        match self {
+            Self::SpeciesRank => votes.at_species_rank,
+            Self::MinAgreeing(min) => votes.agreeing >= min,
+            Self::AgreeingShare(share) => votes.agreeing_weight >= votes.total_weight * share,
+            Self::HasDate | Self::HasLocation | Self::HasMedia => false,
+        }
+    }
+}
+
+/// Whether a community ID with `votes` meets [`RESEARCH_REQUIREMENTS`], i.e.
+/// makes a verifiable occurrence [`QualityGrade::Research`].
+pub fn is_research_consensus(votes: &ConsensusVotes) -> bool {
+    RESEARCH_REQUIREMENTS
+        .iter()
+        .all(|r| r.is_met_by_votes(votes))
+}
+
+/// The [`QualityGrade`] of `row`, given its community ID if it has one.
+pub fn compute_grade(row: &OccurrenceRow, consensus: Option<&ConsensusRow>) -> QualityGrade {
+    let votes = consensus.map(|c| &c.votes);
+    let meets_all =
+        |requirements: &[GradeRequirement]| requirements.iter().all(|r| r.is_met(row, votes));
+    if !meets_all(&VERIFIABLE_REQUIREMENTS) {
+        return QualityGrade::Casual;
+    }
+    if meets_all(&RESEARCH_REQUIREMENTS) {
+        QualityGrade::Research
+    } else {
+        QualityGrade::NeedsId
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -212,6 +318,7 @@ mod tests {
                 },
                 alt: None,
                 license: None,
+                caption: None,
             })
             .collect();
         serde_json::to_value(entries).unwrap()
@@ -369,4 +476,137 @@ mod tests {
             assert_eq!(token.parse::<QualityCriterion>().unwrap(), criterion);
         }
     }
+
+    /// An unweighted consensus: every identifier's vote weighs 1.
+    fn consensus(agreeing: i64, identifiers: i64, at_species_rank: bool) -> ConsensusRow {
+        ConsensusRow {
+            occurrence_uri: "at://did:plc:test/x/1".into(),
+            scientific_name: Some("Quercus alba".into()),
+            votes: ConsensusVotes {
+                agreeing,
+                agreeing_weight: agreeing as f64,
+                total_weight: identifiers as f64,
+                at_species_rank,
+            },
+        }
+    }
+
+    /// Each case varies one input around the research-grade boundary. The
+    /// feeds' grade filter renders the same requirements to SQL.
+    #[test]
+    fn grade_follows_the_requirement_lists() {
+        use QualityGrade::{Casual, NeedsId, Research};
+
+        let mut no_date = base_row();
+        no_date.event_date = None;
+        let mut no_media = base_row();
+        no_media.associated_media = None;
+        let cases = [
+            (base_row(), Some(consensus(2, 3, true)), Research),
+            (base_row(), Some(consensus(2, 4, true)), NeedsId),
+            (base_row(), Some(consensus(1, 1, true)), NeedsId),
+            (base_row(), Some(consensus(3, 3, false)), NeedsId),
+            (base_row(), None, NeedsId),
+            (no_date, Some(consensus(3, 3, true)), Casual),
+            (no_media, None, Casual),
+        ];
+        for (row, consensus, expected) in cases {
+            assert_eq!(
+                compute_grade(&row, consensus.as_ref()),
+                expected,
+                "{consensus:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn research_share_is_taken_over_vote_weight() {
+        // Two agreeing tentative votes (0.5 each) against one certain
+        // dissent (1.5): enough identifiers, but a third of the weight.
+        let mut weighted = consensus(2, 3, true);
+        weighted.votes.agreeing_weight = 1.0;
+        weighted.votes.total_weight = 2.5;
+        assert_eq!(
+            compute_grade(&base_row(), Some(&weighted)),
+            QualityGrade::NeedsId
+        );
+        assert!(!is_research_consensus(&weighted.votes));
+        assert!(is_research_consensus(&consensus(2, 3, true).votes));
+    }
+
+    #[test]
+    fn missing_date_location_or_media_is_casual() {
+        let agreed = consensus(3, 3, true);
+        let mut no_date = base_row();
+        no_date.event_date = None;
+        let mut no_location = base_row();
+        no_location.latitude = None;
+        no_location.longitude = None;
+        let mut no_media = base_row();
+        no_media.associated_media = Some(blobs_json(0));
+        for row in [no_date, no_location, no_media] {
+            assert_eq!(compute_grade(&row, Some(&agreed)), QualityGrade::Casual);
+        }
+    }
+
+    #[test]
+    fn verifiable_without_consensus_needs_id() {
+        assert_eq!(compute_grade(&base_row(), None), QualityGrade::NeedsId);
+    }
+
+    #[test]
+    fn adding_media_moves_casual_to_needs_id() {
+        let mut row = base_row();
+        row.associated_media = None;
+        assert_eq!(compute_grade(&row, None), QualityGrade::Casual);
+        row.associated_media = Some(blobs_json(1));
+        assert_eq!(compute_grade(&row, None), QualityGrade::NeedsId);
+    }
+
+    #[test]
+    fn a_second_agreeing_species_id_reaches_research() {
+        let row = base_row();
+        // The observer's own ID alone isn't a community agreement.
+        assert_eq!(
+            compute_grade(&row, Some(&consensus(1, 1, true))),
+            QualityGrade::NeedsId
+        );
+        assert_eq!(
+            compute_grade(&row, Some(&consensus(2, 2, true))),
+            QualityGrade::Research
+        );
+    }
+
+    #[test]
+    fn consensus_above_species_rank_stays_needs_id() {
+        assert_eq!(
+            compute_grade(&base_row(), Some(&consensus(4, 4, false))),
+            QualityGrade::NeedsId
+        );
+    }
+
+    #[test]
+    fn a_dissenting_id_below_two_thirds_drops_research() {
+        let row = base_row();
+        assert_eq!(
+            compute_grade(&row, Some(&consensus(2, 3, true))),
+            QualityGrade::Research
+        );
+        assert_eq!(
+            compute_grade(&row, Some(&consensus(2, 4, true))),
+            QualityGrade::NeedsId
+        );
+    }
+
+    #[test]
+    fn grades_serialize_to_snake_case() {
+        assert_eq!(
+            serde_json::to_string(&QualityGrade::NeedsId).unwrap(),
+            "\"needs_id\""
+        );
+        assert_eq!(
+            serde_json::from_str::<QualityGrade>("\"research\"").unwrap(),
+            QualityGrade::Research
+        );
+    }
 }
@@ -181,3 +181,57 @@ pub async fn upsert_many(
     qb.build().execute(executor).await?;
     Ok(())
 }
+
+/// Source recorded with a category GBIF reported from the IUCN Red List.
+pub const IUCN_SOURCE: &str = "IUCN";
+
+/// Category recorded for a taxon GBIF has no Red List assessment for, so a
+/// backfill can tell it was checked.
+pub const NOT_EVALUATED: &str = "NE";
+
+/// Source recorded with [`NOT_EVALUATED`].
+pub const NOT_EVALUATED_SOURCE: &str = "GBIF";
+
+/// Record a taxon's conservation category (e.g. `"VU"`), replacing any
+/// previous one. Backs the explore feed's conservation-status filter.
+///
+/// An unchanged status leaves the row alone. Returns whether anything was
+/// written.
+pub async fn upsert_conservation_status(
+    executor: impl sqlx::PgExecutor<'_>,
+    taxon_key: i64,
+    category: &str,
+    source: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"INSERT INTO taxon_conservation (taxon_key, category, source, updated_at)
+        VALUES ($1, $2, $3, NOW())
+        ON CONFLICT (taxon_key) DO UPDATE SET
+            category = EXCLUDED.category,
+            source = EXCLUDED.source,
+            updated_at = NOW(),
+            checked_at = NOW()
+        WHERE (taxon_conservation.category, taxon_conservation.source)
+            IS DISTINCT FROM (EXCLUDED.category, EXCLUDED.source)"#,
+    )
+    .bind(taxon_key)
+    .bind(category)
+    .bind(source)
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Note that `taxon_key`'s recorded status was just looked up upstream, so
+/// a backfill can skip it until it's due again. No-op for a key without a
+/// recorded status.
+pub async fn mark_conservation_checked(
+    executor: impl sqlx::PgExecutor<'_>,
+    taxon_key: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE taxon_conservation SET checked_at = NOW() WHERE taxon_key = $1")
+        .bind(taxon_key)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
@@ -49,6 +49,9 @@ impl From<sqlx::Error> for ResolveError {
 pub struct UpstreamMatch {
     pub target_key: i64,
     pub rows: Vec<TaxonRow>,
+    /// IUCN Red List code (e.g. `"VU"`) the upstream reports for the target,
+    /// if it has one.
+    pub conservation_category: Option<String>,
 }
 
 /// External taxonomy source the resolver delegates to on cache miss.
@@ -82,6 +85,21 @@ pub trait TaxonomyCache {
         &self,
         rows: &[TaxonRow],
     ) -> impl std::future::Future<Output = Result<(), sqlx::Error>> + Send;
+
+    /// Record `taxon_key`'s status, returning whether it was missing or
+    /// changed (and so written).
+    fn upsert_conservation_status(
+        &self,
+        taxon_key: i64,
+        category: &str,
+        source: &str,
+    ) -> impl std::future::Future<Output = Result<bool, sqlx::Error>> + Send;
+
+    /// Stamp `taxon_key`'s recorded status as just checked upstream.
+    fn mark_conservation_checked(
+        &self,
+        taxon_key: i64,
+    ) -> impl std::future::Future<Output = Result<(), sqlx::Error>> + Send;
 }
 
 /// Production cache impl backed by Postgres via the [`crate::taxa`] module.
@@ -101,6 +119,19 @@ impl TaxonomyCache for sqlx::PgPool {
     async fn upsert_many(&self, rows: &[TaxonRow]) -> Result<(), sqlx::Error> {
         taxa::upsert_many(self, rows).await
     }
+
+    async fn upsert_conservation_status(
+        &self,
+        taxon_key: i64,
+        category: &str,
+        source: &str,
+    ) -> Result<bool, sqlx::Error> {
+        taxa::upsert_conservation_status(self, taxon_key, category, source).await
+    }
+
+    async fn mark_conservation_checked(&self, taxon_key: i64) -> Result<(), sqlx::Error> {
+        taxa::mark_conservation_checked(self, taxon_key).await
+    }
 }
 
 /// Cache-backed taxon resolver. Looks up the local cache first; on miss,
@@ -136,9 +167,7 @@ impl<'a, C: TaxonomyCache, U: TaxonomyUpstream> Resolver<'a, C, U> {
         else {
             return Ok(None);
         };
-        let target_key = m.target_key;
-        self.cache.upsert_many(&m.rows).await?;
-        Ok(m.rows.into_iter().find(|r| r.taxon_key == target_key))
+        self.persist(m).await
     }
 
     /// Resolve by GBIF usageKey.
@@ -149,9 +178,60 @@ impl<'a, C: TaxonomyCache, U: TaxonomyUpstream> Resolver<'a, C, U> {
         let Some(m) = self.upstream.get_by_key(taxon_key).await? else {
             return Ok(None);
         };
-        let target_key = m.target_key;
+        self.persist(m).await
+    }
+
+    /// Fetch `taxon_key` from upstream even when cached, to record its
+    /// conservation status, and stamp it as checked. Returns `false` if
+    /// upstream doesn't know the key, which is then recorded as Not
+    /// Evaluated so backfills don't fetch it again until it's due.
+    pub async fn refresh_conservation_status(&self, taxon_key: i64) -> Result<bool, ResolveError> {
+        let found = match self.upstream.get_by_key(taxon_key).await? {
+            Some(m) => {
+                self.persist(m).await?;
+                true
+            }
+            None => {
+                self.cache
+                    .upsert_conservation_status(
+                        taxon_key,
+                        taxa::NOT_EVALUATED,
+                        taxa::NOT_EVALUATED_SOURCE,
+                    )
+                    .await?;
+                false
+            }
+        };
+        self.cache.mark_conservation_checked(taxon_key).await?;
+        Ok(found)
+    }
+
+    /// Write an upstream match's rows and the target's conservation status
+    /// through to the cache, returning the target row. The status is keyed
+    /// by the accepted taxon, which is what the feed filters join on; a
+    /// taxon without one is recorded as Not Evaluated so backfills know it
+    /// was checked.
+    ///
+    /// The status is a side record: failing to write it is logged, not
+    /// returned, and the next resolve or backfill pass tries again.
+    async fn persist(&self, m: UpstreamMatch) -> Result<Option<TaxonRow>, ResolveError> {
         self.cache.upsert_many(&m.rows).await?;
-        Ok(m.rows.into_iter().find(|r| r.taxon_key == target_key))
+        let target = m.rows.into_iter().find(|r| r.taxon_key == m.target_key);
+        if let Some(row) = &target {
+            let (category, source) = match &m.conservation_category {
+                Some(category) => (category.as_str(), taxa::IUCN_SOURCE),
+                None => (taxa::NOT_EVALUATED, taxa::NOT_EVALUATED_SOURCE),
+            };
+            let taxon_key = row.accepted_taxon_key.unwrap_or(row.taxon_key);
+            if let Err(e) = self
+                .cache
+                .upsert_conservation_status(taxon_key, category, source)
+                .await
+            {
+                tracing::warn!(taxon_key, error = %e, "Failed to record conservation status");
+            }
+        }
+        Ok(target)
     }
 }
 
@@ -196,6 +276,9 @@ mod tests {
         rows: Mutex<Vec<TaxonRow>>,
         upsert_calls: Mutex<u32>,
         lookup_calls: Mutex<u32>,
+        conservation: Mutex<Vec<(i64, String, String)>>,
+        conservation_fails: bool,
+        checked: Mutex<Vec<i64>>,
     }
 
     impl FakeCache {
@@ -213,6 +296,14 @@ mod tests {
         fn lookups(&self) -> u32 {
             *self.lookup_calls.lock().unwrap()
         }
+
+        fn conservation(&self) -> Vec<(i64, String, String)> {
+            self.conservation.lock().unwrap().clone()
+        }
+
+        fn checked(&self) -> Vec<i64> {
+            self.checked.lock().unwrap().clone()
+        }
     }
 
     impl TaxonomyCache for FakeCache {
@@ -255,6 +346,35 @@ mod tests {
             }
             Ok(())
         }
+
+        async fn upsert_conservation_status(
+            &self,
+            taxon_key: i64,
+            category: &str,
+            source: &str,
+        ) -> Result<bool, sqlx::Error> {
+            if self.conservation_fails {
+                return Err(sqlx::Error::PoolTimedOut);
+            }
+            let mut written = self.conservation.lock().unwrap();
+            let status = (taxon_key, category.to_string(), source.to_string());
+            // Like the real upsert, a repeat of the recorded status is a no-op.
+            let current = written.iter().rev().find(|(key, ..)| *key == taxon_key);
+            if current == Some(&status) {
+                return Ok(false);
+            }
+            written.push(status);
+            Ok(true)
+        }
+
+        async fn mark_conservation_checked(&self, taxon_key: i64) -> Result<(), sqlx::Error> {
+            // Like the real UPDATE, only a recorded status can be stamped.
+            let recorded = self.conservation.lock().unwrap();
+            if recorded.iter().any(|(key, ..)| *key == taxon_key) {
+                self.checked.lock().unwrap().push(taxon_key);
+            }
+            Ok(())
+        }
     }
 
     #[derive(Default)]
@@ -327,6 +447,7 @@ mod tests {
             UpstreamMatch {
                 target_key: 1,
                 rows: vec![target.clone(), ancestor.clone()],
+                conservation_category: Some("VU".to_string()),
             },
         );
         let resolver = Resolver::new(&cache, &upstream);
@@ -342,6 +463,10 @@ mod tests {
         assert_eq!(cache.rows.lock().unwrap().len(), 2);
         assert_eq!(upstream.name_calls(), 1);
         assert_eq!(cache.upserts(), 1);
+        assert_eq!(
+            cache.conservation(),
+            [(1, "VU".to_string(), "IUCN".to_string())]
+        );
     }
 
     #[tokio::test]
@@ -353,6 +478,7 @@ mod tests {
             UpstreamMatch {
                 target_key: 1,
                 rows: vec![make_row(1, "Quercus alba", Some("Plantae"), "ACCEPTED")],
+                conservation_category: None,
             },
         );
         let resolver = Resolver::new(&cache, &upstream);
@@ -369,6 +495,101 @@ mod tests {
         assert_eq!(upstream.name_calls(), 1);
         assert_eq!(cache.upserts(), 1);
         assert_eq!(cache.lookups(), 2);
+        // No upstream status still records that the taxon was checked.
+        assert_eq!(
+            cache.conservation(),
+            [(1, "NE".to_string(), "GBIF".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn refresh_records_status_of_a_cached_taxon() {
+        let mut synonym = make_row(2, "Quercus pedunculata", Some("Plantae"), "SYNONYM");
+        synonym.accepted_taxon_key = Some(1);
+        let cache = FakeCache::seeded(vec![synonym.clone()]);
+        let mut upstream = FakeUpstream::default();
+        upstream.by_key.insert(
+            2,
+            UpstreamMatch {
+                target_key: 2,
+                rows: vec![synonym],
+                conservation_category: Some("EN".to_string()),
+            },
+        );
+        let resolver = Resolver::new(&cache, &upstream);
+
+        assert!(resolver.refresh_conservation_status(2).await.unwrap());
+        // Keyed by the accepted taxon the feed filters join on.
+        assert_eq!(
+            cache.conservation(),
+            [(1, "EN".to_string(), "IUCN".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn refresh_records_a_key_upstream_does_not_know_as_checked() {
+        let cache = FakeCache::default();
+        let upstream = FakeUpstream::default();
+        let resolver = Resolver::new(&cache, &upstream);
+
+        assert!(!resolver.refresh_conservation_status(3).await.unwrap());
+        // Recorded, so the backfill stops fetching it on every pass.
+        assert_eq!(
+            cache.conservation(),
+            [(3, "NE".to_string(), "GBIF".to_string())]
+        );
+        assert_eq!(cache.checked(), [3]);
+
+        // A later check leaves the status alone but stamps it again.
+        assert!(!resolver.refresh_conservation_status(3).await.unwrap());
+        assert_eq!(cache.conservation().len(), 1);
+        assert_eq!(cache.checked(), [3, 3]);
+    }
+
+    #[tokio::test]
+    async fn refresh_stamps_an_unchanged_status_as_checked() {
+        let cache = FakeCache::default();
+        let mut upstream = FakeUpstream::default();
+        upstream.by_key.insert(
+            1,
+            UpstreamMatch {
+                target_key: 1,
+                rows: vec![make_row(1, "Quercus alba", Some("Plantae"), "ACCEPTED")],
+                conservation_category: Some("VU".to_string()),
+            },
+        );
+        let resolver = Resolver::new(&cache, &upstream);
+
+        assert!(resolver.refresh_conservation_status(1).await.unwrap());
+        assert!(resolver.refresh_conservation_status(1).await.unwrap());
+        assert_eq!(
+            cache.conservation(),
+            [(1, "VU".to_string(), "IUCN".to_string())]
+        );
+        assert_eq!(cache.checked(), [1, 1]);
+    }
+
+    #[tokio::test]
+    async fn failed_status_write_still_resolves() {
+        let cache = FakeCache {
+            conservation_fails: true,
+            ..Default::default()
+        };
+        let mut upstream = FakeUpstream::default();
+        upstream.by_key.insert(
+            1,
+            UpstreamMatch {
+                target_key: 1,
+                rows: vec![make_row(1, "Quercus alba", Some("Plantae"), "ACCEPTED")],
+                conservation_category: Some("VU".to_string()),
+            },
+        );
+        let resolver = Resolver::new(&cache, &upstream);
+
+        let row = resolver.resolve_by_key(1).await.unwrap().unwrap();
+        assert_eq!(row.taxon_key, 1);
+        assert_eq!(cache.upserts(), 1);
+        assert!(cache.conservation().is_empty());
     }
 
     #[tokio::test]
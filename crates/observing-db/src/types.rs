@@ -1,4 +1,5 @@
-use crate::quality::QualitySelection;
+use crate::cursor::{FeedCursor, IdentificationCursor};
+use crate::quality::{QualityGrade, QualitySelection};
 use chrono::{DateTime, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
@@ -13,6 +14,10 @@ pub struct BlobEntry {
     /// SPDX license identifier mirrored from the media record (e.g. `CC-BY-4.0`).
     #[serde(default)]
     pub license: Option<String>,
+    /// Free-text caption the uploader gave the image. Not part of the media
+    /// lexicon; written to the record as an extension field.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
 }
 
 /// Image metadata within a blob entry.
@@ -95,7 +100,8 @@ pub struct OccurrenceRow {
     /// `organism_quantity`.
     #[sqlx(default)]
     pub organism_quantity_type: Option<String>,
-    /// Only present in nearby queries
+    /// Only present in nearby queries (meters from the query point) and
+    /// similar-occurrence queries (meters from the reference occurrence)
     #[sqlx(default)]
     pub distance_meters: Option<f64>,
     /// Only present in home feed queries
@@ -107,13 +113,16 @@ impl OccurrenceRow {
     /// Parse `associated_media` JSONB into typed blob entries.
     /// Returns an empty vec if the field is `None` or cannot be deserialized.
     pub fn blob_entries(&self) -> Vec<BlobEntry> {
-        self.associated_media
-            .as_ref()
-            .and_then(|v| Vec::<BlobEntry>::deserialize(v).ok())
-            .unwrap_or_default()
+        parse_blob_entries(self.associated_media.as_ref())
     }
 }
 
+fn parse_blob_entries(associated_media: Option<&serde_json::Value>) -> Vec<BlobEntry> {
+    associated_media
+        .and_then(|v| Vec::<BlobEntry>::deserialize(v).ok())
+        .unwrap_or_default()
+}
+
 /// Identification row returned from SELECT queries
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, TS)]
 pub struct IdentificationRow {
@@ -127,6 +136,9 @@ pub struct IdentificationRow {
     pub taxon_rank: Option<String>,
     #[ts(optional)]
     pub identification_qualifier: Option<String>,
+    /// An [`IdentificationConfidence`] token, if the record carries one.
+    #[ts(optional)]
+    pub confidence: Option<String>,
     #[ts(optional)]
     pub taxon_id: Option<String>,
     #[ts(optional)]
@@ -151,6 +163,103 @@ pub struct IdentificationRow {
     pub genus: Option<String>,
 }
 
+/// Identification row with its standing on the occurrence, as listed by
+/// `identifications::list_for_occurrence`.
+#[derive(Debug, Clone, FromRow)]
+pub struct IdentificationListRow {
+    #[sqlx(flatten)]
+    pub row: IdentificationRow,
+    /// Superseded by a later identification from the same identifier.
+    pub is_withdrawn: bool,
+    /// Names the occurrence's current community ID.
+    pub agrees_with_community: bool,
+}
+
+impl IdentificationListRow {
+    /// Keyset cursor resuming a listing ordered by `sort` after this row.
+    pub fn cursor(&self, sort: IdentificationSort) -> IdentificationCursor {
+        IdentificationCursor {
+            agrees_with_community: match sort {
+                IdentificationSort::Recent => None,
+                IdentificationSort::Agreement => Some(self.agrees_with_community),
+            },
+            date_identified: self.row.date_identified,
+            uri: self.row.uri.clone(),
+        }
+    }
+}
+
+/// How sure an identifier is of their identification.
+///
+/// The serde tokens are what the appview writes into the record's
+/// `confidence` field and what the `identifications.confidence` column holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "bindings/", rename_all = "lowercase")]
+pub enum IdentificationConfidence {
+    Tentative,
+    Likely,
+    Certain,
+}
+
+impl IdentificationConfidence {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Tentative => "tentative",
+            Self::Likely => "likely",
+            Self::Certain => "certain",
+        }
+    }
+
+    /// Vote weight under confidence-weighted consensus
+    /// ([`ConsensusWeighting::Confidence`](crate::community_ids::ConsensusWeighting::Confidence)).
+    /// An identification without a confidence counts as
+    /// [`Likely`](Self::Likely), i.e. one full vote. The `community_ids`
+    /// matview repeats these weights in SQL (20260708000000), so change both
+    /// together.
+    pub fn weight(confidence: Option<Self>) -> f64 {
+        match confidence {
+            Some(Self::Tentative) => 0.5,
+            Some(Self::Likely) | None => 1.0,
+            Some(Self::Certain) => 1.5,
+        }
+    }
+}
+
+impl std::str::FromStr for IdentificationConfidence {
+    type Err = String;
+
+    fn from_str(token: &str) -> Result<Self, Self::Err> {
+        match token {
+            "tentative" => Ok(Self::Tentative),
+            "likely" => Ok(Self::Likely),
+            "certain" => Ok(Self::Certain),
+            _ => Err(format!("unknown identification confidence: {token}")),
+        }
+    }
+}
+
+/// Ordering for an occurrence's identification list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdentificationSort {
+    /// Newest first.
+    #[default]
+    Recent,
+    /// Those naming the community ID first, newest first within each group.
+    Agreement,
+}
+
+/// Options for listing an occurrence's identifications. The default returns
+/// all of them, newest first.
+#[derive(Debug, Clone, Default)]
+pub struct IdentificationListOptions {
+    pub limit: Option<i64>,
+    /// [`IdentificationListRow::cursor`] of the previous page's last row.
+    pub cursor: Option<IdentificationCursor>,
+    pub sort: IdentificationSort,
+}
+
 /// Comment row returned from SELECT queries
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, TS)]
 pub struct CommentRow {
@@ -167,6 +276,19 @@ pub struct CommentRow {
     pub created_at: DateTime<Utc>,
 }
 
+/// A comment in an occurrence's thread (see [`crate::comments::get_thread`])
+#[derive(Debug, Clone, FromRow)]
+pub struct CommentThreadRow {
+    #[sqlx(flatten)]
+    pub comment: CommentRow,
+    /// `reply_to_uri`, when that comment still exists on the same
+    /// occurrence (soft-deleted or not). `None` for top-level comments and
+    /// for orphaned replies whose parent row is gone.
+    pub parent_uri: Option<String>,
+    /// Soft-deleted; `comment.body` is blanked.
+    pub deleted: bool,
+}
+
 /// Like row
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct LikeRow {
@@ -178,6 +300,25 @@ pub struct LikeRow {
     pub created_at: NaiveDateTime,
 }
 
+/// Options for listing who liked a subject. The default returns the first
+/// page, newest like first.
+#[derive(Debug, Clone, Default)]
+pub struct LikersOptions {
+    pub limit: Option<i64>,
+    /// The previous page's last like, by `(created_at, uri)`.
+    pub cursor: Option<FeedCursor>,
+}
+
+/// One liker of a subject, from [`crate::likes::get_likers`]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct LikerRow {
+    /// The like record's URI.
+    pub uri: String,
+    pub did: String,
+    pub created_at: NaiveDateTime,
+}
+
 /// Interaction row returned from SELECT queries
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, TS)]
 pub struct InteractionRow {
@@ -209,6 +350,45 @@ pub struct InteractionRow {
     pub indexed_at: Option<DateTime<Utc>>,
 }
 
+/// How a taxon takes part in an interaction, seen from its own side:
+/// `Actor` when it does the interacting (it pollinates the partner),
+/// `Target` when the partner does it to it, `Mutual` for bidirectional
+/// records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "bindings/")]
+pub enum InteractionRole {
+    Actor,
+    Target,
+    Mutual,
+}
+
+impl InteractionRole {
+    /// The role of the taxon on subject A (or B, when `taxon_is_subject_a`
+    /// is false) of an interaction recorded with `direction`.
+    pub fn from_direction(direction: &str, taxon_is_subject_a: bool) -> Self {
+        match (direction, taxon_is_subject_a) {
+            ("AtoB", true) | ("BtoA", false) => Self::Actor,
+            ("AtoB", false) | ("BtoA", true) => Self::Target,
+            _ => Self::Mutual,
+        }
+    }
+}
+
+/// Interactions of one type between a taxon and one partner taxon, counted
+/// across records and normalized to the taxon's side.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "bindings/")]
+pub struct TaxonInteraction {
+    pub interaction_type: String,
+    pub role: InteractionRole,
+    pub partner_taxon_name: String,
+    #[ts(optional)]
+    pub partner_kingdom: Option<String>,
+    pub interaction_count: i64,
+}
+
 /// Community ID row from the materialized view
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct CommunityIdRow {
@@ -263,6 +443,9 @@ pub struct UpsertOccurrenceParams {
     pub event_date_raw: Option<String>,
     pub longitude: Option<f64>,
     pub latitude: Option<f64>,
+    /// IANA timezone at the point, so an offset-less `eventDate` can be read
+    /// as local time. NULL without coordinates.
+    pub timezone: Option<String>,
     pub coordinate_uncertainty_meters: Option<i32>,
     /// Darwin Core dwc:organismQuantity — free text (an int/float, or
     /// categorical like "many"/"10-100").
@@ -300,6 +483,9 @@ pub struct UpsertIdentificationParams {
     pub scientific_name: String,
     pub taxon_rank: Option<String>,
     pub taxon_id: Option<String>,
+    /// Darwin Core `identificationQualifier` (e.g. `cf.`, `aff.`).
+    pub identification_qualifier: Option<String>,
+    pub confidence: Option<IdentificationConfidence>,
     pub date_identified: DateTime<Utc>,
     pub kingdom: Option<String>,
     /// Resolved GBIF taxon key for the consensus taxon. Populated by the
@@ -357,7 +543,7 @@ pub struct CreateLikeParams {
 #[derive(Debug, Clone, Default)]
 pub struct ExploreFeedOptions {
     pub limit: Option<i64>,
-    pub cursor: Option<String>,
+    pub cursor: Option<FeedCursor>,
     pub taxon: Option<String>,
     pub kingdom: Option<String>,
     pub start_date: Option<String>,
@@ -365,13 +551,18 @@ pub struct ExploreFeedOptions {
     /// Data-quality criteria every returned row must meet. Empty applies no
     /// filter; see [`crate::quality::QualitySelection`].
     pub quality: QualitySelection,
+    /// IUCN codes (e.g. `"VU"`) the consensus taxon's conservation status
+    /// must be one of. Empty applies no filter.
+    pub conservation_categories: Vec<String>,
+    /// Only occurrences of this grade; see [`crate::quality::compute_grade`].
+    pub quality_grade: Option<QualityGrade>,
 }
 
 /// Options for profile feed queries
 #[derive(Debug, Clone, Default)]
 pub struct ProfileFeedOptions {
     pub limit: Option<i64>,
-    pub cursor: Option<String>,
+    pub cursor: Option<FeedCursor>,
     pub feed_type: Option<ProfileFeedType>,
 }
 
@@ -398,20 +589,312 @@ pub struct ProfileCounts {
     pub species: i64,
 }
 
+/// Options for [`crate::feeds::activity`]
+#[derive(Debug, Clone, Default)]
+pub struct ActivityOptions {
+    pub limit: Option<i64>,
+    pub cursor: Option<FeedCursor>,
+}
+
+/// One event in a user's activity stream: someone else identifying, liking
+/// or commenting on their occurrence, or replying to their comment.
+#[derive(Debug, Clone, FromRow)]
+pub struct ActivityRow {
+    /// `identification`, `comment`, `reply` or `like`.
+    pub kind: String,
+    /// The identification, comment or like record.
+    pub uri: String,
+    /// Who acted.
+    pub did: String,
+    /// The occurrence acted on.
+    pub subject_uri: String,
+    pub created_at: DateTime<Utc>,
+    /// Identifications only.
+    pub scientific_name: Option<String>,
+    /// Comments and replies only.
+    pub body: Option<String>,
+    /// Replies only: the user's comment being answered.
+    pub reply_to_uri: Option<String>,
+}
+
 /// Options for home feed queries
 #[derive(Debug, Clone, Default)]
 pub struct HomeFeedOptions {
     pub limit: Option<i64>,
-    pub cursor: Option<String>,
+    pub cursor: Option<FeedCursor>,
     /// Data-quality criteria every returned row must meet. The home feed
     /// always requests `complete`; see [`crate::quality::QualitySelection`].
     pub quality: QualitySelection,
 }
 
+/// Options for the needs-identification feed
+#[derive(Debug, Clone, Default)]
+pub struct NeedsIdFeedOptions {
+    pub limit: Option<i64>,
+    pub cursor: Option<FeedCursor>,
+    pub kingdom: Option<String>,
+    pub bbox: Option<BoundingBox>,
+    /// Only occurrences within this circle; combines with `bbox`.
+    pub near: Option<NearbyArea>,
+}
+
+/// Local observing activity for one taxon, for the taxon page header
+#[derive(Debug, Clone, Default, Serialize, Deserialize, FromRow, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "bindings/")]
+pub struct TaxonLocalStats {
+    pub occurrence_count: i64,
+    /// Earliest event date; `None` when no occurrence is dated.
+    pub first_observed: Option<DateTime<Utc>>,
+    /// Latest event date; `None` when no occurrence is dated.
+    pub last_observed: Option<DateTime<Utc>>,
+    pub observer_count: i64,
+}
+
 /// Options for taxon occurrence queries
 #[derive(Debug, Clone, Default)]
 pub struct TaxonOccurrenceOptions {
     pub limit: Option<i64>,
-    pub cursor: Option<String>,
+    pub cursor: Option<FeedCursor>,
+    pub kingdom: Option<String>,
+}
+
+/// A lat/lng rectangle used to scope aggregate queries to a region.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min_lat: f64,
+    pub min_lng: f64,
+    pub max_lat: f64,
+    pub max_lng: f64,
+}
+
+impl BoundingBox {
+    /// Whether the point lies inside the box, edges inclusive. Like
+    /// `ST_MakeEnvelope`, a box never wraps the antimeridian.
+    pub fn contains(&self, lat: f64, lng: f64) -> bool {
+        (self.min_lat..=self.max_lat).contains(&lat) && (self.min_lng..=self.max_lng).contains(&lng)
+    }
+}
+
+/// A circle around a point, used to scope a query to "near here".
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NearbyArea {
+    pub lat: f64,
+    pub lng: f64,
+    pub radius_meters: f64,
+}
+
+/// An occurrence's community ID with the votes its quality grade depends
+/// on; see [`crate::quality::compute_grade`].
+#[derive(Debug, Clone, FromRow)]
+pub struct ConsensusRow {
+    pub occurrence_uri: String,
+    pub scientific_name: Option<String>,
+    #[sqlx(flatten)]
+    pub votes: ConsensusVotes,
+}
+
+/// The votes behind a community ID, as the research-grade requirements
+/// ([`crate::quality::RESEARCH_REQUIREMENTS`]) read them. Weights follow the
+/// deployment's [`ConsensusWeighting`](crate::community_ids::ConsensusWeighting),
+/// so unweighted they are identifier counts.
+#[derive(Debug, Clone, Copy, PartialEq, FromRow)]
+pub struct ConsensusVotes {
+    /// Identifiers whose current identification names the consensus taxon.
+    pub agreeing: i64,
+    /// Vote weight behind the consensus taxon.
+    pub agreeing_weight: f64,
+    /// Vote weight of every current identification on the occurrence.
+    pub total_weight: f64,
+    /// The consensus taxon resolved to a species or below.
+    pub at_species_rank: bool,
+}
+
+/// A kingdom of the GBIF backbone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Kingdom {
+    /// Stable lowercase identifier, safe in URLs and CSS class names.
+    pub id: &'static str,
+    /// Backbone spelling, as stored in the `kingdom` columns.
+    pub name: &'static str,
+    /// Common English name for display.
+    pub label: &'static str,
+}
+
+/// Every kingdom of the GBIF backbone; the only values the taxonomy
+/// resolver writes to a `kingdom` column.
+pub const KINGDOMS: &[Kingdom] = &[
+    Kingdom::new("animalia", "Animalia", "Animals"),
+    Kingdom::new("archaea", "Archaea", "Archaea"),
+    Kingdom::new("bacteria", "Bacteria", "Bacteria"),
+    Kingdom::new("chromista", "Chromista", "Chromista"),
+    Kingdom::new("fungi", "Fungi", "Fungi"),
+    Kingdom::new("incertae-sedis", "incertae sedis", "Unplaced"),
+    Kingdom::new("plantae", "Plantae", "Plants"),
+    Kingdom::new("protozoa", "Protozoa", "Protozoa"),
+    Kingdom::new("viruses", "Viruses", "Viruses"),
+];
+
+impl Kingdom {
+    const fn new(id: &'static str, name: &'static str, label: &'static str) -> Self {
+        Self { id, name, label }
+    }
+
+    /// The kingdom with this backbone name or id, ignoring case.
+    pub fn lookup(kingdom: &str) -> Option<&'static Kingdom> {
+        KINGDOMS
+            .iter()
+            .find(|k| k.name.eq_ignore_ascii_case(kingdom) || k.id.eq_ignore_ascii_case(kingdom))
+    }
+}
+
+/// Whether `kingdom` names a backbone kingdom; see [`Kingdom::lookup`].
+pub fn is_valid_kingdom(kingdom: &str) -> bool {
+    Kingdom::lookup(kingdom).is_some()
+}
+
+/// One square of [`crate::occurrences::density_grid`]: the occurrences within
+/// half a cell of the grid point (`lat`, `lng`).
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct DensityCell {
+    pub lat: f64,
+    pub lng: f64,
+    pub count: i64,
+}
+
+/// One shift in an occurrence's community ID
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct TaxonChangeRow {
+    pub old_name: Option<String>,
+    pub new_name: String,
+    /// Newest identification on the occurrence when the change was seen.
+    pub triggered_by_uri: Option<String>,
+    pub changed_at: DateTime<Utc>,
+}
+
+/// An occurrence the appview wrote to its author's PDS that the ingester
+/// hasn't indexed at that version yet
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingOccurrenceRow {
+    pub uri: String,
+    /// CID the PDS returned for the write.
+    pub cid: String,
+    pub written_at: DateTime<Utc>,
+}
+
+/// One taxon in the trending-taxa aggregate
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct TrendingTaxonRow {
+    pub scientific_name: String,
+    pub kingdom: Option<String>,
+    pub observation_count: i64,
+    pub distinct_observer_count: i64,
+}
+
+/// An occurrence with photos of a taxon, for ranking the taxon's images
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct TaxonImageRow {
+    pub uri: String,
+    pub did: String,
+    pub associated_media: Option<serde_json::Value>,
+    pub like_count: i64,
+}
+
+impl TaxonImageRow {
+    /// Parse `associated_media` JSONB into typed blob entries, as
+    /// [`OccurrenceRow::blob_entries`] does.
+    pub fn blob_entries(&self) -> Vec<BlobEntry> {
+        parse_blob_entries(self.associated_media.as_ref())
+    }
+}
+
+/// A consensus taxon name matching an autocomplete prefix
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalTaxonMatchRow {
+    pub scientific_name: String,
     pub kingdom: Option<String>,
+    /// The taxon's GBIF rank (e.g. `SPECIES`).
+    pub taxon_rank: Option<String>,
+    pub observation_count: i64,
+}
+
+/// Ranking metric for the contributor leaderboard
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LeaderboardMetric {
+    #[default]
+    Observations,
+    Identifications,
+    Species,
+}
+
+/// One contributor in the leaderboard aggregate
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct LeaderboardRow {
+    pub did: String,
+    pub count: i64,
+}
+
+/// Options for [`crate::observers::nearby_observers`]
+#[derive(Debug, Clone)]
+pub struct NearbyObserversOptions {
+    pub area: NearbyArea,
+    /// Only occurrences created in this trailing window count.
+    pub window: chrono::Duration,
+    /// Left out of the results, e.g. the requesting viewer.
+    pub exclude_did: Option<String>,
+    pub limit: i64,
+}
+
+/// An observer active near a point, from [`crate::observers::nearby_observers`]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct NearbyObserverRow {
+    pub did: String,
+    /// Their occurrences inside the radius and window.
+    pub occurrence_count: i64,
+    pub last_observed: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backbone_kingdoms_are_valid_in_any_case() {
+        assert!(is_valid_kingdom("Plantae"));
+        assert!(is_valid_kingdom("plantae"));
+        assert!(is_valid_kingdom("FUNGI"));
+        assert!(is_valid_kingdom("incertae sedis"));
+        assert!(is_valid_kingdom("incertae-sedis"));
+        assert_eq!(
+            Kingdom::lookup("animalia").map(|k| k.name),
+            Some("Animalia")
+        );
+    }
+
+    #[test]
+    fn unknown_kingdoms_are_invalid() {
+        assert!(!is_valid_kingdom("Plantea"));
+        assert!(!is_valid_kingdom("Plants"));
+        assert!(!is_valid_kingdom(""));
+    }
+
+    #[test]
+    fn kingdom_ids_are_unique_and_url_safe() {
+        let mut ids: Vec<&str> = KINGDOMS.iter().map(|k| k.id).collect();
+        assert!(ids
+            .iter()
+            .all(|id| id.chars().all(|c| c.is_ascii_lowercase() || c == '-')));
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), KINGDOMS.len());
+    }
 }
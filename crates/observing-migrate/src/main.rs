@@ -4,14 +4,31 @@
 //! DDL privileges), runs all pending sqlx migrations, and exits. Packaged as
 //! its own Cloud Run Job so that migrations happen as an explicit deploy step
 //! instead of a side effect of a service container's startup.
+//!
+//! With `--status` it runs nothing: it prints each migration's state
+//! against the database and exits non-zero if the schema has drifted from
+//! this build (an applied migration was edited, failed, or is unknown), so
+//! a deploy can check before rolling out.
 
+use clap::Parser;
 use observing_bootstrap::db::PoolConfig;
+use observing_db::migrate::MigrationState;
 use std::process::ExitCode;
 use tracing::{error, info};
 use tracing_subscriber::{prelude::*, EnvFilter};
 
+#[derive(Parser)]
+#[command(about = "Run Observ.ing database migrations")]
+struct Cli {
+    /// Print applied/pending migrations and exit without migrating.
+    #[arg(long)]
+    status: bool,
+}
+
 #[tokio::main]
 async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
     let env_filter = EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| EnvFilter::new("observing_migrate=info,sqlx::migrate=info"));
 
@@ -37,6 +54,10 @@ async fn main() -> ExitCode {
         }
     };
 
+    if cli.status {
+        return print_status(&pool).await;
+    }
+
     if let Err(e) = observing_db::migrate::migrate(&pool).await {
         error!(error = %e, "Migration failed");
         return ExitCode::from(1);
@@ -45,3 +66,28 @@ async fn main() -> ExitCode {
     info!("Migrations applied successfully");
     ExitCode::SUCCESS
 }
+
+async fn print_status(pool: &sqlx::PgPool) -> ExitCode {
+    let statuses = match observing_db::migrate::status(pool).await {
+        Ok(s) => s,
+        Err(e) => {
+            error!(error = %e, "Failed to read migration status");
+            return ExitCode::from(1);
+        }
+    };
+    for s in &statuses {
+        let state = match s.state {
+            MigrationState::Applied => "applied",
+            MigrationState::Pending => "pending",
+            MigrationState::Modified => "MODIFIED",
+            MigrationState::Failed => "FAILED",
+            MigrationState::Unknown => "UNKNOWN",
+        };
+        println!("{:<16} {:<9} {}", s.version, state, s.description);
+    }
+    if statuses.iter().any(|s| s.state.is_drift()) {
+        error!("Database schema has drifted from this build");
+        return ExitCode::from(1);
+    }
+    ExitCode::SUCCESS
+}
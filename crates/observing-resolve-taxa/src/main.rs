@@ -7,6 +7,14 @@
 //! the resolved key. Refreshes the `community_ids` materialized view at
 //! the end of each pass so downstream filters pick up the new keys.
 //!
+//! Every upstream match also records the taxon's IUCN category in
+//! `taxon_conservation`. Keys that reached the `taxa` cache some other way
+//! (or before that was recorded) are backfilled by a final conservation
+//! pass, so the explore feed's conservation filters cover every
+//! identified taxon rather than only those viewed in the appview. The same
+//! pass re-checks each key once its last check is
+//! `--conservation-recheck-days` old.
+//!
 //! Two modes:
 //!
 //! - **One-shot** (default): exits after a single pass. Use this from
@@ -63,6 +71,11 @@ struct Cli {
     /// start another. Without this flag, exit after one pass.
     #[arg(long)]
     interval_secs: Option<u64>,
+
+    /// Look a taxon's conservation status up again once its last check is
+    /// this many days old.
+    #[arg(long, default_value_t = 30)]
+    conservation_recheck_days: u32,
 }
 
 #[tokio::main]
@@ -121,7 +134,8 @@ enum PassOutcome {
 
 /// Run one resolution pass: resolve unresolved identifications by scientific
 /// name, then mop up any still-unresolved rows that carry a `taxon_id` URI by
-/// resolving the URI directly, and finally refresh the matview.
+/// resolving the URI directly, backfill conservation statuses for resolved
+/// keys, and finally refresh the matview.
 async fn run_pass<U>(
     pool: &PgPool,
     resolver: &Resolver<'_, PgPool, U>,
@@ -133,6 +147,7 @@ where
 {
     run_name_pass(pool, resolver, cli).await?;
     run_taxon_id_pass(pool, resolver, wikidata, cli).await?;
+    run_conservation_pass(pool, resolver, cli).await?;
 
     if cli.dry_run {
         return Ok(PassOutcome::DryRunDone);
@@ -378,6 +393,99 @@ where
     Ok(())
 }
 
+/// Record a conservation status for every resolved identification key that
+/// doesn't have one yet, or whose last check is due again. The resolver only
+/// writes statuses on an upstream match, so keys already in the `taxa` cache
+/// are re-fetched here. Taxa GBIF has no Red List category for, and keys it
+/// doesn't know at all, are recorded as `NE`, and every key fetched is
+/// stamped as checked, so each is fetched at most once per recheck period.
+async fn run_conservation_pass<U>(
+    pool: &PgPool,
+    resolver: &Resolver<'_, PgPool, U>,
+    cli: &Cli,
+) -> Result<(), std::process::ExitCode>
+where
+    U: observing_db::taxonomy_resolver::TaxonomyUpstream,
+{
+    let q = conservation_backlog_query(cli.conservation_recheck_days, cli.limit);
+    // `q` is a static query plus typed integers from `cli`.
+    let keys: Vec<i64> = match sqlx::query(sqlx::AssertSqlSafe(q)).fetch_all(pool).await {
+        Ok(rows) => rows
+            .into_iter()
+            .map(|r| r.get("accepted_taxon_key"))
+            .collect(),
+        Err(e) => {
+            error!(error = %e, "Failed to enumerate taxa due a conservation status check");
+            return Err(std::process::ExitCode::from(1));
+        }
+    };
+
+    info!(
+        keys = keys.len(),
+        "Discovered taxa due a conservation status check"
+    );
+
+    if keys.is_empty() {
+        return Ok(());
+    }
+
+    if cli.dry_run {
+        for key in &keys {
+            info!(key, "Would record conservation status");
+        }
+        return Ok(());
+    }
+
+    let mut recorded = 0u64;
+    let mut not_found = 0u64;
+    let mut errors = 0u64;
+
+    for (i, key) in keys.iter().enumerate() {
+        match resolver.refresh_conservation_status(*key).await {
+            Ok(true) => recorded += 1,
+            Ok(false) => not_found += 1,
+            Err(e) => {
+                errors += 1;
+                warn!(key, error = %e, "Conservation status lookup failed");
+            }
+        }
+
+        if (i + 1) % 50 == 0 {
+            info!(
+                processed = i + 1,
+                total = keys.len(),
+                recorded,
+                not_found,
+                "Conservation-pass progress",
+            );
+        }
+        rate_limit(cli).await;
+    }
+
+    info!(recorded, not_found, errors, "Conservation pass complete");
+    Ok(())
+}
+
+/// Resolved identification keys with no recorded conservation status, or
+/// whose status was last checked more than `recheck_days` ago.
+fn conservation_backlog_query(recheck_days: u32, limit: Option<i64>) -> String {
+    let mut q = format!(
+        r#"SELECT DISTINCT i.accepted_taxon_key
+           FROM identifications i
+           WHERE i.accepted_taxon_key IS NOT NULL
+             AND NOT EXISTS (
+                 SELECT 1 FROM taxon_conservation tc
+                 WHERE tc.taxon_key = i.accepted_taxon_key
+                   AND tc.checked_at > NOW() - make_interval(days => {recheck_days})
+             )
+           ORDER BY i.accepted_taxon_key"#
+    );
+    if let Some(limit) = limit {
+        q.push_str(&format!(" LIMIT {limit}"));
+    }
+    q
+}
+
 /// Resolve a single `taxon_id` URI to a GBIF usage key. GBIF URIs carry the
 /// key directly; iNaturalist / Wikidata URIs cross-walk to one through
 /// Wikidata's external-ID properties. Returns `Ok(None)` for an unrecognized
@@ -431,3 +539,21 @@ async fn rate_limit(cli: &Cli) {
         tokio::time::sleep(Duration::from_millis(cli.rate_limit_ms)).await;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conservation_backlog_skips_keys_checked_within_the_recheck_period() {
+        let q = conservation_backlog_query(30, Some(500));
+        assert!(
+            q.contains("AND tc.checked_at > NOW() - make_interval(days => 30)"),
+            "got: {q}"
+        );
+        assert!(
+            q.ends_with("ORDER BY i.accepted_taxon_key LIMIT 500"),
+            "got: {q}"
+        );
+    }
+}
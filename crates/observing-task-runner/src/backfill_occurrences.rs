@@ -72,10 +72,9 @@ pub async fn run(args: Args) -> ExitCode {
     };
 
     // A bounded timeout keeps a single slow/hanging PDS from stalling the run.
-    let http = reqwest::Client::builder()
-        .timeout(Duration::from_secs(10))
-        .build()
-        .expect("reqwest client build should not fail with defaults");
+    let http = http_client::HttpClientConfig::default()
+        .with_timeout(Duration::from_secs(10))
+        .build();
     let resolver = BlobResolver::with_client(http);
 
     let rows = match fetch_rows(&pool, &args).await {
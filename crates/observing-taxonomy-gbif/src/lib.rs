@@ -31,7 +31,7 @@ pub struct GbifUpstream {
 impl GbifUpstream {
     pub fn new() -> Self {
         Self {
-            api: GbifClient::new(GBIF_BASE_URL),
+            api: GbifClient::new_with_client(GBIF_BASE_URL, http_client::client()),
         }
     }
 }
@@ -176,7 +176,28 @@ fn build_upstream_match(m: NameUsageMatch) -> Option<UpstreamMatch> {
         target_key,
     ));
 
-    Some(UpstreamMatch { target_key, rows })
+    Some(UpstreamMatch {
+        target_key,
+        rows,
+        conservation_category: iucn_category(&m),
+    })
+}
+
+/// IUCN Red List codes accepted as a conservation category.
+const IUCN_CATEGORIES: [&str; 9] = ["EX", "EW", "CR", "EN", "VU", "NT", "LC", "DD", "NE"];
+
+/// Find the match's additional_status entry tagged with the IUCN dataset
+/// and return its status_code, if it's a recognised Red List code.
+fn iucn_category(m: &NameUsageMatch) -> Option<String> {
+    let code = m
+        .additional_status
+        .iter()
+        .find(|s| s.dataset_alias.as_deref() == Some("IUCN"))?
+        .status_code
+        .as_deref()?
+        .trim()
+        .to_ascii_uppercase();
+    IUCN_CATEGORIES.contains(&code.as_str()).then_some(code)
 }
 
 /// Build a [`TaxonRow`] for one classification ancestor, also recording its
@@ -410,4 +431,44 @@ mod tests {
         let target = upstream.rows.iter().find(|r| r.taxon_key == 1).unwrap();
         assert!(target.phylum.is_none());
     }
+
+    #[test]
+    fn iucn_status_becomes_conservation_category() {
+        let m = match_from_json(json!({
+            "synonym": false,
+            "usage": { "key": "1", "name": "Ursus maritimus", "canonicalName": "Ursus maritimus", "rank": "SPECIES" },
+            "classification": [],
+            "additionalStatus": [
+                { "datasetAlias": "CITES", "statusCode": "II" },
+                { "datasetAlias": "IUCN", "statusCode": "vu" },
+            ]
+        }));
+
+        let upstream = build_upstream_match(m).unwrap();
+        assert_eq!(upstream.conservation_category.as_deref(), Some("VU"));
+    }
+
+    #[test]
+    fn unrecognised_or_missing_iucn_status_is_none() {
+        let unknown = match_from_json(json!({
+            "synonym": false,
+            "usage": { "key": "1", "name": "Quercus alba", "canonicalName": "Quercus alba", "rank": "SPECIES" },
+            "classification": [],
+            "additionalStatus": [{ "datasetAlias": "IUCN", "statusCode": "XX" }]
+        }));
+        let missing = match_from_json(json!({
+            "synonym": false,
+            "usage": { "key": "1", "name": "Quercus alba", "canonicalName": "Quercus alba", "rank": "SPECIES" },
+            "classification": []
+        }));
+
+        assert!(build_upstream_match(unknown)
+            .unwrap()
+            .conservation_category
+            .is_none());
+        assert!(build_upstream_match(missing)
+            .unwrap()
+            .conservation_category
+            .is_none());
+    }
 }
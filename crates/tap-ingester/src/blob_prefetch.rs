@@ -0,0 +1,189 @@
+//! Warm the appview's media cache for newly ingested occurrences.
+//!
+//! Blobs are otherwise only cached when a browser first asks for one, so the
+//! first viewer of a new observation waits on the author's PDS. With
+//! `MEDIA_WARM_URL` set, each occurrence upsert queues its blob CIDs and a
+//! background worker POSTs them to `{MEDIA_WARM_URL}/media/warm`,
+//! authenticated with `MEDIA_WARM_SECRET` (the appview refuses the call
+//! without it).
+//!
+//! Best-effort throughout: the queue is bounded and drops work when full,
+//! and a failed warm request is only logged. Ingestion never waits on it.
+
+use std::time::Duration;
+
+use observing_db::types::BlobEntry;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+/// Occurrences waiting to be warmed before new ones are dropped.
+const QUEUE_CAPACITY: usize = 256;
+/// Per-request timeout for warm calls; the appview answers before fetching.
+const WARM_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Serialize, PartialEq)]
+struct WarmBlob {
+    did: String,
+    cid: String,
+}
+
+#[derive(Serialize)]
+struct WarmRequest<'a> {
+    blobs: &'a [WarmBlob],
+}
+
+/// Handle for queueing warm requests; the worker lives as long as it does.
+pub struct BlobPrefetcher {
+    queue: mpsc::Sender<Vec<WarmBlob>>,
+}
+
+impl BlobPrefetcher {
+    /// `MEDIA_WARM_URL` (the appview's base URL) and `MEDIA_WARM_SECRET`,
+    /// or `None` when prefetching is off. Both must be set.
+    pub fn from_env() -> Option<Self> {
+        let env = |name| {
+            std::env::var(name)
+                .ok()
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+        };
+        let url = env("MEDIA_WARM_URL")?;
+        let Some(secret) = env("MEDIA_WARM_SECRET") else {
+            warn!("MEDIA_WARM_URL is set without MEDIA_WARM_SECRET; blob prefetch disabled");
+            return None;
+        };
+        info!(url = %url, "Blob prefetch enabled");
+        Some(Self::spawn(&url, secret))
+    }
+
+    /// Start the worker posting to `{base_url}/media/warm` with `secret` as
+    /// the bearer token.
+    pub fn spawn(base_url: &str, secret: String) -> Self {
+        let endpoint = format!("{}/media/warm", base_url.trim_end_matches('/'));
+        let client = http_client::HttpClientConfig::default()
+            .with_timeout(WARM_TIMEOUT)
+            .build();
+        let (queue, mut rx) = mpsc::channel::<Vec<WarmBlob>>(QUEUE_CAPACITY);
+        tokio::spawn(async move {
+            while let Some(blobs) = rx.recv().await {
+                let sent = client
+                    .post(&endpoint)
+                    .bearer_auth(&secret)
+                    .json(&WarmRequest { blobs: &blobs })
+                    .send()
+                    .await
+                    .and_then(|r| r.error_for_status());
+                match sent {
+                    Ok(_) => debug!(count = blobs.len(), "Queued blobs for cache warm"),
+                    Err(e) => warn!(error = %e, "Media warm request failed"),
+                }
+            }
+        });
+        Self { queue }
+    }
+
+    /// Queue the blobs in an occurrence's `associated_media` for warming.
+    /// Drops them if the queue is full.
+    pub fn enqueue(&self, did: &str, associated_media: Option<&Value>) {
+        let blobs = blob_cids(did, associated_media);
+        if blobs.is_empty() {
+            return;
+        }
+        if self.queue.try_send(blobs).is_err() {
+            debug!(did, "Blob prefetch queue full; skipping");
+        }
+    }
+}
+
+/// One warm entry per blob in `associated_media`.
+fn blob_cids(did: &str, associated_media: Option<&Value>) -> Vec<WarmBlob> {
+    let Some(entries) = associated_media.and_then(|v| Vec::<BlobEntry>::deserialize(v).ok()) else {
+        return Vec::new();
+    };
+    entries
+        .iter()
+        .map(|entry| WarmBlob {
+            did: did.to_string(),
+            cid: entry.image.ref_.cid().to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn media(cids: &[&str]) -> Value {
+        Value::Array(
+            cids.iter()
+                .map(|cid| {
+                    serde_json::json!({
+                        "image": { "ref": { "$link": cid }, "mimeType": "image/jpeg" },
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn cids_come_from_associated_media() {
+        let blobs = blob_cids("did:plc:abc", Some(&media(&["bafy1", "bafy2"])));
+        assert_eq!(
+            blobs,
+            [
+                WarmBlob {
+                    did: "did:plc:abc".into(),
+                    cid: "bafy1".into()
+                },
+                WarmBlob {
+                    did: "did:plc:abc".into(),
+                    cid: "bafy2".into()
+                },
+            ]
+        );
+        assert!(blob_cids("did:plc:abc", None).is_empty());
+        assert!(blob_cids("did:plc:abc", Some(&serde_json::json!("junk"))).is_empty());
+    }
+
+    #[tokio::test]
+    async fn occurrence_blobs_are_posted_to_the_warm_endpoint() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/media/warm"))
+            .and(header("authorization", "Bearer warm-secret"))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let prefetcher = BlobPrefetcher::spawn(&format!("{}/", server.uri()), "warm-secret".into());
+        prefetcher.enqueue("did:plc:abc", Some(&media(&["bafy1", "bafy2"])));
+        // Nothing to warm: no request.
+        prefetcher.enqueue("did:plc:abc", None);
+
+        let requests = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let received = server.received_requests().await.unwrap_or_default();
+                if !received.is_empty() {
+                    return received;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("warm request sent");
+
+        let body: Value = requests[0].body_json().unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({ "blobs": [
+                { "did": "did:plc:abc", "cid": "bafy1" },
+                { "did": "did:plc:abc", "cid": "bafy2" },
+            ]})
+        );
+    }
+}
@@ -357,6 +357,10 @@ const DASHBOARD_HTML: &str = r#"<!DOCTYPE html>
     <tr><td>Interactions</td><td id="interactions">0</td></tr>
     <tr><td>Likes</td><td id="likes">0</td></tr>
     <tr><td>Errors</td><td id="errors">0</td></tr>
+    <tr><td>Skipped: no record body</td><td id="skipped-missing-record">0</td></tr>
+    <tr><td>Skipped: parse error</td><td id="skipped-parse-error">0</td></tr>
+    <tr><td>Skipped: missing field</td><td id="skipped-missing-field">0</td></tr>
+    <tr><td>Skipped: unsupported collection</td><td id="skipped-unsupported-collection">0</td></tr>
   </table>
 
   <h2>Tap</h2>
@@ -430,6 +434,10 @@ const DASHBOARD_HTML: &str = r#"<!DOCTYPE html>
         document.getElementById('interactions').textContent = fmtNum(stats.stats.interactions);
         document.getElementById('likes').textContent = fmtNum(stats.stats.likes);
         document.getElementById('errors').textContent = fmtNum(stats.stats.errors);
+        document.getElementById('skipped-missing-record').textContent = fmtNum(stats.stats.skipped_missing_record);
+        document.getElementById('skipped-parse-error').textContent = fmtNum(stats.stats.skipped_parse_error);
+        document.getElementById('skipped-missing-field').textContent = fmtNum(stats.stats.skipped_missing_field);
+        document.getElementById('skipped-unsupported-collection').textContent = fmtNum(stats.stats.skipped_unsupported_collection);
 
         document.getElementById('tap-repo-count').textContent = fmtNum(tap.repoCount);
         document.getElementById('tap-record-count').textContent = fmtNum(tap.recordCount);
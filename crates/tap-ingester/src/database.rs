@@ -5,11 +5,17 @@
 //! scalar params (did, uri, cid, time, record JSON) rather than a
 //! firehose-coupled `CommitInfo` struct.
 
-use crate::error::{IngesterError, Result};
+use crate::blob_prefetch::BlobPrefetcher;
+use crate::error::{IngesterError, Result, SkipReason};
 use crate::media_resolver::MediaResolver;
 use chrono::{DateTime, Utc};
 use observing_bootstrap::db::PoolConfig;
-use observing_db::identifications::CommunityIdsRefresher;
+use observing_collections::{
+    COMMENT_COLLECTION, IDENTIFICATION_COLLECTION, INTERACTION_COLLECTION, LIKE_COLLECTION,
+    OCCURRENCE_COLLECTION,
+};
+use observing_db::community_ids::ConsensusWeighting;
+use observing_db::identifications::{self, CommunityIdsRefresher};
 use observing_db::processing;
 use serde_json::Value;
 use sqlx::postgres::PgPool;
@@ -21,6 +27,19 @@ use tracing::{debug, info, warn};
 /// the firehose hot path.
 const COMMUNITY_IDS_REFRESH_DEBOUNCE: Duration = Duration::from_secs(2);
 
+/// From `CONSENSUS_WEIGHTING` (`unweighted` or `confidence`). Unset is
+/// unweighted; an unknown value is warned about and ignored rather than
+/// failing startup.
+fn consensus_weighting_from_env() -> ConsensusWeighting {
+    let Ok(raw) = std::env::var("CONSENSUS_WEIGHTING") else {
+        return ConsensusWeighting::default();
+    };
+    raw.trim().parse().unwrap_or_else(|e| {
+        warn!(value = %raw, error = %e, "ignoring unknown CONSENSUS_WEIGHTING");
+        ConsensusWeighting::default()
+    })
+}
+
 /// Look up the occurrence owner and create a notification (skips self-notifications)
 async fn notify_occurrence_owner(
     pool: &PgPool,
@@ -65,16 +84,29 @@ macro_rules! process_or_fail {
         match processing::$method($($arg),*) {
             Ok(p) => p,
             Err(e) => {
-                return Err(IngesterError::Processing(format!("{}: {}", $label, e)));
+                return Err(IngesterError::Processing(
+                    SkipReason::from(&e),
+                    format!("{}: {}", $label, e),
+                ));
             }
         }
     };
 }
 
+fn unsupported_collection(collection: &str) -> IngesterError {
+    IngesterError::Processing(
+        SkipReason::UnsupportedCollection,
+        format!("unsupported collection {collection}"),
+    )
+}
+
 pub struct Database {
     pool: PgPool,
     media_resolver: MediaResolver,
     community_ids_refresher: CommunityIdsRefresher,
+    /// Warms the appview's media cache for new occurrences; `None` unless
+    /// `MEDIA_WARM_URL` is set.
+    blob_prefetcher: Option<BlobPrefetcher>,
 }
 
 impl Database {
@@ -84,10 +116,19 @@ impl Database {
         info!("Database connection established");
         let community_ids_refresher =
             CommunityIdsRefresher::spawn(pool.clone(), COMMUNITY_IDS_REFRESH_DEBOUNCE);
+        let weighting = consensus_weighting_from_env();
+        if identifications::set_consensus_weighting(&pool, weighting).await? {
+            info!(
+                weighting = weighting.as_str(),
+                "Consensus weighting changed; refreshing community IDs"
+            );
+            community_ids_refresher.request_refresh();
+        }
         Ok(Self {
             pool,
             media_resolver: MediaResolver::new(),
             community_ids_refresher,
+            blob_prefetcher: BlobPrefetcher::from_env(),
         })
     }
 
@@ -135,6 +176,11 @@ impl Database {
         }
 
         observing_db::occurrences::upsert(&self.pool, &parsed.params).await?;
+        self.confirm_write(uri).await;
+
+        if let Some(prefetcher) = &self.blob_prefetcher {
+            prefetcher.enqueue(did, parsed.params.associated_media.as_ref());
+        }
 
         Ok(())
     }
@@ -142,9 +188,19 @@ impl Database {
     pub async fn delete_occurrence(&self, uri: &str) -> Result<()> {
         debug!("Deleting occurrence: {}", uri);
         observing_db::occurrences::delete(&self.pool, uri).await?;
+        self.confirm_write(uri).await;
         Ok(())
     }
 
+    /// Stamp the appview's pending write of `uri` as seen. Best-effort: the
+    /// record itself is already indexed, so a failure only leaves the write
+    /// listed as pending.
+    async fn confirm_write(&self, uri: &str) {
+        if let Err(e) = observing_db::occurrence_writes::confirm(&self.pool, uri).await {
+            warn!(uri, error = %e, "Failed to confirm occurrence write");
+        }
+    }
+
     /// Identifications are written with `accepted_taxon_key = NULL`. The
     /// `observing-resolve-taxa` background worker picks up unresolved rows
     /// on its next pass and stamps them — keeping ingest decoupled from
@@ -280,6 +336,45 @@ impl Database {
         Ok(())
     }
 
+    /// Route a record to the upsert for its collection, or to the delete
+    /// when `record` is `None`. Shared by the live loop and the
+    /// failed-record replay so both write through the same path. A delete
+    /// also clears the URI from the failure ledger so an older failed
+    /// commit can't be replayed over it.
+    pub async fn apply(
+        &self,
+        collection: &str,
+        did: &str,
+        uri: &str,
+        cid: &str,
+        time: DateTime<Utc>,
+        record: Option<&Value>,
+    ) -> Result<()> {
+        let Some(record) = record else {
+            match collection {
+                OCCURRENCE_COLLECTION => self.delete_occurrence(uri).await?,
+                IDENTIFICATION_COLLECTION => self.delete_identification(uri).await?,
+                COMMENT_COLLECTION => self.delete_comment(uri).await?,
+                INTERACTION_COLLECTION => self.delete_interaction(uri).await?,
+                LIKE_COLLECTION => self.delete_like(uri).await?,
+                _ => return Err(unsupported_collection(collection)),
+            }
+            observing_db::failed_records::clear(&self.pool, uri).await?;
+            return Ok(());
+        };
+        match collection {
+            OCCURRENCE_COLLECTION => self.upsert_occurrence(did, uri, cid, time, record).await,
+            IDENTIFICATION_COLLECTION => {
+                self.upsert_identification(did, uri, cid, time, record)
+                    .await
+            }
+            COMMENT_COLLECTION => self.upsert_comment(did, uri, cid, time, record).await,
+            INTERACTION_COLLECTION => self.upsert_interaction(did, uri, cid, time, record).await,
+            LIKE_COLLECTION => self.upsert_like(did, uri, cid, time, record).await,
+            _ => Err(unsupported_collection(collection)),
+        }
+    }
+
     /// Append (or bump) a row in `ingester.failed_records` describing a
     /// record we've decided to drop after `process_record` returned
     /// `Err`. Idempotent: repeat failures for the same URI bump
@@ -1,18 +1,57 @@
 //! Error types for tap-ingester.
 
+use observing_db::processing::ProcessingError;
 use std::fmt;
 
+/// Why a record was dropped without being written.
+///
+/// Counted per reason in [`IngesterStats`](crate::types::IngesterStats),
+/// apart from database errors, so a mapping bug (every record of a
+/// collection skipped for the same reason) stands out from sporadic dirty
+/// data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The event carried no record body, or it wasn't JSON.
+    MissingRecord,
+    /// The record didn't deserialize into its lexicon type.
+    ParseError,
+    /// A field the write path requires is missing or invalid (e.g. an
+    /// identification's scientificName or subject).
+    MissingField,
+    /// No write path exists for the record's collection.
+    UnsupportedCollection,
+}
+
+impl From<&ProcessingError> for SkipReason {
+    fn from(err: &ProcessingError) -> Self {
+        match err {
+            ProcessingError::Deserialization(_) => SkipReason::ParseError,
+            ProcessingError::InvalidField(_) => SkipReason::MissingField,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum IngesterError {
     Database(Box<sqlx::Error>),
     Decode(String),
     Config(String),
     /// Record could not be parsed into its lexicon type or is missing
-    /// fields the ingester treats as required (e.g. coordinates,
-    /// eventDate on an occurrence). Surfaced to the main loop so the
-    /// drop lands in `ingester.failed_records` instead of being
+    /// fields the ingester treats as required. Surfaced to the main loop so
+    /// the drop lands in `ingester.failed_records` instead of being
     /// silently warned-and-acked.
-    Processing(String),
+    Processing(SkipReason, String),
+}
+
+impl IngesterError {
+    /// The skip reason for a record-level failure; `None` for database,
+    /// decode and config errors.
+    pub fn skip_reason(&self) -> Option<SkipReason> {
+        match self {
+            IngesterError::Processing(reason, _) => Some(*reason),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for IngesterError {
@@ -21,7 +60,7 @@ impl fmt::Display for IngesterError {
             IngesterError::Database(err) => write!(f, "Database error: {}", err),
             IngesterError::Decode(msg) => write!(f, "Decode error: {}", msg),
             IngesterError::Config(msg) => write!(f, "Configuration error: {}", msg),
-            IngesterError::Processing(msg) => write!(f, "Processing error: {}", msg),
+            IngesterError::Processing(_, msg) => write!(f, "Processing error: {}", msg),
         }
     }
 }
@@ -80,6 +119,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_processing_error_skip_reasons() {
+        let parse = serde_json::from_str::<u32>("\"x\"").unwrap_err();
+        assert_eq!(
+            SkipReason::from(&ProcessingError::Deserialization(parse)),
+            SkipReason::ParseError
+        );
+        assert_eq!(
+            SkipReason::from(&ProcessingError::InvalidField(
+                "missing scientificName".into()
+            )),
+            SkipReason::MissingField
+        );
+    }
+
+    #[test]
+    fn test_skip_reason_only_on_processing_errors() {
+        let err = IngesterError::Processing(SkipReason::UnsupportedCollection, "x".into());
+        assert_eq!(err.skip_reason(), Some(SkipReason::UnsupportedCollection));
+        assert_eq!(format!("{err}"), "Processing error: x");
+        assert_eq!(IngesterError::Decode("x".into()).skip_reason(), None);
+    }
+
     #[test]
     fn test_error_is_debug() {
         let err = IngesterError::Decode("x".to_string());
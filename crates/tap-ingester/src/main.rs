@@ -26,6 +26,29 @@
 //!                         falls back to `sqlite:///data/tap.db`
 //!                         (instance-ephemeral on Cloud Run).
 //!   PORT                  HTTP server port (default 8080).
+//!   LIKEABLE_COLLECTIONS  Comma-separated collections whose records likes
+//!                         are kept for (default: the occurrence
+//!                         collection). Entries outside the ingested
+//!                         collections are ignored with a warning.
+//!   INGEST_OCCURRENCES, INGEST_IDENTIFICATIONS, INGEST_COMMENTS,
+//!   INGEST_INTERACTIONS, INGEST_LIKES
+//!                         Set to `false` (or `0`) to skip that collection
+//!                         entirely, e.g. to reprocess only likes without
+//!                         reindexing occurrences. All default to on.
+//!   INGEST_SAMPLE_RATE    Fraction (0.0–1.0) of records to process, chosen
+//!                         by hashing the record URI so the sample is stable
+//!                         across restarts. For load-testing staging
+//!                         ingesters; default 1.0 (everything).
+//!   CONSENSUS_WEIGHTING   `confidence` to weight community ID votes by
+//!                         each identification's confidence; default
+//!                         `unweighted` (one vote per identifier). Stored
+//!                         for the `community_ids` matview at startup.
+//!   MEDIA_WARM_URL        Appview base URL. When set, each ingested
+//!                         occurrence's blobs are POSTed to its
+//!                         `/media/warm` so the media cache is populated
+//!                         before the first view. Off by default.
+//!   MEDIA_WARM_SECRET     Bearer token for `/media/warm`; must match the
+//!                         appview's. Prefetch stays off without it.
 //!
 //! HTTP routes (see `dashboard` module for handlers):
 //!   GET /                  Combined ingester + Tap status page.
@@ -33,11 +56,14 @@
 //!   GET /api/stats         Ingester counters (JSON).
 //!   GET /api/tap-stats     Tap-side counts, buffers, cursors (JSON).
 
+mod blob_prefetch;
 mod dashboard;
 mod database;
 mod error;
 mod lag_probe;
 mod media_resolver;
+mod replay;
+mod sampling;
 mod server;
 mod subject_resolver;
 mod types;
@@ -48,11 +74,13 @@ use chrono::Utc;
 use clap::Parser;
 use dashboard::DashboardState;
 use database::Database;
+use error::SkipReason;
 use observing_collections::{
     COMMENT_COLLECTION, IDENTIFICATION_COLLECTION, INTERACTION_COLLECTION, LIKE_COLLECTION,
     OCCURRENCE_COLLECTION,
 };
 use observing_db::failed_records::FailedRecord;
+use sampling::Sampler;
 use serde_json::Value;
 use server::{ServerState, SharedState};
 use subject_resolver::SubjectResolver;
@@ -110,13 +138,26 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     });
 
-    let db = Database::connect(&database_url).await?;
+    let db = Arc::new(Database::connect(&database_url).await?);
     pool_cell.set(db.pool().clone()).ok();
 
+    // Re-run ledgered failures with backoff; see `replay`.
+    tokio::spawn(replay::run(db.clone()));
+
     // Bring up Tap. If TAP_URL is set we treat it as already running;
     // otherwise spawn the bundled binary as a child process. The
     // _process handle keeps the spawned Tap alive for the lifetime of
     // this binary (kill_on_drop).
+    let ingested = IngestedCollections::from_env();
+    info!(collections = ?ingested.wanted(), "ingesting these collections");
+    let sampler = Sampler::from_env();
+    if sampler.rate() < 1.0 {
+        warn!(
+            sample_rate = sampler.rate(),
+            "sampling enabled: only this fraction of records is processed"
+        );
+    }
+
     let admin_password = std::env::var("TAP_ADMIN_PASSWORD").ok();
     let (_process, tap) = match std::env::var("TAP_URL") {
         Ok(url) => {
@@ -132,12 +173,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             let mut builder = TapConfig::builder()
                 .database_url(resolve_tap_database_url())
                 .signal_collection(OCCURRENCE_COLLECTION)
-                .collection_filter(OCCURRENCE_COLLECTION)
-                .collection_filter(IDENTIFICATION_COLLECTION)
-                .collection_filter(COMMENT_COLLECTION)
-                .collection_filter(INTERACTION_COLLECTION)
-                .collection_filter(LIKE_COLLECTION)
                 .log_level(LogLevel::Info);
+            for collection in ingested.wanted() {
+                builder = builder.collection_filter(collection);
+            }
             if let Some(pw) = admin_password.as_deref() {
                 builder = builder.admin_password(pw.to_string());
             }
@@ -191,18 +230,44 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     state.write().await.connected = true;
     info!("tap channel connected");
 
-    while let Ok(received) = channel.recv().await {
+    // The HTTP server drains itself on SIGTERM; this loop stops taking new
+    // events on the same signal so the current record finishes and Tap is
+    // shut down below instead of being killed mid-write.
+    let likeable =
+        parse_likeable_collections(std::env::var("LIKEABLE_COLLECTIONS").ok().as_deref());
+    info!(?likeable, "tracking likes on these collections");
+
+    let shutdown = observing_bootstrap::shutdown_signal();
+    tokio::pin!(shutdown);
+
+    loop {
+        let received = tokio::select! {
+            received = channel.recv() => match received {
+                Ok(received) => received,
+                Err(_) => {
+                    warn!("tap channel closed");
+                    break;
+                }
+            },
+            () = &mut shutdown => {
+                info!("shutdown signal received, stopping ingest");
+                break;
+            }
+        };
         let mut should_ack = true;
         if let Event::Record(record) = &received.event {
-            if let Err(err) = process_record(&db, record, &state).await {
-                // process_record already logged + bumped stats.errors.
+            if let Err(err) =
+                process_record(&db, record, &state, &likeable, &ingested, &sampler).await
+            {
+                // process_record already logged + bumped stats.errors or
+                // the skip counter for the reason.
                 // Reactively ask the resolver for the subject DID; if it
                 // *added* a new DID to Tap, suppress this event's ack so
                 // Tap redelivers after the foreign repo backfills.
                 // Otherwise (subject already tracked, no subject, or
                 // resolver couldn't add), record the drop in
                 // `ingester.failed_records` so the loss is observable
-                // and a future replay job can re-attempt — then ack and
+                // and the replay task can re-attempt — then ack and
                 // move on. Looping on unresolvable records would
                 // saturate the queue.
                 let json = record_json(record);
@@ -245,7 +310,6 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     state.write().await.connected = false;
-    warn!("tap channel closed");
     // _process drops here, sending SIGTERM to the embedded Tap.
     Ok(())
 }
@@ -403,72 +467,66 @@ async fn process_record(
     db: &Database,
     record: &RecordEvent,
     state: &SharedState,
+    likeable: &[&str],
+    ingested: &IngestedCollections,
+    sampler: &Sampler,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let collection = record.collection.as_str();
     let action = action_to_str(record.action);
 
-    let event_type = match collection {
-        OCCURRENCE_COLLECTION => "occurrence",
-        IDENTIFICATION_COLLECTION => "identification",
-        COMMENT_COLLECTION => "comment",
-        INTERACTION_COLLECTION => "interaction",
-        LIKE_COLLECTION => "like",
-        _ => return Ok(()),
+    // Disabled collections return before the write and the stats update.
+    let Some(event_type) = ingested.event_type(collection) else {
+        return Ok(());
     };
 
     let uri = format_uri(record);
+    if !sampler.keeps(&uri) {
+        return Ok(());
+    }
+    let cid = record.cid.as_deref().unwrap_or("");
 
     let result = if matches!(record.action, RecordAction::Delete) {
-        match collection {
-            OCCURRENCE_COLLECTION => db.delete_occurrence(&uri).await,
-            IDENTIFICATION_COLLECTION => db.delete_identification(&uri).await,
-            COMMENT_COLLECTION => db.delete_comment(&uri).await,
-            INTERACTION_COLLECTION => db.delete_interaction(&uri).await,
-            LIKE_COLLECTION => db.delete_like(&uri).await,
-            _ => unreachable!(),
-        }
+        db.apply(collection, &record.did, &uri, cid, Utc::now(), None)
+            .await
     } else {
         let Some(record_value) = record_json(record) else {
             warn!(%uri, "record event without parseable JSON; skipping");
+            state
+                .write()
+                .await
+                .stats
+                .record_skip(SkipReason::MissingRecord);
             return Ok(());
         };
-        let cid = record.cid.as_deref().unwrap_or("");
-        let now = Utc::now();
 
-        // Like records are filtered to occurrence-subjects only.
-        if collection == LIKE_COLLECTION && !subject_uri_is_occurrence(&record_value) {
+        // Like records are filtered to the configured subject collections.
+        if collection == LIKE_COLLECTION && !like_subject_is_tracked(&record_value, likeable) {
             return Ok(());
         }
 
-        match collection {
-            OCCURRENCE_COLLECTION => {
-                db.upsert_occurrence(&record.did, &uri, cid, now, &record_value)
-                    .await
-            }
-            IDENTIFICATION_COLLECTION => {
-                db.upsert_identification(&record.did, &uri, cid, now, &record_value)
-                    .await
-            }
-            COMMENT_COLLECTION => {
-                db.upsert_comment(&record.did, &uri, cid, now, &record_value)
-                    .await
-            }
-            INTERACTION_COLLECTION => {
-                db.upsert_interaction(&record.did, &uri, cid, now, &record_value)
-                    .await
-            }
-            LIKE_COLLECTION => {
-                db.upsert_like(&record.did, &uri, cid, now, &record_value)
-                    .await
-            }
-            _ => unreachable!(),
-        }
+        db.apply(
+            collection,
+            &record.did,
+            &uri,
+            cid,
+            Utc::now(),
+            Some(&record_value),
+        )
+        .await
     };
 
     let mut s = state.write().await;
     if let Err(e) = result {
-        error!(%uri, error = %e, "{} {} failed", event_type, action);
-        s.stats.errors += 1;
+        match e.skip_reason() {
+            Some(reason) => {
+                warn!(%uri, ?reason, error = %e, "{} {} skipped", event_type, action);
+                s.stats.record_skip(reason);
+            }
+            None => {
+                error!(%uri, error = %e, "{} {} failed", event_type, action);
+                s.stats.errors += 1;
+            }
+        }
         Err(e.into())
     } else {
         match event_type {
@@ -489,12 +547,127 @@ async fn process_record(
     }
 }
 
-fn subject_uri_is_occurrence(record: &Value) -> bool {
-    record
+/// Whether a like's `subject.uri` points into one of `likeable`. The URI is
+/// parsed rather than substring-matched, so only the collection segment
+/// counts.
+fn like_subject_is_tracked(record: &Value, likeable: &[&str]) -> bool {
+    let Some(uri) = record
         .get("subject")
         .and_then(|s| s.get("uri"))
         .and_then(|u| u.as_str())
-        .is_some_and(|uri| uri.contains(OCCURRENCE_COLLECTION))
+    else {
+        return false;
+    };
+    let Ok(uri) = jacquard_common::types::string::AtUri::new(uri) else {
+        return false;
+    };
+    uri.collection()
+        .is_some_and(|c| likeable.contains(&c.as_str()))
+}
+
+/// Which collections this ingester writes, from the `INGEST_*` env toggles.
+///
+/// Disabled collections are left out of the embedded Tap's collection
+/// filters, and their events are skipped by [`process_record`] — the latter
+/// also covers a connected `TAP_URL` instance whose filters we don't control.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct IngestedCollections {
+    occurrences: bool,
+    identifications: bool,
+    comments: bool,
+    interactions: bool,
+    likes: bool,
+}
+
+impl Default for IngestedCollections {
+    fn default() -> Self {
+        Self {
+            occurrences: true,
+            identifications: true,
+            comments: true,
+            interactions: true,
+            likes: true,
+        }
+    }
+}
+
+impl IngestedCollections {
+    fn from_env() -> Self {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    /// Each toggle is on unless its variable is `false`/`0`/`no`/`off`.
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let enabled = |name: &str| match lookup(name) {
+            Some(raw) => !matches!(
+                raw.trim().to_ascii_lowercase().as_str(),
+                "false" | "0" | "no" | "off"
+            ),
+            None => true,
+        };
+        Self {
+            occurrences: enabled("INGEST_OCCURRENCES"),
+            identifications: enabled("INGEST_IDENTIFICATIONS"),
+            comments: enabled("INGEST_COMMENTS"),
+            interactions: enabled("INGEST_INTERACTIONS"),
+            likes: enabled("INGEST_LIKES"),
+        }
+    }
+
+    /// The stats/log label for an event in `collection`, or `None` when the
+    /// collection is unknown or disabled.
+    fn event_type(&self, collection: &str) -> Option<&'static str> {
+        match collection {
+            OCCURRENCE_COLLECTION if self.occurrences => Some("occurrence"),
+            IDENTIFICATION_COLLECTION if self.identifications => Some("identification"),
+            COMMENT_COLLECTION if self.comments => Some("comment"),
+            INTERACTION_COLLECTION if self.interactions => Some("interaction"),
+            LIKE_COLLECTION if self.likes => Some("like"),
+            _ => None,
+        }
+    }
+
+    /// Enabled collections, for Tap's collection filters.
+    fn wanted(&self) -> Vec<&'static str> {
+        [
+            OCCURRENCE_COLLECTION,
+            IDENTIFICATION_COLLECTION,
+            COMMENT_COLLECTION,
+            INTERACTION_COLLECTION,
+            LIKE_COLLECTION,
+        ]
+        .into_iter()
+        .filter(|c| self.event_type(c).is_some())
+        .collect()
+    }
+}
+
+/// Collections a like's subject may belong to, from a comma-separated list.
+/// Unset or empty keeps the default of occurrences only; entries that aren't
+/// collections we ingest are dropped with a warning, since likes on them
+/// could never be attached to anything.
+fn parse_likeable_collections(raw: Option<&str>) -> Vec<&'static str> {
+    const LIKEABLE: [&str; 4] = [
+        OCCURRENCE_COLLECTION,
+        IDENTIFICATION_COLLECTION,
+        COMMENT_COLLECTION,
+        INTERACTION_COLLECTION,
+    ];
+    let mut collections = Vec::new();
+    for entry in raw.unwrap_or("").split(',').map(str::trim) {
+        if entry.is_empty() {
+            continue;
+        }
+        match LIKEABLE.iter().find(|c| **c == entry) {
+            Some(c) if !collections.contains(c) => collections.push(*c),
+            Some(_) => {}
+            None => warn!(collection = entry, "ignoring unknown likeable collection"),
+        }
+    }
+    if collections.is_empty() {
+        collections.push(OCCURRENCE_COLLECTION);
+    }
+    collections
 }
 
 fn format_uri(record: &RecordEvent) -> String {
@@ -524,3 +697,114 @@ fn action_to_str(action: RecordAction) -> &'static str {
         _ => "unknown",
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn like_of(uri: &str) -> Value {
+        json!({ "subject": { "uri": uri, "cid": "bafyrei" }, "createdAt": "2026-01-01T00:00:00Z" })
+    }
+
+    #[test]
+    fn occurrence_likes_are_kept_by_default() {
+        let likeable = parse_likeable_collections(None);
+        let like = like_of("at://did:plc:abc/bio.lexicons.temp.v0-1.occurrence/1");
+        assert!(like_subject_is_tracked(&like, &likeable));
+    }
+
+    #[test]
+    fn identification_likes_are_kept_only_when_configured() {
+        let like = like_of("at://did:plc:abc/bio.lexicons.temp.v0-1.identification/1");
+        assert!(!like_subject_is_tracked(
+            &like,
+            &parse_likeable_collections(None)
+        ));
+
+        let likeable = parse_likeable_collections(Some(
+            "bio.lexicons.temp.v0-1.occurrence, bio.lexicons.temp.v0-1.identification",
+        ));
+        assert!(like_subject_is_tracked(&like, &likeable));
+    }
+
+    #[test]
+    fn unrelated_likes_are_dropped() {
+        let likeable = parse_likeable_collections(None);
+        // The old substring check would have kept this: the occurrence NSID
+        // appears in the rkey, not the collection.
+        let sneaky =
+            like_of("at://did:plc:abc/app.bsky.feed.post/bio.lexicons.temp.v0-1.occurrence");
+        assert!(!like_subject_is_tracked(&sneaky, &likeable));
+        assert!(!like_subject_is_tracked(
+            &like_of("at://did:plc:abc/app.bsky.feed.post/1"),
+            &likeable
+        ));
+        assert!(!like_subject_is_tracked(&like_of("not a uri"), &likeable));
+        assert!(!like_subject_is_tracked(&json!({}), &likeable));
+    }
+
+    #[test]
+    fn unknown_likeable_collections_are_ignored() {
+        assert_eq!(
+            parse_likeable_collections(Some("app.bsky.feed.post")),
+            vec![OCCURRENCE_COLLECTION]
+        );
+        assert_eq!(
+            parse_likeable_collections(Some(
+                "ing.observ.temp.comment,app.bsky.feed.post,ing.observ.temp.comment"
+            )),
+            vec![COMMENT_COLLECTION]
+        );
+    }
+
+    fn ingested_with(vars: &[(&str, &str)]) -> IngestedCollections {
+        IngestedCollections::from_lookup(|name| {
+            vars.iter()
+                .find(|(k, _)| *k == name)
+                .map(|(_, v)| v.to_string())
+        })
+    }
+
+    #[test]
+    fn every_collection_is_ingested_by_default() {
+        let ingested = ingested_with(&[]);
+        assert_eq!(ingested, IngestedCollections::default());
+        assert_eq!(ingested.wanted().len(), 5);
+    }
+
+    #[test]
+    fn disabled_occurrences_are_skipped_before_any_write() {
+        let ingested = ingested_with(&[("INGEST_OCCURRENCES", "false")]);
+
+        // No event type means process_record returns before db.apply and
+        // before touching the stats counters.
+        assert_eq!(ingested.event_type(OCCURRENCE_COLLECTION), None);
+        assert_eq!(
+            ingested.event_type(IDENTIFICATION_COLLECTION),
+            Some("identification")
+        );
+        assert!(!ingested.wanted().contains(&OCCURRENCE_COLLECTION));
+        assert!(ingested.wanted().contains(&LIKE_COLLECTION));
+    }
+
+    #[test]
+    fn toggle_values_are_lenient() {
+        let ingested = ingested_with(&[
+            ("INGEST_LIKES", " OFF "),
+            ("INGEST_COMMENTS", "0"),
+            ("INGEST_INTERACTIONS", "true"),
+        ]);
+        assert!(!ingested.likes);
+        assert!(!ingested.comments);
+        assert!(ingested.interactions);
+        assert_eq!(
+            ingested.wanted(),
+            vec![
+                OCCURRENCE_COLLECTION,
+                IDENTIFICATION_COLLECTION,
+                INTERACTION_COLLECTION
+            ]
+        );
+    }
+}
@@ -15,7 +15,6 @@
 use atproto_blob_resolver::BlobResolver;
 use observing_db::processing::AssociatedMediaRef;
 use observing_db::types::{BlobEntry, BlobImage, BlobRef};
-use reqwest::Client;
 use serde_json::Value;
 use std::time::Duration;
 use tracing::warn;
@@ -28,10 +27,9 @@ pub struct MediaResolver {
 
 impl MediaResolver {
     pub fn new() -> Self {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(10))
-            .build()
-            .expect("reqwest client build should not fail with defaults");
+        let client = http_client::HttpClientConfig::default()
+            .with_timeout(Duration::from_secs(10))
+            .build();
         Self {
             blob_resolver: BlobResolver::with_client(client),
         }
@@ -67,7 +65,7 @@ impl Default for MediaResolver {
     }
 }
 
-/// Pull the blob ref, mime type, alt text, and license out of a `bio.lexicons.temp.v0-1.media`
+/// Pull the blob ref, mime type, alt text, license, and caption out of a `bio.lexicons.temp.v0-1.media`
 /// record JSON to build a `BlobEntry`. Returns `None` if the record is missing
 /// the required blob fields.
 fn media_record_to_blob_entry(record: &Value) -> Option<BlobEntry> {
@@ -86,6 +84,10 @@ fn media_record_to_blob_entry(record: &Value) -> Option<BlobEntry> {
         .get("license")
         .and_then(|a| a.as_str())
         .map(|s| s.to_string());
+    let caption = record
+        .get("caption")
+        .and_then(|a| a.as_str())
+        .map(|s| s.to_string());
     Some(BlobEntry {
         image: BlobImage {
             ref_: BlobRef::Link { link: cid },
@@ -93,6 +95,7 @@ fn media_record_to_blob_entry(record: &Value) -> Option<BlobEntry> {
         },
         alt,
         license,
+        caption,
     })
 }
 
@@ -139,6 +142,25 @@ mod tests {
         let entry = media_record_to_blob_entry(&record).expect("should parse");
         assert!(entry.alt.is_none());
         assert!(entry.license.is_none());
+        assert!(entry.caption.is_none());
+    }
+
+    #[test]
+    fn reads_caption_extension_field() {
+        let record = json!({
+            "image": {
+                "ref": { "$link": "bafyreiabc" },
+                "mimeType": "image/jpeg",
+            },
+            "alt": "Underside of a leaf",
+            "caption": "Note the hairs along the veins",
+        });
+        let entry = media_record_to_blob_entry(&record).expect("should parse");
+        assert_eq!(entry.alt.as_deref(), Some("Underside of a leaf"));
+        assert_eq!(
+            entry.caption.as_deref(),
+            Some("Note the hairs along the veins")
+        );
     }
 
     #[test]
@@ -0,0 +1,121 @@
+//! Periodic replay of `ingester.failed_records`.
+//!
+//! Records land in the ledger when `process_record` fails and there's no
+//! cross-repo subject to wait for. Many of those failures are transient (a
+//! DB blip, a subject that arrived later), so this task re-runs them
+//! through the same write path with exponential backoff and clears the ones
+//! that now succeed. Rows superseded by a newer commit or delete for the
+//! same URI are cleared without replaying. Rows that reach [`MAX_REPLAY_ATTEMPTS`] are left in the
+//! ledger but no longer retried here; they need a code fix, after which the
+//! task runner's `replay-failed-records` job picks them up.
+//!
+//! Each pass also prunes appview occurrence writes the ingester confirmed
+//! more than [`CONFIRMED_WRITE_RETENTION`] ago.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use observing_db::failed_records::{self, Backoff, FailedRecord, RetryableRecord};
+use observing_db::occurrence_writes;
+use tracing::{info, warn};
+
+use crate::database::Database;
+use crate::error::{IngesterError, SkipReason};
+
+/// How often the ledger is scanned.
+const REPLAY_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Upper bound on rows fetched per scan.
+const REPLAY_BATCH: i64 = 100;
+
+/// Attempts (including the original failure) before this task stops
+/// retrying a record.
+const MAX_REPLAY_ATTEMPTS: i32 = 8;
+
+/// Wait after the first failure, doubling with each further attempt up to
+/// a 12-hour ceiling.
+const BACKOFF: Backoff = Backoff {
+    base: chrono::Duration::minutes(5),
+    max: chrono::Duration::hours(12),
+};
+
+/// How long a confirmed occurrence write is kept before pruning.
+const CONFIRMED_WRITE_RETENTION: chrono::Duration = chrono::Duration::days(1);
+
+/// Scan the ledger every [`REPLAY_INTERVAL`] until the process exits.
+pub async fn run(db: Arc<Database>) {
+    let mut ticker = tokio::time::interval(REPLAY_INTERVAL);
+    loop {
+        ticker.tick().await;
+        if let Err(e) = replay_due(&db).await {
+            warn!(error = %e, "failed-record replay pass failed");
+        }
+        match occurrence_writes::prune_confirmed(db.pool(), CONFIRMED_WRITE_RETENTION).await {
+            Ok(0) => {}
+            Ok(pruned) => info!(pruned, "pruned confirmed occurrence writes"),
+            Err(e) => warn!(error = %e, "occurrence write prune failed"),
+        }
+    }
+}
+
+async fn replay_due(db: &Database) -> Result<(), sqlx::Error> {
+    let rows =
+        failed_records::list_retryable(db.pool(), MAX_REPLAY_ATTEMPTS, BACKOFF, REPLAY_BATCH)
+            .await?;
+    for row in &rows {
+        if failed_records::superseded(db.pool(), row).await? {
+            failed_records::clear(db.pool(), &row.uri).await?;
+            info!(uri = %row.uri, "dropped failed record superseded by a newer commit");
+            continue;
+        }
+        match replay(db, row).await {
+            Ok(()) => {
+                failed_records::clear(db.pool(), &row.uri).await?;
+                info!(uri = %row.uri, attempts = row.attempts, "replayed failed record");
+            }
+            Err(e) => {
+                let error = e.to_string();
+                failed_records::record(
+                    db.pool(),
+                    FailedRecord {
+                        uri: &row.uri,
+                        collection: &row.collection,
+                        did: &row.did,
+                        cid: row.cid.as_deref(),
+                        action: &row.action,
+                        record_json: row.record_json.as_ref(),
+                        error: &error,
+                    },
+                )
+                .await?;
+                if row.attempts + 1 >= MAX_REPLAY_ATTEMPTS {
+                    warn!(uri = %row.uri, %error, "giving up on retrying failed record");
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn replay(db: &Database, row: &RetryableRecord) -> crate::error::Result<()> {
+    let record = match (row.action.as_str(), row.record_json.as_ref()) {
+        ("delete", _) => None,
+        (_, Some(json)) => Some(json),
+        (_, None) => {
+            return Err(IngesterError::Processing(
+                SkipReason::MissingRecord,
+                "no record JSON to replay".to_string(),
+            ))
+        }
+    };
+    db.apply(
+        &row.collection,
+        &row.did,
+        &row.uri,
+        row.cid.as_deref().unwrap_or(""),
+        Utc::now(),
+        record,
+    )
+    .await
+}
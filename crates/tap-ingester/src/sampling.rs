@@ -0,0 +1,132 @@
+//! Deterministic firehose sampling for load tests.
+//!
+//! A staging ingester can be told to process only a fraction of commits
+//! (`INGEST_SAMPLE_RATE=0.1` for 10% of traffic) to measure downstream
+//! capacity without the full firehose. Whether a record is kept depends only
+//! on its AT URI, so:
+//!
+//! - the same records are kept across restarts and redeliveries, and
+//! - a kept record's updates and delete are kept too, so the sampled
+//!   tables stay internally consistent.
+//!
+//! The URI is hashed with 64-bit FNV-1a rather than `std`'s `DefaultHasher`,
+//! whose output may change between Rust releases.
+
+use tracing::warn;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Keeps a stable `rate` fraction of records, chosen by URI hash.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sampler {
+    rate: f64,
+}
+
+impl Default for Sampler {
+    fn default() -> Self {
+        Self { rate: 1.0 }
+    }
+}
+
+impl Sampler {
+    /// `rate` is clamped to `0.0..=1.0`; NaN keeps everything.
+    pub fn new(rate: f64) -> Self {
+        let rate = if rate.is_nan() {
+            1.0
+        } else {
+            rate.clamp(0.0, 1.0)
+        };
+        Self { rate }
+    }
+
+    /// From `INGEST_SAMPLE_RATE`. Unset keeps everything; an unparseable
+    /// value is warned about and ignored rather than failing startup.
+    pub fn from_env() -> Self {
+        let Ok(raw) = std::env::var("INGEST_SAMPLE_RATE") else {
+            return Self::default();
+        };
+        match raw.trim().parse::<f64>() {
+            Ok(rate) => Self::new(rate),
+            Err(e) => {
+                warn!(value = %raw, error = %e, "ignoring unparseable INGEST_SAMPLE_RATE");
+                Self::default()
+            }
+        }
+    }
+
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    /// Whether the record at `uri` falls inside the sample.
+    pub fn keeps(&self, uri: &str) -> bool {
+        if self.rate >= 1.0 {
+            return true;
+        }
+        // Top 53 bits → uniform in [0, 1), exactly representable as f64.
+        let position = (fnv1a(uri.as_bytes()) >> 11) as f64 / (1u64 << 53) as f64;
+        position < self.rate
+    }
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(FNV_OFFSET_BASIS, |hash, &b| {
+        (hash ^ u64::from(b)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uri(i: usize) -> String {
+        format!(
+            "at://did:plc:user{}/bio.lexicons.temp.v0-1.occurrence/3k{i}",
+            i % 97
+        )
+    }
+
+    #[test]
+    fn kept_fraction_tracks_the_rate() {
+        const N: usize = 20_000;
+        for rate in [0.1, 0.25, 0.5, 0.9] {
+            let sampler = Sampler::new(rate);
+            let kept = (0..N).filter(|&i| sampler.keeps(&uri(i))).count();
+            let fraction = kept as f64 / N as f64;
+            assert!(
+                (fraction - rate).abs() < 0.02,
+                "rate {rate}: kept {fraction}"
+            );
+        }
+    }
+
+    #[test]
+    fn sampling_is_stable_per_uri() {
+        let a = Sampler::new(0.3);
+        let b = Sampler::new(0.3);
+        for i in 0..1_000 {
+            assert_eq!(a.keeps(&uri(i)), b.keeps(&uri(i)));
+        }
+    }
+
+    #[test]
+    fn lower_rates_keep_a_subset() {
+        let low = Sampler::new(0.1);
+        let high = Sampler::new(0.5);
+        for i in 0..1_000 {
+            if low.keeps(&uri(i)) {
+                assert!(high.keeps(&uri(i)));
+            }
+        }
+    }
+
+    #[test]
+    fn bounds_and_defaults() {
+        assert!(Sampler::default().keeps("at://anything"));
+        assert!((0..100).all(|i| Sampler::new(1.5).keeps(&uri(i))));
+        assert!((0..100).all(|i| !Sampler::new(0.0).keeps(&uri(i))));
+        assert_eq!(Sampler::new(-1.0).rate(), 0.0);
+        assert_eq!(Sampler::new(f64::NAN).rate(), 1.0);
+    }
+}
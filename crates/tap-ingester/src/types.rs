@@ -2,6 +2,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::error::SkipReason;
+
 /// Statistics about the ingester's operation
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct IngesterStats {
@@ -11,6 +13,24 @@ pub struct IngesterStats {
     pub interactions: u64,
     pub likes: u64,
     pub errors: u64,
+    /// Records dropped per [`SkipReason`]. Kept apart from `errors` (failed
+    /// database writes) so dirty data and mapping bugs are told apart.
+    pub skipped_missing_record: u64,
+    pub skipped_parse_error: u64,
+    pub skipped_missing_field: u64,
+    pub skipped_unsupported_collection: u64,
+}
+
+impl IngesterStats {
+    pub fn record_skip(&mut self, reason: SkipReason) {
+        let counter = match reason {
+            SkipReason::MissingRecord => &mut self.skipped_missing_record,
+            SkipReason::ParseError => &mut self.skipped_parse_error,
+            SkipReason::MissingField => &mut self.skipped_missing_field,
+            SkipReason::UnsupportedCollection => &mut self.skipped_unsupported_collection,
+        };
+        *counter += 1;
+    }
 }
 
 /// A recent event for display in the dashboard
@@ -34,6 +54,22 @@ mod tests {
         assert_eq!(stats.identifications, 0);
         assert_eq!(stats.likes, 0);
         assert_eq!(stats.errors, 0);
+        assert_eq!(stats.skipped_parse_error, 0);
+    }
+
+    #[test]
+    fn test_record_skip_counts_each_reason_separately() {
+        let mut stats = IngesterStats::default();
+        stats.record_skip(SkipReason::MissingRecord);
+        stats.record_skip(SkipReason::ParseError);
+        stats.record_skip(SkipReason::ParseError);
+        stats.record_skip(SkipReason::MissingField);
+        stats.record_skip(SkipReason::UnsupportedCollection);
+        assert_eq!(stats.skipped_missing_record, 1);
+        assert_eq!(stats.skipped_parse_error, 2);
+        assert_eq!(stats.skipped_missing_field, 1);
+        assert_eq!(stats.skipped_unsupported_collection, 1);
+        assert_eq!(stats.errors, 0, "skips aren't database errors");
     }
 
     #[test]
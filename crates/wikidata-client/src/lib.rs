@@ -1,5 +1,6 @@
-//! Wikidata client for biodiversity lookups: taxon images and entity URLs via
-//! external identifiers (e.g. GBIF taxon ids).
+//! Wikidata client for biodiversity lookups: taxon images, descriptions,
+//! Wikipedia sitelinks and entity URLs via external identifiers (e.g. GBIF
+//! taxon ids).
 //!
 //! A thin domain layer over [`sparql_client`], pinned to Wikidata's public
 //! query service. For raw SPARQL against arbitrary endpoints, use
@@ -30,6 +31,34 @@ pub use sparql_client::{Error, SparqlBinding, SparqlValue};
 const WIKIDATA_ENDPOINT: &str = "https://query.wikidata.org/sparql";
 const DEFAULT_USER_AGENT: &str = "wikidata-client/0.1 (Rust; https://github.com/observ-ing/core)";
 
+/// Descriptive data for the Wikidata item carrying an external identifier.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EntitySummary {
+    /// Entity page, e.g. `https://www.wikidata.org/wiki/Q158746`.
+    pub url: String,
+    /// Short description in the requested language.
+    pub description: Option<String>,
+    /// Primary image (P18) as a Commons thumbnail URL.
+    pub image: Option<String>,
+    /// Wikipedia articles about the item, sorted by language.
+    pub sitelinks: Vec<Sitelink>,
+}
+
+impl EntitySummary {
+    /// The item's Q-id, taken from [`url`](Self::url).
+    pub fn qid(&self) -> Option<&str> {
+        self.url.rsplit('/').next().filter(|s| s.starts_with('Q'))
+    }
+}
+
+/// A Wikipedia article linked from a Wikidata item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sitelink {
+    /// Wikipedia language code, e.g. `en`.
+    pub language: String,
+    pub url: String,
+}
+
 /// Client for querying Wikidata's SPARQL endpoint for biodiversity data.
 pub struct WikidataClient {
     client: SparqlClient,
@@ -46,8 +75,14 @@ impl WikidataClient {
     /// Wikidata's query service requires a meaningful user agent — requests
     /// with generic agents may be throttled or blocked.
     pub fn with_user_agent(user_agent: &str) -> Self {
+        Self::with_endpoint(WIKIDATA_ENDPOINT, user_agent)
+    }
+
+    /// Create a client against a different SPARQL endpoint — e.g. a mirror,
+    /// or a mock server in tests.
+    pub fn with_endpoint(endpoint: &str, user_agent: &str) -> Self {
         Self {
-            client: SparqlClient::with_user_agent(WIKIDATA_ENDPOINT, user_agent),
+            client: SparqlClient::with_user_agent(endpoint, user_agent),
         }
     }
 
@@ -115,6 +150,85 @@ impl WikidataClient {
             .collect()
     }
 
+    /// Summarize the item carrying `external_id` on `property`: its
+    /// `language` description, primary image (as a thumbnail rendered at
+    /// `thumbnail_width` pixels) and Wikipedia sitelinks.
+    ///
+    /// Returns `None` if no item matches, the arguments are malformed, or the
+    /// query fails.
+    pub async fn get_entity_summary_by_property(
+        &self,
+        property: &str,
+        external_id: &str,
+        language: &str,
+        thumbnail_width: u32,
+    ) -> Option<EntitySummary> {
+        if !is_property_id(property) {
+            return None;
+        }
+        let query = format!(
+            r#"SELECT ?item ?description ?image ?article ?lang WHERE {{
+    ?item wdt:{property} "{}" .
+    OPTIONAL {{ ?item schema:description ?description . FILTER(LANG(?description) = "{}") }}
+    OPTIONAL {{ ?item wdt:P18 ?image }}
+    OPTIONAL {{
+        ?article schema:about ?item ;
+                 schema:inLanguage ?lang ;
+                 schema:isPartOf/wikibase:wikiGroup "wikipedia" .
+    }}
+}}"#,
+            escape_literal(external_id),
+            escape_literal(language),
+        );
+
+        /// One row per sitelink (or a single row without one). The item-level
+        /// columns repeat on every row.
+        #[derive(Deserialize)]
+        struct Row {
+            item: String,
+            description: Option<String>,
+            image: Option<String>,
+            article: Option<String>,
+            lang: Option<String>,
+        }
+
+        let rows: Vec<Row> = match self.client.query_into(&query).await {
+            Ok(r) => r,
+            Err(e) => {
+                warn!(error = ?e, "Wikidata summary lookup failed");
+                return None;
+            }
+        };
+
+        // An identifier should name one item; if several carry it, keep the
+        // first consistently.
+        let item = rows.first()?.item.clone();
+        let mut summary = EntitySummary {
+            url: item.replace(
+                "http://www.wikidata.org/entity/",
+                "https://www.wikidata.org/wiki/",
+            ),
+            ..Default::default()
+        };
+        for row in rows.into_iter().filter(|r| r.item == item) {
+            if summary.description.is_none() {
+                summary.description = row.description;
+            }
+            if summary.image.is_none() {
+                summary.image = row.image.map(|i| to_thumbnail_url(&i, thumbnail_width));
+            }
+            if let (Some(url), Some(language)) = (row.article, row.lang) {
+                if !summary.sitelinks.iter().any(|s| s.url == url) {
+                    summary.sitelinks.push(Sitelink { language, url });
+                }
+            }
+        }
+        summary
+            .sitelinks
+            .sort_by(|a, b| a.language.cmp(&b.language));
+        Some(summary)
+    }
+
     /// Cross-walk an external taxon identifier to a GBIF backbone usage key.
     ///
     /// Finds the Wikidata item carrying `external_id` on `source_property`
@@ -294,6 +408,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_summary_qid() {
+        let summary = EntitySummary {
+            url: "https://www.wikidata.org/wiki/Q158746".into(),
+            ..Default::default()
+        };
+        assert_eq!(summary.qid(), Some("Q158746"));
+        assert_eq!(EntitySummary::default().qid(), None);
+    }
+
     #[test]
     fn test_is_property_id() {
         assert!(is_property_id("P846"));
@@ -65,6 +65,7 @@ export const OAK_OBSERVATION: Occurrence = {
   likeCount: 3,
   viewerHasLiked: false,
   qualityIssues: [],
+  qualityGrade: "research",
 };
 
 export const FERN_OBSERVATION: Occurrence = {
@@ -87,6 +88,7 @@ export const FERN_OBSERVATION: Occurrence = {
   createdAt: "2026-04-10T14:05:00Z",
   likeCount: 0,
   qualityIssues: [],
+  qualityGrade: "needs_id",
 };
 
 export const OAK_TAXON_DETAIL: TaxonDetail = {
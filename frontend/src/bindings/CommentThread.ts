@@ -0,0 +1,24 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Profile } from "./Profile";
+
+/**
+ * A comment with its replies nested under it, oldest first
+ */
+export type CommentThread = {
+  /**
+   * The comment was deleted and its body is blank; it's kept because it
+   * still has replies.
+   */
+  deleted: boolean;
+  replies: Array<CommentThread>;
+  commenter: Profile;
+  uri: string;
+  cid: string;
+  did: string;
+  subject_uri: string;
+  subject_cid: string;
+  body: string;
+  reply_to_uri?: string;
+  reply_to_cid?: string;
+  created_at: string;
+};
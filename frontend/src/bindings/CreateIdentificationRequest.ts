@@ -1,4 +1,5 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { IdentificationConfidence } from "./IdentificationConfidence";
 
 export type CreateIdentificationRequest = {
   occurrenceUri: string;
@@ -11,4 +12,14 @@ export type CreateIdentificationRequest = {
    * when validation doesn't return a kingdom of its own.
    */
   kingdom?: string;
+  /**
+   * Darwin Core `identificationQualifier` expressing uncertainty about the
+   * name, e.g. `cf.` or `aff.`.
+   */
+  identificationQualifier?: string;
+  /**
+   * How sure the identifier is. Tokens outside the scale are rejected
+   * when the body is deserialized.
+   */
+  confidence?: IdentificationConfidence;
 };
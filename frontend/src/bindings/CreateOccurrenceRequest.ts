@@ -36,4 +36,15 @@ export type CreateOccurrenceRequest = {
    * URI). Written to the auto-created identification's `taxonID` field.
    */
   taxonId?: string;
+  /**
+   * Whether to auto-create an identification from `scientific_name`.
+   * Defaults to `true`; `false` posts the occurrence without committing
+   * to an ID.
+   */
+  autoIdentify?: boolean;
+  /**
+   * Coarsest rank (e.g. `species`) the resolved taxon may have for the
+   * identification to be auto-created. When omitted, any rank is.
+   */
+  minAutoIdRank?: string;
 };
@@ -6,6 +6,16 @@ import type { Profile } from "./Profile";
  */
 export type Identification = {
   identifier: Profile;
+  /**
+   * Superseded by a later identification from the same identifier. Only
+   * set by the per-occurrence identification listing.
+   */
+  isWithdrawn?: boolean;
+  /**
+   * Names the occurrence's current community ID. Only set by the
+   * per-occurrence identification listing.
+   */
+  agreesWithCommunity?: boolean;
   uri: string;
   cid: string;
   did: string;
@@ -14,6 +24,10 @@ export type Identification = {
   scientific_name: string;
   taxon_rank?: string;
   identification_qualifier?: string;
+  /**
+   * An [`IdentificationConfidence`] token, if the record carries one.
+   */
+  confidence?: string;
   taxon_id?: string;
   identification_verification_status?: string;
   type_status?: string;
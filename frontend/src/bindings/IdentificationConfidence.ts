@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How sure an identifier is of their identification.
+ *
+ * The serde tokens are what the appview writes into the record's
+ * `confidence` field and what the `identifications.confidence` column holds.
+ */
+export type IdentificationConfidence = "tentative" | "likely" | "certain";
@@ -6,4 +6,12 @@ export type ImageUpload = {
    * Deserialized from frontend but unused — PDS infers MIME type from bytes.
    */
   mimeType: string;
+  /**
+   * Alt text describing the image, written to the media record's `alt`.
+   */
+  alt?: string;
+  /**
+   * Free-text caption shown alongside the image.
+   */
+  caption?: string;
 };
@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How a taxon takes part in an interaction, seen from its own side:
+ * `Actor` when it does the interacting (it pollinates the partner),
+ * `Target` when the partner does it to it, `Mutual` for bidirectional
+ * records.
+ */
+export type InteractionRole = "actor" | "target" | "mutual";
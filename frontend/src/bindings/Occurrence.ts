@@ -3,6 +3,7 @@ import type { EffectiveTaxonomy } from "./EffectiveTaxonomy";
 import type { Location } from "./Location";
 import type { OccurrenceImage } from "./OccurrenceImage";
 import type { Profile } from "./Profile";
+import type { QualityGrade } from "./QualityGrade";
 import type { QualityIssue } from "./QualityIssue";
 
 /**
@@ -22,6 +23,12 @@ export type Occurrence = {
    */
   eventDate?: string;
   location?: Location;
+  /**
+   * Meters from the query point; only set by the nearby feed, which
+   * orders by it, and by similar-occurrence queries, where the point is
+   * the reference occurrence.
+   */
+  distanceMeters?: number;
   /**
    * Darwin Core dwc:organismQuantity — free text (an int/float, or
    * categorical like "many"/"10-100").
@@ -41,4 +48,5 @@ export type Occurrence = {
    * on feed requests to filter to just those rows.
    */
   qualityIssues: Array<QualityIssue>;
+  qualityGrade: QualityGrade;
 };
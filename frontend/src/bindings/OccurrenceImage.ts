@@ -1,7 +1,12 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
 /**
- * A single image attached to an occurrence, with the SPDX license the
- * uploader chose (when one is recorded on the underlying media record).
+ * A single image attached to an occurrence, with the SPDX license, alt text
+ * and caption the uploader gave it (when recorded on the media record).
  */
-export type OccurrenceImage = { url: string; license?: string };
+export type OccurrenceImage = {
+  url: string;
+  license?: string;
+  alt?: string;
+  caption?: string;
+};
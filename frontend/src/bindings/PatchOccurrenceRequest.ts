@@ -0,0 +1,25 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Body of `PUT /api/occurrences/{uri}`: a partial edit of an occurrence's
+ * metadata. Only the fields present are changed; everything else on the
+ * record (location, media, `createdAt`) is written back as it was.
+ */
+export type PatchOccurrenceRequest = {
+  /**
+   * See `UpdateOccurrenceRequest::expected_cid`. Without it the patch is
+   * still applied atomically against the version it was read from.
+   */
+  expectedCid?: string;
+  eventDate?: string;
+  /**
+   * See `CreateOccurrenceRequest::organism_quantity`. An empty string
+   * removes the value.
+   */
+  organismQuantity?: string;
+  /**
+   * See `CreateOccurrenceRequest::organism_quantity_type`. An empty
+   * string removes the value.
+   */
+  organismQuantityType?: string;
+};
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * iNaturalist-style quality grade.
+ *
+ * Derived at read time from the occurrence and its row in `community_ids`,
+ * which the ingester refreshes on every identification change, so the grade
+ * follows new and withdrawn identifications without a stored column.
+ */
+export type QualityGrade = "casual" | "needs_id" | "research";
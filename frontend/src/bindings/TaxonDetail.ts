@@ -5,6 +5,7 @@ import type { TaxonAncestor } from "./TaxonAncestor";
 import type { TaxonDescription } from "./TaxonDescription";
 import type { TaxonMedia } from "./TaxonMedia";
 import type { TaxonReference } from "./TaxonReference";
+import type { TaxonSitelink } from "./TaxonSitelink";
 
 export type TaxonDetail = {
   id: string;
@@ -32,4 +33,5 @@ export type TaxonDetail = {
   media?: Array<TaxonMedia>;
   gbifUrl?: string;
   wikidataUrl?: string;
+  sitelinks?: Array<TaxonSitelink>;
 };
@@ -3,11 +3,13 @@ import type { ConservationStatus } from "./ConservationStatus";
 import type { TaxaResult } from "./TaxaResult";
 import type { TaxonAncestor } from "./TaxonAncestor";
 import type { TaxonDescription } from "./TaxonDescription";
+import type { TaxonLocalStats } from "./TaxonLocalStats";
 import type { TaxonMedia } from "./TaxonMedia";
 import type { TaxonReference } from "./TaxonReference";
 
 export type TaxonDetailWithCount = {
   observationCount: bigint;
+  localStats: TaxonLocalStats;
   id: string;
   scientificName: string;
   commonName?: string;
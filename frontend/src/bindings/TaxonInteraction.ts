@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { InteractionRole } from "./InteractionRole";
+
+/**
+ * Interactions of one type between a taxon and one partner taxon, counted
+ * across records and normalized to the taxon's side.
+ */
+export type TaxonInteraction = {
+  interactionType: string;
+  role: InteractionRole;
+  partnerTaxonName: string;
+  partnerKingdom?: string;
+  interactionCount: bigint;
+};
@@ -0,0 +1,17 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Local observing activity for one taxon, for the taxon page header
+ */
+export type TaxonLocalStats = {
+  occurrenceCount: bigint;
+  /**
+   * Earliest event date; `None` when no occurrence is dated.
+   */
+  firstObserved: string | null;
+  /**
+   * Latest event date; `None` when no occurrence is dated.
+   */
+  lastObserved: string | null;
+  observerCount: bigint;
+};
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A Wikipedia article about the taxon, from its Wikidata sitelinks.
+ */
+export type TaxonSitelink = { language: string; url: string };
@@ -3,6 +3,12 @@ import type { ImageUpload } from "./ImageUpload";
 
 export type UpdateOccurrenceRequest = {
   uri: string;
+  /**
+   * CID of the record version the edit was made against. When given, the
+   * update only applies if the record is still at that version and fails
+   * with 409 Conflict otherwise; when omitted the last write wins.
+   */
+  expectedCid?: string;
   latitude: number;
   longitude: number;
   coordinateUncertaintyInMeters?: number;
@@ -15,6 +15,7 @@ const PARTIAL_OBSERVATION: Occurrence = {
   location: { latitude: 51.51, longitude: -0.13, uncertaintyMeters: 4000 },
   likeCount: 8,
   qualityIssues: ["MISSING_MEDIA", "COORDINATES_IMPRECISE"],
+  qualityGrade: "casual",
 };
 
 // A barely-identified row: no taxonomy and no location, so most cells fall
@@ -29,6 +30,7 @@ const {
   cid: "bafyreioak2",
   identificationCount: 0,
   qualityIssues: ["MISSING_MEDIA", "NO_CONSENSUS_ID"],
+  qualityGrade: "casual",
 } satisfies Occurrence;
 void _dropTax;
 void _dropLoc;
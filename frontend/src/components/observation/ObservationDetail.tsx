@@ -243,7 +243,7 @@ export function ObservationDetail() {
               <Box
                 component="img"
                 src={getImageUrl(activeImage.url)}
-                alt={species || "Observation photo"}
+                alt={activeImage.alt || species || "Observation photo"}
                 sx={{
                   width: "100%",
                   maxHeight: 400,
@@ -253,6 +253,14 @@ export function ObservationDetail() {
                 }}
               />
             </ButtonBase>
+            {activeImage.caption && (
+              <Typography
+                variant="body2"
+                sx={{ textAlign: "center", px: 1, pt: { xs: 1, sm: 0.5 } }}
+              >
+                {activeImage.caption}
+              </Typography>
+            )}
             {activeImage.license && (
               <Typography
                 variant="caption"
@@ -285,7 +293,7 @@ export function ObservationDetail() {
                     <Box
                       component="img"
                       src={getImageUrl(img.url)}
-                      alt={`Photo ${idx + 1}`}
+                      alt={img.alt || `Photo ${idx + 1}`}
                       sx={{ width: "100%", height: "100%", objectFit: "cover" }}
                     />
                   </ButtonBase>
@@ -464,7 +472,7 @@ export function ObservationDetail() {
           open={lightboxOpen}
           onClose={() => setLightboxOpen(false)}
           src={getImageUrl(activeImage.url)}
-          alt={species || "Observation photo"}
+          alt={activeImage.alt || species || "Observation photo"}
           license={activeImage.license}
         />
       )}
@@ -143,6 +143,7 @@ export function makeTombstoneOccurrence(input: TombstoneInput): Occurrence {
     likeCount: 0,
     viewerHasLiked: false,
     qualityIssues: [],
+    qualityGrade: "needs_id",
   };
 }
 